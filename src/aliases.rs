@@ -0,0 +1,300 @@
+//! Tool aliases: foreign tool names and argument shapes mapped onto
+//! soul-coder tools.
+//!
+//! Models tuned on other harnesses keep calling the tools they were trained
+//! with — `str_replace_editor` with `old_str`/`new_str`, `list_files`,
+//! `search` with a `query`. A [`ToolAlias`] registers such a name next to
+//! the real tool, renames its arguments on the way in and advertises the
+//! renamed schema, so those calls work without rewriting the prompt.
+//! Aliases marked [deprecated](ToolAlias::with_deprecation) still work but
+//! tell the model which tool to call instead, for migrating old names away.
+//!
+//! [`compat_aliases`](crate::presets::compat_aliases) holds the common ones.
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use soul_core::vexec::NoopExecutor;
+//! use soul_core::vfs::MemoryFs;
+//! use soul_coder::aliases::{alias_registry, ToolAlias};
+//! use soul_coder::presets::all_tools;
+//!
+//! let tools = all_tools(Arc::new(MemoryFs::new()), Arc::new(NoopExecutor), "/workspace");
+//! let registry = alias_registry(
+//!     tools,
+//!     &[ToolAlias::new("search", "grep").with_argument("query", "pattern")],
+//! );
+//! let definition = registry.get("search").unwrap().definition();
+//! assert!(definition.input_schema["properties"].get("query").is_some());
+//! assert!(registry.get("grep").is_some());
+//! ```
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput, ToolRegistry};
+use soul_core::types::ToolDefinition;
+
+use crate::tools::{set_metadata, SharedTool};
+
+/// Another name for a registered tool, with the arguments it takes under
+/// that name.
+#[derive(Debug, Clone)]
+pub struct ToolAlias {
+    name: String,
+    target: String,
+    renames: Vec<(String, String)>,
+    ignored: Vec<String>,
+    defaults: Vec<(String, Value)>,
+    description: Option<String>,
+    deprecated: bool,
+}
+
+impl ToolAlias {
+    /// Register `target` under `name` too.
+    pub fn new(name: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            target: target.into(),
+            renames: Vec::new(),
+            ignored: Vec::new(),
+            defaults: Vec::new(),
+            description: None,
+            deprecated: false,
+        }
+    }
+
+    /// Accept the target's argument `to` under the name `from`.
+    pub fn with_argument(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.renames.push((from.into(), to.into()));
+        self
+    }
+
+    /// Drop argument `name` before calling the target, for options of the
+    /// foreign tool that have no equivalent.
+    pub fn with_ignored(mut self, name: impl Into<String>) -> Self {
+        self.ignored.push(name.into());
+        self
+    }
+
+    /// Pass `value` as argument `name` when the call doesn't set it.
+    pub fn with_default(mut self, name: impl Into<String>, value: Value) -> Self {
+        self.defaults.push((name.into(), value));
+        self
+    }
+
+    /// Replace the target's description (default: the target's, prefixed
+    /// with what the alias stands for).
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Keep the alias working, but point the model to the target in its
+    /// description and in every result.
+    pub fn with_deprecation(mut self) -> Self {
+        self.deprecated = true;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Translate a call's arguments into the target's.
+    fn arguments(&self, arguments: Value) -> Value {
+        // Providers sometimes send the arguments as a JSON string
+        let mut arguments = match arguments.as_str() {
+            Some(raw) => serde_json::from_str(raw).unwrap_or(arguments),
+            None => arguments,
+        };
+        let Some(map) = arguments.as_object_mut() else {
+            return arguments;
+        };
+        for name in &self.ignored {
+            map.remove(name);
+        }
+        for (from, to) in &self.renames {
+            if let Some(value) = map.remove(from) {
+                map.insert(to.clone(), value);
+            }
+        }
+        for (name, value) in &self.defaults {
+            map.entry(name.clone()).or_insert_with(|| value.clone());
+        }
+        arguments
+    }
+
+    /// The target's definition as seen under the alias.
+    fn definition(&self, target: ToolDefinition) -> ToolDefinition {
+        let mut schema = target.input_schema;
+        for (from, to) in &self.renames {
+            if let Some(properties) = schema.get_mut("properties").and_then(|p| p.as_object_mut()) {
+                if let Some(property) = properties.remove(to) {
+                    properties.insert(from.clone(), property);
+                }
+            }
+            if let Some(required) = schema.get_mut("required").and_then(|r| r.as_array_mut()) {
+                for name in required.iter_mut().filter(|n| n.as_str() == Some(to)) {
+                    *name = json!(from);
+                }
+            }
+        }
+        let description = match (&self.description, self.deprecated) {
+            (Some(description), _) => description.clone(),
+            (None, true) => format!(
+                "Deprecated: call {} instead. {}",
+                self.target, target.description
+            ),
+            (None, false) => format!("Same as {}. {}", self.target, target.description),
+        };
+        ToolDefinition {
+            name: self.name.clone(),
+            description,
+            input_schema: schema,
+        }
+    }
+}
+
+/// A registered tool answering to an alias.
+struct AliasedTool {
+    alias: ToolAlias,
+    target: SharedTool,
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for AliasedTool {
+    fn name(&self) -> &str {
+        &self.alias.name
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.alias.definition(self.target.definition())
+    }
+
+    async fn execute(
+        &self,
+        call_id: &str,
+        arguments: serde_json::Value,
+        partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let arguments = self.alias.arguments(arguments);
+        let mut output = self.target.execute(call_id, arguments, partial_tx).await?;
+        if self.alias.deprecated {
+            output.content.push_str(&format!(
+                "\n\n[{} is deprecated; call {} instead]",
+                self.alias.name, self.alias.target
+            ));
+        }
+        set_metadata(
+            &mut output,
+            "alias",
+            json!({
+                "name": self.alias.name,
+                "target": self.alias.target,
+                "deprecated": self.alias.deprecated,
+            }),
+        );
+        Ok(output)
+    }
+}
+
+/// Register `aliases` alongside the tools in `tools`. Aliases whose target
+/// isn't in the registry, or whose name is already taken by a tool, are
+/// skipped.
+pub fn alias_registry(tools: ToolRegistry, aliases: &[ToolAlias]) -> ToolRegistry {
+    let handles = SharedTool::split(tools);
+    let mut registry = ToolRegistry::new();
+    for tool in &handles {
+        registry.register(Box::new(tool.clone()));
+    }
+    for alias in aliases {
+        let target = handles.iter().find(|t| t.name() == alias.target);
+        if let (None, Some(target)) = (registry.get(&alias.name), target) {
+            registry.register(Box::new(AliasedTool {
+                alias: alias.clone(),
+                target: target.clone(),
+            }));
+        }
+    }
+    registry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use soul_core::vexec::NoopExecutor;
+    use soul_core::vfs::{MemoryFs, VirtualFs};
+
+    use crate::presets::{all_tools, compat_aliases};
+
+    async fn registry() -> (Arc<MemoryFs>, ToolRegistry) {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "hello world\n").await.unwrap();
+        let tools = all_tools(fs.clone(), Arc::new(NoopExecutor), "/project");
+        let mut aliases = compat_aliases();
+        aliases.push(ToolAlias::new("old_grep", "grep").with_deprecation());
+        aliases.push(ToolAlias::new("missing", "nope"));
+        aliases.push(ToolAlias::new("read", "ls"));
+        (fs, alias_registry(tools, &aliases))
+    }
+
+    #[tokio::test]
+    async fn str_replace_editor_edits() {
+        let (fs, registry) = registry().await;
+        let tool = registry.get("str_replace_editor").unwrap();
+        let schema = tool.definition().input_schema;
+        assert!(schema["properties"].get("old_str").is_some());
+        assert!(schema["properties"].get("old").is_none());
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({"command": "str_replace", "path": "a.txt", "old_str": "world", "new_str": "there"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.metadata["alias"]["target"], "edit");
+        assert_eq!(
+            fs.read_to_string("/project/a.txt").await.unwrap(),
+            "hello there\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn deprecated_and_skipped_aliases() {
+        let (_fs, registry) = registry().await;
+        let tool = registry.get("old_grep").unwrap();
+        assert!(tool
+            .definition()
+            .description
+            .starts_with("Deprecated: call grep instead."));
+        let result = tool
+            .execute("c1", json!({"pattern": "hello"}), None)
+            .await
+            .unwrap();
+        assert!(result
+            .content
+            .ends_with("[old_grep is deprecated; call grep instead]"));
+        assert_eq!(result.metadata["alias"]["deprecated"], true);
+
+        assert!(registry.get("missing").is_none());
+        assert_eq!(registry.get("read").unwrap().definition().name, "read");
+        let search = registry.get("search").unwrap();
+        let result = search
+            .execute("c2", json!({"query": "world"}), None)
+            .await
+            .unwrap();
+        assert!(result.content.contains("a.txt"), "{}", result.content);
+    }
+}
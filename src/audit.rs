@@ -0,0 +1,670 @@
+//! Audit log of mutating tool operations.
+//!
+//! [`AuditedTool`] wraps any tool and, for mutating tools (write, write_many,
+//! append, edit, bash, scaffold, delete, format, grep_replace, rename),
+//! records who did what to the workspace: timestamp, call id, target path
+//! or command, bytes changed and a hash of the resulting diff.
+//! A write_many call gets one record per file, and so do grep_replace,
+//! rename and scaffold calls (a moved file is recorded as removed from its
+//! old path and created at its new one). Those work on directories, so the
+//! files they changed are taken from their result: grep_replace reports each
+//! file's previous content (removed from the metadata once recorded), a moved
+//! file's content is read at its new path, and scaffold's destination is
+//! read beforehand when it may `overwrite`. Dry runs change nothing and
+//! aren't recorded.
+//! Records go to a pluggable [`AuditSink`] — [`VfsAuditSink`] appends JSON
+//! Lines to a file in the VFS, or hosts can supply their own.
+//!
+//! Whether a call is audited depends on the tool's built-in name, not the
+//! one it is registered under: [`Toolset::with_audit`](crate::Toolset::with_audit)
+//! knows it from the tool's type, so a bash registered as `shell` or called
+//! through an alias is still recorded. [`AuditedTool::new`] only sees the
+//! registered name; pass the built-in one with
+//! [`AuditedTool::with_base_name`] for renamed tools.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use futures::StreamExt;
+use sha2::{Digest, Sha256};
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput, ToolRegistry};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::tools::{resolve_path, set_metadata, unified_diff, DisplayRoot, SharedTool};
+use crate::walk::{walk, WalkOptions};
+
+/// Tools whose invocations are recorded.
+pub const MUTATING_TOOLS: &[&str] = &[
+    "write",
+    "write_many",
+    "append",
+    "edit",
+    "bash",
+    "scaffold",
+    "delete",
+    "format",
+    "grep_replace",
+    "rename",
+];
+
+/// A single audited tool invocation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Milliseconds since the Unix epoch (0 where no clock is available).
+    pub timestamp_ms: u64,
+    pub tool: String,
+    pub call_id: String,
+    /// Resolved file path, or the shell command for bash.
+    pub target: String,
+    /// Size delta of the target file in bytes (`None` for commands).
+    pub bytes_changed: Option<i64>,
+    /// SHA-256 of the unified diff applied to the target file.
+    pub diff_hash: Option<String>,
+    pub is_error: bool,
+}
+
+/// Destination for audit records.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait AuditSink: Send + Sync {
+    async fn record(&self, record: &AuditRecord) -> SoulResult<()>;
+}
+
+/// Appends records as JSON Lines to a file in the VFS.
+pub struct VfsAuditSink {
+    fs: Arc<dyn VirtualFs>,
+    path: String,
+}
+
+impl VfsAuditSink {
+    pub fn new(fs: Arc<dyn VirtualFs>, path: impl Into<String>) -> Self {
+        Self {
+            fs,
+            path: path.into(),
+        }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl AuditSink for VfsAuditSink {
+    async fn record(&self, record: &AuditRecord) -> SoulResult<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        self.fs.append(&self.path, &line).await
+    }
+}
+
+/// Keeps records in memory, for hosts that ship them elsewhere themselves.
+#[derive(Default)]
+pub struct MemoryAuditSink {
+    records: Mutex<Vec<AuditRecord>>,
+}
+
+impl MemoryAuditSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All records captured so far.
+    pub fn records(&self) -> Vec<AuditRecord> {
+        self.records.lock().unwrap().clone()
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl AuditSink for MemoryAuditSink {
+    async fn record(&self, record: &AuditRecord) -> SoulResult<()> {
+        self.records.lock().unwrap().push(record.clone());
+        Ok(())
+    }
+}
+
+/// Wraps a tool and records its mutating invocations to an [`AuditSink`].
+pub struct AuditedTool {
+    inner: Box<dyn Tool>,
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    sink: Arc<dyn AuditSink>,
+    base_name: Option<String>,
+}
+
+impl AuditedTool {
+    pub fn new(
+        inner: Box<dyn Tool>,
+        fs: Arc<dyn VirtualFs>,
+        cwd: impl Into<String>,
+        sink: Arc<dyn AuditSink>,
+    ) -> Self {
+        Self {
+            inner,
+            fs,
+            cwd: cwd.into(),
+            sink,
+            base_name: None,
+        }
+    }
+
+    /// Audit calls as those of the built-in tool `name` (e.g. `bash` for a
+    /// bash registered as `shell`), instead of going by the registered name.
+    pub fn with_base_name(mut self, name: impl Into<String>) -> Self {
+        self.base_name = Some(name.into());
+        self
+    }
+
+    /// Resolved target paths for file-mutating calls: the `path` argument,
+    /// or the path of every entry in `files` (write_many).
+    fn target_paths(&self, arguments: &serde_json::Value) -> Vec<String> {
+        let path = |v: &serde_json::Value| {
+            v.get("path")
+                .and_then(|v| v.as_str())
+                .filter(|p| !p.is_empty())
+                .map(|p| resolve_path(&self.cwd, p))
+        };
+        match arguments.get("files").and_then(|v| v.as_array()) {
+            Some(files) => files.iter().filter_map(path).collect(),
+            None => path(arguments).into_iter().collect(),
+        }
+    }
+
+    /// Files a scaffold call may replace: with `overwrite`, every file already
+    /// under its destination. Without it, scaffold refuses to touch
+    /// existing files.
+    async fn scaffold_overwrites(&self, arguments: &serde_json::Value) -> Vec<String> {
+        let (Some(true), Some(path)) = (arguments["overwrite"].as_bool(), arguments["path"].as_str())
+        else {
+            return Vec::new();
+        };
+        let dest = resolve_path(&self.cwd, path);
+        walk(self.fs.clone(), &dest, WalkOptions::default().with_hidden(true))
+            .map(|file| file.to_string_lossy().into_owned())
+            .collect()
+            .await
+    }
+
+    /// The files a tool that works on directories reports changing in its
+    /// result metadata, with their resolved path and content before and
+    /// after the call. Contents scaffold replaced are taken from `before`.
+    async fn reported_changes(
+        &self,
+        base_name: &str,
+        arguments: &serde_json::Value,
+        output: &mut ToolOutput,
+        before: &mut HashMap<String, String>,
+    ) -> Vec<(String, String, String)> {
+        let mut changes = Vec::new();
+        match base_name {
+            "grep_replace" => {
+                let display = DisplayRoot::from_arguments(&self.cwd, arguments);
+                let files = output.metadata.get_mut("files").and_then(|f| f.as_array_mut());
+                for file in files.into_iter().flatten() {
+                    // Pre-images are for the record, not for whoever gets the result
+                    let previous = file.as_object_mut().and_then(|f| f.remove("before"));
+                    let (Some(shown), Some(previous)) =
+                        (file["path"].as_str(), previous.as_ref().and_then(|v| v.as_str()))
+                    else {
+                        continue;
+                    };
+                    let path = display.resolve(shown);
+                    let after = self.fs.read_to_string(&path).await.unwrap_or_default();
+                    changes.push((path, previous.to_string(), after));
+                }
+            }
+            // A move removes one file and creates another with its content
+            "rename" => {
+                for pair in output.metadata["renamed"].as_array().into_iter().flatten() {
+                    let (Some(from), Some(to)) = (pair["from"].as_str(), pair["to"].as_str())
+                    else {
+                        continue;
+                    };
+                    let to = resolve_path(&self.cwd, to);
+                    let content = self.fs.read_to_string(&to).await.unwrap_or_default();
+                    changes.push((resolve_path(&self.cwd, from), content.clone(), String::new()));
+                    changes.push((to, String::new(), content));
+                }
+            }
+            "scaffold" => {
+                for shown in output.metadata["files"].as_array().into_iter().flatten() {
+                    let Some(shown) = shown.as_str() else {
+                        continue;
+                    };
+                    let path = resolve_path(&self.cwd, shown);
+                    let after = self.fs.read_to_string(&path).await.unwrap_or_default();
+                    changes.push((path.clone(), before.remove(&path).unwrap_or_default(), after));
+                }
+            }
+            _ => {}
+        }
+        changes
+    }
+}
+
+/// Tools whose targets are directories or globs: the files they changed are
+/// taken from their result metadata rather than from a `path` argument.
+const REPORTING_TOOLS: &[&str] = &["grep_replace", "rename", "scaffold"];
+
+/// Tools that only change files when called with `dry_run: false`.
+const DRY_RUN_TOOLS: &[&str] = &["grep_replace", "rename"];
+
+/// Arguments as an object, also when the provider sent them as a JSON
+/// string (write and append accept those).
+fn parse_arguments(arguments: &serde_json::Value) -> serde_json::Value {
+    match arguments.as_str() {
+        Some(s) => serde_json::from_str(s).unwrap_or(serde_json::Value::Null),
+        None => arguments.clone(),
+    }
+}
+
+/// Size delta and diff hash of one file's change.
+fn file_change(path: &str, before: &str, after: &str) -> (Option<i64>, Option<String>) {
+    let diff_hash = (before != after).then(|| {
+        let diff = unified_diff(before, after, path);
+        format!("{:x}", Sha256::digest(diff.as_bytes()))
+    });
+    (Some(after.len() as i64 - before.len() as i64), diff_hash)
+}
+
+/// Current time in milliseconds since the Unix epoch.
+pub(crate) fn now_ms() -> u64 {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        0
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for AuditedTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.inner.definition()
+    }
+
+    async fn execute(
+        &self,
+        call_id: &str,
+        arguments: serde_json::Value,
+        partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let name = self.inner.name().to_string();
+        let base_name = self.base_name.as_deref().unwrap_or(&name);
+        if !MUTATING_TOOLS.contains(&base_name) {
+            return self.inner.execute(call_id, arguments, partial_tx).await;
+        }
+
+        let parsed = parse_arguments(&arguments);
+        let dry_run = parsed["dry_run"].as_bool().unwrap_or(true);
+        if DRY_RUN_TOOLS.contains(&base_name) && dry_run {
+            // Changes nothing
+            return self.inner.execute(call_id, arguments, partial_tx).await;
+        }
+        let reporting = REPORTING_TOOLS.contains(&base_name);
+        let paths = match base_name {
+            "scaffold" => self.scaffold_overwrites(&parsed).await,
+            _ if reporting => Vec::new(),
+            _ => self.target_paths(&parsed),
+        };
+        let mut before = HashMap::new();
+        for p in &paths {
+            if let Ok(content) = self.fs.read_to_string(p).await {
+                before.insert(p.clone(), content);
+            }
+        }
+
+        let mut output = self.inner.execute(call_id, arguments, partial_tx).await?;
+
+        let contents = match reporting {
+            true => {
+                self.reported_changes(base_name, &parsed, &mut output, &mut before)
+                    .await
+            }
+            false => {
+                let mut contents = Vec::with_capacity(paths.len());
+                for p in paths {
+                    let after = self.fs.read_to_string(&p).await.unwrap_or_default();
+                    contents.push((p.clone(), before.remove(&p).unwrap_or_default(), after));
+                }
+                contents
+            }
+        };
+        let mut changes = Vec::with_capacity(contents.len());
+        for (p, before, after) in contents {
+            let (bytes_changed, diff_hash) = file_change(&p, &before, &after);
+            changes.push((p, bytes_changed, diff_hash));
+        }
+        if changes.is_empty() {
+            // A command, or a call that changed no file: record what it
+            // targeted. bash reports the command line it ran, also for argv
+            // and rerun calls.
+            let command = output.metadata["command"].as_str();
+            let target = match command.or(parsed["command"].as_str()) {
+                Some(command) => command.to_string(),
+                None if reporting => resolve_path(&self.cwd, parsed["path"].as_str().unwrap_or(".")),
+                None => String::new(),
+            };
+            changes.push((target, None, None));
+        }
+
+        let timestamp_ms = now_ms();
+        for (target, bytes_changed, diff_hash) in changes {
+            let record = AuditRecord {
+                timestamp_ms,
+                tool: name.clone(),
+                call_id: call_id.to_string(),
+                target,
+                bytes_changed,
+                diff_hash,
+                is_error: output.is_error,
+            };
+
+            // A failing sink must not hide the tool result, but should be visible
+            if let Err(e) = self.sink.record(&record).await {
+                set_metadata(&mut output, "audit_error", json!(e.to_string()));
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// Wrap every tool in `tools` with an [`AuditedTool`] recording to `sink`.
+pub fn audit_registry(
+    tools: ToolRegistry,
+    fs: Arc<dyn VirtualFs>,
+    cwd: impl Into<String>,
+    sink: Arc<dyn AuditSink>,
+) -> ToolRegistry {
+    let cwd = cwd.into();
+    let mut registry = ToolRegistry::new();
+    for tool in SharedTool::split(tools) {
+        registry.register(Box::new(AuditedTool::new(
+            Box::new(tool),
+            fs.clone(),
+            &cwd,
+            sink.clone(),
+        )));
+    }
+    registry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{
+        bash::BashTool, grep_replace::GrepReplaceTool, read::ReadTool, rename::RenameTool,
+        scaffold::ScaffoldTool, write::WriteTool, write_many::WriteManyTool,
+    };
+    use soul_core::vexec::{MockExecutor, VirtualExecutor};
+    use soul_core::vfs::MemoryFs;
+
+    use crate::aliases::ToolAlias;
+    use crate::toolset::Toolset;
+
+    fn audited(tool: Box<dyn Tool>, fs: Arc<MemoryFs>) -> (AuditedTool, Arc<MemoryAuditSink>) {
+        let sink = Arc::new(MemoryAuditSink::new());
+        let audited = AuditedTool::new(
+            tool,
+            fs as Arc<dyn VirtualFs>,
+            "/project",
+            sink.clone() as Arc<dyn AuditSink>,
+        );
+        (audited, sink)
+    }
+
+    #[tokio::test]
+    async fn records_write() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "old").await.unwrap();
+        let write = WriteTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        let (tool, sink) = audited(Box::new(write), fs);
+
+        tool.execute("c1", json!({"path": "a.txt", "content": "newer"}), None)
+            .await
+            .unwrap();
+
+        let records = sink.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].tool, "write");
+        assert_eq!(records[0].call_id, "c1");
+        assert_eq!(records[0].target, "/project/a.txt");
+        assert_eq!(records[0].bytes_changed, Some(2));
+        assert_eq!(records[0].diff_hash.as_ref().unwrap().len(), 64);
+        assert!(!records[0].is_error);
+    }
+
+    #[tokio::test]
+    async fn records_bash_command() {
+        let fs = Arc::new(MemoryFs::new());
+        let exec = Arc::new(MockExecutor::always_ok("ok"));
+        let bash = BashTool::new(exec as Arc<dyn VirtualExecutor>, "/project");
+        let (tool, sink) = audited(Box::new(bash), fs);
+
+        tool.execute("c2", json!({"command": "cargo fmt"}), None)
+            .await
+            .unwrap();
+
+        tool.execute("c3", json!({"argv": ["rm", "a b"]}), None)
+            .await
+            .unwrap();
+        tool.execute("c4", json!({"rerun": 1}), None).await.unwrap();
+
+        let records = sink.records();
+        let targets: Vec<&str> = records.iter().map(|r| r.target.as_str()).collect();
+        assert_eq!(targets, ["cargo fmt", "'rm' 'a b'", "cargo fmt"]);
+        assert_eq!(records[0].bytes_changed, None);
+    }
+
+    #[tokio::test]
+    async fn records_each_file_of_write_many() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "a").await.unwrap();
+        let write_many = WriteManyTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        let (tool, sink) = audited(Box::new(write_many), fs);
+
+        let files = json!({"files": [
+            {"path": "a.txt", "content": "a"},
+            {"path": "b.txt", "content": "bb"},
+        ]});
+        tool.execute("c1", files, None).await.unwrap();
+
+        let records = sink.records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].target, "/project/a.txt");
+        assert_eq!(records[0].diff_hash, None);
+        assert_eq!(records[1].target, "/project/b.txt");
+        assert_eq!(records[1].bytes_changed, Some(2));
+    }
+
+    #[tokio::test]
+    async fn records_each_file_of_grep_replace() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/src/a.rs", "old_name();").await.unwrap();
+        fs.write("/project/src/b.rs", "old_name(); old_name();").await.unwrap();
+        fs.write("/project/src/c.rs", "untouched").await.unwrap();
+        let grep_replace = GrepReplaceTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        let (tool, sink) = audited(Box::new(grep_replace), fs.clone());
+
+        let args = json!({"pattern": "old_name", "replacement": "name", "path": "src"});
+        tool.execute("c1", args.clone(), None).await.unwrap();
+        assert!(sink.records().is_empty(), "dry runs change nothing");
+
+        let mut args = args;
+        args["dry_run"] = json!(false);
+        args["relative_to"] = json!("src");
+        let result = tool.execute("c2", args, None).await.unwrap();
+        assert!(result.metadata["files"][0].get("before").is_none());
+
+        let records = sink.records();
+        let targets: Vec<&str> = records.iter().map(|r| r.target.as_str()).collect();
+        assert_eq!(targets, ["/project/src/a.rs", "/project/src/b.rs"]);
+        assert_eq!(records[0].bytes_changed, Some(-4));
+        assert_eq!(records[1].bytes_changed, Some(-8));
+        assert!(records.iter().all(|r| r.diff_hash.is_some() && r.tool == "grep_replace"));
+    }
+
+    #[tokio::test]
+    async fn records_both_sides_of_each_rename() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/src/a.test.js", "aaa").await.unwrap();
+        fs.write("/project/src/b.test.js", "bbbbb").await.unwrap();
+        let rename = RenameTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        let (tool, sink) = audited(Box::new(rename), fs.clone());
+
+        let args = json!({"from": "*.test.js", "to": "*.spec.js", "dry_run": false});
+        tool.execute("c1", args, None).await.unwrap();
+
+        let records = sink.records();
+        let changes: Vec<(&str, Option<i64>)> = records
+            .iter()
+            .map(|r| (r.target.as_str(), r.bytes_changed))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("/project/src/a.test.js", Some(-3)),
+                ("/project/src/a.spec.js", Some(3)),
+                ("/project/src/b.test.js", Some(-5)),
+                ("/project/src/b.spec.js", Some(5)),
+            ]
+        );
+        assert!(records.iter().all(|r| r.diff_hash.is_some()));
+
+        // Nothing matched: one record of what the call targeted
+        let args = json!({"from": "*.md", "to": "*.txt", "path": "src", "dry_run": false});
+        tool.execute("c2", args, None).await.unwrap();
+        let record = sink.records().pop().unwrap();
+        assert_eq!(record.target, "/project/src");
+        assert_eq!(record.bytes_changed, None);
+    }
+
+    #[tokio::test]
+    async fn records_each_file_of_scaffold() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/tpl/{{name}}.rs", "struct {{name}};").await.unwrap();
+        fs.write("/project/tpl/mod.rs", "mod x;").await.unwrap();
+        fs.write("/project/out/mod.rs", "old").await.unwrap();
+        let scaffold = ScaffoldTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        let (tool, sink) = audited(Box::new(scaffold), fs.clone());
+
+        let args = json!({
+            "template": "tpl",
+            "path": "out",
+            "variables": {"name": "Button"},
+            "overwrite": true
+        });
+        let result = tool.execute("c1", args, None).await.unwrap();
+        assert!(!result.is_error, "{}", result.content);
+
+        let mut records = sink.records();
+        records.sort_by(|a, b| a.target.cmp(&b.target));
+        let changes: Vec<(&str, Option<i64>)> = records
+            .iter()
+            .map(|r| (r.target.as_str(), r.bytes_changed))
+            .collect();
+        assert_eq!(
+            changes,
+            [("/project/out/Button.rs", Some(14)), ("/project/out/mod.rs", Some(3))]
+        );
+        assert!(records.iter().all(|r| r.diff_hash.is_some()));
+    }
+
+    #[tokio::test]
+    async fn skips_read_only_tools() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "data").await.unwrap();
+        let read = ReadTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        let (tool, sink) = audited(Box::new(read), fs);
+
+        let result = tool
+            .execute("c3", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        assert!(result.content.contains("data"));
+        assert!(sink.records().is_empty());
+    }
+
+    #[tokio::test]
+    async fn toolset_audits_renamed_tools_and_aliases() {
+        let fs = Arc::new(MemoryFs::new());
+        let sink = Arc::new(MemoryAuditSink::new());
+        let registry = Toolset::new(
+            fs.clone() as Arc<dyn VirtualFs>,
+            Arc::new(MockExecutor::always_ok("ok")) as Arc<dyn VirtualExecutor>,
+            "/project",
+        )
+        .with_audit(sink.clone() as Arc<dyn AuditSink>)
+        .with_read()
+        .with_write_configured(|write| write.with_name("save"))
+        .with_bash_configured(|bash| bash.with_name("shell"))
+        .with_alias(ToolAlias::new("create_file", "save").with_argument("file_text", "content"))
+        .build_registry();
+
+        registry
+            .get("shell")
+            .unwrap()
+            .execute("c1", json!({"command": "make"}), None)
+            .await
+            .unwrap();
+        registry
+            .get("create_file")
+            .unwrap()
+            .execute("c2", json!({"path": "a.txt", "file_text": "hi"}), None)
+            .await
+            .unwrap();
+        registry
+            .get("read")
+            .unwrap()
+            .execute("c3", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+
+        let records = sink.records();
+        assert_eq!(records.len(), 2, "{:?}", records);
+        assert_eq!((records[0].tool.as_str(), records[0].target.as_str()), ("shell", "make"));
+        assert_eq!(records[1].tool, "save");
+        assert_eq!(records[1].call_id, "c2");
+        assert_eq!(records[1].target, "/project/a.txt");
+        assert_eq!(records[1].bytes_changed, Some(2));
+    }
+
+    #[tokio::test]
+    async fn vfs_sink_appends_jsonl() {
+        let fs = Arc::new(MemoryFs::new());
+        let sink = VfsAuditSink::new(fs.clone() as Arc<dyn VirtualFs>, "/audit.jsonl");
+        let record = AuditRecord {
+            timestamp_ms: 1,
+            tool: "write".into(),
+            call_id: "c1".into(),
+            target: "/a".into(),
+            bytes_changed: Some(3),
+            diff_hash: None,
+            is_error: false,
+        };
+        sink.record(&record).await.unwrap();
+        sink.record(&record).await.unwrap();
+
+        let log = fs.read_to_string("/audit.jsonl").await.unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let parsed: AuditRecord = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(parsed, record);
+    }
+}
@@ -0,0 +1,292 @@
+//! Result caching for repeated exploratory calls.
+//!
+//! Agents often re-read the same unchanged file or re-run the same grep.
+//! [`ResultCache`] is a [`ToolMiddleware`] that remembers successful outputs
+//! of read-only tools keyed on `(tool, arguments, generation)` and replays
+//! them with `cached: true` in the metadata.
+//!
+//! The VFS has no modification times, so freshness is tracked with a
+//! generation counter instead: any call to a tool outside the cacheable set
+//! (write, edit, bash, ...) bumps it and drops every entry; the
+//! [`WORKSPACE_NEUTRAL_TOOLS`] are exempt. Hosts that change
+//! the workspace behind the tools' back call [`ResultCache::invalidate`].
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::ToolOutput;
+
+use crate::middleware::{Next, ToolMiddleware};
+use crate::tools::set_metadata;
+
+/// Tools whose results are cached by default.
+pub const CACHEABLE_TOOLS: &[&str] = &["read", "grep", "find", "ls", "outline"];
+
+/// Tools that never touch the workspace, so calling them keeps the cache.
+/// `set_cwd` is not one: below a [`WorkingDir`](crate::WorkingDir) the cache
+/// sees relative paths, which mean something else once it moves.
+pub const WORKSPACE_NEUTRAL_TOOLS: &[&str] = &["scratch", "open_file"];
+
+/// Default maximum number of cached results.
+pub const DEFAULT_MAX_ENTRIES: usize = 256;
+
+#[derive(Default)]
+struct CacheState {
+    generation: u64,
+    entries: HashMap<String, ToolOutput>,
+    /// Insertion order, for evicting the oldest entry when full.
+    order: VecDeque<String>,
+    hits: u64,
+    misses: u64,
+}
+
+/// Hit/miss counters for a [`ResultCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub generation: u64,
+}
+
+/// Middleware caching results of read-only tools until the workspace changes.
+pub struct ResultCache {
+    tools: Vec<String>,
+    max_entries: usize,
+    state: Mutex<CacheState>,
+}
+
+impl Default for ResultCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResultCache {
+    pub fn new() -> Self {
+        Self {
+            tools: CACHEABLE_TOOLS.iter().map(|t| t.to_string()).collect(),
+            max_entries: DEFAULT_MAX_ENTRIES,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Cache only these tools (by registered name).
+    pub fn with_tools<I, S>(mut self, tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tools = tools.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Keep at most `max` results, evicting the oldest first.
+    pub fn with_max_entries(mut self, max: usize) -> Self {
+        self.max_entries = max.max(1);
+        self
+    }
+
+    /// Drop all cached results, e.g. after the host modified the workspace.
+    pub fn invalidate(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        state.entries.clear();
+        state.order.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let state = self.state.lock().unwrap();
+        CacheStats {
+            hits: state.hits,
+            misses: state.misses,
+            entries: state.entries.len(),
+            generation: state.generation,
+        }
+    }
+
+    fn is_cacheable(&self, tool: &str) -> bool {
+        self.tools.iter().any(|t| t == tool)
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ToolMiddleware for ResultCache {
+    async fn handle(
+        &self,
+        tool: &str,
+        call_id: &str,
+        arguments: serde_json::Value,
+        next: Next<'_>,
+    ) -> SoulResult<ToolOutput> {
+        if WORKSPACE_NEUTRAL_TOOLS.contains(&tool) {
+            return next.run(call_id, arguments).await;
+        }
+        if !self.is_cacheable(tool) {
+            // Anything else may have changed the workspace
+            let output = next.run(call_id, arguments).await;
+            self.invalidate();
+            return output;
+        }
+
+        let (key, generation) = {
+            let mut state = self.state.lock().unwrap();
+            let key = format!("{}\0{}", tool, arguments);
+            if let Some(hit) = state.entries.get(&key) {
+                let mut output = hit.clone();
+                state.hits += 1;
+                set_metadata(&mut output, "cached", json!(true));
+                return Ok(output);
+            }
+            state.misses += 1;
+            (key, state.generation)
+        };
+
+        let output = next.run(call_id, arguments).await?;
+        if !output.is_error {
+            let mut state = self.state.lock().unwrap();
+            // Skip storing if the workspace changed while we were running
+            if state.generation == generation {
+                while state.entries.len() >= self.max_entries {
+                    match state.order.pop_front() {
+                        Some(oldest) => {
+                            state.entries.remove(&oldest);
+                        }
+                        None => break,
+                    }
+                }
+                state.order.push_back(key.clone());
+                state.entries.insert(key, output.clone());
+            }
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use soul_core::tool::ToolRegistry;
+    use soul_core::vexec::{MockExecutor, VirtualExecutor};
+    use soul_core::vfs::{MemoryFs, VirtualFs};
+
+    use crate::middleware::wrap_with_middleware;
+    use crate::presets::all_tools;
+
+    fn setup(fs: Arc<MemoryFs>, cache: Arc<ResultCache>) -> ToolRegistry {
+        let exec = Arc::new(MockExecutor::always_ok("ok"));
+        let tools = all_tools(
+            fs as Arc<dyn VirtualFs>,
+            exec as Arc<dyn VirtualExecutor>,
+            "/project",
+        );
+        wrap_with_middleware(tools, vec![cache])
+    }
+
+    #[tokio::test]
+    async fn repeated_read_is_cached() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "alpha").await.unwrap();
+        let cache = Arc::new(ResultCache::new());
+        let tools = setup(fs.clone(), cache.clone());
+        let read = tools.get("read").unwrap();
+
+        let first = read
+            .execute("c1", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        assert!(first.metadata.get("cached").is_none());
+
+        // Changed behind the cache's back: still served from cache
+        fs.write("/project/a.txt", "changed").await.unwrap();
+        let second = read
+            .execute("c2", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        assert_eq!(second.metadata["cached"], true);
+        assert_eq!(second.content, first.content);
+
+        cache.invalidate();
+        let third = read
+            .execute("c3", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        assert!(third.content.contains("changed"));
+        assert_eq!(cache.stats().hits, 1);
+        assert_eq!(cache.stats().misses, 2);
+    }
+
+    #[tokio::test]
+    async fn mutating_call_invalidates() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "alpha").await.unwrap();
+        let cache = Arc::new(ResultCache::new());
+        let tools = setup(fs, cache.clone());
+
+        let grep = tools.get("grep").unwrap();
+        grep.execute("c1", json!({"pattern": "beta"}), None)
+            .await
+            .unwrap();
+        // Scratch notes live outside the workspace
+        tools
+            .get("scratch")
+            .unwrap()
+            .execute(
+                "c2",
+                json!({"action": "write", "name": "n.txt", "content": "beta"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(cache.stats().generation, 0);
+        tools
+            .get("write")
+            .unwrap()
+            .execute("c2", json!({"path": "b.txt", "content": "beta"}), None)
+            .await
+            .unwrap();
+
+        let result = grep
+            .execute("c3", json!({"pattern": "beta"}), None)
+            .await
+            .unwrap();
+        assert!(result.metadata.get("cached").is_none());
+        assert!(result.content.contains("b.txt"));
+        assert_eq!(cache.stats().generation, 1);
+    }
+
+    #[tokio::test]
+    async fn errors_not_cached_and_eviction() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "a").await.unwrap();
+        fs.write("/project/b.txt", "b").await.unwrap();
+        let cache = Arc::new(ResultCache::new().with_max_entries(1));
+        let tools = setup(fs, cache.clone());
+        let read = tools.get("read").unwrap();
+
+        let missing = json!({"path": "missing.txt"});
+        read.execute("c1", missing.clone(), None).await.unwrap();
+        read.execute("c2", missing, None).await.unwrap();
+        assert_eq!(cache.stats().hits, 0);
+
+        read.execute("c3", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        read.execute("c4", json!({"path": "b.txt"}), None)
+            .await
+            .unwrap();
+        assert_eq!(cache.stats().entries, 1);
+        let result = read
+            .execute("c5", json!({"path": "b.txt"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["cached"], true);
+    }
+}
@@ -0,0 +1,157 @@
+//! Formatting conventions of a text file.
+//!
+//! An edit that uses LF in a CRLF file, or four spaces in a tab-indented
+//! one, leaves the file inconsistent and often fails a later exact match.
+//! [`FileConventions::detect`] reports what a file already uses — line
+//! endings, a byte order mark, whether it ends with a newline and how it is
+//! indented — so the read tool can hand that to the model up front.
+//!
+//! ```rust
+//! use soul_coder::conventions::{FileConventions, Indent, LineEndings};
+//!
+//! let conventions = FileConventions::detect("fn main() {\r\n\tprintln!();\r\n}\r\n");
+//! assert_eq!(conventions.line_endings, LineEndings::Crlf);
+//! assert!(conventions.trailing_newline);
+//! assert_eq!(conventions.indent, Indent::Tabs);
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// Line terminators used in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEndings {
+    Lf,
+    Crlf,
+    /// Both LF and CRLF lines.
+    Mixed,
+    /// A single line without a terminator, or an empty file.
+    None,
+}
+
+/// How lines are indented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "style", rename_all = "lowercase")]
+pub enum Indent {
+    Tabs,
+    /// Spaces, with the width of one level when it can be guessed.
+    Spaces {
+        width: Option<usize>,
+    },
+    /// Tabs and spaces, each on a sizeable share of the indented lines.
+    Mixed,
+    /// No indented lines.
+    None,
+}
+
+/// What [`FileConventions::detect`] found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FileConventions {
+    pub line_endings: LineEndings,
+    /// Starts with a UTF-8 byte order mark.
+    pub bom: bool,
+    /// The last line ends with a line terminator.
+    pub trailing_newline: bool,
+    pub indent: Indent,
+}
+
+impl FileConventions {
+    pub fn detect(content: &str) -> Self {
+        let crlf = content.matches("\r\n").count();
+        let lf = content.matches('\n').count() - crlf;
+        let line_endings = match (lf, crlf) {
+            (0, 0) => LineEndings::None,
+            (_, 0) => LineEndings::Lf,
+            (0, _) => LineEndings::Crlf,
+            _ => LineEndings::Mixed,
+        };
+        Self {
+            line_endings,
+            bom: content.starts_with('\u{FEFF}'),
+            trailing_newline: content.ends_with('\n'),
+            indent: detect_indent(content),
+        }
+    }
+}
+
+/// Guess the indentation from the leading whitespace of each line.
+fn detect_indent(content: &str) -> Indent {
+    let mut tab_lines = 0;
+    let mut space_lines = 0;
+    // How often each increase in space indentation occurs
+    let mut steps: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut previous = 0;
+    for line in content.lines() {
+        let text = line.trim_start_matches([' ', '\t']);
+        if text.is_empty() {
+            continue; // Blank lines say nothing
+        }
+        let leading = &line[..line.len() - text.len()];
+        if leading.starts_with('\t') {
+            tab_lines += 1;
+            continue;
+        }
+        let spaces = leading.len() - leading.trim_start_matches(' ').len();
+        // A single space before `*` continues a block comment
+        if spaces == 1 && text.starts_with('*') {
+            continue;
+        }
+        if spaces > 0 {
+            space_lines += 1;
+        }
+        if spaces > previous {
+            *steps.entry(spaces - previous).or_default() += 1;
+        }
+        previous = spaces;
+    }
+
+    let (majority, minority) = (tab_lines.max(space_lines), tab_lines.min(space_lines));
+    if majority == 0 {
+        return Indent::None;
+    }
+    if minority * 5 >= majority {
+        return Indent::Mixed;
+    }
+    if tab_lines > space_lines {
+        return Indent::Tabs;
+    }
+    // The most common step, the smaller one on a tie
+    let width = steps
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(step, _)| *step);
+    Indent::Spaces { width }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_endings_bom_and_indentation() {
+        let rust = "\u{FEFF}fn main() {\n    if x {\n        y();\n    }\n}";
+        assert_eq!(
+            FileConventions::detect(rust),
+            FileConventions {
+                line_endings: LineEndings::Lf,
+                bom: true,
+                trailing_newline: false,
+                indent: Indent::Spaces { width: Some(4) },
+            }
+        );
+
+        let yaml = "a:\r\n  b:\r\n    c: 1\r\n  d: 2\n";
+        let conventions = FileConventions::detect(yaml);
+        assert_eq!(conventions.line_endings, LineEndings::Mixed);
+        assert_eq!(conventions.indent, Indent::Spaces { width: Some(2) });
+
+        let c = "/*\n * doc\n */\nint f() {\n\treturn 1;\n}\n";
+        assert_eq!(FileConventions::detect(c).indent, Indent::Tabs);
+        let mixed = "a\n\tb\n  c\n";
+        assert_eq!(FileConventions::detect(mixed).indent, Indent::Mixed);
+        assert_eq!(FileConventions::detect("").line_endings, LineEndings::None);
+        assert_eq!(FileConventions::detect("x\ny").indent, Indent::None);
+    }
+}
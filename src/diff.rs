@@ -0,0 +1,243 @@
+//! Unified diffs for write and edit output, capped to a byte budget.
+//!
+//! Rewriting a large file produces a diff that dwarfs everything else in the
+//! tool output. [`render_diff`] keeps whole hunks while they fit the budget
+//! and replaces the rest with a one-line summary such as
+//! `… 14 more hunks, +212/−48 lines …`. [`DiffStats`] always describes the
+//! full diff, so metadata stays exact however much of it was shown.
+//!
+//! ```rust
+//! use soul_coder::diff::render_diff;
+//!
+//! let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+//! let new = "A\nb\nc\nd\ne\nf\ng\nh\ni\nJ\n";
+//! let full = render_diff(old, new, "notes.txt", usize::MAX);
+//! assert_eq!(full.stats.hunks, 2);
+//! assert!(!full.is_truncated());
+//!
+//! let capped = render_diff(old, new, "notes.txt", 70);
+//! assert_eq!(capped.shown_hunks, 1);
+//! assert!(capped.text.ends_with("… 1 more hunk, +1/−1 lines …\n"));
+//! ```
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use similar::{ChangeTag, TextDiff};
+
+/// Unchanged lines shown around each change.
+pub const CONTEXT_LINES: usize = 3;
+
+/// Size of a diff, or of the part of one that was left out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DiffStats {
+    pub hunks: usize,
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// A diff rendered within a byte budget, from [`render_diff`].
+#[derive(Debug, Clone)]
+pub struct RenderedDiff {
+    pub text: String,
+    /// The whole diff.
+    pub stats: DiffStats,
+    /// Hunks included in `text`, the last one possibly cut short.
+    pub shown_hunks: usize,
+    /// What was left out of `text`: hunks not shown at all, and every
+    /// added or removed line missing from it.
+    pub elided: DiffStats,
+    truncated: bool,
+}
+
+impl RenderedDiff {
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Stats for a tool's `diff` metadata key.
+    pub fn to_metadata(&self) -> Value {
+        json!({
+            "hunks": self.stats.hunks,
+            "added": self.stats.added,
+            "removed": self.stats.removed,
+            "shown_hunks": self.shown_hunks,
+            "truncated": self.is_truncated(),
+        })
+    }
+}
+
+/// Render the unified diff of `old` to `new`, keeping it under `max_bytes`
+/// where possible. Hunks that don't fit are summarized; if not even the
+/// first does, it is cut at the last line that fits.
+pub fn render_diff(old: &str, new: &str, path: &str, max_bytes: usize) -> RenderedDiff {
+    let diff = TextDiff::from_lines(old, new);
+    let mut udiff = diff.unified_diff();
+    udiff.context_radius(CONTEXT_LINES);
+
+    let mut text = format!("--- a/{}\n+++ b/{}\n", path, path);
+    let mut stats = DiffStats::default();
+    let mut elided = DiffStats::default();
+    let mut shown_hunks = 0;
+    let mut cut = false;
+
+    for hunk in udiff.iter_hunks() {
+        let mut lines = Vec::new();
+        let mut hunk_stats = DiffStats {
+            hunks: 1,
+            ..DiffStats::default()
+        };
+        for change in hunk.iter_changes() {
+            let sign = match change.tag() {
+                ChangeTag::Delete => {
+                    hunk_stats.removed += 1;
+                    "-"
+                }
+                ChangeTag::Insert => {
+                    hunk_stats.added += 1;
+                    "+"
+                }
+                ChangeTag::Equal => " ",
+            };
+            let mut line = format!("{}{}", sign, change.value());
+            if change.missing_newline() {
+                line.push('\n');
+            }
+            lines.push((line, change.tag()));
+        }
+        stats.hunks += 1;
+        stats.added += hunk_stats.added;
+        stats.removed += hunk_stats.removed;
+
+        let header = format!("{}\n", hunk.header());
+        let size = header.len() + lines.iter().map(|(l, _)| l.len()).sum::<usize>();
+        if cut || (shown_hunks > 0 && text.len() + size > max_bytes) {
+            cut = true;
+            elided.hunks += 1;
+            elided.added += hunk_stats.added;
+            elided.removed += hunk_stats.removed;
+            continue;
+        }
+
+        // Fits, or is the first hunk: keep as many of its lines as fit
+        shown_hunks += 1;
+        text.push_str(&header);
+        for (line, tag) in lines {
+            if !cut && text.len() + line.len() <= max_bytes {
+                text.push_str(&line);
+                continue;
+            }
+            cut = true;
+            match tag {
+                ChangeTag::Delete => elided.removed += 1,
+                ChangeTag::Insert => elided.added += 1,
+                ChangeTag::Equal => {}
+            }
+        }
+    }
+
+    if cut {
+        text.push_str(&summary(&elided));
+    }
+    RenderedDiff {
+        text,
+        stats,
+        shown_hunks,
+        elided,
+        truncated: cut,
+    }
+}
+
+/// The full unified diff of `old` to `new`.
+pub fn unified_diff(old: &str, new: &str, path: &str) -> String {
+    render_diff(old, new, path, usize::MAX).text
+}
+
+/// The line standing in for elided hunks.
+fn summary(elided: &DiffStats) -> String {
+    if elided.hunks == 0 {
+        return format!(
+            "… +{}/−{} more lines in this hunk …\n",
+            elided.added, elided.removed
+        );
+    }
+    format!(
+        "… {} more {}, +{}/−{} lines …\n",
+        elided.hunks,
+        if elided.hunks == 1 { "hunk" } else { "hunks" },
+        elided.added,
+        elided.removed
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `n` numbered lines, with the ones in `changed` rewritten.
+    fn numbered(n: usize, changed: &[usize]) -> String {
+        (1..=n)
+            .map(|i| match changed.contains(&i) {
+                true => format!("changed {}\n", i),
+                false => format!("line {}\n", i),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn hunks_beyond_budget_are_summarized() {
+        let old = numbered(100, &[]);
+        let new = numbered(100, &[10, 30, 50, 70, 90]);
+        let full = render_diff(&old, &new, "f.txt", usize::MAX);
+        assert_eq!(
+            full.stats,
+            DiffStats {
+                hunks: 5,
+                added: 5,
+                removed: 5
+            }
+        );
+        assert_eq!(full.text.matches("@@ -").count(), 5);
+        assert!(full.text.contains("-line 10\n+changed 10\n"));
+
+        let capped = render_diff(&old, &new, "f.txt", 250);
+        assert!(capped.text.len() < 300, "{}", capped.text);
+        assert_eq!(capped.stats, full.stats);
+        assert_eq!(capped.shown_hunks, 2);
+        assert_eq!(
+            capped.elided,
+            DiffStats {
+                hunks: 3,
+                added: 3,
+                removed: 3
+            }
+        );
+        assert!(capped.text.ends_with("… 3 more hunks, +3/−3 lines …\n"));
+        assert_eq!(capped.to_metadata()["truncated"], true);
+    }
+
+    #[test]
+    fn oversized_first_hunk_is_cut() {
+        let old = numbered(50, &[]);
+        let new = numbered(50, &(1..=50).collect::<Vec<_>>());
+        let capped = render_diff(&old, &new, "f.txt", 200);
+        assert_eq!(capped.shown_hunks, 1);
+        assert_eq!(capped.elided.hunks, 0);
+        let shown = capped
+            .text
+            .lines()
+            .skip(3)
+            .filter(|l| l.starts_with('-') || l.starts_with('+'))
+            .count();
+        assert_eq!(shown + capped.elided.added + capped.elided.removed, 100);
+        assert!(capped.text.ends_with("more lines in this hunk …\n"));
+    }
+
+    #[test]
+    fn missing_final_newline_and_no_changes() {
+        let diff = unified_diff("a\nb", "a\nc", "f");
+        assert_eq!(diff, "--- a/f\n+++ b/f\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n");
+        let same = render_diff("a\n", "a\n", "f", 10);
+        assert_eq!(same.stats.hunks, 0);
+        assert!(!same.is_truncated());
+    }
+}
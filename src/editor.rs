@@ -0,0 +1,140 @@
+//! Hook for opening files in the host's editor.
+//!
+//! When an agent points at "line 240 of parser.rs", an embedding UI (a VS
+//! Code extension, a web IDE) can turn that into a jump to the spot. The
+//! [`open_file`](crate::tools::open_file) tool hands each location to an
+//! [`EditorBridge`] the host provides; without one, [`NoEditor`] reports
+//! that nothing is connected and the call does nothing else.
+//!
+//! ```rust
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! use std::sync::{Arc, Mutex};
+//! use soul_coder::editor::{CallbackEditor, EditorBridge, EditorLocation};
+//!
+//! let opened = Arc::new(Mutex::new(Vec::new()));
+//! let sink = opened.clone();
+//! let editor = CallbackEditor::new(move |location: &EditorLocation| {
+//!     sink.lock().unwrap().push(location.to_string());
+//! });
+//! let location = EditorLocation {
+//!     path: "/workspace/src/parser.rs".into(),
+//!     line: Some(240),
+//!     column: None,
+//!     end_line: None,
+//! };
+//! editor.open(&location).await.unwrap();
+//! assert_eq!(opened.lock().unwrap()[0], "/workspace/src/parser.rs:240");
+//! # });
+//! ```
+
+use std::fmt;
+
+use async_trait::async_trait;
+use serde::Serialize;
+
+use soul_core::error::SoulResult;
+
+/// A place in a file to show: an absolute path and an optional 1-based
+/// position or line range.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EditorLocation {
+    pub path: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// Last line of a range to highlight, from `line`.
+    pub end_line: Option<usize>,
+}
+
+impl fmt::Display for EditorLocation {
+    /// `path`, `path:line`, `path:line:column` or `path:line-end_line`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+            match (self.column, self.end_line) {
+                (_, Some(end)) if end > line => write!(f, "-{}", end)?,
+                (Some(column), _) => write!(f, ":{}", column)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Opens locations in the host's editor.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait EditorBridge: Send + Sync {
+    /// Show `location` to the user.
+    async fn open(&self, location: &EditorLocation) -> SoulResult<()>;
+
+    /// Whether an editor is attached; `open` calls on a bridge that isn't
+    /// are reported to the model as not opened.
+    fn is_connected(&self) -> bool {
+        true
+    }
+}
+
+/// The default bridge: no editor attached.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoEditor;
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl EditorBridge for NoEditor {
+    async fn open(&self, _location: &EditorLocation) -> SoulResult<()> {
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        false
+    }
+}
+
+/// A bridge calling a closure, e.g. one posting to an extension's message
+/// channel.
+pub struct CallbackEditor<F> {
+    callback: F,
+}
+
+impl<F> CallbackEditor<F>
+where
+    F: Fn(&EditorLocation) + Send + Sync,
+{
+    pub fn new(callback: F) -> Self {
+        Self { callback }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<F> EditorBridge for CallbackEditor<F>
+where
+    F: Fn(&EditorLocation) + Send + Sync,
+{
+    async fn open(&self, location: &EditorLocation) -> SoulResult<()> {
+        (self.callback)(location);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locations_display_like_compiler_output() {
+        let mut location = EditorLocation {
+            path: "/w/src/parser.rs".into(),
+            line: None,
+            column: None,
+            end_line: None,
+        };
+        assert_eq!(location.to_string(), "/w/src/parser.rs");
+        location.line = Some(240);
+        location.column = Some(9);
+        assert_eq!(location.to_string(), "/w/src/parser.rs:240:9");
+        location.end_line = Some(250);
+        assert_eq!(location.to_string(), "/w/src/parser.rs:240-250");
+    }
+}
@@ -0,0 +1,117 @@
+//! Typed tool failures.
+//!
+//! Tool errors are still returned as `ToolOutput::error` with a message for
+//! the model, but every one also carries an `error_kind` in its metadata so
+//! agent frameworks can branch on the failure programmatically — retry a
+//! timeout, re-read after `not_unique`, surface `policy` to the user.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use soul_core::error::SoulError;
+use soul_core::tool::ToolOutput;
+
+/// Metadata key holding the [`ToolErrorKind`] of a failed call.
+pub const ERROR_KIND_KEY: &str = "error_kind";
+
+/// Category of a tool failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolErrorKind {
+    /// Missing, malformed or contradictory arguments.
+    InvalidArguments,
+    /// The file, directory or item does not exist.
+    NotFound,
+    /// A match that must be unique was ambiguous.
+    NotUnique,
+    /// Refusing to overwrite something that already exists.
+    AlreadyExists,
+    /// Blocked by a configured policy (read-only mode, denied path, ...).
+    Policy,
+    /// The operation ran out of time.
+    Timeout,
+    /// The input or output exceeds a size limit.
+    TooLarge,
+    /// Content is not valid UTF-8 or otherwise undecodable.
+    Encoding,
+    /// The filesystem reported an error.
+    Io,
+    /// The executor could not run the command at all.
+    ExecutorFailure,
+    /// The command ran but exited unsuccessfully.
+    CommandFailed,
+    /// The requested operation is not supported for this input.
+    Unsupported,
+}
+
+impl ToolErrorKind {
+    /// The kind recorded on `output`, if it is a typed error.
+    pub fn of(output: &ToolOutput) -> Option<Self> {
+        output
+            .metadata
+            .get(ERROR_KIND_KEY)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
+    /// Whether repeating the identical call may succeed.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Timeout | Self::Io | Self::ExecutorFailure)
+    }
+
+    /// Classify a VFS or executor error.
+    pub fn from_soul_error(error: &SoulError) -> Self {
+        match error {
+            SoulError::Io(e) => match e.kind() {
+                std::io::ErrorKind::NotFound => Self::NotFound,
+                std::io::ErrorKind::AlreadyExists => Self::AlreadyExists,
+                std::io::ErrorKind::PermissionDenied => Self::Policy,
+                std::io::ErrorKind::InvalidData => Self::Encoding,
+                std::io::ErrorKind::TimedOut => Self::Timeout,
+                _ => Self::Io,
+            },
+            SoulError::ToolExecution { message, .. } if message.contains("timed out") => {
+                Self::Timeout
+            }
+            SoulError::ToolExecution { .. } => Self::ExecutorFailure,
+            _ => Self::Io,
+        }
+    }
+}
+
+/// An error output tagged with `kind`.
+pub(crate) fn tool_error(kind: ToolErrorKind, message: impl Into<String>) -> ToolOutput {
+    ToolOutput::error(message).with_metadata(json!({ ERROR_KIND_KEY: kind }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind_round_trips_through_metadata() {
+        let output = tool_error(ToolErrorKind::NotUnique, "Found 2 occurrences");
+        assert!(output.is_error);
+        assert_eq!(output.metadata[ERROR_KIND_KEY], "not_unique");
+        assert_eq!(ToolErrorKind::of(&output), Some(ToolErrorKind::NotUnique));
+        assert_eq!(ToolErrorKind::of(&ToolOutput::success("ok")), None);
+    }
+
+    #[test]
+    fn classifies_soul_errors() {
+        let missing = SoulError::Io(std::io::Error::new(std::io::ErrorKind::NotFound, "x"));
+        assert_eq!(
+            ToolErrorKind::from_soul_error(&missing),
+            ToolErrorKind::NotFound
+        );
+        let timeout = SoulError::ToolExecution {
+            tool_name: "bash".into(),
+            message: "Command timed out after 5s".into(),
+        };
+        assert_eq!(
+            ToolErrorKind::from_soul_error(&timeout),
+            ToolErrorKind::Timeout
+        );
+        assert!(ToolErrorKind::Timeout.is_transient());
+        assert!(!ToolErrorKind::NotUnique.is_transient());
+    }
+}
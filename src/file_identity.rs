@@ -0,0 +1,230 @@
+//! Telling when two paths reach the same file.
+//!
+//! [`VirtualFs`](soul_core::vfs::VirtualFs) has no notion of symlinks or
+//! mounts, so a file reachable through a symlinked directory or an overlay
+//! shows up once per path in find and grep results, each copy using up
+//! context. Give those tools a [`FileIdentity`] and every path after the
+//! first one to reach a file is dropped from the results and listed under
+//! `duplicates` in the metadata instead.
+//!
+//! [`NativeFileIdentity`] resolves symlinks on the local filesystem;
+//! [`MemoryFileIdentity`] maps path prefixes onto others, for in-memory
+//! workspaces that mirror an overlay.
+//!
+//! ```rust
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! use soul_coder::file_identity::{FileIdentity, MemoryFileIdentity};
+//!
+//! let identity = MemoryFileIdentity::new();
+//! identity.link("/w/vendor/lib", "/w/lib");
+//! assert_eq!(
+//!     identity.identity("/w/vendor/lib/a.rs").await,
+//!     identity.identity("/w/lib/a.rs").await
+//! );
+//! # });
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use soul_core::tool::ToolOutput;
+
+use crate::paths;
+use crate::tools::{set_metadata, DisplayRoot};
+
+/// Links followed before [`MemoryFileIdentity`] gives up on a chain.
+const MAX_LINK_HOPS: usize = 16;
+
+/// Identifies the file behind a path.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait FileIdentity: Send + Sync {
+    /// A key equal for every path reaching the same file, such as its
+    /// canonical path, or `None` if it can't be told.
+    async fn identity(&self, path: &str) -> Option<String>;
+}
+
+/// Identity of files on the local filesystem: their canonical path, with
+/// symlinks resolved. Paths are resolved the way
+/// [`NativeFs`](soul_core::vfs::NativeFs) does.
+#[cfg(feature = "native")]
+pub struct NativeFileIdentity {
+    root: std::path::PathBuf,
+}
+
+#[cfg(feature = "native")]
+impl NativeFileIdentity {
+    /// Relative paths are taken from `root`, as for the `NativeFs` they
+    /// accompany.
+    pub fn new(root: impl Into<std::path::PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl FileIdentity for NativeFileIdentity {
+    async fn identity(&self, path: &str) -> Option<String> {
+        let canonical = tokio::fs::canonicalize(self.root.join(path)).await.ok()?;
+        Some(canonical.to_string_lossy().into_owned())
+    }
+}
+
+/// Identity from a table of links: a path under a linked prefix is the same
+/// file as the path under its target.
+#[derive(Debug, Default)]
+pub struct MemoryFileIdentity {
+    links: Mutex<HashMap<String, String>>,
+}
+
+impl MemoryFileIdentity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make `path`, a file or directory, an alias of `target`.
+    pub fn link(&self, path: &str, target: &str) {
+        self.links
+            .lock()
+            .unwrap()
+            .insert(paths::normalize(path), paths::normalize(target));
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl FileIdentity for MemoryFileIdentity {
+    async fn identity(&self, path: &str) -> Option<String> {
+        let links = self.links.lock().unwrap();
+        let mut path = paths::normalize(path);
+        for _ in 0..MAX_LINK_HOPS {
+            // The longest linked prefix wins
+            let link = links
+                .iter()
+                .filter_map(|(alias, target)| {
+                    let rest = path.strip_prefix(alias.as_str())?;
+                    (rest.is_empty() || rest.starts_with('/')).then_some((alias, target, rest))
+                })
+                .max_by_key(|(alias, _, _)| alias.len());
+            match link {
+                Some((_, target, rest)) => path = format!("{}{}", target, rest),
+                None => return Some(path),
+            }
+        }
+        None // A link cycle
+    }
+}
+
+/// Paths of one call found to reach a file an earlier path reached.
+pub(crate) struct Duplicates {
+    identity: Option<Arc<dyn FileIdentity>>,
+    state: Mutex<DuplicateState>,
+}
+
+#[derive(Default)]
+struct DuplicateState {
+    /// First path seen for each identity.
+    seen: HashMap<String, String>,
+    /// Paths dropped, keyed by the first path to the same file.
+    dropped: BTreeMap<String, Vec<String>>,
+}
+
+impl Duplicates {
+    /// Without an `identity`, no path is ever a duplicate.
+    pub(crate) fn new(identity: Option<Arc<dyn FileIdentity>>) -> Self {
+        Self {
+            identity,
+            state: Mutex::new(DuplicateState::default()),
+        }
+    }
+
+    /// Whether `path` reaches a file an earlier path did; if so it is
+    /// recorded as a duplicate of that path.
+    pub(crate) async fn is_duplicate(&self, path: &str) -> bool {
+        let Some(identity) = &self.identity else {
+            return false;
+        };
+        let Some(key) = identity.identity(path).await else {
+            return false;
+        };
+        let mut state = self.state.lock().unwrap();
+        match state.seen.get(&key).cloned() {
+            Some(first) => {
+                state.dropped.entry(first).or_default().push(path.to_string());
+                true
+            }
+            None => {
+                state.seen.insert(key, path.to_string());
+                false
+            }
+        }
+    }
+
+    /// Drop the duplicates from `paths`, keeping the first path to each file.
+    pub(crate) async fn retain_unique(&self, paths: &mut Vec<String>) {
+        if self.identity.is_none() {
+            return;
+        }
+        let mut unique = Vec::with_capacity(paths.len());
+        for path in paths.drain(..) {
+            if !self.is_duplicate(&path).await {
+                unique.push(path);
+            }
+        }
+        *paths = unique;
+    }
+
+    /// Add `duplicates: [{path, same_file}]` to a successful `output` if any
+    /// paths were dropped, `path` being the one kept.
+    pub(crate) fn annotate(&self, output: &mut ToolOutput, display: &DisplayRoot) {
+        let state = self.state.lock().unwrap();
+        if output.is_error || state.dropped.is_empty() {
+            return;
+        }
+        let duplicates: Vec<_> = state
+            .dropped
+            .iter()
+            .map(|(path, others)| {
+                json!({
+                    "path": display.show(path),
+                    "same_file": others.iter().map(|p| display.show(p)).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        set_metadata(output, "duplicates", json!(duplicates));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn memory_links_resolve_prefixes_and_chains() {
+        let identity = MemoryFileIdentity::new();
+        identity.link("/w/a", "/w/b");
+        identity.link("/w/b/c", "/w/d");
+        assert_eq!(identity.identity("/w/a/c/x").await.unwrap(), "/w/d/x");
+        assert_eq!(identity.identity("/w/ab/x").await.unwrap(), "/w/ab/x");
+
+        identity.link("/w/d", "/w/a/c");
+        assert_eq!(identity.identity("/w/d/x").await, None);
+    }
+
+    #[cfg(all(feature = "native", unix))]
+    #[tokio::test]
+    async fn native_identity_follows_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("real")).unwrap();
+        std::fs::write(dir.path().join("real/a.txt"), "a").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("real"), dir.path().join("link")).unwrap();
+        let identity = NativeFileIdentity::new(dir.path());
+
+        let real = identity.identity("real/a.txt").await.unwrap();
+        assert_eq!(identity.identity("link/a.txt").await.unwrap(), real);
+        assert_eq!(identity.identity("missing").await, None);
+    }
+}
@@ -0,0 +1,191 @@
+//! Permission bits for files the tools rewrite.
+//!
+//! [`VirtualFs`](soul_core::vfs::VirtualFs) only knows about contents, so a
+//! backend that writes by replacing the file drops its mode: an agent edits
+//! `scripts/build.sh`, the script loses `+x`, and the next bash call fails
+//! with "permission denied" for no visible reason. Tools given a
+//! [`FileModes`] read the mode before changing a file and put it back
+//! afterwards, and write accepts a `mode` argument for new scripts.
+//!
+//! [`NativeFileModes`] covers [`NativeFs`](soul_core::vfs::NativeFs) on Unix;
+//! [`MemoryFileModes`] keeps modes in a map, for in-memory workspaces that
+//! are later exported.
+//!
+//! ```rust
+//! use soul_coder::file_modes::parse_mode;
+//! use serde_json::json;
+//!
+//! assert_eq!(parse_mode(&json!("755")), Ok(0o755));
+//! assert_eq!(parse_mode(&json!("0o644")), Ok(0o644));
+//! assert_eq!(parse_mode(&json!(600)), Ok(0o600));
+//! assert!(parse_mode(&json!("rwx")).is_err());
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use soul_core::error::SoulResult;
+
+/// Highest mode accepted: permission bits plus setuid, setgid and sticky.
+const MAX_MODE: u32 = 0o7777;
+
+/// Reads and sets Unix permission bits of files.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait FileModes: Send + Sync {
+    /// The mode of `path` (e.g. `0o755`), or `None` if it doesn't exist or
+    /// has none.
+    async fn mode(&self, path: &str) -> Option<u32>;
+
+    /// Set the mode of `path`.
+    async fn set_mode(&self, path: &str, mode: u32) -> SoulResult<()>;
+}
+
+/// Parse a mode argument: an octal string (`"755"`, `"0755"`, `"0o755"`) or
+/// an integer written with octal digits (`755`).
+pub fn parse_mode(value: &serde_json::Value) -> Result<u32, String> {
+    let digits = match value {
+        serde_json::Value::String(s) => s.trim().trim_start_matches("0o").to_string(),
+        serde_json::Value::Number(n) if n.is_u64() => n.to_string(),
+        other => {
+            return Err(format!(
+                "mode must be an octal string like \"755\", got {}",
+                other
+            ))
+        }
+    };
+    match u32::from_str_radix(&digits, 8) {
+        Ok(mode) if mode <= MAX_MODE && !digits.is_empty() => Ok(mode),
+        _ => Err(format!(
+            "Invalid mode {}: expected octal permissions like \"755\" or \"644\"",
+            value
+        )),
+    }
+}
+
+/// Render a mode the way `chmod` takes it, e.g. `"755"`.
+pub fn format_mode(mode: u32) -> String {
+    format!("{:o}", mode)
+}
+
+/// The mode of `path` before a tool changes it, if the tool has modes.
+pub(crate) async fn current_mode(modes: &Option<Arc<dyn FileModes>>, path: &str) -> Option<u32> {
+    match modes {
+        Some(modes) => modes.mode(path).await,
+        None => None,
+    }
+}
+
+/// Put `mode` back on `path` if it changed; errors are ignored, since the
+/// contents were already written.
+pub(crate) async fn restore_mode(
+    modes: &Option<Arc<dyn FileModes>>,
+    path: &str,
+    mode: Option<u32>,
+) {
+    if let (Some(modes), Some(mode)) = (modes, mode) {
+        if modes.mode(path).await != Some(mode) {
+            let _ = modes.set_mode(path, mode).await;
+        }
+    }
+}
+
+/// Modes of files on the local filesystem, resolving paths the way
+/// [`NativeFs`](soul_core::vfs::NativeFs) does.
+#[cfg(all(feature = "native", unix))]
+pub struct NativeFileModes {
+    root: std::path::PathBuf,
+}
+
+#[cfg(all(feature = "native", unix))]
+impl NativeFileModes {
+    /// Relative paths are taken from `root`, as for the `NativeFs` they
+    /// accompany.
+    pub fn new(root: impl Into<std::path::PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn resolve(&self, path: &str) -> std::path::PathBuf {
+        let p = std::path::Path::new(path);
+        if p.is_absolute() {
+            p.to_path_buf()
+        } else {
+            self.root.join(path)
+        }
+    }
+}
+
+#[cfg(all(feature = "native", unix))]
+#[async_trait]
+impl FileModes for NativeFileModes {
+    async fn mode(&self, path: &str) -> Option<u32> {
+        use std::os::unix::fs::PermissionsExt;
+        let metadata = tokio::fs::metadata(self.resolve(path)).await.ok()?;
+        Some(metadata.permissions().mode() & MAX_MODE)
+    }
+
+    async fn set_mode(&self, path: &str, mode: u32) -> SoulResult<()> {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = std::fs::Permissions::from_mode(mode);
+        tokio::fs::set_permissions(self.resolve(path), permissions).await?;
+        Ok(())
+    }
+}
+
+/// Modes kept in memory, keyed by path. Files never given one have none.
+#[derive(Debug, Default)]
+pub struct MemoryFileModes {
+    modes: Mutex<HashMap<String, u32>>,
+}
+
+impl MemoryFileModes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl FileModes for MemoryFileModes {
+    async fn mode(&self, path: &str) -> Option<u32> {
+        self.modes.lock().unwrap().get(path).copied()
+    }
+
+    async fn set_mode(&self, path: &str, mode: u32) -> SoulResult<()> {
+        self.modes.lock().unwrap().insert(path.to_string(), mode);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_octal_modes() {
+        assert_eq!(parse_mode(&json!("0755")), Ok(0o755));
+        assert_eq!(parse_mode(&json!("4755")), Ok(0o4755));
+        assert!(parse_mode(&json!("")).is_err());
+        assert!(parse_mode(&json!("789")).is_err());
+        assert!(parse_mode(&json!("17777")).is_err());
+        assert!(parse_mode(&json!(true)).is_err());
+        assert_eq!(format_mode(0o644), "644");
+    }
+
+    #[cfg(all(feature = "native", unix))]
+    #[tokio::test]
+    async fn native_modes_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("run.sh"), "echo hi\n").unwrap();
+        let modes = NativeFileModes::new(dir.path());
+
+        modes.set_mode("run.sh", 0o750).await.unwrap();
+        assert_eq!(modes.mode("run.sh").await, Some(0o750));
+        let absolute = dir.path().join("run.sh");
+        assert_eq!(modes.mode(absolute.to_str().unwrap()).await, Some(0o750));
+        assert_eq!(modes.mode("missing").await, None);
+    }
+}
@@ -0,0 +1,209 @@
+//! Hook for running code formatters.
+//!
+//! Agents that lay out code by hand leave it subtly off the project's
+//! style, and every later formatter run shows up as churn. The
+//! [`format`](crate::tools::format) tool hands each file to a
+//! [`Formatter`] instead. On native hosts [`CommandFormatter`] runs the
+//! usual command-line formatter for the file type through a
+//! [`VirtualExecutor`]; hosts without processes, such as WASM, wrap their
+//! own formatting in a [`CallbackFormatter`].
+//!
+//! ```rust
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! use soul_coder::formatter::{CallbackFormatter, Formatter};
+//!
+//! let formatter = CallbackFormatter::new(|path: &str, content: &str| {
+//!     path.ends_with(".txt").then(|| content.trim_end().to_string() + "\n")
+//! });
+//! let formatted = formatter.format("/w/notes.txt", "hello  \n\n").await.unwrap();
+//! assert_eq!(formatted.as_deref(), Some("hello\n"));
+//! assert_eq!(formatter.format("/w/main.rs", "fn main() {}").await.unwrap(), None);
+//! # });
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use soul_core::error::{SoulError, SoulResult};
+use soul_core::vexec::VirtualExecutor;
+
+/// Commands [`CommandFormatter::new`] starts with, by file extension.
+/// `{path}` stands for the file, quoted; the formatted text is read from
+/// the command's standard output.
+pub const DEFAULT_COMMANDS: &[(&str, &str)] = &[
+    ("rs", "rustfmt --edition 2021 < {path}"),
+    ("go", "gofmt {path}"),
+    ("py", "black --quiet - < {path}"),
+    ("js", "prettier {path}"),
+    ("jsx", "prettier {path}"),
+    ("mjs", "prettier {path}"),
+    ("cjs", "prettier {path}"),
+    ("ts", "prettier {path}"),
+    ("tsx", "prettier {path}"),
+    ("json", "prettier {path}"),
+    ("css", "prettier {path}"),
+    ("scss", "prettier {path}"),
+    ("less", "prettier {path}"),
+    ("html", "prettier {path}"),
+    ("vue", "prettier {path}"),
+    ("md", "prettier {path}"),
+    ("yaml", "prettier {path}"),
+    ("yml", "prettier {path}"),
+];
+
+/// Formats file contents.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait Formatter: Send + Sync {
+    /// `content`, the text of the file at the absolute `path`, formatted;
+    /// `None` when no formatter handles that kind of file. An error means
+    /// the formatter rejected the file, e.g. over a syntax error.
+    async fn format(&self, path: &str, content: &str) -> SoulResult<Option<String>>;
+}
+
+/// Runs a formatter command per file extension through an executor.
+///
+/// The command formats the file as it is on disk rather than `content`, so
+/// this suits a [`NativeFs`](soul_core::vfs::NativeFs) workspace, where the
+/// two are the same.
+pub struct CommandFormatter {
+    executor: Arc<dyn VirtualExecutor>,
+    commands: BTreeMap<String, String>,
+    timeout_secs: u64,
+}
+
+impl CommandFormatter {
+    /// A formatter using [`DEFAULT_COMMANDS`].
+    pub fn new(executor: Arc<dyn VirtualExecutor>) -> Self {
+        Self {
+            executor,
+            commands: DEFAULT_COMMANDS
+                .iter()
+                .map(|(ext, command)| (ext.to_string(), command.to_string()))
+                .collect(),
+            timeout_secs: 30,
+        }
+    }
+
+    /// Format files ending in `.<extension>` with `command`, replacing any
+    /// earlier one; an empty command leaves them unformatted.
+    pub fn with_command(mut self, extension: &str, command: impl Into<String>) -> Self {
+        let command = command.into();
+        let extension = extension.trim_start_matches('.').to_ascii_lowercase();
+        if command.is_empty() {
+            self.commands.remove(&extension);
+        } else {
+            self.commands.insert(extension, command);
+        }
+        self
+    }
+
+    /// Seconds a formatter may run (default: 30).
+    pub fn with_timeout(mut self, secs: u64) -> Self {
+        self.timeout_secs = secs;
+        self
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Formatter for CommandFormatter {
+    async fn format(&self, path: &str, _content: &str) -> SoulResult<Option<String>> {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        let Some((_, extension)) = name.rsplit_once('.') else {
+            return Ok(None);
+        };
+        let Some(command) = self.commands.get(&extension.to_ascii_lowercase()) else {
+            return Ok(None);
+        };
+        let quoted = format!("'{}'", path.replace('\'', "'\\''"));
+        let dir = match path.rsplit_once('/') {
+            Some(("", _)) => "/",
+            Some((dir, _)) => dir,
+            None => ".",
+        };
+        let output = self
+            .executor
+            .exec_shell(
+                &command.replace("{path}", &quoted),
+                self.timeout_secs,
+                Some(dir),
+            )
+            .await?;
+        if !output.success() {
+            let program = command.split_whitespace().next().unwrap_or_default();
+            return Err(SoulError::ToolExecution {
+                tool_name: "format".into(),
+                message: format!(
+                    "{} exited with code {}: {}",
+                    program,
+                    output.exit_code,
+                    output.stderr.trim()
+                ),
+            });
+        }
+        Ok(Some(output.stdout))
+    }
+}
+
+/// A formatter calling a closure, e.g. one handing files to a formatter
+/// compiled to WASM.
+pub struct CallbackFormatter<F> {
+    callback: F,
+}
+
+impl<F> CallbackFormatter<F>
+where
+    F: Fn(&str, &str) -> Option<String> + Send + Sync,
+{
+    /// `callback` gets the path and content of a file and returns the
+    /// formatted content, or `None` for files it doesn't handle.
+    pub fn new(callback: F) -> Self {
+        Self { callback }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<F> Formatter for CallbackFormatter<F>
+where
+    F: Fn(&str, &str) -> Option<String> + Send + Sync,
+{
+    async fn format(&self, path: &str, content: &str) -> SoulResult<Option<String>> {
+        Ok((self.callback)(path, content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vexec::{ExecOutput, MockExecutor};
+
+    #[tokio::test]
+    async fn command_formatter_picks_by_extension() {
+        let executor = Arc::new(MockExecutor::new(vec![
+            ExecOutput {
+                stdout: "fn main() {}\n".into(),
+                stderr: String::new(),
+                exit_code: 0,
+            },
+            ExecOutput {
+                stdout: String::new(),
+                stderr: "error: expected `{`\n".into(),
+                exit_code: 1,
+            },
+        ]));
+        let formatter = CommandFormatter::new(executor).with_command("md", "");
+
+        let formatted = formatter.format("/w/main.rs", "fn main(){}").await;
+        assert_eq!(formatted.unwrap().as_deref(), Some("fn main() {}\n"));
+        let error = formatter.format("/w/bad.RS", "fn").await.unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("rustfmt exited with code 1: error: expected `{`"));
+        assert_eq!(formatter.format("/w/README.md", "").await.unwrap(), None);
+        assert_eq!(formatter.format("/w/Makefile", "").await.unwrap(), None);
+    }
+}
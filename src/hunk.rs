@@ -0,0 +1,296 @@
+//! Applying a single unified-diff hunk to a file's content.
+//!
+//! Models trained on diffs often describe an edit more reliably as a hunk
+//! (`-` lines removed, `+` lines added, space lines kept) than as an exact
+//! old/new pair. [`Hunk::apply`] finds where the hunk's context and removed
+//! lines sit in the file the way `patch` does:
+//!
+//! - the lines must match exactly, or failing that, ignoring trailing and
+//!   then all surrounding whitespace;
+//! - several matches are resolved by the line number in the `@@` header, if
+//!   one is given, picking the nearest;
+//! - when nothing matches, up to [`MAX_FUZZ`] leading and trailing context
+//!   lines are dropped and the search retried.
+//!
+//! ```rust
+//! use soul_coder::hunk::Hunk;
+//!
+//! let hunk = Hunk::parse("@@ -2,3 +2,3 @@\n b\n-c\n+C\n d\n").unwrap();
+//! let applied = hunk.apply("a\nb\nc\nd\ne\n").unwrap();
+//! assert_eq!(applied.content, "a\nb\nC\nd\ne\n");
+//! assert_eq!(applied.start_line, 2);
+//! ```
+
+use serde::Serialize;
+
+/// Most context lines dropped from each end of a hunk that doesn't match
+/// as given, like `patch`'s default fuzz factor.
+pub const MAX_FUZZ: usize = 2;
+
+/// One line of a hunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    Context(String),
+    Removed(String),
+    Added(String),
+}
+
+/// A parsed unified-diff hunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// Start line of the old side, from the `@@ -start,count` header.
+    pub old_start: Option<usize>,
+    pub lines: Vec<HunkLine>,
+}
+
+/// How strictly lines were compared to find a hunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineMatch {
+    Exact,
+    /// Trailing whitespace ignored.
+    TrailingWhitespace,
+    /// Leading and trailing whitespace ignored.
+    Whitespace,
+}
+
+impl LineMatch {
+    const ALL: [LineMatch; 3] = [Self::Exact, Self::TrailingWhitespace, Self::Whitespace];
+
+    fn eq(self, a: &str, b: &str) -> bool {
+        match self {
+            Self::Exact => a == b,
+            Self::TrailingWhitespace => a.trim_end() == b.trim_end(),
+            Self::Whitespace => a.trim() == b.trim(),
+        }
+    }
+}
+
+/// A hunk applied by [`Hunk::apply`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedHunk {
+    /// The content with the hunk applied.
+    pub content: String,
+    /// First line of the file the (possibly trimmed) hunk matched.
+    pub start_line: usize,
+    /// Context lines dropped from each end to find a match.
+    pub fuzz: usize,
+    pub line_match: LineMatch,
+}
+
+/// Why a hunk couldn't be applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkError {
+    /// Its context and removed lines appear nowhere in the file.
+    NotFound,
+    /// They appear at each of these lines and there is no header to pick one.
+    Ambiguous(Vec<usize>),
+}
+
+impl Hunk {
+    /// Parse the text of one hunk. File headers (`---`/`+++`) and a
+    /// `\ No newline at end of file` marker are skipped; a blank line counts
+    /// as an empty context line. The `@@` header is optional.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut old_start = None;
+        let mut headers = 0;
+        let mut lines = Vec::new();
+        for line in text.lines() {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if line.starts_with("@@") {
+                headers += 1;
+                if headers > 1 {
+                    return Err("Pass one hunk per call (found a second @@ header)".into());
+                }
+                old_start = parse_header(line);
+                continue;
+            }
+            if lines.is_empty() && (line.starts_with("--- ") || line.starts_with("+++ ")) {
+                continue;
+            }
+            if line.starts_with('\\') {
+                continue;
+            }
+            let parsed = match line.chars().next() {
+                Some(' ') => HunkLine::Context(line[1..].to_string()),
+                Some('-') => HunkLine::Removed(line[1..].to_string()),
+                Some('+') => HunkLine::Added(line[1..].to_string()),
+                None => HunkLine::Context(String::new()),
+                Some(_) => {
+                    return Err(format!(
+                        "Hunk line doesn't start with ' ', '-' or '+': {}",
+                        line
+                    ))
+                }
+            };
+            lines.push(parsed);
+        }
+        // A diff's final newline can leave an empty context line behind
+        while lines.last() == Some(&HunkLine::Context(String::new())) {
+            lines.pop();
+        }
+        if !lines
+            .iter()
+            .any(|l| matches!(l, HunkLine::Removed(_) | HunkLine::Added(_)))
+        {
+            return Err("Hunk has no '-' or '+' lines".into());
+        }
+        Ok(Self { old_start, lines })
+    }
+
+    /// Apply the hunk to `content`.
+    pub fn apply(&self, content: &str) -> Result<AppliedHunk, HunkError> {
+        let file: Vec<&str> = content.lines().collect();
+        let leading = context_run(self.lines.iter());
+        let trailing = context_run(self.lines.iter().rev());
+        let max_fuzz = MAX_FUZZ.min(leading.max(trailing));
+        for fuzz in 0..=max_fuzz {
+            let lines = &self.lines[fuzz.min(leading)..self.lines.len() - fuzz.min(trailing)];
+            let old: Vec<&str> = lines
+                .iter()
+                .filter_map(|l| match l {
+                    HunkLine::Context(text) | HunkLine::Removed(text) => Some(text.as_str()),
+                    HunkLine::Added(_) => None,
+                })
+                .collect();
+            if old.is_empty() {
+                // A pure insertion is placed by its header alone
+                let (Some(start), 0) = (self.old_start, fuzz) else {
+                    return Err(HunkError::NotFound);
+                };
+                let at = start.min(file.len());
+                return Ok(splice(content, &file, lines, at, 0, fuzz, LineMatch::Exact));
+            }
+            for line_match in LineMatch::ALL {
+                let found: Vec<usize> = (0..=file.len().saturating_sub(old.len()))
+                    .filter(|&i| {
+                        file.len() >= old.len()
+                            && old.iter().zip(&file[i..]).all(|(a, b)| line_match.eq(a, b))
+                    })
+                    .collect();
+                let at = match (found.as_slice(), self.old_start) {
+                    ([], _) => continue,
+                    ([at], _) => *at,
+                    (_, Some(start)) => {
+                        // The header counts the context dropped by fuzz
+                        let expected = (start + fuzz.min(leading)).saturating_sub(1);
+                        *found.iter().min_by_key(|&&i| i.abs_diff(expected)).unwrap()
+                    }
+                    (_, None) => {
+                        return Err(HunkError::Ambiguous(found.iter().map(|i| i + 1).collect()))
+                    }
+                };
+                return Ok(splice(
+                    content,
+                    &file,
+                    lines,
+                    at,
+                    old.len(),
+                    fuzz,
+                    line_match,
+                ));
+            }
+        }
+        Err(HunkError::NotFound)
+    }
+}
+
+/// Replace `len` lines of `file` from index `at` with the new side of
+/// `lines`. Context lines keep the file's text; line endings follow the
+/// file's.
+fn splice(
+    content: &str,
+    file: &[&str],
+    lines: &[HunkLine],
+    at: usize,
+    len: usize,
+    fuzz: usize,
+    line_match: LineMatch,
+) -> AppliedHunk {
+    let newline = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut replaced = Vec::new();
+    let mut old_index = at;
+    for line in lines {
+        match line {
+            HunkLine::Context(_) => {
+                replaced.push(file[old_index]);
+                old_index += 1;
+            }
+            HunkLine::Removed(_) => old_index += 1,
+            HunkLine::Added(text) => replaced.push(text.as_str()),
+        }
+    }
+    let mut result: Vec<&str> = file[..at].to_vec();
+    result.extend(replaced);
+    result.extend(&file[at + len..]);
+    let mut content_out = result.join(newline);
+    let ends_open = !content.is_empty() && !content.ends_with('\n');
+    if !content_out.is_empty() && (!ends_open || at + len < file.len()) {
+        content_out.push_str(newline);
+    }
+    AppliedHunk {
+        content: content_out,
+        start_line: at + 1,
+        fuzz,
+        line_match,
+    }
+}
+
+/// Number of context lines before the first change, counting from the end
+/// `lines` starts at.
+fn context_run<'a>(lines: impl Iterator<Item = &'a HunkLine>) -> usize {
+    lines
+        .take_while(|l| matches!(l, HunkLine::Context(_)))
+        .count()
+}
+
+/// The old-side start line of a `@@ -start,count +start,count @@` header.
+fn parse_header(line: &str) -> Option<usize> {
+    let old = line.trim_start_matches('@').trim().strip_prefix('-')?;
+    let start = old.split([',', ' ']).next()?;
+    start.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_with_header_whitespace_and_fuzz() {
+        let content = "fn a() {\n    x();\n}\n\nfn b() {\n    x();\n}\n";
+        let hunk = Hunk::parse(
+            "--- a/f.rs\n+++ b/f.rs\n@@ -5,3 +5,3 @@\n fn b() {\n-    x();\n+    y();\n }\n",
+        )
+        .unwrap();
+        let applied = hunk.apply(content).unwrap();
+        assert_eq!(
+            applied.content,
+            "fn a() {\n    x();\n}\n\nfn b() {\n    y();\n}\n"
+        );
+        assert_eq!((applied.start_line, applied.fuzz), (5, 0));
+
+        // Without the header the two x() lines are indistinguishable
+        let hunk = Hunk::parse("-    x();\n+    y();\n").unwrap();
+        assert_eq!(hunk.apply(content), Err(HunkError::Ambiguous(vec![2, 6])));
+
+        // Re-indented lines match ignoring whitespace; kept lines stay as in the file
+        let hunk = Hunk::parse(" fn a() {\n-  x();\n+    z();\n").unwrap();
+        let applied = hunk.apply(content).unwrap();
+        assert!(applied.content.starts_with("fn a() {\n    z();\n}\n"));
+        assert_eq!(applied.line_match, LineMatch::Whitespace);
+
+        // Stale leading context is dropped
+        let hunk = Hunk::parse(" gone\n fn b() {\n-    x();\n+    w();\n").unwrap();
+        let applied = hunk.apply(content).unwrap();
+        assert_eq!((applied.start_line, applied.fuzz), (5, 1));
+
+        let hunk = Hunk::parse("-nothing\n+here\n").unwrap();
+        assert_eq!(hunk.apply(content), Err(HunkError::NotFound));
+        assert!(Hunk::parse(" only context\n").is_err());
+        assert!(Hunk::parse("@@ -1 +1 @@\n-a\n+b\n@@ -5 +5 @@\n-c\n+d\n").is_err());
+    }
+}
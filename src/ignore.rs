@@ -0,0 +1,182 @@
+//! Minimal `.gitignore` support for workspace walks.
+//!
+//! Covers the parts of the gitignore format that matter for skipping build
+//! output and vendored code: `#` comments, `!` negation, trailing `/` for
+//! directories only, leading `/` (or any inner `/`) anchoring to the file's
+//! directory, and `*`, `?` and `**` wildcards. Rules from nested
+//! `.gitignore` files apply below their own directory, and later rules win.
+
+use soul_core::vfs::VirtualFs;
+
+use crate::paths;
+
+/// Name of the per-directory ignore file.
+pub const GITIGNORE: &str = ".gitignore";
+
+/// Name of the per-directory file hiding paths from the tools only (see
+/// [`crate::soulignore`]).
+pub const SOULIGNORE: &str = ".soulignore";
+
+#[derive(Debug, Clone)]
+struct Rule {
+    /// Directory containing the ignore file the rule came from.
+    base: String,
+    pattern: String,
+    negated: bool,
+    dir_only: bool,
+    /// Match against the base-relative path rather than the basename.
+    anchored: bool,
+}
+
+/// Accumulated ignore rules for a walk.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the rules of an ignore file located in directory `base`.
+    pub fn add(&mut self, base: &str, content: &str) {
+        let base = paths::normalize(base);
+        for line in content.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line.strip_prefix('\\').unwrap_or(line)),
+            };
+            let (dir_only, line) = match line.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let anchored = line.contains('/');
+            let pattern = line.trim_start_matches('/').to_string();
+            if pattern.is_empty() {
+                continue;
+            }
+            self.rules.push(Rule {
+                base: base.clone(),
+                pattern,
+                negated,
+                dir_only,
+                anchored,
+            });
+        }
+    }
+
+    /// Read `dir/.gitignore`, if present, and add its rules.
+    pub async fn load(&mut self, fs: &dyn VirtualFs, dir: &str) {
+        let file = paths::resolve(dir, GITIGNORE);
+        if let Ok(content) = fs.read_to_string(&file).await {
+            self.add(dir, &content);
+        }
+    }
+
+    /// Add all of `other`'s rules after this set's own.
+    pub fn extend(&mut self, other: &IgnoreRules) {
+        self.rules.extend(other.rules.iter().cloned());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether `path` (absolute) is ignored. Walkers prune ignored
+    /// directories, so ancestors are not re-checked here.
+    pub fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let Some(rel) = paths::relative_to(path, &rule.base) else {
+                continue;
+            };
+            let subject = if rule.anchored {
+                rel.as_str()
+            } else {
+                rel.rsplit('/').next().unwrap_or(&rel)
+            };
+            if glob_match(&rule.pattern, subject) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+/// Gitignore-style glob: `*` and `?` stay within a path segment, `**`
+/// spans any number of segments.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    glob_match_at(&p, &t)
+}
+
+fn glob_match_at(p: &[char], t: &[char]) -> bool {
+    match p {
+        [] => t.is_empty(),
+        ['*', '*', rest @ ..] => {
+            // `**/` also matches zero directories
+            let rest = rest.strip_prefix(&['/']).unwrap_or(rest);
+            (0..=t.len()).any(|i| {
+                (i == 0 || t[i - 1] == '/' || rest.is_empty()) && glob_match_at(rest, &t[i..])
+            })
+        }
+        ['*', rest @ ..] => {
+            for i in 0..=t.len() {
+                if glob_match_at(rest, &t[i..]) {
+                    return true;
+                }
+                if i < t.len() && t[i] == '/' {
+                    break;
+                }
+            }
+            false
+        }
+        ['?', rest @ ..] => matches!(t, [c, tail @ ..] if *c != '/' && glob_match_at(rest, tail)),
+        [c, rest @ ..] => matches!(t, [d, tail @ ..] if c == d && glob_match_at(rest, tail)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_wildcards() {
+        assert!(glob_match("*.rs", "main.rs"));
+        assert!(!glob_match("*.rs", "src/main.rs"));
+        assert!(glob_match("src/**/*.rs", "src/a/b/main.rs"));
+        assert!(glob_match("src/**/*.rs", "src/main.rs"));
+        assert!(glob_match("**/build", "a/b/build"));
+        assert!(glob_match("target/**", "target/debug/x"));
+        assert!(glob_match("?.txt", "a.txt"));
+        assert!(!glob_match("?.txt", "ab.txt"));
+    }
+
+    #[test]
+    fn rules_apply_in_order() {
+        let mut rules = IgnoreRules::new();
+        rules.add(
+            "/project",
+            "# build output\ntarget/\n*.log\n!keep.log\n/docs/generated\n",
+        );
+        rules.add("/project/web", "dist\n");
+
+        assert!(rules.is_ignored("/project/target", true));
+        assert!(!rules.is_ignored("/project/target", false));
+        assert!(rules.is_ignored("/project/sub/debug.log", false));
+        assert!(!rules.is_ignored("/project/keep.log", false));
+        assert!(rules.is_ignored("/project/docs/generated", true));
+        assert!(!rules.is_ignored("/project/src/docs/generated", true));
+        assert!(rules.is_ignored("/project/web/dist", true));
+        assert!(!rules.is_ignored("/project/dist", true));
+    }
+}
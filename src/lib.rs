@@ -1,7 +1,8 @@
 //! # soul-coder
 //!
 //! Coding-specific tools for [soul-core](https://crates.io/crates/soul-core) —
-//! read, write, append, edit, bash, grep, find, ls.
+//! read, write, append, edit, bash, grep, find, ls, outline, todo, loc, scratch,
+//! scaffold, project_info, hash, write_many, dupes, stats, grep_replace.
 //!
 //! WASM-first, cross-platform. All tools use `soul_core::vfs::VirtualFs` and
 //! `soul_core::vexec::VirtualExecutor` for platform abstraction, enabling
@@ -15,12 +16,12 @@
 //! use soul_core::vexec::NoopExecutor;
 //! use soul_coder::presets;
 //!
-//! // Create all 8 coding tools with in-memory VFS (WASM-ready)
+//! // Create all 19 coding tools with in-memory VFS (WASM-ready)
 //! let fs = Arc::new(MemoryFs::new());
 //! let exec = Arc::new(NoopExecutor);
 //! let registry = presets::all_tools(fs, exec, "/workspace");
 //!
-//! assert_eq!(registry.len(), 8);
+//! assert_eq!(registry.len(), 19);
 //! ```
 //!
 //! ## Tool Presets
@@ -29,7 +30,29 @@
 //! |--------|-------|----------|
 //! | `coding_tools` | read, write, edit, bash | Interactive coding sessions |
 //! | `read_only_tools` | read, grep, find, ls | Codebase exploration |
-//! | `all_tools` | all 7 tools | Full agent capabilities |
+//! | `read_only_enforced_tools` | read, grep, find, ls, bash | Exploration with enforced read-only access |
+//! | `all_tools` | all 19 tools | Full agent capabilities |
+//!
+//! ## Toolset Builder
+//!
+//! Compose any subset, with per-tool options, using [`Toolset`]:
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use soul_core::vfs::MemoryFs;
+//! use soul_core::vexec::NoopExecutor;
+//! use soul_coder::{ResultCache, Toolset};
+//!
+//! let fs = Arc::new(MemoryFs::new());
+//! let registry = Toolset::new(fs, Arc::new(NoopExecutor), "/workspace")
+//!     .with_read_only()
+//!     .with_outline()
+//!     .with_bash_configured(|bash| bash.with_max_timeout(60))
+//!     .with_middleware(Arc::new(ResultCache::new()))
+//!     .build_registry();
+//!
+//! assert_eq!(registry.len(), 6);
+//! ```
 //!
 //! ## ExecutorRegistry Integration
 //!
@@ -47,6 +70,85 @@
 //! assert!(registry.has_tool("read"));
 //! ```
 //!
+//! ## Audit Log
+//!
+//! Record every mutating call (write, write_many, append, edit, bash,
+//! scaffold, delete, format, grep_replace, rename) with its target path or
+//! command to a JSONL file in the VFS or any custom [`audit::AuditSink`]:
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use soul_core::vfs::MemoryFs;
+//! use soul_core::vexec::NoopExecutor;
+//! use soul_coder::audit::{audit_registry, VfsAuditSink};
+//!
+//! let fs = Arc::new(MemoryFs::new());
+//! let exec = Arc::new(NoopExecutor);
+//! let sink = Arc::new(VfsAuditSink::new(fs.clone(), "/workspace/.soul/audit.jsonl"));
+//! let tools = soul_coder::all_tools(fs.clone(), exec, "/workspace");
+//! let registry = audit_registry(tools, fs, "/workspace", sink);
+//!
+//! assert_eq!(registry.len(), 19);
+//! ```
+//!
+//! ## Middleware
+//!
+//! Intercept every call — rewrite arguments, veto, transform output, retry —
+//! with a [`middleware::ToolMiddleware`] stack:
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use async_trait::async_trait;
+//! use soul_core::error::SoulResult;
+//! use soul_core::tool::ToolOutput;
+//! use soul_core::vfs::MemoryFs;
+//! use soul_core::vexec::NoopExecutor;
+//! use soul_coder::middleware::{wrap_with_middleware, Next, ToolMiddleware};
+//!
+//! struct NoBash;
+//!
+//! #[async_trait]
+//! impl ToolMiddleware for NoBash {
+//!     async fn handle(
+//!         &self,
+//!         tool: &str,
+//!         call_id: &str,
+//!         arguments: serde_json::Value,
+//!         next: Next<'_>,
+//!     ) -> SoulResult<ToolOutput> {
+//!         if tool == "bash" {
+//!             return Ok(ToolOutput::error("bash is disabled"));
+//!         }
+//!         next.run(call_id, arguments).await
+//!     }
+//! }
+//!
+//! let fs = Arc::new(MemoryFs::new());
+//! let tools = soul_coder::all_tools(fs, Arc::new(NoopExecutor), "/workspace");
+//! let registry = wrap_with_middleware(tools, vec![Arc::new(NoBash)]);
+//!
+//! assert_eq!(registry.len(), 19);
+//! ```
+//!
+//! ## Result Cache
+//!
+//! [`cache::ResultCache`] is a middleware that replays repeated read, grep,
+//! find, ls and outline calls until any other tool runs:
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use soul_core::vfs::MemoryFs;
+//! use soul_core::vexec::NoopExecutor;
+//! use soul_coder::{wrap_with_middleware, ResultCache};
+//!
+//! let fs = Arc::new(MemoryFs::new());
+//! let cache = Arc::new(ResultCache::new());
+//! let tools = soul_coder::all_tools(fs, Arc::new(NoopExecutor), "/workspace");
+//! let registry = wrap_with_middleware(tools, vec![cache.clone()]);
+//!
+//! assert_eq!(cache.stats().hits, 0);
+//! ```
+//!
 //! ## Individual Tools
 //!
 //! Each tool can be instantiated independently:
@@ -60,21 +162,100 @@
 //! let tool = ReadTool::new(fs, "/workspace");
 //! ```
 
+pub mod aliases;
+pub mod audit;
+pub mod cache;
+pub mod conventions;
+pub mod diff;
+pub mod editor;
+pub mod error;
+pub mod file_identity;
+pub mod file_modes;
+pub mod formatter;
+pub mod hunk;
+pub mod ignore;
+pub mod locks;
+pub mod manifest;
+pub mod metrics;
+pub mod middleware;
+pub mod notebook;
+pub mod outline;
+pub mod output_parser;
+pub mod paths;
 pub mod presets;
+pub mod pretty;
+#[cfg(feature = "pty")]
+pub mod pty;
+pub mod read_only;
+pub mod redact;
+pub mod search_index;
+pub mod session;
+pub mod simulated_bash;
+pub mod snapshot;
+pub mod soulignore;
+pub mod stats;
+pub mod throttle;
+pub mod tool_config;
 pub mod tools;
+pub mod toolset;
+pub mod trash;
 pub mod truncate;
+pub mod walk;
+pub mod working_dir;
 
 // Re-export key types for convenience
+pub use aliases::{alias_registry, ToolAlias};
+pub use audit::{audit_registry, AuditRecord, AuditSink, AuditedTool, VfsAuditSink};
+pub use cache::{CacheStats, ResultCache};
+pub use editor::{EditorBridge, EditorLocation};
+pub use error::ToolErrorKind;
+pub use file_identity::FileIdentity;
+pub use file_modes::FileModes;
+pub use formatter::Formatter;
+pub use locks::PathLocks;
+pub use metrics::{metrics_registry, Metrics, MetricsMiddleware};
+pub use middleware::{wrap_with_middleware, MiddlewareTool, Next, ToolMiddleware};
+pub use read_only::ReadOnlyFs;
+pub use redact::{RedactionMiddleware, Redactor, Secret};
+pub use search_index::SearchIndex;
+pub use session::{ReadTracker, SessionState};
+pub use simulated_bash::SimulatedBash;
+pub use soulignore::SoulIgnoreFs;
+pub use throttle::OutputThrottle;
+pub use tool_config::{ConfiguredExecutor, ToolConfig};
+pub use toolset::Toolset;
+pub use working_dir::WorkingDir;
 pub use presets::{
-    all_executor, all_tools, coding_executor, coding_tools, read_only_tools, wrap_as_executor,
+    all_executor, all_tools, coding_executor, coding_tools, compat_aliases, compat_tools,
+    docs_executor, docs_tools, read_only_enforced_tools, read_only_tools, review_executor,
+    review_tools, system_prompt_snippet, test_executor, test_tools, wrap_as_configured_executor,
+    wrap_as_executor,
 };
 pub use tools::{
     append::AppendTool,
-    bash::BashTool,
+    bash::{BashTool, CommandHistory, HistoryEntry, ResourceLimits, Shell},
+    delete::DeleteTool,
+    dupes::DupesTool,
     edit::EditTool,
+    env_info::EnvInfoTool,
     find::FindTool,
+    format::FormatTool,
     grep::GrepTool,
+    grep_replace::GrepReplaceTool,
+    hash::HashTool,
+    loc::LocTool,
     ls::LsTool,
+    manifest::ManifestTool,
+    open_file::OpenFileTool,
+    outline::OutlineTool,
+    project_info::ProjectInfoTool,
     read::ReadTool,
+    rename::RenameTool,
+    scaffold::ScaffoldTool,
+    scratch::ScratchTool,
+    set_cwd::SetCwdTool,
+    stats::StatsTool,
+    todo::TodoTool,
     write::WriteTool,
+    write_many::WriteManyTool,
 };
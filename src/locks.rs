@@ -0,0 +1,169 @@
+//! Per-path locks serializing concurrent changes to the same file.
+//!
+//! write, edit and append read a file, compute new contents and write them
+//! back; two of those running at once on one path (parallel tool calls, or
+//! two agent branches sharing a VFS) would silently drop one change, since
+//! [`VirtualFs`](soul_core::vfs::VirtualFs) has no locking of its own. Tools
+//! hold a [`PathLocks`] lock for the path they change, for the whole
+//! read-modify-write. Calls on different paths never wait for each other.
+//!
+//! Each tool creates its own [`PathLocks`]; tools built by a
+//! [`Toolset`](crate::Toolset) share one, so a write and an edit of the
+//! same file are serialized too. Pass one explicitly with `with_locks` to
+//! share it between registries.
+//!
+//! ```rust
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! use soul_coder::locks::PathLocks;
+//!
+//! let locks = PathLocks::new();
+//! let guard = locks.lock("/workspace/src/lib.rs").await;
+//! assert!(!guard.contended());
+//! assert_eq!(locks.held(), 1);
+//! drop(guard);
+//! assert_eq!(locks.held(), 0);
+//! # });
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde_json::json;
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+use soul_core::tool::ToolOutput;
+
+use crate::tools::{resolve_path, set_metadata};
+
+/// Async locks keyed by normalized absolute path.
+#[derive(Debug, Default)]
+pub struct PathLocks {
+    entries: Mutex<HashMap<String, Arc<AsyncMutex<()>>>>,
+}
+
+impl PathLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait for exclusive access to `path`; it is released when the guard
+    /// is dropped.
+    pub async fn lock(&self, path: &str) -> PathLockGuard<'_> {
+        let (entry, queued) = {
+            let mut entries = self.entries.lock().unwrap();
+            let entry = entries.entry(path.to_string()).or_default().clone();
+            // Everyone else holding a handle: the map, the holder, waiters
+            let queued = Arc::strong_count(&entry) - 2;
+            (entry, queued)
+        };
+        let (guard, contended) = match entry.clone().try_lock_owned() {
+            Ok(guard) => (guard, false),
+            Err(_) => (entry.lock_owned().await, true),
+        };
+        PathLockGuard {
+            locks: self,
+            path: path.to_string(),
+            contended,
+            queued,
+            guard: Some(guard),
+        }
+    }
+
+    /// Lock the file named by a tool call's `path` argument, if it has one.
+    /// Arguments passed as a JSON string are looked into too.
+    pub(crate) async fn lock_for(
+        &self,
+        cwd: &str,
+        arguments: &serde_json::Value,
+    ) -> Option<PathLockGuard<'_>> {
+        let parsed;
+        let arguments = match arguments.as_str() {
+            Some(raw) => {
+                parsed = serde_json::from_str::<serde_json::Value>(raw).ok()?;
+                &parsed
+            }
+            None => arguments,
+        };
+        let path = arguments.get("path")?.as_str().filter(|p| !p.is_empty())?;
+        Some(self.lock(&resolve_path(cwd, path)).await)
+    }
+
+    /// Number of paths currently locked or waited on.
+    pub fn held(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+}
+
+/// Exclusive access to one path, from [`PathLocks::lock`].
+#[derive(Debug)]
+pub struct PathLockGuard<'a> {
+    locks: &'a PathLocks,
+    path: String,
+    contended: bool,
+    queued: usize,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl PathLockGuard<'_> {
+    /// Whether another call held the lock when this one asked for it.
+    pub fn contended(&self) -> bool {
+        self.contended
+    }
+
+    /// Calls holding or waiting for the lock when this one asked for it.
+    pub fn queued(&self) -> usize {
+        self.queued
+    }
+
+    /// Record contention under a `lock` metadata key; uncontended calls
+    /// are left unchanged.
+    pub fn annotate(&self, output: &mut ToolOutput) {
+        if self.contended {
+            set_metadata(
+                output,
+                "lock",
+                json!({"path": self.path, "contended": true, "queued": self.queued}),
+            );
+        }
+    }
+}
+
+impl Drop for PathLockGuard<'_> {
+    fn drop(&mut self) {
+        let mut entries = self.locks.entries.lock().unwrap();
+        // Only the map and this guard still refer to it: nobody is waiting
+        if let Some(entry) = entries.get(&self.path) {
+            if Arc::strong_count(entry) == 2 {
+                entries.remove(&self.path);
+            }
+        }
+        self.guard.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn same_path_waits_other_paths_do_not() {
+        let locks = Arc::new(PathLocks::new());
+        let first = locks.lock("/a").await;
+        let other = locks.lock("/b").await;
+        assert!(!other.contended());
+        drop(other);
+
+        let waiter = {
+            let locks = locks.clone();
+            tokio::spawn(async move {
+                let guard = locks.lock("/a").await;
+                (guard.contended(), guard.queued())
+            })
+        };
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+        drop(first);
+        assert_eq!(waiter.await.unwrap(), (true, 1));
+        assert_eq!(locks.held(), 0);
+    }
+}
@@ -0,0 +1,364 @@
+//! Workspace manifests — a nested JSON file tree for UIs.
+//!
+//! Web front-ends showing the agent's workspace want a tree of nodes, not the
+//! text that `ls` or `tree` print. [`build_manifest`] walks a directory
+//! breadth-first, so when [`ManifestOptions::max_entries`] cuts the walk
+//! short the upper levels are complete and only the deepest ones are missing.
+//! Directories below [`ManifestOptions::max_depth`] come back with
+//! `children: None`, which a UI can treat as "not loaded yet" and fetch with
+//! another call rooted there. Hidden entries and `.gitignore`d paths are
+//! skipped by default.
+//!
+//! ```rust
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! use soul_core::vfs::{MemoryFs, VirtualFs};
+//! use soul_coder::manifest::{build_manifest, ManifestOptions};
+//!
+//! let fs = MemoryFs::new();
+//! fs.write("/workspace/src/main.rs", "fn main() {}").await.unwrap();
+//! let tree = build_manifest(&fs, "/workspace", &ManifestOptions::default())
+//!     .await
+//!     .unwrap();
+//!
+//! let src = &tree.children.as_ref().unwrap()[0];
+//! assert_eq!(src.path, "src");
+//! assert_eq!(src.children.as_ref().unwrap()[0].size, Some(12));
+//! # });
+//! ```
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use soul_core::error::{SoulError, SoulResult};
+use soul_core::vfs::VirtualFs;
+
+use crate::ignore::IgnoreRules;
+use crate::paths;
+
+/// Default depth below the root that is expanded.
+pub const DEFAULT_MAX_DEPTH: usize = 3;
+
+/// Default cap on nodes in a manifest, root excluded.
+pub const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+/// Whether a node is a file or a directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeKind {
+    File,
+    Dir,
+}
+
+/// One file or directory in a manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestNode {
+    pub name: String,
+    /// Path relative to the manifest root (`"."` for the root itself).
+    pub path: String,
+    #[serde(rename = "type")]
+    pub kind: NodeKind,
+    /// Size in bytes, for files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Directory contents, directories first then in natural order; `None`
+    /// when the directory was not expanded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<ManifestNode>>,
+    /// The entry cap cut this directory short: `children` is incomplete, or
+    /// `None` because it was never listed.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+/// Limits and filters for [`build_manifest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestOptions {
+    /// Directory levels below the root to expand; 0 lists nothing.
+    pub max_depth: usize,
+    /// Maximum nodes returned, root excluded.
+    pub max_entries: usize,
+    /// Include dot-files and dot-directories.
+    pub include_hidden: bool,
+    /// Skip paths matched by `.gitignore` files.
+    pub respect_gitignore: bool,
+}
+
+impl Default for ManifestOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            include_hidden: false,
+            respect_gitignore: true,
+        }
+    }
+}
+
+impl ManifestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    pub fn with_max_entries(mut self, entries: usize) -> Self {
+        self.max_entries = entries;
+        self
+    }
+
+    pub fn with_hidden(mut self, include: bool) -> Self {
+        self.include_hidden = include;
+        self
+    }
+
+    pub fn with_gitignore(mut self, respect: bool) -> Self {
+        self.respect_gitignore = respect;
+        self
+    }
+}
+
+impl ManifestNode {
+    /// Number of nodes below this one.
+    pub fn count(&self) -> usize {
+        self.children
+            .iter()
+            .flatten()
+            .map(|child| 1 + child.count())
+            .sum()
+    }
+
+    /// Whether any directory in the tree hit the entry cap.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated || self.children.iter().flatten().any(|c| c.is_truncated())
+    }
+}
+
+/// Flat walk state; nodes refer to their children by index.
+struct Slot {
+    node: ManifestNode,
+    abs: String,
+    depth: usize,
+    ignore: IgnoreRules,
+    children: Vec<usize>,
+}
+
+/// Build the manifest of the directory `root`.
+pub async fn build_manifest(
+    fs: &dyn VirtualFs,
+    root: &str,
+    options: &ManifestOptions,
+) -> SoulResult<ManifestNode> {
+    let root = paths::normalize(root);
+    if !fs.metadata(&root).await?.is_dir {
+        return Err(SoulError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Not a directory: {}", root),
+        )));
+    }
+
+    let name = root.rsplit('/').find(|s| !s.is_empty()).unwrap_or("/");
+    let mut slots = vec![Slot {
+        node: ManifestNode {
+            name: name.to_string(),
+            path: ".".into(),
+            kind: NodeKind::Dir,
+            size: None,
+            children: None,
+            truncated: false,
+        },
+        abs: root.clone(),
+        depth: 0,
+        ignore: IgnoreRules::new(),
+        children: Vec::new(),
+    }];
+    let mut queue = VecDeque::from([0]);
+    let mut total = 0;
+
+    while let Some(index) = queue.pop_front() {
+        if slots[index].depth >= options.max_depth {
+            continue;
+        }
+        if total >= options.max_entries {
+            slots[index].node.truncated = true;
+            continue;
+        }
+        let dir = slots[index].abs.clone();
+        let mut ignore = slots[index].ignore.clone();
+        if options.respect_gitignore {
+            ignore.load(fs, &dir).await;
+        }
+        let Ok(mut entries) = fs.read_dir(&dir).await else {
+            continue; // Unreadable: leave unexpanded
+        };
+        entries.retain(|e| options.include_hidden || !e.name.starts_with('.'));
+        entries.sort_by(|a, b| {
+            b.is_dir
+                .cmp(&a.is_dir)
+                .then_with(|| paths::natural_cmp(&a.name, &b.name))
+        });
+
+        let mut children = Vec::new();
+        for entry in entries {
+            let abs = paths::resolve(&dir, &entry.name);
+            if ignore.is_ignored(&abs, entry.is_dir) {
+                continue;
+            }
+            if total >= options.max_entries {
+                slots[index].node.truncated = true;
+                break;
+            }
+            let size = if entry.is_file {
+                Some(fs.metadata(&abs).await.map(|m| m.size).unwrap_or(0))
+            } else {
+                None
+            };
+            total += 1;
+            children.push(slots.len());
+            if entry.is_dir {
+                queue.push_back(slots.len());
+            }
+            slots.push(Slot {
+                node: ManifestNode {
+                    path: paths::relative_to(&abs, &root).unwrap_or_default(),
+                    name: entry.name,
+                    kind: if entry.is_dir {
+                        NodeKind::Dir
+                    } else {
+                        NodeKind::File
+                    },
+                    size,
+                    children: None,
+                    truncated: false,
+                },
+                abs,
+                depth: slots[index].depth + 1,
+                ignore: ignore.clone(),
+                children: Vec::new(),
+            });
+        }
+        slots[index].children = children;
+        slots[index].node.children = Some(Vec::new());
+    }
+
+    // Children always come after their parent, so assemble back to front
+    let mut built: Vec<Option<ManifestNode>> = vec![None; slots.len()];
+    for (index, slot) in slots.into_iter().enumerate().rev() {
+        let mut node = slot.node;
+        if let Some(children) = node.children.as_mut() {
+            children.extend(slot.children.iter().filter_map(|&c| built[c].take()));
+        }
+        built[index] = Some(node);
+    }
+    Ok(built[0].take().expect("root node"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    async fn workspace() -> MemoryFs {
+        let fs = MemoryFs::new();
+        for (path, content) in [
+            ("/w/src/main.rs", "fn main() {}"),
+            ("/w/src/util/file10.rs", ""),
+            ("/w/src/util/file2.rs", ""),
+            ("/w/README.md", "# w"),
+            ("/w/.gitignore", "target/\n"),
+            ("/w/target/debug/app", "bin"),
+            ("/w/.git/HEAD", "ref"),
+        ] {
+            fs.write(path, content).await.unwrap();
+        }
+        fs
+    }
+
+    #[tokio::test]
+    async fn nested_tree_with_sizes() {
+        let fs = workspace().await;
+        let tree = build_manifest(&fs, "/w/", &ManifestOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(tree.name, "w");
+        assert_eq!(tree.path, ".");
+
+        let top: Vec<&str> = tree
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|n| n.path.as_str())
+            .collect();
+        assert_eq!(top, vec!["src", "README.md"]);
+
+        let src = &tree.children.as_ref().unwrap()[0];
+        let util = &src.children.as_ref().unwrap()[0];
+        assert_eq!(util.kind, NodeKind::Dir);
+        let names: Vec<&str> = util
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|n| n.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["file2.rs", "file10.rs"]);
+        assert_eq!(src.children.as_ref().unwrap()[1].size, Some(12));
+        assert_eq!(tree.count(), 6);
+        assert!(!tree.is_truncated());
+
+        let json = serde_json::to_value(&tree).unwrap();
+        assert_eq!(json["children"][1]["type"], "file");
+        assert!(json["children"][1].get("children").is_none());
+        assert!(json.get("truncated").is_none());
+    }
+
+    #[tokio::test]
+    async fn depth_and_entry_caps() {
+        let fs = workspace().await;
+        let tree = build_manifest(&fs, "/w", &ManifestOptions::new().with_max_depth(1))
+            .await
+            .unwrap();
+        let src = &tree.children.as_ref().unwrap()[0];
+        assert_eq!(src.children, None);
+        assert!(!tree.is_truncated());
+
+        // Breadth-first: the top level is complete before anything deeper
+        let tree = build_manifest(&fs, "/w", &ManifestOptions::new().with_max_entries(3))
+            .await
+            .unwrap();
+        assert_eq!(tree.count(), 3);
+        assert_eq!(tree.children.as_ref().unwrap().len(), 2);
+        assert!(tree.is_truncated());
+
+        let tree = build_manifest(
+            &fs,
+            "/w",
+            &ManifestOptions::new()
+                .with_hidden(true)
+                .with_gitignore(false),
+        )
+        .await
+        .unwrap();
+        let top: Vec<&str> = tree
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|n| n.name.as_str())
+            .collect();
+        assert_eq!(
+            top,
+            vec![".git", "src", "target", ".gitignore", "README.md"]
+        );
+
+        assert!(
+            build_manifest(&fs, "/w/README.md", &ManifestOptions::default())
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Tool-level metrics.
+//!
+//! Hosts exporting Prometheus or OpenTelemetry metrics implement [`Metrics`]
+//! — one callback for counters, one for histograms — and add a
+//! [`MetricsMiddleware`] to a registry ([`metrics_registry`] for presets,
+//! [`Toolset::with_metrics`](crate::Toolset::with_metrics) for toolsets).
+//! Every call is then recorded under the names below, labelled with the
+//! tool's registered name (and `kind` for errors):
+//!
+//! | Name | Type | Value |
+//! |------|------|-------|
+//! | [`CALLS`] | counter | 1 per call |
+//! | [`ERRORS`] | counter | 1 per failed call, labelled with its [`ToolErrorKind`] |
+//! | [`BYTES_IN`] | counter | size of the JSON arguments |
+//! | [`BYTES_OUT`] | counter | size of the output content |
+//! | [`TRUNCATIONS`] | counter | 1 per call whose output was truncated |
+//! | [`DURATION`] | histogram | call duration in seconds (native only) |
+//!
+//! WASM has no monotonic clock, so durations are not recorded there.
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use soul_core::vfs::MemoryFs;
+//! use soul_core::vexec::NoopExecutor;
+//! use soul_coder::metrics::{metrics_registry, Metrics};
+//!
+//! struct Log;
+//!
+//! impl Metrics for Log {
+//!     fn counter(&self, name: &str, labels: &[(&str, &str)], value: u64) {
+//!         println!("{name}{labels:?} += {value}");
+//!     }
+//!     fn histogram(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+//!         println!("{name}{labels:?} observe {value}");
+//!     }
+//! }
+//!
+//! let fs = Arc::new(MemoryFs::new());
+//! let tools = soul_coder::all_tools(fs, Arc::new(NoopExecutor), "/workspace");
+//! let registry = metrics_registry(tools, Arc::new(Log));
+//!
+//! assert_eq!(registry.len(), 19);
+//! ```
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{ToolOutput, ToolRegistry};
+
+use crate::error::ToolErrorKind;
+use crate::middleware::{wrap_with_middleware, Next, ToolMiddleware};
+
+/// Counter: tool calls.
+pub const CALLS: &str = "tool_calls_total";
+/// Counter: failed tool calls, labelled with `kind`.
+pub const ERRORS: &str = "tool_errors_total";
+/// Counter: bytes of JSON arguments received.
+pub const BYTES_IN: &str = "tool_input_bytes_total";
+/// Counter: bytes of output content returned.
+pub const BYTES_OUT: &str = "tool_output_bytes_total";
+/// Counter: calls whose output was truncated.
+pub const TRUNCATIONS: &str = "tool_truncations_total";
+/// Histogram: call duration in seconds.
+pub const DURATION: &str = "tool_call_duration_seconds";
+
+/// Receives tool metrics; forward them to your metrics library.
+pub trait Metrics: Send + Sync {
+    /// Add `value` to the counter `name`.
+    fn counter(&self, name: &str, labels: &[(&str, &str)], value: u64);
+
+    /// Record one observation of the histogram `name`.
+    fn histogram(&self, name: &str, labels: &[(&str, &str)], value: f64);
+}
+
+/// Middleware recording every call it sees to a [`Metrics`] sink.
+pub struct MetricsMiddleware {
+    metrics: Arc<dyn Metrics>,
+}
+
+impl MetricsMiddleware {
+    pub fn new(metrics: Arc<dyn Metrics>) -> Self {
+        Self { metrics }
+    }
+
+    /// Count a failure; untyped errors are labelled `unknown`.
+    fn error(&self, tool: &str, kind: Option<ToolErrorKind>) {
+        let kind = kind
+            .and_then(|k| serde_json::to_value(k).ok())
+            .and_then(|v| v.as_str().map(String::from))
+            .unwrap_or_else(|| "unknown".into());
+        self.metrics
+            .counter(ERRORS, &[("tool", tool), ("kind", &kind)], 1);
+    }
+
+    fn record(&self, tool: &str, output: &ToolOutput) {
+        let labels = [("tool", tool)];
+        self.metrics
+            .counter(BYTES_OUT, &labels, output.content.len() as u64);
+        if output.is_error {
+            self.error(tool, ToolErrorKind::of(output));
+        }
+        if is_truncated(output) {
+            self.metrics.counter(TRUNCATIONS, &labels, 1);
+        }
+    }
+}
+
+/// Whether a tool reported truncating its output, either in the shared
+/// `truncation` or `diff` metadata or with a top-level `truncated` flag.
+fn is_truncated(output: &ToolOutput) -> bool {
+    let flag = |v: Option<&serde_json::Value>| v.and_then(|v| v.as_bool()).unwrap_or(false);
+    flag(output.metadata.get("truncated"))
+        || flag(output.metadata.pointer("/truncation/truncated"))
+        || flag(output.metadata.pointer("/diff/truncated"))
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ToolMiddleware for MetricsMiddleware {
+    async fn handle(
+        &self,
+        tool: &str,
+        call_id: &str,
+        arguments: serde_json::Value,
+        next: Next<'_>,
+    ) -> SoulResult<ToolOutput> {
+        let labels = [("tool", tool)];
+        self.metrics.counter(CALLS, &labels, 1);
+        let bytes_in = match &arguments {
+            serde_json::Value::String(raw) => raw.len(),
+            other => other.to_string().len(),
+        };
+        self.metrics.counter(BYTES_IN, &labels, bytes_in as u64);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let started = std::time::Instant::now();
+        let result = next.run(call_id, arguments).await;
+        #[cfg(not(target_arch = "wasm32"))]
+        self.metrics
+            .histogram(DURATION, &labels, started.elapsed().as_secs_f64());
+
+        match &result {
+            Ok(output) => self.record(tool, output),
+            Err(e) => self.error(tool, Some(ToolErrorKind::from_soul_error(e))),
+        }
+        result
+    }
+}
+
+/// Wrap every tool in `tools` with a [`MetricsMiddleware`] reporting to
+/// `metrics`.
+pub fn metrics_registry(tools: ToolRegistry, metrics: Arc<dyn Metrics>) -> ToolRegistry {
+    wrap_with_middleware(tools, vec![Arc::new(MetricsMiddleware::new(metrics))])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    use serde_json::json;
+    use soul_core::vexec::{MockExecutor, VirtualExecutor};
+    use soul_core::vfs::{MemoryFs, VirtualFs};
+
+    use crate::presets::all_tools;
+
+    /// Sums counters and counts histogram observations by `name{labels}`.
+    #[derive(Default)]
+    struct Recorder {
+        counters: Mutex<BTreeMap<String, u64>>,
+        observations: Mutex<BTreeMap<String, usize>>,
+    }
+
+    fn key(name: &str, labels: &[(&str, &str)]) -> String {
+        let labels: Vec<String> = labels.iter().map(|(k, v)| format!("{k}={v}")).collect();
+        format!("{}{{{}}}", name, labels.join(","))
+    }
+
+    impl Metrics for Recorder {
+        fn counter(&self, name: &str, labels: &[(&str, &str)], value: u64) {
+            *self
+                .counters
+                .lock()
+                .unwrap()
+                .entry(key(name, labels))
+                .or_default() += value;
+        }
+
+        fn histogram(&self, name: &str, labels: &[(&str, &str)], _value: f64) {
+            *self
+                .observations
+                .lock()
+                .unwrap()
+                .entry(key(name, labels))
+                .or_default() += 1;
+        }
+    }
+
+    #[tokio::test]
+    async fn records_calls_errors_bytes_and_durations() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "hello").await.unwrap();
+        let exec = Arc::new(MockExecutor::always_ok("ok"));
+        let tools = all_tools(
+            fs as Arc<dyn VirtualFs>,
+            exec as Arc<dyn VirtualExecutor>,
+            "/project",
+        );
+        let recorder = Arc::new(Recorder::default());
+        let registry = metrics_registry(tools, recorder.clone());
+
+        let read = registry.get("read").unwrap();
+        let output = read
+            .execute("c1", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        read.execute("c2", json!({"path": "missing.txt"}), None)
+            .await
+            .unwrap();
+
+        let counters = recorder.counters.lock().unwrap();
+        assert_eq!(counters["tool_calls_total{tool=read}"], 2);
+        assert_eq!(counters["tool_errors_total{tool=read,kind=not_found}"], 1);
+        let bytes_in = json!({"path": "a.txt"}).to_string().len() as u64
+            + json!({"path": "missing.txt"}).to_string().len() as u64;
+        assert_eq!(counters["tool_input_bytes_total{tool=read}"], bytes_in);
+        assert!(counters["tool_output_bytes_total{tool=read}"] > output.content.len() as u64);
+        assert!(!counters.contains_key("tool_truncations_total{tool=read}"));
+        assert_eq!(
+            recorder.observations.lock().unwrap()["tool_call_duration_seconds{tool=read}"],
+            2
+        );
+    }
+
+    #[test]
+    fn truncation_flags() {
+        let mut output = ToolOutput::success("x");
+        assert!(!is_truncated(&output));
+        output.metadata = json!({"truncation": {"truncated": true}});
+        assert!(is_truncated(&output));
+        output.metadata = json!({"truncated": true});
+        assert!(is_truncated(&output));
+        output.metadata = json!({"diff": {"hunks": 3, "truncated": true}});
+        assert!(is_truncated(&output));
+    }
+}
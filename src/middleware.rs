@@ -0,0 +1,307 @@
+//! Tool middleware — intercept every tool call uniformly.
+//!
+//! A [`ToolMiddleware`] sees each call before the tool runs and decides what
+//! happens: pass it on (optionally with rewritten arguments) via [`Next::run`],
+//! veto it by returning its own [`ToolOutput`], transform the output, or call
+//! `next` more than once for retries. [`wrap_with_middleware`] applies a stack
+//! of middlewares to every tool in a registry; the first middleware is outermost.
+//!
+//! Middlewares get the name a tool was called by, which may be an alias or a
+//! host's renaming; [`Next::base_name`] tells which built-in tool answers it
+//! in registries built by [`Toolset`](crate::Toolset).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput, ToolRegistry};
+use soul_core::types::ToolDefinition;
+
+use crate::tools::SharedTool;
+
+/// Intercepts tool calls.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait ToolMiddleware: Send + Sync {
+    /// Handle a call to `tool`. Call `next.run(..)` to continue down the stack.
+    async fn handle(
+        &self,
+        tool: &str,
+        call_id: &str,
+        arguments: serde_json::Value,
+        next: Next<'_>,
+    ) -> SoulResult<ToolOutput>;
+}
+
+/// The rest of the middleware stack, ending in the wrapped tool.
+pub struct Next<'a> {
+    tool: &'a dyn Tool,
+    base_name: Option<&'a str>,
+    middlewares: &'a [Arc<dyn ToolMiddleware>],
+    partial_tx: Option<mpsc::UnboundedSender<String>>,
+}
+
+impl Next<'_> {
+    /// The channel the rest of the stack sends partial output to, if any.
+    pub fn partial_tx(&self) -> Option<&mpsc::UnboundedSender<String>> {
+        self.partial_tx.as_ref()
+    }
+
+    /// Send the rest of the stack's partial output to `partial_tx` instead,
+    /// e.g. to filter or throttle it.
+    pub fn with_partial_tx(mut self, partial_tx: Option<mpsc::UnboundedSender<String>>) -> Self {
+        self.partial_tx = partial_tx;
+        self
+    }
+
+    /// The built-in tool behind the call, e.g. `bash` for a call to an
+    /// `execute_command` alias; the called name where it isn't known.
+    pub fn base_name(&self) -> &str {
+        self.base_name.unwrap_or_else(|| self.tool.name())
+    }
+
+    /// Run the remaining middlewares and the tool. May be called repeatedly.
+    pub async fn run(&self, call_id: &str, arguments: serde_json::Value) -> SoulResult<ToolOutput> {
+        match self.middlewares.split_first() {
+            Some((first, rest)) => {
+                let next = Next {
+                    tool: self.tool,
+                    base_name: self.base_name,
+                    middlewares: rest,
+                    partial_tx: self.partial_tx.clone(),
+                };
+                first
+                    .handle(self.tool.name(), call_id, arguments, next)
+                    .await
+            }
+            None => {
+                self.tool
+                    .execute(call_id, arguments, self.partial_tx.clone())
+                    .await
+            }
+        }
+    }
+}
+
+/// A tool wrapped in a middleware stack.
+pub struct MiddlewareTool {
+    inner: Box<dyn Tool>,
+    middlewares: Vec<Arc<dyn ToolMiddleware>>,
+    base_name: Option<String>,
+}
+
+impl MiddlewareTool {
+    pub fn new(inner: Box<dyn Tool>, middlewares: Vec<Arc<dyn ToolMiddleware>>) -> Self {
+        Self {
+            inner,
+            middlewares,
+            base_name: None,
+        }
+    }
+
+    /// Report `name` as the [`Next::base_name`] of calls to this tool.
+    pub fn with_base_name(mut self, name: impl Into<String>) -> Self {
+        self.base_name = Some(name.into());
+        self
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for MiddlewareTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.inner.definition()
+    }
+
+    async fn execute(
+        &self,
+        call_id: &str,
+        arguments: serde_json::Value,
+        partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let next = Next {
+            tool: self.inner.as_ref(),
+            base_name: self.base_name.as_deref(),
+            middlewares: &self.middlewares,
+            partial_tx,
+        };
+        next.run(call_id, arguments).await
+    }
+}
+
+/// Wrap every tool in `tools` with the given middleware stack.
+pub fn wrap_with_middleware(
+    tools: ToolRegistry,
+    middlewares: Vec<Arc<dyn ToolMiddleware>>,
+) -> ToolRegistry {
+    wrap_with_base_names(tools, middlewares, &HashMap::new())
+}
+
+/// [`wrap_with_middleware`], telling the middlewares the built-in tool
+/// behind each name in `base_names`.
+pub(crate) fn wrap_with_base_names(
+    tools: ToolRegistry,
+    middlewares: Vec<Arc<dyn ToolMiddleware>>,
+    base_names: &HashMap<String, String>,
+) -> ToolRegistry {
+    let mut registry = ToolRegistry::new();
+    for tool in SharedTool::split(tools) {
+        let base_name = base_names.get(tool.name()).cloned();
+        let mut wrapped = MiddlewareTool::new(Box::new(tool), middlewares.clone());
+        if let Some(base_name) = base_name {
+            wrapped = wrapped.with_base_name(base_name);
+        }
+        registry.register(Box::new(wrapped));
+    }
+    registry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use soul_core::vexec::{MockExecutor, VirtualExecutor};
+    use soul_core::vfs::{MemoryFs, VirtualFs};
+    use std::sync::Mutex;
+
+    use crate::presets::all_tools;
+
+    fn registry(fs: Arc<MemoryFs>) -> ToolRegistry {
+        let exec = Arc::new(MockExecutor::always_ok("ok"));
+        all_tools(
+            fs as Arc<dyn VirtualFs>,
+            exec as Arc<dyn VirtualExecutor>,
+            "/project",
+        )
+    }
+
+    /// Records the order in which calls pass through.
+    struct Trace {
+        label: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl ToolMiddleware for Trace {
+        async fn handle(
+            &self,
+            tool: &str,
+            call_id: &str,
+            arguments: serde_json::Value,
+            next: Next<'_>,
+        ) -> SoulResult<ToolOutput> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{}:{}", self.label, tool));
+            next.run(call_id, arguments).await
+        }
+    }
+
+    struct DenyBash;
+
+    #[async_trait]
+    impl ToolMiddleware for DenyBash {
+        async fn handle(
+            &self,
+            tool: &str,
+            call_id: &str,
+            arguments: serde_json::Value,
+            next: Next<'_>,
+        ) -> SoulResult<ToolOutput> {
+            if tool == "bash" {
+                return Ok(ToolOutput::error("bash is disabled"));
+            }
+            next.run(call_id, arguments).await
+        }
+    }
+
+    /// Forces all reads to a fixed file and tags the output.
+    struct Rewrite;
+
+    #[async_trait]
+    impl ToolMiddleware for Rewrite {
+        async fn handle(
+            &self,
+            _tool: &str,
+            call_id: &str,
+            mut arguments: serde_json::Value,
+            next: Next<'_>,
+        ) -> SoulResult<ToolOutput> {
+            arguments["path"] = json!("b.txt");
+            let mut output = next.run(call_id, arguments).await?;
+            output.content.push_str("\n[rewritten]");
+            Ok(output)
+        }
+    }
+
+    #[tokio::test]
+    async fn middlewares_run_outermost_first() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "a").await.unwrap();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let tools = wrap_with_middleware(
+            registry(fs),
+            vec![
+                Arc::new(Trace {
+                    label: "outer",
+                    log: log.clone(),
+                }),
+                Arc::new(Trace {
+                    label: "inner",
+                    log: log.clone(),
+                }),
+            ],
+        );
+        assert_eq!(tools.len(), 19);
+
+        let result = tools
+            .get("read")
+            .unwrap()
+            .execute("c1", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        assert!(result.content.contains('a'));
+        assert_eq!(*log.lock().unwrap(), vec!["outer:read", "inner:read"]);
+    }
+
+    #[tokio::test]
+    async fn middleware_can_veto() {
+        let fs = Arc::new(MemoryFs::new());
+        let tools = wrap_with_middleware(registry(fs), vec![Arc::new(DenyBash)]);
+
+        let result = tools
+            .get("bash")
+            .unwrap()
+            .execute("c2", json!({"command": "rm -rf /"}), None)
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(result.content.contains("disabled"));
+    }
+
+    #[tokio::test]
+    async fn middleware_rewrites_arguments_and_output() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "alpha").await.unwrap();
+        fs.write("/project/b.txt", "beta").await.unwrap();
+        let tools = wrap_with_middleware(registry(fs), vec![Arc::new(Rewrite)]);
+
+        let result = tools
+            .get("read")
+            .unwrap()
+            .execute("c3", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        assert!(result.content.contains("beta"));
+        assert!(!result.content.contains("alpha"));
+        assert!(result.content.ends_with("[rewritten]"));
+    }
+}
@@ -0,0 +1,223 @@
+//! Jupyter notebook (.ipynb) helpers shared by the read and edit tools.
+//!
+//! Notebooks are JSON with each cell's source split into a list of lines, so
+//! string-matching edits on the raw file routinely break escaping or the
+//! surrounding structure. These helpers render cells as plain text (outputs
+//! summarized, never dumped) and replace a single cell's source while leaving
+//! the rest of the document untouched.
+
+use serde_json::{json, Value};
+
+/// Characters of a text output shown before it is elided.
+const OUTPUT_PREVIEW_CHARS: usize = 200;
+
+/// Whether `path` names a Jupyter notebook.
+pub fn is_notebook(path: &str) -> bool {
+    path.to_ascii_lowercase().ends_with(".ipynb")
+}
+
+/// A parsed notebook. Unknown fields are kept so edits round-trip.
+pub struct Notebook {
+    doc: Value,
+}
+
+impl Notebook {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let doc: Value =
+            serde_json::from_str(content).map_err(|e| format!("Invalid notebook JSON: {}", e))?;
+        if !doc.get("cells").is_some_and(Value::is_array) {
+            return Err("Invalid notebook: missing \"cells\" array".into());
+        }
+        Ok(Self { doc })
+    }
+
+    fn cells(&self) -> &[Value] {
+        self.doc["cells"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells().is_empty()
+    }
+
+    /// Source text of cell `index`.
+    pub fn cell_source(&self, index: usize) -> Option<String> {
+        self.cells()
+            .get(index)
+            .map(|cell| join_text(&cell["source"]))
+    }
+
+    /// Replace the source of cell `index`, stored as a list of lines like
+    /// Jupyter itself writes it.
+    pub fn set_cell_source(&mut self, index: usize, source: &str) -> Result<(), String> {
+        let len = self.len();
+        let cell = self.doc["cells"]
+            .get_mut(index)
+            .ok_or_else(|| format!("Cell {} out of range (notebook has {} cells)", index, len))?;
+        let lines: Vec<&str> = source.split_inclusive('\n').collect();
+        cell["source"] = json!(lines);
+        Ok(())
+    }
+
+    /// Render cell `index`, or every cell when `None`.
+    pub fn render(&self, index: Option<usize>) -> Option<String> {
+        match index {
+            Some(i) => self.cells().get(i).map(|cell| render_cell(i, cell)),
+            None => Some(
+                self.cells()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, cell)| render_cell(i, cell))
+                    .collect::<Vec<_>>()
+                    .join("\n\n"),
+            ),
+        }
+    }
+
+    /// Serialize in nbformat's layout: sorted keys, one-space indent,
+    /// trailing newline.
+    pub fn to_json(&self) -> String {
+        let mut out = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+        let mut ser = serde_json::Serializer::with_formatter(&mut out, formatter);
+        serde::Serialize::serialize(&self.doc, &mut ser).expect("JSON values always serialize");
+        let mut json = String::from_utf8(out).expect("serde_json emits UTF-8");
+        json.push('\n');
+        json
+    }
+}
+
+/// Join a notebook multiline string (a string or a list of strings).
+fn join_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(parts) => parts.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+fn render_cell(index: usize, cell: &Value) -> String {
+    let cell_type = cell["cell_type"].as_str().unwrap_or("unknown");
+    let mut header = format!("[cell {}] {}", index, cell_type);
+    if let Some(n) = cell["execution_count"].as_u64() {
+        header.push_str(&format!(" (execution_count: {})", n));
+    }
+
+    let mut out = header;
+    let source = join_text(&cell["source"]);
+    if !source.is_empty() {
+        out.push('\n');
+        out.push_str(source.trim_end_matches('\n'));
+    }
+    if let Some(outputs) = cell["outputs"].as_array() {
+        for output in outputs {
+            out.push_str("\n[output] ");
+            out.push_str(&summarize_output(output));
+        }
+    }
+    out
+}
+
+/// One-line summary of a cell output: text is previewed, rich data is
+/// reduced to its MIME types.
+fn summarize_output(output: &Value) -> String {
+    match output["output_type"].as_str().unwrap_or("unknown") {
+        "stream" => {
+            let name = output["name"].as_str().unwrap_or("stdout");
+            format!("{}: {}", name, preview(&join_text(&output["text"])))
+        }
+        "error" => format!(
+            "error: {}: {}",
+            output["ename"].as_str().unwrap_or("Error"),
+            output["evalue"].as_str().unwrap_or("")
+        ),
+        kind => {
+            let data = output["data"].as_object();
+            match data.and_then(|d| d.get("text/plain")) {
+                Some(text) => format!("{}: {}", kind, preview(&join_text(text))),
+                None => {
+                    let mimes: Vec<&str> = data
+                        .map(|d| d.keys().map(String::as_str).collect())
+                        .unwrap_or_default();
+                    format!("{}: <{}>", kind, mimes.join(", "))
+                }
+            }
+        }
+    }
+}
+
+/// First `OUTPUT_PREVIEW_CHARS` characters of `text` on one line.
+fn preview(text: &str) -> String {
+    let flat = text.trim().replace('\n', " ⏎ ");
+    if flat.chars().count() <= OUTPUT_PREVIEW_CHARS {
+        return flat;
+    }
+    let cut: String = flat.chars().take(OUTPUT_PREVIEW_CHARS).collect();
+    format!("{}… ({} chars)", cut, flat.chars().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": ["# Title\n", "Intro"]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 2,
+   "metadata": {},
+   "outputs": [
+    {"name": "stdout", "output_type": "stream", "text": ["hello\n"]},
+    {"data": {"image/png": "iVBOR..."}, "metadata": {}, "output_type": "display_data"}
+   ],
+   "source": "print('hello')"
+  }
+ ],
+ "metadata": {},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##;
+
+    #[test]
+    fn renders_cells_with_summarized_outputs() {
+        let nb = Notebook::parse(NOTEBOOK).unwrap();
+        assert_eq!(nb.len(), 2);
+        let text = nb.render(None).unwrap();
+        assert!(text.starts_with("[cell 0] markdown\n# Title\nIntro"));
+        assert!(text.contains("[cell 1] code (execution_count: 2)\nprint('hello')"));
+        assert!(text.contains("[output] stdout: hello"));
+        assert!(text.contains("[output] display_data: <image/png>"));
+        assert!(!text.contains("iVBOR"));
+    }
+
+    #[test]
+    fn set_source_round_trips() {
+        let mut nb = Notebook::parse(NOTEBOOK).unwrap();
+        nb.set_cell_source(1, "x = 1\nprint(x)").unwrap();
+        let json = nb.to_json();
+        assert!(json.contains("\"source\": [\n    \"x = 1\\n\",\n    \"print(x)\"\n   ]"));
+
+        let reparsed = Notebook::parse(&json).unwrap();
+        assert_eq!(reparsed.cell_source(1).unwrap(), "x = 1\nprint(x)");
+        assert_eq!(reparsed.cell_source(0).unwrap(), "# Title\nIntro");
+        assert!(nb.set_cell_source(5, "").is_err());
+    }
+
+    #[test]
+    fn rejects_non_notebooks() {
+        assert!(Notebook::parse("not json").is_err());
+        assert!(Notebook::parse("{\"cells\": 1}").is_err());
+        assert!(is_notebook("analysis.IPYNB"));
+    }
+}
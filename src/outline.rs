@@ -0,0 +1,626 @@
+//! Lightweight, language-aware structure extraction.
+//!
+//! Line-based heuristics per language — no parser dependency, so it works
+//! identically in native and WASM builds. Recognizes top-level definitions
+//! (functions, types, impl blocks, classes, exports) and one level of
+//! members nested inside them.
+
+use serde::Serialize;
+
+/// Languages with outline heuristics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    Python,
+    JavaScript,
+    Go,
+    Markdown,
+}
+
+impl Language {
+    /// Detect the language from a file path's extension.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let ext = path.rsplit_once('.').map(|(_, ext)| ext)?;
+        match ext.to_ascii_lowercase().as_str() {
+            "rs" => Some(Self::Rust),
+            "py" | "pyi" => Some(Self::Python),
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => Some(Self::JavaScript),
+            "go" => Some(Self::Go),
+            "md" | "markdown" => Some(Self::Markdown),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::Python => "python",
+            Self::JavaScript => "javascript",
+            Self::Go => "go",
+            Self::Markdown => "markdown",
+        }
+    }
+}
+
+/// A single structural item in a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutlineItem {
+    /// 1-indexed line number of the definition.
+    pub line: usize,
+    /// Item kind (e.g. `fn`, `struct`, `class`, `heading`).
+    pub kind: String,
+    /// Item name (for impl blocks, the full `Type` / `Trait for Type` target).
+    pub name: String,
+    /// Nesting depth: 0 for top-level, 1 for members.
+    pub depth: usize,
+}
+
+/// Maximum nesting depth reported for code languages.
+const MAX_CODE_DEPTH: usize = 1;
+
+/// Extract the structural outline of `content`.
+pub fn extract_outline(content: &str, language: Language) -> Vec<OutlineItem> {
+    let mut items = Vec::new();
+
+    for (idx, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let parsed = match language {
+            Language::Markdown => parse_markdown(trimmed),
+            _ => {
+                let depth = indent_width(line) / 4;
+                if depth > MAX_CODE_DEPTH {
+                    continue;
+                }
+                let found = match language {
+                    Language::Rust => parse_rust(trimmed),
+                    Language::Python => parse_python(trimmed),
+                    Language::JavaScript => parse_javascript(trimmed, depth),
+                    Language::Go => parse_go(trimmed),
+                    Language::Markdown => None,
+                };
+                found.map(|(kind, name)| (kind, name, depth))
+            }
+        };
+
+        if let Some((kind, name, depth)) = parsed {
+            items.push(OutlineItem {
+                line: idx + 1,
+                kind: kind.to_string(),
+                name,
+                depth,
+            });
+        }
+    }
+
+    items
+}
+
+/// Line span of a definition found by [`find_symbol`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SymbolSpan {
+    /// 1-indexed first line, including leading doc comments and attributes.
+    pub start: usize,
+    /// 1-indexed last line (inclusive).
+    pub end: usize,
+    /// 1-indexed line of the definition itself.
+    pub line: usize,
+    pub kind: String,
+    pub name: String,
+}
+
+/// Find every definition named `symbol` and the lines it spans.
+///
+/// `Parent::member` or `Parent.member` restricts the search to members of
+/// `Parent` (a type, impl block or class). Spans follow braces for Rust, Go
+/// and JavaScript, indentation for Python and heading levels for Markdown.
+pub fn find_symbol(content: &str, language: Language, symbol: &str) -> Vec<SymbolSpan> {
+    let symbol = symbol.trim();
+    let items = extract_outline(content, language);
+    let lines: Vec<&str> = content.lines().collect();
+
+    let (parent, member) = match symbol.rsplit_once("::").or_else(|| symbol.rsplit_once('.')) {
+        Some((p, m)) if language != Language::Markdown && !p.is_empty() && !m.is_empty() => {
+            (Some(p), m)
+        }
+        _ => (None, symbol),
+    };
+
+    let mut spans = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        if !names_match(&item.name, member) {
+            continue;
+        }
+        if let Some(parent) = parent {
+            // Nearest enclosing item must be the requested parent
+            let enclosing = items[..i].iter().rev().find(|p| p.depth < item.depth);
+            if !enclosing.is_some_and(|p| names_match(&p.name, parent)) {
+                continue;
+            }
+        }
+        let idx = item.line - 1;
+        let end = match language {
+            Language::Markdown => heading_end(&lines, idx, &items[i + 1..], item.depth),
+            Language::Python => indented_block_end(&lines, idx),
+            _ => braced_block_end(&lines, idx, language),
+        };
+        let start = if language == Language::Markdown {
+            idx
+        } else {
+            leading_comments_start(&lines, idx)
+        };
+        spans.push(SymbolSpan {
+            start: start + 1,
+            end: end + 1,
+            line: item.line,
+            kind: item.kind.clone(),
+            name: item.name.clone(),
+        });
+    }
+    spans
+}
+
+/// 0-indexed last line of the definition whose header is on line `idx`.
+///
+/// Follows indentation for Python (and, for unknown languages, headers
+/// ending in `:`), heading levels for Markdown and braces (or a
+/// `;`-terminated item) otherwise.
+pub fn block_end(lines: &[&str], idx: usize, language: Option<Language>) -> usize {
+    match language {
+        Some(Language::Python) => indented_block_end(lines, idx),
+        Some(Language::Markdown) => {
+            let Some((_, _, depth)) = parse_markdown(lines[idx].trim_start()) else {
+                return idx;
+            };
+            let later: Vec<OutlineItem> = lines
+                .iter()
+                .enumerate()
+                .skip(idx + 1)
+                .filter_map(|(i, line)| {
+                    parse_markdown(line.trim_start()).map(|(kind, name, depth)| OutlineItem {
+                        line: i + 1,
+                        kind: kind.to_string(),
+                        name,
+                        depth,
+                    })
+                })
+                .collect();
+            heading_end(lines, idx, &later, depth)
+        }
+        Some(language) => braced_block_end(lines, idx, language),
+        None if lines[idx].trim_end().ends_with(':') => indented_block_end(lines, idx),
+        None => braced_block_end(lines, idx, Language::Go),
+    }
+}
+
+/// Whether an outline name refers to `wanted`. Impl blocks match their
+/// self type: `<T> Display for Wrapper<T>` matches `Wrapper`.
+fn names_match(name: &str, wanted: &str) -> bool {
+    if name == wanted {
+        return true;
+    }
+    let target = name.rsplit(" for ").next().unwrap_or(name).trim();
+    // Drop impl generics: `<T> Wrapper<T>` → `Wrapper<T>`
+    let target = match target.strip_prefix('<') {
+        Some(rest) => rest.split_once("> ").map(|(_, t)| t).unwrap_or(target),
+        None => target,
+    };
+    target.split('<').next().unwrap_or("").trim() == wanted
+}
+
+/// Walk up over doc comments, attributes and decorators directly above `idx`.
+fn leading_comments_start(lines: &[&str], idx: usize) -> usize {
+    let mut start = idx;
+    while start > 0 {
+        let prev = lines[start - 1].trim_start();
+        let is_prefix = ["///", "//", "#[", "@", "/**", "*", "#"]
+            .iter()
+            .any(|p| prev.starts_with(p))
+            && !prev.starts_with("//!")
+            && !prev.starts_with("#!");
+        if !is_prefix {
+            break;
+        }
+        start -= 1;
+    }
+    start
+}
+
+/// Last line of a `{ ... }` block (or `;`-terminated item) starting at `idx`.
+fn braced_block_end(lines: &[&str], idx: usize, language: Language) -> usize {
+    let mut depth = 0usize;
+    let mut parens = 0usize;
+    let mut opened = false;
+    for (i, line) in lines.iter().enumerate().skip(idx) {
+        let mut chars = line.chars().peekable();
+        let mut quote: Option<char> = None;
+        while let Some(c) = chars.next() {
+            if let Some(q) = quote {
+                if c == '\\' {
+                    chars.next();
+                } else if c == q {
+                    quote = None;
+                }
+                continue;
+            }
+            match c {
+                '"' | '`' => quote = Some(c),
+                '\'' if language == Language::JavaScript => quote = Some(c),
+                '/' if chars.peek() == Some(&'/') => break,
+                '(' | '[' => parens += 1,
+                ')' | ']' => parens = parens.saturating_sub(1),
+                '{' => {
+                    depth += 1;
+                    opened = true;
+                }
+                '}' => {
+                    depth = depth.saturating_sub(1);
+                    if opened && depth == 0 {
+                        return i;
+                    }
+                }
+                ';' if !opened && parens == 0 => return i,
+                _ => {}
+            }
+        }
+        // JavaScript statements may omit the semicolon
+        if language == Language::JavaScript && !opened && parens == 0 {
+            let end = line.trim_end();
+            let continues = ["=", ",", "(", "=>", "+", "-", "&&", "||", "?", ":"]
+                .iter()
+                .any(|op| end.ends_with(op));
+            if !continues {
+                return i;
+            }
+        }
+    }
+    lines.len().saturating_sub(1)
+}
+
+/// Last line of an indentation-delimited Python block starting at `idx`.
+fn indented_block_end(lines: &[&str], idx: usize) -> usize {
+    let base = indent_width(lines[idx]);
+    let mut parens: i32 = 0;
+    let mut end = idx;
+    for (i, line) in lines.iter().enumerate().skip(idx) {
+        if i > idx && parens == 0 && !line.trim().is_empty() && indent_width(line) <= base {
+            break;
+        }
+        parens += line.matches(['(', '[', '{']).count() as i32;
+        parens -= line.matches([')', ']', '}']).count() as i32;
+        parens = parens.max(0);
+        if !line.trim().is_empty() {
+            end = i;
+        }
+    }
+    end
+}
+
+/// Last line of a Markdown section: up to the next heading of the same or a
+/// higher level, without trailing blank lines.
+fn heading_end(lines: &[&str], idx: usize, later: &[OutlineItem], depth: usize) -> usize {
+    let next = later
+        .iter()
+        .find(|h| h.depth <= depth)
+        .map(|h| h.line - 1)
+        .unwrap_or(lines.len());
+    let mut end = next.saturating_sub(1).max(idx);
+    while end > idx && lines[end].trim().is_empty() {
+        end -= 1;
+    }
+    end
+}
+
+/// Leading whitespace width, counting tabs as 4 columns.
+fn indent_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// Take the leading identifier of `s`.
+fn ident(s: &str) -> Option<String> {
+    let name: String = s
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+        .collect();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Strip any of the given prefixes (each followed by whitespace), repeatedly.
+fn strip_modifiers<'a>(mut s: &'a str, modifiers: &[&str]) -> &'a str {
+    loop {
+        let before = s;
+        for m in modifiers {
+            if let Some(rest) = s.strip_prefix(m) {
+                if rest.starts_with(char::is_whitespace) {
+                    s = rest.trim_start();
+                }
+            }
+        }
+        if s == before {
+            return s;
+        }
+    }
+}
+
+fn parse_rust(line: &str) -> Option<(&'static str, String)> {
+    if line.starts_with("//") || line.starts_with("#[") {
+        return None;
+    }
+    let mut s = line;
+    if let Some(rest) = s.strip_prefix("pub(") {
+        s = rest.split_once(')').map(|(_, r)| r.trim_start())?;
+    }
+    let mut s = strip_modifiers(s, &["pub", "async", "unsafe", "default", "extern \"C\""]);
+    // `const fn` is a function; a bare `const` is an item of its own
+    if let Some(rest) = s.strip_prefix("const ") {
+        let rest = strip_modifiers(rest.trim_start(), &["async", "unsafe"]);
+        if rest.starts_with("fn ") {
+            s = rest;
+        }
+    }
+
+    if let Some(rest) = s.strip_prefix("macro_rules!") {
+        return ident(rest).map(|n| ("macro", n));
+    }
+    if let Some(rest) = s.strip_prefix("impl") {
+        if rest.starts_with(|c: char| c.is_whitespace() || c == '<') {
+            let target = rest.split('{').next().unwrap_or("").trim();
+            let target = target
+                .split(" where")
+                .next()
+                .unwrap_or(target)
+                .trim()
+                .to_string();
+            return Some(("impl", target));
+        }
+    }
+
+    for kind in [
+        "fn", "struct", "enum", "trait", "mod", "type", "union", "static", "const",
+    ] {
+        if let Some(rest) = s.strip_prefix(kind) {
+            if rest.starts_with(char::is_whitespace) {
+                return ident(rest).map(|n| (kind, n));
+            }
+        }
+    }
+    None
+}
+
+fn parse_python(line: &str) -> Option<(&'static str, String)> {
+    let s = strip_modifiers(line, &["async"]);
+    for kind in ["def", "class"] {
+        if let Some(rest) = s.strip_prefix(kind) {
+            if rest.starts_with(char::is_whitespace) {
+                return ident(rest).map(|n| (kind, n));
+            }
+        }
+    }
+    None
+}
+
+fn parse_javascript(line: &str, depth: usize) -> Option<(&'static str, String)> {
+    if line.starts_with("//") || line.starts_with('*') || line.starts_with("/*") {
+        return None;
+    }
+    let s = strip_modifiers(line, &["export", "default", "declare", "abstract", "async"]);
+
+    if let Some(rest) = s.strip_prefix("function") {
+        let rest = rest.trim_start_matches('*');
+        if rest.starts_with(|c: char| c.is_whitespace() || c == '(') {
+            return ident(rest).map(|n| ("function", n));
+        }
+    }
+    for kind in ["class", "interface", "enum", "namespace", "type"] {
+        if let Some(rest) = s.strip_prefix(kind) {
+            if rest.starts_with(char::is_whitespace) {
+                return ident(rest).map(|n| (kind, n));
+            }
+        }
+    }
+    // Top-level bindings only — nested consts are locals, not structure
+    if depth == 0 {
+        for kind in ["const", "let", "var"] {
+            if let Some(rest) = s.strip_prefix(kind) {
+                if rest.starts_with(char::is_whitespace) {
+                    return ident(rest).map(|n| (kind, n));
+                }
+            }
+        }
+    }
+    None
+}
+
+fn parse_go(line: &str) -> Option<(&'static str, String)> {
+    if let Some(rest) = line.strip_prefix("func") {
+        if !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let rest = rest.trim_start();
+        // Method: func (r *Recv) Name(...)
+        let rest = if rest.starts_with('(') {
+            rest.split_once(')').map(|(_, r)| r)?
+        } else {
+            rest
+        };
+        return ident(rest).map(|n| ("func", n));
+    }
+    if let Some(rest) = line.strip_prefix("type") {
+        if rest.starts_with(char::is_whitespace) {
+            return ident(rest).map(|n| ("type", n));
+        }
+    }
+    None
+}
+
+fn parse_markdown(line: &str) -> Option<(&'static str, String, usize)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let title = line[level..].trim();
+    if title.is_empty() || !line[level..].starts_with(' ') {
+        return None;
+    }
+    Some(("heading", title.to_string(), level - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(items: &[OutlineItem]) -> Vec<(&str, &str, usize)> {
+        items
+            .iter()
+            .map(|i| (i.kind.as_str(), i.name.as_str(), i.depth))
+            .collect()
+    }
+
+    #[test]
+    fn block_end_per_language() {
+        let rust = "fn a(\n    x: u32,\n) -> u32 {\n    if x > 0 { 1 } else { 0 }\n}\nfn b() {}\n";
+        let lines: Vec<&str> = rust.lines().collect();
+        assert_eq!(block_end(&lines, 0, Some(Language::Rust)), 4);
+        assert_eq!(block_end(&lines, 5, Some(Language::Rust)), 5);
+
+        let python = "def a():\n    x = 1\n\n    return x\n\ndef b():\n    pass\n";
+        let lines: Vec<&str> = python.lines().collect();
+        assert_eq!(block_end(&lines, 0, Some(Language::Python)), 3);
+
+        let markdown = "## One\ntext\n### Sub\nmore\n## Two\n";
+        let lines: Vec<&str> = markdown.lines().collect();
+        assert_eq!(block_end(&lines, 0, Some(Language::Markdown)), 3);
+
+        let unknown = "void f() {\n  g();\n}\n";
+        let lines: Vec<&str> = unknown.lines().collect();
+        assert_eq!(block_end(&lines, 0, None), 2);
+    }
+
+    #[test]
+    fn detect_language() {
+        assert_eq!(Language::from_path("src/main.rs"), Some(Language::Rust));
+        assert_eq!(Language::from_path("app.tsx"), Some(Language::JavaScript));
+        assert_eq!(Language::from_path("README.md"), Some(Language::Markdown));
+        assert_eq!(Language::from_path("Makefile"), None);
+    }
+
+    #[test]
+    fn rust_outline() {
+        let src = "use std::fmt;\n\npub struct Config {\n    name: String,\n}\n\nimpl Config {\n    pub const fn new() -> Self {\n        todo!()\n    }\n}\n\npub(crate) async fn run() {}\nconst MAX: usize = 1;\n";
+        let items = extract_outline(src, Language::Rust);
+        assert_eq!(
+            names(&items),
+            vec![
+                ("struct", "Config", 0),
+                ("impl", "Config", 0),
+                ("fn", "new", 1),
+                ("fn", "run", 0),
+                ("const", "MAX", 0),
+            ]
+        );
+        assert_eq!(items[0].line, 3);
+        assert_eq!(items[2].line, 8);
+    }
+
+    #[test]
+    fn rust_trait_impl_target() {
+        let items = extract_outline(
+            "impl<T> Display for Wrapper<T> where T: Debug {",
+            Language::Rust,
+        );
+        assert_eq!(items[0].name, "<T> Display for Wrapper<T>");
+    }
+
+    #[test]
+    fn python_outline() {
+        let src = "import os\n\nclass Server:\n    def start(self):\n        pass\n\nasync def main():\n    pass\n";
+        let items = extract_outline(src, Language::Python);
+        assert_eq!(
+            names(&items),
+            vec![
+                ("class", "Server", 0),
+                ("def", "start", 1),
+                ("def", "main", 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn javascript_outline() {
+        let src = "export default class App {}\nexport async function load() {}\nconst API_URL = 'x';\ninterface Props {}\n";
+        let items = extract_outline(src, Language::JavaScript);
+        assert_eq!(
+            names(&items),
+            vec![
+                ("class", "App", 0),
+                ("function", "load", 0),
+                ("const", "API_URL", 0),
+                ("interface", "Props", 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn go_outline() {
+        let src = "package main\n\ntype Server struct {}\n\nfunc (s *Server) Start() {}\n\nfunc main() {\n    functions := load()\n    typed := functions\n}\n";
+        let items = extract_outline(src, Language::Go);
+        assert_eq!(
+            names(&items),
+            vec![
+                ("type", "Server", 0),
+                ("func", "Start", 0),
+                ("func", "main", 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn rust_symbol_spans() {
+        let src = "use std::fmt;\n\n/// A config.\n#[derive(Debug)]\npub struct Config {\n    name: String,\n}\n\nimpl Config {\n    pub fn new(\n        name: &str,\n    ) -> Self {\n        let s = \"}\";\n        Self { name: s.into() }\n    }\n}\n\nconst MAX: usize = 1;\n";
+        let config = find_symbol(src, Language::Rust, "Config");
+        assert_eq!((config[0].start, config[0].end, config[0].line), (3, 7, 5));
+        assert_eq!((config[1].start, config[1].end), (9, 16));
+
+        let new = find_symbol(src, Language::Rust, "Config::new");
+        assert_eq!(new.len(), 1);
+        assert_eq!((new[0].start, new[0].end), (10, 15));
+        assert!(find_symbol(src, Language::Rust, "Other::new").is_empty());
+
+        let max = find_symbol(src, Language::Rust, "MAX");
+        assert_eq!((max[0].start, max[0].end), (18, 18));
+    }
+
+    #[test]
+    fn python_and_markdown_symbol_spans() {
+        let src = "class Server:\n    @property\n    def port(self):\n        return (\n    1)\n\n    def stop(self):\n        pass\n";
+        let port = find_symbol(src, Language::Python, "Server.port");
+        assert_eq!((port[0].start, port[0].end), (2, 5));
+        let server = find_symbol(src, Language::Python, "Server");
+        assert_eq!((server[0].start, server[0].end), (1, 8));
+
+        let md = "# Title\n\n## Install\n\nrun it\n\n### Linux\n\napt\n\n## Usage\n";
+        let install = find_symbol(md, Language::Markdown, "Install");
+        assert_eq!((install[0].start, install[0].end), (3, 9));
+    }
+
+    #[test]
+    fn markdown_headings() {
+        let src = "# Title\n\ntext\n\n## Install\n#not-a-heading\n";
+        let items = extract_outline(src, Language::Markdown);
+        assert_eq!(
+            names(&items),
+            vec![("heading", "Title", 0), ("heading", "Install", 1)]
+        );
+    }
+}
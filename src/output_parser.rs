@@ -0,0 +1,259 @@
+//! Post-processing for structured command output.
+//!
+//! `curl` responses and `--format json` flags produce one enormous line of
+//! JSON, which tail truncation cuts into an unreadable fragment; tab- and
+//! comma-separated output is hard to scan without aligned columns.
+//! [`parse_output`] recognizes both and re-renders them: JSON is
+//! pretty-printed when that fits the line and byte budget and compacted
+//! otherwise, and delimited tables are padded into columns. JSON is
+//! re-indented token by token, so key order and number formatting survive.
+//!
+//! ```rust
+//! use soul_coder::output_parser::{parse_output, OutputParser};
+//!
+//! let (json, format) = parse_output("{\"ok\": true}", OutputParser::Auto, 50, 1024).unwrap();
+//! assert_eq!(format, "json");
+//! assert_eq!(json, "{\n  \"ok\": true\n}");
+//!
+//! let (table, _) = parse_output("id\tname\n7\tada\n", OutputParser::Auto, 50, 1024).unwrap();
+//! assert_eq!(table, "id  name\n7   ada");
+//! ```
+
+/// Cells wider than this don't widen their column any further.
+const MAX_COLUMN_WIDTH: usize = 40;
+
+/// Which structured formats to look for in output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputParser {
+    /// Leave output as-is.
+    #[default]
+    None,
+    /// Try JSON, then a tab- or comma-separated table.
+    Auto,
+    Json,
+    /// Tab- or comma-separated rows.
+    Table,
+}
+
+impl OutputParser {
+    /// Parse a parser name (`none`, `auto`, `json`, `table`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" => Some(Self::None),
+            "auto" => Some(Self::Auto),
+            "json" => Some(Self::Json),
+            "table" | "tsv" | "csv" => Some(Self::Table),
+            _ => None,
+        }
+    }
+}
+
+/// Re-render `text` if it is in a format `parser` accepts, returning the new
+/// text and the format name (`"json"` or `"table"`). `None` means the output
+/// is left as it was.
+pub fn parse_output(
+    text: &str,
+    parser: OutputParser,
+    max_lines: usize,
+    max_bytes: usize,
+) -> Option<(String, &'static str)> {
+    let json = || format_json(text, max_lines, max_bytes).map(|s| (s, "json"));
+    let table = || format_table(text).map(|s| (s, "table"));
+    match parser {
+        OutputParser::None => None,
+        OutputParser::Auto => json().or_else(table),
+        OutputParser::Json => json(),
+        OutputParser::Table => table(),
+    }
+}
+
+/// Pretty-print a JSON object or array, falling back to the compact form
+/// when the pretty one exceeds `max_lines` or `max_bytes`.
+pub fn format_json(text: &str, max_lines: usize, max_bytes: usize) -> Option<String> {
+    let text = text.trim();
+    if !(text.starts_with('{') || text.starts_with('[')) {
+        return None;
+    }
+    serde_json::from_str::<serde::de::IgnoredAny>(text).ok()?;
+    let pretty = reindent(text, true);
+    if pretty.len() <= max_bytes && pretty.lines().count() <= max_lines {
+        Some(pretty)
+    } else {
+        Some(reindent(text, false))
+    }
+}
+
+/// Re-emit valid JSON with two-space indentation, or with no whitespace at
+/// all when `pretty` is false.
+fn reindent(json: &str, pretty: bool) -> String {
+    let mut out = String::with_capacity(json.len() + json.len() / 4);
+    let mut depth = 0;
+    let mut chars = json.chars().peekable();
+    let newline = |out: &mut String, depth: usize| {
+        if pretty {
+            out.push('\n');
+            out.push_str(&"  ".repeat(depth));
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                out.push(c);
+                while let Some(c) = chars.next() {
+                    out.push(c);
+                    match c {
+                        '\\' => out.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '{' | '[' => {
+                out.push(c);
+                while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                    chars.next();
+                }
+                if matches!(chars.peek(), Some('}' | ']')) {
+                    out.extend(chars.next()); // Empty container stays on one line
+                } else {
+                    depth += 1;
+                    newline(&mut out, depth);
+                }
+            }
+            '}' | ']' => {
+                depth -= 1;
+                newline(&mut out, depth);
+                out.push(c);
+            }
+            ',' => {
+                out.push(c);
+                newline(&mut out, depth);
+            }
+            ':' => out.push_str(if pretty { ": " } else { ":" }),
+            c if c.is_whitespace() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Align tab-separated rows, or comma-separated rows with CSV quoting, into
+/// padded columns. Every row must have the same number (two or more) of
+/// fields, and there must be at least two rows.
+pub fn format_table(text: &str) -> Option<String> {
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    if lines.len() < 2 {
+        return None;
+    }
+    let rows: Vec<Vec<String>> = if lines.iter().all(|l| l.contains('\t')) {
+        lines
+            .iter()
+            .map(|l| l.split('\t').map(|f| f.trim().to_string()).collect())
+            .collect()
+    } else {
+        lines.iter().map(|l| split_csv(l)).collect::<Option<_>>()?
+    };
+    let columns = rows[0].len();
+    if columns < 2 || rows.iter().any(|r| r.len() != columns) {
+        return None;
+    }
+
+    let widths: Vec<usize> = (0..columns)
+        .map(|c| {
+            rows.iter()
+                .map(|r| r[c].chars().count().min(MAX_COLUMN_WIDTH))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let rendered: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let mut line = String::new();
+            for (cell, width) in row.iter().zip(&widths) {
+                line.push_str(cell);
+                let len = cell.chars().count();
+                line.push_str(&" ".repeat(width.saturating_sub(len) + 2));
+            }
+            line.trim_end().to_string()
+        })
+        .collect();
+    Some(rendered.join("\n"))
+}
+
+/// Split one CSV record; `None` on an unterminated quote.
+fn split_csv(line: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted || field.trim().is_empty() => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    fields.push(field.trim().to_string());
+    Some(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_pretty_then_compact() {
+        let raw = r#"{"b": [1, 2.50, {}], "a": {"s": "x, \"y\": {z}"}, "e": []}"#;
+        let pretty = format_json(raw, 50, 1024).unwrap();
+        assert_eq!(
+            pretty,
+            concat!(
+                "{\n  \"b\": [\n    1,\n    2.50,\n    {}\n  ],\n",
+                "  \"a\": {\n    \"s\": \"x, \\\"y\\\": {z}\"\n  },\n",
+                "  \"e\": []\n}"
+            )
+        );
+        // Over the line budget: compact, order and formatting intact
+        assert_eq!(
+            format_json(raw, 5, 1024).unwrap(),
+            r#"{"b":[1,2.50,{}],"a":{"s":"x, \"y\": {z}"},"e":[]}"#
+        );
+        assert_eq!(format_json("{\"a\": ", 50, 1024), None);
+        assert_eq!(format_json("42", 50, 1024), None);
+    }
+
+    #[test]
+    fn tables_align() {
+        let csv = "name,size,\"note, quoted\"\nlib.rs,120,\"says \"\"hi\"\"\"\nmain.rs,8,\n";
+        assert_eq!(
+            format_table(csv).unwrap(),
+            "name     size  note, quoted\nlib.rs   120   says \"hi\"\nmain.rs  8"
+        );
+        // Ragged rows and prose are left alone
+        assert_eq!(format_table("a,b\nc\n"), None);
+        assert_eq!(format_table("just one line, really"), None);
+        assert_eq!(format_table("Hello, world.\nBye now.\n"), None);
+    }
+
+    #[test]
+    fn parser_selection() {
+        let json = "[1, 2]";
+        assert_eq!(parse_output(json, OutputParser::None, 50, 1024), None);
+        assert_eq!(parse_output(json, OutputParser::Table, 50, 1024), None);
+        assert_eq!(
+            parse_output(json, OutputParser::Json, 50, 1024),
+            Some(("[\n  1,\n  2\n]".to_string(), "json"))
+        );
+        assert_eq!(OutputParser::from_name("TSV"), Some(OutputParser::Table));
+        assert_eq!(OutputParser::from_name("yaml"), None);
+    }
+}
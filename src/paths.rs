@@ -0,0 +1,276 @@
+//! Canonical path handling for VFS paths.
+//!
+//! VFS paths are logical, `/`-separated strings. Everything a tool receives
+//! from a model goes through [`resolve`] first, so `.`/`..` segments,
+//! duplicate and trailing slashes (and, on Windows hosts, `\` separators) are
+//! folded into one spelling before the path reaches the filesystem, the audit
+//! log or an index. [`display`] is the inverse used in tool output.
+//!
+//! Windows paths are understood on every host: a drive prefix (`C:\repo`,
+//! `c:/repo`) makes a path absolute, is spelt `C:/repo` from then on, and
+//! `..` never climbs above `C:/`. A rooted path without a drive (`\src`)
+//! takes the drive of the directory it is resolved against. Bare `\` is
+//! only a separator on Windows hosts, since elsewhere it can be part of a
+//! file name; [`glob_pattern`] does the same for glob patterns.
+//!
+//! ```rust
+//! use soul_coder::paths;
+//!
+//! assert_eq!(paths::resolve("/project", "./src//lib.rs"), "/project/src/lib.rs");
+//! assert_eq!(paths::resolve("/project", "../etc/passwd"), "/etc/passwd");
+//! assert_eq!(paths::display("/project/src/lib.rs", "/project"), "src/lib.rs");
+//! assert_eq!(paths::display("/etc/passwd", "/project"), "/etc/passwd");
+//! assert_eq!(paths::resolve("C:\\repo", "src\\lib.rs"), "C:/repo/src/lib.rs");
+//! assert_eq!(paths::display("c:\\repo\\src\\lib.rs", "C:/repo"), "src/lib.rs");
+//! ```
+
+/// Fold `.`/`..` segments and duplicate or trailing slashes.
+///
+/// Absolute paths stay absolute and `..` never climbs above the root;
+/// relative paths keep leading `..` segments they can't cancel.
+pub fn normalize(path: &str) -> String {
+    let path = native_separators(path);
+    let (drive, path) = match drive_letter(&path) {
+        Some(letter) => (Some(letter.to_ascii_uppercase()), &path[2..]),
+        None => (None, &path[..]),
+    };
+    let absolute = drive.is_some() || path.starts_with('/');
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => match parts.last() {
+                Some(&last) if last != ".." => {
+                    parts.pop();
+                }
+                _ if absolute => {}
+                _ => parts.push(".."),
+            },
+            p => parts.push(p),
+        }
+    }
+    match (drive, absolute, parts.is_empty()) {
+        (Some(drive), _, _) => format!("{}:/{}", drive, parts.join("/")),
+        (None, true, _) => format!("/{}", parts.join("/")),
+        (None, false, true) => ".".to_string(),
+        (None, false, false) => parts.join("/"),
+    }
+}
+
+/// Resolve `path` against `cwd` and normalize the result.
+pub fn resolve(cwd: &str, path: &str) -> String {
+    let path = native_separators(path);
+    if drive_letter(&path).is_some() {
+        return normalize(&path);
+    }
+    if path.starts_with('/') {
+        // Rooted on the drive of `cwd`, if it has one
+        let cwd = native_separators(cwd);
+        return match drive_letter(&cwd) {
+            Some(letter) => normalize(&format!("{}:{}", letter, path)),
+            None => normalize(&path),
+        };
+    }
+    normalize(&format!("{}/{}", cwd, path))
+}
+
+/// Whether `path` is absolute: rooted at `/` or starting with a drive.
+pub fn is_absolute(path: &str) -> bool {
+    let path = native_separators(path);
+    path.starts_with('/') || drive_letter(&path).is_some()
+}
+
+/// `path` relative to `root`, or `None` when it lies outside it.
+/// `root` itself is `"."`.
+pub fn relative_to(path: &str, root: &str) -> Option<String> {
+    let path = normalize(path);
+    let root = normalize(root);
+    if path == root {
+        return Some(".".to_string());
+    }
+    if root.ends_with('/') {
+        // `/` or a drive root such as `C:/`
+        return path.strip_prefix(&root).map(str::to_string);
+    }
+    path.strip_prefix(&root)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .map(str::to_string)
+}
+
+/// Whether `path` is `root` or lies beneath it.
+pub fn is_within(path: &str, root: &str) -> bool {
+    relative_to(path, root).is_some()
+}
+
+/// How a path is shown in tool output: relative to `cwd` when inside it,
+/// otherwise absolute.
+pub fn display(path: &str, cwd: &str) -> String {
+    relative_to(path, cwd).unwrap_or_else(|| normalize(path))
+}
+
+/// `path` relative to `root`, climbing out with `..` segments when it lies
+/// elsewhere. Both are expected to be absolute; `root` itself is `"."`, and
+/// a path on another drive stays absolute.
+pub fn relative_path(path: &str, root: &str) -> String {
+    let path = normalize(path);
+    let root = normalize(root);
+    if drive_letter(&path) != drive_letter(&root) {
+        return path;
+    }
+    let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let root: Vec<&str> = root.split('/').filter(|s| !s.is_empty()).collect();
+    let common = path.iter().zip(&root).take_while(|(a, b)| a == b).count();
+    let mut parts = vec![".."; root.len() - common];
+    parts.extend(&path[common..]);
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+/// Natural ("human") ordering: runs of digits compare by numeric value, so
+/// `file2.rs` sorts before `file10.rs`. Ties (e.g. `a01` vs `a1`) fall back
+/// to byte order, so the ordering is total and independent of the backend.
+pub fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    let (mut x, mut y) = (a, b);
+    loop {
+        match (x.chars().next(), y.chars().next()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(c), Some(d)) if c.is_ascii_digit() && d.is_ascii_digit() => {
+                let x_len = x.find(|c: char| !c.is_ascii_digit()).unwrap_or(x.len());
+                let y_len = y.find(|c: char| !c.is_ascii_digit()).unwrap_or(y.len());
+                let x_num = x[..x_len].trim_start_matches('0');
+                let y_num = y[..y_len].trim_start_matches('0');
+                let ord = x_num.len().cmp(&y_num.len()).then_with(|| x_num.cmp(y_num));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+                x = &x[x_len..];
+                y = &y[y_len..];
+            }
+            (Some(c), Some(d)) => {
+                if c != d {
+                    return c.cmp(&d);
+                }
+                x = &x[c.len_utf8()..];
+                y = &y[d.len_utf8()..];
+            }
+        }
+    }
+}
+
+/// A glob pattern with `\` separators folded into `/` on Windows hosts, so
+/// `src\**\*.rs` matches the normalized paths globs are tested against.
+pub fn glob_pattern(pattern: &str) -> std::borrow::Cow<'_, str> {
+    if cfg!(windows) && pattern.contains('\\') {
+        pattern.replace('\\', "/").into()
+    } else {
+        pattern.into()
+    }
+}
+
+/// On Windows hosts the native VFS accepts `\` separators; fold them into
+/// `/`. Paths with a drive prefix are folded on any host.
+fn native_separators(path: &str) -> std::borrow::Cow<'_, str> {
+    if (cfg!(windows) || drive_letter(path).is_some()) && path.contains('\\') {
+        path.replace('\\', "/").into()
+    } else {
+        path.into()
+    }
+}
+
+/// The drive letter of a path starting `C:`, `C:/` or `C:\\`.
+fn drive_letter(path: &str) -> Option<char> {
+    let mut chars = path.chars();
+    let letter = chars.next().filter(char::is_ascii_alphabetic)?;
+    let rest = chars.as_str().strip_prefix(':')?;
+    (rest.is_empty() || rest.starts_with(['/', '\\'])).then_some(letter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_segments() {
+        assert_eq!(normalize("/a//b/./c/"), "/a/b/c");
+        assert_eq!(normalize("/a/b/../../../c"), "/c");
+        assert_eq!(normalize("/"), "/");
+        assert_eq!(normalize("a/./b/.."), "a");
+        assert_eq!(normalize("../../a"), "../../a");
+        assert_eq!(normalize("a/.."), ".");
+    }
+
+    #[test]
+    fn resolve_against_cwd() {
+        assert_eq!(resolve("/project/", "src/"), "/project/src");
+        assert_eq!(resolve("/project", "."), "/project");
+        assert_eq!(resolve("/project", "../other/x"), "/other/x");
+        assert_eq!(resolve("/project", "/abs//x"), "/abs/x");
+    }
+
+    #[test]
+    fn relative_paths() {
+        assert_eq!(
+            relative_to("/project/src/a.rs", "/project/").as_deref(),
+            Some("src/a.rs")
+        );
+        assert_eq!(relative_to("/project", "/project").as_deref(), Some("."));
+        assert_eq!(relative_to("/projects/a", "/project"), None);
+        assert_eq!(relative_to("/a/b", "/").as_deref(), Some("a/b"));
+        assert!(!is_within("/project/../etc", "/project"));
+        assert_eq!(display("/project/../etc/passwd", "/project"), "/etc/passwd");
+        assert_eq!(relative_path("/project/src/a.rs", "/project"), "src/a.rs");
+        assert_eq!(relative_path("/other/x", "/project/src"), "../../other/x");
+        assert_eq!(relative_path("/project", "/project/src"), "..");
+        assert_eq!(relative_path("/project/", "/project"), ".");
+    }
+
+    #[test]
+    fn windows_paths() {
+        assert_eq!(normalize("c:\\repo\\src\\..\\lib.rs"), "C:/repo/lib.rs");
+        assert_eq!(normalize("C:/../.."), "C:/");
+        assert_eq!(normalize("C:"), "C:/");
+        assert_eq!(resolve("C:/repo", "src\\main.rs"), "C:/repo/src/main.rs");
+        assert_eq!(resolve("C:/repo", "D:\\other"), "D:/other");
+        assert_eq!(resolve("C:\\repo", "/tmp/x"), "C:/tmp/x");
+        assert!(is_absolute("C:\\x") && is_absolute("/x") && !is_absolute("C:x"));
+        assert_eq!(display("C:\\repo\\src\\a.rs", "c:/repo"), "src/a.rs");
+        assert_eq!(relative_to("C:/a/b", "C:/").as_deref(), Some("a/b"));
+        assert!(!is_within("C:/repo", "D:/repo"));
+        assert_eq!(relative_path("C:/repo/a", "C:/repo/src"), "../a");
+        assert_eq!(relative_path("D:/x", "C:/repo"), "D:/x");
+    }
+
+    #[test]
+    fn natural_ordering() {
+        let mut names = vec![
+            "file10.rs",
+            "file2.rs",
+            "file1.rs",
+            "file01.rs",
+            "a/b",
+            "file2a.rs",
+            "file.rs",
+        ];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            names,
+            [
+                "a/b",
+                "file.rs",
+                "file01.rs",
+                "file1.rs",
+                "file2.rs",
+                "file2a.rs",
+                "file10.rs"
+            ]
+        );
+        assert_eq!(natural_cmp("v1.10", "v1.9"), std::cmp::Ordering::Greater);
+    }
+}
@@ -1,21 +1,31 @@
 //! Preset tool collections for common use cases.
 //!
 //! Two integration modes:
-//! - **ToolRegistry** (simple): `coding_tools()`, `read_only_tools()`, `all_tools()`
-//! - **ExecutorRegistry** (config-driven): `coding_executor()`, `all_executor()`
+//! - **ToolRegistry** (simple): `coding_tools()`, `read_only_tools()`,
+//!   `read_only_enforced_tools()`, `all_tools()`, and the role kits
+//!   `review_tools()`, `test_tools()`, `docs_tools()`
+//! - **ExecutorRegistry** (config-driven): `coding_executor()`, `all_executor()`,
+//!   `review_executor()`, `test_executor()`, `docs_executor()`
+//!   ([`wrap_as_configured_executor`] to apply per-tool `executor_config`)
+//!
+//! For any other combination, or per-tool options, use [`Toolset`].
+//! [`compat_aliases`] adds the tool names other harnesses use.
+//! [`system_prompt_snippet`] describes how to use whatever a registry holds.
 
+use std::collections::HashSet;
 use std::sync::Arc;
 
+use soul_core::error::SoulResult;
 use soul_core::executor::direct::DirectExecutor;
 use soul_core::executor::{ConfigTool, ExecutorRegistry, ToolExecutor};
 use soul_core::tool::ToolRegistry;
-use soul_core::vexec::VirtualExecutor;
+use soul_core::vexec::{NoopExecutor, VirtualExecutor};
 use soul_core::vfs::VirtualFs;
 
-use crate::tools::{
-    append::AppendTool, bash::BashTool, edit::EditTool, find::FindTool, grep::GrepTool,
-    ls::LsTool, read::ReadTool, write::WriteTool,
-};
+use crate::aliases::{alias_registry, ToolAlias};
+use crate::tool_config::ConfiguredExecutor;
+use crate::tools::usage_guide;
+use crate::toolset::Toolset;
 
 /// Create coding tools: read, write, edit, bash.
 /// Full modification access for interactive coding sessions.
@@ -24,13 +34,7 @@ pub fn coding_tools(
     executor: Arc<dyn VirtualExecutor>,
     cwd: impl Into<String>,
 ) -> ToolRegistry {
-    let cwd = cwd.into();
-    let mut registry = ToolRegistry::new();
-    registry.register(Box::new(ReadTool::new(fs.clone(), &cwd)));
-    registry.register(Box::new(WriteTool::new(fs.clone(), &cwd)));
-    registry.register(Box::new(EditTool::new(fs, &cwd)));
-    registry.register(Box::new(BashTool::new(executor, &cwd)));
-    registry
+    Toolset::new(fs, executor, cwd).with_coding().build_registry()
 }
 
 /// Create read-only tools: read, grep, find, ls.
@@ -39,33 +43,158 @@ pub fn read_only_tools(
     fs: Arc<dyn VirtualFs>,
     cwd: impl Into<String>,
 ) -> ToolRegistry {
-    let cwd = cwd.into();
-    let mut registry = ToolRegistry::new();
-    registry.register(Box::new(ReadTool::new(fs.clone(), &cwd)));
-    registry.register(Box::new(GrepTool::new(fs.clone(), &cwd)));
-    registry.register(Box::new(FindTool::new(fs.clone(), &cwd)));
-    registry.register(Box::new(LsTool::new(fs, &cwd)));
-    registry
+    Toolset::new(fs, Arc::new(NoopExecutor), cwd)
+        .with_read_only()
+        .build_registry()
+}
+
+/// Create read-only tools plus bash, with read-only enforced:
+/// read, grep, find, ls, bash.
+/// The VFS refuses writes and bash only runs commands that can't modify
+/// files (see [`crate::read_only`]).
+pub fn read_only_enforced_tools(
+    fs: Arc<dyn VirtualFs>,
+    executor: Arc<dyn VirtualExecutor>,
+    cwd: impl Into<String>,
+) -> ToolRegistry {
+    Toolset::new(fs, executor, cwd)
+        .enforce_read_only()
+        .with_read_only()
+        .with_bash()
+        .build_registry()
+}
+
+/// Create code review tools: read, grep, find, ls, outline, bash.
+/// Read-only is enforced as in [`read_only_enforced_tools`]; bash is there
+/// for `git diff`, `git log` and `diff`, which the read-only policy allows.
+pub fn review_tools(
+    fs: Arc<dyn VirtualFs>,
+    executor: Arc<dyn VirtualExecutor>,
+    cwd: impl Into<String>,
+) -> ToolRegistry {
+    Toolset::new(fs, executor, cwd)
+        .enforce_read_only()
+        .with_read_only()
+        .with_outline()
+        .with_bash()
+        .build_registry()
+}
+
+/// Create test runner tools: read, grep, bash.
+/// For an agent that runs the test suite and reports on failures.
+pub fn test_tools(
+    fs: Arc<dyn VirtualFs>,
+    executor: Arc<dyn VirtualExecutor>,
+    cwd: impl Into<String>,
+) -> ToolRegistry {
+    Toolset::new(fs, executor, cwd)
+        .with_read()
+        .with_grep()
+        .with_bash()
+        .build_registry()
 }
 
-/// Create all tools: read, write, append, edit, bash, grep, find, ls.
+/// Create documentation tools: read, write, edit, grep.
+/// For an agent that writes and updates docs without running commands.
+/// Edit is there for small fixes that a full rewrite would make noisy.
+/// There is no fetch tool in this crate, so a host that wants the agent to
+/// pull in web pages adds its own with [`Toolset::with_tool`].
+pub fn docs_tools(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> ToolRegistry {
+    Toolset::new(fs, Arc::new(NoopExecutor), cwd)
+        .with_read()
+        .with_write()
+        .with_edit()
+        .with_grep()
+        .build_registry()
+}
+
+/// Create all tools: read, write, append, edit, bash, grep, find, ls, outline,
+/// todo, loc, scratch, scaffold, project_info, hash, write_many, dupes, stats, grep_replace.
 /// Complete toolkit for full agent capabilities.
+///
+/// Use [`Toolset`] directly to pick a different subset or configure
+/// individual tools.
 pub fn all_tools(
     fs: Arc<dyn VirtualFs>,
     executor: Arc<dyn VirtualExecutor>,
     cwd: impl Into<String>,
 ) -> ToolRegistry {
-    let cwd = cwd.into();
-    let mut registry = ToolRegistry::new();
-    registry.register(Box::new(ReadTool::new(fs.clone(), &cwd)));
-    registry.register(Box::new(WriteTool::new(fs.clone(), &cwd)));
-    registry.register(Box::new(AppendTool::new(fs.clone(), &cwd)));
-    registry.register(Box::new(EditTool::new(fs.clone(), &cwd)));
-    registry.register(Box::new(BashTool::new(executor, &cwd)));
-    registry.register(Box::new(GrepTool::new(fs.clone(), &cwd)));
-    registry.register(Box::new(FindTool::new(fs.clone(), &cwd)));
-    registry.register(Box::new(LsTool::new(fs, &cwd)));
-    registry
+    Toolset::new(fs, executor, cwd).with_all().build_registry()
+}
+
+/// Aliases for the tool names and argument shapes of other coding agents,
+/// for models trained on them:
+///
+/// | Alias | Tool | Arguments |
+/// |-------|------|-----------|
+/// | `str_replace_editor` | edit | `old_str`, `new_str` (the `str_replace` command) |
+/// | `read_file` | read | |
+/// | `write_to_file` | write | |
+/// | `list_files` | ls | |
+/// | `search_files` | grep | `regex`, `file_pattern` |
+/// | `search` | grep | `query` |
+/// | `execute_command`, `run_terminal_cmd` | bash | |
+///
+/// Options without an equivalent, such as `recursive` or
+/// `requires_approval`, are ignored.
+pub fn compat_aliases() -> Vec<ToolAlias> {
+    vec![
+        ToolAlias::new("str_replace_editor", "edit")
+            .with_argument("old_str", "old")
+            .with_argument("new_str", "new")
+            .with_ignored("command"),
+        ToolAlias::new("read_file", "read"),
+        ToolAlias::new("write_to_file", "write"),
+        ToolAlias::new("list_files", "ls").with_ignored("recursive"),
+        ToolAlias::new("search_files", "grep")
+            .with_argument("regex", "pattern")
+            .with_argument("file_pattern", "glob"),
+        ToolAlias::new("search", "grep").with_argument("query", "pattern"),
+        ToolAlias::new("execute_command", "bash").with_ignored("requires_approval"),
+        ToolAlias::new("run_terminal_cmd", "bash")
+            .with_ignored("is_background")
+            .with_ignored("explanation"),
+    ]
+}
+
+/// [`all_tools`] plus [`compat_aliases`].
+pub fn compat_tools(
+    fs: Arc<dyn VirtualFs>,
+    executor: Arc<dyn VirtualExecutor>,
+    cwd: impl Into<String>,
+) -> ToolRegistry {
+    alias_registry(all_tools(fs, executor, cwd), &compat_aliases())
+}
+
+/// Usage guidance for the tools in `registry`, as a Markdown section to
+/// include in a system prompt: when to use grep rather than find, that edit
+/// needs a unique match, reading before editing, and so on.
+///
+/// Tools are covered in registration order. Tools without a guide, such as
+/// ones registered under a different name, are left out; the result is
+/// empty if none have one.
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use soul_core::vfs::MemoryFs;
+/// use soul_core::vexec::NoopExecutor;
+/// use soul_coder::presets::{coding_tools, system_prompt_snippet};
+///
+/// let tools = coding_tools(Arc::new(MemoryFs::new()), Arc::new(NoopExecutor), "/workspace");
+/// let guide = system_prompt_snippet(&tools);
+/// assert!(guide.starts_with("## Tool usage"));
+/// assert!(guide.contains("- **edit**: Read the file before editing it."));
+/// ```
+pub fn system_prompt_snippet(registry: &ToolRegistry) -> String {
+    let entries: Vec<String> = registry
+        .all_names()
+        .into_iter()
+        .filter_map(|name| usage_guide(&name).map(|guide| format!("- **{}**: {}", name, guide)))
+        .collect();
+    if entries.is_empty() {
+        return String::new();
+    }
+    format!("## Tool usage\n\n{}\n", entries.join("\n"))
 }
 
 /// Create an [`ExecutorRegistry`] with all coding tools wired via [`DirectExecutor`].
@@ -105,6 +234,32 @@ pub fn coding_executor(
     wrap_as_executor(tools)
 }
 
+/// Create an [`ExecutorRegistry`] with the [`review_tools`] wired via
+/// [`DirectExecutor`].
+pub fn review_executor(
+    fs: Arc<dyn VirtualFs>,
+    executor: Arc<dyn VirtualExecutor>,
+    cwd: impl Into<String>,
+) -> ExecutorRegistry {
+    wrap_as_executor(review_tools(fs, executor, cwd))
+}
+
+/// Create an [`ExecutorRegistry`] with the [`test_tools`] wired via
+/// [`DirectExecutor`].
+pub fn test_executor(
+    fs: Arc<dyn VirtualFs>,
+    executor: Arc<dyn VirtualExecutor>,
+    cwd: impl Into<String>,
+) -> ExecutorRegistry {
+    wrap_as_executor(test_tools(fs, executor, cwd))
+}
+
+/// Create an [`ExecutorRegistry`] with the [`docs_tools`] wired via
+/// [`DirectExecutor`].
+pub fn docs_executor(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> ExecutorRegistry {
+    wrap_as_executor(docs_tools(fs, cwd))
+}
+
 /// Wrap any [`ToolRegistry`] into an [`ExecutorRegistry`] with [`DirectExecutor`]
 /// as the fallback, and all tool definitions registered as [`ConfigTool`] entries.
 pub fn wrap_as_executor(tools: ToolRegistry) -> ExecutorRegistry {
@@ -125,6 +280,45 @@ pub fn wrap_as_executor(tools: ToolRegistry) -> ExecutorRegistry {
     registry
 }
 
+/// Like [`wrap_as_executor`], but routing through a [`ConfiguredExecutor`]
+/// that applies each tool's [`ToolConfig`](crate::tool_config::ToolConfig),
+/// read from the `executor_config` of the matching entry in `config_tools`.
+///
+/// `config_tools` are registered as given, including ones for other
+/// executors (register those executors on the result); tools they leave out
+/// get an empty config. Fails on an `executor_config` that doesn't parse or
+/// a `"direct"` entry naming a tool `tools` lacks.
+pub fn wrap_as_configured_executor(
+    tools: ToolRegistry,
+    cwd: impl Into<String>,
+    config_tools: Vec<ConfigTool>,
+) -> SoulResult<ExecutorRegistry> {
+    let definitions = tools.definitions();
+    let direct = ConfiguredExecutor::new(Arc::new(tools), cwd).with_config_tools(&config_tools)?;
+
+    let mut registry = ExecutorRegistry::new();
+    registry.register_executor(Arc::new(direct) as Arc<dyn ToolExecutor>);
+
+    let configured: HashSet<String> = config_tools
+        .iter()
+        .map(|tool| tool.definition.name.clone())
+        .collect();
+    for def in definitions {
+        if !configured.contains(&def.name) {
+            registry.register_config_tool(ConfigTool {
+                definition: def,
+                executor_name: "direct".into(),
+                executor_config: serde_json::json!({}),
+            });
+        }
+    }
+    for config_tool in config_tools {
+        registry.register_config_tool(config_tool);
+    }
+
+    Ok(registry)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,11 +349,40 @@ mod tests {
     }
 
     #[test]
-    fn all_tools_has_eight() {
+    fn read_only_enforced_tools_has_five() {
+        let fs = Arc::new(MemoryFs::new());
+        let exec = Arc::new(NoopExecutor);
+        let registry = read_only_enforced_tools(fs, exec, "/");
+        assert_eq!(registry.len(), 5);
+        assert!(registry.get("bash").is_some());
+        assert!(registry.get("write").is_none());
+    }
+
+    #[test]
+    fn role_presets() {
+        let fs = Arc::new(MemoryFs::new());
+        let exec = Arc::new(NoopExecutor);
+        let review = review_tools(fs.clone(), exec.clone(), "/");
+        assert_eq!(
+            review.names(),
+            vec!["read", "grep", "find", "ls", "outline", "bash"]
+        );
+        let test = test_tools(fs.clone(), exec.clone(), "/");
+        assert_eq!(test.names(), vec!["read", "grep", "bash"]);
+        let docs = docs_tools(fs.clone(), "/");
+        assert_eq!(docs.names(), vec!["read", "write", "edit", "grep"]);
+
+        assert!(review_executor(fs.clone(), exec.clone(), "/").has_tool("outline"));
+        assert!(test_executor(fs.clone(), exec, "/").has_tool("bash"));
+        assert!(!docs_executor(fs, "/").has_tool("bash"));
+    }
+
+    #[test]
+    fn all_tools_has_nineteen() {
         let fs = Arc::new(MemoryFs::new());
         let exec = Arc::new(NoopExecutor);
         let registry = all_tools(fs, exec, "/");
-        assert_eq!(registry.len(), 8);
+        assert_eq!(registry.len(), 19);
         let names = registry.names();
         assert!(names.contains(&"read"));
         assert!(names.contains(&"write"));
@@ -169,6 +392,27 @@ mod tests {
         assert!(names.contains(&"grep"));
         assert!(names.contains(&"find"));
         assert!(names.contains(&"ls"));
+        assert!(names.contains(&"outline"));
+        assert!(names.contains(&"todo"));
+        assert!(names.contains(&"loc"));
+        assert!(names.contains(&"scratch"));
+        assert!(names.contains(&"scaffold"));
+        assert!(names.contains(&"project_info"));
+        assert!(names.contains(&"hash"));
+        assert!(names.contains(&"write_many"));
+        assert!(names.contains(&"dupes"));
+        assert!(names.contains(&"stats"));
+        assert!(names.contains(&"grep_replace"));
+    }
+
+    #[test]
+    fn compat_tools_adds_aliases() {
+        let fs = Arc::new(MemoryFs::new());
+        let exec = Arc::new(NoopExecutor);
+        let registry = compat_tools(fs, exec, "/");
+        assert_eq!(registry.len(), 19 + compat_aliases().len());
+        let search = registry.get("search_files").unwrap().definition();
+        assert_eq!(search.input_schema["required"], serde_json::json!(["regex"]));
     }
 
     #[test]
@@ -183,6 +427,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn system_prompt_snippet_covers_registered_tools() {
+        let fs = Arc::new(MemoryFs::new());
+        let snippet = system_prompt_snippet(&read_only_tools(fs.clone(), "/"));
+        let tools: Vec<&str> = snippet
+            .lines()
+            .filter_map(|l| l.strip_prefix("- **")?.split("**").next())
+            .collect();
+        assert_eq!(tools, vec!["read", "grep", "find", "ls"]);
+        assert!(snippet.contains("use grep instead"));
+        assert!(!snippet.contains("**edit**"));
+
+        let all = all_tools(fs.clone(), Arc::new(NoopExecutor), "/");
+        assert_eq!(system_prompt_snippet(&all).matches("\n- **").count(), 19);
+
+        let renamed = Toolset::new(fs, Arc::new(NoopExecutor), "/")
+            .with_bash_configured(|bash| bash.with_name("shell"))
+            .build_registry();
+        assert_eq!(system_prompt_snippet(&renamed), "");
+    }
+
     #[test]
     fn all_executor_has_tools() {
         let fs = Arc::new(MemoryFs::new());
@@ -196,7 +461,18 @@ mod tests {
         assert!(registry.has_tool("grep"));
         assert!(registry.has_tool("find"));
         assert!(registry.has_tool("ls"));
-        assert_eq!(registry.definitions().len(), 8);
+        assert!(registry.has_tool("outline"));
+        assert!(registry.has_tool("todo"));
+        assert!(registry.has_tool("loc"));
+        assert!(registry.has_tool("scratch"));
+        assert!(registry.has_tool("scaffold"));
+        assert!(registry.has_tool("project_info"));
+        assert!(registry.has_tool("hash"));
+        assert!(registry.has_tool("write_many"));
+        assert!(registry.has_tool("dupes"));
+        assert!(registry.has_tool("stats"));
+        assert!(registry.has_tool("grep_replace"));
+        assert_eq!(registry.definitions().len(), 19);
     }
 
     #[test]
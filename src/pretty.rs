@@ -0,0 +1,148 @@
+//! Re-indenting JSON for reading.
+//!
+//! A minified config or a one-line API dump is unusable with line-based
+//! reads: the whole file is one line. [`pretty_json`] puts every member and
+//! element on its own line, indented two spaces per level. It re-indents the
+//! text rather than round-tripping it through `serde_json::Value`, so keys
+//! keep their order and numbers and escapes stay exactly as written — any
+//! value in the output can be searched for verbatim in the file.
+//! [`pretty_json_lines`] does the same for each record of a JSON Lines file.
+//!
+//! ```rust
+//! use soul_coder::pretty::pretty_json;
+//!
+//! let pretty = pretty_json(r#"{"b":[1,2],"a":{}}"#).unwrap();
+//! assert_eq!(pretty, "{\n  \"b\": [\n    1,\n    2\n  ],\n  \"a\": {}\n}");
+//! ```
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+use serde::de::IgnoredAny;
+
+/// File extensions of JSON Lines files.
+const JSON_LINES_EXTENSIONS: &[&str] = &[".jsonl", ".ndjson"];
+
+/// Re-indent one JSON document, or say why it isn't valid JSON.
+pub fn pretty_json(text: &str) -> Result<String, String> {
+    serde_json::from_str::<IgnoredAny>(text).map_err(|e| e.to_string())?;
+    Ok(reindent(text))
+}
+
+/// Re-indent each record of a JSON Lines file; blank lines are skipped.
+pub fn pretty_json_lines(text: &str) -> Result<String, String> {
+    let mut records = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        records.push(pretty_json(line).map_err(|e| format!("line {}: {}", i + 1, e))?);
+    }
+    Ok(records.join("\n"))
+}
+
+/// Re-indent `text` as JSON Lines if `path` says so, otherwise as one JSON
+/// document, falling back to JSON Lines when it holds several.
+pub fn pretty_json_auto(path: &str, text: &str) -> Result<String, String> {
+    let lower = path.to_lowercase();
+    if JSON_LINES_EXTENSIONS.iter().any(|ext| lower.ends_with(ext)) {
+        return pretty_json_lines(text);
+    }
+    pretty_json(text).or_else(|e| {
+        if text.trim().lines().count() > 1 {
+            pretty_json_lines(text).map_err(|_| e)
+        } else {
+            Err(e)
+        }
+    })
+}
+
+/// Whether `path` names a JSON or JSON Lines file.
+pub fn is_json_path(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    lower.ends_with(".json") || JSON_LINES_EXTENSIONS.iter().any(|ext| lower.ends_with(ext))
+}
+
+/// Re-indent text already known to be valid JSON.
+fn reindent(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut depth = 0usize;
+    let mut chars = text.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                out.push(c);
+                while let Some(c) = chars.next() {
+                    out.push(c);
+                    match c {
+                        '\\' => out.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '{' | '[' => {
+                out.push(c);
+                skip_whitespace(&mut chars);
+                // Empty containers stay on one line
+                if let Some(close @ ('}' | ']')) = chars.peek().copied() {
+                    chars.next();
+                    out.push(close);
+                } else {
+                    depth += 1;
+                    newline(&mut out, depth);
+                }
+            }
+            '}' | ']' => {
+                depth = depth.saturating_sub(1);
+                newline(&mut out, depth);
+                out.push(c);
+            }
+            ',' => {
+                out.push(c);
+                newline(&mut out, depth);
+            }
+            ':' => out.push_str(": "),
+            c if c.is_whitespace() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+fn newline(out: &mut String, depth: usize) {
+    out.push('\n');
+    for _ in 0..depth {
+        out.push_str("  ");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reindents_json_and_json_lines() {
+        let text = r#"{"z":1e3,"s":"a,\"b\":{[c]}","list":[ ],"nested":{"x":[true,null]}}"#;
+        assert_eq!(
+            pretty_json(text).unwrap(),
+            "{\n  \"z\": 1e3,\n  \"s\": \"a,\\\"b\\\":{[c]}\",\n  \"list\": [],\n  \
+             \"nested\": {\n    \"x\": [\n      true,\n      null\n    ]\n  }\n}"
+        );
+        assert!(pretty_json("{\"a\": 1,}").is_err());
+
+        let lines = "{\"a\":1}\n\n[2]\n";
+        assert_eq!(
+            pretty_json_auto("log.txt", lines).unwrap(),
+            "{\n  \"a\": 1\n}\n[\n  2\n]"
+        );
+        let err = pretty_json_auto("events.jsonl", "{\"a\":1}\n{oops}\n").unwrap_err();
+        assert!(err.starts_with("line 2:"), "{}", err);
+    }
+}
@@ -0,0 +1,196 @@
+//! Pseudo-terminal execution for commands that need a TTY.
+//!
+//! Watch modes, some test runners and interactive prompts behave differently
+//! (or refuse to run) when stdout isn't a terminal. [`run_in_pty`] spawns the
+//! command under a native PTY via `portable-pty`, feeds scripted answers to
+//! its input, and captures the combined terminal output. Only available with
+//! the `pty` feature on native targets; the VFS/executor abstraction is
+//! bypassed, so the command runs on the host, which is why bash only offers
+//! it after [`BashTool::with_pty`](crate::BashTool::with_pty).
+//!
+//! The terminal keeps the size it was opened with: each call runs to
+//! completion with its answers scripted up front, so there is no point at
+//! which a caller could resize it.
+
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+
+/// Terminal dimensions reported to the command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerminalSize {
+    pub cols: u16,
+    pub rows: u16,
+}
+
+impl Default for TerminalSize {
+    fn default() -> Self {
+        Self { cols: 120, rows: 40 }
+    }
+}
+
+/// What a PTY run produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PtyOutput {
+    /// Raw terminal output (ANSI sequences and `\r` included).
+    pub output: String,
+    /// Exit code, or `None` when the command was killed on timeout.
+    pub exit_code: Option<u32>,
+    pub timed_out: bool,
+}
+
+/// How often the child is polled for exit.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long to keep draining output after the child exits.
+const DRAIN_GRACE: Duration = Duration::from_millis(100);
+
+/// Run `command` under `sh -c` in a fresh PTY of `size`, writing each of
+/// `answers` followed by a newline to its input up front.
+pub async fn run_in_pty(
+    command: &str,
+    cwd: &str,
+    size: TerminalSize,
+    answers: &[String],
+    timeout: Duration,
+) -> Result<PtyOutput, String> {
+    let command = command.to_string();
+    let cwd = cwd.to_string();
+    let answers = answers.to_vec();
+    tokio::task::spawn_blocking(move || run_blocking(&command, &cwd, size, &answers, timeout))
+        .await
+        .map_err(|e| format!("PTY task failed: {}", e))?
+}
+
+fn run_blocking(
+    command: &str,
+    cwd: &str,
+    size: TerminalSize,
+    answers: &[String],
+    timeout: Duration,
+) -> Result<PtyOutput, String> {
+    let pair = native_pty_system()
+        .openpty(PtySize {
+            rows: size.rows,
+            cols: size.cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("Failed to open PTY: {}", e))?;
+
+    let mut cmd = CommandBuilder::new("sh");
+    cmd.args(["-c", command]);
+    cmd.cwd(cwd);
+    cmd.env("TERM", "xterm-256color");
+    cmd.env("COLUMNS", size.cols.to_string());
+    cmd.env("LINES", size.rows.to_string());
+
+    let mut child = pair
+        .slave
+        .spawn_command(cmd)
+        .map_err(|e| format!("Failed to spawn in PTY: {}", e))?;
+    // Only the child should hold the slave, so reads end when it exits
+    drop(pair.slave);
+
+    let mut reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|e| format!("Failed to read PTY: {}", e))?;
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    let sink = captured.clone();
+    // Detached: a grandchild holding the terminal open must not hang the call
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 8192];
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => sink.lock().unwrap().extend_from_slice(&buf[..n]),
+            }
+        }
+    });
+
+    if !answers.is_empty() {
+        let mut writer = pair
+            .master
+            .take_writer()
+            .map_err(|e| format!("Failed to write PTY: {}", e))?;
+        for answer in answers {
+            writer
+                .write_all(format!("{}\n", answer).as_bytes())
+                .map_err(|e| format!("Failed to write PTY: {}", e))?;
+        }
+        let _ = writer.flush();
+    }
+
+    let started = Instant::now();
+    let (exit_code, timed_out) = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break (Some(status.exit_code()), false),
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break (None, true);
+            }
+            Ok(None) => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(format!("Failed to wait for command: {}", e)),
+        }
+    };
+    std::thread::sleep(DRAIN_GRACE);
+
+    let bytes = captured.lock().unwrap().clone();
+    Ok(PtyOutput {
+        output: String::from_utf8_lossy(&bytes).into_owned(),
+        exit_code,
+        timed_out,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn command_sees_a_terminal() {
+        let out = run_in_pty(
+            "if [ -t 1 ]; then echo tty; else echo pipe; fi; stty size",
+            "/",
+            TerminalSize { cols: 90, rows: 30 },
+            &[],
+            Duration::from_secs(10),
+        )
+        .await
+        .unwrap();
+        assert!(out.output.contains("tty"), "{:?}", out.output);
+        assert!(out.output.contains("30 90"), "{:?}", out.output);
+        assert_eq!(out.exit_code, Some(0));
+    }
+
+    #[tokio::test]
+    async fn scripted_answers_and_timeout() {
+        let out = run_in_pty(
+            "read -r name; echo \"hello $name\"; exit 3",
+            "/",
+            TerminalSize::default(),
+            &["soul".to_string()],
+            Duration::from_secs(10),
+        )
+        .await
+        .unwrap();
+        assert!(out.output.contains("hello soul"), "{:?}", out.output);
+        assert_eq!(out.exit_code, Some(3));
+
+        let out = run_in_pty(
+            "sleep 5",
+            "/",
+            TerminalSize::default(),
+            &[],
+            Duration::from_millis(200),
+        )
+        .await
+        .unwrap();
+        assert!(out.timed_out);
+        assert_eq!(out.exit_code, None);
+    }
+}
@@ -0,0 +1,724 @@
+//! Read-only enforcement at the tool layer.
+//!
+//! [`read_only_tools`](crate::presets::read_only_tools) simply leaves the
+//! mutating tools out, which stops being a guarantee as soon as bash is added
+//! back for diagnostics. This module makes "read-only" hold regardless of
+//! which tools are registered:
+//!
+//! - [`ReadOnlyFs`] wraps any [`VirtualFs`] and fails every write, append,
+//!   directory creation and removal with a permission error, which tools
+//!   report as [`ToolErrorKind::Policy`](crate::ToolErrorKind::Policy).
+//! - [`check_command`] vets a shell command before it reaches the executor.
+//!   It is an allowlist: every program must be a known read-only one
+//!   ([`READ_ONLY_COMMANDS`]), output may only be redirected to `/dev/null`
+//!   or another descriptor, only harmless variables such as `LC_ALL` may be
+//!   set ([`SAFE_VARIABLES`]), and command substitution is refused outright.
+//!   [`BashTool::with_read_only_policy`](crate::BashTool::with_read_only_policy)
+//!   applies it to every call.
+//!
+//! [`Toolset::enforce_read_only`](crate::Toolset::enforce_read_only) and
+//! [`read_only_enforced_tools`](crate::presets::read_only_enforced_tools) wire
+//! both up:
+//!
+//! ```rust
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! use std::sync::Arc;
+//! use serde_json::json;
+//! use soul_core::vfs::MemoryFs;
+//! use soul_core::vexec::NoopExecutor;
+//! use soul_coder::{ToolErrorKind, Toolset};
+//!
+//! let fs = Arc::new(MemoryFs::new());
+//! let registry = Toolset::new(fs, Arc::new(NoopExecutor), "/workspace")
+//!     .enforce_read_only()
+//!     .with_read_only()
+//!     .with_write()
+//!     .with_bash()
+//!     .build_registry();
+//!
+//! let bash = registry.get("bash").unwrap();
+//! let out = bash.execute("c1", json!({"command": "echo hi > notes.txt"}), None).await.unwrap();
+//! assert_eq!(ToolErrorKind::of(&out), Some(ToolErrorKind::Policy));
+//!
+//! let write = registry.get("write").unwrap();
+//! let out = write
+//!     .execute("c2", json!({"path": "notes.txt", "content": "hi"}), None)
+//!     .await
+//!     .unwrap();
+//! assert_eq!(ToolErrorKind::of(&out), Some(ToolErrorKind::Policy));
+//! # });
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use soul_core::error::{SoulError, SoulResult};
+use soul_core::vfs::{VfsDirEntry, VfsMetadata, VirtualFs};
+
+/// Programs [`check_command`] lets through. A few have flags that write
+/// files (`find -delete`, `sort -o`, ...); those are refused separately.
+pub const READ_ONLY_COMMANDS: &[&str] = &[
+    "basename",
+    "cat",
+    "cd",
+    "cmp",
+    "comm",
+    "cut",
+    "date",
+    "df",
+    "diff",
+    "dirname",
+    "du",
+    "echo",
+    "egrep",
+    "false",
+    "fgrep",
+    "file",
+    "find",
+    "git",
+    "grep",
+    "head",
+    "hexdump",
+    "id",
+    "jq",
+    "ls",
+    "md5sum",
+    "nl",
+    "od",
+    "printf",
+    "pwd",
+    "readlink",
+    "realpath",
+    "rg",
+    "seq",
+    "sha1sum",
+    "sha256sum",
+    "sort",
+    "stat",
+    "strings",
+    "tail",
+    "test",
+    "tr",
+    "tree",
+    "true",
+    "uname",
+    "uniq",
+    "wc",
+    "which",
+    "whoami",
+    "[",
+];
+
+/// Git subcommands that only inspect the repository.
+const GIT_READ_ONLY: &[&str] = &[
+    "blame",
+    "cat-file",
+    "describe",
+    "diff",
+    "grep",
+    "log",
+    "ls-files",
+    "ls-tree",
+    "rev-list",
+    "rev-parse",
+    "shortlog",
+    "show",
+    "status",
+];
+
+/// `find` actions that write, delete or run other programs.
+const FIND_WRITE_ACTIONS: &[&str] = &[
+    "-delete", "-exec", "-execdir", "-ok", "-okdir", "-fprint", "-fprint0", "-fprintf", "-fls",
+];
+
+/// Variables a command may set with a `NAME=value` prefix. Others are
+/// refused: many change what a read-only program runs (`GIT_EXTERNAL_DIFF`,
+/// `GIT_PAGER`, `LD_PRELOAD`, ...) or where it writes.
+pub const SAFE_VARIABLES: &[&str] = &[
+    "CLICOLOR", "COLUMNS", "LANG", "LANGUAGE", "LINES", "NO_COLOR", "TERM", "TZ",
+];
+
+/// `sort` long options that write files or run other programs.
+const SORT_WRITE_OPTIONS: &[&str] = &["--output", "--compress-program"];
+
+/// Shell words that introduce or close a compound command rather than name a
+/// program.
+const SHELL_KEYWORDS: &[&str] = &[
+    "!", "{", "}", "if", "then", "elif", "else", "fi", "while", "until", "do", "done", "time",
+];
+
+fn read_only_error(path: &str) -> SoulError {
+    SoulError::Io(std::io::Error::new(
+        std::io::ErrorKind::PermissionDenied,
+        format!("{} is read-only", path),
+    ))
+}
+
+/// A [`VirtualFs`] that passes reads through and refuses every change.
+pub struct ReadOnlyFs {
+    inner: Arc<dyn VirtualFs>,
+}
+
+impl ReadOnlyFs {
+    pub fn new(inner: Arc<dyn VirtualFs>) -> Self {
+        Self { inner }
+    }
+}
+
+impl VirtualFs for ReadOnlyFs {
+    fn read_to_string<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<String>> + Send + 'a>> {
+        self.inner.read_to_string(path)
+    }
+
+    fn write<'a>(
+        &'a self,
+        path: &'a str,
+        _contents: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<()>> + Send + 'a>> {
+        Box::pin(async move { Err(read_only_error(path)) })
+    }
+
+    fn append<'a>(
+        &'a self,
+        path: &'a str,
+        _contents: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<()>> + Send + 'a>> {
+        Box::pin(async move { Err(read_only_error(path)) })
+    }
+
+    fn exists<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<bool>> + Send + 'a>> {
+        self.inner.exists(path)
+    }
+
+    fn create_dir_all<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<()>> + Send + 'a>> {
+        Box::pin(async move { Err(read_only_error(path)) })
+    }
+
+    fn remove_file<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<()>> + Send + 'a>> {
+        Box::pin(async move { Err(read_only_error(path)) })
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<Vec<VfsDirEntry>>> + Send + 'a>> {
+        self.inner.read_dir(path)
+    }
+
+    fn metadata<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<VfsMetadata>> + Send + 'a>> {
+        self.inner.metadata(path)
+    }
+}
+
+/// What the next word finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pending {
+    Nothing,
+    /// Target of `>`, `>>` or `&>`: only `/dev/null` is allowed.
+    Output,
+    /// Target of `>&`: must be a descriptor.
+    Duplicate,
+    /// Source of `<`, `<&` or `<<<`: never written.
+    Input,
+    /// Delimiter of a here-document.
+    Heredoc,
+}
+
+/// Splits a command line into simple commands, rejecting writes it can see
+/// from the syntax alone.
+struct Splitter {
+    commands: Vec<Vec<String>>,
+    words: Vec<String>,
+    word: String,
+    in_word: bool,
+    pending: Pending,
+    heredocs: Vec<String>,
+}
+
+impl Splitter {
+    fn end_word(&mut self) -> Result<(), String> {
+        if !self.in_word {
+            return Ok(());
+        }
+        let word = std::mem::take(&mut self.word);
+        self.in_word = false;
+        match std::mem::replace(&mut self.pending, Pending::Nothing) {
+            Pending::Nothing => self.words.push(word),
+            Pending::Output if word == "/dev/null" => {}
+            Pending::Output => return Err(format!("output redirection to {}", word)),
+            Pending::Duplicate if word == "-" || word.chars().all(|c| c.is_ascii_digit()) => {}
+            Pending::Duplicate => return Err(format!("output redirection to {}", word)),
+            Pending::Input => {}
+            Pending::Heredoc => self
+                .heredocs
+                .push(word.trim_matches(|c| c == '\'' || c == '"').to_string()),
+        }
+        Ok(())
+    }
+
+    fn end_command(&mut self) -> Result<(), String> {
+        self.end_word()?;
+        if self.pending != Pending::Nothing {
+            return Err("redirection without a target".into());
+        }
+        if !self.words.is_empty() {
+            self.commands.push(std::mem::take(&mut self.words));
+        }
+        Ok(())
+    }
+
+    /// The word so far is a file descriptor number (`2` in `2>`), not an
+    /// argument.
+    fn drop_fd_prefix(&mut self) -> Result<(), String> {
+        if self.in_word && !self.word.is_empty() && self.word.chars().all(|c| c.is_ascii_digit()) {
+            self.word.clear();
+            self.in_word = false;
+            Ok(())
+        } else {
+            self.end_word()
+        }
+    }
+}
+
+/// Split `command` into simple commands (lists of words with quotes removed
+/// and redirections stripped).
+fn split_commands(command: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut s = Splitter {
+        commands: Vec::new(),
+        words: Vec::new(),
+        word: String::new(),
+        in_word: false,
+        pending: Pending::Nothing,
+        heredocs: Vec::new(),
+    };
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                s.in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => s.word.push(c),
+                        None => return Err("unterminated quote".into()),
+                    }
+                }
+            }
+            '"' => {
+                s.in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            if let Some(c) = chars.next() {
+                                s.word.push(c);
+                            }
+                        }
+                        Some('`') => return Err("command substitution".into()),
+                        Some('$') if chars.peek() == Some(&'(') => {
+                            return Err("command substitution".into())
+                        }
+                        Some(c) => s.word.push(c),
+                        None => return Err("unterminated quote".into()),
+                    }
+                }
+            }
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    if c != '\n' {
+                        s.in_word = true;
+                        s.word.push(c);
+                    }
+                }
+            }
+            '`' => return Err("command substitution".into()),
+            '$' if chars.peek() == Some(&'(') => return Err("command substitution".into()),
+            '#' if !s.in_word => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            ' ' | '\t' => s.end_word()?,
+            '\n' => {
+                s.end_command()?;
+                // Skip here-document bodies
+                for delimiter in std::mem::take(&mut s.heredocs) {
+                    let mut line = String::new();
+                    for c in chars.by_ref() {
+                        if c != '\n' {
+                            line.push(c);
+                        } else if line.trim() == delimiter {
+                            break;
+                        } else {
+                            line.clear();
+                        }
+                    }
+                }
+            }
+            ';' | '|' | '(' | ')' => s.end_command()?,
+            '&' => {
+                if chars.peek() == Some(&'>') {
+                    chars.next();
+                    s.end_word()?;
+                    if chars.peek() == Some(&'>') {
+                        chars.next();
+                    }
+                    s.pending = Pending::Output;
+                } else {
+                    s.end_command()?;
+                }
+            }
+            '>' => {
+                s.drop_fd_prefix()?;
+                if s.pending != Pending::Nothing {
+                    return Err("redirection without a target".into());
+                }
+                s.pending = Pending::Output;
+                match chars.peek() {
+                    Some('>') | Some('|') => {
+                        chars.next();
+                    }
+                    Some('&') => {
+                        chars.next();
+                        s.pending = Pending::Duplicate;
+                    }
+                    Some('(') => return Err("process substitution".into()),
+                    _ => {}
+                }
+            }
+            '<' => {
+                s.drop_fd_prefix()?;
+                if s.pending != Pending::Nothing {
+                    return Err("redirection without a target".into());
+                }
+                s.pending = Pending::Input;
+                match chars.peek() {
+                    Some('(') => return Err("process substitution".into()),
+                    Some('>') => return Err("read-write redirection".into()),
+                    Some('&') => {
+                        chars.next();
+                    }
+                    Some('<') => {
+                        chars.next();
+                        if chars.peek() == Some(&'<') {
+                            chars.next();
+                        } else {
+                            if chars.peek() == Some(&'-') {
+                                chars.next();
+                            }
+                            s.pending = Pending::Heredoc;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            c => {
+                s.in_word = true;
+                s.word.push(c);
+            }
+        }
+    }
+    s.end_command()?;
+    Ok(s.commands)
+}
+
+/// The variable a `NAME=value` assignment sets.
+fn assigned_name(word: &str) -> Option<&str> {
+    let (name, _) = word.split_once('=')?;
+    let valid = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then_some(name)
+}
+
+fn is_safe_variable(name: &str) -> bool {
+    SAFE_VARIABLES.contains(&name) || name.starts_with("LC_")
+}
+
+/// Whether `arg` is `option`, or an abbreviation of it as `getopt_long`
+/// accepts, with or without `=value`.
+fn is_long_option(arg: &str, option: &str) -> bool {
+    let name = arg.split('=').next().unwrap_or(arg);
+    name.len() > 2 && name.starts_with("--") && option.starts_with(name)
+}
+
+fn check_git(args: &[&str]) -> Result<(), String> {
+    let mut args = args.iter().copied();
+    let subcommand = loop {
+        match args.next() {
+            Some("--no-pager") => {}
+            Some("-C") => {
+                args.next();
+            }
+            Some(word) => break word,
+            None => return Ok(()),
+        }
+    };
+    if !GIT_READ_ONLY.contains(&subcommand) {
+        return Err(format!("git {} can modify the repository", subcommand));
+    }
+    for arg in args {
+        if arg.starts_with("--output") || arg == "-O" || arg.starts_with("--open-files-in-pager") {
+            return Err(format!("git {} {} writes files", subcommand, arg));
+        }
+    }
+    Ok(())
+}
+
+fn check_simple_command(words: &[String]) -> Result<(), String> {
+    let mut words = words.iter().map(String::as_str).peekable();
+    while let Some(&word) = words.peek() {
+        if let Some(name) = assigned_name(word) {
+            // Assignments to exported variables reach later commands too
+            if !is_safe_variable(name) {
+                return Err(format!("setting {} can change what commands run", name));
+            }
+        } else if !SHELL_KEYWORDS.contains(&word) {
+            break;
+        }
+        words.next();
+    }
+    let Some(program) = words.next() else {
+        return Ok(());
+    };
+    let args: Vec<&str> = words.collect();
+    // The loop header; its body is checked as separate commands
+    if program == "for" {
+        return Ok(());
+    }
+    if !READ_ONLY_COMMANDS.contains(&program) {
+        return Err(format!("{} is not on the read-only allowlist", program));
+    }
+    let refused = |flags: &[&str]| {
+        args.iter()
+            .find(|arg| {
+                flags
+                    .iter()
+                    .any(|f| arg == &f || arg.starts_with(&format!("{}=", f)))
+            })
+            .map(|arg| Err(format!("{} {} writes files", program, arg)))
+            .unwrap_or(Ok(()))
+    };
+    match program {
+        "git" => check_git(&args),
+        "find" => refused(FIND_WRITE_ACTIONS),
+        "sort" => {
+            if args
+                .iter()
+                .any(|a| a.starts_with("-o") && !a.starts_with("--"))
+            {
+                return Err(format!("{} -o writes files", program));
+            }
+            match args.iter().find(|a| {
+                SORT_WRITE_OPTIONS
+                    .iter()
+                    .any(|option| is_long_option(a, option))
+            }) {
+                Some(arg) => Err(format!("{} {} writes files or runs programs", program, arg)),
+                None => Ok(()),
+            }
+        }
+        "tree" => refused(&["-o"]),
+        "rg" => refused(&["--pre"]),
+        // `uniq INPUT OUTPUT` writes its second operand
+        "uniq" if args.iter().filter(|a| !a.starts_with('-')).count() > 1 => {
+            Err("uniq with an output file writes files".into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check that `command` can't modify anything when run by a POSIX shell.
+///
+/// Returns the reason when it might. The check is deliberately conservative:
+/// anything it can't prove harmless — an unknown program, a redirection to a
+/// file, `$(...)` — is refused.
+pub fn check_command(command: &str) -> Result<(), String> {
+    for words in split_commands(command)? {
+        check_simple_command(&words)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use soul_core::vexec::MockExecutor;
+    use soul_core::vfs::MemoryFs;
+
+    use crate::error::ToolErrorKind;
+    use crate::toolset::Toolset;
+
+    #[test]
+    fn allows_read_only_commands() {
+        for command in [
+            "ls -la src",
+            "grep -rn 'fn main' src | head -20",
+            "cat Cargo.toml && git status --short",
+            "git --no-pager log --oneline -5 2>&1",
+            "find . -name '*.rs' -type f | wc -l",
+            "rg TODO 2>/dev/null || echo none",
+            "echo \"a > b\" ; echo '$(not run)'",
+            "LC_ALL=C sort -u names.txt",
+            "TZ=UTC NO_COLOR=1 git log -1",
+            "sort --check names.txt",
+            "for f in a b; do wc -l $f; done",
+            "if test -f x; then cat x; fi",
+            "cat <<EOF\nrm -rf /\nEOF\nls",
+            "grep -c x a.txt >&2 # > comment",
+        ] {
+            assert_eq!(check_command(command), Ok(()), "{}", command);
+        }
+    }
+
+    #[test]
+    fn refuses_writes() {
+        for command in [
+            "echo hi > notes.txt",
+            "echo hi >> notes.txt",
+            "ls &> out.log",
+            "cat a 2> err.log",
+            "rm -rf target",
+            "touch x",
+            "sed -i s/a/b/ f.rs",
+            "cat a | tee b",
+            "git commit -am wip",
+            "git -C sub checkout main",
+            "git diff --output=patch.diff",
+            "find . -name '*.tmp' -delete",
+            "find . -exec rm {} +",
+            "sort -o out.txt in.txt",
+            "echo $(rm x)",
+            "echo \"`rm x`\"",
+            "ls; /bin/rm x",
+            "bash -c 'rm x'",
+            "xargs rm < list",
+            "diff <(ls a) <(ls b)",
+            "cargo install ripgrep",
+            "uniq in.txt out.txt",
+            "echo hi >",
+            "sort --compress-program=rm -S 1 big.txt",
+            "sort --compress-prog gzip big.txt",
+            "sort --out=x in.txt",
+        ] {
+            assert!(check_command(command).is_err(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn refuses_variable_assignments() {
+        for command in [
+            "GIT_EXTERNAL_DIFF=rm git diff",
+            "GIT_PAGER='rm -rf .' git --paginate log",
+            "GIT_DIR=/tmp/other git status",
+            "LD_PRELOAD=./evil.so ls",
+            "LC_ALL=C PAGER=rm git log",
+            "GIT_EXTERNAL_DIFF=rm; git diff",
+        ] {
+            let err = check_command(command).unwrap_err();
+            assert!(err.starts_with("setting "), "{}: {}", command, err);
+        }
+    }
+
+    #[tokio::test]
+    async fn fs_refuses_changes() {
+        let inner = Arc::new(MemoryFs::new());
+        inner.write("/p/a.txt", "alpha").await.unwrap();
+        let fs = ReadOnlyFs::new(inner.clone());
+
+        assert_eq!(fs.read_to_string("/p/a.txt").await.unwrap(), "alpha");
+        assert!(fs.exists("/p/a.txt").await.unwrap());
+        assert_eq!(fs.read_dir("/p").await.unwrap().len(), 1);
+        for result in [
+            fs.write("/p/a.txt", "x").await,
+            fs.append("/p/a.txt", "x").await,
+            fs.create_dir_all("/p/new").await,
+            fs.remove_file("/p/a.txt").await,
+        ] {
+            let err = result.unwrap_err();
+            assert_eq!(ToolErrorKind::from_soul_error(&err), ToolErrorKind::Policy);
+        }
+        assert_eq!(inner.read_to_string("/p/a.txt").await.unwrap(), "alpha");
+    }
+
+    #[tokio::test]
+    async fn enforced_toolset_blocks_every_path() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "alpha\n").await.unwrap();
+        let executor = Arc::new(MockExecutor::always_ok("ok"));
+        let registry = Toolset::new(fs.clone(), executor, "/project")
+            .enforce_read_only()
+            .with_all()
+            .build_registry();
+
+        let calls = [
+            ("write", json!({"path": "b.txt", "content": "x"})),
+            (
+                "write_many",
+                json!({"files": [{"path": "b.txt", "content": "x"}]}),
+            ),
+            ("append", json!({"path": "a.txt", "content": "x"})),
+            (
+                "edit",
+                json!({"path": "a.txt", "old": "alpha", "new": "beta"}),
+            ),
+            ("bash", json!({"command": "echo x > a.txt"})),
+            ("bash", json!({"argv": ["rm", "a.txt"]})),
+        ];
+        for (i, (tool, args)) in calls.into_iter().enumerate() {
+            let out = registry
+                .get(tool)
+                .unwrap()
+                .execute(&format!("c{}", i), args.clone(), None)
+                .await
+                .unwrap();
+            assert_eq!(
+                ToolErrorKind::of(&out),
+                Some(ToolErrorKind::Policy),
+                "{} {}: {}",
+                tool,
+                args,
+                out.content
+            );
+        }
+
+        // The stdin wrapper is added after the check
+        let out = registry
+            .get("bash")
+            .unwrap()
+            .execute("b", json!({"command": "wc -l", "stdin": "x"}), None)
+            .await
+            .unwrap();
+        assert!(!out.is_error, "{}", out.content);
+        let out = registry
+            .get("read")
+            .unwrap()
+            .execute("r", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        assert!(!out.is_error);
+        assert_eq!(
+            fs.read_to_string("/project/a.txt").await.unwrap(),
+            "alpha\n"
+        );
+        assert!(!fs.exists("/project/b.txt").await.unwrap());
+    }
+}
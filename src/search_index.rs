@@ -0,0 +1,134 @@
+//! Trigram index for fast repeated grep over large workspaces.
+//!
+//! [`SearchIndex`] records the set of (lowercased) byte trigrams of every
+//! file grep reads. Later searches skip files whose trigram set can't contain
+//! the pattern without reading them, so repeated greps cost roughly the size
+//! of the candidate files instead of the whole tree.
+//!
+//! The index is built lazily and shared: pass it to
+//! [`GrepTool::with_index`](crate::tools::grep::GrepTool::with_index) and
+//! install it as a [`ToolMiddleware`] on the registry so write, append and
+//! edit calls drop the entries of the files they touch (any other
+//! non-read-only tool, e.g. bash, clears the whole index).
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::ToolOutput;
+
+use crate::cache::CACHEABLE_TOOLS;
+use crate::middleware::{Next, ToolMiddleware};
+use crate::tools::resolve_path;
+
+/// Tools whose `path` argument names the only file they modify.
+const PATH_SCOPED_TOOLS: &[&str] = &["write", "append", "edit"];
+
+type Trigram = [u8; 3];
+
+/// Lowercased byte trigrams of `text`.
+fn trigrams(text: &str) -> HashSet<Trigram> {
+    text.to_lowercase()
+        .as_bytes()
+        .windows(3)
+        .map(|w| [w[0], w[1], w[2]])
+        .collect()
+}
+
+/// Shared, lazily built trigram index over VFS files.
+pub struct SearchIndex {
+    cwd: String,
+    files: Mutex<HashMap<String, HashSet<Trigram>>>,
+}
+
+impl SearchIndex {
+    /// `cwd` resolves relative `path` arguments seen by the middleware.
+    pub fn new(cwd: impl Into<String>) -> Self {
+        Self {
+            cwd: cwd.into(),
+            files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Forget one file (absolute path), e.g. after modifying it directly.
+    pub fn invalidate(&self, path: &str) {
+        self.files.lock().unwrap().remove(path);
+    }
+
+    /// Forget every file.
+    pub fn clear(&self) {
+        self.files.lock().unwrap().clear();
+    }
+
+    /// Number of indexed files.
+    pub fn len(&self) -> usize {
+        self.files.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether `path` may contain `pattern` (case-insensitively).
+    /// `None` if the file isn't indexed yet.
+    pub(crate) fn may_contain(&self, path: &str, pattern: &str) -> Option<bool> {
+        let files = self.files.lock().unwrap();
+        let grams = files.get(path)?;
+        // Shorter patterns have no trigrams to prune on
+        Some(trigrams(pattern).iter().all(|t| grams.contains(t)))
+    }
+
+    /// Index `content` as the current contents of `path`.
+    pub(crate) fn insert(&self, path: &str, content: &str) {
+        let grams = trigrams(content);
+        self.files.lock().unwrap().insert(path.to_string(), grams);
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ToolMiddleware for SearchIndex {
+    async fn handle(
+        &self,
+        tool: &str,
+        call_id: &str,
+        arguments: serde_json::Value,
+        next: Next<'_>,
+    ) -> SoulResult<ToolOutput> {
+        if CACHEABLE_TOOLS.contains(&tool) {
+            return next.run(call_id, arguments).await;
+        }
+        let path = arguments
+            .get("path")
+            .and_then(|v| v.as_str())
+            .map(|p| resolve_path(&self.cwd, p));
+        let output = next.run(call_id, arguments).await;
+        match path {
+            Some(path) if PATH_SCOPED_TOOLS.contains(&tool) => self.invalidate(&path),
+            _ => self.clear(),
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prunes_on_missing_trigrams() {
+        let index = SearchIndex::new("/project");
+        assert_eq!(index.may_contain("/project/a.rs", "main"), None);
+
+        index.insert("/project/a.rs", "fn main() {}");
+        assert_eq!(index.may_contain("/project/a.rs", "MAIN"), Some(true));
+        assert_eq!(index.may_contain("/project/a.rs", "parse"), Some(false));
+        // Too short to prune
+        assert_eq!(index.may_contain("/project/a.rs", "zz"), Some(true));
+
+        index.invalidate("/project/a.rs");
+        assert!(index.is_empty());
+    }
+}
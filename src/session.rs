@@ -0,0 +1,606 @@
+//! Session state that survives process restarts.
+//!
+//! Most tool state already lives in the VFS (the todo list, scratch notes),
+//! but some is only held in memory — chiefly which files the agent has read.
+//! A browser agent whose page reloads gets its [`MemoryFs`] back from
+//! IndexedDB yet forgets everything else. [`SessionState`] bundles that state
+//! into one JSON file in the VFS: [`capture`](SessionState::capture) and
+//! [`save`](SessionState::save) before shutting down, then
+//! [`load`](SessionState::load) and [`restore`](SessionState::restore) on
+//! the way back up.
+//!
+//! The tracker also notes which files were edited or created, and
+//! [`SessionState::working_set`] turns all of it into a short summary —
+//! each file with its current line count and hash — for hosts to put in the
+//! system prompt every turn, so the agent still knows what it has touched
+//! after its context is compacted.
+//!
+//! ```rust
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! use std::sync::Arc;
+//! use soul_core::vfs::MemoryFs;
+//! use soul_coder::session::{ReadTracker, SessionState};
+//!
+//! let fs = Arc::new(MemoryFs::new());
+//! let reads = Arc::new(ReadTracker::new("/workspace"));
+//! reads.record("/workspace/src/lib.rs");
+//!
+//! let state = SessionState::capture(fs.as_ref(), "/workspace", &reads).await;
+//! state.save(fs.as_ref(), "/workspace/.soul/session.json").await.unwrap();
+//!
+//! // ...after a restart
+//! let reads = Arc::new(ReadTracker::new("/workspace"));
+//! let saved = SessionState::load(fs.as_ref(), "/workspace/.soul/session.json").await.unwrap();
+//! if let Some(state) = saved {
+//!     state.restore(fs.as_ref(), "/workspace", &reads).await.unwrap();
+//! }
+//! assert!(reads.has_read("src/lib.rs"));
+//! # });
+//! ```
+//!
+//! [`MemoryFs`]: soul_core::vfs::MemoryFs
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use soul_core::error::{SoulError, SoulResult};
+use soul_core::tool::ToolOutput;
+use soul_core::vfs::VirtualFs;
+
+use crate::middleware::{Next, ToolMiddleware};
+use crate::paths;
+use crate::tools::resolve_path;
+use crate::tools::todo::DEFAULT_TODO_PATH;
+
+/// Default location of the saved state, relative to the working directory.
+pub const DEFAULT_SESSION_PATH: &str = ".soul/session.json";
+
+/// Format version written by [`SessionState::save`].
+pub const SESSION_STATE_VERSION: u32 = 1;
+
+/// Most files listed by [`WorkingSet::render`].
+pub const MAX_WORKING_SET_FILES: usize = 100;
+
+/// How a file was changed this session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChange {
+    /// The session created it (or moved it there).
+    Created,
+    Edited,
+}
+
+/// Middleware remembering which files were read successfully, and which
+/// were edited or created by write, write_many, append, edit, format,
+/// grep_replace, scaffold and rename.
+pub struct ReadTracker {
+    cwd: String,
+    paths: Mutex<BTreeSet<String>>,
+    changes: Mutex<BTreeMap<String, FileChange>>,
+}
+
+impl ReadTracker {
+    pub fn new(cwd: impl Into<String>) -> Self {
+        Self {
+            cwd: cwd.into(),
+            paths: Mutex::new(BTreeSet::new()),
+            changes: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Mark `path` (relative to the working directory or absolute) as read.
+    pub fn record(&self, path: &str) {
+        let path = resolve_path(&self.cwd, path);
+        self.paths.lock().unwrap().insert(path);
+    }
+
+    /// Whether `path` has been read this session.
+    pub fn has_read(&self, path: &str) -> bool {
+        let path = resolve_path(&self.cwd, path);
+        self.paths.lock().unwrap().contains(&path)
+    }
+
+    /// Absolute paths of every file read, sorted.
+    pub fn paths(&self) -> Vec<String> {
+        self.paths.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Mark `path` as changed. A file created this session stays created
+    /// when it is edited afterwards.
+    pub fn record_change(&self, path: &str, change: FileChange) {
+        let path = resolve_path(&self.cwd, path);
+        let mut changes = self.changes.lock().unwrap();
+        let entry = changes.entry(path).or_insert(change);
+        *entry = (*entry).min(change);
+    }
+
+    /// How `path` was changed this session, if it was.
+    pub fn change(&self, path: &str) -> Option<FileChange> {
+        let path = resolve_path(&self.cwd, path);
+        self.changes.lock().unwrap().get(&path).copied()
+    }
+
+    /// Absolute paths of every changed file with how it changed, sorted.
+    pub fn changes(&self) -> Vec<(String, FileChange)> {
+        let changes = self.changes.lock().unwrap();
+        changes.iter().map(|(path, change)| (path.clone(), *change)).collect()
+    }
+
+    pub fn clear(&self) {
+        self.paths.lock().unwrap().clear();
+        self.changes.lock().unwrap().clear();
+    }
+
+    /// Record the files a successful call to `tool` changed, from its
+    /// arguments and result metadata.
+    fn record_call(&self, tool: &str, arguments: &serde_json::Value, output: &ToolOutput) {
+        let meta = &output.metadata;
+        let str_of = |v: &serde_json::Value| v.as_str().map(str::to_string);
+        let path = arguments.get("path").and_then(str_of);
+        let dry_run = arguments.get("dry_run").and_then(|v| v.as_bool()) != Some(false);
+        let mut changed: Vec<(String, FileChange)> = Vec::new();
+        match tool {
+            "write" | "append" | "edit" | "format" => {
+                let unchanged = meta.get("modified") == Some(&false.into())
+                    || meta.get("written") == Some(&false.into())
+                    || meta.get("changed") == Some(&false.into());
+                if let (Some(path), false) = (path, unchanged) {
+                    let change = match meta.get("created") == Some(&true.into()) {
+                        true => FileChange::Created,
+                        false => FileChange::Edited,
+                    };
+                    changed.push((path, change));
+                }
+            }
+            "write_many" => {
+                let files = arguments.get("files").and_then(|v| v.as_array());
+                let statuses = meta.get("files").and_then(|v| v.as_array());
+                let statuses = statuses.into_iter().flatten();
+                for (file, status) in files.into_iter().flatten().zip(statuses) {
+                    let change = match status.get("status").and_then(|v| v.as_str()) {
+                        Some("created") => FileChange::Created,
+                        Some("updated") => FileChange::Edited,
+                        _ => continue,
+                    };
+                    if let Some(path) = file.get("path").and_then(str_of) {
+                        changed.push((path, change));
+                    }
+                }
+            }
+            "grep_replace" if !dry_run => {
+                // Listed relative to `relative_to` when one is given
+                let base = match arguments.get("relative_to").and_then(|v| v.as_str()) {
+                    Some(dir) => resolve_path(&self.cwd, dir),
+                    None => self.cwd.clone(),
+                };
+                let files = meta.get("files").and_then(|v| v.as_array());
+                for file in files.into_iter().flatten() {
+                    if let Some(path) = file.get("path").and_then(|v| v.as_str()) {
+                        changed.push((resolve_path(&base, path), FileChange::Edited));
+                    }
+                }
+            }
+            "scaffold" => {
+                let files = meta.get("files").and_then(|v| v.as_array());
+                for path in files.into_iter().flatten().filter_map(str_of) {
+                    changed.push((path, FileChange::Created));
+                }
+            }
+            "rename" if !dry_run => {
+                let renamed = meta.get("renamed").and_then(|v| v.as_array());
+                for pair in renamed.into_iter().flatten() {
+                    if let Some(from) = pair.get("from").and_then(|v| v.as_str()) {
+                        let from = resolve_path(&self.cwd, from);
+                        self.changes.lock().unwrap().remove(&from);
+                        self.paths.lock().unwrap().remove(&from);
+                    }
+                    if let Some(to) = pair.get("to").and_then(str_of) {
+                        changed.push((to, FileChange::Created));
+                    }
+                }
+            }
+            _ => {}
+        }
+        for (path, change) in changed {
+            self.record_change(&path, change);
+        }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ToolMiddleware for ReadTracker {
+    async fn handle(
+        &self,
+        tool: &str,
+        call_id: &str,
+        arguments: serde_json::Value,
+        next: Next<'_>,
+    ) -> SoulResult<ToolOutput> {
+        if tool != "read" {
+            let recorded = arguments.clone();
+            let output = next.run(call_id, arguments).await?;
+            if !output.is_error {
+                self.record_call(tool, &recorded, &output);
+            }
+            return Ok(output);
+        }
+        let path = arguments
+            .get("path")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        let output = next.run(call_id, arguments).await?;
+        if let Some(path) = path {
+            if !output.is_error {
+                self.record(&path);
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// Tool-layer state saved to and restored from the VFS.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionState {
+    pub version: u32,
+    /// Absolute paths of files the agent has read.
+    #[serde(default)]
+    pub read_files: Vec<String>,
+    /// Absolute paths of files the agent has edited, other than those it
+    /// created.
+    #[serde(default)]
+    pub edited_files: Vec<String>,
+    /// Absolute paths of files the agent has created.
+    #[serde(default)]
+    pub created_files: Vec<String>,
+    /// Contents of the todo store, if there was one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub todos: Option<serde_json::Value>,
+}
+
+fn invalid(message: impl Into<String>) -> SoulError {
+    SoulError::Io(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        message.into(),
+    ))
+}
+
+impl SessionState {
+    /// Snapshot `reads` and the todo list stored at its default location
+    /// under `cwd`.
+    pub async fn capture(fs: &dyn VirtualFs, cwd: &str, reads: &ReadTracker) -> Self {
+        let todo_path = resolve_path(cwd, DEFAULT_TODO_PATH);
+        let todos = match fs.read_to_string(&todo_path).await {
+            Ok(raw) => serde_json::from_str(&raw).ok(),
+            Err(_) => None,
+        };
+        let changes = reads.changes();
+        let changed = |kind| {
+            changes
+                .iter()
+                .filter(|(_, change)| *change == kind)
+                .map(|(path, _)| path.clone())
+                .collect()
+        };
+        Self {
+            version: SESSION_STATE_VERSION,
+            read_files: reads.paths(),
+            edited_files: changed(FileChange::Edited),
+            created_files: changed(FileChange::Created),
+            todos,
+        }
+    }
+
+    /// The files this state lists, each with its line count and sha256 as
+    /// they are now in `fs`: created files first, then edited, then read.
+    /// Paths are shown relative to `cwd`.
+    pub async fn working_set(&self, fs: &dyn VirtualFs, cwd: &str) -> WorkingSet {
+        let created: BTreeSet<&String> = self.created_files.iter().collect();
+        let edited: BTreeSet<&String> = self
+            .edited_files
+            .iter()
+            .filter(|path| !created.contains(path))
+            .collect();
+        let read = self
+            .read_files
+            .iter()
+            .filter(|path| !created.contains(path) && !edited.contains(path));
+        let listed = created
+            .iter()
+            .map(|path| (*path, FileActivity::Created))
+            .chain(edited.iter().map(|path| (*path, FileActivity::Edited)))
+            .chain(read.map(|path| (path, FileActivity::Read)));
+
+        let mut files = Vec::new();
+        for (path, activity) in listed {
+            let (lines, sha256) = match fs.read_to_string(path).await {
+                Ok(content) => (
+                    Some(content.lines().count()),
+                    Some(format!("{:x}", Sha256::digest(content.as_bytes()))),
+                ),
+                Err(_) => (None, None),
+            };
+            files.push(WorkingFile {
+                path: paths::display(path, cwd),
+                activity,
+                lines,
+                sha256,
+            });
+        }
+        WorkingSet { files }
+    }
+
+    /// Write the state as JSON to `path`, creating parent directories.
+    pub async fn save(&self, fs: &dyn VirtualFs, path: &str) -> SoulResult<()> {
+        if let Some((parent, _)) = path.rsplit_once('/') {
+            if !parent.is_empty() {
+                fs.create_dir_all(parent).await?;
+            }
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| invalid(e.to_string()))?;
+        fs.write(path, &json).await
+    }
+
+    /// Read state saved at `path`; `None` when nothing was saved there.
+    pub async fn load(fs: &dyn VirtualFs, path: &str) -> SoulResult<Option<Self>> {
+        if !fs.exists(path).await.unwrap_or(false) {
+            return Ok(None);
+        }
+        let raw = fs.read_to_string(path).await?;
+        let state: Self = serde_json::from_str(&raw)
+            .map_err(|e| invalid(format!("Corrupt session state at {}: {}", path, e)))?;
+        if state.version > SESSION_STATE_VERSION {
+            return Err(invalid(format!(
+                "Session state at {} has version {}, newer than supported {}",
+                path, state.version, SESSION_STATE_VERSION
+            )));
+        }
+        Ok(Some(state))
+    }
+
+    /// Put the state back: re-mark read files in `reads` and rewrite the
+    /// todo store under `cwd`.
+    pub async fn restore(
+        &self,
+        fs: &dyn VirtualFs,
+        cwd: &str,
+        reads: &ReadTracker,
+    ) -> SoulResult<()> {
+        for path in &self.read_files {
+            reads.record(path);
+        }
+        for path in &self.edited_files {
+            reads.record_change(path, FileChange::Edited);
+        }
+        for path in &self.created_files {
+            reads.record_change(path, FileChange::Created);
+        }
+        if let Some(todos) = &self.todos {
+            let todo_path = resolve_path(cwd, DEFAULT_TODO_PATH);
+            if let Some((parent, _)) = todo_path.rsplit_once('/') {
+                fs.create_dir_all(parent).await?;
+            }
+            let json = serde_json::to_string_pretty(todos).map_err(|e| invalid(e.to_string()))?;
+            fs.write(&todo_path, &json).await?;
+        }
+        Ok(())
+    }
+}
+
+/// What the session did with a file in its [`WorkingSet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileActivity {
+    Created,
+    Edited,
+    Read,
+}
+
+/// One file of a [`WorkingSet`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorkingFile {
+    pub path: String,
+    pub activity: FileActivity,
+    /// `None` when the file was deleted since (or isn't text).
+    pub lines: Option<usize>,
+    pub sha256: Option<String>,
+}
+
+/// The files a session touched, from [`SessionState::working_set`].
+/// Its [`Display`](fmt::Display) form is the summary meant for the system
+/// prompt.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct WorkingSet {
+    pub files: Vec<WorkingFile>,
+}
+
+impl WorkingSet {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+impl fmt::Display for WorkingSet {
+    /// One line per file with its activity, line count and a 12-digit hash
+    /// prefix, listing at most [`MAX_WORKING_SET_FILES`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Files touched this session ({}):", self.files.len())?;
+        for file in self.files.iter().take(MAX_WORKING_SET_FILES) {
+            let activity = match file.activity {
+                FileActivity::Created => "created",
+                FileActivity::Edited => "edited",
+                FileActivity::Read => "read",
+            };
+            match (file.lines, &file.sha256) {
+                (Some(lines), Some(hash)) => write!(
+                    f,
+                    "\n  {:<7} {} ({} lines, sha256 {})",
+                    activity,
+                    file.path,
+                    lines,
+                    &hash[..12]
+                )?,
+                _ => write!(f, "\n  {:<7} {} (deleted or unreadable)", activity, file.path)?,
+            }
+        }
+        if self.files.len() > MAX_WORKING_SET_FILES {
+            write!(
+                f,
+                "\n  ... and {} more",
+                self.files.len() - MAX_WORKING_SET_FILES
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use soul_core::tool::Tool;
+    use soul_core::vexec::NoopExecutor;
+    use soul_core::vfs::MemoryFs;
+    use std::sync::Arc;
+
+    use crate::middleware::wrap_with_middleware;
+
+    #[tokio::test]
+    async fn tracks_successful_reads() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.rs", "fn a() {}").await.unwrap();
+        let reads = Arc::new(ReadTracker::new("/project"));
+        let tools = crate::all_tools(fs.clone(), Arc::new(NoopExecutor), "/project");
+        let registry = wrap_with_middleware(tools, vec![reads.clone()]);
+
+        let read = registry.get("read").unwrap();
+        read.execute("c1", json!({"path": "a.rs"}), None)
+            .await
+            .unwrap();
+        read.execute("c2", json!({"path": "missing.rs"}), None)
+            .await
+            .unwrap();
+        registry
+            .get("ls")
+            .unwrap()
+            .execute("c3", json!({"path": "."}), None)
+            .await
+            .unwrap();
+
+        assert_eq!(reads.paths(), vec!["/project/a.rs".to_string()]);
+        assert!(reads.has_read("./a.rs"));
+        assert!(!reads.has_read("missing.rs"));
+    }
+
+    #[tokio::test]
+    async fn summarizes_the_working_set() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.rs", "fn a() {}\n").await.unwrap();
+        fs.write("/project/old.rs", "x\n").await.unwrap();
+        let reads = Arc::new(ReadTracker::new("/project"));
+        let tools = crate::all_tools(fs.clone(), Arc::new(NoopExecutor), "/project");
+        let registry = wrap_with_middleware(tools, vec![reads.clone()]);
+        let calls = [
+            ("read", json!({"path": "a.rs"})),
+            ("read", json!({"path": "old.rs"})),
+            ("write", json!({"path": "src/new.rs", "content": "one\ntwo\n"})),
+            ("edit", json!({"path": "src/new.rs", "old": "two", "new": "2"})),
+            ("edit", json!({"path": "a.rs", "old": "a()", "new": "b()"})),
+            ("write", json!({"path": "a.rs", "content": "fn b() {}\n"})),
+        ];
+        for (i, (tool, args)) in calls.into_iter().enumerate() {
+            let out = registry
+                .get(tool)
+                .unwrap()
+                .execute(&format!("c{}", i), args, None)
+                .await
+                .unwrap();
+            assert!(!out.is_error, "{}", out.content);
+        }
+        fs.remove_file("/project/old.rs").await.unwrap();
+        assert_eq!(reads.change("src/new.rs"), Some(FileChange::Created));
+        assert_eq!(reads.change("a.rs"), Some(FileChange::Edited));
+
+        let state = SessionState::capture(fs.as_ref(), "/project", &reads).await;
+        let set = state.working_set(fs.as_ref(), "/project").await;
+        let activity: Vec<_> = set.files.iter().map(|f| (f.path.as_str(), f.activity)).collect();
+        assert_eq!(
+            activity,
+            [
+                ("src/new.rs", FileActivity::Created),
+                ("a.rs", FileActivity::Edited),
+                ("old.rs", FileActivity::Read),
+            ]
+        );
+        assert_eq!(set.files[0].lines, Some(2));
+        let summary = set.to_string();
+        assert!(summary.starts_with(
+            "Files touched this session (3):\n  created src/new.rs (2 lines, sha256 "
+        ));
+        assert!(summary.ends_with("\n  read    old.rs (deleted or unreadable)"), "{}", summary);
+    }
+
+    #[tokio::test]
+    async fn save_load_restore_round_trip() {
+        let fs = Arc::new(MemoryFs::new());
+        let todo = crate::TodoTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        todo.execute(
+            "c1",
+            json!({"action": "add", "titles": ["Port parser"]}),
+            None,
+        )
+        .await
+        .unwrap();
+        let reads = ReadTracker::new("/project");
+        reads.record("src/lib.rs");
+        reads.record_change("src/parser.rs", FileChange::Created);
+
+        let state = SessionState::capture(fs.as_ref(), "/project", &reads).await;
+        assert_eq!(state.version, SESSION_STATE_VERSION);
+        assert!(state.todos.is_some());
+        state
+            .save(fs.as_ref(), "/project/.soul/session.json")
+            .await
+            .unwrap();
+
+        // Simulate a restart with a fresh tracker and a lost todo store
+        let fresh = MemoryFs::new();
+        let saved = fs
+            .read_to_string("/project/.soul/session.json")
+            .await
+            .unwrap();
+        fresh.write("/state.json", &saved).await.unwrap();
+        let loaded = SessionState::load(&fresh, "/state.json")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded, state);
+
+        let reads = ReadTracker::new("/project");
+        loaded.restore(&fresh, "/project", &reads).await.unwrap();
+        assert!(reads.has_read("src/lib.rs"));
+        assert_eq!(reads.change("src/parser.rs"), Some(FileChange::Created));
+        let todos = fresh
+            .read_to_string("/project/.soul/todos.json")
+            .await
+            .unwrap();
+        assert!(todos.contains("Port parser"));
+    }
+
+    #[tokio::test]
+    async fn load_missing_and_corrupt() {
+        let fs = MemoryFs::new();
+        assert_eq!(SessionState::load(&fs, "/none.json").await.unwrap(), None);
+        fs.write("/bad.json", "{not json").await.unwrap();
+        assert!(SessionState::load(&fs, "/bad.json").await.is_err());
+        fs.write("/future.json", r#"{"version": 99}"#)
+            .await
+            .unwrap();
+        assert!(SessionState::load(&fs, "/future.json").await.is_err());
+    }
+}
@@ -0,0 +1,1428 @@
+//! Simulated shell — a [`VirtualExecutor`] that interprets common commands
+//! directly against a [`VirtualFs`].
+//!
+//! Browser-only deployments have no process to spawn; with [`NoopExecutor`]
+//! every bash call fails and agents lose their bearings. [`SimulatedBash`]
+//! gives them a small, safe, shell-like surface instead:
+//!
+//! `cat cd cp echo grep head ls mkdir mv printf pwd rm tail wc`
+//!
+//! along with quoting, `|` pipes, `&&` / `||` / `;` lists, `{ ... }` groups,
+//! `>` / `>>` / `<` / `2>` / `2>&1` redirects and `*` / `?` globs in the last
+//! path segment. Anything else exits 127 with the list of supported commands.
+//! `grep` matches literal strings, like the grep tool. Directories emptied by
+//! `rm -r` or `mv` remain, since `VirtualFs` has no directory removal.
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use soul_core::vfs::MemoryFs;
+//! use soul_coder::simulated_bash::SimulatedBash;
+//!
+//! let fs = Arc::new(MemoryFs::new());
+//! let exec = Arc::new(SimulatedBash::new(fs.clone()));
+//! let registry = soul_coder::all_tools(fs, exec, "/workspace");
+//! ```
+//!
+//! [`NoopExecutor`]: soul_core::vexec::NoopExecutor
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use soul_core::error::SoulResult;
+use soul_core::vexec::{ExecOutput, VirtualExecutor};
+use soul_core::vfs::VirtualFs;
+
+use crate::tools::resolve_path;
+use crate::walk::WalkGuard;
+
+/// Commands the simulated shell understands.
+pub const SUPPORTED_COMMANDS: &[&str] = &[
+    "cat", "cd", "cp", "echo", "grep", "head", "ls", "mkdir", "mv", "printf", "pwd", "rm", "tail",
+    "wc",
+];
+
+/// Executor that interprets a safe subset of shell commands against a VFS.
+pub struct SimulatedBash {
+    fs: Arc<dyn VirtualFs>,
+}
+
+impl SimulatedBash {
+    pub fn new(fs: Arc<dyn VirtualFs>) -> Self {
+        Self { fs }
+    }
+}
+
+impl VirtualExecutor for SimulatedBash {
+    fn exec_shell<'a>(
+        &'a self,
+        command: &'a str,
+        _timeout_secs: u64,
+        cwd: Option<&'a str>,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<ExecOutput>> + Send + 'a>> {
+        Box::pin(async move {
+            let list = match tokenize(command).and_then(|tokens| Parser::new(tokens).parse()) {
+                Ok(list) => list,
+                Err(e) => {
+                    return Ok(ExecOutput {
+                        stdout: String::new(),
+                        stderr: format!("sh: {}\n", e),
+                        exit_code: 2,
+                    })
+                }
+            };
+            let mut cwd = resolve_path("/", cwd.unwrap_or("/"));
+            let out = Shell {
+                fs: self.fs.as_ref(),
+            }
+            .run_list(&list, &mut cwd, "")
+            .await;
+            Ok(ExecOutput {
+                stdout: out.stdout,
+                stderr: out.stderr,
+                exit_code: out.code,
+            })
+        })
+    }
+}
+
+// ─── Lexing ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// A word, and whether it contains unquoted glob characters.
+    Word(String, bool),
+    Pipe,
+    And,
+    Or,
+    Semi,
+    LBrace,
+    RBrace,
+    /// `>` / `>>` / `2>` / `2>>`
+    RedirectOut {
+        fd: u8,
+        append: bool,
+    },
+    /// `<`
+    RedirectIn,
+    /// `2>&1`
+    ErrToOut,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut word = String::new();
+    let mut quoted = false;
+    let mut glob = false;
+
+    fn finish(tokens: &mut Vec<Token>, word: &mut String, quoted: &mut bool, glob: &mut bool) {
+        if !word.is_empty() || *quoted {
+            let token = match (word.as_str(), *quoted) {
+                ("{", false) => Token::LBrace,
+                ("}", false) => Token::RBrace,
+                _ => Token::Word(std::mem::take(word), *glob),
+            };
+            tokens.push(token);
+        }
+        word.clear();
+        *quoted = false;
+        *glob = false;
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' => finish(&mut tokens, &mut word, &mut quoted, &mut glob),
+            '\n' | ';' => {
+                finish(&mut tokens, &mut word, &mut quoted, &mut glob);
+                tokens.push(Token::Semi);
+            }
+            '#' if word.is_empty() && !quoted => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            '|' => {
+                finish(&mut tokens, &mut word, &mut quoted, &mut glob);
+                if chars.next_if_eq(&'|').is_some() {
+                    tokens.push(Token::Or);
+                } else {
+                    tokens.push(Token::Pipe);
+                }
+            }
+            '&' => {
+                finish(&mut tokens, &mut word, &mut quoted, &mut glob);
+                if chars.next_if_eq(&'&').is_none() {
+                    return Err("background jobs (&) are not supported".into());
+                }
+                tokens.push(Token::And);
+            }
+            '>' => {
+                let fd = if word == "2" && !quoted {
+                    word.clear();
+                    2
+                } else {
+                    finish(&mut tokens, &mut word, &mut quoted, &mut glob);
+                    1
+                };
+                if fd == 2 && chars.next_if_eq(&'&').is_some() {
+                    if chars.next_if_eq(&'1').is_none() {
+                        return Err("only 2>&1 is supported".into());
+                    }
+                    tokens.push(Token::ErrToOut);
+                } else {
+                    let append = chars.next_if_eq(&'>').is_some();
+                    tokens.push(Token::RedirectOut { fd, append });
+                }
+            }
+            '<' => {
+                finish(&mut tokens, &mut word, &mut quoted, &mut glob);
+                tokens.push(Token::RedirectIn);
+            }
+            '\'' => {
+                quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated single quote".into()),
+                    }
+                }
+            }
+            '"' => {
+                quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err("unterminated double quote".into()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated double quote".into()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') | None => {}
+                Some(c) => {
+                    quoted = true;
+                    word.push(c);
+                }
+            },
+            c => {
+                if c == '*' || c == '?' {
+                    glob = true;
+                }
+                word.push(c);
+            }
+        }
+    }
+    finish(&mut tokens, &mut word, &mut quoted, &mut glob);
+    Ok(tokens)
+}
+
+// ─── Parsing ───────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Connector {
+    Seq,
+    And,
+    Or,
+}
+
+type List = Vec<(Connector, Vec<Command>)>;
+
+#[derive(Debug)]
+enum Redirect {
+    Out {
+        fd: u8,
+        append: bool,
+        target: String,
+    },
+    In(String),
+    ErrToOut,
+}
+
+#[derive(Debug)]
+enum Command {
+    Simple {
+        words: Vec<(String, bool)>,
+        redirects: Vec<Redirect>,
+    },
+    Group {
+        list: List,
+        redirects: Vec<Redirect>,
+    },
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>) -> Self {
+        Self { tokens, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse(mut self) -> Result<List, String> {
+        let list = self.parse_list(false)?;
+        match self.peek() {
+            None => Ok(list),
+            Some(t) => Err(format!("syntax error near {:?}", t)),
+        }
+    }
+
+    fn parse_list(&mut self, in_group: bool) -> Result<List, String> {
+        let mut list = Vec::new();
+        let mut connector = Connector::Seq;
+        loop {
+            while self.peek() == Some(&Token::Semi) {
+                self.pos += 1;
+            }
+            match self.peek() {
+                None => break,
+                Some(Token::RBrace) if in_group => break,
+                _ => {}
+            }
+            list.push((connector, self.parse_pipeline()?));
+            connector = match self.peek() {
+                Some(Token::Semi) => Connector::Seq,
+                Some(Token::And) => Connector::And,
+                Some(Token::Or) => Connector::Or,
+                _ => break,
+            };
+            self.pos += 1;
+            if connector != Connector::Seq
+                && matches!(self.peek(), None | Some(Token::Semi) | Some(Token::RBrace))
+            {
+                return Err("syntax error: missing command after && or ||".into());
+            }
+        }
+        Ok(list)
+    }
+
+    fn parse_pipeline(&mut self) -> Result<Vec<Command>, String> {
+        let mut pipeline = vec![self.parse_command()?];
+        while self.peek() == Some(&Token::Pipe) {
+            self.pos += 1;
+            pipeline.push(self.parse_command()?);
+        }
+        Ok(pipeline)
+    }
+
+    fn parse_command(&mut self) -> Result<Command, String> {
+        if self.peek() == Some(&Token::LBrace) {
+            self.pos += 1;
+            let list = self.parse_list(true)?;
+            if self.next() != Some(Token::RBrace) {
+                return Err("syntax error: missing }".into());
+            }
+            let redirects = self.parse_redirects(&mut Vec::new())?;
+            return Ok(Command::Group { list, redirects });
+        }
+
+        let mut words = Vec::new();
+        let redirects = self.parse_redirects(&mut words)?;
+        if words.is_empty() {
+            return Err(match self.peek() {
+                Some(t) => format!("syntax error near {:?}", t),
+                None => "syntax error: unexpected end of input".into(),
+            });
+        }
+        Ok(Command::Simple { words, redirects })
+    }
+
+    /// Consume words and redirects up to the next operator.
+    fn parse_redirects(
+        &mut self,
+        words: &mut Vec<(String, bool)>,
+    ) -> Result<Vec<Redirect>, String> {
+        let mut redirects = Vec::new();
+        loop {
+            match self.peek() {
+                Some(Token::Word(..)) => {
+                    if let Some(Token::Word(w, glob)) = self.next() {
+                        words.push((w, glob));
+                    }
+                }
+                Some(Token::RedirectOut { .. }) | Some(Token::RedirectIn) => {
+                    let op = self.next();
+                    let target = match self.next() {
+                        Some(Token::Word(w, _)) => w,
+                        _ => return Err("syntax error: missing redirect target".into()),
+                    };
+                    redirects.push(match op {
+                        Some(Token::RedirectOut { fd, append }) => {
+                            Redirect::Out { fd, append, target }
+                        }
+                        _ => Redirect::In(target),
+                    });
+                }
+                Some(Token::ErrToOut) => {
+                    self.pos += 1;
+                    redirects.push(Redirect::ErrToOut);
+                }
+                _ => return Ok(redirects),
+            }
+        }
+    }
+}
+
+// ─── Execution ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Default)]
+struct Output {
+    stdout: String,
+    stderr: String,
+    code: i32,
+}
+
+impl Output {
+    fn ok(stdout: String) -> Self {
+        Self {
+            stdout,
+            ..Self::default()
+        }
+    }
+
+    fn fail(stderr: String) -> Self {
+        Self {
+            stderr,
+            code: 1,
+            ..Self::default()
+        }
+    }
+}
+
+fn join(dir: &str, name: &str) -> String {
+    format!("{}/{}", dir.trim_end_matches('/'), name)
+}
+
+fn basename(path: &str) -> &str {
+    path.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(path)
+}
+
+/// Wildcard match supporting `*` and `?`.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ni));
+            pi += 1;
+        } else if let Some((bp, bn)) = backtrack {
+            pi = bp + 1;
+            ni = bn + 1;
+            backtrack = Some((bp, bn + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Split leading `-abc` flags from operands. Stops at `--` or the first operand.
+fn split_flags(args: &[String]) -> (String, Vec<String>) {
+    let mut flags = String::new();
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+    for arg in iter.by_ref() {
+        if arg == "--" {
+            break;
+        }
+        if arg.len() > 1 && arg.starts_with('-') {
+            flags.push_str(&arg[1..]);
+        } else {
+            rest.push(arg.clone());
+            break;
+        }
+    }
+    rest.extend(iter.cloned());
+    (flags, rest)
+}
+
+/// Parse a `-n N`, `-nN`, `-N` line count for head/tail. Returns (count, from_start, files).
+fn line_count_args(args: &[String]) -> Result<(usize, bool, Vec<String>), String> {
+    let mut count = 10;
+    let mut from_start = false;
+    let mut files = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = if arg == "-n" {
+            Some(
+                iter.next()
+                    .ok_or("option requires an argument -- n")?
+                    .clone(),
+            )
+        } else if let Some(v) = arg.strip_prefix("-n") {
+            Some(v.to_string())
+        } else if arg.len() > 1
+            && arg.starts_with('-')
+            && arg[1..].chars().all(|c| c.is_ascii_digit())
+        {
+            Some(arg[1..].to_string())
+        } else {
+            files.push(arg.clone());
+            None
+        };
+        if let Some(v) = value {
+            let v = match v.strip_prefix('+') {
+                Some(v) => {
+                    from_start = true;
+                    v.to_string()
+                }
+                None => v,
+            };
+            count = v
+                .parse()
+                .map_err(|_| format!("invalid number of lines: '{}'", v))?;
+        }
+    }
+    Ok((count, from_start, files))
+}
+
+/// Expand `printf` escapes and `%s`/`%d` specifiers, reusing the format
+/// while arguments remain.
+fn printf_format(format: &str, args: &[String]) -> String {
+    let mut out = String::new();
+    let mut args = args.iter();
+    loop {
+        let mut consumed = false;
+        let mut chars = format.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('\\') => out.push('\\'),
+                    Some(c) => {
+                        out.push('\\');
+                        out.push(c);
+                    }
+                    None => out.push('\\'),
+                },
+                '%' => match chars.next() {
+                    Some('%') => out.push('%'),
+                    Some('s') | Some('d') => {
+                        consumed = true;
+                        if let Some(arg) = args.next() {
+                            out.push_str(arg);
+                        }
+                    }
+                    Some(c) => {
+                        out.push('%');
+                        out.push(c);
+                    }
+                    None => out.push('%'),
+                },
+                c => out.push(c),
+            }
+        }
+        if !consumed || args.len() == 0 {
+            return out;
+        }
+    }
+}
+
+struct Shell<'a> {
+    fs: &'a dyn VirtualFs,
+}
+
+impl Shell<'_> {
+    /// `Some(is_dir)` if the path exists.
+    async fn kind(&self, path: &str) -> Option<bool> {
+        if !self.fs.exists(path).await.unwrap_or(false) {
+            return None;
+        }
+        self.fs.metadata(path).await.ok().map(|m| m.is_dir)
+    }
+
+    /// All files under `dir`, as paths relative to it, sorted.
+    async fn walk(&self, dir: &str) -> Vec<String> {
+        let mut files = Vec::new();
+        let guard = WalkGuard::new();
+        guard.enter(dir, 0);
+        let mut stack = vec![String::new()];
+        while let Some(rel) = stack.pop() {
+            let abs = if rel.is_empty() {
+                dir.to_string()
+            } else {
+                join(dir, &rel)
+            };
+            for entry in self.fs.read_dir(&abs).await.unwrap_or_default() {
+                let child = if rel.is_empty() {
+                    entry.name.clone()
+                } else {
+                    format!("{}/{}", rel, entry.name)
+                };
+                if entry.is_dir {
+                    if guard.enter(&join(dir, &child), child.split('/').count()) {
+                        stack.push(child);
+                    }
+                } else if entry.is_file {
+                    files.push(child);
+                }
+            }
+        }
+        files.sort();
+        files
+    }
+
+    async fn run_list(&self, list: &List, cwd: &mut String, stdin: &str) -> Output {
+        let mut out = Output::default();
+        for (connector, pipeline) in list {
+            let skip = match connector {
+                Connector::Seq => false,
+                Connector::And => out.code != 0,
+                Connector::Or => out.code == 0,
+            };
+            if skip {
+                continue;
+            }
+            let result = self.run_pipeline(pipeline, cwd, stdin).await;
+            out.stdout.push_str(&result.stdout);
+            out.stderr.push_str(&result.stderr);
+            out.code = result.code;
+        }
+        out
+    }
+
+    async fn run_pipeline(&self, pipeline: &[Command], cwd: &mut String, stdin: &str) -> Output {
+        let mut input = stdin.to_string();
+        let mut stderr = String::new();
+        let mut code = 0;
+        for command in pipeline {
+            let result = self.run_command(command, cwd, &input).await;
+            input = result.stdout;
+            stderr.push_str(&result.stderr);
+            code = result.code;
+        }
+        Output {
+            stdout: input,
+            stderr,
+            code,
+        }
+    }
+
+    async fn run_command(&self, command: &Command, cwd: &mut String, stdin: &str) -> Output {
+        let (redirects, mut stdin) = match command {
+            Command::Simple { redirects, .. } | Command::Group { redirects, .. } => {
+                (redirects, stdin.to_string())
+            }
+        };
+
+        for redirect in redirects {
+            if let Redirect::In(target) = redirect {
+                let path = resolve_path(cwd, target);
+                match self.fs.read_to_string(&path).await {
+                    Ok(content) => stdin = content,
+                    Err(_) => {
+                        return Output::fail(format!("sh: {}: No such file or directory\n", target))
+                    }
+                }
+            }
+        }
+
+        let mut out = match command {
+            Command::Group { list, .. } => Box::pin(self.run_list(list, cwd, &stdin)).await,
+            Command::Simple { words, .. } => {
+                let mut args = Vec::new();
+                for (word, glob) in words {
+                    if *glob {
+                        args.extend(self.expand_glob(word, cwd).await);
+                    } else {
+                        args.push(word.clone());
+                    }
+                }
+                let name = args.remove(0);
+                self.builtin(&name, &args, cwd, &stdin).await
+            }
+        };
+
+        for redirect in redirects {
+            match redirect {
+                Redirect::ErrToOut => {
+                    let stderr = std::mem::take(&mut out.stderr);
+                    out.stdout.push_str(&stderr);
+                }
+                Redirect::Out { fd, append, target } => {
+                    let data = if *fd == 2 {
+                        std::mem::take(&mut out.stderr)
+                    } else {
+                        std::mem::take(&mut out.stdout)
+                    };
+                    if target == "/dev/null" {
+                        continue;
+                    }
+                    let path = resolve_path(cwd, target);
+                    if self.kind(&path).await == Some(true) {
+                        out.stderr
+                            .push_str(&format!("sh: {}: Is a directory\n", target));
+                        out.code = 1;
+                        continue;
+                    }
+                    let result = if *append {
+                        self.fs.append(&path, &data).await
+                    } else {
+                        self.fs.write(&path, &data).await
+                    };
+                    if let Err(e) = result {
+                        out.stderr.push_str(&format!("sh: {}: {}\n", target, e));
+                        out.code = 1;
+                    }
+                }
+                Redirect::In(_) => {}
+            }
+        }
+        out
+    }
+
+    /// Expand `*` / `?` in the last path segment; unmatched patterns stay literal.
+    async fn expand_glob(&self, word: &str, cwd: &str) -> Vec<String> {
+        let (dir_part, pattern) = match word.rsplit_once('/') {
+            Some((dir, pattern)) => (Some(dir), pattern),
+            None => (None, word),
+        };
+        if dir_part.is_some_and(|d| d.contains(['*', '?'])) {
+            return vec![word.to_string()];
+        }
+        let dir = match dir_part {
+            Some("") => "/".to_string(),
+            Some(d) => resolve_path(cwd, d),
+            None => cwd.to_string(),
+        };
+        let mut names: Vec<String> = self
+            .fs
+            .read_dir(&dir)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|e| e.name)
+            .filter(|n| !n.starts_with('.') || pattern.starts_with('.'))
+            .filter(|n| wildcard_match(pattern, n))
+            .collect();
+        if names.is_empty() {
+            return vec![word.to_string()];
+        }
+        names.sort();
+        names
+            .into_iter()
+            .map(|n| match dir_part {
+                Some(d) => format!("{}/{}", d, n),
+                None => n,
+            })
+            .collect()
+    }
+
+    async fn builtin(&self, name: &str, args: &[String], cwd: &mut String, stdin: &str) -> Output {
+        match name {
+            "pwd" => Output::ok(format!("{}\n", cwd)),
+            "cd" => self.cd(args, cwd).await,
+            "echo" => {
+                let (newline, args) = match args.first().map(String::as_str) {
+                    Some("-n") => (false, &args[1..]),
+                    _ => (true, args),
+                };
+                let mut text = args.join(" ");
+                if newline {
+                    text.push('\n');
+                }
+                Output::ok(text)
+            }
+            "printf" => match args.split_first() {
+                Some((format, rest)) => Output::ok(printf_format(format, rest)),
+                None => Output::fail("printf: usage: printf format [arguments]\n".into()),
+            },
+            "cat" => self.cat(args, cwd, stdin).await,
+            "ls" => self.ls(args, cwd).await,
+            "mkdir" => self.mkdir(args, cwd).await,
+            "rm" => self.rm(args, cwd).await,
+            "cp" => self.copy(args, cwd, false).await,
+            "mv" => self.copy(args, cwd, true).await,
+            "grep" => self.grep(args, cwd, stdin).await,
+            "wc" => self.wc(args, cwd, stdin).await,
+            "head" | "tail" => self.head_tail(name == "head", args, cwd, stdin).await,
+            _ => Output {
+                stdout: String::new(),
+                stderr: format!(
+                    "{}: command not found (simulated shell supports: {})\n",
+                    name,
+                    SUPPORTED_COMMANDS.join(", ")
+                ),
+                code: 127,
+            },
+        }
+    }
+
+    async fn cd(&self, args: &[String], cwd: &mut String) -> Output {
+        let target = match args.first() {
+            Some(t) => resolve_path(cwd, t),
+            None => "/".to_string(),
+        };
+        if self.kind(&target).await != Some(true) && target != "/" {
+            return Output::fail(format!(
+                "cd: {}: No such file or directory\n",
+                args.first().map(String::as_str).unwrap_or("/")
+            ));
+        }
+        *cwd = target;
+        Output::default()
+    }
+
+    /// Read each operand, or stdin when there are none. Errors go to stderr.
+    async fn inputs(
+        &self,
+        cmd: &str,
+        files: &[String],
+        cwd: &str,
+        stdin: &str,
+    ) -> (Vec<(Option<String>, String)>, Output) {
+        let mut err = Output::default();
+        if files.is_empty() {
+            return (vec![(None, stdin.to_string())], err);
+        }
+        let mut inputs = Vec::new();
+        for file in files {
+            if file == "-" {
+                inputs.push((None, stdin.to_string()));
+                continue;
+            }
+            let path = resolve_path(cwd, file);
+            match self.kind(&path).await {
+                Some(true) => {
+                    err.stderr
+                        .push_str(&format!("{}: {}: Is a directory\n", cmd, file));
+                    err.code = 1;
+                }
+                Some(false) => match self.fs.read_to_string(&path).await {
+                    Ok(content) => inputs.push((Some(file.clone()), content)),
+                    Err(e) => {
+                        err.stderr.push_str(&format!("{}: {}: {}\n", cmd, file, e));
+                        err.code = 1;
+                    }
+                },
+                None => {
+                    err.stderr
+                        .push_str(&format!("{}: {}: No such file or directory\n", cmd, file));
+                    err.code = 1;
+                }
+            }
+        }
+        (inputs, err)
+    }
+
+    async fn cat(&self, args: &[String], cwd: &str, stdin: &str) -> Output {
+        let (flags, files) = split_flags(args);
+        let (inputs, mut out) = self.inputs("cat", &files, cwd, stdin).await;
+        let text: String = inputs.into_iter().map(|(_, c)| c).collect();
+        out.stdout = if flags.contains('n') {
+            text.lines()
+                .enumerate()
+                .map(|(i, l)| format!("{:>6}\t{}\n", i + 1, l))
+                .collect()
+        } else {
+            text
+        };
+        out
+    }
+
+    async fn ls(&self, args: &[String], cwd: &str) -> Output {
+        let (flags, mut paths) = split_flags(args);
+        let all = flags.contains('a');
+        let long = flags.contains('l');
+        if paths.is_empty() {
+            paths.push(".".into());
+        }
+
+        let mut out = Output::default();
+        let multiple = paths.len() > 1;
+        for (i, arg) in paths.iter().enumerate() {
+            let path = resolve_path(cwd, arg);
+            match self.kind(&path).await {
+                None if path != "/" => {
+                    out.stderr.push_str(&format!(
+                        "ls: cannot access '{}': No such file or directory\n",
+                        arg
+                    ));
+                    out.code = 2;
+                }
+                Some(false) => {
+                    out.stdout
+                        .push_str(&self.ls_line(&path, arg, false, long).await);
+                }
+                _ => {
+                    if multiple {
+                        if i > 0 {
+                            out.stdout.push('\n');
+                        }
+                        out.stdout.push_str(&format!("{}:\n", arg));
+                    }
+                    let mut entries = self.fs.read_dir(&path).await.unwrap_or_default();
+                    entries.sort_by(|a, b| a.name.cmp(&b.name));
+                    for entry in entries {
+                        if !all && entry.name.starts_with('.') {
+                            continue;
+                        }
+                        let line = self
+                            .ls_line(&join(&path, &entry.name), &entry.name, entry.is_dir, long)
+                            .await;
+                        out.stdout.push_str(&line);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    async fn ls_line(&self, path: &str, display: &str, is_dir: bool, long: bool) -> String {
+        if !long {
+            return format!("{}\n", display);
+        }
+        let size = if is_dir {
+            0
+        } else {
+            self.fs.metadata(path).await.map(|m| m.size).unwrap_or(0)
+        };
+        let mode = if is_dir { "drwxr-xr-x" } else { "-rw-r--r--" };
+        format!("{} {:>8} {}\n", mode, size, display)
+    }
+
+    async fn mkdir(&self, args: &[String], cwd: &str) -> Output {
+        let (flags, dirs) = split_flags(args);
+        let parents = flags.contains('p');
+        if dirs.is_empty() {
+            return Output::fail("mkdir: missing operand\n".into());
+        }
+        let mut out = Output::default();
+        for dir in &dirs {
+            let path = resolve_path(cwd, dir);
+            if self.kind(&path).await.is_some() {
+                if !parents {
+                    out.stderr.push_str(&format!(
+                        "mkdir: cannot create directory '{}': File exists\n",
+                        dir
+                    ));
+                    out.code = 1;
+                }
+                continue;
+            }
+            let parent = path.rsplit_once('/').map(|(p, _)| p).unwrap_or("");
+            if !parents && !parent.is_empty() && self.kind(parent).await != Some(true) {
+                out.stderr.push_str(&format!(
+                    "mkdir: cannot create directory '{}': No such file or directory\n",
+                    dir
+                ));
+                out.code = 1;
+                continue;
+            }
+            if let Err(e) = self.fs.create_dir_all(&path).await {
+                out.stderr.push_str(&format!(
+                    "mkdir: cannot create directory '{}': {}\n",
+                    dir, e
+                ));
+                out.code = 1;
+            }
+        }
+        out
+    }
+
+    async fn rm(&self, args: &[String], cwd: &str) -> Output {
+        let (flags, targets) = split_flags(args);
+        let recursive = flags.contains('r') || flags.contains('R');
+        let force = flags.contains('f');
+        if targets.is_empty() && !force {
+            return Output::fail("rm: missing operand\n".into());
+        }
+        let mut out = Output::default();
+        for target in &targets {
+            let path = resolve_path(cwd, target);
+            if recursive && path == "/" {
+                out.stderr
+                    .push_str("rm: it is dangerous to operate recursively on '/'\n");
+                out.code = 1;
+                continue;
+            }
+            match self.kind(&path).await {
+                None => {
+                    if !force {
+                        out.stderr.push_str(&format!(
+                            "rm: cannot remove '{}': No such file or directory\n",
+                            target
+                        ));
+                        out.code = 1;
+                    }
+                }
+                Some(true) if !recursive => {
+                    out.stderr
+                        .push_str(&format!("rm: cannot remove '{}': Is a directory\n", target));
+                    out.code = 1;
+                }
+                Some(true) => {
+                    for rel in self.walk(&path).await {
+                        if let Err(e) = self.fs.remove_file(&join(&path, &rel)).await {
+                            out.stderr.push_str(&format!(
+                                "rm: cannot remove '{}': {}\n",
+                                join(target, &rel),
+                                e
+                            ));
+                            out.code = 1;
+                        }
+                    }
+                }
+                Some(false) => {
+                    if let Err(e) = self.fs.remove_file(&path).await {
+                        out.stderr
+                            .push_str(&format!("rm: cannot remove '{}': {}\n", target, e));
+                        out.code = 1;
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// `cp` (and `mv` when `remove_source`): copy files, or directories with `-r`.
+    async fn copy(&self, args: &[String], cwd: &str, remove_source: bool) -> Output {
+        let cmd = if remove_source { "mv" } else { "cp" };
+        let (flags, mut operands) = split_flags(args);
+        let recursive = remove_source || flags.contains('r') || flags.contains('R');
+        if operands.len() < 2 {
+            return Output::fail(format!("{}: missing file operand\n", cmd));
+        }
+        let dest_arg = operands.pop().unwrap_or_default();
+        let dest = resolve_path(cwd, &dest_arg);
+        let dest_is_dir = self.kind(&dest).await == Some(true);
+        if operands.len() > 1 && !dest_is_dir {
+            return Output::fail(format!(
+                "{}: target '{}' is not a directory\n",
+                cmd, dest_arg
+            ));
+        }
+
+        let mut out = Output::default();
+        for source_arg in &operands {
+            let source = resolve_path(cwd, source_arg);
+            let target = if dest_is_dir {
+                join(&dest, basename(&source))
+            } else {
+                dest.clone()
+            };
+            let files = match self.kind(&source).await {
+                None => {
+                    out.stderr.push_str(&format!(
+                        "{}: cannot stat '{}': No such file or directory\n",
+                        cmd, source_arg
+                    ));
+                    out.code = 1;
+                    continue;
+                }
+                Some(true) if !recursive => {
+                    out.stderr.push_str(&format!(
+                        "cp: -r not specified; omitting directory '{}'\n",
+                        source_arg
+                    ));
+                    out.code = 1;
+                    continue;
+                }
+                Some(true) => {
+                    if target == source || target.starts_with(&format!("{}/", source)) {
+                        out.stderr.push_str(&format!(
+                            "{}: cannot copy a directory, '{}', into itself\n",
+                            cmd, source_arg
+                        ));
+                        out.code = 1;
+                        continue;
+                    }
+                    let _ = self.fs.create_dir_all(&target).await;
+                    self.walk(&source)
+                        .await
+                        .into_iter()
+                        .map(|rel| (join(&source, &rel), join(&target, &rel)))
+                        .collect()
+                }
+                Some(false) => vec![(source.clone(), target)],
+            };
+            for (from, to) in files {
+                if from == to {
+                    continue;
+                }
+                let result = match self.fs.read_to_string(&from).await {
+                    Ok(content) => self.fs.write(&to, &content).await,
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(()) if remove_source => {
+                        if let Err(e) = self.fs.remove_file(&from).await {
+                            out.stderr
+                                .push_str(&format!("mv: cannot remove '{}': {}\n", from, e));
+                            out.code = 1;
+                        }
+                    }
+                    Ok(()) => {}
+                    Err(e) => {
+                        out.stderr.push_str(&format!("{}: {}: {}\n", cmd, from, e));
+                        out.code = 1;
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    async fn grep(&self, args: &[String], cwd: &str, stdin: &str) -> Output {
+        let mut flags = String::new();
+        let mut pattern = None;
+        let mut files = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "-e" {
+                pattern = iter.next().cloned();
+            } else if arg.len() > 1 && arg.starts_with('-') && files.is_empty() && pattern.is_none()
+            {
+                flags.push_str(&arg[1..]);
+            } else if pattern.is_none() {
+                pattern = Some(arg.clone());
+            } else {
+                files.push(arg.clone());
+            }
+        }
+        let Some(pattern) = pattern else {
+            return Output {
+                stderr: "usage: grep [-chilnqrv] pattern [file...]\n".into(),
+                code: 2,
+                ..Output::default()
+            };
+        };
+        let ignore_case = flags.contains('i');
+        let invert = flags.contains('v');
+        let recursive = flags.contains('r') || flags.contains('R');
+        let needle = if ignore_case {
+            pattern.to_lowercase()
+        } else {
+            pattern.clone()
+        };
+
+        // Expand directories when recursive
+        let mut out = Output::default();
+        let mut operands = Vec::new();
+        if recursive {
+            if files.is_empty() {
+                for rel in self.walk(cwd).await {
+                    operands.push(rel);
+                }
+            }
+            for file in &files {
+                let path = resolve_path(cwd, file);
+                if self.kind(&path).await == Some(true) {
+                    for rel in self.walk(&path).await {
+                        operands.push(join(file, &rel));
+                    }
+                } else {
+                    operands.push(file.clone());
+                }
+            }
+        } else {
+            operands = files;
+        }
+
+        let show_names = (operands.len() > 1 || recursive) && !flags.contains('h');
+        let (inputs, err) = self.inputs("grep", &operands, cwd, stdin).await;
+        out.stderr = err.stderr;
+
+        let mut any = false;
+        for (name, content) in inputs {
+            let label = name.unwrap_or_else(|| "(standard input)".into());
+            let mut count = 0;
+            for (i, line) in content.lines().enumerate() {
+                let hay = if ignore_case {
+                    line.to_lowercase()
+                } else {
+                    line.to_string()
+                };
+                if hay.contains(&needle) == invert {
+                    continue;
+                }
+                count += 1;
+                if flags.contains('c') || flags.contains('l') {
+                    continue;
+                }
+                if show_names {
+                    out.stdout.push_str(&format!("{}:", label));
+                }
+                if flags.contains('n') {
+                    out.stdout.push_str(&format!("{}:", i + 1));
+                }
+                out.stdout.push_str(line);
+                out.stdout.push('\n');
+            }
+            any |= count > 0;
+            if flags.contains('l') {
+                if count > 0 {
+                    out.stdout.push_str(&format!("{}\n", label));
+                }
+            } else if flags.contains('c') {
+                if show_names {
+                    out.stdout.push_str(&format!("{}:", label));
+                }
+                out.stdout.push_str(&format!("{}\n", count));
+            }
+        }
+        if flags.contains('q') {
+            out.stdout.clear();
+        }
+        out.code = if err.code != 0 {
+            2
+        } else if any {
+            0
+        } else {
+            1
+        };
+        out
+    }
+
+    async fn wc(&self, args: &[String], cwd: &str, stdin: &str) -> Output {
+        let (flags, files) = split_flags(args);
+        let all = !flags.contains(['l', 'w', 'c']);
+        let (inputs, mut out) = self.inputs("wc", &files, cwd, stdin).await;
+
+        let format = |counts: [usize; 3], name: Option<&str>| {
+            let mut line = String::new();
+            for (i, flag) in ['l', 'w', 'c'].iter().enumerate() {
+                if all || flags.contains(*flag) {
+                    line.push_str(&format!("{:>8}", counts[i]));
+                }
+            }
+            if let Some(name) = name {
+                line.push_str(&format!(" {}", name));
+            }
+            line.push('\n');
+            line
+        };
+
+        let mut total = [0; 3];
+        let count = inputs.len();
+        for (name, content) in inputs {
+            let counts = [
+                content.matches('\n').count(),
+                content.split_whitespace().count(),
+                content.len(),
+            ];
+            for i in 0..3 {
+                total[i] += counts[i];
+            }
+            out.stdout.push_str(&format(counts, name.as_deref()));
+        }
+        if count > 1 {
+            out.stdout.push_str(&format(total, Some("total")));
+        }
+        out
+    }
+
+    async fn head_tail(&self, head: bool, args: &[String], cwd: &str, stdin: &str) -> Output {
+        let cmd = if head { "head" } else { "tail" };
+        let (count, from_start, files) = match line_count_args(args) {
+            Ok(parsed) => parsed,
+            Err(e) => return Output::fail(format!("{}: {}\n", cmd, e)),
+        };
+        let (inputs, mut out) = self.inputs(cmd, &files, cwd, stdin).await;
+        let multiple = inputs.len() > 1;
+        for (i, (name, content)) in inputs.into_iter().enumerate() {
+            if multiple {
+                if i > 0 {
+                    out.stdout.push('\n');
+                }
+                out.stdout
+                    .push_str(&format!("==> {} <==\n", name.as_deref().unwrap_or("-")));
+            }
+            let lines: Vec<&str> = content.lines().collect();
+            let selected = if head {
+                &lines[..count.min(lines.len())]
+            } else if from_start {
+                &lines[count.saturating_sub(1).min(lines.len())..]
+            } else {
+                &lines[lines.len().saturating_sub(count)..]
+            };
+            for line in selected {
+                out.stdout.push_str(line);
+                out.stdout.push('\n');
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    async fn setup() -> (Arc<MemoryFs>, SimulatedBash) {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write(
+            "/project/src/main.rs",
+            "fn main() {\n    println!(\"hi\");\n}\n",
+        )
+        .await
+        .unwrap();
+        fs.write("/project/src/lib.rs", "pub fn lib() {}\n")
+            .await
+            .unwrap();
+        fs.write("/project/README.md", "# Demo\n").await.unwrap();
+        let sh = SimulatedBash::new(fs.clone() as Arc<dyn VirtualFs>);
+        (fs, sh)
+    }
+
+    async fn run(sh: &SimulatedBash, command: &str) -> ExecOutput {
+        sh.exec_shell(command, 10, Some("/project")).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn ls_cat_pwd() {
+        let (_fs, sh) = setup().await;
+        assert_eq!(run(&sh, "pwd").await.stdout, "/project\n");
+        assert_eq!(run(&sh, "ls").await.stdout, "README.md\nsrc\n");
+        assert_eq!(run(&sh, "cat src/lib.rs").await.stdout, "pub fn lib() {}\n");
+
+        let missing = run(&sh, "cat nope.txt").await;
+        assert_eq!(missing.exit_code, 1);
+        assert!(missing.stderr.contains("No such file"));
+    }
+
+    #[tokio::test]
+    async fn pipes_and_lists() {
+        let (_fs, sh) = setup().await;
+        let out = run(&sh, "cat src/main.rs | grep -n println | wc -l").await;
+        assert_eq!(out.stdout.trim(), "1");
+
+        let out = run(&sh, "grep -q nothing README.md && echo yes || echo no").await;
+        assert_eq!(out.stdout, "no\n");
+
+        let out = run(&sh, "cd src; ls *.rs").await;
+        assert_eq!(out.stdout, "lib.rs\nmain.rs\n");
+    }
+
+    #[tokio::test]
+    async fn redirects_and_file_ops() {
+        let (fs, sh) = setup().await;
+        let out = run(
+            &sh,
+            "mkdir -p out && echo 'a b' > out/x.txt && echo c >> out/x.txt && cp out/x.txt out/y.txt && mv out/y.txt z.txt",
+        )
+        .await;
+        assert_eq!(out.exit_code, 0, "{}", out.stderr);
+        assert_eq!(
+            fs.read_to_string("/project/out/x.txt").await.unwrap(),
+            "a b\nc\n"
+        );
+        assert_eq!(
+            fs.read_to_string("/project/z.txt").await.unwrap(),
+            "a b\nc\n"
+        );
+        assert!(!fs.exists("/project/out/y.txt").await.unwrap());
+
+        run(&sh, "rm -r out").await;
+        assert!(!fs.exists("/project/out/x.txt").await.unwrap());
+
+        let out = run(&sh, "cat missing 2>&1").await;
+        assert!(out.stdout.contains("No such file"));
+        assert_eq!(run(&sh, "cat missing 2>/dev/null").await.stderr, "");
+    }
+
+    #[tokio::test]
+    async fn failed_removals_are_reported() {
+        let (fs, _) = setup().await;
+        fs.write("/project/.soulignore", "secrets/\n").await.unwrap();
+        let read_only = Arc::new(crate::read_only::ReadOnlyFs::new(fs.clone()));
+        let sh = SimulatedBash::new(read_only);
+
+        let out = run(&sh, "rm -r src").await;
+        assert_eq!(out.exit_code, 1);
+        assert!(
+            out.stderr.contains("rm: cannot remove 'src/lib.rs'"),
+            "{}",
+            out.stderr
+        );
+        assert!(fs.exists("/project/src/lib.rs").await.unwrap());
+
+        let out = run(&sh, "rm -rf /").await;
+        assert_eq!(out.exit_code, 1);
+        assert!(out.stderr.contains("dangerous"), "{}", out.stderr);
+        assert!(fs.exists("/project/README.md").await.unwrap());
+
+        // The copy is written, but the original can't be removed
+        let ignoring = crate::soulignore::SoulIgnoreFs::new(fs.clone(), "/project");
+        let sh = SimulatedBash::new(Arc::new(ignoring));
+        let out = run(&sh, "mv .soulignore rules.txt").await;
+        assert_eq!(out.exit_code, 1);
+        assert!(out.stderr.contains("mv: cannot remove"), "{}", out.stderr);
+        assert!(fs.exists("/project/.soulignore").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn head_tail_wc_grep() {
+        let (fs, sh) = setup().await;
+        let numbers: String = (1..=20).map(|i| format!("{}\n", i)).collect();
+        fs.write("/project/n.txt", &numbers).await.unwrap();
+
+        assert_eq!(run(&sh, "head -n 2 n.txt").await.stdout, "1\n2\n");
+        assert_eq!(run(&sh, "tail -3 n.txt").await.stdout, "18\n19\n20\n");
+        assert_eq!(run(&sh, "wc -l < n.txt").await.stdout.trim(), "20");
+
+        let out = run(&sh, "grep -rn fn src").await;
+        assert_eq!(
+            out.stdout,
+            "src/lib.rs:1:pub fn lib() {}\nsrc/main.rs:1:fn main() {\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn stdin_wrapper_from_bash_tool() {
+        let (_fs, sh) = setup().await;
+        let out = run(&sh, "printf '%s' 'one\ntwo' | {\nwc -l\n}").await;
+        assert_eq!(out.stdout.trim(), "1");
+    }
+
+    #[tokio::test]
+    async fn unsupported_command() {
+        let (_fs, sh) = setup().await;
+        let out = run(&sh, "cargo build").await;
+        assert_eq!(out.exit_code, 127);
+        assert!(out.stderr.contains("command not found"));
+        assert!(out.stderr.contains("grep"));
+
+        let out = run(&sh, "sleep 1 &").await;
+        assert_eq!(out.exit_code, 2);
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(wildcard_match("*.rs", "main.rs"));
+        assert!(wildcard_match("m?in.*", "main.rs"));
+        assert!(!wildcard_match("*.rs", "main.ts"));
+        assert!(wildcard_match("*", ""));
+    }
+}
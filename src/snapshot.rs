@@ -0,0 +1,330 @@
+//! Workspace snapshots — export a VFS subtree to a tar archive and restore it.
+//!
+//! Archives are plain POSIX ustar, so they can be persisted anywhere (IndexedDB,
+//! object storage, disk), inspected with standard `tar`, and restored into any
+//! [`VirtualFs`] backend — moving a workspace between WASM and native runs.
+//!
+//! ```rust
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! use soul_core::vfs::{MemoryFs, VirtualFs};
+//! use soul_coder::snapshot::{export_tar, import_tar};
+//!
+//! let source = MemoryFs::new();
+//! source.write("/workspace/src/main.rs", "fn main() {}").await.unwrap();
+//! let archive = export_tar(&source, "/workspace").await.unwrap();
+//!
+//! let target = MemoryFs::new();
+//! import_tar(&target, "/restored", &archive).await.unwrap();
+//! assert_eq!(target.read_to_string("/restored/src/main.rs").await.unwrap(), "fn main() {}");
+//! # });
+//! ```
+
+use soul_core::error::{SoulError, SoulResult};
+use soul_core::vfs::VirtualFs;
+
+use crate::paths;
+use crate::walk::WalkGuard;
+
+const BLOCK: usize = 512;
+
+/// What an export or import covered.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotSummary {
+    pub files: usize,
+    pub dirs: usize,
+    pub bytes: usize,
+}
+
+fn invalid(message: impl Into<String>) -> SoulError {
+    SoulError::Io(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        message.into(),
+    ))
+}
+
+/// Recursively collect `(relative_path, is_dir)` entries under `dir`.
+async fn walk(
+    fs: &dyn VirtualFs,
+    dir: &str,
+    rel: &str,
+    entries: &mut Vec<(String, bool)>,
+    guard: &WalkGuard,
+) -> SoulResult<()> {
+    let mut children = fs.read_dir(dir).await?;
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+    for child in children {
+        let path = format!("{}/{}", dir.trim_end_matches('/'), child.name);
+        let child_rel = if rel.is_empty() {
+            child.name.clone()
+        } else {
+            format!("{}/{}", rel, child.name)
+        };
+        if child.is_dir {
+            let depth = child_rel.split('/').count();
+            if guard.enter(&path, depth) {
+                entries.push((child_rel.clone(), true));
+                Box::pin(walk(fs, &path, &child_rel, entries, guard)).await?;
+            }
+        } else if child.is_file {
+            entries.push((child_rel, false));
+        }
+    }
+    Ok(())
+}
+
+/// Write `value` as a NUL-terminated, zero-padded octal field.
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    let s = format!("{:0width$o}", value, width = digits);
+    field[..digits].copy_from_slice(&s.as_bytes()[s.len() - digits..]);
+    field[digits] = 0;
+}
+
+fn header(path: &str, size: usize, is_dir: bool) -> SoulResult<[u8; BLOCK]> {
+    let mut h = [0u8; BLOCK];
+
+    // Split long paths across the ustar prefix (155) and name (100) fields
+    let (prefix, name) = if path.len() <= 100 {
+        ("", path)
+    } else {
+        let split = path
+            .char_indices()
+            .filter(|(i, c)| *c == '/' && *i <= 155 && path.len() - i - 1 <= 100)
+            .map(|(i, _)| i)
+            .next()
+            .ok_or_else(|| invalid(format!("Path too long for tar archive: {}", path)))?;
+        (&path[..split], &path[split + 1..])
+    };
+
+    h[..name.len()].copy_from_slice(name.as_bytes());
+    write_octal(&mut h[100..108], if is_dir { 0o755 } else { 0o644 });
+    write_octal(&mut h[108..116], 0);
+    write_octal(&mut h[116..124], 0);
+    write_octal(&mut h[124..136], size as u64);
+    write_octal(&mut h[136..148], 0);
+    h[156] = if is_dir { b'5' } else { b'0' };
+    h[257..263].copy_from_slice(b"ustar\0");
+    h[263..265].copy_from_slice(b"00");
+    h[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+    // Checksum is computed with the checksum field itself as spaces
+    h[148..156].copy_from_slice(b"        ");
+    let sum: u32 = h.iter().map(|&b| b as u32).sum();
+    let chk = format!("{:06o}\0 ", sum);
+    h[148..156].copy_from_slice(chk.as_bytes());
+
+    Ok(h)
+}
+
+/// Serialize the subtree at `root` into a ustar archive.
+/// Entry paths are relative to `root`.
+pub async fn export_tar(fs: &dyn VirtualFs, root: &str) -> SoulResult<Vec<u8>> {
+    let mut entries = Vec::new();
+    let guard = WalkGuard::new();
+    guard.enter(root, 0);
+    walk(fs, root, "", &mut entries, &guard).await?;
+    if guard.depth_limited() > 0 {
+        let notice = guard.notice().unwrap_or_default();
+        return Err(invalid(format!("Refusing to export a partial tree: {}", notice)));
+    }
+
+    let mut archive = Vec::new();
+    for (rel, is_dir) in entries {
+        if is_dir {
+            archive.extend_from_slice(&header(&format!("{}/", rel), 0, true)?);
+            continue;
+        }
+        let path = format!("{}/{}", root.trim_end_matches('/'), rel);
+        let content = fs.read_to_string(&path).await?;
+        archive.extend_from_slice(&header(&rel, content.len(), false)?);
+        archive.extend_from_slice(content.as_bytes());
+        let padding = (BLOCK - content.len() % BLOCK) % BLOCK;
+        archive.extend(std::iter::repeat(0u8).take(padding));
+    }
+    archive.extend_from_slice(&[0u8; BLOCK * 2]);
+    Ok(archive)
+}
+
+fn parse_str(field: &[u8]) -> SoulResult<&str> {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    std::str::from_utf8(&field[..end]).map_err(|_| invalid("Non-UTF-8 path in tar header"))
+}
+
+fn parse_octal(field: &[u8]) -> SoulResult<usize> {
+    let s = parse_str(field)?.trim();
+    if s.is_empty() {
+        return Ok(0);
+    }
+    usize::from_str_radix(s, 8)
+        .map_err(|_| invalid(format!("Invalid octal field in tar header: {}", s)))
+}
+
+/// Restore a ustar archive into `dest`, creating directories as needed.
+///
+/// Only regular files and directories are restored; other entry types are
+/// skipped. Entries that would escape `dest` (absolute paths, `..`) are rejected.
+pub async fn import_tar(
+    fs: &dyn VirtualFs,
+    dest: &str,
+    archive: &[u8],
+) -> SoulResult<SnapshotSummary> {
+    let dest = dest.trim_end_matches('/');
+    let mut summary = SnapshotSummary::default();
+    let mut offset = 0;
+
+    while offset + BLOCK <= archive.len() {
+        let h = &archive[offset..offset + BLOCK];
+        offset += BLOCK;
+        if h.iter().all(|&b| b == 0) {
+            break;
+        }
+
+        let stored: u32 = parse_octal(&h[148..156])? as u32;
+        let actual: u32 = h[..148]
+            .iter()
+            .chain(&[b' '; 8])
+            .chain(&h[156..])
+            .map(|&b| b as u32)
+            .sum();
+        if stored != actual {
+            return Err(invalid("Tar header checksum mismatch"));
+        }
+
+        let name = parse_str(&h[..100])?;
+        let prefix = parse_str(&h[345..500])?;
+        let rel = if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        let size = parse_octal(&h[124..136])?;
+        let typeflag = h[156];
+
+        let data_end = offset + size;
+        if data_end > archive.len() {
+            return Err(invalid(format!("Truncated tar entry: {}", rel)));
+        }
+        let data = &archive[offset..data_end];
+        offset += size.div_ceil(BLOCK) * BLOCK;
+
+        let rel = rel.trim_end_matches('/');
+        if paths::is_absolute(rel) || rel.split('/').any(|part| part == "..") {
+            return Err(invalid(format!("Tar entry escapes destination: {}", rel)));
+        }
+        if rel.is_empty() || rel == "." {
+            continue;
+        }
+        let target = format!("{}/{}", dest, rel.trim_start_matches("./"));
+
+        match typeflag {
+            b'0' | 0 => {
+                let content = std::str::from_utf8(data)
+                    .map_err(|_| invalid(format!("Binary tar entry not supported: {}", rel)))?;
+                if let Some((parent, _)) = target.rsplit_once('/') {
+                    if !parent.is_empty() {
+                        fs.create_dir_all(parent).await?;
+                    }
+                }
+                fs.write(&target, content).await?;
+                summary.files += 1;
+                summary.bytes += size;
+            }
+            b'5' => {
+                fs.create_dir_all(&target).await?;
+                summary.dirs += 1;
+            }
+            _ => {} // Symlinks, pax headers, devices: not representable in a VFS
+        }
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    #[tokio::test]
+    async fn export_refuses_endless_trees() {
+        let fs = crate::walk::tests::loop_fs().await;
+        let err = export_tar(fs.as_ref(), "/w").await.unwrap_err();
+        assert!(err.to_string().contains("Walk stopped at depth 64"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn roundtrip_subtree() {
+        let source = MemoryFs::new();
+        source
+            .write("/ws/src/main.rs", "fn main() {}\n")
+            .await
+            .unwrap();
+        source.write("/ws/.hidden/config", "x=1").await.unwrap();
+        source.write("/ws/README.md", "").await.unwrap();
+        source.create_dir_all("/ws/empty").await.unwrap();
+        source.write("/other/ignored.txt", "no").await.unwrap();
+
+        let archive = export_tar(&source, "/ws").await.unwrap();
+        assert_eq!(archive.len() % BLOCK, 0);
+
+        let target = MemoryFs::new();
+        let summary = import_tar(&target, "/restored", &archive).await.unwrap();
+        assert_eq!(summary.files, 3);
+        assert_eq!(summary.dirs, 3);
+
+        assert_eq!(
+            target
+                .read_to_string("/restored/src/main.rs")
+                .await
+                .unwrap(),
+            "fn main() {}\n"
+        );
+        assert_eq!(
+            target
+                .read_to_string("/restored/.hidden/config")
+                .await
+                .unwrap(),
+            "x=1"
+        );
+        assert_eq!(
+            target.read_to_string("/restored/README.md").await.unwrap(),
+            ""
+        );
+        assert!(target.exists("/restored/empty").await.unwrap());
+        assert!(!target.exists("/restored/ignored.txt").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn long_paths_use_prefix() {
+        let source = MemoryFs::new();
+        let deep = format!("/ws/{}/{}.txt", "d".repeat(120), "f".repeat(60));
+        source.write(&deep, "deep").await.unwrap();
+
+        let archive = export_tar(&source, "/ws").await.unwrap();
+        let target = MemoryFs::new();
+        import_tar(&target, "/ws", &archive).await.unwrap();
+        assert_eq!(target.read_to_string(&deep).await.unwrap(), "deep");
+    }
+
+    #[tokio::test]
+    async fn rejects_path_traversal() {
+        let mut archive = header("../escape.txt", 1, false).unwrap().to_vec();
+        archive.extend_from_slice(b"x");
+        archive.extend(std::iter::repeat(0u8).take(BLOCK - 1 + BLOCK * 2));
+
+        let target = MemoryFs::new();
+        let err = import_tar(&target, "/ws", &archive).await.unwrap_err();
+        assert!(err.to_string().contains("escapes"));
+    }
+
+    #[tokio::test]
+    async fn rejects_corrupt_header() {
+        let source = MemoryFs::new();
+        source.write("/ws/a.txt", "a").await.unwrap();
+        let mut archive = export_tar(&source, "/ws").await.unwrap();
+        archive[0] = b'z';
+
+        let target = MemoryFs::new();
+        assert!(import_tar(&target, "/ws", &archive).await.is_err());
+    }
+}
@@ -0,0 +1,365 @@
+//! `.soulignore`: agent-specific visibility rules shipped with a repository.
+//!
+//! `.gitignore` answers "what does git track", which is not the same question
+//! as "what should an agent see": a repo may want to hide `secrets/` or a
+//! 2 GB fixture directory from its tools while still committing them.
+//! [`SoulIgnoreFs`] wraps any [`VirtualFs`] and hides every path matched by a
+//! `.soulignore` file in the workspace root or a directory below it. The
+//! files use the gitignore syntax ([`crate::ignore`]); rules of nested files
+//! apply below their own directory, and later rules win.
+//!
+//! Hidden paths behave as if they didn't exist: they are left out of
+//! directory listings, `exists` reports `false`, and reading them fails with
+//! a not-found error. Creating, changing or removing one fails with a
+//! permission error instead ([`ToolErrorKind::Policy`]), as does changing a
+//! `.soulignore` file, so the agent can't lift its own restrictions. The
+//! rules are read once per directory; call [`SoulIgnoreFs::reload`] after
+//! editing them from the host.
+//!
+//! Only file tools go through the VFS: bash still sees everything the
+//! executor does.
+//!
+//! ```rust
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! use std::sync::Arc;
+//! use serde_json::json;
+//! use soul_core::vfs::{MemoryFs, VirtualFs};
+//! use soul_core::vexec::NoopExecutor;
+//! use soul_coder::Toolset;
+//!
+//! let fs = Arc::new(MemoryFs::new());
+//! fs.write("/workspace/.soulignore", "secrets/\n").await.unwrap();
+//! fs.write("/workspace/secrets/prod.env", "TOKEN=1").await.unwrap();
+//! fs.write("/workspace/src/main.rs", "fn main() {}").await.unwrap();
+//!
+//! let registry = Toolset::new(fs, Arc::new(NoopExecutor), "/workspace")
+//!     .with_soulignore()
+//!     .with_read_only()
+//!     .build_registry();
+//!
+//! let ls = registry.get("ls").unwrap();
+//! let out = ls.execute("c1", json!({}), None).await.unwrap();
+//! assert!(!out.content.contains("secrets"));
+//! # });
+//! ```
+//!
+//! [`ToolErrorKind::Policy`]: crate::ToolErrorKind::Policy
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use soul_core::error::{SoulError, SoulResult};
+use soul_core::vfs::{VfsDirEntry, VfsMetadata, VirtualFs};
+
+use crate::ignore::{IgnoreRules, SOULIGNORE};
+use crate::paths;
+
+fn not_found(path: &str) -> SoulError {
+    SoulError::Io(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("No such file or directory: {}", path),
+    ))
+}
+
+fn denied(path: &str, why: &str) -> SoulError {
+    SoulError::Io(std::io::Error::new(
+        std::io::ErrorKind::PermissionDenied,
+        format!("{} {}", path, why),
+    ))
+}
+
+/// A [`VirtualFs`] hiding the paths `.soulignore` files match.
+pub struct SoulIgnoreFs {
+    inner: Arc<dyn VirtualFs>,
+    root: String,
+    /// Rules of each directory's `.soulignore`, `None` where there is none.
+    rules: Mutex<HashMap<String, Option<Arc<IgnoreRules>>>>,
+}
+
+impl SoulIgnoreFs {
+    /// Hide what the `.soulignore` files in `root` and below it match.
+    pub fn new(inner: Arc<dyn VirtualFs>, root: impl Into<String>) -> Self {
+        Self {
+            inner,
+            root: paths::normalize(&root.into()),
+            rules: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Forget the rules read so far, so changed `.soulignore` files take
+    /// effect.
+    pub fn reload(&self) {
+        self.rules.lock().unwrap().clear();
+    }
+
+    /// The rules of `dir/.soulignore`, read on first use.
+    async fn dir_rules(&self, dir: &str) -> Option<Arc<IgnoreRules>> {
+        if let Some(cached) = self.rules.lock().unwrap().get(dir) {
+            return cached.clone();
+        }
+        let loaded = match self
+            .inner
+            .read_to_string(&paths::resolve(dir, SOULIGNORE))
+            .await
+        {
+            Ok(content) => {
+                let mut rules = IgnoreRules::new();
+                rules.add(dir, &content);
+                (!rules.is_empty()).then(|| Arc::new(rules))
+            }
+            Err(_) => None,
+        };
+        self.rules
+            .lock()
+            .unwrap()
+            .insert(dir.to_string(), loaded.clone());
+        loaded
+    }
+
+    /// The rules in force inside `dir`: those of its own `.soulignore` and
+    /// of every directory between it and the root.
+    async fn rules_in(&self, dir: &str) -> IgnoreRules {
+        let mut rules = IgnoreRules::new();
+        let Some(rel) = paths::relative_to(dir, &self.root) else {
+            return rules;
+        };
+        let mut current = self.root.clone();
+        let segments = rel.split('/').filter(|s| *s != ".");
+        for segment in std::iter::once("").chain(segments) {
+            if !segment.is_empty() {
+                current = paths::resolve(&current, segment);
+            }
+            if let Some(dir_rules) = self.dir_rules(&current).await {
+                rules.extend(&dir_rules);
+            }
+        }
+        rules
+    }
+
+    /// Whether `path` or one of its parent directories is hidden.
+    async fn is_hidden(&self, path: &str) -> bool {
+        let path = paths::normalize(path);
+        let Some(rel) = paths::relative_to(&path, &self.root).filter(|r| r != ".") else {
+            return false;
+        };
+        let mut rules = IgnoreRules::new();
+        let mut current = self.root.clone();
+        let segments: Vec<&str> = rel.split('/').collect();
+        for (i, segment) in segments.iter().enumerate() {
+            if let Some(dir_rules) = self.dir_rules(&current).await {
+                rules.extend(&dir_rules);
+            }
+            current = paths::resolve(&current, segment);
+            let last = i + 1 == segments.len();
+            if rules.is_ignored(&current, !last) {
+                return true;
+            }
+            // Directory-only rules need to know what the path is
+            if last && rules.is_ignored(&current, true) {
+                return self.inner.metadata(&current).await.is_ok_and(|m| m.is_dir);
+            }
+        }
+        false
+    }
+
+    /// Refuse to change hidden paths and `.soulignore` files.
+    async fn check_writable(&self, path: &str) -> SoulResult<()> {
+        if paths::normalize(path).rsplit('/').next() == Some(SOULIGNORE) {
+            return Err(denied(path, "is a .soulignore file and can't be changed"));
+        }
+        if self.is_hidden(path).await {
+            return Err(denied(path, "is hidden by .soulignore"));
+        }
+        Ok(())
+    }
+}
+
+impl VirtualFs for SoulIgnoreFs {
+    fn read_to_string<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<String>> + Send + 'a>> {
+        Box::pin(async move {
+            if self.is_hidden(path).await {
+                return Err(not_found(path));
+            }
+            self.inner.read_to_string(path).await
+        })
+    }
+
+    fn write<'a>(
+        &'a self,
+        path: &'a str,
+        contents: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<()>> + Send + 'a>> {
+        Box::pin(async move {
+            self.check_writable(path).await?;
+            self.inner.write(path, contents).await
+        })
+    }
+
+    fn append<'a>(
+        &'a self,
+        path: &'a str,
+        contents: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<()>> + Send + 'a>> {
+        Box::pin(async move {
+            self.check_writable(path).await?;
+            self.inner.append(path, contents).await
+        })
+    }
+
+    fn exists<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<bool>> + Send + 'a>> {
+        Box::pin(async move {
+            if self.is_hidden(path).await {
+                return Ok(false);
+            }
+            self.inner.exists(path).await
+        })
+    }
+
+    fn create_dir_all<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<()>> + Send + 'a>> {
+        Box::pin(async move {
+            self.check_writable(path).await?;
+            self.inner.create_dir_all(path).await
+        })
+    }
+
+    fn remove_file<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<()>> + Send + 'a>> {
+        Box::pin(async move {
+            self.check_writable(path).await?;
+            self.inner.remove_file(path).await
+        })
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<Vec<VfsDirEntry>>> + Send + 'a>> {
+        Box::pin(async move {
+            if self.is_hidden(path).await {
+                return Err(not_found(path));
+            }
+            let mut entries = self.inner.read_dir(path).await?;
+            let dir = paths::normalize(path);
+            if paths::is_within(&dir, &self.root) {
+                let rules = self.rules_in(&dir).await;
+                if !rules.is_empty() {
+                    entries.retain(|entry| {
+                        !rules.is_ignored(&paths::resolve(&dir, &entry.name), entry.is_dir)
+                    });
+                }
+            }
+            Ok(entries)
+        })
+    }
+
+    fn metadata<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> Pin<Box<dyn Future<Output = SoulResult<VfsMetadata>> + Send + 'a>> {
+        Box::pin(async move {
+            if self.is_hidden(path).await {
+                return Err(not_found(path));
+            }
+            self.inner.metadata(path).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use soul_core::vexec::NoopExecutor;
+    use soul_core::vfs::MemoryFs;
+
+    use crate::error::ToolErrorKind;
+    use crate::toolset::Toolset;
+
+    #[tokio::test]
+    async fn hides_ignored_paths() {
+        let inner = Arc::new(MemoryFs::new());
+        for (path, content) in [
+            ("/project/.soulignore", "secrets/\n*.fixture\n"),
+            ("/project/secrets/prod.env", "TOKEN=abc"),
+            ("/project/data/big.fixture", "x"),
+            ("/project/data/.soulignore", "private.csv\n!keep.fixture\n"),
+            ("/project/data/keep.fixture", "kept"),
+            ("/project/data/private.csv", "a,b"),
+            ("/project/data/public.csv", "c,d"),
+            ("/project/src/main.rs", "fn main() { let secrets = 1; }"),
+        ] {
+            inner.write(path, content).await.unwrap();
+        }
+        let fs = SoulIgnoreFs::new(inner.clone() as Arc<dyn VirtualFs>, "/project");
+
+        let mut names: Vec<String> = fs
+            .read_dir("/project/data")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, [".soulignore", "keep.fixture", "public.csv"]);
+        assert!(!fs.exists("/project/secrets").await.unwrap());
+        assert!(fs
+            .read_to_string("/project/secrets/prod.env")
+            .await
+            .is_err());
+        assert!(fs
+            .read_to_string("/project/data/keep.fixture")
+            .await
+            .is_ok());
+
+        let registry = Toolset::new(inner, Arc::new(NoopExecutor), "/project")
+            .with_soulignore()
+            .with_read()
+            .with_grep()
+            .with_write()
+            .build_registry();
+        let grep = registry.get("grep").unwrap();
+        let result = grep
+            .execute("c1", json!({"pattern": "TOKEN"}), None)
+            .await
+            .unwrap();
+        assert!(
+            result.content.starts_with("No matches"),
+            "{}",
+            result.content
+        );
+
+        let read = registry.get("read").unwrap();
+        let result = read
+            .execute("c2", json!({"path": "secrets/prod.env"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+
+        let write = registry.get("write").unwrap();
+        for path in ["secrets/new.env", ".soulignore"] {
+            let result = write
+                .execute("c3", json!({"path": path, "content": ""}), None)
+                .await
+                .unwrap();
+            assert_eq!(
+                ToolErrorKind::of(&result),
+                Some(ToolErrorKind::Policy),
+                "{}",
+                path
+            );
+        }
+    }
+}
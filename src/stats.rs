@@ -0,0 +1,224 @@
+//! Whole-workspace size statistics.
+//!
+//! Before deciding how much of a repository to put in front of a model, a
+//! host needs to know how big it is: [`workspace_stats`] totals the files,
+//! bytes and lines under a directory, lists the largest files and aggregates
+//! by extension. Like [`loc`](crate::tools::loc), it skips hidden
+//! directories and `.gitignore`d paths. Files that can't be read as text
+//! count towards bytes but not lines.
+//!
+//! ```rust
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! use soul_core::vfs::{MemoryFs, VirtualFs};
+//! use soul_coder::stats::workspace_stats;
+//!
+//! let fs = MemoryFs::new();
+//! fs.write("/workspace/src/lib.rs", "pub mod a;\npub mod b;\n").await.unwrap();
+//! fs.write("/workspace/README.md", "# Demo\n").await.unwrap();
+//!
+//! let stats = workspace_stats(&fs, "/workspace").await.unwrap();
+//! assert_eq!((stats.files, stats.bytes, stats.lines), (2, 29, 3));
+//! assert_eq!(stats.largest[0].path, "src/lib.rs");
+//! assert_eq!(stats.extensions["rs"].lines, 2);
+//! # });
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use soul_core::error::{SoulError, SoulResult};
+use soul_core::vfs::VirtualFs;
+
+use crate::ignore::IgnoreRules;
+use crate::paths;
+use crate::walk::WalkGuard;
+
+/// Maximum files counted before the walk stops.
+pub const MAX_FILES: usize = 50_000;
+
+/// Number of files kept in [`WorkspaceStats::largest`].
+pub const LARGEST_FILES: usize = 20;
+
+/// Totals for one extension.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ExtensionStats {
+    pub files: usize,
+    pub bytes: u64,
+    pub lines: usize,
+}
+
+/// Size of one file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileStats {
+    /// Path relative to the root.
+    pub path: String,
+    pub bytes: u64,
+    /// `None` for files that can't be read as text.
+    pub lines: Option<usize>,
+}
+
+/// What [`workspace_stats`] found.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct WorkspaceStats {
+    pub files: usize,
+    pub bytes: u64,
+    /// Lines in the files readable as text.
+    pub lines: usize,
+    /// Files that couldn't be read as text (binary or unreadable).
+    pub binary_files: usize,
+    /// The largest files by bytes, at most [`LARGEST_FILES`].
+    pub largest: Vec<FileStats>,
+    /// Totals keyed by lowercased extension, `""` for files without one.
+    pub extensions: BTreeMap<String, ExtensionStats>,
+    /// The walk stopped at [`MAX_FILES`].
+    pub truncated: bool,
+    /// Directories the walk skipped as already visited or too deep.
+    pub cut_short: bool,
+}
+
+impl WorkspaceStats {
+    /// Extensions with their totals, most bytes first.
+    pub fn extensions_by_size(&self) -> Vec<(&str, &ExtensionStats)> {
+        let mut extensions: Vec<_> = self
+            .extensions
+            .iter()
+            .map(|(ext, stats)| (ext.as_str(), stats))
+            .collect();
+        extensions.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then_with(|| a.0.cmp(b.0)));
+        extensions
+    }
+
+    fn add(&mut self, file: FileStats) {
+        self.files += 1;
+        self.bytes += file.bytes;
+        let name = file.path.rsplit('/').next().unwrap_or_default();
+        let ext = match name.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => ext.to_ascii_lowercase(),
+            _ => String::new(),
+        };
+        let totals = self.extensions.entry(ext).or_default();
+        totals.files += 1;
+        totals.bytes += file.bytes;
+        match file.lines {
+            Some(lines) => {
+                self.lines += lines;
+                totals.lines += lines;
+            }
+            None => self.binary_files += 1,
+        }
+
+        // Keep the largest files sorted, biggest first
+        let at = self
+            .largest
+            .partition_point(|f| (f.bytes, &file.path) >= (file.bytes, &f.path));
+        if at < LARGEST_FILES {
+            self.largest.insert(at, file);
+            self.largest.truncate(LARGEST_FILES);
+        }
+    }
+}
+
+/// Gather statistics for the files under the directory `root`.
+pub async fn workspace_stats(fs: &dyn VirtualFs, root: &str) -> SoulResult<WorkspaceStats> {
+    let root = paths::normalize(root);
+    if !fs.metadata(&root).await?.is_dir {
+        return Err(SoulError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Not a directory: {}", root),
+        )));
+    }
+    let mut stats = WorkspaceStats::default();
+    let guard = WalkGuard::new();
+    guard.enter(&root, 0);
+    walk(fs, &root, &root, 0, &IgnoreRules::new(), &guard, &mut stats).await;
+    stats.cut_short = guard.is_cut_short();
+    Ok(stats)
+}
+
+/// Add the files under `dir`, `depth` levels below `root`.
+async fn walk(
+    fs: &dyn VirtualFs,
+    root: &str,
+    dir: &str,
+    depth: usize,
+    ignore: &IgnoreRules,
+    guard: &WalkGuard,
+    stats: &mut WorkspaceStats,
+) {
+    let mut ignore = ignore.clone();
+    ignore.load(fs, dir).await;
+    let Ok(mut entries) = fs.read_dir(dir).await else {
+        return; // Skip unreadable dirs
+    };
+    entries.sort_by(|a, b| paths::natural_cmp(&a.name, &b.name));
+
+    for entry in entries {
+        if stats.files >= MAX_FILES {
+            stats.truncated = true;
+            return;
+        }
+        let path = paths::resolve(dir, &entry.name);
+        if (entry.is_dir && entry.name.starts_with('.')) || ignore.is_ignored(&path, entry.is_dir) {
+            continue;
+        }
+        if entry.is_dir {
+            if guard.enter(&path, depth + 1) {
+                Box::pin(walk(fs, root, &path, depth + 1, &ignore, guard, stats)).await;
+            }
+        } else if entry.is_file {
+            let content = fs.read_to_string(&path).await.ok();
+            let bytes = match &content {
+                Some(content) => content.len() as u64,
+                None => fs.metadata(&path).await.map(|m| m.size).unwrap_or(0),
+            };
+            stats.add(FileStats {
+                path: paths::relative_path(&path, root),
+                bytes,
+                lines: content.map(|c| c.lines().count()),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    #[tokio::test]
+    async fn totals_largest_and_extensions() {
+        let fs = MemoryFs::new();
+        for (path, content) in [
+            ("/w/src/main.rs", "fn main() {}\n".repeat(10)),
+            ("/w/src/util.rs", "fn util() {}\n".to_string()),
+            ("/w/README.md", "# W\n\nText\n".to_string()),
+            ("/w/Makefile", "all:\n".to_string()),
+            ("/w/.git/HEAD", "ref: main\n".to_string()),
+            ("/w/target/debug/w", "binary-ish".repeat(100)),
+            ("/w/.gitignore", "target/\n".to_string()),
+        ] {
+            fs.write(path, &content).await.unwrap();
+        }
+
+        let stats = workspace_stats(&fs, "/w").await.unwrap();
+        assert_eq!(stats.files, 5);
+        assert_eq!(stats.bytes, 130 + 13 + 10 + 5 + 8);
+        assert_eq!(stats.lines, 10 + 1 + 3 + 1 + 1);
+        assert_eq!(stats.largest[0].path, "src/main.rs");
+        assert_eq!(stats.largest.len(), 5);
+        assert_eq!(
+            stats.extensions["rs"],
+            ExtensionStats {
+                files: 2,
+                bytes: 143,
+                lines: 11
+            }
+        );
+        // Makefile and .gitignore have no extension
+        assert_eq!(stats.extensions[""].files, 2);
+        assert_eq!(stats.extensions_by_size()[0].0, "rs");
+
+        assert!(workspace_stats(&fs, "/w/README.md").await.is_err());
+    }
+}
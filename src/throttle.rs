@@ -0,0 +1,278 @@
+//! Throttled partial output.
+//!
+//! Tools stream progress and command output through `partial_tx` as it
+//! comes, which can mean thousands of tiny messages during a fast build or
+//! a long search. [`OutputThrottle`] is a [`ToolMiddleware`] that coalesces
+//! them: messages are buffered and forwarded at most every
+//! [`DEFAULT_INTERVAL`], or as soon as [`DEFAULT_MAX_BYTES`] have piled up,
+//! and whatever is left is flushed before the call returns. Coalesced
+//! messages are joined with a newline unless the earlier one ends with one.
+//!
+//! WASM has no clock, so there messages are only coalesced by size.
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//! use soul_core::vfs::MemoryFs;
+//! use soul_core::vexec::NoopExecutor;
+//! use soul_coder::throttle::OutputThrottle;
+//! use soul_coder::Toolset;
+//!
+//! let registry = Toolset::new(Arc::new(MemoryFs::new()), Arc::new(NoopExecutor), "/workspace")
+//!     .with_all()
+//!     .with_middleware(Arc::new(OutputThrottle::new().with_interval(Duration::from_millis(250))))
+//!     .build_registry();
+//! # assert_eq!(registry.len(), 19);
+//! ```
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::ToolOutput;
+
+use crate::middleware::{Next, ToolMiddleware};
+
+/// Default shortest time between two forwarded messages.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Default buffered size that is forwarded without waiting.
+pub const DEFAULT_MAX_BYTES: usize = 4096;
+
+#[cfg(feature = "native")]
+type Clock = tokio::time::Instant;
+
+/// Coalesces partial output sent by every tool it wraps.
+#[derive(Debug, Clone)]
+pub struct OutputThrottle {
+    interval: Duration,
+    max_bytes: usize,
+}
+
+impl Default for OutputThrottle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutputThrottle {
+    pub fn new() -> Self {
+        Self {
+            interval: DEFAULT_INTERVAL,
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+
+    /// Forward at most one message per `interval` (default:
+    /// [`DEFAULT_INTERVAL`]).
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Forward buffered output early once it reaches `bytes` (default:
+    /// [`DEFAULT_MAX_BYTES`]).
+    pub fn with_max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = bytes;
+        self
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ToolMiddleware for OutputThrottle {
+    async fn handle(
+        &self,
+        _tool: &str,
+        call_id: &str,
+        arguments: serde_json::Value,
+        next: Next<'_>,
+    ) -> SoulResult<ToolOutput> {
+        let Some(downstream) = next.partial_tx().cloned() else {
+            return next.run(call_id, arguments).await;
+        };
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let next = next.with_partial_tx(Some(tx));
+        let mut buffer = Buffer::new(downstream, self);
+
+        let run = next.run(call_id, arguments);
+        futures::pin_mut!(run);
+        let result = loop {
+            tokio::select! {
+                result = &mut run => break result,
+                Some(message) = rx.recv() => buffer.push(message),
+                _ = buffer.deadline() => buffer.flush(),
+            }
+        };
+        // Messages sent just before the tool returned
+        while let Ok(message) = rx.try_recv() {
+            buffer.push(message);
+        }
+        buffer.flush();
+        result
+    }
+}
+
+/// Output waiting to be forwarded.
+struct Buffer {
+    downstream: mpsc::UnboundedSender<String>,
+    pending: String,
+    max_bytes: usize,
+    #[cfg(feature = "native")]
+    interval: Duration,
+    /// When the last message was forwarded.
+    #[cfg(feature = "native")]
+    flushed: Option<Clock>,
+}
+
+impl Buffer {
+    fn new(downstream: mpsc::UnboundedSender<String>, throttle: &OutputThrottle) -> Self {
+        Self {
+            downstream,
+            pending: String::new(),
+            max_bytes: throttle.max_bytes,
+            #[cfg(feature = "native")]
+            interval: throttle.interval,
+            #[cfg(feature = "native")]
+            flushed: None,
+        }
+    }
+
+    fn push(&mut self, message: String) {
+        if !self.pending.is_empty() && !self.pending.ends_with('\n') {
+            self.pending.push('\n');
+        }
+        self.pending.push_str(&message);
+        if self.pending.len() >= self.max_bytes || self.due() {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let _ = self.downstream.send(std::mem::take(&mut self.pending));
+        #[cfg(feature = "native")]
+        {
+            self.flushed = Some(Clock::now());
+        }
+    }
+
+    /// Whether the interval since the last forwarded message has passed.
+    #[cfg(feature = "native")]
+    fn due(&self) -> bool {
+        self.flushed
+            .map_or(true, |at| at.elapsed() >= self.interval)
+    }
+
+    #[cfg(not(feature = "native"))]
+    fn due(&self) -> bool {
+        false
+    }
+
+    /// Resolves when buffered output is due to be forwarded; never while
+    /// the buffer is empty.
+    async fn deadline(&self) {
+        #[cfg(feature = "native")]
+        if let (false, Some(at)) = (self.pending.is_empty(), self.flushed) {
+            return tokio::time::sleep_until(at + self.interval).await;
+        }
+        std::future::pending::<()>().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use serde_json::json;
+    use soul_core::tool::{Tool, ToolRegistry};
+    use soul_core::types::ToolDefinition;
+
+    use crate::middleware::wrap_with_middleware;
+
+    /// Sends `count` messages, pausing `pause_ms` after every tenth.
+    struct Chatty;
+
+    #[async_trait]
+    impl Tool for Chatty {
+        fn name(&self) -> &str {
+            "chatty"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                name: "chatty".into(),
+                description: String::new(),
+                input_schema: json!({"type": "object"}),
+            }
+        }
+
+        async fn execute(
+            &self,
+            _call_id: &str,
+            arguments: serde_json::Value,
+            partial_tx: Option<mpsc::UnboundedSender<String>>,
+        ) -> SoulResult<ToolOutput> {
+            let count = arguments["count"].as_u64().unwrap();
+            let pause = Duration::from_millis(arguments["pause_ms"].as_u64().unwrap());
+            for i in 1..=count {
+                partial_tx
+                    .as_ref()
+                    .unwrap()
+                    .send(format!("line {}", i))
+                    .unwrap();
+                if i % 10 == 0 {
+                    tokio::time::sleep(pause).await;
+                }
+            }
+            Ok(ToolOutput::success("done"))
+        }
+    }
+
+    async fn messages(throttle: OutputThrottle, arguments: serde_json::Value) -> Vec<String> {
+        let mut tools = ToolRegistry::new();
+        tools.register(Box::new(Chatty));
+        let tools = wrap_with_middleware(tools, vec![Arc::new(throttle)]);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let tool = tools.get("chatty").unwrap();
+        tool.execute("c1", arguments, Some(tx)).await.unwrap();
+        let mut received = Vec::new();
+        while let Ok(message) = rx.try_recv() {
+            received.push(message);
+        }
+        received
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn coalesces_partial_output() {
+        // 30 messages in three bursts 1s apart: the first of each burst
+        // goes out at once, the other nine together once the interval passes
+        let received = messages(
+            OutputThrottle::new(),
+            json!({"count": 30, "pause_ms": 1000}),
+        )
+        .await;
+        assert_eq!(received.len(), 6, "{:?}", received);
+        assert_eq!(received[0], "line 1");
+        assert!(received[1].starts_with("line 2\nline 3\n"));
+        assert_eq!(received[2], "line 11");
+        let all: Vec<String> = (1..=30).map(|i| format!("line {}", i)).collect();
+        assert_eq!(received.join("\n"), all.join("\n"));
+
+        // Without pauses only the size cap forwards early
+        let received = messages(
+            OutputThrottle::new().with_max_bytes(60),
+            json!({"count": 30, "pause_ms": 0}),
+        )
+        .await;
+        assert!(received.len() > 2 && received.len() < 10, "{:?}", received);
+        assert!(received[1..received.len() - 1]
+            .iter()
+            .all(|m| m.len() >= 60));
+    }
+}
@@ -0,0 +1,350 @@
+//! Per-tool configuration for the executor registry.
+//!
+//! soul-core routes config-defined tools by [`ConfigTool`], whose
+//! `executor_config` is free-form JSON. [`ConfiguredExecutor`] reads a
+//! [`ToolConfig`] from it for each tool and applies it whenever it routes a
+//! call, so limits, sandbox roots and policies can live in the same config
+//! file as the tool list. Build one with
+//! [`wrap_as_configured_executor`](crate::presets::wrap_as_configured_executor).
+//!
+//! ```rust
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! use std::sync::Arc;
+//! use serde_json::json;
+//! use soul_core::executor::ConfigTool;
+//! use soul_core::vexec::NoopExecutor;
+//! use soul_core::vfs::{MemoryFs, VirtualFs};
+//! use soul_coder::presets::{all_tools, wrap_as_configured_executor};
+//!
+//! let fs = Arc::new(MemoryFs::new());
+//! fs.write("/workspace/src/lib.rs", "pub fn f() {}\n").await.unwrap();
+//! let tools = all_tools(fs, Arc::new(NoopExecutor), "/workspace");
+//! let read = tools.get("read").unwrap().definition();
+//!
+//! let config = vec![ConfigTool {
+//!     definition: read,
+//!     executor_name: "direct".into(),
+//!     executor_config: json!({"roots": ["src"]}),
+//! }];
+//! let registry = wrap_as_configured_executor(tools, "/workspace", config).unwrap();
+//!
+//! let ok = registry.execute("read", "c1", json!({"path": "src/lib.rs"}), None).await;
+//! assert!(!ok.unwrap().is_error);
+//! let refused = registry.execute("read", "c2", json!({"path": "/etc/passwd"}), None).await;
+//! assert!(refused.unwrap().is_error);
+//! # });
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tokio::sync::mpsc;
+
+use soul_core::error::{SoulError, SoulResult};
+use soul_core::executor::{ConfigTool, ToolExecutor};
+use soul_core::tool::{ToolOutput, ToolRegistry};
+use soul_core::types::ToolDefinition;
+
+use crate::audit::MUTATING_TOOLS;
+use crate::error::{tool_error, ToolErrorKind};
+use crate::paths;
+use crate::read_only;
+use crate::tools::set_metadata;
+use crate::truncate::truncate_head;
+
+/// Settings for one tool, deserialized from its `executor_config`.
+///
+/// Every field is optional; `{}` leaves the tool as it is. Unknown fields
+/// are rejected, so a misspelt limit fails loudly instead of being ignored.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolConfig {
+    /// Refuse every call.
+    pub disabled: bool,
+    /// Refuse calls that could change the workspace: mutating tools are
+    /// refused outright, and a bash `command` must pass
+    /// [`read_only::check_command`].
+    pub read_only: bool,
+    /// Directories (relative to the working directory or absolute) that
+    /// the call's `path`, `cwd` and `files[].path` arguments must lie in.
+    /// A call without one works on the working directory, which must then
+    /// lie in a root. Commands run by bash can still reach anything.
+    pub roots: Vec<String>,
+    /// Upper bound on the call's `timeout` argument in seconds; calls
+    /// without one get this.
+    pub timeout_secs: Option<u64>,
+    /// Cut the output content after this many bytes.
+    pub max_output_bytes: Option<usize>,
+    /// Arguments filled in when the call leaves them out.
+    pub defaults: Map<String, Value>,
+}
+
+impl ToolConfig {
+    /// Parse a tool's `executor_config`; `null` counts as `{}`.
+    pub fn from_value(tool_name: &str, config: &Value) -> SoulResult<Self> {
+        if config.is_null() {
+            return Ok(Self::default());
+        }
+        Self::deserialize(config).map_err(|e| SoulError::ToolExecution {
+            tool_name: tool_name.to_string(),
+            message: format!("Invalid executor_config: {}", e),
+        })
+    }
+
+    /// Apply the settings to the arguments of a call to `tool_name` from
+    /// `cwd`, or the refusal to return instead of running it.
+    fn prepare(&self, tool_name: &str, cwd: &str, arguments: &mut Value) -> Result<(), ToolOutput> {
+        if self.disabled {
+            return Err(tool_error(
+                ToolErrorKind::Policy,
+                format!("{} is disabled by its configuration", tool_name),
+            ));
+        }
+        if let Some(map) = arguments.as_object_mut() {
+            for (key, value) in &self.defaults {
+                map.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+            if let Some(max) = self.timeout_secs {
+                let timeout = map.get("timeout").and_then(|v| v.as_u64());
+                map.insert("timeout".into(), json!(timeout.map_or(max, |t| t.min(max))));
+            }
+        }
+        if self.read_only {
+            self.check_read_only(tool_name, arguments)?;
+        }
+        if !self.roots.is_empty() {
+            self.check_roots(tool_name, cwd, arguments)?;
+        }
+        Ok(())
+    }
+
+    fn check_read_only(&self, tool_name: &str, arguments: &Value) -> Result<(), ToolOutput> {
+        let refuse = |reason: String| {
+            Err(tool_error(
+                ToolErrorKind::Policy,
+                format!(
+                    "{} is read-only by its configuration: {}",
+                    tool_name, reason
+                ),
+            ))
+        };
+        if tool_name == "bash" {
+            if arguments.get("argv").is_some() || arguments.get("rerun").is_some() {
+                return refuse("only plain commands can be checked".into());
+            }
+            let command = arguments.get("command").and_then(|v| v.as_str());
+            return match command.map(read_only::check_command) {
+                Some(Err(reason)) => refuse(reason),
+                _ => Ok(()),
+            };
+        }
+        if MUTATING_TOOLS.contains(&tool_name) {
+            return refuse(format!("{} changes files", tool_name));
+        }
+        Ok(())
+    }
+
+    fn check_roots(&self, tool_name: &str, cwd: &str, arguments: &Value) -> Result<(), ToolOutput> {
+        let mut targets: Vec<&str> = ["path", "cwd"]
+            .iter()
+            .filter_map(|key| arguments.get(key).and_then(|v| v.as_str()))
+            .collect();
+        if let Some(files) = arguments.get("files").and_then(|v| v.as_array()) {
+            targets.extend(files.iter().filter_map(|f| f.get("path")?.as_str()));
+        }
+        if targets.is_empty() {
+            targets.push(".");
+        }
+        let roots: Vec<String> = self.roots.iter().map(|r| paths::resolve(cwd, r)).collect();
+        for target in targets {
+            let resolved = paths::resolve(cwd, target);
+            if !roots.iter().any(|root| paths::is_within(&resolved, root)) {
+                return Err(tool_error(
+                    ToolErrorKind::Policy,
+                    format!(
+                        "{} is outside the roots configured for {}",
+                        paths::display(&resolved, cwd),
+                        tool_name
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Routes calls to a [`ToolRegistry`] like soul-core's `DirectExecutor`
+/// (and under the same name, `"direct"`), applying each tool's
+/// [`ToolConfig`] on the way.
+pub struct ConfiguredExecutor {
+    tools: Arc<ToolRegistry>,
+    cwd: String,
+    configs: HashMap<String, ToolConfig>,
+}
+
+impl ConfiguredExecutor {
+    /// Route to `tools`, resolving relative paths in calls and configs
+    /// against `cwd`.
+    pub fn new(tools: Arc<ToolRegistry>, cwd: impl Into<String>) -> Self {
+        Self {
+            tools,
+            cwd: cwd.into(),
+            configs: HashMap::new(),
+        }
+    }
+
+    /// Apply `config` to calls to `tool_name`, replacing any earlier one.
+    pub fn with_config(mut self, tool_name: impl Into<String>, config: ToolConfig) -> Self {
+        self.configs.insert(tool_name.into(), config);
+        self
+    }
+
+    /// Take the configuration of every config tool routed to this executor
+    /// from its `executor_config`. Fails on a config that doesn't parse or
+    /// names a tool the registry lacks.
+    pub fn with_config_tools<'a>(
+        mut self,
+        config_tools: impl IntoIterator<Item = &'a ConfigTool>,
+    ) -> SoulResult<Self> {
+        for config_tool in config_tools {
+            if config_tool.executor_name != self.executor_name() {
+                continue;
+            }
+            let name = &config_tool.definition.name;
+            if self.tools.get(name).is_none() {
+                return Err(SoulError::ToolExecution {
+                    tool_name: name.clone(),
+                    message: format!("Unknown tool: {}", name),
+                });
+            }
+            let config = ToolConfig::from_value(name, &config_tool.executor_config)?;
+            self.configs.insert(name.clone(), config);
+        }
+        Ok(self)
+    }
+
+    /// The configuration applied to `tool_name`, if any.
+    pub fn config(&self, tool_name: &str) -> Option<&ToolConfig> {
+        self.configs.get(tool_name)
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ToolExecutor for ConfiguredExecutor {
+    async fn execute(
+        &self,
+        definition: &ToolDefinition,
+        call_id: &str,
+        mut arguments: Value,
+        partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let name = &definition.name;
+        let tool = self
+            .tools
+            .get(name)
+            .ok_or_else(|| SoulError::ToolExecution {
+                tool_name: name.clone(),
+                message: format!("Unknown tool: {}", name),
+            })?;
+        let Some(config) = self.configs.get(name) else {
+            return tool.execute(call_id, arguments, partial_tx).await;
+        };
+        if let Err(refusal) = config.prepare(name, &self.cwd, &mut arguments) {
+            return Ok(refusal);
+        }
+
+        let mut output = tool.execute(call_id, arguments, partial_tx).await?;
+        if let Some(max_bytes) = config.max_output_bytes {
+            let truncated = truncate_head(&output.content, usize::MAX, max_bytes);
+            if let Some(notice) = truncated.truncation_notice() {
+                output.content = format!("{}\n\n{}", truncated.content, notice);
+                set_metadata(&mut output, "truncated", json!(true));
+            }
+        }
+        Ok(output)
+    }
+
+    fn executor_name(&self) -> &str {
+        "direct"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::{all_tools, wrap_as_configured_executor};
+    use soul_core::vexec::NoopExecutor;
+    use soul_core::vfs::{MemoryFs, VirtualFs};
+
+    fn config_tool(tools: &ToolRegistry, name: &str, config: Value) -> ConfigTool {
+        ConfigTool {
+            definition: tools.get(name).unwrap().definition(),
+            executor_name: "direct".into(),
+            executor_config: config,
+        }
+    }
+
+    #[tokio::test]
+    async fn applies_executor_config() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/w/src/a.txt", "one\ntwo\nthree\n").await.unwrap();
+        fs.write("/w/README.md", "# w\n").await.unwrap();
+        let tools = all_tools(
+            fs.clone() as Arc<dyn VirtualFs>,
+            Arc::new(NoopExecutor),
+            "/w",
+        );
+        let config = vec![
+            config_tool(
+                &tools,
+                "read",
+                json!({"roots": ["src"], "defaults": {"limit": 2}}),
+            ),
+            config_tool(&tools, "write", json!({"read_only": true})),
+            config_tool(&tools, "bash", json!({"read_only": true})),
+            config_tool(&tools, "ls", json!({"max_output_bytes": 10})),
+            config_tool(&tools, "todo", json!({"disabled": true})),
+        ];
+        let registry = wrap_as_configured_executor(tools, "/w", config).unwrap();
+        let call = |name: &'static str, arguments: Value| {
+            let registry = &registry;
+            async move { registry.execute(name, "c", arguments, None).await.unwrap() }
+        };
+
+        let result = call("read", json!({"path": "src/a.txt"})).await;
+        assert!(result.content.contains("two") && !result.content.contains("three"));
+        let result = call("read", json!({"path": "/etc/passwd"})).await;
+        assert_eq!(
+            result.content,
+            "/etc/passwd is outside the roots configured for read"
+        );
+        let result = call("write", json!({"path": "b.txt", "content": "b"})).await;
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::Policy));
+        assert!(!fs.exists("/w/b.txt").await.unwrap());
+        let result = call("bash", json!({"command": "rm -rf src"})).await;
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::Policy));
+        let result = call("ls", json!({})).await;
+        assert!(
+            result.content.starts_with("README.md\n\n[Truncated"),
+            "{}",
+            result.content
+        );
+        let result = call("todo", json!({})).await;
+        assert_eq!(result.content, "todo is disabled by its configuration");
+        let result = call("grep", json!({"pattern": "two"})).await;
+        assert!(!result.is_error, "{}", result.content);
+
+        let tools = all_tools(fs as Arc<dyn VirtualFs>, Arc::new(NoopExecutor), "/w");
+        let config = vec![config_tool(&tools, "read", json!({"root": "src"}))];
+        let error = wrap_as_configured_executor(tools, "/w", config)
+            .err()
+            .unwrap();
+        assert!(error
+            .to_string()
+            .contains("Invalid executor_config: unknown field `root`"));
+    }
+}
@@ -11,11 +11,23 @@ use soul_core::tool::{Tool, ToolOutput};
 use soul_core::types::ToolDefinition;
 use soul_core::vfs::VirtualFs;
 
-use super::resolve_path;
+use crate::error::{tool_error, ToolErrorKind};
+use crate::file_modes::{current_mode, restore_mode, FileModes};
+use crate::locks::PathLocks;
+
+use super::{impl_overrides, resolve_path, ToolOverrides};
+
+/// System-prompt guidance returned by [`AppendTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Add text to the end of a file (a log, changelog or test list) without reading or \
+    resending the rest of it. Use edit to change existing lines.";
 
 pub struct AppendTool {
     fs: Arc<dyn VirtualFs>,
     cwd: String,
+    locks: Arc<PathLocks>,
+    modes: Option<Arc<dyn FileModes>>,
+    overrides: ToolOverrides,
 }
 
 impl AppendTool {
@@ -23,19 +35,38 @@ impl AppendTool {
         Self {
             fs,
             cwd: cwd.into(),
+            locks: Arc::new(PathLocks::new()),
+            modes: None,
+            overrides: ToolOverrides::default(),
         }
     }
+
+    /// Share `locks` with other tools, so that their changes to the same
+    /// file wait for each other (default: a set of this tool's own).
+    pub fn with_locks(mut self, locks: Arc<PathLocks>) -> Self {
+        self.locks = locks;
+        self
+    }
+
+    /// Put back the permissions of files this tool rewrites (default: left
+    /// to the filesystem).
+    pub fn with_file_modes(mut self, modes: Arc<dyn FileModes>) -> Self {
+        self.modes = Some(modes);
+        self
+    }
 }
 
+impl_overrides!(AppendTool);
+
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl Tool for AppendTool {
     fn name(&self) -> &str {
-        "append"
+        self.overrides.name("append")
     }
 
     fn definition(&self) -> ToolDefinition {
-        ToolDefinition {
+        self.overrides.apply(ToolDefinition {
             name: "append".into(),
             description: "Append content to the end of a file. \
                           Creates the file and parent directories if they don't exist. \
@@ -55,7 +86,7 @@ impl Tool for AppendTool {
                 },
                 "required": ["path", "content"]
             }),
-        }
+        })
     }
 
     async fn execute(
@@ -64,6 +95,19 @@ impl Tool for AppendTool {
         arguments: serde_json::Value,
         _partial_tx: Option<mpsc::UnboundedSender<String>>,
     ) -> SoulResult<ToolOutput> {
+        let lock = self.locks.lock_for(&self.cwd, &arguments).await;
+        let mut output = self.apply(arguments).await?;
+        if let Some(lock) = lock {
+            lock.annotate(&mut output);
+        }
+        Ok(output)
+    }
+}
+
+impl AppendTool {
+    /// Carry out a call; [`execute`](Tool::execute) holds the path lock
+    /// around it.
+    async fn apply(&self, arguments: serde_json::Value) -> SoulResult<ToolOutput> {
         // If the provider passed args as a JSON string, unwrap it.
         let arguments = if let Some(s) = arguments.as_str() {
             serde_json::from_str(s).unwrap_or(arguments)
@@ -81,7 +125,10 @@ impl Tool for AppendTool {
             .unwrap_or("");
 
         if path.is_empty() {
-            return Ok(ToolOutput::error("Missing required parameter: path"));
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Missing required parameter: path",
+            ));
         }
 
         let resolved = resolve_path(&self.cwd, path);
@@ -94,27 +141,32 @@ impl Tool for AppendTool {
         }
 
         // Read existing content, then append
-        let existing = match self.fs.read_to_string(&resolved).await {
-            Ok(s) => s,
-            Err(_) => String::new(), // file doesn't exist yet
-        };
+        // Missing file reads as empty (file doesn't exist yet)
+        let existing = self.fs.read_to_string(&resolved).await.unwrap_or_default();
 
         let new_content = format!("{}{}", existing, content);
         let appended_bytes = content.len();
 
+        let mode = current_mode(&self.modes, &resolved).await;
         match self.fs.write(&resolved, &new_content).await {
-            Ok(()) => Ok(ToolOutput::success(format!(
-                "Appended {} bytes to {} ({} bytes total)",
-                appended_bytes,
-                path,
-                new_content.len()
-            ))
-            .with_metadata(json!({
-                "bytes_appended": appended_bytes,
-                "total_bytes": new_content.len(),
-                "path": path,
-            }))),
-            Err(e) => Ok(ToolOutput::error(format!("Failed to append to {}: {}", path, e))),
+            Ok(()) => {
+                restore_mode(&self.modes, &resolved, mode).await;
+                Ok(ToolOutput::success(format!(
+                    "Appended {} bytes to {} ({} bytes total)",
+                    appended_bytes,
+                    path,
+                    new_content.len()
+                ))
+                .with_metadata(json!({
+                    "bytes_appended": appended_bytes,
+                    "total_bytes": new_content.len(),
+                    "path": path,
+                })))
+            }
+            Err(e) => Ok(tool_error(
+                ToolErrorKind::from_soul_error(&e),
+                format!("Failed to append to {}: {}", path, e),
+            )),
         }
     }
 }
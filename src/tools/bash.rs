@@ -1,43 +1,361 @@
 //! Bash tool — execute shell commands with output truncation and timeout.
 //!
 //! Delegates to [`soul_core::executor::ShellExecutor`] for command execution,
-//! then applies ANSI stripping and tail truncation on top.
+//! then applies ANSI stripping and tail truncation on top. With the `pty`
+//! feature, [`BashTool::with_pty`] lets `pty: true` run the command on the
+//! host under a pseudo-terminal instead (see [`crate::pty`]). [`ResourceLimits`] cap CPU time, memory and
+//! captured output per command, and the read-only policy refuses commands
+//! that could modify files (see [`crate::read_only`]). `parse_output`
+//! re-renders JSON and tabular stdout (see [`crate::output_parser`]).
+//! [`BashTool::with_retries`] re-runs commands the executor failed to run at
+//! all (spawn errors, broken pipes); commands that ran and exited non-zero
+//! are never retried.
+//!
+//! With `args`, the command is a template: each `{{name}}` placeholder is
+//! replaced by the matching argument quoted for the configured shell, so
+//! paths with spaces or quotes reach the program as one word.
+//!
+//! Every command that runs is numbered in a [`CommandHistory`] with its exit
+//! code and duration: `history: true` lists them and `rerun: N` runs one
+//! again, so re-running a failing test after a fix doesn't mean rewriting it.
+//! Each result's `command` metadata holds the command line that ran, also
+//! for `argv` and `rerun` calls.
 
-use std::sync::Arc;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use async_trait::async_trait;
+use serde::Serialize;
 use serde_json::json;
 use tokio::sync::mpsc;
 
-use soul_core::error::SoulResult;
+use soul_core::error::{SoulError, SoulResult};
 use soul_core::executor::shell::ShellExecutor;
 use soul_core::executor::ToolExecutor;
 use soul_core::tool::{Tool, ToolOutput};
 use soul_core::types::ToolDefinition;
-use soul_core::vexec::VirtualExecutor;
+use soul_core::vexec::{ExecOutput, VirtualExecutor};
 
-use crate::truncate::{truncate_tail, MAX_BYTES};
+use crate::error::{tool_error, ToolErrorKind};
+use crate::output_parser::{parse_output, OutputParser};
+use crate::read_only;
+use crate::truncate::{truncate_line, truncate_tail, TruncationResult, MAX_BYTES};
+
+use super::scaffold::render_template;
+use super::{impl_overrides, resolve_path, set_metadata, ToolOverrides};
 
 /// Maximum lines kept from bash output (tail).
 const BASH_MAX_LINES: usize = 50;
 
 /// Default command timeout in seconds.
-const DEFAULT_TIMEOUT: u64 = 120;
+pub const DEFAULT_TIMEOUT: u64 = 120;
+
+/// Default upper bound on caller-supplied timeouts, in seconds.
+pub const DEFAULT_MAX_TIMEOUT: u64 = 600;
+
+/// Largest `stdin` accepted, in bytes once shell-quoted. The input travels
+/// inside the single `sh -c` argument, which Linux caps at 128 KiB
+/// (`MAX_ARG_STRLEN`), so half of that is left for the command around it.
+pub const MAX_STDIN_BYTES: usize = 64 * 1024;
+
+/// Interpreter that commands are run under.
+///
+/// The executor receives a POSIX `sh` command line, since executors only
+/// take a command string; other shells are invoked through it with the
+/// command passed as a single quoted argument. Every variant, PowerShell
+/// included, therefore needs an executor providing `sh` (on Windows, e.g.
+/// Git Bash or MSYS2); there is no way to hand the executor an argv.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Shell {
+    /// Pass commands to the executor unchanged (`sh -c` for [`NativeExecutor`]).
+    ///
+    /// [`NativeExecutor`]: soul_core::vexec::NativeExecutor
+    #[default]
+    Sh,
+    Bash,
+    Zsh,
+    PowerShell,
+    /// No shell semantics: commands are split on whitespace and run as argv.
+    /// Commands with quotes or backslashes are refused rather than split
+    /// wrongly; pass `argv` for arguments containing spaces.
+    None,
+}
+
+impl Shell {
+    /// Parse a shell name (`sh`, `bash`, `zsh`, `powershell`/`pwsh`, `none`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sh" => Some(Self::Sh),
+            "bash" => Some(Self::Bash),
+            "zsh" => Some(Self::Zsh),
+            "powershell" | "pwsh" => Some(Self::PowerShell),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    /// Build the command line handed to the executor.
+    fn wrap(&self, command: &str) -> String {
+        match self {
+            Self::Sh => command.to_string(),
+            Self::Bash => format!("bash -c {}", shell_quote(command)),
+            Self::Zsh => format!("zsh -c {}", shell_quote(command)),
+            Self::PowerShell => format!(
+                "powershell -NoProfile -NonInteractive -Command {}",
+                shell_quote(command)
+            ),
+            Self::None => argv_command(command.split_whitespace()),
+        }
+    }
+
+    /// Quote `word` so this shell passes it through as a single argument.
+    fn quote(&self, word: &str) -> String {
+        match self {
+            Self::PowerShell => format!("'{}'", word.replace('\'', "''")),
+            _ => shell_quote(word),
+        }
+    }
+}
+
+/// Per-command resource limits, all off by default.
+///
+/// CPU time and memory are enforced by the shell (`ulimit -t` / `ulimit -v`,
+/// i.e. `RLIMIT_CPU` / `RLIMIT_AS`) for the command and everything it spawns,
+/// so they need an executor running a POSIX shell on a Unix host; elsewhere
+/// they are skipped and reported as not applied. The output cap is applied
+/// to what the executor captured, keeping the most recent bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    pub cpu_secs: Option<u64>,
+    pub memory_bytes: Option<u64>,
+    pub max_output_bytes: Option<usize>,
+}
+
+impl ResourceLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Kill commands after `secs` seconds of CPU time.
+    pub fn with_cpu_secs(mut self, secs: u64) -> Self {
+        self.cpu_secs = Some(secs.max(1));
+        self
+    }
+
+    /// Cap each process's address space at `bytes`.
+    pub fn with_memory_bytes(mut self, bytes: u64) -> Self {
+        self.memory_bytes = Some(bytes);
+        self
+    }
+
+    /// Keep at most `bytes` of each captured output stream.
+    pub fn with_max_output_bytes(mut self, bytes: usize) -> Self {
+        self.max_output_bytes = Some(bytes);
+        self
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// `ulimit` prelude for the process limits, when the host supports them.
+    fn shell_prelude(&self) -> Option<String> {
+        if !cfg!(unix) {
+            return None;
+        }
+        let mut prelude = String::new();
+        if let Some(secs) = self.cpu_secs {
+            prelude.push_str(&format!("ulimit -t {}; ", secs));
+        }
+        if let Some(bytes) = self.memory_bytes {
+            prelude.push_str(&format!("ulimit -v {}; ", (bytes / 1024).max(1)));
+        }
+        (!prelude.is_empty()).then_some(prelude)
+    }
+
+    /// Prefix `command` with the process limits; returns whether they apply.
+    fn apply(&self, command: &mut String) -> bool {
+        match self.shell_prelude() {
+            Some(prelude) => {
+                command.insert_str(0, &prelude);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Keep the last `max_output_bytes` of `text`; returns whether it was cut.
+    fn cap_output(&self, text: &str) -> (String, bool) {
+        match self.max_output_bytes {
+            Some(max) if text.len() > max => {
+                let mut start = text.len() - max;
+                while !text.is_char_boundary(start) {
+                    start += 1;
+                }
+                (
+                    format!(
+                        "[Output exceeded the {}-byte limit; earlier output dropped]\n{}",
+                        max,
+                        &text[start..]
+                    ),
+                    true,
+                )
+            }
+            _ => (text.to_string(), false),
+        }
+    }
+
+    fn to_metadata(self, applied: bool, output_capped: bool) -> serde_json::Value {
+        json!({
+            "cpu_secs": self.cpu_secs,
+            "memory_bytes": self.memory_bytes,
+            "max_output_bytes": self.max_output_bytes,
+            "process_limits_applied": applied,
+            "output_capped": output_capped,
+        })
+    }
+}
+
+/// Commands kept in a [`CommandHistory`]; older ones are dropped.
+pub const MAX_HISTORY: usize = 100;
+
+/// Characters of each command shown in the history listing.
+const HISTORY_COMMAND_CHARS: usize = 120;
+
+/// A command bash ran.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryEntry {
+    /// Number to pass as `rerun`, counting from 1 for the session.
+    pub index: usize,
+    /// The command line, after `args` substitution (argv joined and quoted).
+    pub command: String,
+    /// `None` when the command timed out or couldn't be run.
+    pub exit_code: Option<i32>,
+    /// `None` under WASM, which has no clock.
+    pub duration_ms: Option<u64>,
+    /// The call's arguments, replayed by `rerun`.
+    #[serde(skip)]
+    pub arguments: serde_json::Value,
+}
+
+/// The commands a bash tool ran this session, newest last.
+#[derive(Debug, Default)]
+pub struct CommandHistory {
+    entries: Mutex<VecDeque<HistoryEntry>>,
+}
+
+impl CommandHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The recorded commands, oldest first (at most [`MAX_HISTORY`]).
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// The command numbered `index`, if it is still kept.
+    pub fn get(&self, index: usize) -> Option<HistoryEntry> {
+        let entries = self.entries.lock().unwrap();
+        entries.iter().find(|e| e.index == index).cloned()
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Record a command and return its number.
+    fn record(
+        &self,
+        command: String,
+        exit_code: Option<i32>,
+        duration_ms: Option<u64>,
+        arguments: serde_json::Value,
+    ) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let index = entries.back().map_or(1, |e| e.index + 1);
+        if entries.len() == MAX_HISTORY {
+            entries.pop_front();
+        }
+        entries.push_back(HistoryEntry {
+            index,
+            command,
+            exit_code,
+            duration_ms,
+            arguments,
+        });
+        index
+    }
+
+    /// The listing returned for `history: true`.
+    fn to_output(&self) -> ToolOutput {
+        let entries = self.entries();
+        if entries.is_empty() {
+            return ToolOutput::success("No commands run yet")
+                .with_metadata(json!({"history": []}));
+        }
+        let lines: Vec<String> = entries
+            .iter()
+            .map(|e| {
+                let exit = e
+                    .exit_code
+                    .map_or_else(|| "exit ?".to_string(), |code| format!("exit {}", code));
+                let duration = match e.duration_ms {
+                    Some(ms) if ms < 1000 => format!("{}ms", ms),
+                    Some(ms) => format!("{:.1}s", ms as f64 / 1000.0),
+                    None => "-".to_string(),
+                };
+                let mut command = e.command.lines().next().unwrap_or_default().to_string();
+                if e.command.contains('\n') {
+                    command.push_str(" ...");
+                }
+                format!(
+                    "{:>3}  {:<8} {:>6}  {}",
+                    e.index,
+                    exit,
+                    duration,
+                    truncate_line(&command, HISTORY_COMMAND_CHARS)
+                )
+            })
+            .collect();
+        ToolOutput::success(lines.join("\n")).with_metadata(json!({"history": entries}))
+    }
+}
+
+/// System-prompt guidance returned by [`BashTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Run builds, tests, git and other programs. Don't use it to read, search or change \
+    files (cat, grep, find, sed): the dedicated tools are faster and their output is \
+    easier to work with. Only the last 50 lines of output are kept, so filter noisy \
+    commands. Pass argv for arguments containing quotes or spaces, and stdin instead of \
+    heredocs.";
 
 pub struct BashTool {
-    shell: ShellExecutor,
+    executor: Arc<dyn VirtualExecutor>,
+    cwd: String,
     definition: ToolDefinition,
+    interpreter: Shell,
+    default_timeout: u64,
+    max_timeout: u64,
+    limits: ResourceLimits,
+    read_only: bool,
+    retries: u32,
+    retry_backoff: Duration,
+    history: Arc<CommandHistory>,
+    #[cfg(feature = "pty")]
+    pty: bool,
+    #[cfg(feature = "pty")]
+    pty_size: crate::pty::TerminalSize,
+    overrides: ToolOverrides,
 }
 
 impl BashTool {
     pub fn new(executor: Arc<dyn VirtualExecutor>, cwd: impl Into<String>) -> Self {
-        let shell = ShellExecutor::new(executor)
-            .with_timeout(DEFAULT_TIMEOUT)
-            .with_cwd(cwd);
-
         let definition = ToolDefinition {
             name: "bash".into(),
-            description: "Execute a shell command. Returns stdout and stderr. Output is truncated to the last 50 lines.".into(),
+            description: "Execute a shell command, or a program with arguments via argv (no shell quoting). Put file names and other values in args and refer to them as {{name}} in command to have them quoted for you. Returns stdout and stderr (set separate_streams to label them separately). Output is truncated to the last 50 lines. Pass history to list earlier commands and rerun to repeat one by number, e.g. a failing test after a fix.".into(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -45,17 +363,373 @@ impl BashTool {
                         "type": "string",
                         "description": "The shell command to execute"
                     },
+                    "argv": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "Program and arguments to run directly, without shell parsing (alternative to command; no quoting needed)"
+                    },
+                    "args": {
+                        "type": "object",
+                        "description": "Values for {{name}} placeholders in command, each substituted as one safely quoted word (an array becomes one word per element). Don't add quotes around placeholders, e.g. command \"wc -l {{file}}\" with args {\"file\": \"my notes.txt\"}"
+                    },
+                    "cwd": {
+                        "type": "string",
+                        "description": "Directory to run in (default: the working directory)"
+                    },
                     "timeout": {
                         "type": "integer",
-                        "description": "Timeout in seconds (default: 120)"
+                        "description": "Timeout in seconds"
+                    },
+                    "stdin": {
+                        "type": "string",
+                        "description": "Text passed to the command's standard input (avoids heredocs and temp files); up to 64 KiB, larger input belongs in a file"
+                    },
+                    "separate_streams": {
+                        "type": "boolean",
+                        "description": "Return stdout and stderr as separately labeled, separately truncated sections (default: false)"
+                    },
+                    "parse_output": {
+                        "type": "string",
+                        "enum": ["none", "auto", "json", "table"],
+                        "description": "Re-render successful stdout: json pretty-prints JSON (compacted if too long), table aligns TSV/CSV columns, auto tries both (default: none)"
+                    },
+                    "history": {
+                        "type": "boolean",
+                        "description": "List the commands run so far, numbered, with exit codes and durations, instead of running one"
+                    },
+                    "rerun": {
+                        "type": "integer",
+                        "description": "Run command number N from history again, with the same command, args, stdin and cwd; other arguments given override the recorded ones"
                     }
                 },
-                "required": ["command"]
+                "required": []
             }),
         };
+        Self {
+            executor,
+            cwd: cwd.into(),
+            definition,
+            interpreter: Shell::default(),
+            default_timeout: DEFAULT_TIMEOUT,
+            max_timeout: DEFAULT_MAX_TIMEOUT,
+            limits: ResourceLimits::default(),
+            read_only: false,
+            retries: 0,
+            retry_backoff: Duration::ZERO,
+            history: Arc::new(CommandHistory::new()),
+            #[cfg(feature = "pty")]
+            pty: false,
+            #[cfg(feature = "pty")]
+            pty_size: crate::pty::TerminalSize::default(),
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+    /// Accept `pty: true`, which runs the command on the host under a
+    /// pseudo-terminal instead of through the executor (see [`crate::pty`]).
+    ///
+    /// Off by default: a PTY run bypasses the injected executor and any
+    /// sandbox behind it, so only enable it where the host is the sandbox.
+    #[cfg(feature = "pty")]
+    pub fn with_pty(mut self) -> Self {
+        if self.pty {
+            return self;
+        }
+        self.pty = true;
+        let definition = &mut self.definition;
+        definition.description.push_str(" Set pty for commands that need a terminal (watch modes, prompts answered via answers).");
+        let properties = &mut definition.input_schema["properties"];
+        properties["pty"] = json!({
+            "type": "boolean",
+            "description": "Run under a pseudo-terminal so the command sees a TTY; stdout and stderr are merged (default: false)"
+        });
+        properties["answers"] = json!({
+            "type": "array",
+            "items": {"type": "string"},
+            "description": "Lines typed into the terminal, in order, for interactive prompts (pty only)"
+        });
+        properties["cols"] = json!({
+            "type": "integer",
+            "description": "Terminal width in columns (pty only)"
+        });
+        properties["rows"] = json!({
+            "type": "integer",
+            "description": "Terminal height in rows (pty only)"
+        });
+        self
+    }
+
+    /// Terminal size for `pty` calls that don't pass cols/rows
+    /// (default: 120x40).
+    #[cfg(feature = "pty")]
+    pub fn with_pty_size(mut self, cols: u16, rows: u16) -> Self {
+        self.pty_size = crate::pty::TerminalSize { cols, rows };
+        self
+    }
+
+    /// Timeout used when the caller doesn't pass one (default: 120s).
+    pub fn with_default_timeout(mut self, secs: u64) -> Self {
+        self.default_timeout = secs.max(1);
+        self
+    }
+
+    /// Upper bound applied to every timeout, caller-supplied or default
+    /// (default: 600s).
+    pub fn with_max_timeout(mut self, secs: u64) -> Self {
+        self.max_timeout = secs.max(1);
+        self
+    }
+
+    /// Effective timeout for a call requesting `requested` seconds.
+    fn timeout_for(&self, requested: Option<u64>) -> u64 {
+        requested
+            .unwrap_or(self.default_timeout)
+            .clamp(1, self.max_timeout)
+    }
+
+    /// Apply CPU, memory and output limits to every command (default: none).
+    pub fn with_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Run commands under a specific shell instead of the executor's default.
+    pub fn with_shell(mut self, shell: Shell) -> Self {
+        self.interpreter = shell;
+        self
+    }
+
+    /// Refuse any command [`read_only::check_command`] can't prove harmless:
+    /// programs off the allowlist, redirections into files, command
+    /// substitution.
+    pub fn with_read_only_policy(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Re-run a command up to `retries` times when the executor fails to run
+    /// it (spawn errors, broken pipes), waiting `backoff` before the first
+    /// retry and twice as long before each one after (default: no retries).
+    /// Timeouts and non-zero exits are returned as they are. Under WASM
+    /// retries happen without waiting.
+    pub fn with_retries(mut self, retries: u32, backoff: Duration) -> Self {
+        self.retries = retries;
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Record commands in `history`, e.g. to read it from the host or share
+    /// it with another bash tool (default: a history of this tool's own).
+    pub fn with_history(mut self, history: Arc<CommandHistory>) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Run `attempt` until it succeeds or fails with an error that isn't
+    /// [retryable](is_retryable), returning the result and the retries used.
+    async fn retrying<T, F, Fut>(&self, mut attempt: F) -> (SoulResult<T>, u32)
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = SoulResult<T>>,
+    {
+        let mut retries = 0;
+        let mut backoff = self.retry_backoff;
+        loop {
+            match attempt().await {
+                Err(e) if retries < self.retries && is_retryable(&e) => {
+                    retries += 1;
+                    #[cfg(feature = "native")]
+                    if !backoff.is_zero() {
+                        tokio::time::sleep(backoff).await;
+                    }
+                    backoff = backoff.saturating_mul(2);
+                }
+                result => return (result, retries),
+            }
+        }
+    }
+
+    /// Error output for an executor failure, noting any retries.
+    fn executor_error(&self, e: &SoulError, retries: u32) -> ToolOutput {
+        let mut output = if retries > 0 {
+            tool_error(
+                ToolErrorKind::from_soul_error(e),
+                format!("Command failed after {} retries: {}", retries, e),
+            )
+        } else {
+            tool_error(
+                ToolErrorKind::from_soul_error(e),
+                format!("Command failed: {}", e),
+            )
+        };
+        if self.retries > 0 {
+            set_metadata(&mut output, "retries", json!(retries));
+        }
+        output
+    }
+}
+
+impl_overrides!(BashTool);
+
+/// Whether the executor failed to run a command at all (as opposed to it
+/// timing out), so that running it again may succeed.
+fn is_retryable(error: &SoulError) -> bool {
+    matches!(
+        ToolErrorKind::from_soul_error(error),
+        ToolErrorKind::ExecutorFailure | ToolErrorKind::Io
+    )
+}
+
+/// Quote a string for POSIX shells: wrap in single quotes, escaping embedded ones.
+pub(super) fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Join argv into a command line whose words reach the program verbatim:
+/// no globbing, expansion or operators.
+fn argv_command<'a>(argv: impl IntoIterator<Item = &'a str>) -> String {
+    argv.into_iter()
+        .map(shell_quote)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Fill the `{{name}}` placeholders of `template` with `args`, each quoted
+/// for `shell`. Strings, numbers and booleans become one word, arrays of
+/// them one word per element. Every placeholder needs an argument and every
+/// argument a placeholder.
+fn render_command(
+    template: &str,
+    args: &serde_json::Map<String, serde_json::Value>,
+    shell: Shell,
+) -> Result<String, String> {
+    let word = |name: &str, value: &serde_json::Value| match value {
+        serde_json::Value::String(s) => Ok(shell.quote(s)),
+        serde_json::Value::Number(n) => Ok(shell.quote(&n.to_string())),
+        serde_json::Value::Bool(b) => Ok(shell.quote(&b.to_string())),
+        _ => Err(format!(
+            "args.{} must be a string, number, boolean or an array of them",
+            name
+        )),
+    };
+    let mut quoted = BTreeMap::new();
+    for (name, value) in args {
+        let text = match value {
+            serde_json::Value::Array(items) => items
+                .iter()
+                .map(|item| word(name, item))
+                .collect::<Result<Vec<_>, _>>()?
+                .join(" "),
+            value => word(name, value)?,
+        };
+        quoted.insert(name.clone(), text);
+    }
+
+    let mut placeholders = BTreeSet::new();
+    render_template(template, &BTreeMap::new(), &mut placeholders);
+    let unused: Vec<&str> = quoted
+        .keys()
+        .filter(|name| !placeholders.contains(*name))
+        .map(String::as_str)
+        .collect();
+    if !unused.is_empty() {
+        return Err(format!(
+            "args not used by the command: {} (write them as {{{{name}}}})",
+            unused.join(", ")
+        ));
+    }
+    let mut missing = BTreeSet::new();
+    let command = render_template(template, &quoted, &mut missing);
+    if !missing.is_empty() {
+        let names: Vec<String> = missing.into_iter().collect();
+        return Err(format!("Missing args for placeholders: {}", names.join(", ")));
+    }
+    Ok(command)
+}
 
-        Self { shell, definition }
+/// Rewrite `command` so it reads `stdin` from a pipe.
+///
+/// The executor only takes a command string, so the input is embedded in it
+/// and fed through the shell's `printf` builtin (byte-exact, no trailing
+/// newline). It counts against the argument size limit of the command
+/// string; callers check [`MAX_STDIN_BYTES`] first.
+fn with_stdin(command: &str, stdin: &str) -> String {
+    format!("printf '%s' {} | {{\n{}\n}}", shell_quote(stdin), command)
+}
+
+/// Clean and tail-truncate one output stream, returning the text and
+/// its truncation metadata.
+fn clean_stream(raw: &str) -> (String, TruncationResult) {
+    let cleaned = strip_ansi(raw);
+    let truncated = truncate_tail(&cleaned, BASH_MAX_LINES, MAX_BYTES);
+    let mut content = truncated.content.trim_end_matches('\n').to_string();
+    if let Some(notice) = truncated.truncation_notice() {
+        content = format!("{}\n{}", notice, content);
+    }
+    if content.is_empty() {
+        content = "(empty)".into();
+    }
+    (content, truncated)
+}
+
+/// Render an exec result with labeled stdout and stderr sections, returning
+/// whether `limits` cut either stream.
+fn separate_streams_output(
+    output: &ExecOutput,
+    limits: &ResourceLimits,
+    parser: OutputParser,
+) -> (ToolOutput, bool) {
+    let (raw_stdout, stdout_capped) = limits.cap_output(&output.stdout);
+    let (raw_stderr, stderr_capped) = limits.cap_output(&output.stderr);
+    let parsed = output
+        .success()
+        .then(|| parse_output(&strip_ansi(&raw_stdout), parser, BASH_MAX_LINES, MAX_BYTES))
+        .flatten();
+    let (stdout, stdout_truncation) = match &parsed {
+        Some((text, _)) => clean_stream(text),
+        None => clean_stream(&raw_stdout),
+    };
+    let (stderr, stderr_truncation) = clean_stream(&raw_stderr);
+    let stdout_truncated = stdout_truncation.is_truncated();
+    let stderr_truncated = stderr_truncation.is_truncated();
+
+    let mut content = String::new();
+    if !output.success() {
+        content.push_str(&format!("Exit code: {}\n", output.exit_code));
     }
+    content.push_str(&format!(
+        "--- stdout ---\n{}\n--- stderr ---\n{}",
+        stdout, stderr
+    ));
+
+    let mut tool_output = if output.success() {
+        ToolOutput::success(content)
+    } else {
+        tool_error(ToolErrorKind::CommandFailed, content)
+    };
+    set_metadata(&mut tool_output, "exit_code", json!(output.exit_code));
+    set_metadata(&mut tool_output, "stdout_bytes", json!(output.stdout.len()));
+    set_metadata(&mut tool_output, "stderr_bytes", json!(output.stderr.len()));
+    set_metadata(&mut tool_output, "stdout_truncated", json!(stdout_truncated));
+    set_metadata(&mut tool_output, "stderr_truncated", json!(stderr_truncated));
+    if let Some((_, format)) = parsed {
+        set_metadata(&mut tool_output, "parsed_as", json!(format));
+    }
+    set_metadata(
+        &mut tool_output,
+        "truncated",
+        json!(stdout_truncated || stderr_truncated),
+    );
+    // One entry per stream, since each is truncated independently
+    set_metadata(
+        &mut tool_output,
+        "truncation",
+        json!({
+            "stdout": stdout_truncation.to_metadata(),
+            "stderr": stderr_truncation.to_metadata(),
+        }),
+    );
+    (tool_output, stdout_capped || stderr_capped)
 }
 
 /// Strip ANSI escape codes from output.
@@ -86,27 +760,270 @@ fn strip_ansi(input: &str) -> String {
     result
 }
 
-#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
-#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
-impl Tool for BashTool {
-    fn name(&self) -> &str {
-        "bash"
+#[cfg(feature = "pty")]
+impl BashTool {
+    /// PTY mode: run on the host under a pseudo-terminal, feeding `answers`
+    /// to its input.
+    async fn execute_pty(
+        &self,
+        command: &str,
+        arguments: &serde_json::Value,
+        cwd: &str,
+        timeout: u64,
+        limits_applied: bool,
+    ) -> ToolOutput {
+        use crate::pty::{run_in_pty, TerminalSize};
+
+        let answers: Vec<String> = arguments
+            .get("answers")
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|v| v.as_str()).map(String::from).collect())
+            .unwrap_or_default();
+        let dimension = |key: &str, default: u16| {
+            arguments
+                .get(key)
+                .and_then(|v| v.as_u64())
+                .map(|v| v.clamp(1, u16::MAX as u64) as u16)
+                .unwrap_or(default)
+        };
+        let size = TerminalSize {
+            cols: dimension("cols", self.pty_size.cols),
+            rows: dimension("rows", self.pty_size.rows),
+        };
+
+        let output = match run_in_pty(
+            command,
+            cwd,
+            size,
+            &answers,
+            std::time::Duration::from_secs(timeout),
+        )
+        .await
+        {
+            Ok(output) => output,
+            Err(e) => return tool_error(ToolErrorKind::ExecutorFailure, e),
+        };
+
+        let (raw, output_capped) = self.limits.cap_output(&output.output);
+        let (content, truncation) = clean_stream(&raw);
+        let mut tool_output = match output.exit_code {
+            _ if output.timed_out => tool_error(
+                ToolErrorKind::Timeout,
+                format!("Command timed out after {}s\n{}", timeout, content),
+            ),
+            Some(0) => ToolOutput::success(content),
+            Some(code) => tool_error(
+                ToolErrorKind::CommandFailed,
+                format!("Exit code: {}\n{}", code, content),
+            ),
+            None => tool_error(ToolErrorKind::CommandFailed, content),
+        };
+        set_metadata(&mut tool_output, "pty", json!(true));
+        set_metadata(&mut tool_output, "exit_code", json!(output.exit_code));
+        set_metadata(&mut tool_output, "timed_out", json!(output.timed_out));
+        set_metadata(&mut tool_output, "cols", json!(size.cols));
+        set_metadata(&mut tool_output, "rows", json!(size.rows));
+        set_metadata(&mut tool_output, "truncated", json!(truncation.is_truncated()));
+        set_metadata(&mut tool_output, "truncation", truncation.to_metadata());
+        set_metadata(&mut tool_output, "timeout_secs", json!(timeout));
+        if !self.limits.is_empty() {
+            set_metadata(
+                &mut tool_output,
+                "limits",
+                self.limits.to_metadata(limits_applied, output_capped),
+            );
+        }
+        tool_output
     }
+}
 
-    fn definition(&self) -> ToolDefinition {
-        self.definition.clone()
+impl BashTool {
+    /// The command a call asks for, with `args` substituted into its
+    /// template, and whether it goes through the shell (`false` for argv).
+    fn command_line(&self, arguments: &serde_json::Value) -> Result<(String, bool), ToolOutput> {
+        // argv bypasses shell parsing; otherwise apply the configured shell
+        let command = match arguments.get("argv").and_then(|v| v.as_array()) {
+            Some(argv) => {
+                let words: Option<Vec<&str>> = argv.iter().map(|v| v.as_str()).collect();
+                match words {
+                    Some(words) if !words.is_empty() => Some((argv_command(words), false)),
+                    _ => {
+                        return Err(tool_error(
+                            ToolErrorKind::InvalidArguments,
+                            "argv must be a non-empty array of strings",
+                        ))
+                    }
+                }
+            }
+            None => arguments
+                .get("command")
+                .and_then(|v| v.as_str())
+                .map(|c| (c.to_string(), true)),
+        };
+        let Some((mut command, use_shell)) = command else {
+            return Err(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Missing required parameter: command (or argv)",
+            ));
+        };
+
+        // Templated command: substitute quoted args before anything else
+        if let Some(args) = arguments.get("args").filter(|v| !v.is_null()) {
+            let Some(args) = args.as_object().filter(|_| use_shell) else {
+                return Err(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    "args must be an object, used with command (argv needs no quoting)",
+                ));
+            };
+            if self.interpreter == Shell::None {
+                return Err(tool_error(
+                    ToolErrorKind::Unsupported,
+                    "args needs a shell to quote for; pass argv instead",
+                ));
+            }
+            command = match render_command(&command, args, self.interpreter) {
+                Ok(command) => command,
+                Err(message) => return Err(tool_error(ToolErrorKind::InvalidArguments, message)),
+            };
+        }
+        Ok((command, use_shell))
     }
 
-    async fn execute(
+    /// Run `command`, from [`Self::command_line`], with the options in
+    /// `arguments`.
+    async fn run(
         &self,
         call_id: &str,
-        arguments: serde_json::Value,
+        mut arguments: serde_json::Value,
+        command: String,
+        use_shell: bool,
         partial_tx: Option<mpsc::UnboundedSender<String>>,
     ) -> SoulResult<ToolOutput> {
+        // Vet what the caller wrote, before any wrapping of our own
+        if self.read_only {
+            if let Err(reason) = read_only::check_command(&command) {
+                return Ok(tool_error(
+                    ToolErrorKind::Policy,
+                    format!("Command refused in read-only mode: {}", reason),
+                ));
+            }
+        }
+        if use_shell
+            && self.interpreter == Shell::None
+            && command.contains(['\'', '"', '\\', '`'])
+        {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Commands are split on whitespace (no shell), so quotes and backslashes can't be used; pass argv instead",
+            ));
+        }
+        let mut command = if use_shell {
+            self.interpreter.wrap(&command)
+        } else {
+            command
+        };
+
+        if let Some(stdin) = arguments.get("stdin").and_then(|v| v.as_str()) {
+            let quoted = shell_quote(stdin).len();
+            if quoted > MAX_STDIN_BYTES {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!(
+                        "stdin is too large: {} bytes once quoted (max {}); write it to a file and redirect it instead",
+                        quoted, MAX_STDIN_BYTES
+                    ),
+                ));
+            }
+            command = with_stdin(&command, stdin);
+        }
+        let limits_applied = self.limits.apply(&mut command);
+        arguments["command"] = json!(command);
+
+        let timeout = self.timeout_for(arguments.get("timeout").and_then(|v| v.as_u64()));
+        let cwd = arguments
+            .get("cwd")
+            .and_then(|v| v.as_str())
+            .map_or_else(|| self.cwd.clone(), |dir| resolve_path(&self.cwd, dir));
+
+        let parser = match arguments.get("parse_output").and_then(|v| v.as_str()) {
+            Some(name) => match OutputParser::from_name(name) {
+                Some(parser) => parser,
+                None => {
+                    return Ok(tool_error(
+                        ToolErrorKind::InvalidArguments,
+                        format!(
+                            "Unknown parse_output: {} (expected none, auto, json or table)",
+                            name
+                        ),
+                    ))
+                }
+            },
+            None => OutputParser::None,
+        };
+
+        // ShellExecutor drops stderr on success, so run the command directly
+        let separate_streams = arguments
+            .get("separate_streams")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        #[cfg(feature = "pty")]
+        if arguments.get("pty").and_then(|v| v.as_bool()).unwrap_or(false) {
+            if !self.pty {
+                return Ok(tool_error(
+                    ToolErrorKind::Policy,
+                    "pty is not enabled for this tool",
+                ));
+            }
+            if separate_streams {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    "pty merges stdout and stderr; it cannot be combined with separate_streams",
+                ));
+            }
+            let tool_output = self
+                .execute_pty(&command, &arguments, &cwd, timeout, limits_applied)
+                .await;
+            if let Some(ref tx) = partial_tx {
+                let _ = tx.send(tool_output.content.clone());
+            }
+            return Ok(tool_output);
+        }
+        if separate_streams {
+            let (result, retries) = self
+                .retrying(|| self.executor.exec_shell(&command, timeout, Some(&cwd)))
+                .await;
+            return match result {
+                Ok(output) => {
+                    let (mut tool_output, output_capped) =
+                        separate_streams_output(&output, &self.limits, parser);
+                    if let Some(ref tx) = partial_tx {
+                        let _ = tx.send(tool_output.content.clone());
+                    }
+                    set_metadata(&mut tool_output, "timeout_secs", json!(timeout));
+                    if self.retries > 0 {
+                        set_metadata(&mut tool_output, "retries", json!(retries));
+                    }
+                    if !self.limits.is_empty() {
+                        set_metadata(
+                            &mut tool_output,
+                            "limits",
+                            self.limits.to_metadata(limits_applied, output_capped),
+                        );
+                    }
+                    Ok(tool_output)
+                }
+                Err(e) => Ok(self.executor_error(&e, retries)),
+            };
+        }
+
         // Delegate to ShellExecutor from soul-core
-        let result = self
-            .shell
-            .execute(&self.definition, call_id, arguments, partial_tx.clone())
+        let shell = ShellExecutor::new(self.executor.clone())
+            .with_timeout(timeout)
+            .with_cwd(&cwd);
+        let definition = self.definition();
+        let (result, retries) = self
+            .retrying(|| shell.execute(&definition, call_id, arguments.clone(), partial_tx.clone()))
             .await;
 
         match result {
@@ -116,31 +1033,178 @@ impl Tool for BashTool {
                     let _ = tx.send(output.content.clone());
                 }
 
-                // Apply ANSI stripping
-                let cleaned = strip_ansi(&output.content);
+                // Apply the output cap, then ANSI stripping
+                let (raw, output_capped) = self.limits.cap_output(&output.content);
+                let mut cleaned = strip_ansi(&raw);
+
+                // Re-render structured stdout before truncation sees it
+                let mut parsed_as = None;
+                if !output.is_error {
+                    if let Some((text, format)) =
+                        parse_output(&cleaned, parser, BASH_MAX_LINES, MAX_BYTES)
+                    {
+                        cleaned = text;
+                        parsed_as = Some(format);
+                    }
+                }
 
                 // Apply tail truncation (errors/final output matter most)
                 let truncated = truncate_tail(&cleaned, BASH_MAX_LINES, MAX_BYTES);
 
                 let notice = truncated.truncation_notice();
                 let is_truncated = truncated.is_truncated();
+                let truncation = truncated.to_metadata();
                 let mut result_content = truncated.content;
                 if let Some(notice) = notice {
                     result_content = format!("{}\n{}", notice, result_content);
                 }
 
-                let tool_output = if output.is_error {
-                    ToolOutput::error(result_content)
+                let mut tool_output = if output.is_error {
+                    tool_error(ToolErrorKind::CommandFailed, result_content)
                 } else {
                     ToolOutput::success(result_content)
                 };
+                // ShellExecutor reports a failure's exit code in the text only
+                let exit_code = match output.is_error {
+                    false => Some(0),
+                    true => output
+                        .content
+                        .strip_prefix("Exit code: ")
+                        .and_then(|rest| rest.split('\n').next())
+                        .and_then(|code| code.parse::<i32>().ok()),
+                };
+                set_metadata(&mut tool_output, "exit_code", json!(exit_code));
+                set_metadata(&mut tool_output, "truncated", json!(is_truncated));
+                set_metadata(&mut tool_output, "truncation", truncation);
+                set_metadata(&mut tool_output, "timeout_secs", json!(timeout));
+                if self.retries > 0 {
+                    set_metadata(&mut tool_output, "retries", json!(retries));
+                }
+                if let Some(format) = parsed_as {
+                    set_metadata(&mut tool_output, "parsed_as", json!(format));
+                }
+                if !self.limits.is_empty() {
+                    set_metadata(
+                        &mut tool_output,
+                        "limits",
+                        self.limits.to_metadata(limits_applied, output_capped),
+                    );
+                }
+
+                Ok(tool_output)
+            }
+            Err(e) => Ok(self.executor_error(&e, retries)),
+        }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for BashTool {
+    fn name(&self) -> &str {
+        self.overrides.name("bash")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        // Advertise the configured bounds so models pick sensible timeouts
+        let default = self.timeout_for(None);
+        let mut definition = self.definition.clone();
+        definition.description.push_str(&format!(
+            " Timeout defaults to {}s, max {}s.",
+            default, self.max_timeout
+        ));
+        definition.input_schema["properties"]["timeout"]["description"] = json!(format!(
+            "Timeout in seconds (default: {}, max: {})",
+            default, self.max_timeout
+        ));
+        self.overrides.apply(definition)
+    }
+
+    async fn execute(
+        &self,
+        call_id: &str,
+        arguments: serde_json::Value,
+        partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        if arguments.get("history").and_then(|v| v.as_bool()) == Some(true) {
+            return Ok(self.history.to_output());
+        }
 
-                Ok(tool_output.with_metadata(json!({
-                    "truncated": is_truncated,
-                })))
+        let rerun = arguments.get("rerun").filter(|v| !v.is_null());
+        let (arguments, rerun) = match rerun {
+            None => (arguments, None),
+            Some(index) => {
+                let Some(index) = index.as_u64().map(|i| i as usize) else {
+                    return Ok(tool_error(
+                        ToolErrorKind::InvalidArguments,
+                        "rerun must be a command number from history",
+                    ));
+                };
+                if arguments.get("command").is_some() || arguments.get("argv").is_some() {
+                    return Ok(tool_error(
+                        ToolErrorKind::InvalidArguments,
+                        "rerun repeats a recorded command; don't pass command or argv with it",
+                    ));
+                }
+                let Some(entry) = self.history.get(index) else {
+                    return Ok(tool_error(
+                        ToolErrorKind::NotFound,
+                        format!(
+                            "No command #{} in history; pass history: true to list them",
+                            index
+                        ),
+                    ));
+                };
+                // Options given with rerun override the recorded ones
+                let mut recorded = entry.arguments;
+                if let (Some(recorded), Some(given)) =
+                    (recorded.as_object_mut(), arguments.as_object())
+                {
+                    for (key, value) in given.iter().filter(|(key, _)| *key != "rerun") {
+                        recorded.insert(key.clone(), value.clone());
+                    }
+                }
+                (recorded, Some(index))
             }
-            Err(e) => Ok(ToolOutput::error(format!("Command failed: {}", e))),
+        };
+
+        let (command, use_shell) = match self.command_line(&arguments) {
+            Ok(command) => command,
+            Err(output) => return Ok(output),
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        let started = std::time::Instant::now();
+        let mut output = self
+            .run(call_id, arguments.clone(), command.clone(), use_shell, partial_tx)
+            .await?;
+        set_metadata(&mut output, "command", json!(command));
+        #[cfg(not(target_arch = "wasm32"))]
+        let duration_ms = Some(started.elapsed().as_millis() as u64);
+        #[cfg(target_arch = "wasm32")]
+        let duration_ms = None;
+
+        // Calls refused before running anything are not history
+        let refused = matches!(
+            ToolErrorKind::of(&output),
+            Some(
+                ToolErrorKind::InvalidArguments
+                    | ToolErrorKind::Policy
+                    | ToolErrorKind::Unsupported
+            )
+        );
+        if !refused {
+            let exit_code = output
+                .metadata
+                .get("exit_code")
+                .and_then(|v| v.as_i64())
+                .map(|code| code as i32);
+            let index = self.history.record(command, exit_code, duration_ms, arguments);
+            set_metadata(&mut output, "history_index", json!(index));
         }
+        if let Some(index) = rerun {
+            set_metadata(&mut output, "rerun", json!(index));
+        }
+        Ok(output)
     }
 }
 
@@ -186,6 +1250,10 @@ mod tests {
 
         assert!(result.is_error);
         assert!(result.content.contains("command not found"));
+        assert_eq!(result.metadata["error_kind"], "command_failed");
+        assert_eq!(result.metadata["truncated"], false);
+        assert_eq!(result.metadata["truncation"]["strategy"], "tail");
+        assert!(result.metadata["truncation"]["truncated_by"].is_null());
     }
 
     #[tokio::test]
@@ -225,6 +1293,120 @@ mod tests {
         assert!(result.content.contains("out"));
     }
 
+    #[tokio::test]
+    async fn separate_streams_labels_each() {
+        let tool = setup_with(vec![
+            ExecOutput {
+                stdout: "built\n".into(),
+                stderr: "\x1b[33mwarning: unused\x1b[0m\n".into(),
+                exit_code: 0,
+            },
+            ExecOutput {
+                stdout: String::new(),
+                stderr: "error[E0308]: mismatched types\n".into(),
+                exit_code: 101,
+            },
+        ]);
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({"command": "cargo build", "separate_streams": true}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        assert_eq!(
+            result.content,
+            "--- stdout ---\nbuilt\n--- stderr ---\nwarning: unused"
+        );
+        assert_eq!(result.metadata["stdout_bytes"], 6);
+        assert_eq!(result.metadata["stderr_bytes"], 25);
+        assert_eq!(result.metadata["truncation"]["stdout"]["output_lines"], 1);
+        assert_eq!(result.metadata["truncation"]["stderr"]["truncated"], false);
+
+        let result = tool
+            .execute(
+                "c2",
+                json!({"command": "cargo build", "separate_streams": true}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert_eq!(
+            result.content,
+            "Exit code: 101\n--- stdout ---\n(empty)\n--- stderr ---\nerror[E0308]: mismatched types"
+        );
+        assert_eq!(result.metadata["error_kind"], "command_failed");
+        assert_eq!(result.metadata["exit_code"], 101);
+    }
+
+    #[tokio::test]
+    async fn parse_output_renders_structured_stdout() {
+        let body = r#"{"id": 7, "tags": ["a"]}"#;
+        let ok = |stdout: &str| ExecOutput {
+            stdout: stdout.into(),
+            stderr: String::new(),
+            exit_code: 0,
+        };
+        let tool = setup_with(vec![
+            ok(body),
+            ok("pid\tcmd\n1\tinit\n"),
+            ExecOutput {
+                stdout: "plain, words\n".into(),
+                stderr: "warn\n".into(),
+                exit_code: 0,
+            },
+            ok(body),
+        ]);
+
+        let result = tool
+            .execute("c1", json!({"command": "curl -s api", "parse_output": "auto"}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content,
+            "{\n  \"id\": 7,\n  \"tags\": [\n    \"a\"\n  ]\n}"
+        );
+        assert_eq!(result.metadata["parsed_as"], "json");
+
+        let result = tool
+            .execute("c2", json!({"command": "ps", "parse_output": "table"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "pid  cmd\n1    init");
+
+        // Nothing recognizable: unchanged, and no parsed_as
+        let result = tool
+            .execute(
+                "c3",
+                json!({"command": "echo", "parse_output": "auto", "separate_streams": true}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content,
+            "--- stdout ---\nplain, words\n--- stderr ---\nwarn"
+        );
+        assert!(result.metadata.get("parsed_as").is_none());
+
+        // Off by default
+        let result = tool
+            .execute("c4", json!({"command": "curl -s api"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, body);
+
+        let result = tool
+            .execute("c5", json!({"command": "ls", "parse_output": "yaml"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::InvalidArguments));
+    }
+
     #[tokio::test]
     async fn streaming_output() {
         let tool = setup_ok("streamed\n");
@@ -240,12 +1422,499 @@ mod tests {
         assert!(partial.contains("streamed"));
     }
 
+    #[test]
+    fn stdin_wrapping_quotes_input() {
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(
+            with_stdin("wc -l", "a\nb"),
+            "printf '%s' 'a\nb' | {\nwc -l\n}"
+        );
+    }
+
+    #[tokio::test]
+    async fn oversized_stdin_is_refused() {
+        let tool = setup_ok("");
+        let stdin = "x".repeat(MAX_STDIN_BYTES);
+        let result = tool
+            .execute("c1", json!({"command": "wc -c", "stdin": stdin}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::InvalidArguments));
+        assert!(result.content.contains("stdin is too large"), "{}", result.content);
+
+        let result = tool
+            .execute("c2", json!({"command": "wc -c", "stdin": "x".repeat(1000)}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn stdin_reaches_process() {
+        use soul_core::vexec::NativeExecutor;
+        let tool = BashTool::new(Arc::new(NativeExecutor::new()) as Arc<dyn VirtualExecutor>, "/");
+        let result = tool
+            .execute(
+                "c6",
+                json!({"command": "cat; echo", "stdin": "it's $HOME `x`"}),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.content.trim_end(), "it's $HOME `x`");
+    }
+
     #[tokio::test]
     async fn tool_name_and_definition() {
         let tool = setup_ok("");
         assert_eq!(tool.name(), "bash");
         let def = tool.definition();
         assert_eq!(def.name, "bash");
-        assert!(def.input_schema["required"].as_array().unwrap().contains(&json!("command")));
+        assert!(def.input_schema["properties"]["command"].is_object());
+        assert!(def.input_schema["properties"]["argv"].is_object());
+    }
+
+    #[test]
+    fn shell_wrapping() {
+        assert_eq!(Shell::Sh.wrap("ls -la"), "ls -la");
+        assert_eq!(Shell::Bash.wrap("echo $0"), "bash -c 'echo $0'");
+        assert_eq!(
+            Shell::PowerShell.wrap("Get-ChildItem"),
+            "powershell -NoProfile -NonInteractive -Command 'Get-ChildItem'"
+        );
+        assert_eq!(Shell::None.wrap("ls  *.rs"), "'ls' '*.rs'");
+        assert_eq!(Shell::from_name("pwsh"), Some(Shell::PowerShell));
+        assert_eq!(Shell::from_name("fish"), None);
+    }
+
+    #[tokio::test]
+    async fn no_shell_refuses_quotes() {
+        let tool = setup_ok("ok").with_shell(Shell::None);
+        for command in ["echo 'a b'", "echo \"a b\"", "echo a\\ b"] {
+            let result = tool
+                .execute("c1", json!({"command": command}), None)
+                .await
+                .unwrap();
+            assert_eq!(
+                ToolErrorKind::of(&result),
+                Some(ToolErrorKind::InvalidArguments),
+                "{}",
+                command
+            );
+        }
+        let result = tool
+            .execute("c2", json!({"argv": ["echo", "a b"]}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+    }
+
+    #[test]
+    fn command_templates_quote_args() {
+        let args = json!({"file": "it's a file.txt", "n": 3, "dirs": ["src", "my tests"]});
+        let args = args.as_object().unwrap();
+        assert_eq!(
+            render_command("head -n {{n}} {{file}} && ls {{ dirs }}", args, Shell::Sh).unwrap(),
+            "head -n '3' 'it'\\''s a file.txt' && ls 'src' 'my tests'"
+        );
+        assert_eq!(
+            render_command("Get-Content {{file}}; {{n}}{{dirs}}", args, Shell::PowerShell).unwrap(),
+            "Get-Content 'it''s a file.txt'; '3''src' 'my tests'"
+        );
+        // awk and find braces aren't placeholders
+        let one = json!({"file": "a b"});
+        assert_eq!(
+            render_command("awk '{print $1}' {{file}}", one.as_object().unwrap(), Shell::Sh)
+                .unwrap(),
+            "awk '{print $1}' 'a b'"
+        );
+
+        let err = render_command("cat {{file}} {{other}}", args, Shell::Sh).unwrap_err();
+        assert!(err.contains("not used by the command: dirs, n"), "{}", err);
+        let err = render_command("cat {{file}} {{other}}", one.as_object().unwrap(), Shell::Sh)
+            .unwrap_err();
+        assert_eq!(err, "Missing args for placeholders: other");
+        let nested = json!({"file": {"path": "a"}});
+        assert!(render_command("cat {{file}}", nested.as_object().unwrap(), Shell::Sh).is_err());
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn templated_command_runs_with_awkward_paths() {
+        use soul_core::vexec::NativeExecutor;
+        let tool = BashTool::new(Arc::new(NativeExecutor::new()) as Arc<dyn VirtualExecutor>, "/");
+        let result = tool
+            .execute(
+                "c1",
+                json!({
+                    "command": "printf '%s|' {{name}} {{rest}}",
+                    "args": {"name": "Bob's \"file\" $HOME.txt", "rest": ["a b", "*"]}
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.content.trim(), "Bob's \"file\" $HOME.txt|a b|*|");
+
+        let result = tool
+            .execute("c2", json!({"argv": ["ls"], "args": {"a": "b"}}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::InvalidArguments));
+    }
+
+    #[test]
+    fn argv_quoting() {
+        assert_eq!(
+            argv_command(["grep", "-r", "it's $HOME", "src dir"]),
+            "'grep' '-r' 'it'\\''s $HOME' 'src dir'"
+        );
+    }
+
+    /// Records the timeout each command was run with.
+    struct TimeoutProbe(std::sync::Mutex<Vec<u64>>);
+
+    impl VirtualExecutor for TimeoutProbe {
+        fn exec_shell<'a>(
+            &'a self,
+            _command: &'a str,
+            timeout_secs: u64,
+            _cwd: Option<&'a str>,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = SoulResult<ExecOutput>> + Send + 'a>,
+        > {
+            self.0.lock().unwrap().push(timeout_secs);
+            Box::pin(async {
+                Ok(ExecOutput {
+                    stdout: String::new(),
+                    stderr: String::new(),
+                    exit_code: 0,
+                })
+            })
+        }
+    }
+
+    /// Fails the first `failures` commands with `error`, then succeeds.
+    struct Flaky {
+        failures: usize,
+        error: fn() -> SoulError,
+        calls: std::sync::Mutex<usize>,
+    }
+
+    impl VirtualExecutor for Flaky {
+        fn exec_shell<'a>(
+            &'a self,
+            _command: &'a str,
+            _timeout_secs: u64,
+            _cwd: Option<&'a str>,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = SoulResult<ExecOutput>> + Send + 'a>,
+        > {
+            let mut calls = self.calls.lock().unwrap();
+            *calls += 1;
+            let result = if *calls <= self.failures {
+                Err((self.error)())
+            } else {
+                Ok(ExecOutput {
+                    stdout: "done".into(),
+                    stderr: String::new(),
+                    exit_code: 0,
+                })
+            };
+            Box::pin(async move { result })
+        }
+    }
+
+    fn flaky(failures: usize, error: fn() -> SoulError) -> Arc<Flaky> {
+        Arc::new(Flaky {
+            failures,
+            error,
+            calls: std::sync::Mutex::new(0),
+        })
+    }
+
+    #[tokio::test]
+    async fn retries_executor_failures() {
+        let broken_pipe = || SoulError::Io(std::io::ErrorKind::BrokenPipe.into());
+        let exec = flaky(2, broken_pipe);
+        let tool = BashTool::new(exec.clone() as Arc<dyn VirtualExecutor>, "/")
+            .with_retries(2, Duration::from_millis(1));
+        let result = tool
+            .execute("c1", json!({"command": "make"}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.content, "done");
+        assert_eq!(result.metadata["retries"], 2);
+        assert_eq!(*exec.calls.lock().unwrap(), 3);
+
+        // Out of retries: the last error, with the count
+        let exec = flaky(3, broken_pipe);
+        let tool = BashTool::new(exec.clone() as Arc<dyn VirtualExecutor>, "/")
+            .with_retries(2, Duration::ZERO);
+        let result = tool
+            .execute("c2", json!({"command": "make", "separate_streams": true}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::Io));
+        assert!(result.content.starts_with("Command failed after 2 retries"));
+        assert_eq!(result.metadata["retries"], 2);
+
+        // Timeouts are not retried
+        let timeout = || SoulError::ToolExecution {
+            tool_name: "bash".into(),
+            message: "command timed out".into(),
+        };
+        let exec = flaky(1, timeout);
+        let tool = BashTool::new(exec.clone() as Arc<dyn VirtualExecutor>, "/")
+            .with_retries(3, Duration::ZERO);
+        let result = tool
+            .execute("c3", json!({"command": "make"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::Timeout));
+        assert_eq!(result.metadata["retries"], 0);
+        assert_eq!(*exec.calls.lock().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn non_zero_exit_is_not_retried() {
+        let executor = Arc::new(MockExecutor::new(vec![
+            ExecOutput {
+                stdout: String::new(),
+                stderr: "boom".into(),
+                exit_code: 1,
+            },
+            ExecOutput {
+                stdout: "second".into(),
+                stderr: String::new(),
+                exit_code: 0,
+            },
+        ]));
+        let tool = BashTool::new(executor as Arc<dyn VirtualExecutor>, "/")
+            .with_retries(3, Duration::ZERO);
+        let result = tool
+            .execute("c1", json!({"command": "false"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::CommandFailed));
+        assert_eq!(result.metadata["retries"], 0);
+    }
+
+    #[tokio::test]
+    async fn timeouts_default_and_clamped() {
+        let probe = Arc::new(TimeoutProbe(std::sync::Mutex::new(Vec::new())));
+        let tool = BashTool::new(probe.clone() as Arc<dyn VirtualExecutor>, "/")
+            .with_default_timeout(10)
+            .with_max_timeout(60);
+
+        tool.execute("c1", json!({"command": "true"}), None)
+            .await
+            .unwrap();
+        tool.execute("c2", json!({"command": "true", "timeout": 30}), None)
+            .await
+            .unwrap();
+        let result = tool
+            .execute("c3", json!({"command": "true", "timeout": 3600}), None)
+            .await
+            .unwrap();
+        assert_eq!(*probe.0.lock().unwrap(), vec![10, 30, 60]);
+        assert_eq!(result.metadata["timeout_secs"], 60);
+
+        let def = tool.definition();
+        assert!(def.description.contains("defaults to 10s, max 60s"));
+        assert_eq!(
+            def.input_schema["properties"]["timeout"]["description"],
+            "Timeout in seconds (default: 10, max: 60)"
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_command_and_argv() {
+        let tool = setup_ok("");
+        let result = tool.execute("c1", json!({}), None).await.unwrap();
+        assert!(result.is_error);
+
+        let result = tool
+            .execute("c2", json!({"argv": []}), None)
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(result.content.contains("argv"));
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn argv_bypasses_shell() {
+        use soul_core::vexec::NativeExecutor;
+        let tool = BashTool::new(Arc::new(NativeExecutor::new()) as Arc<dyn VirtualExecutor>, "/");
+        let result = tool
+            .execute(
+                "c7",
+                json!({"argv": ["printf", "%s|", "a b", "$HOME", "*"]}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.content.trim(), "a b|$HOME|*|");
+    }
+
+    #[cfg(all(feature = "native", unix))]
+    #[tokio::test]
+    async fn resource_limits_reach_process() {
+        use soul_core::vexec::NativeExecutor;
+        let limits = ResourceLimits::new()
+            .with_cpu_secs(5)
+            .with_memory_bytes(2 * 1024 * 1024 * 1024);
+        let tool = BashTool::new(Arc::new(NativeExecutor::new()) as Arc<dyn VirtualExecutor>, "/")
+            .with_limits(limits);
+        let result = tool
+            .execute("c1", json!({"command": "ulimit -t; ulimit -v"}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.content.trim(), "5\n2097152");
+        assert_eq!(result.metadata["limits"]["process_limits_applied"], true);
+        assert_eq!(result.metadata["limits"]["cpu_secs"], 5);
+    }
+
+    #[tokio::test]
+    async fn output_cap_keeps_the_tail() {
+        let tool = setup_ok("0123456789abcdef")
+            .with_limits(ResourceLimits::new().with_max_output_bytes(6));
+        let result = tool
+            .execute("c1", json!({"command": "noisy"}), None)
+            .await
+            .unwrap();
+        assert!(result.content.contains("6-byte limit"), "{}", result.content);
+        assert!(result.content.ends_with("abcdef"), "{}", result.content);
+        assert!(!result.content.contains("0123"));
+        assert_eq!(result.metadata["limits"]["output_capped"], true);
+
+        // No limits configured: no limits metadata
+        let result = setup_ok("x")
+            .execute("c2", json!({"command": "x"}), None)
+            .await
+            .unwrap();
+        assert!(result.metadata.get("limits").is_none());
+    }
+
+    #[tokio::test]
+    async fn history_and_rerun() {
+        let output = |stdout: &str, exit_code| ExecOutput {
+            stdout: stdout.into(),
+            stderr: String::new(),
+            exit_code,
+        };
+        let tool = setup_with(vec![
+            output("1 failed", 101),
+            output("fixed", 0),
+            output("all passed", 0),
+        ]);
+        let result = tool.execute("c0", json!({"history": true}), None).await.unwrap();
+        assert_eq!(result.content, "No commands run yet");
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({"command": "cargo test {{name}}", "args": {"name": "parse"}}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["exit_code"], 101);
+        assert_eq!(result.metadata["history_index"], 1);
+        tool.execute("c2", json!({"argv": ["touch", "a b"]}), None)
+            .await
+            .unwrap();
+        // Refused calls are not recorded
+        tool.execute("c3", json!({"argv": []}), None).await.unwrap();
+
+        let result = tool.execute("c4", json!({"history": true}), None).await.unwrap();
+        let lines: Vec<&str> = result.content.lines().collect();
+        assert_eq!(lines.len(), 2, "{}", result.content);
+        assert!(lines[0].starts_with("  1  exit 101"), "{}", lines[0]);
+        assert!(lines[0].ends_with("  cargo test 'parse'"), "{}", lines[0]);
+        assert!(lines[1].ends_with("  'touch' 'a b'"), "{}", lines[1]);
+        assert_eq!(result.metadata["history"][1]["exit_code"], 0);
+
+        let result = tool.execute("c5", json!({"rerun": 1}), None).await.unwrap();
+        assert_eq!(result.content, "all passed");
+        assert_eq!(result.metadata["rerun"], 1);
+        assert_eq!(result.metadata["command"], "cargo test 'parse'");
+        assert_eq!(result.metadata["history_index"], 3);
+        assert_eq!(tool.history.entries()[2].command, "cargo test 'parse'");
+
+        let result = tool.execute("c6", json!({"rerun": 9}), None).await.unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+        let result = tool
+            .execute("c7", json!({"rerun": 1, "command": "ls"}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            ToolErrorKind::of(&result),
+            Some(ToolErrorKind::InvalidArguments)
+        );
+    }
+
+    #[tokio::test]
+    async fn renamed_to_shell() {
+        let tool = setup_ok("hi").with_name("shell");
+        assert_eq!(tool.name(), "shell");
+        assert_eq!(tool.definition().name, "shell");
+
+        let result = tool
+            .execute("c1", json!({"command": "echo hi"}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        assert!(result.content.contains("hi"));
+    }
+
+    #[cfg(feature = "pty")]
+    #[tokio::test]
+    async fn pty_mode_answers_prompts() {
+        let result = setup_ok("unused")
+            .execute("c0", json!({"command": "true", "pty": true}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::Policy));
+        assert!(setup_ok("unused").definition().input_schema["properties"]["pty"].is_null());
+
+        let tool = setup_ok("unused").with_pty().with_pty_size(100, 20);
+        assert!(tool.definition().input_schema["properties"]["pty"].is_object());
+        let result = tool
+            .execute(
+                "c1",
+                json!({
+                    "command": "[ -t 0 ] && printf '\\033[32mName?\\033[0m '; read -r n; echo \"hi $n\"",
+                    "pty": true,
+                    "answers": ["soul"]
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("Name?"));
+        assert!(result.content.contains("hi soul"));
+        assert!(!result.content.contains("\x1b["));
+        assert_eq!(result.metadata["pty"], true);
+        assert_eq!(result.metadata["cols"], 100);
+
+        let result = tool
+            .execute(
+                "c2",
+                json!({"command": "true", "pty": true, "separate_streams": true}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::InvalidArguments));
     }
 }
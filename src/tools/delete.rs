@@ -0,0 +1,324 @@
+//! Delete tool — remove files and directories recoverably.
+//!
+//! Deleted paths go to a [`Trash`] under the working directory
+//! ([`DEFAULT_TRASH_ROOT`]) instead of being removed, and the same tool lists
+//! and restores them. Each call first drops trash entries older than the
+//! retention period. Not part of [`all_tools`](crate::presets::all_tools);
+//! add it with [`Toolset::with_delete`](crate::Toolset::with_delete).
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::{SoulError, SoulResult};
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::audit::now_ms;
+use crate::error::{tool_error, ToolErrorKind};
+use crate::paths;
+use crate::trash::{Trash, DEFAULT_RETENTION, DEFAULT_TRASH_ROOT};
+
+use super::{impl_overrides, resolve_path, set_metadata, ToolOverrides};
+
+/// System-prompt guidance returned by [`DeleteTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Remove files with delete rather than rm in bash: deleted files can be \
+    listed and restored until they expire.";
+
+pub struct DeleteTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    root: String,
+    retention: Duration,
+    overrides: ToolOverrides,
+}
+
+impl DeleteTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            root: DEFAULT_TRASH_ROOT.into(),
+            retention: DEFAULT_RETENTION,
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+    /// Keep deleted files under `root` (relative to the working directory or
+    /// absolute).
+    pub fn with_trash_root(mut self, root: impl Into<String>) -> Self {
+        self.root = root.into();
+        self
+    }
+
+    /// Time deleted files are kept before a later call drops them (default:
+    /// a week).
+    pub fn with_retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// A failed trash operation, with paths shown relative to the working
+    /// directory.
+    fn failure(&self, error: SoulError) -> ToolOutput {
+        let message = match &error {
+            SoulError::Io(e) => e.to_string(),
+            other => other.to_string(),
+        };
+        let message = message.replace(&format!("{}/", self.cwd), "");
+        tool_error(ToolErrorKind::from_soul_error(&error), message)
+    }
+}
+
+impl_overrides!(DeleteTool);
+
+fn file_count(count: usize) -> String {
+    match count {
+        1 => "1 file".to_string(),
+        n => format!("{} files", n),
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for DeleteTool {
+    fn name(&self) -> &str {
+        self.overrides.name("delete")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "delete".into(),
+            description: "Delete a file or directory by moving it to the trash, from where it can be restored. Actions: delete (default), list (what the trash holds), restore (put an entry back by id).".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "action": {
+                        "type": "string",
+                        "enum": ["delete", "list", "restore"],
+                        "description": "Operation to perform (default: delete)"
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "File or directory to delete (delete)"
+                    },
+                    "id": {
+                        "type": "string",
+                        "description": "Trash entry to restore, as reported by delete or list (restore)"
+                    },
+                    "overwrite": {
+                        "type": "boolean",
+                        "description": "Replace files created at the original paths since the delete (restore, default: false)"
+                    }
+                }
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let action = arguments
+            .get("action")
+            .and_then(|v| v.as_str())
+            .unwrap_or("delete");
+        let root = resolve_path(&self.cwd, &self.root);
+        let trash = Trash::new(self.fs.clone(), &root).with_retention(self.retention);
+        let expired = trash.purge(now_ms()).await.map_or(0, |purged| purged.len());
+
+        let mut output = match action {
+            "delete" => {
+                let path = arguments.get("path").and_then(|v| v.as_str()).unwrap_or("");
+                if path.is_empty() {
+                    return Ok(tool_error(
+                        ToolErrorKind::InvalidArguments,
+                        "Missing required parameter: path",
+                    ));
+                }
+                let resolved = resolve_path(&self.cwd, path);
+                if resolved == self.cwd {
+                    return Ok(tool_error(
+                        ToolErrorKind::Policy,
+                        "Refusing to delete the working directory",
+                    ));
+                }
+                match trash.delete(&resolved).await {
+                    Ok(entry) => {
+                        let count = entry.files.len();
+                        ToolOutput::success(format!(
+                            "Moved {} to the trash ({}); restore it with id {}",
+                            paths::display(&entry.path, &self.cwd),
+                            file_count(count),
+                            entry.id
+                        ))
+                        .with_metadata(json!({
+                            "id": entry.id,
+                            "path": entry.path,
+                            "files": count,
+                            "trash": root,
+                        }))
+                    }
+                    Err(e) => self.failure(e),
+                }
+            }
+            "list" => match trash.list().await {
+                Ok(entries) if entries.is_empty() => ToolOutput::success("The trash is empty")
+                    .with_metadata(json!({"entries": [], "trash": root})),
+                Ok(entries) => {
+                    let listing: Vec<String> = entries
+                        .iter()
+                        .map(|entry| {
+                            format!(
+                                "{}  {} ({})",
+                                entry.id,
+                                paths::display(&entry.path, &self.cwd),
+                                file_count(entry.files.len())
+                            )
+                        })
+                        .collect();
+                    ToolOutput::success(listing.join("\n"))
+                        .with_metadata(json!({"entries": entries, "trash": root}))
+                }
+                Err(e) => self.failure(e),
+            },
+            "restore" => {
+                let id = match arguments.get("id") {
+                    Some(serde_json::Value::String(id)) => id.clone(),
+                    Some(serde_json::Value::Number(id)) => id.to_string(),
+                    _ => {
+                        return Ok(tool_error(
+                            ToolErrorKind::InvalidArguments,
+                            "Missing required parameter: id",
+                        ))
+                    }
+                };
+                let overwrite = arguments
+                    .get("overwrite")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                match trash.restore(&id, overwrite).await {
+                    Ok(entry) => ToolOutput::success(format!(
+                        "Restored {} ({})",
+                        paths::display(&entry.path, &self.cwd),
+                        file_count(entry.files.len())
+                    ))
+                    .with_metadata(json!({
+                        "id": entry.id,
+                        "path": entry.path,
+                        "files": entry.files,
+                    })),
+                    Err(e) => self.failure(e),
+                }
+            }
+            other => {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!(
+                        "Unknown action: {} (expected delete, list or restore)",
+                        other
+                    ),
+                ))
+            }
+        };
+        if expired > 0 {
+            set_metadata(&mut output, "expired", json!(expired));
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    #[tokio::test]
+    async fn delete_list_and_restore() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/old.rs", "fn old() {}").await.unwrap();
+        let tool = DeleteTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+
+        let result = tool
+            .execute("c1", json!({"path": "old.rs"}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        let id = result.metadata["id"].as_str().unwrap().to_string();
+        assert_eq!(
+            result.content,
+            format!(
+                "Moved old.rs to the trash (1 file); restore it with id {}",
+                id
+            )
+        );
+        assert!(!fs.exists("/project/old.rs").await.unwrap());
+
+        let result = tool
+            .execute("c2", json!({"action": "list"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, format!("{}  old.rs (1 file)", id));
+
+        fs.write("/project/old.rs", "fn new() {}").await.unwrap();
+        let result = tool
+            .execute("c3", json!({"action": "restore", "id": id}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            ToolErrorKind::of(&result),
+            Some(ToolErrorKind::AlreadyExists)
+        );
+        assert_eq!(result.content, "Already exists: old.rs");
+        let result = tool
+            .execute(
+                "c4",
+                json!({"action": "restore", "id": id, "overwrite": true}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.content, "Restored old.rs (1 file)");
+        assert_eq!(
+            fs.read_to_string("/project/old.rs").await.unwrap(),
+            "fn old() {}"
+        );
+
+        let result = tool
+            .execute("c5", json!({"path": "."}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::Policy));
+        let result = tool
+            .execute("c6", json!({"path": "missing.rs"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_dropped() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "a").await.unwrap();
+        let tool = DeleteTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project")
+            .with_retention(Duration::ZERO);
+
+        tool.execute("c1", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(2));
+        let result = tool
+            .execute("c2", json!({"action": "list"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "The trash is empty");
+        assert_eq!(result.metadata["expired"], 1);
+    }
+}
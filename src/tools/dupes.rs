@@ -0,0 +1,341 @@
+//! Dupes tool — find files with identical contents.
+//!
+//! Copy-pasted fixtures and repeated assets pile up unnoticed. `dupes` walks
+//! the files matching a glob, groups them by size (from metadata, so files
+//! of a unique size are never read), and hashes only the files that share a
+//! size with another. Everything goes through the VFS, so it works under
+//! WASM. As with [`hash`](super::hash), binary files can't be read and are
+//! skipped.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::error::{tool_error, ToolErrorKind};
+use crate::paths;
+use crate::walk::WalkGuard;
+
+use super::hash::matching_files;
+use super::{impl_overrides, resolve_path, DisplayRoot, ToolOverrides};
+
+/// Maximum files considered for one call.
+const MAX_FILES: usize = 5000;
+
+/// Maximum duplicate groups listed in the output.
+const MAX_GROUPS: usize = 50;
+
+/// System-prompt guidance returned by [`DupesTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    To find copy-pasted fixtures or repeated assets, run dupes over a pattern \
+    rather than hashing files one by one.";
+
+pub struct DupesTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    overrides: ToolOverrides,
+}
+
+impl DupesTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            overrides: ToolOverrides::default(),
+        }
+    }
+}
+
+impl_overrides!(DupesTool);
+
+/// Files with identical contents.
+struct Group {
+    hash: String,
+    bytes: u64,
+    paths: Vec<String>,
+}
+
+impl Group {
+    /// Bytes taken by all copies but one.
+    fn redundant_bytes(&self) -> u64 {
+        self.bytes * (self.paths.len() as u64 - 1)
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for DupesTool {
+    fn name(&self) -> &str {
+        self.overrides.name("dupes")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "dupes".into(),
+            description: "Find files with identical contents under a directory, optionally limited to a glob pattern. Reports groups of duplicates, largest savings first, with the bytes each group wastes.".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "Directory to search (defaults to working directory)"
+                    },
+                    "pattern": {
+                        "type": "string",
+                        "description": "Glob selecting files under path (e.g. '*.json', 'tests/fixtures/**'; default: all files)"
+                    },
+                    "min_bytes": {
+                        "type": "integer",
+                        "description": "Ignore files smaller than this (default: 1, so empty files are not reported)"
+                    },
+                    "relative_to": DisplayRoot::schema()
+                }
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let path = arguments.get("path").and_then(|v| v.as_str()).unwrap_or("");
+        let resolved = resolve_path(&self.cwd, path);
+        let pattern = arguments
+            .get("pattern")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .unwrap_or("*");
+        let min_bytes = arguments
+            .get("min_bytes")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1);
+
+        match self.fs.metadata(&resolved).await {
+            Ok(metadata) if metadata.is_dir => {}
+            Ok(_) => {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!(
+                        "{} is a file; pass the directory to search",
+                        paths::display(&resolved, &self.cwd)
+                    ),
+                ))
+            }
+            Err(_) => {
+                return Ok(tool_error(
+                    ToolErrorKind::NotFound,
+                    format!(
+                        "Directory not found: {}",
+                        paths::display(&resolved, &self.cwd)
+                    ),
+                ))
+            }
+        }
+
+        let guard = Arc::new(WalkGuard::new());
+        let (files, capped) = matching_files(
+            self.fs.clone(),
+            &resolved,
+            pattern,
+            MAX_FILES,
+            guard.clone(),
+        )
+        .await;
+
+        // Only files sharing a size with another can be duplicates
+        let mut by_size: BTreeMap<u64, Vec<String>> = BTreeMap::new();
+        for file in files.iter() {
+            if let Ok(metadata) = self.fs.metadata(file).await {
+                if metadata.size >= min_bytes {
+                    by_size.entry(metadata.size).or_default().push(file.clone());
+                }
+            }
+        }
+
+        let display = DisplayRoot::from_arguments(&self.cwd, &arguments);
+        let mut hashed = 0;
+        let mut unreadable = Vec::new();
+        let mut groups = Vec::new();
+        for (size, candidates) in by_size.into_iter().filter(|(_, f)| f.len() > 1) {
+            let mut by_hash: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for file in candidates {
+                let Ok(content) = self.fs.read_to_string(&file).await else {
+                    unreadable.push(display.show(&file));
+                    continue;
+                };
+                hashed += 1;
+                let hash = format!("{:x}", Sha256::digest(content.as_bytes()));
+                by_hash.entry(hash).or_default().push(display.show(&file));
+            }
+            groups.extend(
+                by_hash
+                    .into_iter()
+                    .filter(|(_, paths)| paths.len() > 1)
+                    .map(|(hash, mut paths)| {
+                        paths.sort();
+                        Group {
+                            hash,
+                            bytes: size,
+                            paths,
+                        }
+                    }),
+            );
+        }
+        groups.sort_by(|a, b| {
+            b.redundant_bytes()
+                .cmp(&a.redundant_bytes())
+                .then_with(|| a.paths.cmp(&b.paths))
+        });
+
+        let redundant_files: usize = groups.iter().map(|g| g.paths.len() - 1).sum();
+        let redundant_bytes: u64 = groups.iter().map(Group::redundant_bytes).sum();
+        let mut content = match groups.len() {
+            0 => format!(
+                "No duplicate files among {} file(s) matching '{}' in {}",
+                files.len(),
+                pattern,
+                paths::display(&resolved, &self.cwd)
+            ),
+            n => format!(
+                "{} group(s) of identical files among {} file(s): {} redundant copies, {} bytes",
+                n,
+                files.len(),
+                redundant_files,
+                redundant_bytes
+            ),
+        };
+        for group in groups.iter().take(MAX_GROUPS) {
+            content.push_str(&format!(
+                "\n\n{} × {} bytes (sha256 {})",
+                group.paths.len(),
+                group.bytes,
+                &group.hash[..12]
+            ));
+            for path in &group.paths {
+                content.push_str(&format!("\n  {}", path));
+            }
+        }
+        if groups.len() > MAX_GROUPS {
+            content.push_str(&format!(
+                "\n\n[{} more groups not shown]",
+                groups.len() - MAX_GROUPS
+            ));
+        }
+        if !unreadable.is_empty() {
+            content.push_str(&format!(
+                "\n[Skipped {} binary or unreadable files: {}]",
+                unreadable.len(),
+                unreadable.join(", ")
+            ));
+        }
+        if capped {
+            content.push_str(&format!("\n[Stopped after {} files]", MAX_FILES));
+        }
+
+        let mut output = ToolOutput::success(content).with_metadata(json!({
+            "groups": groups
+                .iter()
+                .map(|g| json!({"hash": g.hash, "bytes": g.bytes, "paths": g.paths}))
+                .collect::<Vec<_>>(),
+            "files_scanned": files.len(),
+            "files_hashed": hashed,
+            "redundant_files": redundant_files,
+            "redundant_bytes": redundant_bytes,
+            "unreadable": unreadable,
+            "limit_reached": capped,
+        }));
+        guard.annotate(&mut output);
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    async fn setup() -> (Arc<MemoryFs>, DupesTool) {
+        let fs = Arc::new(MemoryFs::new());
+        let big = "{\"users\": []}\n".repeat(20);
+        for (path, content) in [
+            ("/project/tests/fixtures/users.json", big.as_str()),
+            ("/project/tests/fixtures/copy/users.json", big.as_str()),
+            ("/project/tests/legacy/users_old.json", big.as_str()),
+            ("/project/assets/a.svg", "<svg/>"),
+            ("/project/assets/b.svg", "<svg/>"),
+            ("/project/assets/c.svg", "<svg>"),
+            ("/project/empty1.txt", ""),
+            ("/project/empty2.txt", ""),
+        ] {
+            fs.write(path, content).await.unwrap();
+        }
+        let tool = DupesTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        (fs, tool)
+    }
+
+    #[tokio::test]
+    async fn groups_identical_files_largest_first() {
+        let (_fs, tool) = setup().await;
+        let result = tool.execute("c1", json!({}), None).await.unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert!(
+            result.content.starts_with(
+                "2 group(s) of identical files among 8 file(s): 3 redundant copies, 566 bytes"
+            ),
+            "{}",
+            result.content
+        );
+        let groups = result.metadata["groups"].as_array().unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0]["paths"].as_array().unwrap().len(), 3);
+        assert_eq!(groups[1]["paths"], json!(["assets/a.svg", "assets/b.svg"]));
+        // c.svg has a unique size and is never read
+        assert_eq!(result.metadata["files_hashed"], 5);
+        assert!(result.content.contains("\n\n3 × 280 bytes (sha256 "));
+    }
+
+    #[tokio::test]
+    async fn pattern_path_and_min_bytes() {
+        let (_fs, tool) = setup().await;
+        let result = tool
+            .execute("c1", json!({"path": "tests", "pattern": "*.json"}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.metadata["groups"][0]["paths"][0],
+            "tests/fixtures/copy/users.json"
+        );
+        assert_eq!(result.metadata["files_scanned"], 3);
+
+        let result = tool
+            .execute("c2", json!({"pattern": "*.txt", "min_bytes": 0}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["redundant_files"], 1);
+
+        let result = tool
+            .execute("c3", json!({"pattern": "*.svg", "min_bytes": 100}), None)
+            .await
+            .unwrap();
+        assert!(result
+            .content
+            .starts_with("No duplicate files among 3 file(s)"));
+
+        let result = tool
+            .execute("c4", json!({"path": "missing"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+    }
+}
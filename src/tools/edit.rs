@@ -1,5 +1,9 @@
 //! Edit tool — precise text replacement with exact matching and fuzzy fallback.
+//!
+//! Edits can also be given as one unified-diff hunk, applied with
+//! [`crate::hunk`].
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -12,11 +16,31 @@ use soul_core::tool::{Tool, ToolOutput};
 use soul_core::types::ToolDefinition;
 use soul_core::vfs::VirtualFs;
 
-use super::resolve_path;
+use crate::error::{tool_error, ToolErrorKind};
+use crate::file_modes::{current_mode, restore_mode, FileModes};
+use crate::hunk::{Hunk, HunkError, HunkLine};
+use crate::locks::PathLocks;
+use crate::notebook::{is_notebook, Notebook};
+use crate::outline::{block_end, Language};
+use crate::diff::render_diff;
+use crate::truncate::{truncate_line, MAX_DIFF_BYTES};
+
+use super::{impl_overrides, resolve_path, set_metadata, ToolOverrides};
+
+/// System-prompt guidance returned by [`EditTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Read the file before editing it. old must match exactly one place in the file, \
+    indentation and whitespace included; if it matches several, add surrounding lines \
+    until it is unique. Never include the line-number prefixes from read output. Prefer \
+    several small edits over one large one, and edit over write for existing files.";
 
 pub struct EditTool {
     fs: Arc<dyn VirtualFs>,
     cwd: String,
+    locks: Arc<PathLocks>,
+    modes: Option<Arc<dyn FileModes>>,
+    max_diff_bytes: usize,
+    overrides: ToolOverrides,
 }
 
 impl EditTool {
@@ -24,64 +48,662 @@ impl EditTool {
         Self {
             fs,
             cwd: cwd.into(),
+            locks: Arc::new(PathLocks::new()),
+            modes: None,
+            max_diff_bytes: MAX_DIFF_BYTES,
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+    /// Share `locks` with other tools, so that their changes to the same
+    /// file wait for each other (default: a set of this tool's own).
+    pub fn with_locks(mut self, locks: Arc<PathLocks>) -> Self {
+        self.locks = locks;
+        self
+    }
+
+    /// Put back the permissions of files this tool rewrites (default: left
+    /// to the filesystem).
+    pub fn with_file_modes(mut self, modes: Arc<dyn FileModes>) -> Self {
+        self.modes = Some(modes);
+        self
+    }
+
+    /// Cap the diff in the output at about `bytes`, summarizing the hunks
+    /// left out; the `diff` metadata still counts all of them (default:
+    /// [`MAX_DIFF_BYTES`]).
+    pub fn with_max_diff_bytes(mut self, bytes: usize) -> Self {
+        self.max_diff_bytes = bytes;
+        self
+    }
+}
+
+impl_overrides!(EditTool);
+
+/// Characters copy-pasted text carries invisibly: the BOM, zero-width
+/// spaces and joiners, and bidi controls. Fuzzy matching ignores them.
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{FEFF}' // byte order mark / zero-width no-break space
+            | '\u{200B}'..='\u{200F}' // zero-width space/joiners, LRM/RLM
+            | '\u{2060}' // word joiner
+            | '\u{061C}' // arabic letter mark
+            | '\u{202A}'..='\u{202E}' // bidi embeddings and overrides
+            | '\u{2066}'..='\u{2069}' // bidi isolates
+    )
+}
+
+/// Text normalized for fuzzy matching, with the byte range of the original
+/// each normalized byte came from, so that a match can be replaced without
+/// touching the rest of the file.
+struct Normalized {
+    text: String,
+    spans: Vec<(usize, usize)>,
+}
+
+impl Normalized {
+    /// Normalize `text`: trim trailing whitespace per line, normalize smart
+    /// quotes to ASCII, normalize unicode dashes, drop invisible characters.
+    fn new(text: &str) -> Self {
+        let mut normalized = Self {
+            text: String::with_capacity(text.len()),
+            spans: Vec::with_capacity(text.len()),
+        };
+        let mut line_start = 0;
+        let mut newline = None;
+        for line in text.split_inclusive('\n') {
+            let line_end = line_start + line.len();
+            let body = line.strip_suffix('\n').unwrap_or(line);
+            let body = body.strip_suffix('\r').unwrap_or(body);
+            if let Some(span) = newline {
+                normalized.push('\n', span);
+            }
+            newline = Some((line_start + body.len(), line_end));
+
+            let mut chars: Vec<(char, (usize, usize))> = body
+                .char_indices()
+                .filter(|(_, c)| !is_invisible(*c))
+                .map(|(i, c)| {
+                    let span = (line_start + i, line_start + i + c.len_utf8());
+                    let c = match c {
+                        '\u{2018}' | '\u{2019}' => '\'', // left/right single quote
+                        '\u{201C}' | '\u{201D}' => '"', // left/right double quote
+                        '\u{2013}' | '\u{2014}' => '-', // en/em dash
+                        '\u{00A0}' | '\u{202F}' => ' ', // non-breaking/narrow no-break space
+                        c => c,
+                    };
+                    (c, span)
+                })
+                .collect();
+            while chars.last().is_some_and(|(c, _)| c.is_whitespace()) {
+                chars.pop();
+            }
+            for (c, span) in chars {
+                normalized.push(c, span);
+            }
+            line_start = line_end;
+        }
+        normalized
+    }
+
+    fn push(&mut self, c: char, span: (usize, usize)) {
+        self.text.push(c);
+        self.spans.extend(std::iter::repeat(span).take(c.len_utf8()));
+    }
+
+    /// The range of the original covered by normalized bytes `start..end`.
+    fn original_range(&self, start: usize, end: usize) -> std::ops::Range<usize> {
+        if start == end {
+            let at = start.checked_sub(1).map_or(0, |i| self.spans[i].1);
+            return at..at;
         }
+        self.spans[start].0..self.spans[end - 1].1
     }
 }
 
-/// Normalize text for fuzzy matching: trim trailing whitespace per line,
-/// normalize smart quotes to ASCII, normalize unicode dashes.
+/// Normalize text for fuzzy matching; see [`Normalized::new`].
+#[cfg(test)]
 fn normalize_for_fuzzy(text: &str) -> String {
-    text.lines()
-        .map(|line| {
-            let trimmed = line.trim_end();
-            trimmed
-                .replace('\u{2018}', "'")  // left single quote
-                .replace('\u{2019}', "'")  // right single quote
-                .replace('\u{201C}', "\"") // left double quote
-                .replace('\u{201D}', "\"") // right double quote
-                .replace('\u{2013}', "-")  // en dash
-                .replace('\u{2014}', "-")  // em dash
-                .replace('\u{00A0}', " ")  // non-breaking space
-                .replace('\u{202F}', " ")  // narrow no-break space
-        })
-        .collect::<Vec<_>>()
-        .join("\n")
+    Normalized::new(text).text
+}
+
+/// Maximum number of occurrences listed when a match is ambiguous.
+const MAX_PREVIEW_OCCURRENCES: usize = 10;
+
+/// Maximum characters of each previewed line.
+const PREVIEW_LINE_CHARS: usize = 80;
+
+/// Describe where each match (byte offset into `searched`) sits, so the next
+/// attempt can add the right context. Snippets come from `original`, which
+/// has the same line structure as `searched`.
+fn occurrence_preview(
+    searched: &str,
+    original: &str,
+    positions: &[usize],
+) -> (String, Vec<serde_json::Value>) {
+    let original_lines: Vec<&str> = original.lines().collect();
+    let mut text = String::new();
+    let mut occurrences = Vec::new();
+    for &pos in positions.iter().take(MAX_PREVIEW_OCCURRENCES) {
+        let line = searched[..pos].matches('\n').count() + 1;
+        let snippet = original_lines
+            .get(line - 1)
+            .map(|l| truncate_line(l.trim(), PREVIEW_LINE_CHARS))
+            .unwrap_or_default();
+        text.push_str(&format!("\n  line {}: {}", line, snippet));
+        occurrences.push(json!({"line": line, "snippet": snippet}));
+    }
+    if positions.len() > MAX_PREVIEW_OCCURRENCES {
+        text.push_str(&format!(
+            "\n  ... and {} more",
+            positions.len() - MAX_PREVIEW_OCCURRENCES
+        ));
+    }
+    (text, occurrences)
+}
+
+/// Files longer than this skip the closest-match search on a miss.
+const MAX_CANDIDATE_SEARCH_LINES: usize = 20_000;
+
+/// Windows rescored with the precise character-level ratio.
+const CANDIDATE_RESCORE: usize = 8;
+
+/// Minimum similarity (0.0–1.0) for a region to be suggested.
+const MIN_CANDIDATE_RATIO: f32 = 0.5;
+
+/// The region of a file most similar to an `old` text that wasn't found.
+struct Candidate {
+    /// 1-indexed, inclusive line range.
+    start_line: usize,
+    end_line: usize,
+    ratio: f32,
+    text: String,
 }
 
-/// Generate a unified diff between old and new content.
-fn unified_diff(old: &str, new: &str, path: &str) -> String {
-    let diff = TextDiff::from_lines(old, new);
-    let mut output = format!("--- a/{}\n+++ b/{}\n", path, path);
+/// Find the window of lines most similar to `old`, trying windows one line
+/// shorter and longer too since models often drop or invent a line.
+fn closest_candidate(content: &str, old: &str) -> Option<Candidate> {
+    let lines: Vec<&str> = content.lines().collect();
+    let old = old.trim_end_matches('\n');
+    let size = old.lines().count().max(1);
+    if lines.is_empty() || lines.len() > MAX_CANDIDATE_SEARCH_LINES {
+        return None;
+    }
+
+    // Rank every window by how many of old's words it shares (a sliding
+    // bag-of-words count, linear in the file), then rescore the
+    // front-runners with a real character diff
+    let mut wanted: HashMap<&str, usize> = HashMap::new();
+    for word in old.lines().flat_map(line_words) {
+        *wanted.entry(word).or_default() += 1;
+    }
+    let wanted_total: usize = wanted.values().sum();
+    let line_words_cache: Vec<Vec<&str>> = lines.iter().map(|l| line_words(l).collect()).collect();
+
+    let mut ranked: Vec<(f32, usize, usize)> = Vec::new();
+    for window in [size, size.saturating_sub(1), size + 1] {
+        if window == 0 || window > lines.len() {
+            continue;
+        }
+        let mut have: HashMap<&str, usize> = HashMap::new();
+        let (mut shared, mut total) = (0usize, 0usize);
+        for end in 0..lines.len() {
+            for &word in &line_words_cache[end] {
+                let count = have.entry(word).or_default();
+                if *count < wanted.get(word).copied().unwrap_or(0) {
+                    shared += 1;
+                }
+                *count += 1;
+                total += 1;
+            }
+            if end + 1 > window {
+                for &word in &line_words_cache[end - window] {
+                    let count = have.get_mut(word).expect("word counted on entry");
+                    *count -= 1;
+                    if *count < wanted.get(word).copied().unwrap_or(0) {
+                        shared -= 1;
+                    }
+                    total -= 1;
+                }
+            }
+            if end + 1 >= window {
+                let score = 2.0 * shared as f32 / (wanted_total + total).max(1) as f32;
+                ranked.push((score, end + 1 - window, window));
+            }
+        }
+    }
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut best: Option<Candidate> = None;
+    for &(_, start, window) in ranked.iter().take(CANDIDATE_RESCORE) {
+        let text = lines[start..start + window].join("\n");
+        let ratio = TextDiff::from_chars(old, text.as_str()).ratio();
+        if best.as_ref().map_or(true, |b| ratio > b.ratio) {
+            best = Some(Candidate {
+                start_line: start + 1,
+                end_line: start + window,
+                ratio,
+                text,
+            });
+        }
+    }
+    best.filter(|b| b.ratio >= MIN_CANDIDATE_RATIO)
+}
 
-    let mut udiff = diff.unified_diff();
-    output.push_str(&udiff.header("", "").to_string());
+/// Identifier-ish words of a line, for the candidate prefilter.
+fn line_words(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|w| !w.is_empty())
+}
 
+/// Line diff from `old` (`-`) to the candidate region (`+`).
+fn candidate_diff(old: &str, candidate: &str) -> String {
+    let old = old.trim_end_matches('\n');
+    let diff = TextDiff::from_lines(old, candidate);
+    let mut out = String::new();
     for change in diff.iter_all_changes() {
         let sign = match change.tag() {
-            ChangeTag::Delete => "-",
-            ChangeTag::Insert => "+",
-            ChangeTag::Equal => " ",
+            ChangeTag::Delete => '-',
+            ChangeTag::Insert => '+',
+            ChangeTag::Equal => ' ',
         };
-        output.push_str(&format!("{}{}", sign, change));
-        if change.missing_newline() {
-            output.push('\n');
+        out.push(sign);
+        out.push_str(change.value().trim_end_matches('\n'));
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+/// Split an identifier into lowercase words on `_`, `-` and case boundaries.
+/// `parseHTTPResponse` → `["parse", "http", "response"]`.
+fn split_identifier(ident: &str) -> Vec<String> {
+    let chars: Vec<char> = ident.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '_' || c == '-' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_is_lower)
+            {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// snake_case, SCREAMING_SNAKE_CASE, camelCase and PascalCase renderings of `words`.
+fn case_variants(words: &[String]) -> [String; 4] {
+    let snake = words.join("_");
+    let screaming = snake.to_uppercase();
+    let pascal: String = words.iter().map(|w| capitalize(w)).collect();
+    let camel = match words.split_first() {
+        Some((first, rest)) => {
+            let tail: String = rest.iter().map(|w| capitalize(w)).collect();
+            format!("{}{}", first, tail)
+        }
+        None => String::new(),
+    };
+    [snake, screaming, camel, pascal]
+}
+
+fn is_identifier(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// One naming-convention variant of a case-preserving rename.
+#[derive(Debug)]
+struct VariantReplacement {
+    old: String,
+    new: String,
+    count: usize,
+}
+
+/// Replace every occurrence of each case variant of `old` with the matching
+/// variant of `new`, in a single pass so replacements are never re-matched.
+/// Returns the new content and per-variant replacement counts.
+fn replace_case_variants(
+    content: &str,
+    old: &str,
+    new: &str,
+) -> Result<(String, Vec<VariantReplacement>), String> {
+    if !is_identifier(old) || !is_identifier(new) {
+        return Err("match_case_variants requires old and new to be identifiers (letters, digits, '_' or '-')".into());
+    }
+
+    let old_variants = case_variants(&split_identifier(old));
+    let new_variants = case_variants(&split_identifier(new));
+
+    let mut pairs: Vec<VariantReplacement> = Vec::new();
+    for (o, n) in old_variants.into_iter().zip(new_variants) {
+        if !o.is_empty() && !pairs.iter().any(|p| p.old == o) {
+            pairs.push(VariantReplacement {
+                old: o,
+                new: n,
+                count: 0,
+            });
+        }
+    }
+    // Longest first so `FOO_BAR` wins over a shorter overlapping variant
+    pairs.sort_by_key(|p| std::cmp::Reverse(p.old.len()));
+
+    let mut output = String::with_capacity(content.len());
+    let mut prev: Option<char> = None;
+    let mut i = 0;
+    'scan: while i < content.len() {
+        let rest = &content[i..];
+        for pair in pairs.iter_mut() {
+            if rest.starts_with(pair.old.as_str()) {
+                // Only match whole identifier words: not mid-word, except a
+                // capitalized variant joined onto a lowercase prefix (`getFooBar`)
+                // or a camel variant followed by the next word (`fooBarValue`)
+                let starts_upper = pair.old.starts_with(char::is_uppercase);
+                let at_start = match prev {
+                    None => true,
+                    Some(p) => !p.is_alphanumeric() || (starts_upper && !p.is_uppercase()),
+                };
+                let camel = !pair.old.contains('_') && !pair.old.ends_with(char::is_uppercase);
+                let at_end = match rest[pair.old.len()..].chars().next() {
+                    None => true,
+                    Some(n) => !n.is_alphanumeric() || (camel && n.is_uppercase()),
+                };
+                if at_start && at_end {
+                    output.push_str(&pair.new);
+                    pair.count += 1;
+                    i += pair.old.len();
+                    prev = pair.old.chars().last();
+                    continue 'scan;
+                }
+            }
+        }
+        let c = rest.chars().next().unwrap_or_default();
+        output.push(c);
+        prev = Some(c);
+        i += c.len_utf8();
+    }
+
+    Ok((output, pairs))
+}
+
+/// A definition located by its signature line for `replace_block`.
+struct Block {
+    /// Byte range from the start of the signature line to the end of the
+    /// block's last line (excluding its newline).
+    range: std::ops::Range<usize>,
+    /// 1-indexed first and last lines.
+    start_line: usize,
+    end_line: usize,
+}
+
+/// Find the single line starting with `signature` (ignoring indentation)
+/// and the block that follows it.
+fn find_block(content: &str, path: &str, signature: &str) -> Result<Block, ToolOutput> {
+    let signature = signature.trim();
+    if signature.lines().count() != 1 {
+        return Err(tool_error(
+            ToolErrorKind::InvalidArguments,
+            "replace_block expects old to be a single signature line",
+        ));
+    }
+
+    let mut offsets = Vec::new();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        offsets.push(offset);
+        offset += line.len();
+    }
+    let lines: Vec<&str> = content.lines().collect();
+    let candidates: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim_start().starts_with(signature))
+        .map(|(i, _)| i)
+        .collect();
+
+    let idx = match candidates.as_slice() {
+        [idx] => *idx,
+        [] => {
+            return Err(tool_error(
+                ToolErrorKind::NotFound,
+                format!("No line starts with the signature '{}'", signature),
+            ))
+        }
+        _ => {
+            let positions: Vec<usize> = candidates.iter().map(|&i| offsets[i]).collect();
+            let (preview, occurrences) = occurrence_preview(content, content, &positions);
+            let mut output = tool_error(
+                ToolErrorKind::NotUnique,
+                format!(
+                    "Found {} lines starting with the signature — must be unique. Provide more of the signature:{}",
+                    candidates.len(),
+                    preview
+                ),
+            );
+            set_metadata(&mut output, "occurrences", json!(occurrences));
+            return Err(output);
         }
+    };
+
+    let end = block_end(&lines, idx, Language::from_path(path));
+    // An unterminated block runs to the end of the file; refuse rather than
+    // replace everything below the signature
+    let last = lines[end].trim_end();
+    if end + 1 == lines.len() && end > idx && !last.ends_with('}') && !last.ends_with(';') {
+        let indented = matches!(Language::from_path(path), Some(Language::Python))
+            || lines[idx].trim_end().ends_with(':');
+        if !indented {
+            return Err(tool_error(
+                ToolErrorKind::NotFound,
+                format!(
+                    "Could not find the end of the block starting at line {}",
+                    idx + 1
+                ),
+            ));
+        }
+    }
+
+    Ok(Block {
+        range: offsets[idx]..offsets[end] + lines[end].len(),
+        start_line: idx + 1,
+        end_line: end + 1,
+    })
+}
+
+/// How strings and comments look in a language checked by [`bracket_balance`].
+struct BracketSyntax {
+    /// Line comments start with `//`, block comments are `/* ... */`.
+    c_comments: bool,
+    /// Line comments start with `#`.
+    hash_comments: bool,
+    /// `'...'` is a string; otherwise `'x'` is a char literal and a lone
+    /// `'` (a Rust lifetime) is ignored.
+    single_quote_strings: bool,
+    /// `` `...` `` is a string.
+    backticks: bool,
+}
+
+impl BracketSyntax {
+    /// Syntax for the file's extension; `None` for files that aren't
+    /// checked (prose, markup, shell scripts with unpaired `case` parens).
+    fn from_path(path: &str) -> Option<Self> {
+        let ext = path.rsplit_once('.').map(|(_, ext)| ext)?;
+        let (c_comments, hash_comments, single_quote_strings, backticks) =
+            match ext.to_ascii_lowercase().as_str() {
+                "rs" | "c" | "h" | "cc" | "cpp" | "hpp" | "java" | "kt" | "cs" | "swift"
+                | "scala" => (true, false, false, false),
+                "go" => (true, false, false, true),
+                "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => {
+                    (true, false, true, true)
+                }
+                "py" | "pyi" => (false, true, true, false),
+                "json" => (false, false, false, false),
+                _ => return None,
+            };
+        Some(Self {
+            c_comments,
+            hash_comments,
+            single_quote_strings,
+            backticks,
+        })
+    }
+}
+
+/// Net unclosed brackets of each kind (negative: extra closers), and
+/// whether the file ends inside a string.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Balance {
+    parens: i64,
+    brackets: i64,
+    braces: i64,
+    open_string: bool,
+}
+
+impl Balance {
+    fn is_balanced(&self) -> bool {
+        *self == Self::default()
     }
 
-    output
+    fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        for (count, open, close) in [
+            (self.braces, '{', '}'),
+            (self.parens, '(', ')'),
+            (self.brackets, '[', ']'),
+        ] {
+            if count > 0 {
+                parts.push(format!("{} unclosed '{}'", count, open));
+            } else if count < 0 {
+                parts.push(format!("{} extra '{}'", -count, close));
+            }
+        }
+        if self.open_string {
+            parts.push("an unterminated string".to_string());
+        }
+        parts.join(", ")
+    }
+}
+
+/// Count brackets outside strings and comments. A heuristic: it knows
+/// nothing of raw strings or nested block comments, which is why edits are
+/// judged by the change in balance rather than the balance itself.
+fn bracket_balance(text: &str, syntax: &BracketSyntax) -> Balance {
+    let chars: Vec<char> = text.chars().collect();
+    let mut balance = Balance::default();
+    let mut quote: Option<char> = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if let Some(q) = quote {
+            if c == '\\' {
+                i += 1;
+            } else if c == q {
+                quote = None;
+            }
+            i += 1;
+            continue;
+        }
+        match c {
+            '"' => quote = Some(c),
+            '`' if syntax.backticks => quote = Some(c),
+            '\'' if syntax.single_quote_strings => quote = Some(c),
+            '\'' => {
+                // Char literal ('x' or '\n'); anything else is a lifetime
+                if next == Some('\\') {
+                    if let Some(end) = (i + 2..chars.len().min(i + 12)).find(|&j| chars[j] == '\'')
+                    {
+                        i = end;
+                    }
+                } else if chars.get(i + 2) == Some(&'\'') {
+                    i += 2;
+                }
+            }
+            '/' if syntax.c_comments && next == Some('/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '#' if syntax.hash_comments => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if syntax.c_comments && next == Some('*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 1;
+            }
+            '(' => balance.parens += 1,
+            ')' => balance.parens -= 1,
+            '[' => balance.brackets += 1,
+            ']' => balance.brackets -= 1,
+            '{' => balance.braces += 1,
+            '}' => balance.braces -= 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    balance.open_string = quote.is_some();
+    balance
+}
+
+/// A warning when an edit leaves `path` with unbalanced brackets or an
+/// unterminated string it didn't have before — usually a truncated `new`.
+fn syntax_warning(path: &str, content: &str, new_content: &str) -> Option<String> {
+    let syntax = BracketSyntax::from_path(path)?;
+    let before = bracket_balance(content, &syntax);
+    let after = bracket_balance(new_content, &syntax);
+    if after.is_balanced() || after == before {
+        return None;
+    }
+    let was = if before.is_balanced() {
+        "was balanced".to_string()
+    } else {
+        format!("had {}", before.describe())
+    };
+    Some(format!(
+        "Warning: after this edit {} has {} (it {} before). The replacement may be truncated \
+         or missing a closing delimiter; read the changed lines and fix them if so.",
+        path,
+        after.describe(),
+        was
+    ))
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl Tool for EditTool {
     fn name(&self) -> &str {
-        "edit"
+        self.overrides.name("edit")
     }
 
     fn definition(&self) -> ToolDefinition {
-        ToolDefinition {
+        self.overrides.apply(ToolDefinition {
             name: "edit".into(),
-            description: "Perform an exact text replacement in a file. The old text must match uniquely. Falls back to fuzzy matching (smart quote normalization, trailing whitespace) if exact match fails.".into(),
+            description: "Perform an exact text replacement in a file. The old text must match uniquely. Falls back to fuzzy matching (smart quote normalization, trailing whitespace, invisible characters such as BOMs and zero-width spaces) if exact match fails. Set match_case_variants to rename an identifier across snake_case/camelCase/PascalCase/SCREAMING_SNAKE_CASE. Set replace_block to pass only a function/struct/class signature line as old and replace the whole definition it opens. To create a new file, pass an empty old with create: true. For .ipynb notebooks pass cell to edit one cell's source (an empty old replaces the whole cell). Alternatively, pass hunk with a single unified-diff hunk instead of old/new.".into(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -91,16 +713,36 @@ impl Tool for EditTool {
                     },
                     "old": {
                         "type": "string",
-                        "description": "Exact text to find and replace"
+                        "description": "Exact text to find and replace (empty when create is true)"
                     },
                     "new": {
                         "type": "string",
                         "description": "Replacement text"
+                    },
+                    "match_case_variants": {
+                        "type": "boolean",
+                        "description": "Treat old/new as identifiers and rename every occurrence of their snake_case, SCREAMING_SNAKE_CASE, camelCase and PascalCase variants (default: false)"
+                    },
+                    "replace_block": {
+                        "type": "boolean",
+                        "description": "Treat old as the signature line of a definition (e.g. 'fn parse(' or 'def load(self') and replace everything from that line through the end of its block — matched braces, or indentation for Python — with new (default: false)"
+                    },
+                    "create": {
+                        "type": "boolean",
+                        "description": "Create a new file containing new; requires an empty old and fails if the file already has content (default: false)"
+                    },
+                    "cell": {
+                        "type": "integer",
+                        "description": "0-indexed notebook cell to edit (.ipynb only); old/new apply to that cell's source"
+                    },
+                    "hunk": {
+                        "type": "string",
+                        "description": "One unified-diff hunk to apply instead of old/new: ' ' lines are context, '-' lines removed, '+' lines added; an optional '@@ -line,count +line,count @@' header picks between repeated matches. Context that has drifted (whitespace, a stale line or two at either end) is tolerated"
                     }
                 },
-                "required": ["path", "old", "new"]
+                "required": ["path"]
             }),
-        }
+        })
     }
 
     async fn execute(
@@ -109,6 +751,19 @@ impl Tool for EditTool {
         arguments: serde_json::Value,
         _partial_tx: Option<mpsc::UnboundedSender<String>>,
     ) -> SoulResult<ToolOutput> {
+        let lock = self.locks.lock_for(&self.cwd, &arguments).await;
+        let mut output = self.apply(arguments).await?;
+        if let Some(lock) = lock {
+            lock.annotate(&mut output);
+        }
+        Ok(output)
+    }
+}
+
+impl EditTool {
+    /// Carry out a call; [`execute`](Tool::execute) holds the path lock
+    /// around it.
+    async fn apply(&self, arguments: serde_json::Value) -> SoulResult<ToolOutput> {
         let path = arguments
             .get("path")
             .and_then(|v| v.as_str())
@@ -121,15 +776,78 @@ impl Tool for EditTool {
             .get("new")
             .and_then(|v| v.as_str())
             .unwrap_or("");
+        let match_case_variants = arguments
+            .get("match_case_variants")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let replace_block = arguments
+            .get("replace_block")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let create = arguments
+            .get("create")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
 
         if path.is_empty() {
-            return Ok(ToolOutput::error("Missing required parameter: path"));
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Missing required parameter: path",
+            ));
+        }
+        if let Some(hunk) = arguments.get("hunk").and_then(|v| v.as_str()) {
+            let conflict = ["old", "new", "cell", "create", "match_case_variants", "replace_block"]
+                .into_iter()
+                .find(|key| {
+                    arguments
+                        .get(*key)
+                        .is_some_and(|v| !(v.is_null() || v == "" || v == false))
+                });
+            if let Some(key) = conflict {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!("hunk can't be combined with {}", key),
+                ));
+            }
+            return self.apply_hunk(path, hunk).await;
+        }
+        if let Some(cell) = arguments.get("cell").and_then(|v| v.as_u64()) {
+            if create || match_case_variants || replace_block {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    "cell cannot be combined with create, match_case_variants or replace_block",
+                ));
+            }
+            return self
+                .edit_notebook_cell(path, cell as usize, old_text, new_text)
+                .await;
+        }
+        if create {
+            if !old_text.is_empty() {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    "old must be empty when create is true",
+                ));
+            }
+            return self.create_file(path, new_text).await;
         }
         if old_text.is_empty() {
-            return Ok(ToolOutput::error("Missing required parameter: old"));
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Missing required parameter: old (to create a new file, pass an empty old with create: true)",
+            ));
+        }
+        if replace_block && match_case_variants {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "replace_block cannot be combined with match_case_variants",
+            ));
         }
         if old_text == new_text {
-            return Ok(ToolOutput::error(
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
                 "old and new text are identical — no change would occur",
             ));
         }
@@ -138,65 +856,398 @@ impl Tool for EditTool {
 
         let exists = self.fs.exists(&resolved).await?;
         if !exists {
-            return Ok(ToolOutput::error(format!("File not found: {}", path)));
+            return Ok(tool_error(
+                ToolErrorKind::NotFound,
+                format!("File not found: {}", path),
+            ));
         }
 
         let content = match self.fs.read_to_string(&resolved).await {
             Ok(c) => c,
-            Err(e) => return Ok(ToolOutput::error(format!("Failed to read {}: {}", path, e))),
+            Err(e) => {
+                return Ok(tool_error(
+                    ToolErrorKind::from_soul_error(&e),
+                    format!("Failed to read {}: {}", path, e),
+                ))
+            }
         };
 
+        // Whole-definition replacement located by its signature line
+        if replace_block {
+            let block = match find_block(&content, path, old_text) {
+                Ok(block) => block,
+                Err(output) => return Ok(output),
+            };
+            let replacement = new_text.strip_suffix('\n').unwrap_or(new_text);
+            let mut new_content = content.clone();
+            new_content.replace_range(block.range, replacement);
+            let mut output = self
+                .write_edit(&resolved, path, &content, &new_content, "block", &[])
+                .await?;
+            if !output.is_error {
+                set_metadata(
+                    &mut output,
+                    "block_lines",
+                    json!([block.start_line, block.end_line]),
+                );
+            }
+            return Ok(output);
+        }
+
+        // Identifier rename across naming conventions: replaces all occurrences
+        if match_case_variants {
+            let (renamed, variants) = match replace_case_variants(&content, old_text, new_text) {
+                Ok(r) => r,
+                Err(msg) => return Ok(tool_error(ToolErrorKind::InvalidArguments, msg)),
+            };
+            if variants.iter().all(|v| v.count == 0) {
+                return Ok(tool_error(
+                    ToolErrorKind::NotFound,
+                    format!("No case variants of '{}' found in file", old_text),
+                ));
+            }
+            return self
+                .write_edit(&resolved, path, &content, &renamed, "case_variants", &variants)
+                .await;
+        }
+
         // Phase 1: exact match
         let matches: Vec<_> = content.match_indices(old_text).collect();
 
         let (new_content, method) = if matches.len() == 1 {
             (content.replacen(old_text, new_text, 1), "exact")
         } else if matches.len() > 1 {
-            return Ok(ToolOutput::error(format!(
-                "Found {} occurrences of the old text — must be unique. Provide more context to disambiguate.",
-                matches.len()
-            )));
+            let positions: Vec<usize> = matches.iter().map(|(i, _)| *i).collect();
+            let (preview, occurrences) = occurrence_preview(&content, &content, &positions);
+            let mut output = tool_error(
+                ToolErrorKind::NotUnique,
+                format!(
+                    "Found {} occurrences of the old text — must be unique. Provide more context to disambiguate:{}",
+                    matches.len(),
+                    preview
+                ),
+            );
+            set_metadata(&mut output, "occurrences", json!(occurrences));
+            return Ok(output);
         } else {
             // Phase 2: fuzzy match
-            let norm_content = normalize_for_fuzzy(&content);
-            let norm_old = normalize_for_fuzzy(old_text);
+            let norm_content = Normalized::new(&content);
+            let norm_old = Normalized::new(old_text).text;
 
-            let fuzzy_matches: Vec<_> = norm_content.match_indices(&norm_old).collect();
+            let fuzzy_matches: Vec<_> = norm_content.text.match_indices(&norm_old).collect();
 
             if fuzzy_matches.len() == 1 {
-                // Find the corresponding position in the original content
-                let fuzzy_pos = fuzzy_matches[0].0;
-                // Map normalized position back to original by matching line-by-line
-                let norm_lines_before = norm_content[..fuzzy_pos].lines().count();
-                let original_lines: Vec<&str> = content.lines().collect();
-                let search_lines: Vec<&str> = old_text.lines().collect();
-
-                if norm_lines_before > 0 && norm_lines_before <= original_lines.len() {
-                    let start_line = norm_lines_before.saturating_sub(1);
-                    let end_line = (start_line + search_lines.len()).min(original_lines.len());
-                    let original_section = original_lines[start_line..end_line].join("\n");
-                    (content.replacen(&original_section, new_text, 1), "fuzzy")
-                } else {
-                    // Fallback: replace in normalized then write
-                    let result = norm_content.replacen(&norm_old, new_text, 1);
-                    (result, "fuzzy")
-                }
+                // Replace only the original bytes the match came from, so
+                // invisible characters elsewhere in the file are kept
+                let pos = fuzzy_matches[0].0;
+                let range = norm_content.original_range(pos, pos + norm_old.len());
+                let mut result = String::with_capacity(content.len() + new_text.len());
+                result.push_str(&content[..range.start]);
+                result.push_str(new_text);
+                result.push_str(&content[range.end..]);
+                (result, "fuzzy")
             } else if fuzzy_matches.len() > 1 {
-                return Ok(ToolOutput::error(format!(
-                    "Found {} fuzzy occurrences — must be unique. Provide more context.",
-                    fuzzy_matches.len()
-                )));
+                let positions: Vec<usize> = fuzzy_matches.iter().map(|(i, _)| *i).collect();
+                let (preview, occurrences) =
+                    occurrence_preview(&norm_content.text, &content, &positions);
+                let mut output = tool_error(
+                    ToolErrorKind::NotUnique,
+                    format!(
+                        "Found {} fuzzy occurrences — must be unique. Provide more context:{}",
+                        fuzzy_matches.len(),
+                        preview
+                    ),
+                );
+                set_metadata(&mut output, "occurrences", json!(occurrences));
+                return Ok(output);
             } else {
-                return Ok(ToolOutput::error(
-                    "Text not found in file (tried exact and fuzzy matching). Verify the old text matches the file content.",
+                let message = "Text not found in file (tried exact and fuzzy matching).";
+                let Some(candidate) = closest_candidate(&content, old_text) else {
+                    return Ok(tool_error(
+                        ToolErrorKind::NotFound,
+                        format!("{} Verify the old text matches the file content.", message),
+                    ));
+                };
+                let mut output = tool_error(
+                    ToolErrorKind::NotFound,
+                    format!(
+                        "{} Closest match is lines {}-{} ({:.0}% similar); '-' is your old text, '+' is the file:\n{}",
+                        message,
+                        candidate.start_line,
+                        candidate.end_line,
+                        candidate.ratio * 100.0,
+                        candidate_diff(old_text, &candidate.text)
+                    ),
+                );
+                set_metadata(
+                    &mut output,
+                    "closest_match",
+                    json!({
+                        "start_line": candidate.start_line,
+                        "end_line": candidate.end_line,
+                        "similarity": candidate.ratio,
+                        "text": candidate.text,
+                    }),
+                );
+                return Ok(output);
+            }
+        };
+
+        self.write_edit(&resolved, path, &content, &new_content, method, &[])
+            .await
+    }
+}
+
+impl EditTool {
+    /// Hunk mode: apply one unified-diff hunk, located as `patch` would.
+    async fn apply_hunk(&self, path: &str, text: &str) -> SoulResult<ToolOutput> {
+        let hunk = match Hunk::parse(text) {
+            Ok(hunk) => hunk,
+            Err(message) => return Ok(tool_error(ToolErrorKind::InvalidArguments, message)),
+        };
+        let resolved = resolve_path(&self.cwd, path);
+        let content = match self.fs.read_to_string(&resolved).await {
+            Ok(c) => c,
+            Err(e) => {
+                return Ok(tool_error(
+                    ToolErrorKind::from_soul_error(&e),
+                    format!("Failed to read {}: {}", path, e),
+                ))
+            }
+        };
+
+        let applied = match hunk.apply(&content) {
+            Ok(applied) => applied,
+            Err(HunkError::Ambiguous(lines)) => {
+                let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+                return Ok(tool_error(
+                    ToolErrorKind::NotUnique,
+                    format!(
+                        "The hunk matches at lines {}. Add an @@ header with the line number, or more context",
+                        lines.join(", ")
+                    ),
+                ));
+            }
+            Err(HunkError::NotFound) => {
+                let old: Vec<&str> = hunk
+                    .lines
+                    .iter()
+                    .filter_map(|l| match l {
+                        HunkLine::Context(text) | HunkLine::Removed(text) => Some(text.as_str()),
+                        HunkLine::Added(_) => None,
+                    })
+                    .collect();
+                let message = "The hunk's context and '-' lines were not found in the file.";
+                let Some(candidate) = closest_candidate(&content, &old.join("\n")) else {
+                    return Ok(tool_error(ToolErrorKind::NotFound, message));
+                };
+                return Ok(tool_error(
+                    ToolErrorKind::NotFound,
+                    format!(
+                        "{} Closest match is lines {}-{} ({:.0}% similar); '-' is the hunk, '+' is the file:\n{}",
+                        message,
+                        candidate.start_line,
+                        candidate.end_line,
+                        candidate.ratio * 100.0,
+                        candidate_diff(&old.join("\n"), &candidate.text)
+                    ),
                 ));
             }
         };
+        if applied.content == content {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "The hunk changes nothing",
+            ));
+        }
+
+        let mut output = self
+            .write_edit(&resolved, path, &content, &applied.content, "hunk", &[])
+            .await?;
+        if !output.is_error {
+            set_metadata(
+                &mut output,
+                "hunk",
+                json!({
+                    "start_line": applied.start_line,
+                    "fuzz": applied.fuzz,
+                    "line_match": applied.line_match,
+                }),
+            );
+        }
+        Ok(output)
+    }
+
+    /// Notebook mode: replace `old_text` within one cell's source (or the
+    /// whole source when `old_text` is empty) and re-serialize the notebook.
+    async fn edit_notebook_cell(
+        &self,
+        path: &str,
+        cell: usize,
+        old_text: &str,
+        new_text: &str,
+    ) -> SoulResult<ToolOutput> {
+        if !is_notebook(path) {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                format!("cell only applies to .ipynb notebooks, not {}", path),
+            ));
+        }
+
+        let resolved = resolve_path(&self.cwd, path);
+        let content = match self.fs.read_to_string(&resolved).await {
+            Ok(c) => c,
+            Err(e) => {
+                return Ok(tool_error(
+                    ToolErrorKind::from_soul_error(&e),
+                    format!("Failed to read {}: {}", path, e),
+                ))
+            }
+        };
+        let mut notebook = match Notebook::parse(&content) {
+            Ok(nb) => nb,
+            Err(e) => return Ok(tool_error(ToolErrorKind::Encoding, format!("{}: {}", path, e))),
+        };
+        let Some(source) = notebook.cell_source(cell) else {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                format!(
+                    "Cell {} out of range ({} has {} cells)",
+                    cell,
+                    path,
+                    notebook.len()
+                ),
+            ));
+        };
 
-        // Write the modified content
-        match self.fs.write(&resolved, &new_content).await {
+        let new_source = if old_text.is_empty() {
+            new_text.to_string()
+        } else {
+            match source.matches(old_text).count() {
+                0 => {
+                    return Ok(tool_error(
+                        ToolErrorKind::NotFound,
+                        format!(
+                            "Text not found in cell {}. Read the cell to check its current source.",
+                            cell
+                        ),
+                    ))
+                }
+                1 => source.replacen(old_text, new_text, 1),
+                n => {
+                    return Ok(tool_error(
+                        ToolErrorKind::NotUnique,
+                        format!(
+                            "Found {} occurrences of the old text in cell {} — must be unique",
+                            n, cell
+                        ),
+                    ))
+                }
+            }
+        };
+        if new_source == source {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                format!(
+                    "Cell {} already has this source — no change would occur",
+                    cell
+                ),
+            ));
+        }
+
+        if let Err(e) = notebook.set_cell_source(cell, &new_source) {
+            return Ok(tool_error(ToolErrorKind::InvalidArguments, e));
+        }
+        let mode = current_mode(&self.modes, &resolved).await;
+        match self.fs.write(&resolved, &notebook.to_json()).await {
             Ok(()) => {
-                let diff = unified_diff(&content, &new_content, path);
+                restore_mode(&self.modes, &resolved, mode).await;
+                let label = format!("{} [cell {}]", path, cell);
+                let diff = render_diff(&source, &new_source, &label, self.max_diff_bytes);
+                Ok(ToolOutput::success(format!(
+                    "Applied edit to {} (notebook_cell)\n\n{}",
+                    label, diff.text
+                ))
+                .with_metadata(json!({
+                    "method": "notebook_cell",
+                    "cell": cell,
+                    "path": path,
+                    "replacements": 1,
+                    "diff": diff.to_metadata(),
+                })))
+            }
+            Err(e) => Ok(tool_error(
+                ToolErrorKind::from_soul_error(&e),
+                format!("Failed to write {}: {}", path, e),
+            )),
+        }
+    }
+
+    /// Create mode: write `new_text` to a file that is missing or empty.
+    async fn create_file(&self, path: &str, new_text: &str) -> SoulResult<ToolOutput> {
+        let resolved = resolve_path(&self.cwd, path);
+
+        let existing = if self.fs.exists(&resolved).await? {
+            match self.fs.read_to_string(&resolved).await {
+                Ok(c) => c,
+                Err(e) => {
+                    return Ok(tool_error(
+                        ToolErrorKind::from_soul_error(&e),
+                        format!("Failed to read {}: {}", path, e),
+                    ))
+                }
+            }
+        } else {
+            String::new()
+        };
+        if !existing.is_empty() {
+            return Ok(tool_error(
+                ToolErrorKind::AlreadyExists,
+                format!(
+                    "File already exists: {}. Use old/new to edit it, or the write tool to overwrite it.",
+                    path
+                ),
+            ));
+        }
+
+        // Auto-create parent directories
+        if let Some(parent) = resolved.rsplit_once('/') {
+            if !parent.0.is_empty() {
+                let _ = self.fs.create_dir_all(parent.0).await;
+            }
+        }
+
+        self.write_edit(&resolved, path, "", new_text, "create", &[])
+            .await
+    }
+
+    /// Write the edited content and report the diff.
+    async fn write_edit(
+        &self,
+        resolved: &str,
+        path: &str,
+        content: &str,
+        new_content: &str,
+        method: &str,
+        variants: &[VariantReplacement],
+    ) -> SoulResult<ToolOutput> {
+        let replacements = if variants.is_empty() {
+            1
+        } else {
+            variants.iter().map(|v| v.count).sum()
+        };
+        let variant_meta: serde_json::Map<String, serde_json::Value> = variants
+            .iter()
+            .filter(|v| v.count > 0)
+            .map(|v| (v.old.clone(), json!({"new": v.new, "count": v.count})))
+            .collect();
+
+        // Write the modified content, keeping the file's permissions
+        let mode = current_mode(&self.modes, resolved).await;
+        match self.fs.write(resolved, new_content).await {
+            Ok(()) => {
+                restore_mode(&self.modes, resolved, mode).await;
+                let diff = render_diff(content, new_content, path, self.max_diff_bytes);
                 // Find first changed line
                 let first_changed_line = content
                     .lines()
@@ -206,20 +1257,32 @@ impl Tool for EditTool {
                     .map(|(i, _)| i + 1)
                     .unwrap_or(1);
 
-                Ok(ToolOutput::success(format!(
-                    "Applied edit to {} ({})\n\n{}",
-                    path, method, diff
-                ))
-                .with_metadata(json!({
+                let summary = if replacements > 1 {
+                    format!("{}, {} replacements", method, replacements)
+                } else {
+                    method.to_string()
+                };
+                let warning = syntax_warning(path, content, new_content);
+
+                let mut text = format!("Applied edit to {} ({})\n\n{}", path, summary, diff.text);
+                if let Some(warning) = &warning {
+                    text.push_str("\n\n");
+                    text.push_str(warning);
+                }
+                Ok(ToolOutput::success(text).with_metadata(json!({
                     "method": method,
                     "first_changed_line": first_changed_line,
                     "path": path,
+                    "replacements": replacements,
+                    "variants": variant_meta,
+                    "syntax_warning": warning.is_some(),
+                    "diff": diff.to_metadata(),
                 })))
             }
-            Err(e) => Ok(ToolOutput::error(format!(
-                "Failed to write {}: {}",
-                path, e
-            ))),
+            Err(e) => Ok(tool_error(
+                ToolErrorKind::from_soul_error(&e),
+                format!("Failed to write {}: {}", path, e),
+            )),
         }
     }
 }
@@ -306,6 +1369,32 @@ mod tests {
         assert!(result.content.contains("occurrences"));
     }
 
+    #[tokio::test]
+    async fn multiple_matches_preview_lines() {
+        let (fs, tool) = setup().await;
+        fs.write(
+            "/project/lib.rs",
+            "fn a() {\n    let x = 1;\n}\nfn b() {\n    let x = 1;\n}\n",
+        )
+        .await
+        .unwrap();
+
+        let result = tool
+            .execute(
+                "c3",
+                json!({"path": "lib.rs", "old": "let x = 1;", "new": "let x = 2;"}),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(result.is_error);
+        assert!(result.content.contains("line 2: let x = 1;"));
+        assert!(result.content.contains("line 5: let x = 1;"));
+        assert_eq!(result.metadata["occurrences"][1]["line"], 5);
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotUnique));
+    }
+
     #[tokio::test]
     async fn text_not_found() {
         let (fs, tool) = setup().await;
@@ -324,6 +1413,41 @@ mod tests {
 
         assert!(result.is_error);
         assert!(result.content.contains("not found"));
+        assert!(result.metadata.get("closest_match").is_none());
+    }
+
+    #[tokio::test]
+    async fn text_not_found_suggests_closest() {
+        let (fs, tool) = setup().await;
+        fs.write(
+            "/project/lib.rs",
+            "use std::io;\n\nfn load(path: &str) -> io::Result<String> {\n    let data = std::fs::read_to_string(path)?;\n    Ok(data)\n}\n\nfn main() {}\n",
+        )
+        .await
+        .unwrap();
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({
+                    "path": "lib.rs",
+                    "old": "fn load(path: &Path) -> io::Result<String> {\n    let data = fs::read_to_string(path)?;",
+                    "new": "x"
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+        assert!(result.content.contains("Closest match is lines 3-4"), "{}", result.content);
+        assert!(result
+            .content
+            .contains("-fn load(path: &Path) -> io::Result<String> {"));
+        assert!(result
+            .content
+            .contains("+fn load(path: &str) -> io::Result<String> {"));
+        assert_eq!(result.metadata["closest_match"]["start_line"], 3);
+        assert_eq!(result.metadata["closest_match"]["end_line"], 4);
     }
 
     #[tokio::test]
@@ -358,6 +1482,97 @@ mod tests {
         assert!(result.is_error);
     }
 
+    #[tokio::test]
+    async fn large_diff_is_capped() {
+        let (fs, tool) = setup().await;
+        let tool = tool.with_max_diff_bytes(300);
+        let old: String = (1..=60).map(|i| format!("let a{} = {};\n", i, i)).collect();
+        let new = old.replace("let a", "let b");
+        fs.write("/project/big.rs", &old).await.unwrap();
+
+        let result = tool
+            .execute("c1", json!({"path": "big.rs", "old": old, "new": new}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.len() < 500, "{}", result.content);
+        assert!(result.content.contains("more lines in this hunk …"));
+        assert_eq!(result.metadata["diff"]["removed"], 60);
+        assert_eq!(result.metadata["diff"]["added"], 60);
+        assert_eq!(result.metadata["diff"]["truncated"], true);
+    }
+
+    #[cfg(all(feature = "native", unix))]
+    #[tokio::test]
+    async fn edit_keeps_executable_bit() {
+        use crate::file_modes::{FileModes, NativeFileModes};
+        use soul_core::vfs::NativeFs;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap().to_string();
+        let modes = Arc::new(NativeFileModes::new(&root));
+        let tool = EditTool::new(Arc::new(NativeFs::new(&root)), root.clone())
+            .with_file_modes(modes.clone());
+        let script = format!("{}/build.sh", root);
+        std::fs::write(&script, "#!/bin/sh\necho old\n").unwrap();
+        modes.set_mode(&script, 0o755).await.unwrap();
+
+        let result = tool
+            .execute("c1", json!({"path": "build.sh", "old": "old", "new": "new"}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(std::fs::read_to_string(&script).unwrap(), "#!/bin/sh\necho new\n");
+        assert_eq!(modes.mode(&script).await, Some(0o755));
+    }
+
+    #[tokio::test]
+    async fn syntax_warning_on_unbalanced_edit() {
+        let (fs, tool) = setup().await;
+        let src = "fn main() {\n    let c = '{';\n    if ok() {\n        run(\"}\");\n    }\n}\n";
+        fs.write("/project/main.rs", src).await.unwrap();
+
+        // Truncated replacement drops the closing brace of the if
+        let result = tool
+            .execute(
+                "c1",
+                json!({
+                    "path": "main.rs",
+                    "old": "        run(\"}\");\n    }\n",
+                    "new": "        go();\n"
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.metadata["syntax_warning"], true);
+        assert!(result.content.contains("has 1 unclosed '{' (it was balanced before)"));
+
+        // Edits that keep the balance (comments and strings aside) are quiet
+        let result = tool
+            .execute(
+                "c2",
+                json!({
+                    "path": "main.rs",
+                    "old": "        go();\n",
+                    "new": "        go(); // {\n    }\n"
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["syntax_warning"], false);
+        assert!(!result.content.contains("Warning"));
+
+        fs.write("/project/notes.txt", "(a)").await.unwrap();
+        let result = tool
+            .execute("c3", json!({"path": "notes.txt", "old": "(a)", "new": "(a"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["syntax_warning"], false);
+    }
+
     #[tokio::test]
     async fn diff_output() {
         let (fs, tool) = setup().await;
@@ -379,6 +1594,139 @@ mod tests {
         assert!(result.content.contains("+modified"));
     }
 
+    #[tokio::test]
+    async fn case_variant_rename() {
+        let (fs, tool) = setup().await;
+        fs.write(
+            "/project/user.rs",
+            "const MAX_USER_ID: u32 = 1;\nstruct UserId;\nfn parse_user_id() {}\nlet userId = getUserId();\n",
+        )
+        .await
+        .unwrap();
+
+        let result = tool
+            .execute(
+                "c8",
+                json!({
+                    "path": "user.rs",
+                    "old": "user_id",
+                    "new": "account_key",
+                    "match_case_variants": true
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error, "{}", result.content);
+        let content = fs.read_to_string("/project/user.rs").await.unwrap();
+        assert_eq!(
+            content,
+            "const MAX_ACCOUNT_KEY: u32 = 1;\nstruct AccountKey;\nfn parse_account_key() {}\nlet accountKey = getAccountKey();\n"
+        );
+        assert_eq!(result.metadata["method"], "case_variants");
+        assert_eq!(result.metadata["replacements"], 5);
+    }
+
+    #[tokio::test]
+    async fn case_variant_rename_skips_longer_words() {
+        let (fs, tool) = setup().await;
+        fs.write(
+            "/project/user.rs",
+            "let user_identity = UserIdentity::new(user_id);
+let userIdValue = USER_IDS + USER_ID_MAX;
+",
+        )
+        .await
+        .unwrap();
+
+        let result = tool
+            .execute(
+                "c8",
+                json!({
+                    "path": "user.rs",
+                    "old": "user_id",
+                    "new": "account_key",
+                    "match_case_variants": true
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error, "{}", result.content);
+        let content = fs.read_to_string("/project/user.rs").await.unwrap();
+        assert_eq!(
+            content,
+            "let user_identity = UserIdentity::new(account_key);
+let accountKeyValue = USER_IDS + ACCOUNT_KEY_MAX;
+"
+        );
+        assert_eq!(result.metadata["replacements"], 3);
+    }
+
+    #[tokio::test]
+    async fn case_variant_requires_identifiers() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/a.txt", "foo bar").await.unwrap();
+
+        let result = tool
+            .execute(
+                "c9",
+                json!({"path": "a.txt", "old": "foo bar", "new": "baz", "match_case_variants": true}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(result.content.contains("identifiers"));
+    }
+
+    #[tokio::test]
+    async fn hunk_edit() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/a.rs", "fn a() {\n    one();\n    two();\n}\n")
+            .await
+            .unwrap();
+
+        let hunk = "@@ -1,4 +1,4 @@\n fn a() {\n     one();\n-    two();\n+    three();\n }\n";
+        let result = tool
+            .execute("c1", json!({"path": "a.rs", "hunk": hunk}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.starts_with("Applied edit to a.rs (hunk)"));
+        assert_eq!(result.metadata["hunk"]["start_line"], 1);
+        assert_eq!(
+            fs.read_to_string("/project/a.rs").await.unwrap(),
+            "fn a() {\n    one();\n    three();\n}\n"
+        );
+
+        let result = tool
+            .execute("c2", json!({"path": "a.rs", "hunk": "-    four();\n+    five();\n"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+        let result = tool
+            .execute("c3", json!({"path": "a.rs", "hunk": hunk, "old": "x"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "hunk can't be combined with old");
+    }
+
+    #[test]
+    fn split_identifier_words() {
+        assert_eq!(split_identifier("parseHTTPResponse"), vec!["parse", "http", "response"]);
+        assert_eq!(split_identifier("MAX_USER_ID"), vec!["max", "user", "id"]);
+        assert_eq!(split_identifier("UserId"), vec!["user", "id"]);
+    }
+
+    #[test]
+    fn case_variants_rendering() {
+        let words = split_identifier("user_id");
+        assert_eq!(case_variants(&words), ["user_id", "USER_ID", "userId", "UserId"]);
+    }
+
     #[test]
     fn normalize_fuzzy_quotes() {
         let input = "\u{201C}hello\u{201D} \u{2018}world\u{2019}";
@@ -400,6 +1748,209 @@ mod tests {
         assert_eq!(normalized, "hello\nworld");
     }
 
+    #[test]
+    fn normalize_fuzzy_invisible_characters() {
+        let input = "\u{FEFF}a\u{200B}b \u{200D}\r\n\u{202E}c\u{2069}";
+        let normalized = Normalized::new(input);
+        assert_eq!(normalized.text, "ab\nc");
+        assert_eq!(normalized.original_range(1, 2), 7..8);
+        assert_eq!(normalized.original_range(0, 4), 3..18);
+    }
+
+    #[tokio::test]
+    async fn fuzzy_ignores_invisible_characters() {
+        let (fs, tool) = setup().await;
+        let original = "\u{FEFF}// \u{2067}note\u{2069}\n\
+                        let total = price\u{200B} * qty;\nlet x = 1;\n";
+        fs.write("/project/calc.rs", original).await.unwrap();
+
+        // The snippet carries a zero-width space the file doesn't have there
+        let result = tool
+            .execute(
+                "c1",
+                json!({
+                    "path": "calc.rs",
+                    "old": "total = price * qty;\nlet x\u{200B} = 1;",
+                    "new": "total = price * qty + tax;\nlet x = 2;"
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.metadata["method"], "fuzzy");
+        let content = fs.read_to_string("/project/calc.rs").await.unwrap();
+        assert_eq!(
+            content,
+            "\u{FEFF}// \u{2067}note\u{2069}\nlet total = price * qty + tax;\nlet x = 2;\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn create_mode() {
+        let (fs, tool) = setup().await;
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({"path": "src/new.rs", "old": "", "new": "fn new() {}\n", "create": true}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("+fn new() {}"));
+        assert_eq!(result.metadata["method"], "create");
+        assert_eq!(
+            fs.read_to_string("/project/src/new.rs").await.unwrap(),
+            "fn new() {}\n"
+        );
+
+        // Refuses to clobber existing content
+        let result = tool
+            .execute(
+                "c2",
+                json!({"path": "src/new.rs", "old": "", "new": "x", "create": true}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(result.content.contains("already exists"));
+    }
+
+    #[tokio::test]
+    async fn notebook_cell_edit() {
+        let (fs, tool) = setup().await;
+        fs.write(
+            "/project/nb.ipynb",
+            r#"{"cells": [{"cell_type": "code", "source": ["x = 1\n", "print(x)"], "outputs": []}, {"cell_type": "code", "source": "x = 1"}], "nbformat": 4}"#,
+        )
+        .await
+        .unwrap();
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({"path": "nb.ipynb", "cell": 0, "old": "x = 1", "new": "x = \"a\\nb\""}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("+x = \"a\\nb\""));
+        assert_eq!(result.metadata["cell"], 0);
+
+        let saved =
+            Notebook::parse(&fs.read_to_string("/project/nb.ipynb").await.unwrap()).unwrap();
+        assert_eq!(saved.cell_source(0).unwrap(), "x = \"a\\nb\"\nprint(x)");
+        assert_eq!(saved.cell_source(1).unwrap(), "x = 1");
+
+        // Empty old replaces the whole cell
+        let result = tool
+            .execute(
+                "c2",
+                json!({"path": "nb.ipynb", "cell": 1, "old": "", "new": "y = 2"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+
+        let result = tool
+            .execute(
+                "c3",
+                json!({"path": "nb.ipynb", "cell": 7, "old": "", "new": "z"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::InvalidArguments));
+    }
+
+    #[tokio::test]
+    async fn replace_block_by_signature() {
+        let (fs, tool) = setup().await;
+        fs.write(
+            "/project/lib.rs",
+            "fn keep() {}\n\npub fn parse(input: &str) -> u32 {\n    let s = \"}\";\n    input.len() as u32\n}\n\nfn after() {}\n",
+        )
+        .await
+        .unwrap();
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({
+                    "path": "lib.rs",
+                    "old": "pub fn parse(",
+                    "new": "pub fn parse(_input: &str) -> u32 {\n    0\n}\n",
+                    "replace_block": true
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.metadata["method"], "block");
+        assert_eq!(result.metadata["block_lines"], json!([3, 6]));
+        assert_eq!(
+            fs.read_to_string("/project/lib.rs").await.unwrap(),
+            "fn keep() {}\n\npub fn parse(_input: &str) -> u32 {\n    0\n}\n\nfn after() {}\n"
+        );
+
+        let result = tool
+            .execute(
+                "c2",
+                json!({"path": "lib.rs", "old": "fn", "new": "x", "replace_block": true}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotUnique));
+    }
+
+    #[tokio::test]
+    async fn replace_block_python_indentation() {
+        let (fs, tool) = setup().await;
+        fs.write(
+            "/project/app.py",
+            "class A:\n    def run(self):\n        x = 1\n\n        return x\n\n    def stop(self):\n        pass\n",
+        )
+        .await
+        .unwrap();
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({
+                    "path": "app.py",
+                    "old": "def run(self)",
+                    "new": "    def run(self):\n        return 2",
+                    "replace_block": true
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(
+            fs.read_to_string("/project/app.py").await.unwrap(),
+            "class A:\n    def run(self):\n        return 2\n\n    def stop(self):\n        pass\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_old_without_create_hints() {
+        let (_fs, tool) = setup().await;
+        let result = tool
+            .execute("c1", json!({"path": "a.rs", "old": "", "new": "x"}), None)
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(result.content.contains("create: true"));
+    }
+
     #[test]
     fn tool_name_and_definition() {
         let fs = Arc::new(MemoryFs::new());
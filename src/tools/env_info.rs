@@ -0,0 +1,419 @@
+//! Env info tool — what the executor's environment has to offer, in one call.
+//!
+//! Sessions that run commands tend to open with `uname`, `which git`,
+//! `node --version`, `echo $PATH`, one turn each. `env_info` runs a single
+//! POSIX `sh` script through the [`VirtualExecutor`] and reports the OS and
+//! architecture, the login shell, whether each of a list of key tools is
+//! installed (with its path and version) and a summary of `PATH`, flagging
+//! directories that are missing or listed twice.
+//!
+//! The script needs a POSIX shell; executors that can't run one (such as
+//! [`NoopExecutor`](soul_core::vexec::NoopExecutor)) get an error.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vexec::VirtualExecutor;
+
+use crate::error::{tool_error, ToolErrorKind};
+
+use super::{impl_overrides, ToolOverrides};
+
+/// Tools probed unless configured otherwise.
+pub const DEFAULT_TOOLS: &[&str] = &[
+    "bash", "git", "node", "npm", "cargo", "rustc", "python3", "python", "go", "make",
+];
+
+/// Default timeout for the probe script, in seconds.
+pub const DEFAULT_TIMEOUT: u64 = 15;
+
+/// Most extra tools a call may ask about.
+const MAX_EXTRA_TOOLS: usize = 20;
+
+/// System-prompt guidance returned by [`EnvInfoTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Call once before running commands in a new environment instead of probing it \
+    with which, --version and echo $PATH: it reports the OS, shell, installed tool \
+    versions and PATH in one call. Pass tools to check for anything else you need.";
+
+/// One probed tool.
+struct ToolProbe {
+    name: String,
+    path: Option<String>,
+    /// First line of `<tool> --version`.
+    version_line: Option<String>,
+}
+
+/// What the probe script reported.
+#[derive(Default)]
+struct Probe {
+    os: String,
+    kernel: String,
+    arch: String,
+    distro: Option<String>,
+    shell: Option<String>,
+    tools: Vec<ToolProbe>,
+    /// `PATH` entries in order, and whether each directory exists.
+    path: Vec<(String, bool)>,
+}
+
+/// The `sh` script probing the OS, shell, `tools` and `PATH`. Each line of
+/// its output is `key=value`; tool lines are `tool=name\tpath\tversion`.
+fn probe_script(tools: &[String]) -> String {
+    format!(
+        r#"printf 'os=%s\n' "$(uname -s 2>/dev/null)"
+printf 'kernel=%s\n' "$(uname -r 2>/dev/null)"
+printf 'arch=%s\n' "$(uname -m 2>/dev/null)"
+if [ -r /etc/os-release ]; then printf 'distro=%s\n' "$(. /etc/os-release && echo "$PRETTY_NAME")"; fi
+if command -v sw_vers >/dev/null 2>&1; then printf 'distro=macOS %s\n' "$(sw_vers -productVersion)"; fi
+printf 'shell=%s\n' "${{SHELL:-}}"
+for t in {}; do
+  p=$(command -v "$t" 2>/dev/null)
+  if [ -n "$p" ]; then
+    v=$("$t" --version 2>&1 </dev/null | head -n 1)
+    printf 'tool=%s\t%s\t%s\n' "$t" "$p" "$v"
+  else
+    printf 'tool=%s\n' "$t"
+  fi
+done
+(IFS=:; for d in $PATH; do
+  if [ -d "$d" ]; then printf 'path=1%s\n' "$d"; else printf 'path=0%s\n' "$d"; fi
+done)
+"#,
+        tools.join(" ")
+    )
+}
+
+/// Parse the probe script's output.
+fn parse_probe(stdout: &str) -> Probe {
+    let mut probe = Probe::default();
+    for line in stdout.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim_end_matches('\r');
+        let non_empty = || Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        match key {
+            "os" => probe.os = value.to_string(),
+            "kernel" => probe.kernel = value.to_string(),
+            "arch" => probe.arch = value.to_string(),
+            "distro" => probe.distro = non_empty(),
+            "shell" => probe.shell = non_empty(),
+            "tool" => {
+                let mut fields = value.splitn(3, '\t');
+                let name = fields.next().unwrap_or_default().to_string();
+                let path = fields.next().map(str::to_string);
+                let version_line = fields
+                    .next()
+                    .map(|v| v.trim().to_string())
+                    .filter(|v| !v.is_empty());
+                probe.tools.push(ToolProbe {
+                    name,
+                    path,
+                    version_line,
+                });
+            }
+            "path" if !value.is_empty() => {
+                let (exists, dir) = value.split_at(1);
+                probe.path.push((dir.to_string(), exists == "1"));
+            }
+            _ => {}
+        }
+    }
+    probe
+}
+
+/// The version number in a `--version` line: the first word that, past a
+/// prefix like `v` or `go`, starts with a digit and contains a dot.
+fn version_number(line: &str) -> Option<&str> {
+    line.split_whitespace().find_map(|word| {
+        let start = word.find(|c: char| c.is_ascii_digit())?;
+        let version = word[start..].trim_end_matches([',', ')', ';']);
+        let prefix_ok = word[..start].chars().all(|c| c.is_ascii_alphabetic());
+        (prefix_ok && version.contains('.')).then_some(version)
+    })
+}
+
+/// Whether `name` can be put in the probe script as-is.
+fn is_tool_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '+'))
+}
+
+pub struct EnvInfoTool {
+    executor: Arc<dyn VirtualExecutor>,
+    cwd: String,
+    tools: Vec<String>,
+    timeout: u64,
+    overrides: ToolOverrides,
+}
+
+impl EnvInfoTool {
+    pub fn new(executor: Arc<dyn VirtualExecutor>, cwd: impl Into<String>) -> Self {
+        Self {
+            executor,
+            cwd: cwd.into(),
+            tools: DEFAULT_TOOLS.iter().map(|t| t.to_string()).collect(),
+            timeout: DEFAULT_TIMEOUT,
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+    /// Probe these tools instead of [`DEFAULT_TOOLS`]. Names that aren't
+    /// plain command names are dropped.
+    pub fn with_tools<I, S>(mut self, tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tools = tools
+            .into_iter()
+            .map(Into::into)
+            .filter(|t| is_tool_name(t))
+            .collect();
+        self
+    }
+
+    /// Give up on the probe script after `secs` seconds (default: 15).
+    pub fn with_timeout(mut self, secs: u64) -> Self {
+        self.timeout = secs;
+        self
+    }
+}
+
+impl_overrides!(EnvInfoTool);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for EnvInfoTool {
+    fn name(&self) -> &str {
+        self.overrides.name("env_info")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "env_info".into(),
+            description: format!("Describe the command environment in one call: OS and architecture, login shell, PATH (with missing and duplicate directories flagged) and the path and version of key tools ({}). Use before running commands instead of which/--version probes.", self.tools.join(", ")),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "tools": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "Extra command names to check for, e.g. [\"docker\", \"pnpm\"]"
+                    }
+                }
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let mut tools = self.tools.clone();
+        if let Some(extra) = arguments.get("tools").filter(|v| !v.is_null()) {
+            let Some(extra) = extra.as_array() else {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    "tools must be an array of command names",
+                ));
+            };
+            if extra.len() > MAX_EXTRA_TOOLS {
+                return Ok(tool_error(
+                    ToolErrorKind::TooLarge,
+                    format!("At most {} extra tools per call", MAX_EXTRA_TOOLS),
+                ));
+            }
+            for name in extra {
+                match name.as_str().map(str::trim) {
+                    Some(name) if is_tool_name(name) => {
+                        if !tools.iter().any(|t| t == name) {
+                            tools.push(name.to_string());
+                        }
+                    }
+                    _ => {
+                        return Ok(tool_error(
+                            ToolErrorKind::InvalidArguments,
+                            format!("Not a command name: {}", name),
+                        ))
+                    }
+                }
+            }
+        }
+
+        let script = probe_script(&tools);
+        let output = match self
+            .executor
+            .exec_shell(&script, self.timeout, Some(&self.cwd))
+            .await
+        {
+            Ok(output) => output,
+            Err(e) => {
+                return Ok(tool_error(
+                    ToolErrorKind::ExecutorFailure,
+                    format!("Failed to run the environment probe: {}", e),
+                ))
+            }
+        };
+        let probe = parse_probe(&output.stdout);
+        if probe.os.is_empty() && probe.tools.is_empty() {
+            let reason = output.stderr.trim();
+            return Ok(tool_error(
+                ToolErrorKind::Unsupported,
+                format!(
+                    "The executor can't run the environment probe (it needs a POSIX sh){}",
+                    if reason.is_empty() {
+                        String::new()
+                    } else {
+                        format!(": {}", reason)
+                    }
+                ),
+            ));
+        }
+
+        let mut out = format!("OS: {} {} {}", probe.os, probe.kernel, probe.arch)
+            .trim_end()
+            .to_string();
+        if let Some(distro) = &probe.distro {
+            out.push_str(&format!(" ({})", distro));
+        }
+        out.push_str(&format!(
+            "\nShell: {}\n\nTools:\n",
+            probe.shell.as_deref().unwrap_or("(SHELL not set)")
+        ));
+        let width = probe.tools.iter().map(|t| t.name.len()).max().unwrap_or(0);
+        let mut tools_meta = Vec::new();
+        for tool in &probe.tools {
+            let version = tool.version_line.as_deref().and_then(version_number);
+            match &tool.path {
+                Some(path) => out.push_str(&format!(
+                    "  {:width$}  {}  {}\n",
+                    tool.name,
+                    version.unwrap_or("?"),
+                    path
+                )),
+                None => out.push_str(&format!("  {:width$}  not found\n", tool.name)),
+            }
+            tools_meta.push(json!({
+                "name": tool.name,
+                "found": tool.path.is_some(),
+                "path": tool.path,
+                "version": version,
+                "version_line": tool.version_line,
+            }));
+        }
+
+        let mut seen = HashSet::new();
+        let mut missing = Vec::new();
+        let mut duplicates = Vec::new();
+        for (dir, exists) in &probe.path {
+            if !seen.insert(dir.as_str()) {
+                duplicates.push(dir.as_str());
+            } else if !exists {
+                missing.push(dir.as_str());
+            }
+        }
+        out.push_str(&format!("\nPATH: {} entries", probe.path.len()));
+        if !missing.is_empty() || !duplicates.is_empty() {
+            out.push_str(&format!(
+                " ({} missing, {} duplicate)",
+                missing.len(),
+                duplicates.len()
+            ));
+        }
+        out.push('\n');
+        for (dir, exists) in &probe.path {
+            out.push_str(&format!(
+                "  {}{}\n",
+                dir,
+                if *exists { "" } else { "  (missing)" }
+            ));
+        }
+
+        Ok(
+            ToolOutput::success(out.trim_end().to_string()).with_metadata(json!({
+                "os": probe.os,
+                "kernel": probe.kernel,
+                "arch": probe.arch,
+                "distro": probe.distro,
+                "shell": probe.shell,
+                "tools": tools_meta,
+                "path": probe.path.iter().map(|(dir, _)| dir).collect::<Vec<_>>(),
+                "path_missing": missing,
+                "path_duplicates": duplicates,
+            })),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vexec::{ExecOutput, MockExecutor, NoopExecutor};
+
+    #[tokio::test]
+    async fn env_info_reports_probe() {
+        let stdout = "os=Linux\nkernel=6.1.0\narch=x86_64\ndistro=Debian GNU/Linux 12 (bookworm)\n\
+            shell=/bin/bash\ntool=git\t/usr/bin/git\tgit version 2.39.2\n\
+            tool=node\ntool=go\t/usr/local/go/bin/go\tgo version go1.22.1 linux/amd64\n\
+            path=1/usr/local/bin\npath=0/opt/gone/bin\npath=1/usr/local/bin\n";
+        let executor = Arc::new(MockExecutor::new(vec![ExecOutput {
+            stdout: stdout.into(),
+            stderr: String::new(),
+            exit_code: 0,
+        }]));
+        let tool = EnvInfoTool::new(executor, "/project").with_tools(["git", "node", "go"]);
+        let result = tool.execute("c1", json!({}), None).await.unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.starts_with(
+            "OS: Linux 6.1.0 x86_64 (Debian GNU/Linux 12 (bookworm))\nShell: /bin/bash\n"
+        ));
+        assert!(result
+            .content
+            .contains("  git   2.39.2  /usr/bin/git\n  node  not found\n"));
+        assert!(result
+            .content
+            .contains("PATH: 3 entries (1 missing, 1 duplicate)\n"));
+        assert_eq!(result.metadata["tools"][2]["version"], "1.22.1");
+        assert_eq!(result.metadata["tools"][1]["found"], false);
+        assert_eq!(result.metadata["path_missing"], json!(["/opt/gone/bin"]));
+
+        let result = tool
+            .execute("c2", json!({"tools": ["docker; rm -rf /"]}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            ToolErrorKind::of(&result),
+            Some(ToolErrorKind::InvalidArguments)
+        );
+
+        let tool = EnvInfoTool::new(Arc::new(NoopExecutor), "/project");
+        let result = tool.execute("c3", json!({}), None).await.unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::Unsupported));
+    }
+
+    #[cfg(all(feature = "native", unix))]
+    #[tokio::test]
+    async fn env_info_native() {
+        use soul_core::vexec::NativeExecutor;
+
+        let tool = EnvInfoTool::new(Arc::new(NativeExecutor::new()), "/").with_tools(["sh"]);
+        let result = tool.execute("c1", json!({}), None).await.unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.metadata["tools"][0]["found"], true);
+        assert!(!result.metadata["path"].as_array().unwrap().is_empty());
+    }
+}
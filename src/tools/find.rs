@@ -1,11 +1,15 @@
 //! Find tool — search for files by name/glob pattern.
 //!
-//! Uses VirtualFs for WASM compatibility. Recursively walks directories
-//! and matches filenames against glob patterns.
+//! Uses VirtualFs for WASM compatibility. Matches filenames against glob
+//! patterns as [`walk`] streams them in, stopping once the limit is reached.
+//! `output_format: "print0"` and `"quoted"` list the results NUL-terminated
+//! or as shell words, for passing to `xargs -0` or a bash command line
+//! verbatim when paths contain spaces.
 
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use futures::StreamExt;
 use serde_json::json;
 use tokio::sync::mpsc;
 
@@ -14,16 +18,33 @@ use soul_core::tool::{Tool, ToolOutput};
 use soul_core::types::ToolDefinition;
 use soul_core::vfs::VirtualFs;
 
+use crate::error::{tool_error, ToolErrorKind};
+use crate::file_identity::{Duplicates, FileIdentity};
+use crate::ignore::glob_match;
+use crate::paths;
 use crate::truncate::{truncate_head, MAX_BYTES};
+use crate::walk::{walk_guarded, WalkGuard, WalkOptions};
 
 /// Maximum results returned.
 const MAX_RESULTS: usize = 1000;
 
-use super::resolve_path;
+use super::bash::shell_quote;
+use super::{
+    impl_overrides, resolve_path, DisplayRoot, Progress, SortOrder, ToolOverrides,
+    DEFAULT_PROGRESS_INTERVAL,
+};
+
+/// System-prompt guidance returned by [`FindTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Locate files by name or path pattern (**/*.rs, **/test_*.py). To find files by \
+    what they contain, use grep instead.";
 
 pub struct FindTool {
     fs: Arc<dyn VirtualFs>,
     cwd: String,
+    progress_interval: usize,
+    identity: Option<Arc<dyn FileIdentity>>,
+    overrides: ToolOverrides,
 }
 
 impl FindTool {
@@ -31,8 +52,51 @@ impl FindTool {
         Self {
             fs,
             cwd: cwd.into(),
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
+            identity: None,
+            overrides: ToolOverrides::default(),
         }
     }
+
+    /// Files visited between progress updates sent through `partial_tx`
+    /// (default: 500; 0 disables them).
+    pub fn with_progress_interval(mut self, files: usize) -> Self {
+        self.progress_interval = files;
+        self
+    }
+
+    /// List each file once when several paths reach it, such as through a
+    /// symlinked directory (default: every path is listed).
+    pub fn with_file_identity(mut self, identity: Arc<dyn FileIdentity>) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+}
+
+impl_overrides!(FindTool);
+
+/// How results are listed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ListFormat {
+    /// One path per line, followed by any notices.
+    Text,
+    /// Each path followed by a NUL byte, like `find -print0`.
+    Print0,
+    /// Paths as POSIX shell words separated by spaces.
+    Quoted,
+}
+
+/// `path` as one POSIX shell word, quoted only when it has to be.
+fn shell_word(path: &str) -> String {
+    let plain = !path.is_empty()
+        && path
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_./-+,:@%=".contains(c));
+    if plain {
+        path.to_string()
+    } else {
+        shell_quote(path)
+    }
 }
 
 /// Match a filename against a glob pattern.
@@ -69,13 +133,11 @@ fn matches_simple_glob(name: &str, pattern: &str) -> bool {
         return name.contains(middle);
     }
 
-    if pattern.starts_with('*') {
-        let suffix = &pattern[1..];
+    if let Some(suffix) = pattern.strip_prefix('*') {
         return name.ends_with(suffix);
     }
 
-    if pattern.ends_with('*') {
-        let prefix = &pattern[..pattern.len() - 1];
+    if let Some(prefix) = pattern.strip_suffix('*') {
         return name.starts_with(prefix);
     }
 
@@ -108,154 +170,275 @@ fn path_matches_glob(path: &str, pattern: &str) -> bool {
     pi == 0
 }
 
-/// Recursively collect matching files.
-async fn find_files(
-    fs: &dyn VirtualFs,
-    dir: &str,
-    pattern: &str,
-    results: &mut Vec<String>,
-    limit: usize,
-) -> SoulResult<()> {
-    if results.len() >= limit {
-        return Ok(());
-    }
-
-    let entries = match fs.read_dir(dir).await {
-        Ok(e) => e,
-        Err(_) => return Ok(()), // Skip unreadable dirs
-    };
-
-    for entry in entries {
-        if results.len() >= limit {
-            break;
-        }
+/// Ordered include/exclude globs. Entries prefixed with `!` exclude, and
+/// like gitignore the last entry that matches a file decides. A list made
+/// only of exclusions starts from "everything".
+struct PatternSet {
+    /// `(negated, glob)` in the order given.
+    rules: Vec<(bool, String)>,
+    /// Search root, for matching `dir/**`-style globs against relative paths.
+    root: String,
+}
 
-        let path = if dir == "/" || dir.is_empty() {
-            format!("/{}", entry.name)
-        } else {
-            format!("{}/{}", dir.trim_end_matches('/'), entry.name)
+impl PatternSet {
+    /// Parse a `pattern` argument: one glob or an array of them.
+    fn parse(value: Option<&serde_json::Value>, root: &str) -> Result<Self, String> {
+        let raw: Vec<&str> = match value {
+            Some(serde_json::Value::String(s)) => vec![s.as_str()],
+            Some(serde_json::Value::Array(items)) => items
+                .iter()
+                .map(|v| v.as_str().ok_or("pattern array must contain only strings"))
+                .collect::<Result<_, _>>()?,
+            _ => Vec::new(),
         };
+        let rules: Vec<(bool, String)> = raw
+            .iter()
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .map(|p| match p.strip_prefix('!') {
+                Some(rest) => (true, paths::glob_pattern(rest.trim()).into_owned()),
+                None => (false, paths::glob_pattern(p).into_owned()),
+            })
+            .filter(|(_, glob)| !glob.is_empty())
+            .collect();
+        if rules.is_empty() {
+            return Err("Missing required parameter: pattern".into());
+        }
+        Ok(Self {
+            rules,
+            root: root.to_string(),
+        })
+    }
 
-        if entry.is_dir {
-            if !entry.name.starts_with('.') {
-                Box::pin(find_files(fs, &path, pattern, results, limit)).await?;
+    fn matches(&self, name: &str, full_path: &str) -> bool {
+        let relative = paths::relative_to(full_path, &self.root);
+        let mut included = self.rules.iter().all(|(negated, _)| *negated);
+        for (negated, glob) in &self.rules {
+            let hit = matches_glob(name, full_path, glob)
+                || (glob.contains('/')
+                    && relative.as_deref().is_some_and(|rel| glob_match(glob, rel)));
+            if hit {
+                included = !negated;
             }
-        } else if entry.is_file && matches_glob(&entry.name, &path, pattern) {
-            results.push(path);
         }
+        included
     }
 
-    Ok(())
+    /// The patterns as given, for messages.
+    fn describe(&self) -> String {
+        self.rules
+            .iter()
+            .map(|(negated, glob)| format!("{}{}", if *negated { "!" } else { "" }, glob))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Whether the file at `path` contains `needle`. Unreadable files never do.
+async fn file_contains(fs: &dyn VirtualFs, path: &str, needle: &str) -> bool {
+    fs.read_to_string(path)
+        .await
+        .map(|content| content.contains(needle))
+        .unwrap_or(false)
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl Tool for FindTool {
     fn name(&self) -> &str {
-        "find"
+        self.overrides.name("find")
     }
 
     fn definition(&self) -> ToolDefinition {
-        ToolDefinition {
+        self.overrides.apply(ToolDefinition {
             name: "find".into(),
-            description: "Find files matching a glob pattern. Returns matching file paths.".into(),
+            description: "Find files matching a glob pattern (or an ordered list of patterns where '!' entries exclude), optionally only those containing some text. Returns matching file paths.".into(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
                     "pattern": {
-                        "type": "string",
-                        "description": "Glob pattern to match files (e.g., '*.rs', 'src/**/*.ts', 'Cargo.toml')"
+                        "type": ["string", "array"],
+                        "items": {"type": "string"},
+                        "description": "Glob pattern to match files (e.g., '*.rs', 'src/**/*.ts', 'Cargo.toml'), or a list evaluated in order like gitignore where '!' entries exclude (e.g., ['*.rs', '!*_test.rs', '!target/**'])"
                     },
                     "path": {
                         "type": "string",
                         "description": "Directory to search in (defaults to working directory)"
                     },
+                    "containing": {
+                        "type": "string",
+                        "description": "Only return files whose contents include this text (case-sensitive)"
+                    },
                     "limit": {
                         "type": "integer",
                         "description": "Maximum number of results (default: 1000)"
-                    }
+                    },
+                    "output_format": {
+                        "type": "string",
+                        "enum": ["text", "print0", "quoted"],
+                        "description": "'print0' ends each path with a NUL byte (for xargs -0), 'quoted' lists the paths as shell-quoted words on one line to paste into a bash command; both print nothing else, with limits reported in metadata only (default: text)"
+                    },
+                    "detail": {
+                        "type": "boolean",
+                        "description": "Also return an entries metadata record per result with its size and is_dir, so results need not be stat'ed one by one (default: false)"
+                    },
+                    "sort": SortOrder::schema(),
+                    "relative_to": DisplayRoot::schema()
                 },
                 "required": ["pattern"]
             }),
-        }
+        })
     }
 
     async fn execute(
         &self,
         _call_id: &str,
         arguments: serde_json::Value,
-        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+        partial_tx: Option<mpsc::UnboundedSender<String>>,
     ) -> SoulResult<ToolOutput> {
-        let pattern = arguments
-            .get("pattern")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-
-        if pattern.is_empty() {
-            return Ok(ToolOutput::error("Missing required parameter: pattern"));
-        }
-
         let search_path = arguments
             .get("path")
             .and_then(|v| v.as_str())
             .map(|p| resolve_path(&self.cwd, p))
             .unwrap_or_else(|| self.cwd.clone());
 
+        let patterns = match PatternSet::parse(arguments.get("pattern"), &search_path) {
+            Ok(patterns) => patterns,
+            Err(e) => return Ok(tool_error(ToolErrorKind::InvalidArguments, e)),
+        };
+        let pattern = patterns.describe();
+
         let limit = arguments
             .get("limit")
             .and_then(|v| v.as_u64())
             .map(|v| (v as usize).min(MAX_RESULTS))
             .unwrap_or(MAX_RESULTS);
 
+        let containing = arguments
+            .get("containing")
+            .and_then(|v| v.as_str())
+            .filter(|c| !c.is_empty());
+        let detail = arguments
+            .get("detail")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let sort = match SortOrder::parse(arguments.get("sort")) {
+            Ok(sort) => sort,
+            Err(e) => return Ok(e),
+        };
+        let format = match arguments.get("output_format").and_then(|v| v.as_str()) {
+            None | Some("text") => ListFormat::Text,
+            Some("print0") => ListFormat::Print0,
+            Some("quoted") => ListFormat::Quoted,
+            Some(other) => {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!(
+                        "Unknown output_format: {} (expected text, print0 or quoted)",
+                        other
+                    ),
+                ))
+            }
+        };
+
         let mut results = Vec::new();
-        if let Err(e) =
-            find_files(self.fs.as_ref(), &search_path, pattern, &mut results, limit).await
-        {
-            return Ok(ToolOutput::error(format!(
-                "Failed to search {}: {}",
-                search_path, e
-            )));
+        let duplicates = Duplicates::new(self.identity.clone());
+        let mut progress = Progress::new(partial_tx, self.progress_interval, None);
+        let guard = Arc::new(WalkGuard::new());
+        let files = walk_guarded(
+            self.fs.clone(),
+            &search_path,
+            WalkOptions::default(),
+            guard.clone(),
+        );
+        futures::pin_mut!(files);
+        while let Some(file) = files.next().await {
+            if results.len() >= limit {
+                break; // Dropping the stream ends the walk
+            }
+            progress.tick(results.len());
+            let path = file.to_string_lossy().into_owned();
+            let name = path.rsplit('/').next().unwrap_or_default();
+            if !patterns.matches(name, &path) {
+                continue;
+            }
+            if let Some(needle) = containing {
+                if !file_contains(self.fs.as_ref(), &path, needle).await {
+                    continue;
+                }
+            }
+            if duplicates.is_duplicate(&path).await {
+                continue;
+            }
+            results.push(path);
         }
 
-        results.sort();
+        sort.sort(&mut results);
 
         if results.is_empty() {
-            return Ok(ToolOutput::success(format!(
-                "No files matching '{}' found",
-                pattern
-            ))
-            .with_metadata(json!({"count": 0})));
+            let message = match containing {
+                // Nothing to pass on
+                _ if format != ListFormat::Text => String::new(),
+                Some(needle) => format!(
+                    "No files matching '{}' containing '{}' found",
+                    pattern, needle
+                ),
+                None => format!("No files matching '{}' found", pattern),
+            };
+            let mut output = ToolOutput::success(message).with_metadata(json!({"count": 0}));
+            guard.annotate(&mut output);
+            return Ok(output);
         }
 
-        // Make paths relative to cwd
-        let cwd_prefix = format!("{}/", self.cwd.trim_end_matches('/'));
-        let relative: Vec<String> = results
-            .iter()
-            .map(|p| {
-                if p.starts_with(&cwd_prefix) {
-                    p[cwd_prefix.len()..].to_string()
-                } else {
-                    p.clone()
-                }
-            })
-            .collect();
+        // Make paths relative to cwd (or the requested root)
+        let display = DisplayRoot::from_arguments(&self.cwd, &arguments);
+        let relative: Vec<String> = results.iter().map(|p| display.show(p)).collect();
 
-        let output = relative.join("\n");
+        let listed: Vec<String> = match format {
+            ListFormat::Quoted => relative.iter().map(|p| shell_word(p)).collect(),
+            _ => relative.clone(),
+        };
+        let output = listed.join("\n");
         let truncated = truncate_head(&output, results.len(), MAX_BYTES);
 
         let notice = truncated.truncation_notice();
-        let mut result = truncated.content;
-        if results.len() >= limit {
-            result.push_str(&format!("\n[Reached limit: {} results]", limit));
-        }
-        if let Some(notice) = notice {
-            result.push_str(&format!("\n{}", notice));
+        let truncation = truncated.to_metadata();
+        let kept = &listed[..truncated.output_lines.min(listed.len())];
+        let mut result = match format {
+            ListFormat::Text => truncated.content,
+            ListFormat::Print0 => kept.iter().map(|p| format!("{}\0", p)).collect(),
+            ListFormat::Quoted => kept.join(" "),
+        };
+        if format == ListFormat::Text {
+            if results.len() >= limit {
+                result.push_str(&format!("\n[Reached limit: {} results]", limit));
+            }
+            if let Some(notice) = notice {
+                result.push_str(&format!("\n{}", notice));
+            }
         }
 
-        Ok(ToolOutput::success(result).with_metadata(json!({
+        let mut metadata = json!({
             "count": results.len(),
             "limit_reached": results.len() >= limit,
-        })))
+            "truncation": truncation,
+        });
+        if detail {
+            let mut entries = Vec::with_capacity(results.len());
+            for (path, shown) in results.iter().zip(&relative) {
+                let stat = self.fs.metadata(path).await.ok();
+                entries.push(json!({
+                    "path": shown,
+                    "size": stat.as_ref().map(|m| m.size),
+                    "is_dir": stat.is_some_and(|m| m.is_dir),
+                }));
+            }
+            metadata["entries"] = json!(entries);
+        }
+        let mut output = ToolOutput::success(result).with_metadata(metadata);
+        guard.annotate(&mut output);
+        duplicates.annotate(&mut output, &display);
+        Ok(output)
     }
 }
 
@@ -287,7 +470,7 @@ mod tests {
     #[tokio::test]
     async fn find_by_extension() {
         let (fs, tool) = setup().await;
-        populate(&*fs).await;
+        populate(&fs).await;
 
         let result = tool
             .execute("c1", json!({"pattern": "*.rs"}), None)
@@ -303,7 +486,7 @@ mod tests {
     #[tokio::test]
     async fn find_exact_name() {
         let (fs, tool) = setup().await;
-        populate(&*fs).await;
+        populate(&fs).await;
 
         let result = tool
             .execute("c2", json!({"pattern": "Cargo.toml"}), None)
@@ -318,7 +501,7 @@ mod tests {
     #[tokio::test]
     async fn find_no_results() {
         let (fs, tool) = setup().await;
-        populate(&*fs).await;
+        populate(&fs).await;
 
         let result = tool
             .execute("c3", json!({"pattern": "*.py"}), None)
@@ -332,7 +515,7 @@ mod tests {
     #[tokio::test]
     async fn find_with_limit() {
         let (fs, tool) = setup().await;
-        populate(&*fs).await;
+        populate(&fs).await;
 
         let result = tool
             .execute("c4", json!({"pattern": "*", "limit": 2}), None)
@@ -343,6 +526,227 @@ mod tests {
         assert_eq!(result.metadata["count"].as_u64().unwrap(), 2);
     }
 
+    #[tokio::test]
+    async fn find_containing() {
+        let (fs, tool) = setup().await;
+        populate(&fs).await;
+        fs.write("/project/crates/a/Cargo.toml", "[package]\nname = \"a\"")
+            .await
+            .unwrap();
+        fs.write("/project/Cargo.toml", "[workspace]\nmembers = [\"crates/a\"]")
+            .await
+            .unwrap();
+
+        let result = tool
+            .execute(
+                "c6",
+                json!({"pattern": "*.toml", "containing": "workspace"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.content, "Cargo.toml");
+        assert_eq!(result.metadata["count"], 1);
+
+        let result = tool
+            .execute("c7", json!({"pattern": "*.rs", "containing": "nowhere"}), None)
+            .await
+            .unwrap();
+        assert!(result.content.contains("containing 'nowhere'"));
+    }
+
+    #[tokio::test]
+    async fn find_reports_progress() {
+        let (fs, _) = setup().await;
+        populate(&fs).await;
+        let tool = FindTool::new(fs as Arc<dyn VirtualFs>, "/project").with_progress_interval(2);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tool.execute("c1", json!({"pattern": "*.rs"}), Some(tx))
+            .await
+            .unwrap();
+
+        let first = rx.try_recv().unwrap();
+        assert!(first.starts_with("scanned 2 files, "), "{}", first);
+    }
+
+    #[tokio::test]
+    async fn find_negated_patterns() {
+        let (fs, tool) = setup().await;
+        populate(&fs).await;
+        fs.write("/project/src/lib_test.rs", "#[test] fn t() {}")
+            .await
+            .unwrap();
+        fs.write("/project/target/debug/build.rs", "")
+            .await
+            .unwrap();
+
+        let result = tool
+            .execute(
+                "c8",
+                json!({"pattern": ["*.rs", "!*_test.rs", "!target/**"]}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.content, "src/lib.rs\nsrc/main.rs");
+
+        // Later entries override earlier ones
+        let result = tool
+            .execute("c9", json!({"pattern": ["*.rs", "!src/**", "main.rs"]}), None)
+            .await
+            .unwrap();
+        assert!(result.content.contains("src/main.rs"));
+        assert!(!result.content.contains("lib.rs"));
+
+        // Exclusions alone start from every file
+        let result = tool
+            .execute("c10", json!({"pattern": ["!*.rs"], "path": "src"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "src/utils.ts");
+    }
+
+    #[tokio::test]
+    async fn find_natural_sort() {
+        let (fs, tool) = setup().await;
+        for name in ["file10.rs", "file2.rs", "file1.rs"] {
+            fs.write(&format!("/project/{}", name), "").await.unwrap();
+        }
+
+        let result = tool
+            .execute("c1", json!({"pattern": "*.rs"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "file1.rs\nfile2.rs\nfile10.rs");
+
+        let result = tool
+            .execute("c2", json!({"pattern": "*.rs", "sort": "path"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "file1.rs\nfile10.rs\nfile2.rs");
+
+        // The walk is ordered too, so a limit keeps the first files
+        let result = tool
+            .execute("c3", json!({"pattern": "*.rs", "limit": 2}), None)
+            .await
+            .unwrap();
+        assert!(result.content.starts_with("file1.rs\nfile2.rs"));
+
+        let result = tool
+            .execute("c4", json!({"pattern": "*.rs", "sort": "mtime"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::Unsupported));
+    }
+
+    #[tokio::test]
+    async fn find_print0_and_quoted() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/docs/My Notes.md", "x").await.unwrap();
+        fs.write("/project/docs/it's.md", "x").await.unwrap();
+        fs.write("/project/docs/plain.md", "x").await.unwrap();
+
+        let args = json!({"pattern": "docs/*.md", "output_format": "print0"});
+        let result = tool.execute("c1", args, None).await.unwrap();
+        assert_eq!(
+            result.content,
+            "docs/My Notes.md\0docs/it's.md\0docs/plain.md\0"
+        );
+
+        let args = json!({"pattern": "docs/*.md", "output_format": "quoted", "limit": 2});
+        let result = tool.execute("c2", args, None).await.unwrap();
+        assert_eq!(result.content, r"'docs/My Notes.md' 'docs/it'\''s.md'");
+        assert_eq!(result.metadata["limit_reached"], true);
+
+        let args = json!({"pattern": "*.none", "output_format": "print0"});
+        let result = tool.execute("c3", args, None).await.unwrap();
+        assert_eq!(result.content, "");
+        let args = json!({"pattern": "*.md", "output_format": "csv"});
+        let result = tool.execute("c4", args, None).await.unwrap();
+        assert_eq!(
+            ToolErrorKind::of(&result),
+            Some(ToolErrorKind::InvalidArguments)
+        );
+    }
+
+    #[tokio::test]
+    async fn find_detail_entries() {
+        let (fs, tool) = setup().await;
+        populate(&fs).await;
+
+        let result = tool
+            .execute("c1", json!({"pattern": "src/*.rs", "detail": true}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "src/lib.rs\nsrc/main.rs");
+        assert_eq!(
+            result.metadata["entries"],
+            json!([
+                {"path": "src/lib.rs", "size": 12, "is_dir": false},
+                {"path": "src/main.rs", "size": 12, "is_dir": false},
+            ])
+        );
+
+        let result = tool
+            .execute("c2", json!({"pattern": "*.rs"}), None)
+            .await
+            .unwrap();
+        assert!(result.metadata.get("entries").is_none());
+    }
+
+    #[tokio::test]
+    async fn find_relative_to() {
+        let (fs, tool) = setup().await;
+        fs.write("/shared/lib/util.rs", "").await.unwrap();
+        fs.write("/project/src/main.rs", "").await.unwrap();
+
+        // Outside the working directory: absolute by default
+        let result = tool
+            .execute("c1", json!({"pattern": "*.rs", "path": "../shared"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "/shared/lib/util.rs");
+
+        let result = tool
+            .execute(
+                "c2",
+                json!({"pattern": "*.rs", "path": "../shared", "relative_to": "."}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.content, "../shared/lib/util.rs");
+
+        let result = tool
+            .execute("c3", json!({"pattern": "*.rs", "relative_to": "src"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "main.rs");
+    }
+
+    #[tokio::test]
+    async fn find_collapses_linked_paths() {
+        use crate::file_identity::MemoryFileIdentity;
+
+        let (fs, tool) = setup().await;
+        fs.write("/project/lib/a.rs", "").await.unwrap();
+        fs.write("/project/vendor/lib/a.rs", "").await.unwrap();
+        fs.write("/project/vendor/b.rs", "").await.unwrap();
+        let identity = Arc::new(MemoryFileIdentity::new());
+        identity.link("/project/vendor/lib", "/project/lib");
+        let tool = tool.with_file_identity(identity);
+
+        let result = tool
+            .execute("c1", json!({"pattern": "*.rs"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "lib/a.rs\nvendor/b.rs");
+        assert_eq!(
+            result.metadata["duplicates"],
+            json!([{"path": "lib/a.rs", "same_file": ["vendor/lib/a.rs"]}])
+        );
+    }
+
     #[tokio::test]
     async fn find_empty_pattern() {
         let (_fs, tool) = setup().await;
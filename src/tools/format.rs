@@ -0,0 +1,285 @@
+//! Format tool — run the project's formatter over a file and show the diff.
+//!
+//! Hands the file to a [`Formatter`] (see [`crate::formatter`]), writes the
+//! result back and reports what changed, so the model doesn't hand-format
+//! code and churn files. Not part of [`all_tools`](crate::presets::all_tools);
+//! add it with [`Toolset::with_formatter`](crate::Toolset::with_formatter).
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::{SoulError, SoulResult};
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::diff::render_diff;
+use crate::error::{tool_error, ToolErrorKind};
+use crate::file_modes::{current_mode, restore_mode, FileModes};
+use crate::formatter::Formatter;
+use crate::locks::PathLocks;
+use crate::truncate::MAX_DIFF_BYTES;
+
+use super::{impl_overrides, resolve_path, ToolOverrides};
+
+/// System-prompt guidance returned by [`FormatTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    After writing or editing code, run format on the file instead of fixing \
+    layout by hand, then read the diff before editing the file again.";
+
+pub struct FormatTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    formatter: Option<Arc<dyn Formatter>>,
+    locks: Arc<PathLocks>,
+    modes: Option<Arc<dyn FileModes>>,
+    max_diff_bytes: usize,
+    overrides: ToolOverrides,
+}
+
+impl FormatTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            formatter: None,
+            locks: Arc::new(PathLocks::new()),
+            modes: None,
+            max_diff_bytes: MAX_DIFF_BYTES,
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+    /// Format files with `formatter` (default: none, and every call fails
+    /// as unsupported).
+    pub fn with_formatter(mut self, formatter: Arc<dyn Formatter>) -> Self {
+        self.formatter = Some(formatter);
+        self
+    }
+
+    /// Share `locks` with other tools, so that their changes to the same
+    /// file wait for each other (default: a set of this tool's own).
+    pub fn with_locks(mut self, locks: Arc<PathLocks>) -> Self {
+        self.locks = locks;
+        self
+    }
+
+    /// Put back the permissions of files this tool rewrites (default: left
+    /// to the filesystem).
+    pub fn with_file_modes(mut self, modes: Arc<dyn FileModes>) -> Self {
+        self.modes = Some(modes);
+        self
+    }
+
+    /// Cap the diff in the output at about `bytes` (default:
+    /// [`MAX_DIFF_BYTES`]).
+    pub fn with_max_diff_bytes(mut self, bytes: usize) -> Self {
+        self.max_diff_bytes = bytes;
+        self
+    }
+}
+
+impl_overrides!(FormatTool);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for FormatTool {
+    fn name(&self) -> &str {
+        self.overrides.name("format")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "format".into(),
+            description: "Format a file with the project's formatter for its type (e.g. rustfmt, prettier) and show the diff of what changed. With check: true, only reports whether the file needs formatting.".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "File to format"
+                    },
+                    "check": {
+                        "type": "boolean",
+                        "description": "Show the diff without writing it (default: false)"
+                    }
+                },
+                "required": ["path"]
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let lock = self.locks.lock_for(&self.cwd, &arguments).await;
+        let mut output = self.apply(arguments).await?;
+        if let Some(lock) = lock {
+            lock.annotate(&mut output);
+        }
+        Ok(output)
+    }
+}
+
+impl FormatTool {
+    /// Carry out a call; [`execute`](Tool::execute) holds the path lock
+    /// around it.
+    async fn apply(&self, arguments: serde_json::Value) -> SoulResult<ToolOutput> {
+        let path = arguments.get("path").and_then(|v| v.as_str()).unwrap_or("");
+        let check = arguments
+            .get("check")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if path.is_empty() {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Missing required parameter: path",
+            ));
+        }
+        let Some(formatter) = &self.formatter else {
+            return Ok(tool_error(
+                ToolErrorKind::Unsupported,
+                "No formatter is configured",
+            ));
+        };
+
+        let resolved = resolve_path(&self.cwd, path);
+        let content = match self.fs.read_to_string(&resolved).await {
+            Ok(content) => content,
+            Err(e) => {
+                return Ok(tool_error(
+                    ToolErrorKind::from_soul_error(&e),
+                    format!("Failed to read {}: {}", path, e),
+                ))
+            }
+        };
+        let formatted = match formatter.format(&resolved, &content).await {
+            Ok(Some(formatted)) => formatted,
+            Ok(None) => {
+                return Ok(tool_error(
+                    ToolErrorKind::Unsupported,
+                    format!("No formatter handles {}", path),
+                ))
+            }
+            Err(e) => {
+                let message = match e {
+                    SoulError::ToolExecution { message, .. } => message,
+                    other => other.to_string(),
+                };
+                return Ok(tool_error(
+                    ToolErrorKind::CommandFailed,
+                    format!("Formatting {} failed: {}", path, message),
+                ));
+            }
+        };
+
+        if formatted == content {
+            return Ok(
+                ToolOutput::success(format!("{} is already formatted", path))
+                    .with_metadata(json!({"path": path, "changed": false, "written": false})),
+            );
+        }
+        let diff = render_diff(&content, &formatted, path, self.max_diff_bytes);
+        let metadata = json!({
+            "path": path,
+            "changed": true,
+            "written": !check,
+            "diff": diff.to_metadata(),
+        });
+        if check {
+            return Ok(
+                ToolOutput::success(format!("{} needs formatting\n\n{}", path, diff.text))
+                    .with_metadata(metadata),
+            );
+        }
+
+        let mode = current_mode(&self.modes, &resolved).await;
+        match self.fs.write(&resolved, &formatted).await {
+            Ok(()) => {
+                restore_mode(&self.modes, &resolved, mode).await;
+                Ok(
+                    ToolOutput::success(format!("Formatted {}\n\n{}", path, diff.text))
+                        .with_metadata(metadata),
+                )
+            }
+            Err(e) => Ok(tool_error(
+                ToolErrorKind::from_soul_error(&e),
+                format!("Failed to write {}: {}", path, e),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formatter::CallbackFormatter;
+    use soul_core::vfs::MemoryFs;
+
+    #[tokio::test]
+    async fn formats_and_reports_the_diff() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "one  \ntwo\n").await.unwrap();
+        fs.write("/project/b.bin", "x").await.unwrap();
+        let formatter = CallbackFormatter::new(|path: &str, content: &str| {
+            path.ends_with(".txt").then(|| {
+                content
+                    .lines()
+                    .map(|l| format!("{}\n", l.trim_end()))
+                    .collect()
+            })
+        });
+        let tool = FormatTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project")
+            .with_formatter(Arc::new(formatter));
+
+        let result = tool
+            .execute("c1", json!({"path": "a.txt", "check": true}), None)
+            .await
+            .unwrap();
+        assert!(result.content.starts_with("a.txt needs formatting"));
+        assert!(
+            result.content.contains("-one  \n+one\n"),
+            "{}",
+            result.content
+        );
+        assert_eq!(result.metadata["written"], false);
+        assert_eq!(
+            fs.read_to_string("/project/a.txt").await.unwrap(),
+            "one  \ntwo\n"
+        );
+
+        let result = tool
+            .execute("c2", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        assert!(result.content.starts_with("Formatted a.txt"));
+        assert_eq!(result.metadata["diff"]["added"], 1);
+        assert_eq!(
+            fs.read_to_string("/project/a.txt").await.unwrap(),
+            "one\ntwo\n"
+        );
+
+        let result = tool
+            .execute("c3", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "a.txt is already formatted");
+
+        let result = tool
+            .execute("c4", json!({"path": "b.bin"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::Unsupported));
+        let result = FormatTool::new(fs as Arc<dyn VirtualFs>, "/project")
+            .execute("c5", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "No formatter is configured");
+    }
+}
@@ -2,8 +2,23 @@
 //!
 //! Uses VirtualFs for WASM compatibility. In WASM mode, performs regex search
 //! over all files in the VFS. In native mode, can delegate to ripgrep via VirtualExecutor.
+//! `binary: true` searches raw bytes instead of lines, reporting byte offsets
+//! with an `xxd`-style hex dump around each match. `unique: true` lists each
+//! distinct matching line once with how often and in how many files it
+//! occurs. `group_by_file: true` prints each file's path once as a heading
+//! above its `line:text` matches, like ripgrep's default terminal output.
+//!
+//! Every result carries a `scan` metadata object — files read, files skipped
+//! as binary, too large or unreadable, bytes read and elapsed milliseconds —
+//! for spotting workspaces whose ignore rules let too much through.
+//!
+//! With [`GrepTool::with_scan_budget`], a file whose lines take longer than
+//! the budget to search (megabyte-long minified lines, say) is given up on
+//! and listed under `scan.timed_out_files` instead of stalling the call.
 
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use async_trait::async_trait;
 use serde_json::json;
@@ -14,16 +29,50 @@ use soul_core::tool::{Tool, ToolOutput};
 use soul_core::types::ToolDefinition;
 use soul_core::vfs::VirtualFs;
 
+use crate::error::{tool_error, ToolErrorKind};
+use crate::file_identity::{Duplicates, FileIdentity};
+use crate::paths;
+use crate::search_index::SearchIndex;
 use crate::truncate::{truncate_head, truncate_line, GREP_MAX_LINE_LENGTH, MAX_BYTES};
+use crate::walk::WalkGuard;
+
+use super::set_metadata;
 
 /// Maximum number of matches returned.
 const MAX_MATCHES: usize = 100;
 
-use super::resolve_path;
+/// Default markers wrapped around matched spans when highlighting.
+const HIGHLIGHT_MARKERS: (&str, &str) = (">>>", "<<<");
+
+/// Default bytes of hex dump shown either side of a binary match.
+const BINARY_CONTEXT: usize = 16;
+
+/// Upper bound on binary-mode context, in bytes.
+const BINARY_MAX_CONTEXT: usize = 256;
+
+/// Bytes of a file searched between checks of the scan budget.
+const BUDGET_CHECK_BYTES: usize = 64 * 1024;
+
+use super::{
+    impl_overrides, resolve_path, DisplayRoot, Progress, SortOrder, ToolOverrides,
+    DEFAULT_PROGRESS_INTERVAL,
+};
+
+/// System-prompt guidance returned by [`GrepTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Search file contents: where a function is defined or called, where an error \
+    message comes from. Use find instead when you know the file name but not its \
+    contents. Narrow large searches with path or glob.";
 
 pub struct GrepTool {
     fs: Arc<dyn VirtualFs>,
     cwd: String,
+    index: Option<Arc<SearchIndex>>,
+    progress_interval: usize,
+    max_file_bytes: Option<u64>,
+    scan_budget: Option<Duration>,
+    identity: Option<Arc<dyn FileIdentity>>,
+    overrides: ToolOverrides,
 }
 
 impl GrepTool {
@@ -31,10 +80,94 @@ impl GrepTool {
         Self {
             fs,
             cwd: cwd.into(),
+            index: None,
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
+            max_file_bytes: None,
+            scan_budget: None,
+            identity: None,
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+    /// Files searched between progress updates sent through `partial_tx`
+    /// (default: 500; 0 disables them).
+    pub fn with_progress_interval(mut self, files: usize) -> Self {
+        self.progress_interval = files;
+        self
+    }
+
+    /// Skip files that can't match using a shared trigram index, filling it
+    /// as files are read.
+    pub fn with_index(mut self, index: Arc<SearchIndex>) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Skip files larger than `bytes` without reading them (default: no
+    /// limit). Skipped files are counted under `scan.skipped.too_large`.
+    pub fn with_max_file_bytes(mut self, bytes: u64) -> Self {
+        self.max_file_bytes = Some(bytes);
+        self
+    }
+
+    /// Give up on a file once searching its lines has taken longer than
+    /// `budget` (default: no limit). Such files contribute no matches and
+    /// are listed under `scan.timed_out_files`; binary mode and WASM, which
+    /// has no clock, ignore the budget.
+    pub fn with_scan_budget(mut self, budget: Duration) -> Self {
+        self.scan_budget = Some(budget);
+        self
+    }
+
+    /// Search each file once when several paths reach it, such as through a
+    /// symlinked directory (default: every path is searched).
+    pub fn with_file_identity(mut self, identity: Arc<dyn FileIdentity>) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    /// Read a file to search, recording the outcome in `scan`.
+    async fn read_file(&self, path: &str, scan: &ScanStats) -> Option<String> {
+        if let Some(max) = self.max_file_bytes {
+            if self.fs.metadata(path).await.is_ok_and(|m| m.size > max) {
+                scan.skip(|skipped| skipped.too_large += 1);
+                return None;
+            }
+        }
+        let content = self.fs.read_to_string(path).await;
+        scan.record(&content);
+        content.ok()
+    }
+
+    /// Read a file to search, or `None` if it is skipped or the index
+    /// proves it can't contain `pattern` (counted in `pruned`).
+    async fn read_candidate(
+        &self,
+        path: &str,
+        pattern: &str,
+        pruned: &mut usize,
+        scan: &ScanStats,
+    ) -> Option<String> {
+        let Some(index) = &self.index else {
+            return self.read_file(path, scan).await;
+        };
+        match index.may_contain(path, pattern) {
+            Some(false) => {
+                *pruned += 1;
+                None
+            }
+            Some(true) => self.read_file(path, scan).await,
+            None => {
+                let content = self.read_file(path, scan).await?;
+                index.insert(path, &content);
+                Some(content)
+            }
         }
     }
 }
 
+impl_overrides!(GrepTool);
+
 /// Simple pattern matching (supports literal and basic regex via contains).
 fn matches_pattern(line: &str, pattern: &str, literal: bool, ignore_case: bool) -> bool {
     if literal {
@@ -54,14 +187,85 @@ fn matches_pattern(line: &str, pattern: &str, literal: bool, ignore_case: bool)
     }
 }
 
-/// Recursively collect all file paths from a VFS directory.
-async fn collect_files(
+/// Number of non-overlapping occurrences of `pattern` in `line` — what a
+/// replace-all would touch.
+fn count_occurrences(line: &str, pattern: &str, ignore_case: bool) -> usize {
+    if ignore_case {
+        line.to_lowercase()
+            .matches(&pattern.to_lowercase())
+            .count()
+    } else {
+        line.matches(pattern).count()
+    }
+}
+
+/// Byte ranges of the non-overlapping occurrences of `pattern` in `line`.
+pub(super) fn match_spans(line: &str, pattern: &str, ignore_case: bool) -> Vec<(usize, usize)> {
+    if !ignore_case {
+        return line
+            .match_indices(pattern)
+            .map(|(start, m)| (start, start + m.len()))
+            .collect();
+    }
+    // Compare char by char so offsets stay valid in the original line
+    let needle: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    let mut spans = Vec::new();
+    let mut from = 0;
+    for (start, _) in line.char_indices() {
+        if start < from {
+            continue;
+        }
+        let mut matched = 0;
+        let mut end = None;
+        'chars: for (offset, c) in line[start..].char_indices() {
+            for lower in c.to_lowercase() {
+                if needle.get(matched) != Some(&lower) {
+                    break 'chars;
+                }
+                matched += 1;
+            }
+            if matched == needle.len() {
+                end = Some(start + offset + c.len_utf8());
+                break;
+            }
+        }
+        if let Some(end) = end {
+            spans.push((start, end));
+            from = end;
+        }
+    }
+    spans
+}
+
+/// Wrap each span of `line` in `open`/`close` markers.
+fn highlight(line: &str, spans: &[(usize, usize)], open: &str, close: &str) -> String {
+    let mut out = String::with_capacity(line.len() + spans.len() * (open.len() + close.len()));
+    let mut last = 0;
+    for &(start, end) in spans {
+        out.push_str(&line[last..start]);
+        out.push_str(open);
+        out.push_str(&line[start..end]);
+        out.push_str(close);
+        last = end;
+    }
+    out.push_str(&line[last..]);
+    out
+}
+
+/// Recursively collect all file paths from a VFS directory, descending at
+/// most `max_depth` levels (1 = only files directly in `dir`). `depth` is
+/// how far `dir` is below the search root, for `guard`.
+pub(super) async fn collect_files(
     fs: &dyn VirtualFs,
     dir: &str,
     files: &mut Vec<String>,
     glob_filter: Option<&str>,
+    max_depth: Option<usize>,
+    guard: &WalkGuard,
+    depth: usize,
 ) -> SoulResult<()> {
-    let entries = fs.read_dir(dir).await?;
+    let mut entries = fs.read_dir(dir).await?;
+    entries.sort_by(|a, b| paths::natural_cmp(&a.name, &b.name));
     for entry in entries {
         let path = if dir == "/" || dir.is_empty() {
             format!("/{}", entry.name)
@@ -70,9 +274,13 @@ async fn collect_files(
         };
 
         if entry.is_dir {
-            // Skip hidden dirs
-            if !entry.name.starts_with('.') {
-                Box::pin(collect_files(fs, &path, files, glob_filter)).await?;
+            // Skip hidden dirs, and stop descending at the depth limit
+            let descend = max_depth.map_or(true, |d| d > 1);
+            if descend && !entry.name.starts_with('.') && guard.enter(&path, depth + 1) {
+                let remaining = max_depth.map(|d| d - 1);
+                let children =
+                    collect_files(fs, &path, files, glob_filter, remaining, guard, depth + 1);
+                Box::pin(children).await?;
             }
         } else if entry.is_file {
             if let Some(glob) = glob_filter {
@@ -105,17 +313,712 @@ fn matches_glob(filename: &str, glob: &str) -> bool {
     }
 }
 
+/// Parse a `binary` mode pattern: hex digits (spaces and a `0x` prefix
+/// allowed, e.g. `89 50 4e 47`), or else text with `\xNN`, `\0`, `\n`, `\r`,
+/// `\t` and `\\` escapes.
+fn parse_byte_pattern(pattern: &str) -> Result<Vec<u8>, String> {
+    let hex: String = pattern
+        .trim()
+        .trim_start_matches("0x")
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        if hex.len() % 2 == 1 {
+            return Err(format!("Hex pattern has an odd number of digits: {}", pattern));
+        }
+        return Ok((0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect());
+    }
+
+    let mut bytes = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        let byte = match chars.next() {
+            Some('x') => {
+                let digits: String = chars.by_ref().take(2).collect();
+                u8::from_str_radix(&digits, 16)
+                    .ok()
+                    .filter(|_| digits.len() == 2)
+                    .ok_or_else(|| format!("Invalid escape \\x{} in pattern", digits))?
+            }
+            Some('0') => 0,
+            Some('n') => b'\n',
+            Some('r') => b'\r',
+            Some('t') => b'\t',
+            Some('\\') => b'\\',
+            Some(other) => return Err(format!("Unknown escape \\{} in pattern", other)),
+            None => return Err("Pattern ends with a lone backslash".into()),
+        };
+        bytes.push(byte);
+    }
+    Ok(bytes)
+}
+
+/// Byte offsets of non-overlapping occurrences of `needle`, at most `limit`.
+fn find_bytes(haystack: &[u8], needle: &[u8], ignore_case: bool, limit: usize) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut i = 0;
+    while offsets.len() < limit && i + needle.len() <= haystack.len() {
+        let window = &haystack[i..i + needle.len()];
+        let hit = if ignore_case {
+            window.eq_ignore_ascii_case(needle)
+        } else {
+            window == needle
+        };
+        if hit {
+            offsets.push(i);
+            i += needle.len();
+        } else {
+            i += 1;
+        }
+    }
+    offsets
+}
+
+/// `xxd`-style rows covering `bytes[start..end]`, widened to whole 16-byte rows.
+fn hex_dump(bytes: &[u8], start: usize, end: usize) -> String {
+    let mut out = String::new();
+    let mut row = start / 16 * 16;
+    while row < end.min(bytes.len()) {
+        let chunk = &bytes[row..(row + 16).min(bytes.len())];
+        let hex: Vec<String> = chunk
+            .chunks(2)
+            .map(|pair| pair.iter().map(|b| format!("{:02x}", b)).collect())
+            .collect();
+        let text: String = chunk
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        out.push_str(&format!("{:08x}: {:<39}  {}\n", row, hex.join(" "), text));
+        row += 16;
+    }
+    out
+}
+
+/// Search options shared by the output modes.
+struct SearchOptions<'a> {
+    pattern: &'a str,
+    literal: bool,
+    ignore_case: bool,
+    context_lines: usize,
+    max_matches: usize,
+    max_per_file: Option<usize>,
+    display: &'a DisplayRoot,
+    scan: &'a ScanStats,
+}
+
+/// Files a call skipped instead of searching.
+#[derive(Default)]
+struct Skipped {
+    binary: usize,
+    too_large: usize,
+    unreadable: usize,
+    timed_out: usize,
+}
+
+#[derive(Default)]
+struct ScanCounts {
+    files: usize,
+    bytes: usize,
+    skipped: Skipped,
+    timed_out_files: Vec<String>,
+}
+
+/// What one call read, reported under a `scan` metadata key.
+struct ScanStats {
+    counts: Mutex<ScanCounts>,
+    #[cfg(not(target_arch = "wasm32"))]
+    started: std::time::Instant,
+    #[cfg(not(target_arch = "wasm32"))]
+    budget: Option<Duration>,
+}
+
+impl ScanStats {
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    fn new(budget: Option<Duration>) -> Self {
+        Self {
+            counts: Mutex::new(ScanCounts::default()),
+            #[cfg(not(target_arch = "wasm32"))]
+            started: std::time::Instant::now(),
+            #[cfg(not(target_arch = "wasm32"))]
+            budget,
+        }
+    }
+
+    /// Feed the lines of `path` to `visit`, with their indexes, until it
+    /// returns false. Returns false instead if the file's scan budget runs
+    /// out first, recording it as timed out; its results should be dropped.
+    fn scan_lines<'l>(
+        &self,
+        path: &str,
+        lines: impl IntoIterator<Item = &'l str>,
+        mut visit: impl FnMut(usize, &'l str) -> bool,
+    ) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        let deadline = self.budget.map(|b| std::time::Instant::now() + b);
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut unchecked = 0;
+        for (idx, line) in lines.into_iter().enumerate() {
+            if !visit(idx, line) {
+                break;
+            }
+            // Checked after the line, so one huge line can't hide the overrun
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(deadline) = deadline {
+                unchecked += line.len() + 1;
+                if unchecked >= BUDGET_CHECK_BYTES {
+                    unchecked = 0;
+                    if std::time::Instant::now() >= deadline {
+                        let mut counts = self.counts.lock().unwrap();
+                        counts.skipped.timed_out += 1;
+                        counts.timed_out_files.push(path.to_string());
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Count a read: searched if it succeeded, otherwise skipped as binary
+    /// (not valid UTF-8) or unreadable.
+    fn record(&self, content: &SoulResult<String>) {
+        match content {
+            Ok(content) => {
+                let mut counts = self.counts.lock().unwrap();
+                counts.files += 1;
+                counts.bytes += content.len();
+            }
+            Err(e) if ToolErrorKind::from_soul_error(e) == ToolErrorKind::Encoding => {
+                self.skip(|skipped| skipped.binary += 1)
+            }
+            Err(_) => self.skip(|skipped| skipped.unreadable += 1),
+        }
+    }
+
+    fn skip(&self, count: impl FnOnce(&mut Skipped)) {
+        count(&mut self.counts.lock().unwrap().skipped);
+    }
+
+    /// Milliseconds since the call started; `None` in WASM, which has no
+    /// clock to measure with.
+    fn elapsed_ms(&self) -> Option<u64> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            Some(self.started.elapsed().as_millis() as u64)
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            None
+        }
+    }
+
+    /// Record the counts under `scan` in a successful call's metadata.
+    fn annotate(&self, output: &mut ToolOutput, display: &DisplayRoot) {
+        if output.is_error {
+            return;
+        }
+        let counts = self.counts.lock().unwrap();
+        set_metadata(
+            output,
+            "scan",
+            json!({
+                "files_scanned": counts.files,
+                "bytes_scanned": counts.bytes,
+                "skipped": {
+                    "binary": counts.skipped.binary,
+                    "too_large": counts.skipped.too_large,
+                    "unreadable": counts.skipped.unreadable,
+                    "timed_out": counts.skipped.timed_out,
+                },
+                "timed_out_files": counts
+                    .timed_out_files
+                    .iter()
+                    .map(|path| display.show(path))
+                    .collect::<Vec<_>>(),
+                "elapsed_ms": self.elapsed_ms(),
+            }),
+        );
+    }
+}
+
+/// Per-file or overall counters in ripgrep's `stats` shape.
+#[derive(Default)]
+struct RgStats {
+    searches: usize,
+    searches_with_match: usize,
+    bytes_searched: usize,
+    bytes_printed: usize,
+    matched_lines: usize,
+    matches: usize,
+}
+
+impl RgStats {
+    fn add(&mut self, other: &RgStats) {
+        self.searches += other.searches;
+        self.searches_with_match += other.searches_with_match;
+        self.bytes_searched += other.bytes_searched;
+        self.bytes_printed += other.bytes_printed;
+        self.matched_lines += other.matched_lines;
+        self.matches += other.matches;
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "elapsed": rg_elapsed(),
+            "searches": self.searches,
+            "searches_with_match": self.searches_with_match,
+            "bytes_searched": self.bytes_searched,
+            "bytes_printed": self.bytes_printed,
+            "matched_lines": self.matched_lines,
+            "matches": self.matches,
+        })
+    }
+}
+
+/// ripgrep's `elapsed` shape. The search runs without a clock (it must work
+/// in WASM), so durations are always reported as zero.
+fn rg_elapsed() -> serde_json::Value {
+    json!({"secs": 0, "nanos": 0, "human": "0.000000s"})
+}
+
+/// One ripgrep `match` or `context` event.
+fn rg_line_event(
+    kind: &str,
+    path: &str,
+    line: &str,
+    line_number: usize,
+    offset: usize,
+    submatches: &[(usize, usize)],
+) -> serde_json::Value {
+    let text = line.trim_end_matches(['\n', '\r']);
+    let submatches: Vec<_> = submatches
+        .iter()
+        .map(|&(start, end)| json!({"match": {"text": &text[start..end]}, "start": start, "end": end}))
+        .collect();
+    json!({
+        "type": kind,
+        "data": {
+            "path": {"text": path},
+            "lines": {"text": line},
+            "line_number": line_number,
+            "absolute_offset": offset,
+            "submatches": submatches,
+        }
+    })
+}
+
+impl GrepTool {
+    /// Emit ripgrep `--json` events (`begin`, `match`, `context`, `end`,
+    /// `summary`), one JSON object per line.
+    async fn rg_json(
+        &self,
+        files: &[String],
+        opts: &SearchOptions<'_>,
+        progress: &mut Progress,
+    ) -> SoulResult<ToolOutput> {
+        let mut events: Vec<String> = Vec::new();
+        let mut totals = RgStats::default();
+        let mut pruned = 0;
+
+        for file_path in files {
+            if totals.matched_lines >= opts.max_matches {
+                break;
+            }
+            let content = self
+                .read_candidate(file_path, opts.pattern, &mut pruned, opts.scan)
+                .await;
+            progress.tick(totals.matched_lines);
+            let Some(content) = content else {
+                continue; // Unreadable or ruled out by the index
+            };
+
+            let mut stats = RgStats {
+                searches: 1,
+                bytes_searched: content.len(),
+                ..Default::default()
+            };
+            // Lines keep their terminators, as rg reports them
+            let lines: Vec<&str> = content.split_inclusive('\n').collect();
+            let mut offsets = Vec::with_capacity(lines.len());
+            let mut offset = 0;
+            for line in &lines {
+                offsets.push(offset);
+                offset += line.len();
+            }
+
+            let budget = opts
+                .max_per_file
+                .unwrap_or(usize::MAX)
+                .min(opts.max_matches - totals.matched_lines);
+            let mut match_idxs = Vec::new();
+            let complete = opts.scan.scan_lines(file_path, lines.iter().copied(), |i, line| {
+                let text = line.trim_end_matches(['\n', '\r']);
+                if matches_pattern(text, opts.pattern, opts.literal, opts.ignore_case) {
+                    match_idxs.push(i);
+                }
+                match_idxs.len() < budget
+            });
+            if !complete {
+                continue;
+            }
+            if match_idxs.is_empty() {
+                totals.add(&stats);
+                continue;
+            }
+
+            let shown_path = opts.display.show(file_path);
+            let mut file_events =
+                vec![json!({"type": "begin", "data": {"path": {"text": shown_path}}})];
+            let mut next_unprinted = 0;
+            for &match_idx in &match_idxs {
+                let start = match_idx
+                    .saturating_sub(opts.context_lines)
+                    .max(next_unprinted);
+                let end = (match_idx + opts.context_lines + 1).min(lines.len());
+                for idx in start..end {
+                    let line = lines[idx];
+                    let event = if match_idxs.binary_search(&idx).is_ok() {
+                        let text = line.trim_end_matches(['\n', '\r']);
+                        let spans = match_spans(text, opts.pattern, opts.ignore_case);
+                        stats.matched_lines += 1;
+                        stats.matches += spans.len();
+                        rg_line_event("match", &shown_path, line, idx + 1, offsets[idx], &spans)
+                    } else {
+                        rg_line_event("context", &shown_path, line, idx + 1, offsets[idx], &[])
+                    };
+                    stats.bytes_printed += event.to_string().len() + 1;
+                    file_events.push(event);
+                }
+                next_unprinted = next_unprinted.max(end);
+            }
+            stats.searches_with_match = 1;
+            file_events.push(json!({
+                "type": "end",
+                "data": {
+                    "path": {"text": shown_path},
+                    "binary_offset": null,
+                    "stats": stats.to_json(),
+                }
+            }));
+            events.extend(file_events.iter().map(|e| e.to_string()));
+            totals.add(&stats);
+        }
+
+        // Truncate whole events so every line stays valid JSON; the summary
+        // always comes last
+        let body = events.join("\n");
+        let truncated = truncate_head(&body, events.len(), MAX_BYTES);
+        let mut result = truncated.content.clone();
+        let summary = json!({
+            "type": "summary",
+            "data": {
+                "elapsed_total": rg_elapsed(),
+                "stats": totals.to_json(),
+            }
+        });
+        if !result.is_empty() {
+            result.push('\n');
+        }
+        result.push_str(&summary.to_string());
+
+        Ok(ToolOutput::success(result).with_metadata(json!({
+            "format": "json",
+            "matches": totals.matched_lines,
+            "files_with_matches": totals.searches_with_match,
+            "index_pruned": pruned,
+            "truncated": truncated.is_truncated(),
+            "truncation": truncated.to_metadata(),
+        })))
+    }
+
+    /// Binary mode: search each file's bytes for `needle`, reporting offsets
+    /// with a hex dump of `opts.context_lines` bytes either side.
+    async fn search_bytes(
+        &self,
+        files: &[String],
+        needle: &[u8],
+        opts: &SearchOptions<'_>,
+        progress: &mut Progress,
+    ) -> SoulResult<ToolOutput> {
+        let context = opts.context_lines.min(BINARY_MAX_CONTEXT);
+        let mut output = String::new();
+        let mut offsets_meta = Vec::new();
+        let mut files_with_matches = 0;
+        let mut files_capped = 0;
+        let mut unreadable = 0;
+
+        for file_path in files {
+            if offsets_meta.len() >= opts.max_matches {
+                break;
+            }
+            let content = self.read_file(file_path, opts.scan).await;
+            progress.tick(offsets_meta.len());
+            let Some(content) = content else {
+                unreadable += 1; // Not representable through the VFS
+                continue;
+            };
+            let bytes = content.as_bytes();
+            let budget = opts
+                .max_per_file
+                .unwrap_or(usize::MAX)
+                .min(opts.max_matches - offsets_meta.len());
+            let offsets = find_bytes(bytes, needle, opts.ignore_case, budget.saturating_add(1));
+            if offsets.is_empty() {
+                continue;
+            }
+            if offsets.len() > budget && opts.max_per_file.is_some_and(|cap| budget >= cap) {
+                files_capped += 1;
+            }
+
+            files_with_matches += 1;
+            let shown_path = opts.display.show(file_path);
+            for &offset in offsets.iter().take(budget) {
+                if !output.is_empty() {
+                    output.push('\n');
+                }
+                output.push_str(&format!("{} @ 0x{:x} ({})\n", shown_path, offset, offset));
+                output.push_str(&hex_dump(
+                    bytes,
+                    offset.saturating_sub(context),
+                    offset + needle.len() + context,
+                ));
+                offsets_meta.push(json!({"path": shown_path, "offset": offset}));
+            }
+        }
+
+        let total = offsets_meta.len();
+        if total == 0 {
+            return Ok(ToolOutput::success(format!(
+                "No matches found for bytes '{}' in {} file(s)",
+                opts.pattern,
+                files.len() - unreadable
+            ))
+            .with_metadata(json!({"matches": 0, "files": 0, "unreadable": unreadable})));
+        }
+
+        let truncated = truncate_head(&output, output.lines().count(), MAX_BYTES);
+        let mut result = truncated.content.trim_end().to_string();
+        if total >= opts.max_matches {
+            result.push_str(&format!("\n[Reached max matches limit: {}]", opts.max_matches));
+        }
+        if files_capped > 0 {
+            result.push_str(&format!(
+                "\n[Per-file match limit reached in {} file(s)]",
+                files_capped
+            ));
+        }
+        if let Some(notice) = truncated.truncation_notice() {
+            result.push_str(&format!("\n{}", notice));
+        }
+        Ok(ToolOutput::success(result).with_metadata(json!({
+            "matches": total,
+            "files_with_matches": files_with_matches,
+            "files_capped": files_capped,
+            "unreadable": unreadable,
+            "offsets": offsets_meta,
+            "truncated": truncated.is_truncated(),
+            "truncation": truncated.to_metadata(),
+        })))
+    }
+
+    /// Report per-file occurrence counts instead of matching lines.
+    async fn count_replacements(
+        &self,
+        files: &[String],
+        opts: &SearchOptions<'_>,
+        search_path: &str,
+        progress: &mut Progress,
+    ) -> SoulResult<ToolOutput> {
+        let (pattern, display) = (opts.pattern, opts.display);
+        let mut counts = Vec::new();
+        let mut pruned = 0;
+        let mut found = 0;
+        for file_path in files {
+            let content = self
+                .read_candidate(file_path, pattern, &mut pruned, opts.scan)
+                .await;
+            progress.tick(found);
+            let Some(content) = content else {
+                continue; // Unreadable or ruled out by the index
+            };
+            let mut count = 0;
+            let complete = opts.scan.scan_lines(file_path, content.lines(), |_, line| {
+                count += count_occurrences(line, pattern, opts.ignore_case);
+                true
+            });
+            if complete && count > 0 {
+                counts.push((display.show(file_path), count));
+                found += count;
+            }
+        }
+
+        let total: usize = counts.iter().map(|(_, c)| c).sum();
+        if total == 0 {
+            return Ok(ToolOutput::success(format!(
+                "No occurrences of '{}' in {}",
+                pattern,
+                display.show(search_path)
+            ))
+            .with_metadata(json!({"occurrences": 0, "files": []})));
+        }
+
+        // Largest blast radius first
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let listing = counts
+            .iter()
+            .map(|(path, count)| format!("{}: {}", path, count))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let truncated = truncate_head(&listing, listing.lines().count(), MAX_BYTES);
+        let mut result = format!(
+            "{} occurrence(s) of '{}' in {} file(s)\n{}",
+            total,
+            pattern,
+            counts.len(),
+            truncated.content
+        );
+        if let Some(notice) = truncated.truncation_notice() {
+            result.push_str(&format!("\n{}", notice));
+        }
+
+        let files: Vec<_> = counts
+            .iter()
+            .map(|(path, count)| json!({"path": path, "count": count}))
+            .collect();
+        Ok(ToolOutput::success(result).with_metadata(json!({
+            "occurrences": total,
+            "files": files,
+            "truncation": truncated.to_metadata(),
+        })))
+    }
+}
+
+impl GrepTool {
+    /// Report each distinct matching line (ignoring surrounding whitespace)
+    /// once, most frequent first, instead of every match.
+    async fn unique_lines(
+        &self,
+        files: &[String],
+        opts: &SearchOptions<'_>,
+        search_path: &str,
+        progress: &mut Progress,
+    ) -> SoulResult<ToolOutput> {
+        // Line -> (occurrences, files, first seen)
+        let mut seen: HashMap<String, (usize, usize, usize)> = HashMap::new();
+        let mut pruned = 0;
+        let mut matches = 0;
+        for file_path in files {
+            let content = self
+                .read_candidate(file_path, opts.pattern, &mut pruned, opts.scan)
+                .await;
+            progress.tick(matches);
+            let Some(content) = content else {
+                continue; // Unreadable or ruled out by the index
+            };
+            let mut matched = Vec::new();
+            let complete = opts.scan.scan_lines(file_path, content.lines(), |_, line| {
+                if matches_pattern(line, opts.pattern, opts.literal, opts.ignore_case) {
+                    matched.push(line.trim());
+                }
+                true
+            });
+            if !complete {
+                continue;
+            }
+            let mut in_file = HashSet::new();
+            for line in matched {
+                matches += 1;
+                let order = seen.len();
+                let entry = seen.entry(line.to_string()).or_insert((0, 0, order));
+                entry.0 += 1;
+                if in_file.insert(line) {
+                    entry.1 += 1;
+                }
+            }
+        }
+
+        if seen.is_empty() {
+            return Ok(ToolOutput::success(format!(
+                "No matches found for pattern '{}' in {}",
+                opts.pattern,
+                opts.display.show(search_path)
+            ))
+            .with_metadata(json!({"matches": 0, "unique_lines": 0})));
+        }
+
+        let mut lines: Vec<(String, (usize, usize, usize))> = seen.into_iter().collect();
+        lines.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then(a.1 .2.cmp(&b.1 .2)));
+        let distinct = lines.len();
+        lines.truncate(opts.max_matches);
+
+        let width = lines[0].1 .0.to_string().len();
+        let listing = lines
+            .iter()
+            .map(|(line, (count, files, _))| {
+                let files = match files {
+                    1 => String::new(),
+                    n => format!("  [{} files]", n),
+                };
+                format!(
+                    "{:>width$}  {}{}",
+                    count,
+                    truncate_line(line, GREP_MAX_LINE_LENGTH),
+                    files
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let truncated = truncate_head(&listing, listing.lines().count(), MAX_BYTES);
+        let mut result = format!(
+            "{} distinct line(s) in {} match(es) of '{}'\n{}",
+            distinct, matches, opts.pattern, truncated.content
+        );
+        if distinct > lines.len() {
+            result.push_str(&format!(
+                "\n[Showing the {} most frequent of {} distinct lines]",
+                lines.len(),
+                distinct
+            ));
+        }
+        if let Some(notice) = truncated.truncation_notice() {
+            result.push_str(&format!("\n{}", notice));
+        }
+
+        let listed: Vec<_> = lines
+            .iter()
+            .map(|(line, (count, files, _))| json!({"line": line, "count": count, "files": files}))
+            .collect();
+        Ok(ToolOutput::success(result).with_metadata(json!({
+            "matches": matches,
+            "unique_lines": distinct,
+            "lines": listed,
+            "index_pruned": pruned,
+            "truncation": truncated.to_metadata(),
+        })))
+    }
+}
+
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl Tool for GrepTool {
     fn name(&self) -> &str {
-        "grep"
+        self.overrides.name("grep")
     }
 
     fn definition(&self) -> ToolDefinition {
-        ToolDefinition {
+        self.overrides.apply(ToolDefinition {
             name: "grep".into(),
-            description: "Search file contents for a pattern. Returns matching lines with file paths and line numbers.".into(),
+            description: "Search file contents for a pattern. Returns matching lines with file paths and line numbers. Set search_names to match file paths in the same call.".into(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -143,21 +1046,90 @@ impl Tool for GrepTool {
                         "type": "integer",
                         "description": "Number of context lines before and after each match"
                     },
+                    "max_depth": {
+                        "type": "integer",
+                        "description": "Maximum directory depth to search (1 = only files directly in path; default: unlimited)"
+                    },
                     "max_matches": {
                         "type": "integer",
                         "description": "Maximum number of matches to return (default: 100)"
+                    },
+                    "max_matches_per_file": {
+                        "type": "integer",
+                        "description": "Maximum matches reported from any single file, so one noisy file can't use up the whole budget"
+                    },
+                    "search_names": {
+                        "type": "boolean",
+                        "description": "Also match the pattern against file paths, listing hits as 'path (filename match)' before content matches"
+                    },
+                    "highlight": {
+                        "type": "boolean",
+                        "description": "Wrap each matched substring in markers (default >>>match<<<) and report match columns in metadata"
+                    },
+                    "highlight_markers": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "Opening and closing markers to use instead of >>> and <<<"
+                    },
+                    "group_by_file": {
+                        "type": "boolean",
+                        "description": "Print each file's path once as a heading followed by its 'line:text' matches, instead of repeating the path on every line; shorter when files have many matches (default: false)"
+                    },
+                    "count_replacements": {
+                        "type": "boolean",
+                        "description": "Only report how many occurrences each file contains (no lines), to gauge the blast radius of a rename before editing"
+                    },
+                    "unique": {
+                        "type": "boolean",
+                        "description": "List each distinct matching line once (ignoring indentation) with its occurrence and file counts, most frequent first; max_matches caps the lines listed. For questions like 'which versions of X appear' or 'all imports of Y'"
+                    },
+                    "sort": SortOrder::schema(),
+                    "relative_to": DisplayRoot::schema(),
+                    "binary": {
+                        "type": "boolean",
+                        "description": "Search raw bytes instead of lines: pattern is hex (e.g. '89 50 4e 47') or text with \\xNN escapes, context counts bytes (default 16), and each match is reported as a byte offset with a hex dump"
+                    },
+                    "output_format": {
+                        "type": "string",
+                        "enum": ["text", "json"],
+                        "description": "'json' emits ripgrep --json events (begin/match/context/end/summary), one per line, for tools that consume rg output (default: text)"
                     }
                 },
                 "required": ["pattern"]
             }),
-        }
+        })
     }
 
     async fn execute(
         &self,
         _call_id: &str,
         arguments: serde_json::Value,
-        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+        partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let guard = WalkGuard::new();
+        let scan = ScanStats::new(self.scan_budget);
+        let duplicates = Duplicates::new(self.identity.clone());
+        let display = DisplayRoot::from_arguments(&self.cwd, &arguments);
+        let mut output = self
+            .search(arguments, partial_tx, &guard, &scan, &duplicates)
+            .await?;
+        guard.annotate(&mut output);
+        scan.annotate(&mut output, &display);
+        duplicates.annotate(&mut output, &display);
+        Ok(output)
+    }
+}
+
+impl GrepTool {
+    /// Carry out a call; [`execute`](Tool::execute) reports walks `guard`
+    /// cut short, what `scan` counted and the files `duplicates` dropped.
+    async fn search(
+        &self,
+        arguments: serde_json::Value,
+        partial_tx: Option<mpsc::UnboundedSender<String>>,
+        guard: &WalkGuard,
+        scan: &ScanStats,
+        duplicates: &Duplicates,
     ) -> SoulResult<ToolOutput> {
         let pattern = arguments
             .get("pattern")
@@ -165,7 +1137,10 @@ impl Tool for GrepTool {
             .unwrap_or("");
 
         if pattern.is_empty() {
-            return Ok(ToolOutput::error("Missing required parameter: pattern"));
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Missing required parameter: pattern",
+            ));
         }
 
         let search_path = arguments
@@ -173,6 +1148,7 @@ impl Tool for GrepTool {
             .and_then(|v| v.as_str())
             .map(|p| resolve_path(&self.cwd, p))
             .unwrap_or_else(|| self.cwd.clone());
+        let display = DisplayRoot::from_arguments(&self.cwd, &arguments);
 
         let glob_filter = arguments.get("glob").and_then(|v| v.as_str());
         let ignore_case = arguments
@@ -192,77 +1168,316 @@ impl Tool for GrepTool {
             .and_then(|v| v.as_u64())
             .map(|v| (v as usize).min(MAX_MATCHES))
             .unwrap_or(MAX_MATCHES);
+        let max_per_file = arguments
+            .get("max_matches_per_file")
+            .and_then(|v| v.as_u64())
+            .map(|v| (v as usize).max(1));
+
+        let max_depth = arguments
+            .get("max_depth")
+            .and_then(|v| v.as_u64())
+            .map(|v| (v as usize).max(1));
 
         // Collect files to search
         let mut files = Vec::new();
-        if let Err(e) = collect_files(self.fs.as_ref(), &search_path, &mut files, glob_filter).await
+        guard.enter(&search_path, 0);
+        if let Err(e) = collect_files(
+            self.fs.as_ref(),
+            &search_path,
+            &mut files,
+            glob_filter,
+            max_depth,
+            guard,
+            0,
+        )
+        .await
         {
-            return Ok(ToolOutput::error(format!(
-                "Failed to enumerate files in {}: {}",
-                search_path, e
-            )));
+            return Ok(tool_error(
+                ToolErrorKind::from_soul_error(&e),
+                format!("Failed to enumerate files in {}: {}", search_path, e),
+            ));
         }
 
-        files.sort();
-
-        let mut output = String::new();
-        let mut total_matches = 0;
-        let mut files_with_matches = 0;
+        let sort = match SortOrder::parse(arguments.get("sort")) {
+            Ok(sort) => sort,
+            Err(e) => return Ok(e),
+        };
+        sort.sort(&mut files);
+        duplicates.retain_unique(&mut files).await;
+        let mut progress = Progress::new(partial_tx, self.progress_interval, Some(files.len()));
 
-        'files: for file_path in &files {
-            let content = match self.fs.read_to_string(file_path).await {
-                Ok(c) => c,
-                Err(_) => continue, // Skip unreadable files
+        let count_only = arguments
+            .get("count_replacements")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let json_output = match arguments.get("output_format").and_then(|v| v.as_str()) {
+            None | Some("text") => false,
+            Some("json") => true,
+            Some(other) => {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!("Unknown output_format: {} (expected text or json)", other),
+                ))
+            }
+        };
+        if arguments.get("binary").and_then(|v| v.as_bool()) == Some(true) {
+            let conflict = [
+                "count_replacements",
+                "search_names",
+                "highlight",
+                "unique",
+                "group_by_file",
+            ]
+            .into_iter()
+            .find(|key| arguments.get(*key).and_then(|v| v.as_bool()) == Some(true))
+            .or(json_output.then_some("output_format json"));
+            if let Some(key) = conflict {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!("binary can't be combined with {}", key),
+                ));
+            }
+            let needle = match parse_byte_pattern(pattern) {
+                Ok(needle) if !needle.is_empty() => needle,
+                Ok(_) => {
+                    return Ok(tool_error(
+                        ToolErrorKind::InvalidArguments,
+                        "Binary pattern is empty",
+                    ))
+                }
+                Err(e) => return Ok(tool_error(ToolErrorKind::InvalidArguments, e)),
+            };
+            let opts = SearchOptions {
+                pattern,
+                literal: true,
+                ignore_case,
+                context_lines: arguments
+                    .get("context")
+                    .and_then(|v| v.as_u64())
+                    .map_or(BINARY_CONTEXT, |v| v as usize),
+                max_matches,
+                max_per_file,
+                display: &display,
+                scan,
+            };
+            return self
+                .search_bytes(&files, &needle, &opts, &mut progress)
+                .await;
+        }
+        if json_output {
+            let conflict = [
+                "count_replacements",
+                "search_names",
+                "highlight",
+                "unique",
+                "group_by_file",
+            ]
+            .into_iter()
+            .find(|key| arguments.get(*key).and_then(|v| v.as_bool()) == Some(true));
+            if let Some(key) = conflict {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!("output_format json can't be combined with {}", key),
+                ));
+            }
+            let opts = SearchOptions {
+                pattern,
+                literal,
+                ignore_case,
+                context_lines,
+                max_matches,
+                max_per_file,
+                display: &display,
+                scan,
+            };
+            return self.rg_json(&files, &opts, &mut progress).await;
+        }
+        if arguments.get("unique").and_then(|v| v.as_bool()) == Some(true) {
+            let conflict = ["count_replacements", "search_names", "highlight", "group_by_file"]
+                .into_iter()
+                .find(|key| arguments.get(*key).and_then(|v| v.as_bool()) == Some(true))
+                .or((context_lines > 0).then_some("context"));
+            if let Some(key) = conflict {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!("unique can't be combined with {}", key),
+                ));
+            }
+            let opts = SearchOptions {
+                pattern,
+                literal,
+                ignore_case,
+                context_lines,
+                max_matches,
+                max_per_file,
+                display: &display,
+                scan,
+            };
+            return self
+                .unique_lines(&files, &opts, &search_path, &mut progress)
+                .await;
+        }
+        if count_only {
+            let opts = SearchOptions {
+                pattern,
+                literal,
+                ignore_case,
+                context_lines,
+                max_matches,
+                max_per_file,
+                display: &display,
+                scan,
+            };
+            return self
+                .count_replacements(&files, &opts, &search_path, &mut progress)
+                .await;
+        }
+
+        let mut output = String::new();
+        let mut total_matches = 0;
+        let mut files_with_matches = 0;
+
+        let mut files_capped = 0;
+        let mut index_pruned = 0;
+
+        let markers = arguments
+            .get("highlight_markers")
+            .and_then(|v| v.as_array())
+            .and_then(|m| match m.as_slice() {
+                [open, close] => Some((open.as_str()?, close.as_str()?)),
+                _ => None,
+            });
+        let highlight_markers = arguments
+            .get("highlight")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(markers.is_some())
+            .then(|| markers.unwrap_or(HIGHLIGHT_MARKERS));
+        let mut spans_meta = Vec::new();
+        let group_by_file = arguments
+            .get("group_by_file")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        // Path matches first, so "where is anything about X" needs one call
+        let search_names = arguments
+            .get("search_names")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let mut name_matches = 0;
+        if search_names {
+            for file_path in &files {
+                if total_matches >= max_matches {
+                    break;
+                }
+                let shown_path = display.show(file_path);
+                if matches_pattern(&shown_path, pattern, literal, ignore_case) {
+                    output.push_str(&format!("{} (filename match)\n", shown_path));
+                    name_matches += 1;
+                    total_matches += 1;
+                }
+            }
+        }
+
+        for file_path in &files {
+            if total_matches >= max_matches {
+                break;
+            }
+
+            let content = self
+                .read_candidate(file_path, pattern, &mut index_pruned, scan)
+                .await;
+            progress.tick(total_matches);
+            let Some(content) = content else {
+                continue; // Unreadable or ruled out by the index
             };
 
             let lines: Vec<&str> = content.lines().collect();
-            let mut file_had_match = false;
 
-            for (line_idx, line) in lines.iter().enumerate() {
-                if matches_pattern(line, pattern, literal, ignore_case) {
-                    if !file_had_match {
-                        if !output.is_empty() {
-                            output.push('\n');
-                        }
-                        files_with_matches += 1;
-                        file_had_match = true;
+            // Collect match positions, bounded by the per-file and global budgets
+            let file_budget = max_per_file
+                .unwrap_or(usize::MAX)
+                .min(max_matches - total_matches);
+            let mut match_idxs = Vec::new();
+            let complete = scan.scan_lines(file_path, lines.iter().copied(), |line_idx, line| {
+                if !matches_pattern(line, pattern, literal, ignore_case) {
+                    return true;
+                }
+                if match_idxs.len() >= file_budget {
+                    if max_per_file.is_some_and(|cap| match_idxs.len() >= cap) {
+                        files_capped += 1;
                     }
+                    return false;
+                }
+                match_idxs.push(line_idx);
+                true
+            });
 
-                    // Context before
-                    let ctx_start = line_idx.saturating_sub(context_lines);
-                    for ctx_idx in ctx_start..line_idx {
-                        output.push_str(&format!(
-                            "{}:{}-{}\n",
-                            display_path(file_path, &self.cwd),
-                            ctx_idx + 1,
-                            truncate_line(lines[ctx_idx], GREP_MAX_LINE_LENGTH)
-                        ));
-                    }
+            if !complete || match_idxs.is_empty() {
+                continue;
+            }
+
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            files_with_matches += 1;
+            total_matches += match_idxs.len();
+
+            let shown_path = display.show(file_path);
+            if group_by_file {
+                output.push_str(&shown_path);
+                output.push('\n');
+            }
+
+            // Emit each line at most once, even where context windows overlap
+            let mut next_unprinted = 0;
+            for (i, &match_idx) in match_idxs.iter().enumerate() {
+                let ctx_start = match_idx.saturating_sub(context_lines).max(next_unprinted);
+                let ctx_end = (match_idx + context_lines + 1).min(lines.len());
+
+                if context_lines > 0 && i > 0 && ctx_start > next_unprinted {
+                    output.push_str("--\n");
+                }
 
-                    // Match line
-                    output.push_str(&format!(
-                        "{}:{}:{}\n",
-                        display_path(file_path, &self.cwd),
-                        line_idx + 1,
-                        truncate_line(line, GREP_MAX_LINE_LENGTH)
-                    ));
-
-                    // Context after
-                    let ctx_end = (line_idx + context_lines + 1).min(lines.len());
-                    for ctx_idx in (line_idx + 1)..ctx_end {
+                for (idx, line) in lines.iter().enumerate().take(ctx_end).skip(ctx_start) {
+                    let is_match = match_idxs.binary_search(&idx).is_ok();
+                    let sep = if is_match { ':' } else { '-' };
+                    let shown_line = match highlight_markers {
+                        Some((open, close)) if is_match => {
+                            let spans = match_spans(line, pattern, ignore_case);
+                            // Char columns, 0-based with exclusive end
+                            let columns: Vec<_> = spans
+                                .iter()
+                                .map(|&(start, end)| {
+                                    let col = line[..start].chars().count();
+                                    json!({"start": col, "end": col + line[start..end].chars().count()})
+                                })
+                                .collect();
+                            spans_meta.push(json!({
+                                "path": shown_path,
+                                "line": idx + 1,
+                                "spans": columns,
+                            }));
+                            let extra = spans.len() * (open.len() + close.len());
+                            truncate_line(
+                                &highlight(line, &spans, open, close),
+                                GREP_MAX_LINE_LENGTH + extra,
+                            )
+                        }
+                        _ => truncate_line(line, GREP_MAX_LINE_LENGTH),
+                    };
+                    if group_by_file {
+                        output.push_str(&format!("{}{}{}\n", idx + 1, sep, shown_line));
+                    } else {
                         output.push_str(&format!(
-                            "{}:{}-{}\n",
-                            display_path(file_path, &self.cwd),
-                            ctx_idx + 1,
-                            truncate_line(lines[ctx_idx], GREP_MAX_LINE_LENGTH)
+                            "{}:{}{}{}\n",
+                            shown_path,
+                            idx + 1,
+                            sep,
+                            shown_line
                         ));
                     }
-
-                    total_matches += 1;
-                    if total_matches >= max_matches {
-                        break 'files;
-                    }
                 }
+                next_unprinted = next_unprinted.max(ctx_end);
             }
         }
 
@@ -270,16 +1485,17 @@ impl Tool for GrepTool {
             return Ok(ToolOutput::success(format!(
                 "No matches found for pattern '{}' in {}",
                 pattern,
-                display_path(&search_path, &self.cwd)
+                display.show(&search_path)
             ))
             .with_metadata(json!({"matches": 0, "files": 0})));
         }
 
         // Apply byte truncation
-        let truncated = truncate_head(&output, total_matches + (total_matches * context_lines * 2), MAX_BYTES);
+        let truncated = truncate_head(&output, output.lines().count(), MAX_BYTES);
 
         let notice = truncated.truncation_notice();
         let is_truncated = truncated.is_truncated();
+        let truncated_meta = truncated.to_metadata();
         let mut result = truncated.content;
         if total_matches >= max_matches {
             result.push_str(&format!(
@@ -287,25 +1503,29 @@ impl Tool for GrepTool {
                 max_matches
             ));
         }
+        if files_capped > 0 {
+            result.push_str(&format!(
+                "\n[Per-file match limit reached in {} file(s)]",
+                files_capped
+            ));
+        }
         if let Some(notice) = notice {
             result.push_str(&format!("\n{}", notice));
         }
 
-        Ok(ToolOutput::success(result).with_metadata(json!({
+        let mut metadata = json!({
             "matches": total_matches,
             "files_with_matches": files_with_matches,
+            "files_capped": files_capped,
+            "name_matches": name_matches,
+            "index_pruned": index_pruned,
             "truncated": is_truncated,
-        })))
-    }
-}
-
-/// Make paths relative to cwd for display.
-fn display_path(path: &str, cwd: &str) -> String {
-    let cwd_prefix = format!("{}/", cwd.trim_end_matches('/'));
-    if path.starts_with(&cwd_prefix) {
-        path[cwd_prefix.len()..].to_string()
-    } else {
-        path.to_string()
+            "truncation": truncated_meta,
+        });
+        if highlight_markers.is_some() {
+            metadata["match_spans"] = json!(spans_meta);
+        }
+        Ok(ToolOutput::success(result).with_metadata(metadata))
     }
 }
 
@@ -320,6 +1540,66 @@ mod tests {
         (fs, tool)
     }
 
+    #[tokio::test]
+    async fn grep_rg_json_events() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/a.rs", "fn main() {\n    let x = foo(foo);\n}\n")
+            .await
+            .unwrap();
+        fs.write("/project/b.rs", "nothing here\n").await.unwrap();
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({"pattern": "foo", "output_format": "json", "context": 1}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        let events: Vec<serde_json::Value> = result
+            .content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let kinds: Vec<&str> = events.iter().map(|e| e["type"].as_str().unwrap()).collect();
+        assert_eq!(
+            kinds,
+            ["begin", "context", "match", "context", "end", "summary"]
+        );
+
+        let m = &events[2]["data"];
+        assert_eq!(m["path"]["text"], "a.rs");
+        assert_eq!(m["lines"]["text"], "    let x = foo(foo);\n");
+        assert_eq!(m["line_number"], 2);
+        assert_eq!(m["absolute_offset"], 12);
+        assert_eq!(
+            m["submatches"][0],
+            json!({"match": {"text": "foo"}, "start": 12, "end": 15})
+        );
+        assert_eq!(m["submatches"][1]["start"], 16);
+
+        assert_eq!(events[4]["data"]["stats"]["matches"], 2);
+        let summary = &events[5]["data"]["stats"];
+        assert_eq!(summary["searches"], 2);
+        assert_eq!(summary["searches_with_match"], 1);
+        assert_eq!(summary["matched_lines"], 1);
+        assert_eq!(result.metadata["format"], "json");
+
+        let result = tool
+            .execute(
+                "c2",
+                json!({"pattern": "foo", "output_format": "json", "count_replacements": true}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            ToolErrorKind::of(&result),
+            Some(ToolErrorKind::InvalidArguments)
+        );
+    }
+
     #[tokio::test]
     async fn grep_simple_match() {
         let (fs, tool) = setup().await;
@@ -381,6 +1661,135 @@ mod tests {
         assert!(!result.content.contains("readme.md"));
     }
 
+    #[tokio::test]
+    async fn grep_stops_endless_trees() {
+        let fs = crate::walk::tests::loop_fs().await;
+        let tool = GrepTool::new(fs, "/w");
+        let result = tool
+            .execute("c1", json!({"pattern": "needle"}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("loop/start.txt:1:needle"), "{}", result.content);
+        assert!(result.content.contains("[Walk stopped at depth 64 in 1 directory"));
+        assert_eq!(result.metadata["walk"]["depth_limited"], 1);
+    }
+
+    #[tokio::test]
+    async fn grep_max_depth() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/Cargo.toml", "version = 1").await.unwrap();
+        fs.write("/project/crates/a/Cargo.toml", "version = 2")
+            .await
+            .unwrap();
+        fs.write("/project/crates/Cargo.toml", "version = 3")
+            .await
+            .unwrap();
+
+        let result = tool
+            .execute("c1", json!({"pattern": "version", "max_depth": 1}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["matches"], 1);
+        assert!(result.content.contains("Cargo.toml:1:version = 1"));
+
+        let result = tool
+            .execute("c2", json!({"pattern": "version", "max_depth": 2}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["matches"], 2);
+        assert!(!result.content.contains("crates/a/"));
+    }
+
+    #[tokio::test]
+    async fn grep_natural_sort() {
+        let (fs, tool) = setup().await;
+        for name in ["part10.txt", "part9.txt", "part1.txt"] {
+            fs.write(&format!("/project/{}", name), "needle").await.unwrap();
+        }
+
+        let result = tool
+            .execute("c1", json!({"pattern": "needle"}), None)
+            .await
+            .unwrap();
+        let files: Vec<&str> = result
+            .content
+            .lines()
+            .filter(|l| !l.is_empty())
+            .filter_map(|l| l.split(':').next())
+            .collect();
+        assert_eq!(files, ["part1.txt", "part9.txt", "part10.txt"]);
+
+        let result = tool
+            .execute("c2", json!({"pattern": "needle", "sort": "size"}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            ToolErrorKind::of(&result),
+            Some(ToolErrorKind::InvalidArguments)
+        );
+    }
+
+    #[tokio::test]
+    async fn grep_collapses_linked_paths() {
+        use crate::file_identity::MemoryFileIdentity;
+
+        let (fs, tool) = setup().await;
+        fs.write("/project/lib/a.rs", "needle").await.unwrap();
+        fs.write("/project/vendor/lib/a.rs", "needle").await.unwrap();
+        let identity = Arc::new(MemoryFileIdentity::new());
+        identity.link("/project/vendor/lib", "/project/lib");
+        let tool = tool.with_file_identity(identity);
+
+        let result = tool
+            .execute("c1", json!({"pattern": "needle"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content.matches("needle").count(), 1);
+        assert!(result.content.contains("lib/a.rs"), "{}", result.content);
+        assert_eq!(
+            result.metadata["duplicates"][0]["same_file"],
+            json!(["vendor/lib/a.rs"])
+        );
+    }
+
+    #[tokio::test]
+    async fn grep_relative_to() {
+        let (fs, tool) = setup().await;
+        fs.write("/shared/util.rs", "fn helper() {}").await.unwrap();
+
+        let result = tool
+            .execute("c1", json!({"pattern": "helper", "path": "/shared"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content.trim_end(), "/shared/util.rs:1:fn helper() {}");
+
+        let result = tool
+            .execute(
+                "c2",
+                json!({"pattern": "helper", "path": "/shared", "relative_to": "."}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.content.trim_end(), "../shared/util.rs:1:fn helper() {}");
+
+        let result = tool
+            .execute(
+                "c3",
+                json!({
+                    "pattern": "helper",
+                    "path": "/shared",
+                    "relative_to": ".",
+                    "count_replacements": true
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(result.content.contains("../shared/util.rs"), "{}", result.content);
+    }
+
     #[tokio::test]
     async fn grep_no_matches() {
         let (fs, tool) = setup().await;
@@ -428,6 +1837,414 @@ mod tests {
         assert!(result.content.contains("d")); // after context
     }
 
+    #[tokio::test]
+    async fn grep_context_not_duplicated() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/file.txt", "a\nhit1\nb\nhit2\nc\n\n\n\nhit3")
+            .await
+            .unwrap();
+
+        let result = tool
+            .execute("c7", json!({"pattern": "hit", "context": 1}), None)
+            .await
+            .unwrap();
+
+        assert!(!result.is_error);
+        assert_eq!(
+            result.content,
+            "file.txt:1-a\nfile.txt:2:hit1\nfile.txt:3-b\nfile.txt:4:hit2\nfile.txt:5-c\n--\nfile.txt:8-\nfile.txt:9:hit3\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn grep_group_by_file() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/a.rs", "use x;\nfn a() { x(); }\n// x\n")
+            .await
+            .unwrap();
+        fs.write("/project/b.rs", "fn b() {}\nfn c() { x(); }\n")
+            .await
+            .unwrap();
+
+        let result = tool
+            .execute("c1", json!({"pattern": "x(", "group_by_file": true}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "a.rs\n2:fn a() { x(); }\n\nb.rs\n2:fn c() { x(); }\n");
+        assert_eq!(result.metadata["matches"], 2);
+
+        let args = json!({"pattern": "x(", "group_by_file": true, "context": 1, "glob": "b.rs"});
+        let result = tool.execute("c2", args, None).await.unwrap();
+        assert_eq!(result.content, "b.rs\n1-fn b() {}\n2:fn c() { x(); }\n");
+
+        let args = json!({"pattern": "x", "group_by_file": true, "unique": true});
+        let result = tool.execute("c3", args, None).await.unwrap();
+        assert_eq!(
+            ToolErrorKind::of(&result),
+            Some(ToolErrorKind::InvalidArguments)
+        );
+    }
+
+    #[tokio::test]
+    async fn grep_max_matches_per_file() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/a_noisy.js", &"needle\n".repeat(50))
+            .await
+            .unwrap();
+        fs.write("/project/b_quiet.rs", "needle").await.unwrap();
+
+        let result = tool
+            .execute(
+                "c8",
+                json!({"pattern": "needle", "max_matches": 10, "max_matches_per_file": 3}),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error);
+        assert_eq!(result.metadata["matches"], 4);
+        assert_eq!(result.metadata["files_capped"], 1);
+        assert!(result.content.contains("b_quiet.rs:1:needle"));
+        assert!(result.content.contains("Per-file match limit"));
+    }
+
+    #[tokio::test]
+    async fn grep_highlight() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/a.rs", "let total = total + Total;\nnone")
+            .await
+            .unwrap();
+
+        let result = tool
+            .execute(
+                "c11",
+                json!({"pattern": "total", "highlight": true, "context": 1}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(result
+            .content
+            .contains("a.rs:1:let >>>total<<< = >>>total<<< + Total;"));
+        assert!(result.content.contains("a.rs:2-none"));
+        let spans = &result.metadata["match_spans"][0];
+        assert_eq!(spans["line"], 1);
+        assert_eq!(spans["spans"], json!([{"start": 4, "end": 9}, {"start": 12, "end": 17}]));
+
+        let result = tool
+            .execute(
+                "c12",
+                json!({"pattern": "TOTAL", "ignore_case": true, "highlight_markers": ["[", "]"]}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(result.content.contains("let [total] = [total] + [Total];"));
+    }
+
+    #[test]
+    fn match_spans_unicode_ignore_case() {
+        assert_eq!(match_spans("Ärger ärger", "ärger", true), vec![(0, 6), (7, 13)]);
+        assert_eq!(match_spans("aaaa", "aa", false), vec![(0, 2), (2, 4)]);
+    }
+
+    #[tokio::test]
+    async fn grep_with_index() {
+        use crate::middleware::wrap_with_middleware;
+        use crate::tools::write::WriteTool;
+        use soul_core::tool::ToolRegistry;
+
+        let (fs, _) = setup().await;
+        fs.write("/project/a.rs", "fn parse() {}").await.unwrap();
+        fs.write("/project/b.rs", "fn render() {}").await.unwrap();
+
+        let index = Arc::new(SearchIndex::new("/project"));
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(
+            GrepTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project").with_index(index.clone()),
+        ));
+        registry.register(Box::new(WriteTool::new(
+            fs.clone() as Arc<dyn VirtualFs>,
+            "/project",
+        )));
+        let tools = wrap_with_middleware(registry, vec![index.clone()]);
+        let grep = tools.get("grep").unwrap();
+
+        let result = grep
+            .execute("c1", json!({"pattern": "parse"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["index_pruned"], 0);
+        assert_eq!(index.len(), 2);
+
+        let result = grep
+            .execute("c2", json!({"pattern": "parse"}), None)
+            .await
+            .unwrap();
+        assert!(result.content.contains("a.rs:1:fn parse() {}"));
+        assert_eq!(result.metadata["index_pruned"], 1);
+
+        // Writes through the registry invalidate the touched file
+        tools
+            .get("write")
+            .unwrap()
+            .execute("c3", json!({"path": "b.rs", "content": "fn parse_b() {}"}), None)
+            .await
+            .unwrap();
+        assert_eq!(index.len(), 1);
+        let result = grep
+            .execute("c4", json!({"pattern": "parse"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["matches"], 2);
+    }
+
+    #[tokio::test]
+    async fn grep_reports_progress() {
+        let fs = Arc::new(MemoryFs::new());
+        for i in 0..5 {
+            fs.write(&format!("/project/f{}.txt", i), "needle").await.unwrap();
+        }
+        let tool = GrepTool::new(fs as Arc<dyn VirtualFs>, "/project").with_progress_interval(2);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let result = tool
+            .execute("c1", json!({"pattern": "needle"}), Some(tx))
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["matches"], 5);
+
+        let mut updates = Vec::new();
+        while let Ok(update) = rx.try_recv() {
+            updates.push(update);
+        }
+        assert_eq!(
+            updates,
+            vec![
+                "scanned 2/5 files, 1 matches so far",
+                "scanned 4/5 files, 3 matches so far"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn grep_search_names() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/src/auth/login.rs", "fn login() {}").await.unwrap();
+        fs.write("/project/src/main.rs", "mod auth;").await.unwrap();
+        fs.write("/project/README.md", "nothing here").await.unwrap();
+
+        let result = tool
+            .execute("c10", json!({"pattern": "auth", "search_names": true}), None)
+            .await
+            .unwrap();
+
+        assert!(!result.is_error);
+        assert!(result.content.contains("src/auth/login.rs (filename match)"));
+        assert!(result.content.contains("src/main.rs:1:mod auth;"));
+        assert!(!result.content.contains("README"));
+        assert_eq!(result.metadata["name_matches"], 1);
+        assert_eq!(result.metadata["matches"], 2);
+    }
+
+    #[tokio::test]
+    async fn grep_count_replacements() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/a.rs", "foo(foo)\nbar\nfoo").await.unwrap();
+        fs.write("/project/b.rs", "Foo").await.unwrap();
+        fs.write("/project/c.rs", "nothing").await.unwrap();
+
+        let result = tool
+            .execute(
+                "c9",
+                json!({"pattern": "foo", "count_replacements": true, "ignore_case": true}),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error);
+        assert!(result.content.starts_with("4 occurrence(s) of 'foo' in 2 file(s)"));
+        assert!(result.content.contains("a.rs: 3\nb.rs: 1"));
+        assert!(!result.content.contains("bar"));
+        assert_eq!(result.metadata["occurrences"], 4);
+        assert_eq!(result.metadata["files"][0]["path"], "a.rs");
+        assert_eq!(result.metadata["files"][0]["count"], 3);
+    }
+
+    #[tokio::test]
+    async fn grep_unique_lines() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/a.py", "import os\nimport json\n\nimport os\n")
+            .await
+            .unwrap();
+        fs.write("/project/b.py", "    import os\nimport sys\n")
+            .await
+            .unwrap();
+
+        let result = tool
+            .execute("c1", json!({"pattern": "import", "unique": true}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(
+            result.content,
+            "3 distinct line(s) in 5 match(es) of 'import'\n\
+             3  import os  [2 files]\n\
+             1  import json\n\
+             1  import sys"
+        );
+        assert_eq!(result.metadata["unique_lines"], 3);
+        assert_eq!(
+            result.metadata["lines"][0],
+            json!({"line": "import os", "count": 3, "files": 2})
+        );
+
+        let result = tool
+            .execute(
+                "c2",
+                json!({"pattern": "import", "unique": true, "max_matches": 1}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(result
+            .content
+            .ends_with("[Showing the 1 most frequent of 3 distinct lines]"));
+
+        let result = tool
+            .execute(
+                "c3",
+                json!({"pattern": "import", "unique": true, "context": 2}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            ToolErrorKind::of(&result),
+            Some(ToolErrorKind::InvalidArguments)
+        );
+    }
+
+    #[cfg(all(feature = "native", unix))]
+    #[tokio::test]
+    async fn grep_scan_statistics() {
+        use soul_core::vfs::NativeFs;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap().to_string();
+        std::fs::write(dir.path().join("a.rs"), "let needle = 1;\n").unwrap();
+        std::fs::write(dir.path().join("b.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(dir.path().join("bundle.js"), "x".repeat(500)).unwrap();
+        std::fs::write(dir.path().join("logo.png"), [0x89, b'P', b'N', b'G', 0xff]).unwrap();
+        let tool = GrepTool::new(Arc::new(NativeFs::new(&root)), root.clone())
+            .with_max_file_bytes(100);
+
+        let result = tool
+            .execute("c1", json!({"pattern": "needle"}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        let scan = &result.metadata["scan"];
+        assert_eq!(scan["files_scanned"], 2);
+        assert_eq!(scan["bytes_scanned"], 29);
+        assert_eq!(
+            scan["skipped"],
+            json!({"binary": 1, "too_large": 1, "unreadable": 0, "timed_out": 0})
+        );
+        assert!(scan["elapsed_ms"].is_u64());
+
+        // Every mode reports what it read, matches or not
+        let result = tool
+            .execute("c2", json!({"pattern": "nothing", "count_replacements": true}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["scan"]["files_scanned"], 2);
+        let result = tool
+            .execute("c3", json!({"pattern": ""}), None)
+            .await
+            .unwrap();
+        assert!(result.metadata.get("scan").is_none());
+    }
+
+    #[tokio::test]
+    async fn grep_scan_budget() {
+        let (fs, _) = setup().await;
+        fs.write("/project/small.rs", "let needle = 1;\n").await.unwrap();
+        let minified = format!("{}needle\n", "x".repeat(BUDGET_CHECK_BYTES));
+        fs.write("/project/bundle.js", &minified.repeat(3)).await.unwrap();
+        let tool = GrepTool::new(fs as Arc<dyn VirtualFs>, "/project")
+            .with_scan_budget(Duration::ZERO);
+
+        for args in [
+            json!({"pattern": "needle"}),
+            json!({"pattern": "needle", "unique": true}),
+            json!({"pattern": "needle", "count_replacements": true}),
+            json!({"pattern": "needle", "output_format": "json"}),
+        ] {
+            let result = tool.execute("c1", args, None).await.unwrap();
+            assert!(!result.content.contains("bundle.js"), "{}", result.content);
+            assert_eq!(result.metadata["matches"].as_u64().unwrap_or(1), 1);
+            let scan = &result.metadata["scan"];
+            assert_eq!(scan["skipped"]["timed_out"], 1);
+            assert_eq!(scan["timed_out_files"], json!(["bundle.js"]));
+        }
+    }
+
+    #[tokio::test]
+    async fn grep_binary_offsets_and_hex_dump() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/img.png", "\u{7f}PNG\r\n\u{1a}\n\0\0\0\rIHDR\0\0\u{1}\0")
+            .await
+            .unwrap();
+        fs.write("/project/notes.txt", "mentions IHDR twice: ihdr")
+            .await
+            .unwrap();
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({"pattern": "49 48 44 52", "binary": true, "glob": "*.png", "context": 2}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(
+            result.content,
+            "img.png @ 0xc (12)\n\
+             00000000: 7f50 4e47 0d0a 1a0a 0000 000d 4948 4452  .PNG........IHDR\n\
+             00000010: 0000 0100                                ...."
+        );
+        assert_eq!(result.metadata["offsets"][0]["offset"], 12);
+
+        // Escaped text, case-insensitive, across files
+        let result = tool
+            .execute(
+                "c2",
+                json!({"pattern": "\\x00\\rihdr", "binary": true, "ignore_case": true}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["matches"], 1);
+        let result = tool
+            .execute("c3", json!({"pattern": "IHDR", "binary": true, "ignore_case": true}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["matches"], 3);
+        assert_eq!(result.metadata["files_with_matches"], 2);
+
+        for args in [
+            json!({"pattern": "abc", "binary": true}),
+            json!({"pattern": "\\q", "binary": true}),
+            json!({"pattern": "ff", "binary": true, "count_replacements": true}),
+        ] {
+            let result = tool.execute("c4", args, None).await.unwrap();
+            assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::InvalidArguments));
+        }
+    }
+
     #[test]
     fn glob_matching() {
         assert!(matches_glob("file.rs", "*.rs"));
@@ -437,8 +2254,8 @@ mod tests {
 
     #[test]
     fn display_path_relative() {
-        assert_eq!(display_path("/project/src/main.rs", "/project"), "src/main.rs");
-        assert_eq!(display_path("/other/file.txt", "/project"), "/other/file.txt");
+        assert_eq!(paths::display("/project/src/main.rs", "/project"), "src/main.rs");
+        assert_eq!(paths::display("/other/file.txt", "/project"), "/other/file.txt");
     }
 
     #[tokio::test]
@@ -0,0 +1,453 @@
+//! Grep replace tool — find and replace across files in one pass.
+//!
+//! A grep followed by one edit per match leaves a window in which another
+//! tool (or the user) can change a file between the search and the edits,
+//! and costs a call per file. grep_replace searches and rewrites each file
+//! while holding its path lock, and returns one combined diff. Calls are dry
+//! runs unless `dry_run: false` is passed. Patterns are regular expressions
+//! (the [`regex`] crate's syntax, `^` and `$` matching at line ends) and
+//! replacements may refer to their groups as `$1` or `${name}`; with
+//! `literal: true` both are taken as plain text instead, as grep matches.
+//! When it writes, each entry of the `files` metadata also holds the file's
+//! previous content under `before`, for undoing or auditing the change.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use regex::{Captures, Regex, RegexBuilder};
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::diff::render_diff;
+use crate::error::{tool_error, ToolErrorKind};
+use crate::file_modes::{current_mode, restore_mode, FileModes};
+use crate::locks::PathLocks;
+use crate::truncate::MAX_DIFF_BYTES;
+use crate::walk::WalkGuard;
+
+use super::grep::collect_files;
+use super::{impl_overrides, resolve_path, set_metadata, DisplayRoot, ToolOverrides};
+
+/// System-prompt guidance returned by [`GrepReplaceTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    For the same change in many places, such as a rename, run grep_replace as a \
+    dry run, check the diff, then repeat the call with dry_run: false.";
+
+pub struct GrepReplaceTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    locks: Arc<PathLocks>,
+    modes: Option<Arc<dyn FileModes>>,
+    max_diff_bytes: usize,
+    overrides: ToolOverrides,
+}
+
+impl GrepReplaceTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            locks: Arc::new(PathLocks::new()),
+            modes: None,
+            max_diff_bytes: MAX_DIFF_BYTES,
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+    /// Share `locks` with other tools, so that their changes to the same
+    /// file wait for each other (default: a set of this tool's own).
+    pub fn with_locks(mut self, locks: Arc<PathLocks>) -> Self {
+        self.locks = locks;
+        self
+    }
+
+    /// Put back the permissions of files this tool rewrites (default: left
+    /// to the filesystem).
+    pub fn with_file_modes(mut self, modes: Arc<dyn FileModes>) -> Self {
+        self.modes = Some(modes);
+        self
+    }
+
+    /// Cap the combined diff in the output at about `bytes`, listing the
+    /// files beyond it without their diffs (default: [`MAX_DIFF_BYTES`]).
+    pub fn with_max_diff_bytes(mut self, bytes: usize) -> Self {
+        self.max_diff_bytes = bytes;
+        self
+    }
+}
+
+impl_overrides!(GrepReplaceTool);
+
+/// Compile `pattern`, escaped first when `literal`.
+fn build_regex(pattern: &str, literal: bool, ignore_case: bool) -> Result<Regex, regex::Error> {
+    let pattern = match literal {
+        true => regex::escape(pattern),
+        false => pattern.to_string(),
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(ignore_case)
+        .multi_line(true)
+        .build()
+}
+
+/// `content` with every match of `regex` replaced, and how many there were.
+/// Unless `literal`, `$1` and `${name}` in `replacement` expand to groups.
+fn replace_all(content: &str, regex: &Regex, replacement: &str, literal: bool) -> (String, usize) {
+    let mut count = 0;
+    let replaced = regex.replace_all(content, |captures: &Captures| {
+        count += 1;
+        let mut expanded = String::new();
+        match literal {
+            true => expanded.push_str(replacement),
+            false => captures.expand(replacement, &mut expanded),
+        }
+        expanded
+    });
+    (replaced.into_owned(), count)
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for GrepReplaceTool {
+    fn name(&self) -> &str {
+        self.overrides.name("grep_replace")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "grep_replace".into(),
+            description: "Replace every match of a regex in the files under a directory (or in one file), searching and rewriting each file in one step, and show the combined diff. The replacement can use groups as $1 or ${name}; pass literal to match plain text. Dry run by default: pass dry_run: false to write the changes.".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "pattern": {
+                        "type": "string",
+                        "description": "Regular expression to replace; ^ and $ match at line starts and ends"
+                    },
+                    "replacement": {
+                        "type": "string",
+                        "description": "Text to put in its place (may be empty); $1 or ${name} insert a group, $$ a dollar sign"
+                    },
+                    "literal": {
+                        "type": "boolean",
+                        "description": "Treat pattern and replacement as plain text (default: false)"
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "File or directory to search (defaults to working directory)"
+                    },
+                    "glob": {
+                        "type": "string",
+                        "description": "Only files whose names match, e.g. '*.rs'"
+                    },
+                    "ignore_case": {
+                        "type": "boolean",
+                        "description": "Match case-insensitively (default: false)"
+                    },
+                    "max_depth": {
+                        "type": "integer",
+                        "description": "Directory levels to descend, 1 being the search directory itself (default: unlimited)"
+                    },
+                    "dry_run": {
+                        "type": "boolean",
+                        "description": "Show the diff without writing it (default: true)"
+                    },
+                    "relative_to": DisplayRoot::schema()
+                },
+                "required": ["pattern", "replacement"]
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let pattern = arguments
+            .get("pattern")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        if pattern.is_empty() {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Missing required parameter: pattern",
+            ));
+        }
+        let Some(replacement) = arguments.get("replacement").and_then(|v| v.as_str()) else {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Missing required parameter: replacement",
+            ));
+        };
+        let ignore_case = arguments
+            .get("ignore_case")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let literal = arguments
+            .get("literal")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let regex = match build_regex(pattern, literal, ignore_case) {
+            Ok(regex) => regex,
+            Err(e) => {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!("Invalid regex pattern: {} (pass literal: true to match plain text)", e),
+                ))
+            }
+        };
+        let dry_run = arguments
+            .get("dry_run")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        let glob = arguments.get("glob").and_then(|v| v.as_str());
+        let max_depth = arguments
+            .get("max_depth")
+            .and_then(|v| v.as_u64())
+            .map(|v| (v as usize).max(1));
+        let search_path = arguments
+            .get("path")
+            .and_then(|v| v.as_str())
+            .map(|p| resolve_path(&self.cwd, p))
+            .unwrap_or_else(|| self.cwd.clone());
+        let display = DisplayRoot::from_arguments(&self.cwd, &arguments);
+
+        let guard = WalkGuard::new();
+        let mut files = Vec::new();
+        match self.fs.metadata(&search_path).await {
+            Ok(meta) if meta.is_file => files.push(search_path.clone()),
+            Ok(_) => {
+                guard.enter(&search_path, 0);
+                let walk = collect_files(
+                    self.fs.as_ref(),
+                    &search_path,
+                    &mut files,
+                    glob,
+                    max_depth,
+                    &guard,
+                    0,
+                );
+                if let Err(e) = walk.await {
+                    return Ok(tool_error(
+                        ToolErrorKind::from_soul_error(&e),
+                        format!("Failed to enumerate files in {}: {}", search_path, e),
+                    ));
+                }
+            }
+            Err(_) => {
+                return Ok(tool_error(
+                    ToolErrorKind::NotFound,
+                    format!("Path not found: {}", display.show(&search_path)),
+                ))
+            }
+        }
+
+        let mut diffs = String::new();
+        let mut budget = self.max_diff_bytes;
+        let mut omitted = 0;
+        let mut total = 0;
+        let mut changed = Vec::new();
+        let mut failed = Vec::new();
+        for file in &files {
+            let _lock = self.locks.lock(file).await;
+            let Ok(content) = self.fs.read_to_string(file).await else {
+                continue; // Binary or unreadable
+            };
+            let (replaced, count) = replace_all(&content, &regex, replacement, literal);
+            if count == 0 || replaced == content {
+                continue;
+            }
+            let shown = display.show(file);
+            if !dry_run {
+                let mode = current_mode(&self.modes, file).await;
+                if let Err(e) = self.fs.write(file, &replaced).await {
+                    failed.push(json!({"path": shown, "error": e.to_string()}));
+                    continue;
+                }
+                restore_mode(&self.modes, file, mode).await;
+            }
+            let diff = if budget == 0 {
+                omitted += 1;
+                json!(render_diff(&content, &replaced, &shown, 0).stats)
+            } else {
+                let rendered = render_diff(&content, &replaced, &shown, budget);
+                budget = budget.saturating_sub(rendered.text.len());
+                diffs.push_str("\n\n");
+                diffs.push_str(&rendered.text);
+                rendered.to_metadata()
+            };
+            total += count;
+            let mut entry = json!({"path": shown, "replacements": count, "diff": diff});
+            if !dry_run {
+                entry["before"] = json!(content);
+            }
+            changed.push(entry);
+        }
+
+        if changed.is_empty() && failed.is_empty() {
+            let mut output = ToolOutput::success(format!(
+                "No occurrences of '{}' in {}",
+                pattern,
+                display.show(&search_path)
+            ))
+            .with_metadata(json!({"replacements": 0, "files": [], "dry_run": dry_run}));
+            guard.annotate(&mut output);
+            return Ok(output);
+        }
+
+        let mut result = format!(
+            "{} {} occurrence(s) of '{}' in {} file(s){}",
+            if dry_run { "Would replace" } else { "Replaced" },
+            total,
+            pattern,
+            changed.len(),
+            if dry_run {
+                " (dry run; pass dry_run: false to write)"
+            } else {
+                ""
+            }
+        );
+        result.push_str(&diffs);
+        if omitted > 0 {
+            result.push_str(&format!("\n\n[Diffs of {} more file(s) omitted]", omitted));
+        }
+        let mut output = if failed.is_empty() {
+            ToolOutput::success(result)
+        } else {
+            result.push_str(&format!("\n\nFailed to write {} file(s)", failed.len()));
+            tool_error(ToolErrorKind::Io, result)
+        };
+        set_metadata(&mut output, "replacements", json!(total));
+        set_metadata(&mut output, "files", json!(changed));
+        set_metadata(&mut output, "dry_run", json!(dry_run));
+        if !failed.is_empty() {
+            set_metadata(&mut output, "failed", json!(failed));
+        }
+        guard.annotate(&mut output);
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    #[tokio::test]
+    async fn dry_run_then_replace() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/src/a.rs", "fn old_name() {}\nold_name();\n")
+            .await
+            .unwrap();
+        fs.write("/project/src/b.rs", "use crate::Old_Name;\n")
+            .await
+            .unwrap();
+        fs.write("/project/notes.md", "old_name\n").await.unwrap();
+        let tool = GrepReplaceTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+
+        let args = json!({"pattern": "old_name", "replacement": "new_name", "glob": "*.rs"});
+        let result = tool.execute("c1", args.clone(), None).await.unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result
+            .content
+            .starts_with("Would replace 2 occurrence(s) of 'old_name' in 1 file(s) (dry run"));
+        assert!(result.content.contains("-old_name();\n+fn new_name() {}\n+new_name();\n"));
+        assert_eq!(result.metadata["files"][0]["path"], "src/a.rs");
+        assert!(fs
+            .read_to_string("/project/src/a.rs")
+            .await
+            .unwrap()
+            .contains("old_name"));
+
+        let mut args = args;
+        args["dry_run"] = json!(false);
+        args["ignore_case"] = json!(true);
+        let result = tool.execute("c2", args, None).await.unwrap();
+        assert!(result.content.starts_with("Replaced 3 occurrence(s)"));
+        assert_eq!(
+            fs.read_to_string("/project/src/a.rs").await.unwrap(),
+            "fn new_name() {}\nnew_name();\n"
+        );
+        assert_eq!(
+            result.metadata["files"][0]["before"],
+            "fn old_name() {}\nold_name();\n"
+        );
+        assert_eq!(
+            fs.read_to_string("/project/src/b.rs").await.unwrap(),
+            "use crate::new_name;\n"
+        );
+        assert_eq!(
+            fs.read_to_string("/project/notes.md").await.unwrap(),
+            "old_name\n"
+        );
+
+        let result = tool
+            .execute(
+                "c3",
+                json!({"pattern": "old_name", "replacement": "", "path": "notes.md"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["replacements"], 1);
+        let result = tool
+            .execute(
+                "c4",
+                json!({"pattern": "x", "replacement": "y", "path": "nope"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+    }
+
+    #[tokio::test]
+    async fn regex_groups_and_literal() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write(
+            "/project/a.rs",
+            "let a = foo(1, 2);
+let b = foo(x, y);
+// cost: $5 (foo(a, b))
+",
+        )
+        .await
+        .unwrap();
+        let tool = GrepReplaceTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+
+        let args = json!({
+            "pattern": r"^let (\w+) = foo\((\w+), (\w+)\);$",
+            "replacement": "let $1 = bar($3, $2);",
+            "dry_run": false
+        });
+        let result = tool.execute("c1", args, None).await.unwrap();
+        assert_eq!(result.metadata["replacements"], 2, "{}", result.content);
+
+        let args = json!({
+            "pattern": "$5 (foo(a, b))",
+            "replacement": "$$6",
+            "literal": true,
+            "dry_run": false
+        });
+        let result = tool.execute("c2", args, None).await.unwrap();
+        assert_eq!(result.metadata["replacements"], 1, "{}", result.content);
+        assert_eq!(
+            fs.read_to_string("/project/a.rs").await.unwrap(),
+            "let a = bar(2, 1);\nlet b = bar(y, x);\n// cost: $$6\n"
+        );
+
+        let result = tool
+            .execute("c3", json!({"pattern": "foo(", "replacement": "x"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::InvalidArguments));
+        assert!(result.content.contains("literal: true"));
+    }
+}
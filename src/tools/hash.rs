@@ -0,0 +1,419 @@
+//! Hash tool — checksums of files through the VFS.
+//!
+//! `sha256sum` and friends aren't available under WASM, and shelling out
+//! for them elsewhere costs a bash call per check. `hash` computes sha256,
+//! md5 or xxh64 of one file or of every file matching a glob, flags files
+//! with identical contents, and compares against an expected checksum.
+//! The VFS reads text only, so binary files are reported as unreadable;
+//! text files hash exactly as `sha256sum` would hash them on disk.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::error::{tool_error, ToolErrorKind};
+use crate::ignore::glob_match;
+use crate::paths;
+use crate::walk::{walk_guarded, WalkGuard, WalkOptions};
+
+use super::{impl_overrides, resolve_path, DisplayRoot, ToolOverrides};
+
+/// Maximum files hashed for one pattern.
+const MAX_FILES: usize = 1000;
+
+/// Checksum algorithms offered by the tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Algorithm {
+    Sha256,
+    Md5,
+    Xxh64,
+}
+
+impl Algorithm {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sha256" => Some(Self::Sha256),
+            "md5" => Some(Self::Md5),
+            "xxh64" | "xxhash" => Some(Self::Xxh64),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Md5 => "md5",
+            Self::Xxh64 => "xxh64",
+        }
+    }
+
+    /// Lowercase hex digest of `data`.
+    fn digest(&self, data: &[u8]) -> String {
+        match self {
+            Self::Sha256 => format!("{:x}", Sha256::digest(data)),
+            Self::Md5 => format!("{:x}", md5::compute(data)),
+            Self::Xxh64 => format!("{:016x}", xxhash_rust::xxh64::xxh64(data, 0)),
+        }
+    }
+}
+
+/// System-prompt guidance returned by [`HashTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Compute checksums instead of running sha256sum or md5sum: to verify a file \
+    against a published checksum (pass expected), to check whether generated output \
+    changed, or to find duplicate files under a pattern.";
+
+pub struct HashTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    overrides: ToolOverrides,
+}
+
+impl HashTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+}
+
+/// Up to `max` files under `root` matching `pattern`, in walk order, and
+/// whether more were left out. Patterns with a `/` are matched against the
+/// path relative to `root`, others against the file name.
+pub(crate) async fn matching_files(
+    fs: Arc<dyn VirtualFs>,
+    root: &str,
+    pattern: &str,
+    max: usize,
+    guard: Arc<WalkGuard>,
+) -> (Vec<String>, bool) {
+    let files = walk_guarded(fs, root, WalkOptions::default(), guard);
+    futures::pin_mut!(files);
+    let mut matched = Vec::new();
+    while let Some(file) = files.next().await {
+        let path = file.to_string_lossy().into_owned();
+        let subject = if pattern.contains('/') {
+            paths::relative_to(&path, root).unwrap_or_default()
+        } else {
+            path.rsplit('/').next().unwrap_or_default().to_string()
+        };
+        if !glob_match(pattern, &subject) {
+            continue;
+        }
+        if matched.len() >= max {
+            return (matched, true);
+        }
+        matched.push(path);
+    }
+    (matched, false)
+}
+
+impl_overrides!(HashTool);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for HashTool {
+    fn name(&self) -> &str {
+        self.overrides.name("hash")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "hash".into(),
+            description: "Compute the sha256 (default), md5 or xxh64 checksum of a file, or of every file matching a glob pattern. Output is sha256sum-style ('<hash>  <path>'); files with identical contents are grouped, and expected checks the result against a known checksum.".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "File to hash, or the directory to search when pattern is given (defaults to working directory)"
+                    },
+                    "pattern": {
+                        "type": "string",
+                        "description": "Glob selecting files under path (e.g. '*.json', 'fixtures/**/*.txt')"
+                    },
+                    "algorithm": {
+                        "type": "string",
+                        "enum": ["sha256", "md5", "xxh64"],
+                        "description": "Checksum algorithm (default: sha256)"
+                    },
+                    "expected": {
+                        "type": "string",
+                        "description": "Known checksum (hex, case-insensitive) to compare every hashed file against"
+                    },
+                    "relative_to": DisplayRoot::schema()
+                }
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let algorithm = match arguments.get("algorithm").and_then(|v| v.as_str()) {
+            Some(name) => match Algorithm::from_name(name) {
+                Some(algorithm) => algorithm,
+                None => {
+                    return Ok(tool_error(
+                        ToolErrorKind::InvalidArguments,
+                        format!(
+                            "Unknown algorithm: {} (expected sha256, md5 or xxh64)",
+                            name
+                        ),
+                    ))
+                }
+            },
+            None => Algorithm::Sha256,
+        };
+        let expected = arguments
+            .get("expected")
+            .and_then(|v| v.as_str())
+            .map(|e| e.trim().to_ascii_lowercase())
+            .filter(|e| !e.is_empty());
+        let path = arguments.get("path").and_then(|v| v.as_str()).unwrap_or("");
+        let resolved = resolve_path(&self.cwd, path);
+        let pattern = arguments
+            .get("pattern")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(paths::glob_pattern);
+
+        let guard = Arc::new(WalkGuard::new());
+        let (files, capped) = match &pattern {
+            Some(pattern) => {
+                matching_files(self.fs.clone(), &resolved, pattern, MAX_FILES, guard.clone()).await
+            }
+            None if path.is_empty() => {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    "Missing required parameter: path (or pattern)",
+                ))
+            }
+            None => (vec![resolved.clone()], false),
+        };
+        if files.is_empty() {
+            return Ok(tool_error(
+                ToolErrorKind::NotFound,
+                format!(
+                    "No files matching '{}' in {}",
+                    pattern.unwrap_or_default(),
+                    paths::display(&resolved, &self.cwd)
+                ),
+            ));
+        }
+
+        let display = DisplayRoot::from_arguments(&self.cwd, &arguments);
+        let mut lines = Vec::new();
+        let mut hashed = Vec::new();
+        let mut unreadable = Vec::new();
+        let mut by_hash: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for file in &files {
+            let shown = display.show(file);
+            let content = match self.fs.read_to_string(file).await {
+                Ok(content) => content,
+                Err(e) if pattern.is_none() => {
+                    return Ok(tool_error(
+                        ToolErrorKind::from_soul_error(&e),
+                        format!("Failed to read {}: {}", shown, e),
+                    ))
+                }
+                Err(_) => {
+                    unreadable.push(shown);
+                    continue;
+                }
+            };
+            let hash = algorithm.digest(content.as_bytes());
+            let matches = expected.as_deref().map(|e| e == hash);
+            lines.push(match matches {
+                Some(true) => format!("{}  {}  (matches expected)", hash, shown),
+                _ => format!("{}  {}", hash, shown),
+            });
+            by_hash.entry(hash.clone()).or_default().push(shown.clone());
+            hashed.push(json!({
+                "path": shown,
+                "hash": hash,
+                "bytes": content.len(),
+                "matches_expected": matches,
+            }));
+        }
+
+        let duplicates: Vec<Vec<String>> = by_hash
+            .into_values()
+            .filter(|paths| paths.len() > 1)
+            .collect();
+        let mut content = lines.join("\n");
+        for group in &duplicates {
+            content.push_str(&format!("\n[Identical: {}]", group.join(", ")));
+        }
+        if let Some(expected) = &expected {
+            let matching = hashed
+                .iter()
+                .filter(|h| h["matches_expected"] == true)
+                .count();
+            content.push_str(&match (matching, hashed.len()) {
+                (0, 1) => format!("\n[Does NOT match expected {}]", expected),
+                (1, 1) => "\n[Matches expected]".to_string(),
+                (m, n) => format!("\n[{} of {} files match expected {}]", m, n, expected),
+            });
+        }
+        if !unreadable.is_empty() {
+            content.push_str(&format!(
+                "\n[Skipped {} binary or unreadable files: {}]",
+                unreadable.len(),
+                unreadable.join(", ")
+            ));
+        }
+        if capped {
+            content.push_str(&format!("\n[Stopped after {} files]", MAX_FILES));
+        }
+
+        let mut output = ToolOutput::success(content).with_metadata(json!({
+            "algorithm": algorithm.name(),
+            "files": hashed,
+            "duplicates": duplicates,
+            "unreadable": unreadable,
+            "limit_reached": capped,
+        }));
+        guard.annotate(&mut output);
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    async fn setup() -> (Arc<MemoryFs>, HashTool) {
+        let fs = Arc::new(MemoryFs::new());
+        let tool = HashTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        (fs, tool)
+    }
+
+    #[test]
+    fn known_digests() {
+        assert_eq!(
+            Algorithm::Sha256.digest(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            Algorithm::Md5.digest(b"abc"),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+        assert_eq!(Algorithm::Xxh64.digest(b""), "ef46db3751d8e999");
+    }
+
+    #[tokio::test]
+    async fn hash_file_and_verify() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/a.txt", "abc").await.unwrap();
+
+        let result = tool
+            .execute("c1", json!({"path": "a.txt", "algorithm": "md5"}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.content, "900150983cd24fb0d6963f7d28e17f72  a.txt");
+        assert_eq!(result.metadata["files"][0]["bytes"], 3);
+
+        let sha = "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD";
+        let result = tool
+            .execute("c2", json!({"path": "a.txt", "expected": sha}), None)
+            .await
+            .unwrap();
+        assert!(result.content.ends_with("[Matches expected]"));
+        assert_eq!(result.metadata["files"][0]["matches_expected"], true);
+
+        let result = tool
+            .execute("c3", json!({"path": "a.txt", "expected": "00"}), None)
+            .await
+            .unwrap();
+        assert!(result.content.contains("Does NOT match expected 00"));
+
+        let result = tool
+            .execute("c4", json!({"path": "missing.txt"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+        let result = tool
+            .execute("c5", json!({"path": "a.txt", "algorithm": "crc32"}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            ToolErrorKind::of(&result),
+            Some(ToolErrorKind::InvalidArguments)
+        );
+    }
+
+    #[tokio::test]
+    async fn hash_pattern_groups_duplicates() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/fixtures/a.json", "{}").await.unwrap();
+        fs.write("/project/fixtures/nested/b.json", "{}")
+            .await
+            .unwrap();
+        fs.write("/project/fixtures/c.json", "[]").await.unwrap();
+        fs.write("/project/fixtures/notes.md", "{}").await.unwrap();
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({"pattern": "*.json", "algorithm": "xxh64"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.metadata["files"].as_array().unwrap().len(), 3);
+        assert_eq!(
+            result.metadata["duplicates"],
+            json!([["fixtures/a.json", "fixtures/nested/b.json"]])
+        );
+        assert!(result
+            .content
+            .contains("[Identical: fixtures/a.json, fixtures/nested/b.json]"));
+
+        let result = tool
+            .execute(
+                "c2",
+                json!({"path": "fixtures", "pattern": "nested/**"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.metadata["files"][0]["path"],
+            "fixtures/nested/b.json"
+        );
+
+        let result = tool
+            .execute("c3", json!({"pattern": "*.bin"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+    }
+
+    #[tokio::test]
+    async fn tool_name_and_definition() {
+        let (_fs, tool) = setup().await;
+        assert_eq!(tool.name(), "hash");
+        assert_eq!(tool.definition().name, "hash");
+    }
+}
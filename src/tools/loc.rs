@@ -0,0 +1,378 @@
+//! Loc tool — per-language file and line counts for a directory tree.
+//!
+//! A lightweight tokei: walks the tree (skipping hidden directories and
+//! anything matched by `.gitignore`), classifies files by extension and
+//! splits their lines into code, comment and blank.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::error::{tool_error, ToolErrorKind};
+use crate::ignore::IgnoreRules;
+use crate::paths;
+use crate::walk::WalkGuard;
+
+use super::{impl_overrides, resolve_path, ToolOverrides};
+
+/// Maximum files read before the walk stops.
+const MAX_FILES: usize = 20_000;
+
+/// Language name, file extensions, and line-comment prefixes.
+const LANGUAGES: &[(&str, &[&str], &[&str])] = &[
+    ("Rust", &["rs"], &["//"]),
+    ("Python", &["py", "pyi"], &["#"]),
+    ("JavaScript", &["js", "jsx", "mjs", "cjs"], &["//"]),
+    ("TypeScript", &["ts", "tsx", "mts", "cts"], &["//"]),
+    ("Go", &["go"], &["//"]),
+    ("C", &["c", "h"], &["//"]),
+    ("C++", &["cc", "cpp", "cxx", "hpp", "hh", "hxx"], &["//"]),
+    ("C#", &["cs"], &["//"]),
+    ("Java", &["java"], &["//"]),
+    ("Kotlin", &["kt", "kts"], &["//"]),
+    ("Swift", &["swift"], &["//"]),
+    ("Ruby", &["rb"], &["#"]),
+    ("PHP", &["php"], &["//", "#"]),
+    ("Shell", &["sh", "bash", "zsh"], &["#"]),
+    ("Lua", &["lua"], &["--"]),
+    ("SQL", &["sql"], &["--"]),
+    ("HTML", &["html", "htm"], &[]),
+    ("CSS", &["css", "scss", "sass", "less"], &[]),
+    ("Markdown", &["md", "markdown"], &[]),
+    ("JSON", &["json"], &[]),
+    ("YAML", &["yml", "yaml"], &["#"]),
+    ("TOML", &["toml"], &["#"]),
+];
+
+/// Language name and comment prefixes for `path`, by extension.
+fn classify(path: &str) -> Option<(&'static str, &'static [&'static str])> {
+    let ext = path
+        .rsplit('/')
+        .next()?
+        .rsplit_once('.')?
+        .1
+        .to_ascii_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|(_, exts, _)| exts.contains(&ext.as_str()))
+        .map(|(name, _, comments)| (*name, *comments))
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Counts {
+    files: usize,
+    code: usize,
+    comments: usize,
+    blank: usize,
+}
+
+impl Counts {
+    fn lines(&self) -> usize {
+        self.code + self.comments + self.blank
+    }
+
+    fn add_file(&mut self, content: &str, comment_prefixes: &[&str]) {
+        self.files += 1;
+        for line in content.lines() {
+            let line = line.trim_start();
+            if line.is_empty() {
+                self.blank += 1;
+            } else if comment_prefixes.iter().any(|p| line.starts_with(p)) {
+                self.comments += 1;
+            } else {
+                self.code += 1;
+            }
+        }
+    }
+}
+
+/// Totals gathered by a walk.
+#[derive(Default)]
+struct Walk {
+    languages: BTreeMap<&'static str, Counts>,
+    files_seen: usize,
+    unrecognized: usize,
+    guard: WalkGuard,
+}
+
+/// Count the files under `dir`, `depth` levels below the walk's root.
+async fn walk(
+    fs: &dyn VirtualFs,
+    dir: &str,
+    depth: usize,
+    ignore: &IgnoreRules,
+    stats: &mut Walk,
+) {
+    let mut ignore = ignore.clone();
+    ignore.load(fs, dir).await;
+
+    let Ok(mut entries) = fs.read_dir(dir).await else {
+        return; // Skip unreadable dirs
+    };
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    for entry in entries {
+        if stats.files_seen >= MAX_FILES {
+            return;
+        }
+        if entry.name.starts_with('.') {
+            continue;
+        }
+        let path = paths::resolve(dir, &entry.name);
+        if ignore.is_ignored(&path, entry.is_dir) {
+            continue;
+        }
+        if entry.is_dir {
+            if stats.guard.enter(&path, depth + 1) {
+                Box::pin(walk(fs, &path, depth + 1, &ignore, stats)).await;
+            }
+        } else if entry.is_file {
+            stats.files_seen += 1;
+            let Some((language, comments)) = classify(&path) else {
+                stats.unrecognized += 1;
+                continue;
+            };
+            let Ok(content) = fs.read_to_string(&path).await else {
+                stats.unrecognized += 1; // Binary or unreadable
+                continue;
+            };
+            stats
+                .languages
+                .entry(language)
+                .or_default()
+                .add_file(&content, comments);
+        }
+    }
+}
+
+/// System-prompt guidance returned by [`LocTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Get the languages and size of an unfamiliar project before exploring it.";
+
+pub struct LocTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    overrides: ToolOverrides,
+}
+
+impl LocTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            overrides: ToolOverrides::default(),
+        }
+    }
+}
+
+impl_overrides!(LocTool);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for LocTool {
+    fn name(&self) -> &str {
+        self.overrides.name("loc")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "loc".into(),
+            description: "Count files and lines (code, comments, blank) per language under a directory, skipping hidden and .gitignore'd paths. Use first to get an overview of an unfamiliar project.".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "Directory to analyze (defaults to working directory)"
+                    }
+                }
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let path = arguments.get("path").and_then(|v| v.as_str()).unwrap_or("");
+        let resolved = resolve_path(&self.cwd, path);
+
+        let is_dir = self
+            .fs
+            .metadata(&resolved)
+            .await
+            .map(|m| m.is_dir)
+            .unwrap_or(false);
+        if !is_dir {
+            return Ok(tool_error(
+                ToolErrorKind::NotFound,
+                format!(
+                    "Directory not found: {}",
+                    paths::display(&resolved, &self.cwd)
+                ),
+            ));
+        }
+
+        let mut stats = Walk::default();
+        stats.guard.enter(&resolved, 0);
+        walk(self.fs.as_ref(), &resolved, 0, &IgnoreRules::new(), &mut stats).await;
+        let truncated = stats.files_seen >= MAX_FILES;
+
+        let mut languages: Vec<(&str, Counts)> = stats.languages.into_iter().collect();
+        languages.sort_by(|a, b| b.1.code.cmp(&a.1.code).then_with(|| a.0.cmp(b.0)));
+
+        let mut total = Counts::default();
+        for (_, c) in &languages {
+            total.files += c.files;
+            total.code += c.code;
+            total.comments += c.comments;
+            total.blank += c.blank;
+        }
+
+        let row = |name: &str, c: &Counts| {
+            format!(
+                "{:<12} {:>7} {:>9} {:>9} {:>9} {:>9}",
+                name,
+                c.files,
+                c.lines(),
+                c.code,
+                c.comments,
+                c.blank
+            )
+        };
+        let mut output = format!(
+            "{:<12} {:>7} {:>9} {:>9} {:>9} {:>9}\n",
+            "Language", "Files", "Lines", "Code", "Comments", "Blank"
+        );
+        for (name, counts) in &languages {
+            output.push_str(&row(name, counts));
+            output.push('\n');
+        }
+        output.push_str(&row("Total", &total));
+        if stats.unrecognized > 0 {
+            output.push_str(&format!(
+                "\n[{} other file(s) not counted]",
+                stats.unrecognized
+            ));
+        }
+        if truncated {
+            output.push_str(&format!("\n[Stopped after {} files]", MAX_FILES));
+        }
+
+        let language_meta: Vec<_> = languages
+            .iter()
+            .map(|(name, c)| {
+                json!({
+                    "language": name,
+                    "files": c.files,
+                    "lines": c.lines(),
+                    "code": c.code,
+                    "comments": c.comments,
+                    "blank": c.blank,
+                })
+            })
+            .collect();
+        let mut output = ToolOutput::success(output).with_metadata(json!({
+            "languages": language_meta,
+            "total_files": total.files,
+            "total_lines": total.lines(),
+            "unrecognized_files": stats.unrecognized,
+            "truncated": truncated,
+        }));
+        stats.guard.annotate(&mut output);
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    async fn setup() -> (Arc<MemoryFs>, LocTool) {
+        let fs = Arc::new(MemoryFs::new());
+        let tool = LocTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        (fs, tool)
+    }
+
+    #[test]
+    fn counts_lines_by_kind() {
+        let mut counts = Counts::default();
+        counts.add_file("// header\nfn main() {\n\n    // inline\n}\n", &["//"]);
+        assert_eq!(
+            counts,
+            Counts {
+                files: 1,
+                code: 2,
+                comments: 2,
+                blank: 1
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn loc_summarizes_languages() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/.gitignore", "target/\n*.gen.py\n")
+            .await
+            .unwrap();
+        fs.write("/project/src/main.rs", "fn main() {}\n// done\n")
+            .await
+            .unwrap();
+        fs.write("/project/src/lib.rs", "pub fn a() {}\n\npub fn b() {}\n")
+            .await
+            .unwrap();
+        fs.write("/project/tool.py", "# script\nprint(1)\n")
+            .await
+            .unwrap();
+        fs.write("/project/schema.gen.py", "x = 1\n").await.unwrap();
+        fs.write("/project/target/debug/out.rs", "fn x() {}\n")
+            .await
+            .unwrap();
+        fs.write("/project/.hidden/a.rs", "fn h() {}\n")
+            .await
+            .unwrap();
+        fs.write("/project/LICENSE", "MIT\n").await.unwrap();
+
+        let result = tool.execute("c1", json!({}), None).await.unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        let lines: Vec<&str> = result.content.lines().collect();
+        assert!(lines[1].starts_with("Rust"));
+        assert!(lines[2].starts_with("Python"));
+        assert!(result.content.contains("[1 other file(s) not counted]"));
+
+        let rust = &result.metadata["languages"][0];
+        assert_eq!(rust["files"], 2);
+        assert_eq!(rust["code"], 3);
+        assert_eq!(rust["comments"], 1);
+        assert_eq!(rust["blank"], 1);
+        assert_eq!(result.metadata["total_files"], 3);
+    }
+
+    #[tokio::test]
+    async fn loc_missing_directory() {
+        let (_fs, tool) = setup().await;
+        let result = tool
+            .execute("c2", json!({"path": "nope"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+    }
+
+    #[tokio::test]
+    async fn tool_name_and_definition() {
+        let (_fs, tool) = setup().await;
+        assert_eq!(tool.name(), "loc");
+        assert_eq!(tool.definition().name, "loc");
+    }
+}
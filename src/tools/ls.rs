@@ -1,4 +1,8 @@
 //! Ls tool — list directory contents with metadata.
+//!
+//! With `recursive`, lists the whole subtree instead, showing but not
+//! descending into generated directories ([`DEFAULT_SKIPPED_DIRS`]) so the
+//! listing is useful even where no `.gitignore` exists to exclude them.
 
 use std::sync::Arc;
 
@@ -9,16 +13,32 @@ use tokio::sync::mpsc;
 use soul_core::error::SoulResult;
 use soul_core::tool::{Tool, ToolOutput};
 use soul_core::types::ToolDefinition;
-use soul_core::vfs::VirtualFs;
+use soul_core::vfs::{VfsDirEntry, VirtualFs};
 
 /// Maximum entries returned.
 const MAX_ENTRIES: usize = 500;
 
-use super::resolve_path;
+/// Directories a recursive listing shows but doesn't descend into: build
+/// output, dependencies and version control data.
+pub const DEFAULT_SKIPPED_DIRS: &[&str] =
+    &["target", "node_modules", ".git", "dist", "build", "__pycache__"];
+
+use crate::error::{tool_error, ToolErrorKind};
+use crate::truncate::{truncate_head, MAX_BYTES};
+use crate::walk::{WalkGuard, MAX_DEPTH};
+
+use super::{impl_overrides, resolve_path, DisplayRoot, ToolOverrides};
+
+/// System-prompt guidance returned by [`LsTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Look inside one directory. For an overview of a whole project use loc, or find \
+    with a pattern, rather than listing directories one by one.";
 
 pub struct LsTool {
     fs: Arc<dyn VirtualFs>,
     cwd: String,
+    skipped_dirs: Vec<String>,
+    overrides: ToolOverrides,
 }
 
 impl LsTool {
@@ -26,21 +46,139 @@ impl LsTool {
         Self {
             fs,
             cwd: cwd.into(),
+            skipped_dirs: DEFAULT_SKIPPED_DIRS.iter().map(|d| d.to_string()).collect(),
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+    /// Names of directories recursive listings don't descend into (default:
+    /// [`DEFAULT_SKIPPED_DIRS`]). Calls can replace the list with `skip`.
+    pub fn with_skipped_dirs<I, S>(mut self, dirs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.skipped_dirs = dirs.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+impl_overrides!(LsTool);
+
+/// Size summary of a directory subtree.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct DirSummary {
+    /// Immediate children (files and directories).
+    entries: usize,
+    /// Files anywhere below the directory.
+    files: usize,
+    /// Cumulative size of those files in bytes.
+    bytes: u64,
+}
+
+/// Recursively summarize a directory `depth` levels below the one the walk
+/// started at, entering subdirectories through `guard`.
+async fn summarize_dir(
+    fs: &dyn VirtualFs,
+    dir: &str,
+    guard: &WalkGuard,
+    depth: usize,
+) -> SoulResult<DirSummary> {
+    let entries = fs.read_dir(dir).await?;
+    let mut summary = DirSummary {
+        entries: entries.len(),
+        ..DirSummary::default()
+    };
+    for entry in entries {
+        let path = format!("{}/{}", dir.trim_end_matches('/'), entry.name);
+        if entry.is_dir {
+            if !guard.enter(&path, depth + 1) {
+                continue;
+            }
+            let child = Box::pin(summarize_dir(fs, &path, guard, depth + 1)).await?;
+            summary.files += child.files;
+            summary.bytes += child.bytes;
+        } else if entry.is_file {
+            summary.files += 1;
+            summary.bytes += fs.metadata(&path).await.map(|m| m.size).unwrap_or(0);
         }
     }
+    Ok(summary)
+}
+
+/// Sort entries alphabetically (case-insensitive).
+fn sort_entries(entries: &mut [VfsDirEntry]) {
+    entries.sort_by(|a, b| {
+        a.name
+            .to_lowercase()
+            .cmp(&b.name.to_lowercase())
+    });
+}
+
+/// A recursive listing in progress: entries are named by their path below
+/// the listed directory, depth-first, each directory sorted.
+struct Tree<'a> {
+    fs: &'a dyn VirtualFs,
+    skip: &'a [String],
+    /// Levels to list; the listed directory's children are level 1.
+    max_depth: usize,
+    guard: WalkGuard,
+    entries: Vec<VfsDirEntry>,
+    /// Directories shown but not descended into, as in `skip`.
+    skipped: Vec<String>,
+}
+
+impl Tree<'_> {
+    /// Add the entries of `dir`, at `depth`, named `prefix` + their name.
+    async fn collect(&mut self, dir: &str, prefix: &str, depth: usize) {
+        let Ok(mut entries) = self.fs.read_dir(dir).await else {
+            return;
+        };
+        sort_entries(&mut entries);
+        for entry in entries {
+            let name = format!("{}{}", prefix, entry.name);
+            let path = format!("{}/{}", dir.trim_end_matches('/'), entry.name);
+            let descend = entry.is_dir && depth < self.max_depth;
+            let skipped = descend && self.skip.contains(&entry.name);
+            self.entries.push(VfsDirEntry {
+                name: name.clone(),
+                ..entry
+            });
+            if skipped {
+                self.skipped.push(name);
+            } else if descend && self.guard.enter(&path, depth + 1) {
+                Box::pin(self.collect(&path, &format!("{}/", name), depth + 1)).await;
+            }
+        }
+    }
+}
+
+/// Human-readable byte count (B, KB, MB, GB).
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl Tool for LsTool {
     fn name(&self) -> &str {
-        "ls"
+        self.overrides.name("ls")
     }
 
     fn definition(&self) -> ToolDefinition {
-        ToolDefinition {
+        self.overrides.apply(ToolDefinition {
             name: "ls".into(),
-            description: "List the contents of a directory. Shows files and subdirectories with '/' suffix for directories.".into(),
+            description: "List the contents of a directory. Shows files and subdirectories with '/' suffix for directories. Set recursive to list the whole subtree (generated directories such as target and node_modules are shown but not entered), and sizes to see how many files and bytes each directory holds.".into(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -51,10 +189,28 @@ impl Tool for LsTool {
                     "limit": {
                         "type": "integer",
                         "description": "Maximum entries to return (default: 500)"
-                    }
+                    },
+                    "recursive": {
+                        "type": "boolean",
+                        "description": "List subdirectories' contents too, as paths below the directory (default: false)"
+                    },
+                    "depth": {
+                        "type": "integer",
+                        "description": "Levels to list when recursive, 1 being the directory's own entries (default: unlimited)"
+                    },
+                    "skip": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "Names of directories a recursive listing doesn't enter (default: target, node_modules, .git, dist, build, __pycache__; [] enters all)"
+                    },
+                    "sizes": {
+                        "type": "boolean",
+                        "description": "Annotate each directory with its immediate entry count, total file count and cumulative size (default: false)"
+                    },
+                    "relative_to": DisplayRoot::schema()
                 }
             }),
-        }
+        })
     }
 
     async fn execute(
@@ -73,6 +229,7 @@ impl Tool for LsTool {
         } else {
             resolve_path(&self.cwd, path)
         };
+        let display = DisplayRoot::from_arguments(&self.cwd, &arguments);
 
         let limit = arguments
             .get("limit")
@@ -83,49 +240,155 @@ impl Tool for LsTool {
         // Check if path exists
         let exists = self.fs.exists(&resolved).await?;
         if !exists {
-            return Ok(ToolOutput::error(format!(
-                "Directory not found: {}",
-                if path.is_empty() { &self.cwd } else { path }
-            )));
+            return Ok(tool_error(
+                ToolErrorKind::NotFound,
+                format!("Directory not found: {}", display.show(&resolved)),
+            ));
         }
 
+        let recursive = arguments
+            .get("recursive")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
         let entries = match self.fs.read_dir(&resolved).await {
-            Ok(e) => e,
+            Ok(e) if !recursive => e,
+            Ok(_) => Vec::new(),
             Err(e) => {
-                return Ok(ToolOutput::error(format!(
-                    "Failed to read directory {}: {}",
-                    path, e
-                )));
+                return Ok(tool_error(
+                    ToolErrorKind::from_soul_error(&e),
+                    format!(
+                        "Failed to read directory {}: {}",
+                        display.show(&resolved),
+                        e
+                    ),
+                ));
             }
         };
 
-        // Sort alphabetically (case-insensitive)
-        let mut sorted: Vec<_> = entries.into_iter().collect();
-        sorted.sort_by(|a, b| {
-            a.name
-                .to_lowercase()
-                .cmp(&b.name.to_lowercase())
-        });
+        let mut sorted = entries;
+        let mut skipped = Vec::new();
+        let mut guard = None;
+        if recursive {
+            let skip: Vec<String> = match arguments.get("skip").and_then(|v| v.as_array()) {
+                Some(names) => names
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect(),
+                None => self.skipped_dirs.clone(),
+            };
+            let max_depth = arguments
+                .get("depth")
+                .and_then(|v| v.as_u64())
+                .map_or(MAX_DEPTH, |d| (d as usize).max(1));
+            let mut tree = Tree {
+                fs: self.fs.as_ref(),
+                skip: &skip,
+                max_depth,
+                guard: WalkGuard::new(),
+                entries: Vec::new(),
+                skipped: Vec::new(),
+            };
+            tree.guard.enter(&resolved, 0);
+            tree.collect(&resolved, "", 1).await;
+            sorted = tree.entries;
+            skipped = tree.skipped;
+            guard = Some(tree.guard);
+        } else {
+            sort_entries(&mut sorted);
+        }
+
+        let sizes = arguments
+            .get("sizes")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        // Bare names, or paths under the requested root
+        let label = |name: &str| {
+            if display.is_explicit() {
+                display.show(&format!("{}/{}", resolved.trim_end_matches('/'), name))
+            } else {
+                name.to_string()
+            }
+        };
 
         let total = sorted.len();
-        let displayed: Vec<String> = sorted
+        // Truncation is measured on the plain listing, before size annotations
+        let listing = sorted
             .iter()
-            .take(limit)
             .map(|e| {
                 if e.is_dir {
-                    format!("{}/", e.name)
+                    format!("{}/", label(&e.name))
                 } else {
-                    e.name.clone()
+                    label(&e.name)
                 }
             })
-            .collect();
+            .collect::<Vec<_>>()
+            .join("\n");
+        let truncated = truncate_head(&listing, limit, MAX_BYTES);
+
+        let mut displayed = Vec::new();
+        let mut dir_sizes = Vec::new();
+        let mut incomplete_sizes = 0;
+        for e in sorted.iter().take(truncated.output_lines) {
+            if !e.is_dir {
+                displayed.push(label(&e.name));
+                continue;
+            }
+            if !sizes {
+                displayed.push(format!("{}/", label(&e.name)));
+                continue;
+            }
+            let dir_path = format!("{}/{}", resolved.trim_end_matches('/'), e.name);
+            let size_guard = WalkGuard::new();
+            size_guard.enter(&dir_path, 0);
+            let summary = summarize_dir(self.fs.as_ref(), &dir_path, &size_guard, 0)
+                .await
+                .unwrap_or_default();
+            let complete = !size_guard.is_cut_short();
+            if !complete {
+                incomplete_sizes += 1;
+            }
+            displayed.push(format!(
+                "{}/ ({} entries, {} files, {}{})",
+                label(&e.name),
+                summary.entries,
+                summary.files,
+                format_size(summary.bytes),
+                if complete { "" } else { ", incomplete" }
+            ));
+            dir_sizes.push(json!({
+                "name": e.name,
+                "entries": summary.entries,
+                "files": summary.files,
+                "bytes": summary.bytes,
+                "complete": complete,
+            }));
+        }
 
         let mut output = displayed.join("\n");
 
-        if total > limit {
+        if displayed.len() < total {
             output.push_str(&format!(
                 "\n[Showing {} of {} entries]",
-                limit, total
+                displayed.len(),
+                total
+            ));
+        }
+
+        if incomplete_sizes > 0 {
+            output.push_str(&format!(
+                "\n[Sizes of {} {} marked incomplete leave out subdirectories below depth {} or already counted (a symlink loop)]",
+                incomplete_sizes,
+                if incomplete_sizes == 1 { "directory" } else { "directories" },
+                MAX_DEPTH
+            ));
+        }
+
+        if !skipped.is_empty() {
+            let dirs: Vec<String> = skipped.iter().map(|d| format!("{}/", label(d))).collect();
+            output.push_str(&format!(
+                "\n[Not entered: {} (pass skip: [] to list them)]",
+                dirs.join(", ")
             ));
         }
 
@@ -133,10 +396,23 @@ impl Tool for LsTool {
             output = "(empty directory)".into();
         }
 
-        Ok(ToolOutput::success(output).with_metadata(json!({
+        let mut metadata = json!({
             "total_entries": total,
             "displayed": displayed.len(),
-        })))
+            "truncation": truncated.to_metadata(),
+        });
+        if sizes {
+            metadata["directories"] = json!(dir_sizes);
+        }
+        if recursive {
+            metadata["skipped"] = json!(skipped);
+        }
+
+        let mut output = ToolOutput::success(output).with_metadata(metadata);
+        if let Some(guard) = guard {
+            guard.annotate(&mut output);
+        }
+        Ok(output)
     }
 }
 
@@ -183,6 +459,25 @@ mod tests {
         assert!(result.content.contains("main.rs"));
     }
 
+    #[tokio::test]
+    async fn ls_relative_to() {
+        let (fs, tool) = setup().await;
+        fs.write("/shared/a.txt", "").await.unwrap();
+        fs.write("/shared/sub/b.txt", "").await.unwrap();
+
+        let result = tool
+            .execute("c1", json!({"path": "/shared", "relative_to": "."}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "../shared/a.txt\n../shared/sub/");
+
+        let result = tool
+            .execute("c2", json!({"path": "../nope", "relative_to": "."}), None)
+            .await
+            .unwrap();
+        assert!(result.content.contains("Directory not found: ../nope"));
+    }
+
     #[tokio::test]
     async fn ls_empty_dir() {
         let (fs, tool) = setup().await;
@@ -222,6 +517,11 @@ mod tests {
 
         assert!(!result.is_error);
         assert!(result.content.contains("Showing 3 of 10"));
+        let truncation = &result.metadata["truncation"];
+        assert_eq!(truncation["truncated"], true);
+        assert_eq!(truncation["truncated_by"], "lines");
+        assert_eq!(truncation["original_lines"], 10);
+        assert_eq!(truncation["output_lines"], 3);
     }
 
     #[tokio::test]
@@ -243,6 +543,85 @@ mod tests {
         assert_eq!(lines[2], "Cherry.txt");
     }
 
+    #[tokio::test]
+    async fn ls_with_sizes() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/src/main.rs", "fn main() {}").await.unwrap();
+        fs.write("/project/src/util/mod.rs", &"x".repeat(2048)).await.unwrap();
+        fs.write("/project/README.md", "hi").await.unwrap();
+
+        let result = tool
+            .execute("c7", json!({"sizes": true}), None)
+            .await
+            .unwrap();
+
+        assert!(!result.is_error);
+        assert!(result.content.contains("README.md\n"));
+        assert!(result.content.contains("src/ (2 entries, 2 files, 2.0 KB)"));
+        assert_eq!(result.metadata["directories"][0]["files"], 2);
+        assert_eq!(result.metadata["directories"][0]["bytes"], 2060);
+    }
+
+    #[tokio::test]
+    async fn ls_sizes_stop_endless_trees() {
+        let fs = crate::walk::tests::loop_fs().await;
+        let tool = LsTool::new(fs, "/w");
+        let result = tool
+            .execute("c1", json!({"sizes": true}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains(", incomplete)"), "{}", result.content);
+        assert!(result.content.contains("[Sizes of "), "{}", result.content);
+        let dirs = result.metadata["directories"].as_array().unwrap();
+        let complete = |name: &str| dirs.iter().find(|d| d["name"] == name).unwrap()["complete"].clone();
+        assert_eq!(complete("loop"), false);
+    }
+
+    #[tokio::test]
+    async fn ls_recursive_skips_generated_dirs() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/src/main.rs", "").await.unwrap();
+        fs.write("/project/src/util/mod.rs", "").await.unwrap();
+        fs.write("/project/target/debug/app", "").await.unwrap();
+        fs.write("/project/Cargo.toml", "").await.unwrap();
+
+        let result = tool
+            .execute("c1", json!({"recursive": true}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content,
+            "Cargo.toml\nsrc/\nsrc/main.rs\nsrc/util/\nsrc/util/mod.rs\ntarget/\n\
+             [Not entered: target/ (pass skip: [] to list them)]"
+        );
+        assert_eq!(result.metadata["skipped"], json!(["target"]));
+
+        let result = tool
+            .execute("c2", json!({"recursive": true, "skip": [], "depth": 2}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content,
+            "Cargo.toml\nsrc/\nsrc/main.rs\nsrc/util/\ntarget/\ntarget/debug/"
+        );
+
+        let tool = tool.with_skipped_dirs(["src"]);
+        let result = tool
+            .execute("c3", json!({"recursive": true}), None)
+            .await
+            .unwrap();
+        assert!(result.content.contains("target/debug/app"));
+        assert!(!result.content.contains("main.rs"));
+    }
+
+    #[test]
+    fn size_formatting() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+    }
+
     #[tokio::test]
     async fn tool_name_and_definition() {
         let (_fs, tool) = setup().await;
@@ -0,0 +1,204 @@
+//! Manifest tool — the workspace as a nested JSON tree.
+//!
+//! A thin wrapper over [`crate::manifest::build_manifest`] for hosts that want
+//! the model (or a UI listening to tool results) to receive the tree itself.
+//! Not part of [`all_tools`](crate::presets::all_tools); add it with
+//! [`Toolset::with_manifest`](crate::Toolset::with_manifest).
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::error::{tool_error, ToolErrorKind};
+use crate::manifest::{build_manifest, ManifestOptions, DEFAULT_MAX_DEPTH, DEFAULT_MAX_ENTRIES};
+use crate::paths;
+
+use super::{impl_overrides, resolve_path, ToolOverrides};
+
+/// Upper bound on `max_entries` a caller may request.
+const MAX_ENTRIES_LIMIT: usize = 10_000;
+
+/// System-prompt guidance returned by [`ManifestTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Fetch a directory tree as JSON when you need the structure rather than file \
+    contents. Directories left without children can be expanded with another call \
+    rooted there.";
+
+pub struct ManifestTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    overrides: ToolOverrides,
+}
+
+impl ManifestTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            overrides: ToolOverrides::default(),
+        }
+    }
+}
+
+impl_overrides!(ManifestTool);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for ManifestTool {
+    fn name(&self) -> &str {
+        self.overrides.name("manifest")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "manifest".into(),
+            description: "Return a directory tree as nested JSON: {name, path, type, size, children}. Directories deeper than max_depth have no children; truncated marks directories cut short by max_entries. Hidden and .gitignore'd paths are skipped unless hidden is set.".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "Root directory (defaults to working directory)"
+                    },
+                    "max_depth": {
+                        "type": "integer",
+                        "description": format!("Directory levels to expand (default: {})", DEFAULT_MAX_DEPTH)
+                    },
+                    "max_entries": {
+                        "type": "integer",
+                        "description": format!("Maximum nodes returned (default: {}, at most {})", DEFAULT_MAX_ENTRIES, MAX_ENTRIES_LIMIT)
+                    },
+                    "hidden": {
+                        "type": "boolean",
+                        "description": "Include dot-files and .gitignore'd paths (default: false)"
+                    }
+                }
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let path = arguments.get("path").and_then(|v| v.as_str()).unwrap_or("");
+        let resolved = resolve_path(&self.cwd, path);
+
+        let hidden = arguments
+            .get("hidden")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let mut options = ManifestOptions::new()
+            .with_hidden(hidden)
+            .with_gitignore(!hidden);
+        if let Some(depth) = arguments.get("max_depth").and_then(|v| v.as_u64()) {
+            options = options.with_max_depth(depth as usize);
+        }
+        if let Some(entries) = arguments.get("max_entries").and_then(|v| v.as_u64()) {
+            options = options.with_max_entries((entries as usize).min(MAX_ENTRIES_LIMIT));
+        }
+
+        let is_dir = self
+            .fs
+            .metadata(&resolved)
+            .await
+            .map(|m| m.is_dir)
+            .unwrap_or(false);
+        if !is_dir {
+            return Ok(tool_error(
+                ToolErrorKind::NotFound,
+                format!(
+                    "Directory not found: {}",
+                    paths::display(&resolved, &self.cwd)
+                ),
+            ));
+        }
+
+        let tree = match build_manifest(self.fs.as_ref(), &resolved, &options).await {
+            Ok(tree) => tree,
+            Err(e) => {
+                return Ok(tool_error(
+                    ToolErrorKind::from_soul_error(&e),
+                    format!("Failed to build manifest: {}", e),
+                ))
+            }
+        };
+        let content = serde_json::to_string(&tree).unwrap_or_default();
+        Ok(ToolOutput::success(content).with_metadata(json!({
+            "root": resolved,
+            "entries": tree.count(),
+            "truncated": tree.is_truncated(),
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    use crate::manifest::ManifestNode;
+
+    async fn setup() -> (Arc<MemoryFs>, ManifestTool) {
+        let fs = Arc::new(MemoryFs::new());
+        let tool = ManifestTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        (fs, tool)
+    }
+
+    #[tokio::test]
+    async fn manifest_returns_parseable_tree() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/src/lib.rs", "pub fn a() {}")
+            .await
+            .unwrap();
+        fs.write("/project/src/deep/x/y.rs", "").await.unwrap();
+        fs.write("/project/.env", "SECRET=1").await.unwrap();
+
+        let result = tool
+            .execute("c1", json!({"max_depth": 2}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        let tree: ManifestNode = serde_json::from_str(&result.content).unwrap();
+        let src = &tree.children.as_ref().unwrap()[0];
+        assert_eq!(src.path, "src");
+        let deep = &src.children.as_ref().unwrap()[0];
+        assert_eq!(deep.path, "src/deep");
+        assert_eq!(deep.children, None);
+        assert_eq!(result.metadata["entries"], 3);
+        assert_eq!(result.metadata["truncated"], false);
+
+        let result = tool
+            .execute("c2", json!({"hidden": true, "max_entries": 1}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["entries"], 1);
+        assert_eq!(result.metadata["truncated"], true);
+    }
+
+    #[tokio::test]
+    async fn manifest_missing_directory() {
+        let (_fs, tool) = setup().await;
+        let result = tool
+            .execute("c1", json!({"path": "nope"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+    }
+
+    #[tokio::test]
+    async fn tool_name_and_definition() {
+        let (_fs, tool) = setup().await;
+        assert_eq!(tool.name(), "manifest");
+        assert_eq!(tool.definition().name, "manifest");
+    }
+}
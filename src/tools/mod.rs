@@ -1,19 +1,417 @@
 pub mod append;
 pub mod bash;
+pub mod delete;
+pub mod dupes;
 pub mod edit;
+pub mod env_info;
 pub mod find;
+pub mod format;
 pub mod grep;
+pub mod grep_replace;
+pub mod hash;
+pub mod loc;
 pub mod ls;
+pub mod manifest;
+pub mod open_file;
+pub mod outline;
+pub mod project_info;
 pub mod read;
+pub mod rename;
+pub mod scaffold;
+pub mod scratch;
+pub mod set_cwd;
+pub mod stats;
+pub mod todo;
 pub mod write;
+pub mod write_many;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use soul_core::error::{SoulError, SoulResult};
+use soul_core::tool::{Tool, ToolOutput, ToolRegistry};
+use soul_core::types::ToolDefinition;
+
+pub(crate) use crate::diff::unified_diff;
+use crate::error::{tool_error, ToolErrorKind};
+use crate::paths;
+
+/// Host-supplied overrides for a tool's advertised definition.
+///
+/// Set through each tool's `with_name`, `with_description` and
+/// `with_schema_extras` builder methods.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ToolOverrides {
+    pub(crate) name: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) schema_extras: Option<serde_json::Value>,
+}
+
+impl ToolOverrides {
+    /// The overridden name, or `default`.
+    pub(crate) fn name<'a>(&'a self, default: &'a str) -> &'a str {
+        self.name.as_deref().unwrap_or(default)
+    }
+
+    /// Apply the overrides to a tool's built-in definition.
+    pub(crate) fn apply(&self, mut definition: ToolDefinition) -> ToolDefinition {
+        if let Some(name) = &self.name {
+            definition.name = name.clone();
+        }
+        if let Some(description) = &self.description {
+            definition.description = description.clone();
+        }
+        if let Some(extras) = &self.schema_extras {
+            merge_json(&mut definition.input_schema, extras);
+        }
+        definition
+    }
+}
+
+/// Deep-merge `extras` into `base`: objects merge key by key, anything else replaces.
+fn merge_json(base: &mut serde_json::Value, extras: &serde_json::Value) {
+    match (base, extras) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(extras)) => {
+            for (key, value) in extras {
+                match base.get_mut(key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, extras) => *base = extras.clone(),
+    }
+}
+
+/// Generate the definition-override builder methods for a tool with an
+/// `overrides: ToolOverrides` field.
+macro_rules! impl_overrides {
+    ($tool:ty) => {
+        impl $tool {
+            /// Advertise the tool under a different name (e.g. `shell` instead of `bash`).
+            pub fn with_name(mut self, name: impl Into<String>) -> Self {
+                self.overrides.name = Some(name.into());
+                self
+            }
+
+            /// Replace the description shown to the model.
+            pub fn with_description(mut self, description: impl Into<String>) -> Self {
+                self.overrides.description = Some(description.into());
+                self
+            }
+
+            /// Deep-merge `extras` into the input schema, e.g. to reword a
+            /// parameter description or add `"additionalProperties": false`.
+            pub fn with_schema_extras(mut self, extras: serde_json::Value) -> Self {
+                self.overrides.schema_extras = Some(extras);
+                self
+            }
+
+            /// When to reach for this tool and the rules it expects callers to
+            /// follow, written for inclusion in a system prompt.
+            pub fn usage_guide(&self) -> &'static str {
+                USAGE_GUIDE
+            }
+        }
+    };
+}
+pub(crate) use impl_overrides;
+
+/// The [usage guide](read::ReadTool::usage_guide) of the built-in tool
+/// called `name`; renamed tools are not recognized.
+pub fn usage_guide(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "read" => read::USAGE_GUIDE,
+        "write" => write::USAGE_GUIDE,
+        "append" => append::USAGE_GUIDE,
+        "edit" => edit::USAGE_GUIDE,
+        "bash" => bash::USAGE_GUIDE,
+        "grep" => grep::USAGE_GUIDE,
+        "find" => find::USAGE_GUIDE,
+        "ls" => ls::USAGE_GUIDE,
+        "outline" => outline::USAGE_GUIDE,
+        "todo" => todo::USAGE_GUIDE,
+        "loc" => loc::USAGE_GUIDE,
+        "scratch" => scratch::USAGE_GUIDE,
+        "scaffold" => scaffold::USAGE_GUIDE,
+        "project_info" => project_info::USAGE_GUIDE,
+        "hash" => hash::USAGE_GUIDE,
+        "write_many" => write_many::USAGE_GUIDE,
+        "dupes" => dupes::USAGE_GUIDE,
+        "stats" => stats::USAGE_GUIDE,
+        "grep_replace" => grep_replace::USAGE_GUIDE,
+        "manifest" => manifest::USAGE_GUIDE,
+        "open_file" => open_file::USAGE_GUIDE,
+        "set_cwd" => set_cwd::USAGE_GUIDE,
+        "delete" => delete::USAGE_GUIDE,
+        "format" => format::USAGE_GUIDE,
+        "rename" => rename::USAGE_GUIDE,
+        "env_info" => env_info::USAGE_GUIDE,
+        _ => return None,
+    })
+}
+
+/// The name the built-in tool type `T` is registered under by default,
+/// whatever name an instance of it was given.
+pub(crate) fn builtin_name<T: 'static>() -> Option<&'static str> {
+    use std::any::TypeId;
+    let id = TypeId::of::<T>();
+    [
+        (TypeId::of::<append::AppendTool>(), "append"),
+        (TypeId::of::<bash::BashTool>(), "bash"),
+        (TypeId::of::<delete::DeleteTool>(), "delete"),
+        (TypeId::of::<dupes::DupesTool>(), "dupes"),
+        (TypeId::of::<edit::EditTool>(), "edit"),
+        (TypeId::of::<env_info::EnvInfoTool>(), "env_info"),
+        (TypeId::of::<find::FindTool>(), "find"),
+        (TypeId::of::<format::FormatTool>(), "format"),
+        (TypeId::of::<grep::GrepTool>(), "grep"),
+        (TypeId::of::<grep_replace::GrepReplaceTool>(), "grep_replace"),
+        (TypeId::of::<hash::HashTool>(), "hash"),
+        (TypeId::of::<loc::LocTool>(), "loc"),
+        (TypeId::of::<ls::LsTool>(), "ls"),
+        (TypeId::of::<manifest::ManifestTool>(), "manifest"),
+        (TypeId::of::<open_file::OpenFileTool>(), "open_file"),
+        (TypeId::of::<outline::OutlineTool>(), "outline"),
+        (TypeId::of::<project_info::ProjectInfoTool>(), "project_info"),
+        (TypeId::of::<read::ReadTool>(), "read"),
+        (TypeId::of::<rename::RenameTool>(), "rename"),
+        (TypeId::of::<scaffold::ScaffoldTool>(), "scaffold"),
+        (TypeId::of::<scratch::ScratchTool>(), "scratch"),
+        (TypeId::of::<set_cwd::SetCwdTool>(), "set_cwd"),
+        (TypeId::of::<stats::StatsTool>(), "stats"),
+        (TypeId::of::<todo::TodoTool>(), "todo"),
+        (TypeId::of::<write::WriteTool>(), "write"),
+        (TypeId::of::<write_many::WriteManyTool>(), "write_many"),
+    ]
+    .into_iter()
+    .find(|(type_id, _)| *type_id == id)
+    .map(|(_, name)| name)
+}
 
 /// Resolve a path relative to the working directory.
-/// Absolute paths are returned as-is; relative paths are joined with cwd.
+/// Absolute paths are kept; relative paths are joined with cwd. Either way
+/// the result is normalized (see [`crate::paths`]).
 pub(crate) fn resolve_path(cwd: &str, path: &str) -> String {
-    if path.starts_with('/') {
-        path.to_string()
-    } else {
-        format!("{}/{}", cwd.trim_end_matches('/'), path)
+    paths::resolve(cwd, path)
+}
+
+/// Insert a key into a tool output's metadata object, creating it if needed.
+pub(crate) fn set_metadata(output: &mut ToolOutput, key: &str, value: serde_json::Value) {
+    if !output.metadata.is_object() {
+        output.metadata = serde_json::json!({});
+    }
+    if let Some(map) = output.metadata.as_object_mut() {
+        map.insert(key.to_string(), value);
+    }
+}
+
+/// Default number of files between progress updates from grep and find.
+pub const DEFAULT_PROGRESS_INTERVAL: usize = 500;
+
+/// Periodic "scanned N files" updates sent through a tool's `partial_tx`
+/// during long walks, so frontends can show activity.
+pub(crate) struct Progress {
+    tx: Option<mpsc::UnboundedSender<String>>,
+    interval: usize,
+    total: Option<usize>,
+    scanned: usize,
+}
+
+impl Progress {
+    /// `interval` of 0 disables updates; `total` is the file count when
+    /// known up front.
+    pub(crate) fn new(
+        tx: Option<mpsc::UnboundedSender<String>>,
+        interval: usize,
+        total: Option<usize>,
+    ) -> Self {
+        Self {
+            tx,
+            interval,
+            total,
+            scanned: 0,
+        }
+    }
+
+    /// Count one scanned file, reporting every `interval` files.
+    pub(crate) fn tick(&mut self, matches: usize) {
+        self.scanned += 1;
+        let Some(tx) = &self.tx else {
+            return;
+        };
+        if self.interval == 0 || self.scanned % self.interval != 0 {
+            return;
+        }
+        let scanned = match self.total {
+            // The final result follows immediately
+            Some(total) if self.scanned >= total => return,
+            Some(total) => format!("{}/{}", self.scanned, total),
+            None => self.scanned.to_string(),
+        };
+        let _ = tx.send(format!("scanned {} files, {} matches so far", scanned, matches));
+    }
+}
+
+/// Order of file results from find and grep.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum SortOrder {
+    /// Numbers compare by value: `file2.rs` before `file10.rs`.
+    #[default]
+    Natural,
+    /// Plain byte order.
+    Path,
+}
+
+impl SortOrder {
+    /// Schema for the `sort` parameter.
+    pub(crate) fn schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "string",
+            "enum": ["natural", "path"],
+            "description": "Result order: 'natural' (default; file2 before file10) or 'path' (byte order). Results are stable across filesystem backends"
+        })
+    }
+
+    /// Parse the `sort` argument; errors are ready-made tool outputs. `mtime`
+    /// isn't advertised, but still gets a clear error.
+    pub(crate) fn parse(value: Option<&serde_json::Value>) -> Result<Self, ToolOutput> {
+        match value.and_then(|v| v.as_str()) {
+            None | Some("natural") => Ok(Self::Natural),
+            Some("path") => Ok(Self::Path),
+            Some("mtime") => Err(tool_error(
+                ToolErrorKind::Unsupported,
+                "sort: mtime is not supported: the virtual filesystem has no modification times",
+            )),
+            Some(other) => Err(tool_error(
+                ToolErrorKind::InvalidArguments,
+                format!("Unknown sort: {} (expected natural or path)", other),
+            )),
+        }
+    }
+
+    pub(crate) fn sort(self, paths: &mut [String]) {
+        match self {
+            Self::Natural => paths.sort_by(|a, b| paths::natural_cmp(a, b)),
+            Self::Path => paths.sort(),
+        }
+    }
+}
+
+/// How grep, find and ls show paths: relative to the working directory
+/// (absolute when outside it), or, given `relative_to`, always relative to
+/// that directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DisplayRoot {
+    root: String,
+    explicit: bool,
+}
+
+impl DisplayRoot {
+    /// Schema for the `relative_to` parameter.
+    pub(crate) fn schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "string",
+            "description": "Show result paths relative to this directory (e.g. '.' for the working directory), using '../' for paths outside it, so they can be passed straight to other tools. Default: relative to the working directory, absolute outside it"
+        })
+    }
+
+    /// Read `relative_to` from the arguments, resolved against `cwd`.
+    pub(crate) fn from_arguments(cwd: &str, arguments: &serde_json::Value) -> Self {
+        match arguments.get("relative_to").and_then(|v| v.as_str()) {
+            Some(root) => Self {
+                root: resolve_path(cwd, root),
+                explicit: true,
+            },
+            None => Self {
+                root: paths::normalize(cwd),
+                explicit: false,
+            },
+        }
+    }
+
+    pub(crate) fn is_explicit(&self) -> bool {
+        self.explicit
+    }
+
+    /// `path` (absolute) as shown in output.
+    pub(crate) fn show(&self, path: &str) -> String {
+        if self.explicit {
+            paths::relative_path(path, &self.root)
+        } else {
+            paths::display(path, &self.root)
+        }
+    }
+
+    /// The absolute path of a path [`show`](Self::show) returned.
+    pub(crate) fn resolve(&self, shown: &str) -> String {
+        resolve_path(&self.root, shown)
+    }
+}
+
+/// A handle to one tool inside a shared [`ToolRegistry`].
+///
+/// Registries can't hand out owned tools, so wrappers that decorate an
+/// existing registry (audit, middleware) delegate through this instead.
+#[derive(Clone)]
+pub(crate) struct SharedTool {
+    registry: Arc<ToolRegistry>,
+    name: String,
+}
+
+impl SharedTool {
+    /// One handle per static tool in `registry`.
+    pub(crate) fn split(registry: ToolRegistry) -> Vec<SharedTool> {
+        let names: Vec<String> = registry.names().iter().map(|n| n.to_string()).collect();
+        let registry = Arc::new(registry);
+        names
+            .into_iter()
+            .map(|name| SharedTool {
+                registry: registry.clone(),
+                name,
+            })
+            .collect()
+    }
+
+    fn tool(&self) -> SoulResult<&dyn Tool> {
+        self.registry
+            .get(&self.name)
+            .ok_or_else(|| SoulError::ToolExecution {
+                tool_name: self.name.clone(),
+                message: format!("Unknown tool: {}", self.name),
+            })
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for SharedTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        match self.tool() {
+            Ok(tool) => tool.definition(),
+            Err(_) => ToolDefinition {
+                name: self.name.clone(),
+                description: String::new(),
+                input_schema: serde_json::json!({"type": "object"}),
+            },
+        }
+    }
+
+    async fn execute(
+        &self,
+        call_id: &str,
+        arguments: serde_json::Value,
+        partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        self.tool()?.execute(call_id, arguments, partial_tx).await
     }
 }
 
@@ -35,4 +433,33 @@ mod tests {
     fn cwd_trailing_slash_stripped() {
         assert_eq!(resolve_path("/project/", "file.txt"), "/project/file.txt");
     }
+
+    #[test]
+    fn dot_segments_normalized() {
+        assert_eq!(resolve_path("/project", "./src/../lib.rs"), "/project/lib.rs");
+        assert_eq!(resolve_path("/project", "../etc/passwd"), "/etc/passwd");
+    }
+
+    #[test]
+    fn overrides_apply_to_definition() {
+        use soul_core::vfs::{MemoryFs, VirtualFs};
+
+        let fs = Arc::new(MemoryFs::new()) as Arc<dyn VirtualFs>;
+        let tool = read::ReadTool::new(fs, "/project")
+            .with_name("view")
+            .with_description("Ver un archivo")
+            .with_schema_extras(serde_json::json!({
+                "properties": {"path": {"description": "Ruta"}},
+                "additionalProperties": false
+            }));
+
+        assert_eq!(tool.name(), "view");
+        let def = tool.definition();
+        assert_eq!(def.name, "view");
+        assert_eq!(def.description, "Ver un archivo");
+        assert_eq!(def.input_schema["properties"]["path"]["description"], "Ruta");
+        assert_eq!(def.input_schema["properties"]["path"]["type"], "string");
+        assert_eq!(def.input_schema["properties"]["offset"]["type"], "integer");
+        assert_eq!(def.input_schema["additionalProperties"], false);
+    }
 }
@@ -0,0 +1,277 @@
+//! Open file tool — show a file location in the host's editor.
+//!
+//! Forwards a path and line to an [`EditorBridge`], so a reference the
+//! model makes becomes something the user can jump to. Not part of
+//! [`all_tools`](crate::presets::all_tools); add it with
+//! [`Toolset::with_editor`](crate::Toolset::with_editor). Without a bridge
+//! it checks the location and reports that no editor is connected.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::editor::{EditorBridge, EditorLocation, NoEditor};
+use crate::error::{tool_error, ToolErrorKind};
+use crate::paths;
+
+use super::{impl_overrides, resolve_path, ToolOverrides};
+
+/// System-prompt guidance returned by [`OpenFileTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    When you point the user at a specific place in the code, open it for them with \
+    its line number. It only changes what the user sees, not any file.";
+
+pub struct OpenFileTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    bridge: Arc<dyn EditorBridge>,
+    overrides: ToolOverrides,
+}
+
+impl OpenFileTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            bridge: Arc::new(NoEditor),
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+    /// Send locations to `bridge` (default: [`NoEditor`]).
+    pub fn with_bridge(mut self, bridge: Arc<dyn EditorBridge>) -> Self {
+        self.bridge = bridge;
+        self
+    }
+}
+
+impl_overrides!(OpenFileTool);
+
+/// A positive integer argument, or an error message naming it.
+fn position(arguments: &serde_json::Value, name: &str) -> Result<Option<usize>, String> {
+    match arguments.get(name) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => match value.as_u64() {
+            Some(n) if n >= 1 => Ok(Some(n as usize)),
+            _ => Err(format!(
+                "{} must be a positive integer, got {}",
+                name, value
+            )),
+        },
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for OpenFileTool {
+    fn name(&self) -> &str {
+        self.overrides.name("open_file")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "open_file".into(),
+            description: "Open a file in the user's editor, optionally at a line (and column) or highlighting a line range. Use it to show the user code you refer to; it does not return the file's contents.".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "File to open (relative to working directory or absolute)"
+                    },
+                    "line": {
+                        "type": "integer",
+                        "description": "1-indexed line to show"
+                    },
+                    "column": {
+                        "type": "integer",
+                        "description": "1-indexed column on that line"
+                    },
+                    "end_line": {
+                        "type": "integer",
+                        "description": "Last line of a range to highlight, starting at line"
+                    }
+                },
+                "required": ["path"]
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let path = arguments.get("path").and_then(|v| v.as_str()).unwrap_or("");
+        if path.is_empty() {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Missing required parameter: path",
+            ));
+        }
+        let (line, column, end_line) = match (
+            position(&arguments, "line"),
+            position(&arguments, "column"),
+            position(&arguments, "end_line"),
+        ) {
+            (Ok(line), Ok(column), Ok(end_line)) => (line, column, end_line),
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                return Ok(tool_error(ToolErrorKind::InvalidArguments, e))
+            }
+        };
+        if end_line.is_some() && line.is_none() {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "end_line needs line",
+            ));
+        }
+
+        let resolved = resolve_path(&self.cwd, path);
+        let shown = paths::display(&resolved, &self.cwd);
+        let content = match self.fs.read_to_string(&resolved).await {
+            Ok(content) => content,
+            Err(_) => {
+                return Ok(tool_error(
+                    ToolErrorKind::NotFound,
+                    format!("File not found: {}", shown),
+                ))
+            }
+        };
+        let lines = content.lines().count().max(1);
+        if let Some(beyond) = [line, end_line].into_iter().flatten().find(|&l| l > lines) {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                format!(
+                    "Line {} is past the end of {} ({} lines)",
+                    beyond, shown, lines
+                ),
+            ));
+        }
+
+        let location = EditorLocation {
+            path: resolved,
+            line,
+            column,
+            end_line: end_line.filter(|&end| Some(end) > line),
+        };
+        let target = EditorLocation {
+            path: shown,
+            ..location.clone()
+        };
+        let opened = self.bridge.is_connected();
+        if opened {
+            if let Err(e) = self.bridge.open(&location).await {
+                return Ok(tool_error(
+                    ToolErrorKind::from_soul_error(&e),
+                    format!("Failed to open {}: {}", target, e),
+                ));
+            }
+        }
+        let message = match opened {
+            true => format!("Opened {} in the editor", target),
+            false => format!("No editor connected; {} was not opened", target),
+        };
+        Ok(ToolOutput::success(message).with_metadata(json!({
+            "location": location,
+            "opened": opened,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    use soul_core::vfs::MemoryFs;
+
+    use crate::editor::CallbackEditor;
+
+    async fn setup() -> (Arc<Mutex<Vec<String>>>, OpenFileTool) {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write(
+            "/project/src/parser.rs",
+            "fn a() {}\nfn b() {}\nfn c() {}\n",
+        )
+        .await
+        .unwrap();
+        let opened = Arc::new(Mutex::new(Vec::new()));
+        let sink = opened.clone();
+        let bridge = CallbackEditor::new(move |location: &EditorLocation| {
+            sink.lock().unwrap().push(location.to_string());
+        });
+        let tool =
+            OpenFileTool::new(fs as Arc<dyn VirtualFs>, "/project").with_bridge(Arc::new(bridge));
+        (opened, tool)
+    }
+
+    #[tokio::test]
+    async fn forwards_locations_to_the_bridge() {
+        let (opened, tool) = setup().await;
+        let result = tool
+            .execute(
+                "c1",
+                json!({"path": "src/parser.rs", "line": 2, "column": 4}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.content, "Opened src/parser.rs:2:4 in the editor");
+        assert_eq!(
+            result.metadata["location"]["path"],
+            "/project/src/parser.rs"
+        );
+        tool.execute(
+            "c2",
+            json!({"path": "src/parser.rs", "line": 1, "end_line": 3}),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            *opened.lock().unwrap(),
+            vec!["/project/src/parser.rs:2:4", "/project/src/parser.rs:1-3"]
+        );
+
+        let result = tool
+            .execute("c3", json!({"path": "src/parser.rs", "line": 40}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            ToolErrorKind::of(&result),
+            Some(ToolErrorKind::InvalidArguments)
+        );
+        let result = tool
+            .execute("c4", json!({"path": "src/lexer.rs"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+        assert_eq!(opened.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn no_editor_by_default() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "x\n").await.unwrap();
+        let tool = OpenFileTool::new(fs as Arc<dyn VirtualFs>, "/project");
+        let result = tool
+            .execute("c1", json!({"path": "a.txt", "line": 1}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        assert_eq!(
+            result.content,
+            "No editor connected; a.txt:1 was not opened"
+        );
+        assert_eq!(result.metadata["opened"], false);
+        assert_eq!(tool.name(), "open_file");
+    }
+}
@@ -0,0 +1,247 @@
+//! Outline tool — list a file's top-level structure with line numbers.
+//!
+//! Uses per-language heuristics from [`crate::outline`] so agents can navigate
+//! large files without reading them wholesale.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::error::{tool_error, ToolErrorKind};
+use crate::outline::{extract_outline, Language};
+use crate::truncate::{truncate_head, MAX_BYTES, MAX_LINES};
+
+use super::{impl_overrides, resolve_path, ToolOverrides};
+
+/// System-prompt guidance returned by [`OutlineTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    List a file's definitions with their line numbers. Use it on large files to pick \
+    the range to read instead of reading everything.";
+
+pub struct OutlineTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    overrides: ToolOverrides,
+}
+
+impl OutlineTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            overrides: ToolOverrides::default(),
+        }
+    }
+}
+
+impl_overrides!(OutlineTool);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for OutlineTool {
+    fn name(&self) -> &str {
+        self.overrides.name("outline")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "outline".into(),
+            description: "Show a file's structure (functions, types, classes, impl blocks, headings) with line numbers. Use before reading large files to find the section you need. Supports Rust, Python, JavaScript/TypeScript, Go and Markdown using line-based heuristics, not a full parser (no tree-sitter), so unusual formatting can be missed.".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "File path to outline (relative to working directory or absolute)"
+                    }
+                },
+                "required": ["path"]
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let path = arguments.get("path").and_then(|v| v.as_str()).unwrap_or("");
+
+        if path.is_empty() {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Missing required parameter: path",
+            ));
+        }
+
+        let language = match Language::from_path(path) {
+            Some(l) => l,
+            None => {
+                return Ok(tool_error(
+                    ToolErrorKind::Unsupported,
+                    format!("Unsupported file type for outline: {}. Use read instead.", path),
+                ))
+            }
+        };
+
+        let resolved = resolve_path(&self.cwd, path);
+
+        let exists = self.fs.exists(&resolved).await?;
+        if !exists {
+            return Ok(tool_error(
+                ToolErrorKind::NotFound,
+                format!("File not found: {}", path),
+            ));
+        }
+
+        let content = match self.fs.read_to_string(&resolved).await {
+            Ok(c) => c,
+            Err(e) => {
+                return Ok(tool_error(
+                    ToolErrorKind::from_soul_error(&e),
+                    format!("Failed to read {}: {}", path, e),
+                ))
+            }
+        };
+
+        let total_lines = content.lines().count();
+        let items = extract_outline(&content, language);
+
+        if items.is_empty() {
+            return Ok(ToolOutput::success(format!(
+                "No structure found in {} ({} lines)",
+                path, total_lines
+            ))
+            .with_metadata(json!({
+                "language": language.name(),
+                "total_lines": total_lines,
+                "items": [],
+            })));
+        }
+
+        let listing = items
+            .iter()
+            .map(|item| {
+                format!(
+                    "{:>6}\t{}{} {}",
+                    item.line,
+                    "  ".repeat(item.depth),
+                    item.kind,
+                    item.name
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let truncated = truncate_head(&listing, MAX_LINES, MAX_BYTES);
+        let mut output = format!(
+            "{} ({}, {} lines, {} items)\n{}",
+            path,
+            language.name(),
+            total_lines,
+            items.len(),
+            truncated.content
+        );
+        if let Some(notice) = truncated.truncation_notice() {
+            output.push('\n');
+            output.push_str(&notice);
+        }
+
+        Ok(ToolOutput::success(output).with_metadata(json!({
+            "language": language.name(),
+            "total_lines": total_lines,
+            "items": items,
+            "truncated": truncated.is_truncated(),
+            "truncation": truncated.to_metadata(),
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    async fn setup() -> (Arc<MemoryFs>, OutlineTool) {
+        let fs = Arc::new(MemoryFs::new());
+        let tool = OutlineTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        (fs, tool)
+    }
+
+    #[tokio::test]
+    async fn outline_rust_file() {
+        let (fs, tool) = setup().await;
+        fs.write(
+            "/project/lib.rs",
+            "pub struct Foo;\n\nimpl Foo {\n    pub fn bar(&self) {}\n}\n\nfn helper() {}\n",
+        )
+        .await
+        .unwrap();
+
+        let result = tool
+            .execute("c1", json!({"path": "lib.rs"}), None)
+            .await
+            .unwrap();
+
+        assert!(!result.is_error);
+        assert!(result.content.contains("     1\tstruct Foo"));
+        assert!(result.content.contains("     3\timpl Foo"));
+        assert!(result.content.contains("     4\t  fn bar"));
+        assert!(result.content.contains("     7\tfn helper"));
+        assert_eq!(result.metadata["items"].as_array().unwrap().len(), 4);
+        assert_eq!(result.metadata["language"], "rust");
+    }
+
+    #[tokio::test]
+    async fn outline_unsupported_extension() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/data.bin", "xyz").await.unwrap();
+
+        let result = tool
+            .execute("c2", json!({"path": "data.bin"}), None)
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(result.content.contains("Unsupported"));
+    }
+
+    #[tokio::test]
+    async fn outline_no_structure() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/empty.py", "x = 1\n").await.unwrap();
+
+        let result = tool
+            .execute("c3", json!({"path": "empty.py"}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        assert!(result.content.contains("No structure"));
+    }
+
+    #[tokio::test]
+    async fn outline_nonexistent() {
+        let (_fs, tool) = setup().await;
+        let result = tool
+            .execute("c4", json!({"path": "nope.rs"}), None)
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(result.content.contains("not found"));
+    }
+
+    #[tokio::test]
+    async fn tool_name_and_definition() {
+        let (_fs, tool) = setup().await;
+        assert_eq!(tool.name(), "outline");
+        let def = tool.definition();
+        assert_eq!(def.name, "outline");
+    }
+}
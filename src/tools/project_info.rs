@@ -0,0 +1,459 @@
+//! Project info tool — one-call orientation in an unfamiliar workspace.
+//!
+//! Sessions tend to open with the same handful of calls: list the root, look
+//! for a manifest, read it, skim the README, guess where the code starts.
+//! `project_info` answers all of them at once, from VFS reads only, within a
+//! byte budget: detected build systems, likely entry points, the head of the
+//! README and a depth-2 tree (from [`crate::manifest::build_manifest`]).
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::error::{tool_error, ToolErrorKind};
+use crate::manifest::{build_manifest, ManifestNode, ManifestOptions, NodeKind};
+use crate::paths;
+use crate::truncate::{truncate_head, MAX_BYTES};
+
+use super::{impl_overrides, resolve_path, ToolOverrides};
+
+/// Default byte budget for the whole report.
+pub const DEFAULT_MAX_BYTES: usize = 8 * 1024;
+
+/// Lines of the README included.
+const README_LINES: usize = 20;
+
+/// Cap on README bytes, whatever the overall budget.
+const README_MAX_BYTES: usize = 2048;
+
+/// Entries listed in the tree before it is cut short.
+const TREE_MAX_ENTRIES: usize = 200;
+
+/// Marker file, build system, and language.
+const BUILD_SYSTEMS: &[(&str, &str, &str)] = &[
+    ("Cargo.toml", "Cargo", "Rust"),
+    ("package.json", "npm", "JavaScript/TypeScript"),
+    ("deno.json", "Deno", "JavaScript/TypeScript"),
+    ("pyproject.toml", "pyproject", "Python"),
+    ("setup.py", "setuptools", "Python"),
+    ("requirements.txt", "pip", "Python"),
+    ("go.mod", "Go modules", "Go"),
+    ("pom.xml", "Maven", "Java"),
+    ("build.gradle", "Gradle", "Java/Kotlin"),
+    ("build.gradle.kts", "Gradle", "Java/Kotlin"),
+    ("CMakeLists.txt", "CMake", "C/C++"),
+    ("Makefile", "Make", ""),
+    ("Gemfile", "Bundler", "Ruby"),
+    ("composer.json", "Composer", "PHP"),
+    ("mix.exs", "Mix", "Elixir"),
+    ("Package.swift", "SwiftPM", "Swift"),
+];
+
+/// Conventional entry points, checked relative to the project root.
+const ENTRY_POINTS: &[&str] = &[
+    "src/main.rs",
+    "src/lib.rs",
+    "main.go",
+    "main.py",
+    "app.py",
+    "manage.py",
+    "__main__.py",
+    "index.js",
+    "index.ts",
+    "src/index.js",
+    "src/index.ts",
+    "src/main.js",
+    "src/main.ts",
+    "src/App.tsx",
+    "src/main/java/Main.java",
+    "main.c",
+    "src/main.c",
+    "main.cpp",
+    "src/main.cpp",
+];
+
+const READMES: &[&str] = &[
+    "README.md",
+    "README.rst",
+    "README.txt",
+    "README",
+    "readme.md",
+];
+
+/// A detected build system.
+struct BuildSystem {
+    file: &'static str,
+    name: &'static str,
+    language: &'static str,
+    /// Package name and scripts, where the manifest is easy to read.
+    details: Option<String>,
+}
+
+/// Value of `key = "..."` in the `[section]` table of a TOML file.
+fn toml_value(content: &str, section: &str, key: &str) -> Option<String> {
+    let mut in_section = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_section = line == format!("[{}]", section);
+        } else if let Some((k, v)) = line.split_once('=').filter(|_| in_section) {
+            if k.trim() == key {
+                return Some(v.trim().trim_matches('"').to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Short summary of a manifest's contents.
+fn manifest_details(file: &str, content: &str) -> Option<String> {
+    match file {
+        "Cargo.toml" => {
+            let package = toml_value(content, "package", "name").map(|n| format!("package {}", n));
+            let workspace = content
+                .lines()
+                .any(|l| l.trim() == "[workspace]")
+                .then(|| "workspace".to_string());
+            let parts: Vec<String> = package.into_iter().chain(workspace).collect();
+            (!parts.is_empty()).then(|| parts.join(", "))
+        }
+        "pyproject.toml" => toml_value(content, "project", "name")
+            .or_else(|| toml_value(content, "tool.poetry", "name"))
+            .map(|n| format!("package {}", n)),
+        "package.json" => {
+            let json: serde_json::Value = serde_json::from_str(content).ok()?;
+            let mut parts = Vec::new();
+            if let Some(name) = json.get("name").and_then(|v| v.as_str()) {
+                parts.push(format!("package {}", name));
+            }
+            if let Some(scripts) = json.get("scripts").and_then(|v| v.as_object()) {
+                let names: Vec<&str> = scripts.keys().map(String::as_str).collect();
+                parts.push(format!("scripts: {}", names.join(", ")));
+            }
+            (!parts.is_empty()).then(|| parts.join("; "))
+        }
+        "go.mod" => content
+            .lines()
+            .find_map(|l| l.strip_prefix("module "))
+            .map(|m| format!("module {}", m.trim())),
+        _ => None,
+    }
+}
+
+/// Indented listing of a manifest tree, directories suffixed with `/`.
+fn render_tree(node: &ManifestNode, depth: usize, out: &mut String) {
+    for child in node.children.iter().flatten() {
+        out.push_str(&"  ".repeat(depth));
+        out.push_str(&child.name);
+        if child.kind == NodeKind::Dir {
+            out.push('/');
+        }
+        out.push('\n');
+        render_tree(child, depth + 1, out);
+    }
+    if node.truncated {
+        out.push_str(&"  ".repeat(depth));
+        out.push_str("...\n");
+    }
+}
+
+/// System-prompt guidance returned by [`ProjectInfoTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Call once at the start of a session in an unfamiliar project: it returns the \
+    build system, likely entry points, the top of the README and a shallow tree, \
+    replacing a round of ls, find and read calls.";
+
+pub struct ProjectInfoTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    max_bytes: usize,
+    overrides: ToolOverrides,
+}
+
+impl ProjectInfoTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            max_bytes: DEFAULT_MAX_BYTES,
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+    /// Budget for the whole report when a call doesn't pass `max_bytes`
+    /// (default: 8 KB).
+    pub fn with_max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = bytes;
+        self
+    }
+
+    async fn read(&self, root: &str, rel: &str) -> Option<String> {
+        self.fs
+            .read_to_string(&paths::resolve(root, rel))
+            .await
+            .ok()
+    }
+
+    async fn is_file(&self, root: &str, rel: &str) -> bool {
+        self.fs
+            .metadata(&paths::resolve(root, rel))
+            .await
+            .map(|m| m.is_file)
+            .unwrap_or(false)
+    }
+}
+
+impl_overrides!(ProjectInfoTool);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for ProjectInfoTool {
+    fn name(&self) -> &str {
+        self.overrides.name("project_info")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "project_info".into(),
+            description: "Summarize a project in one call: detected build systems (Cargo.toml, package.json, pyproject.toml, go.mod, ...), likely entry points, the first lines of the README and a depth-2 directory tree. Use at the start of a session instead of several ls/find/read calls.".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "Project root (defaults to working directory)"
+                    },
+                    "max_bytes": {
+                        "type": "integer",
+                        "description": format!("Size budget for the report (default: {}, at most {})", DEFAULT_MAX_BYTES, MAX_BYTES)
+                    }
+                }
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let path = arguments.get("path").and_then(|v| v.as_str()).unwrap_or("");
+        let root = resolve_path(&self.cwd, path);
+        let budget = arguments
+            .get("max_bytes")
+            .and_then(|v| v.as_u64())
+            .map_or(self.max_bytes, |b| b as usize)
+            .min(MAX_BYTES);
+
+        let is_dir = self
+            .fs
+            .metadata(&root)
+            .await
+            .map(|m| m.is_dir)
+            .unwrap_or(false);
+        if !is_dir {
+            return Ok(tool_error(
+                ToolErrorKind::NotFound,
+                format!("Directory not found: {}", paths::display(&root, &self.cwd)),
+            ));
+        }
+
+        let mut systems = Vec::new();
+        for &(file, name, language) in BUILD_SYSTEMS {
+            if let Some(content) = self.read(&root, file).await {
+                systems.push(BuildSystem {
+                    file,
+                    name,
+                    language,
+                    details: manifest_details(file, &content),
+                });
+            }
+        }
+
+        let mut entry_points = Vec::new();
+        for &candidate in ENTRY_POINTS {
+            if self.is_file(&root, candidate).await {
+                entry_points.push(candidate.to_string());
+            }
+        }
+        // package.json can name its own entry point
+        if let Some(main) = self.read(&root, "package.json").await.and_then(|c| {
+            let json: serde_json::Value = serde_json::from_str(&c).ok()?;
+            Some(
+                json.get("main")?
+                    .as_str()?
+                    .trim_start_matches("./")
+                    .to_string(),
+            )
+        }) {
+            if !entry_points.contains(&main) && self.is_file(&root, &main).await {
+                entry_points.push(main);
+            }
+        }
+
+        let mut readme = None;
+        for &name in READMES {
+            if let Some(content) = self.read(&root, name).await {
+                readme = Some((name, content));
+                break;
+            }
+        }
+
+        let mut out = format!("# Project: {}\n", paths::display(&root, &self.cwd));
+        out.push_str("\n## Build system\n");
+        if systems.is_empty() {
+            out.push_str("(none detected)\n");
+        }
+        for system in &systems {
+            out.push_str(&format!("- {}: {}", system.file, system.name));
+            if !system.language.is_empty() {
+                out.push_str(&format!(" ({})", system.language));
+            }
+            if let Some(details) = &system.details {
+                out.push_str(&format!(" — {}", details));
+            }
+            out.push('\n');
+        }
+        out.push_str("\n## Entry points\n");
+        if entry_points.is_empty() {
+            out.push_str("(none found)\n");
+        }
+        for entry in &entry_points {
+            out.push_str(&format!("- {}\n", entry));
+        }
+
+        let mut truncated = false;
+        if let Some((name, content)) = &readme {
+            let head = truncate_head(
+                content,
+                README_LINES,
+                README_MAX_BYTES.min(budget.saturating_sub(out.len()) / 2),
+            );
+            truncated |= head.is_truncated();
+            out.push_str(&format!(
+                "\n## {} (first {} lines)\n",
+                name, head.output_lines
+            ));
+            out.push_str(head.content.trim_end());
+            out.push('\n');
+        }
+
+        let options = ManifestOptions::new()
+            .with_max_depth(2)
+            .with_max_entries(TREE_MAX_ENTRIES);
+        let tree = build_manifest(self.fs.as_ref(), &root, &options).await?;
+        let mut listing = String::new();
+        render_tree(&tree, 0, &mut listing);
+        out.push_str("\n## Tree (depth 2)\n");
+        let room = budget.saturating_sub(out.len() + "...\n".len());
+        let tree_text = truncate_head(&listing, listing.lines().count(), room);
+        truncated |= tree_text.is_truncated() || tree.is_truncated();
+        out.push_str(&tree_text.content);
+        if tree_text.is_truncated() {
+            out.push_str("...\n");
+        }
+
+        let systems_meta: Vec<_> = systems
+            .iter()
+            .map(|s| json!({"file": s.file, "name": s.name, "language": s.language}))
+            .collect();
+        Ok(
+            ToolOutput::success(out.trim_end().to_string()).with_metadata(json!({
+                "root": root,
+                "build_systems": systems_meta,
+                "entry_points": entry_points,
+                "readme": readme.map(|(name, _)| name),
+                "truncated": truncated,
+            })),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    async fn setup() -> (Arc<MemoryFs>, ProjectInfoTool) {
+        let fs = Arc::new(MemoryFs::new());
+        let tool = ProjectInfoTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        (fs, tool)
+    }
+
+    #[tokio::test]
+    async fn project_info_summarizes_workspace() {
+        let (fs, tool) = setup().await;
+        for (path, content) in [
+            (
+                "/project/Cargo.toml",
+                "[package]\n\nname = \"demo\"\nversion = \"0.1.0\"\n",
+            ),
+            (
+                "/project/package.json",
+                r#"{"name": "demo-web", "main": "web/app.js", "scripts": {"build": "vite"}}"#,
+            ),
+            ("/project/web/app.js", ""),
+            ("/project/README.md", "# Demo\n\nA demo project.\n"),
+            ("/project/src/main.rs", "fn main() {}"),
+            ("/project/src/cli/args.rs", ""),
+            ("/project/src/cli/deep/x.rs", ""),
+            ("/project/.git/HEAD", "ref"),
+        ] {
+            fs.write(path, content).await.unwrap();
+        }
+
+        let result = tool.execute("c1", json!({}), None).await.unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        let out = &result.content;
+        assert!(out.contains("- Cargo.toml: Cargo (Rust) — package demo\n"));
+        assert!(out.contains("— package demo-web; scripts: build\n"));
+        assert!(out.contains("## Entry points\n- src/main.rs\n- web/app.js\n"));
+        assert!(out.contains("## README.md (first 3 lines)\n# Demo\n\nA demo project.\n"));
+        assert!(out.contains("src/\n  cli/\n  main.rs\n"));
+        assert!(!out.contains("deep") && !out.contains(".git"));
+        assert_eq!(result.metadata["build_systems"][1]["name"], "npm");
+        assert_eq!(result.metadata["truncated"], false);
+    }
+
+    #[tokio::test]
+    async fn project_info_respects_budget() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/README", &"long readme line\n".repeat(50))
+            .await
+            .unwrap();
+        for i in 0..100 {
+            fs.write(&format!("/project/file{}.txt", i), "")
+                .await
+                .unwrap();
+        }
+        let result = tool
+            .execute("c1", json!({"max_bytes": 600}), None)
+            .await
+            .unwrap();
+        assert!(result.content.len() <= 600, "{}", result.content.len());
+        assert!(result.content.contains("(none detected)"));
+        assert!(result.content.ends_with("..."));
+        assert_eq!(result.metadata["truncated"], true);
+
+        let result = tool
+            .execute("c2", json!({"path": "missing"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+    }
+
+    #[tokio::test]
+    async fn tool_name_and_definition() {
+        let (_fs, tool) = setup().await;
+        assert_eq!(tool.name(), "project_info");
+        assert_eq!(tool.definition().name, "project_info");
+    }
+}
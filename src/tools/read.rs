@@ -1,4 +1,20 @@
 //! Read tool — read file contents with line numbers, offset, and truncation.
+//!
+//! `byte_offset`/`byte_limit` read a byte range instead, for files where
+//! lines are useless as units (minified JS, one-line JSON blobs). Line reads
+//! cut such lines short (`…[+48,200 chars]`) so one of them can't use up the
+//! whole output budget; metadata lists the lines that were cut.
+//!
+//! `ranges` reads several line ranges in one call, e.g. a function and its
+//! call site, each with its own line numbers.
+//!
+//! Line, range and symbol reads also report the file's line endings, byte order
+//! mark, trailing newline and indentation under `conventions` (see
+//! [`crate::conventions`]), so edits can match them on the first try.
+//!
+//! `pretty` re-indents a JSON or JSON Lines file before reading it by line
+//! (see [`crate::pretty`]); line numbers then count lines of the re-indented
+//! view, not of the file.
 
 use std::sync::Arc;
 
@@ -11,13 +27,30 @@ use soul_core::tool::{Tool, ToolOutput};
 use soul_core::types::ToolDefinition;
 use soul_core::vfs::VirtualFs;
 
-use crate::truncate::{add_line_numbers, truncate_head, MAX_BYTES, MAX_LINES};
+use crate::conventions::FileConventions;
+use crate::error::{tool_error, ToolErrorKind};
+use crate::notebook::{is_notebook, Notebook};
+use crate::outline::{extract_outline, find_symbol, Language};
+use crate::pretty::{is_json_path, pretty_json_auto};
+use crate::truncate::{
+    add_line_numbers_styled, clip_line, truncate_head, truncate_tail, LineNumberStyle, MAX_BYTES,
+    MAX_LINES, READ_MAX_LINE_LENGTH,
+};
 
-use super::resolve_path;
+use super::{impl_overrides, resolve_path, ToolOverrides};
+
+/// System-prompt guidance returned by [`ReadTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Read a file before editing it. For large files, run outline (or pass symbol) \
+    first and read only the range you need with offset and limit. Lines come back \
+    with line-number prefixes that are not part of the file.";
 
 pub struct ReadTool {
     fs: Arc<dyn VirtualFs>,
     cwd: String,
+    line_numbers: LineNumberStyle,
+    max_line_length: usize,
+    overrides: ToolOverrides,
 }
 
 impl ReadTool {
@@ -25,21 +58,426 @@ impl ReadTool {
         Self {
             fs,
             cwd: cwd.into(),
+            line_numbers: LineNumberStyle::default(),
+            max_line_length: READ_MAX_LINE_LENGTH,
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+    /// Gutter used when a call doesn't pass `line_numbers` (default: `cat -n`
+    /// style, six columns and a tab).
+    pub fn with_line_numbers(mut self, style: LineNumberStyle) -> Self {
+        self.line_numbers = style;
+        self
+    }
+
+    /// Cut lines longer than `chars` characters, noting how many were left
+    /// out (default: [`READ_MAX_LINE_LENGTH`]; `usize::MAX` keeps lines
+    /// whole). Byte-range reads are never cut.
+    pub fn with_max_line_length(mut self, chars: usize) -> Self {
+        self.max_line_length = chars;
+        self
+    }
+}
+
+impl_overrides!(ReadTool);
+
+/// Maximum symbol names suggested when a lookup fails.
+const MAX_SYMBOL_SUGGESTIONS: usize = 20;
+
+/// Maximum cut line numbers listed in the output notice.
+const MAX_LISTED_LONG_LINES: usize = 10;
+
+/// Maximum ranges in one `ranges` read.
+const MAX_RANGES: usize = 20;
+
+/// Line between two ranges of a `ranges` read.
+const RANGE_SEPARATOR: &str = "...";
+
+/// Lines shown from each end of a file in a `summary` read.
+const SUMMARY_LINES: usize = 50;
+
+/// Maximum outline items listed for the middle of a `summary` read.
+const MAX_SUMMARY_ITEMS: usize = 200;
+
+/// Parse a `ranges` argument: `[[start, end], ...]`, 1-indexed and
+/// inclusive. Returns them sorted, with overlapping and adjacent ranges
+/// merged.
+fn parse_ranges(value: &serde_json::Value) -> Result<Vec<(usize, usize)>, String> {
+    let items = value
+        .as_array()
+        .filter(|items| !items.is_empty())
+        .ok_or("ranges must be a non-empty array of [start, end] pairs")?;
+    if items.len() > MAX_RANGES {
+        return Err(format!("At most {} ranges per read", MAX_RANGES));
+    }
+    let mut ranges = Vec::new();
+    for item in items {
+        let pair = item
+            .as_array()
+            .filter(|pair| pair.len() == 2)
+            .and_then(|pair| Some((pair[0].as_u64()? as usize, pair[1].as_u64()? as usize)));
+        match pair {
+            Some((start, end)) if start >= 1 && end >= start => ranges.push((start, end)),
+            _ => {
+                return Err(format!(
+                    "Invalid range {}: expected [start, end] with 1 <= start <= end",
+                    item
+                ))
+            }
+        }
+    }
+    ranges.sort();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
         }
     }
+    Ok(merged)
+}
+
+/// Join `lines`, the first being line `first_line`, cutting those longer
+/// than `max_chars`. Also returns the numbers of the lines cut.
+fn clip_lines(lines: &[&str], first_line: usize, max_chars: usize) -> (String, Vec<usize>) {
+    let mut cut = Vec::new();
+    let clipped: Vec<std::borrow::Cow<str>> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| match clip_line(line, max_chars) {
+            Some(clipped) => {
+                cut.push(first_line + i);
+                clipped.into()
+            }
+            None => (*line).into(),
+        })
+        .collect();
+    (clipped.join("\n"), cut)
+}
+
+/// Notice for lines cut by [`clip_lines`], or `None` if there were none.
+fn long_lines_notice(cut: &[usize], max_chars: usize) -> Option<String> {
+    let (first, rest) = cut.split_first()?;
+    let mut listed = first.to_string();
+    for line in rest.iter().take(MAX_LISTED_LONG_LINES - 1) {
+        listed.push_str(&format!(", {}", line));
+    }
+    if cut.len() > MAX_LISTED_LONG_LINES {
+        listed.push_str(&format!(" and {} more", cut.len() - MAX_LISTED_LONG_LINES));
+    }
+    Some(format!(
+        "[{} {} cut at {} characters; read with byte_offset to see {} in full]",
+        if cut.len() == 1 { "Line" } else { "Lines" },
+        listed,
+        max_chars,
+        if cut.len() == 1 { "it" } else { "them" }
+    ))
+}
+
+impl ReadTool {
+    /// Symbol mode: return just the named definition with its real line numbers.
+    fn read_symbol(
+        &self,
+        path: &str,
+        content: &str,
+        symbol: &str,
+        style: LineNumberStyle,
+    ) -> ToolOutput {
+        let Some(language) = Language::from_path(path) else {
+            return tool_error(
+                ToolErrorKind::Unsupported,
+                format!(
+                    "Symbol lookup is not supported for {}. Use offset/limit instead.",
+                    path
+                ),
+            );
+        };
+
+        let spans = find_symbol(content, language, symbol);
+        let Some(span) = spans.first() else {
+            let names: Vec<String> = extract_outline(content, language)
+                .into_iter()
+                .filter(|i| i.depth == 0)
+                .map(|i| i.name)
+                .take(MAX_SYMBOL_SUGGESTIONS)
+                .collect();
+            return tool_error(
+                ToolErrorKind::NotFound,
+                format!(
+                    "Symbol '{}' not found in {}. Top-level symbols: {}",
+                    symbol,
+                    path,
+                    names.join(", ")
+                ),
+            );
+        };
+
+        let lines: Vec<&str> = content.lines().collect();
+        let (section, mut long_lines) =
+            clip_lines(&lines[span.start - 1..span.end], span.start, self.max_line_length);
+        let truncated = truncate_head(&section, MAX_LINES, MAX_BYTES);
+        long_lines.retain(|&line| line < span.start + truncated.output_lines);
+        let mut output = add_line_numbers_styled(&truncated.content, span.start, style);
+        if let Some(notice) = truncated.truncation_notice() {
+            output.push('\n');
+            output.push_str(&notice);
+        }
+        if let Some(notice) = long_lines_notice(&long_lines, self.max_line_length) {
+            output.push('\n');
+            output.push_str(&notice);
+        }
+        if spans.len() > 1 {
+            let others: Vec<String> = spans[1..].iter().map(|s| s.line.to_string()).collect();
+            output.push_str(&format!(
+                "\n[{} is also defined at line(s) {}]",
+                symbol,
+                others.join(", ")
+            ));
+        }
+
+        ToolOutput::success(output).with_metadata(json!({
+            "total_lines": lines.len(),
+            "symbol": span.name,
+            "kind": span.kind,
+            "start_line": span.start,
+            "end_line": span.end,
+            "matches": spans.len(),
+            "truncated": truncated.is_truncated(),
+            "truncation": truncated.to_metadata(),
+            "long_lines": long_lines,
+            "conventions": FileConventions::detect(content),
+        }))
+    }
+
+    /// Summary mode, for files too large to read whole: the first and last
+    /// [`SUMMARY_LINES`] lines, with an outline of the definitions and
+    /// headings between them.
+    fn read_summary(&self, path: &str, content: &str, style: LineNumberStyle) -> ToolOutput {
+        let lines: Vec<&str> = content.lines().collect();
+        let total_lines = lines.len();
+        let edge = SUMMARY_LINES.min(total_lines / 2);
+
+        let (head, mut long_lines) = clip_lines(&lines[..edge], 1, self.max_line_length);
+        let head = truncate_head(&head, edge, MAX_BYTES / 2);
+        let (tail, tail_long) =
+            clip_lines(&lines[total_lines - edge..], total_lines - edge + 1, self.max_line_length);
+        let tail = truncate_tail(&tail, edge, MAX_BYTES / 2);
+        let hidden_start = head.output_lines + 1;
+        let tail_start = total_lines - tail.output_lines + 1;
+        long_lines.retain(|&line| line < hidden_start);
+        long_lines.extend(tail_long.into_iter().filter(|&line| line >= tail_start));
+
+        let items: Vec<_> = match Language::from_path(path) {
+            Some(language) => extract_outline(content, language)
+                .into_iter()
+                .filter(|item| (hidden_start..tail_start).contains(&item.line))
+                .collect(),
+            None => Vec::new(),
+        };
+        let mut middle = match (Language::from_path(path), items.is_empty()) {
+            (None, _) => format!(
+                "[Lines {}-{} not shown; no outline is available for this file type]",
+                hidden_start,
+                tail_start - 1
+            ),
+            (Some(_), true) => format!(
+                "[Lines {}-{} not shown; they hold no definitions or headings]",
+                hidden_start,
+                tail_start - 1
+            ),
+            (Some(_), false) => format!(
+                "[Lines {}-{} not shown; they contain:]",
+                hidden_start,
+                tail_start - 1
+            ),
+        };
+        for item in items.iter().take(MAX_SUMMARY_ITEMS) {
+            middle.push_str(&format!(
+                "\n  line {}: {}{} {}",
+                item.line,
+                "  ".repeat(item.depth),
+                item.kind,
+                item.name
+            ));
+        }
+        if items.len() > MAX_SUMMARY_ITEMS {
+            middle.push_str(&format!("\n  [{} more]", items.len() - MAX_SUMMARY_ITEMS));
+        }
+
+        let mut output = format!(
+            "{}\n{}\n{}\n[Summary of {} lines. Read a part with offset and limit{}]",
+            add_line_numbers_styled(&head.content, 1, style),
+            middle,
+            add_line_numbers_styled(&tail.content, tail_start, style),
+            total_lines,
+            if items.is_empty() { "" } else { ", or a definition with symbol" }
+        );
+        if let Some(notice) = long_lines_notice(&long_lines, self.max_line_length) {
+            output.push('\n');
+            output.push_str(&notice);
+        }
+
+        ToolOutput::success(output).with_metadata(json!({
+            "total_lines": total_lines,
+            "summary": true,
+            "head_lines": head.output_lines,
+            "tail_start": tail_start,
+            "outline": items,
+            "long_lines": long_lines,
+            "conventions": FileConventions::detect(content),
+        }))
+    }
+
+    /// Ranges mode: several line ranges, each with its real line numbers,
+    /// separated by [`RANGE_SEPARATOR`] and sharing one output budget.
+    fn read_ranges(
+        &self,
+        content: &str,
+        ranges: &[(usize, usize)],
+        style: LineNumberStyle,
+    ) -> ToolOutput {
+        let lines: Vec<&str> = content.lines().collect();
+        if let Some((start, _)) = ranges.iter().find(|(start, _)| *start > lines.len()) {
+            return tool_error(
+                ToolErrorKind::InvalidArguments,
+                format!(
+                    "Range starting at line {} exceeds file length ({} lines)",
+                    start,
+                    lines.len()
+                ),
+            );
+        }
+
+        let mut sections = Vec::new();
+        let mut returned = Vec::new();
+        let mut long_lines = Vec::new();
+        let (mut lines_left, mut bytes_left) = (MAX_LINES, MAX_BYTES);
+        let mut stopped_at = None;
+        for &(start, end) in ranges {
+            let end = end.min(lines.len());
+            let (section, mut cut) =
+                clip_lines(&lines[start - 1..end], start, self.max_line_length);
+            let truncated = truncate_head(&section, lines_left, bytes_left);
+            if truncated.output_lines > 0 {
+                let last = start + truncated.output_lines - 1;
+                cut.retain(|&line| line <= last);
+                long_lines.extend(cut);
+                sections.push(add_line_numbers_styled(&truncated.content, start, style));
+                returned.push([start, last]);
+                lines_left -= truncated.output_lines;
+                bytes_left = bytes_left.saturating_sub(truncated.output_bytes + 1);
+            }
+            if truncated.is_truncated() {
+                stopped_at = Some(start + truncated.output_lines);
+                break;
+            }
+        }
+
+        let mut output = sections.join(&format!("\n{}\n", RANGE_SEPARATOR));
+        if let Some(line) = stopped_at {
+            output.push_str(&format!(
+                "\n[Output limit reached at line {}; read the rest with offset={}]",
+                line, line
+            ));
+        }
+        if let Some(notice) = long_lines_notice(&long_lines, self.max_line_length) {
+            output.push('\n');
+            output.push_str(&notice);
+        }
+
+        ToolOutput::success(output).with_metadata(json!({
+            "total_lines": lines.len(),
+            "ranges": returned,
+            "truncated": stopped_at.is_some(),
+            "long_lines": long_lines,
+            "conventions": FileConventions::detect(content),
+        }))
+    }
+
+    /// Notebook mode: render cells (or one cell) with outputs summarized.
+    fn read_notebook(&self, path: &str, notebook: &Notebook, cell: Option<usize>) -> ToolOutput {
+        let Some(rendered) = notebook.render(cell) else {
+            return tool_error(
+                ToolErrorKind::InvalidArguments,
+                format!(
+                    "Cell {} out of range ({} has {} cells)",
+                    cell.unwrap_or_default(),
+                    path,
+                    notebook.len()
+                ),
+            );
+        };
+
+        let truncated = truncate_head(&rendered, MAX_LINES, MAX_BYTES);
+        let mut output = truncated.content.clone();
+        if let Some(notice) = truncated.truncation_notice() {
+            output.push('\n');
+            output.push_str(&notice);
+            output.push_str("\n[Pass cell to read a single cell]");
+        }
+
+        ToolOutput::success(output).with_metadata(json!({
+            "notebook": true,
+            "cells": notebook.len(),
+            "cell": cell,
+            "truncated": truncated.is_truncated(),
+            "truncation": truncated.to_metadata(),
+        }))
+    }
+
+    /// Byte range mode: `limit` bytes from `offset`, with both ends moved
+    /// inward to the nearest char boundary so no character is split.
+    fn read_bytes(&self, path: &str, content: &str, offset: usize, limit: usize) -> ToolOutput {
+        let total = content.len();
+        if offset > total || (offset == total && total > 0) {
+            return tool_error(
+                ToolErrorKind::InvalidArguments,
+                format!("byte_offset {} exceeds file length ({} bytes)", offset, total),
+            );
+        }
+        let mut start = offset;
+        while !content.is_char_boundary(start) {
+            start += 1;
+        }
+        let mut end = offset.saturating_add(limit).min(total).max(start);
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        // A limit smaller than the character at start would return nothing
+        if end == start && start < total {
+            end = start + content[start..].chars().next().map_or(0, char::len_utf8);
+        }
+
+        let mut output = content[start..end].to_string();
+        let remaining = total - end;
+        if remaining > 0 {
+            output.push_str(&format!(
+                "\n[Bytes {}-{} of {} in {}. To continue reading: byte_offset={}]",
+                start, end, total, path, end
+            ));
+        }
+        ToolOutput::success(output).with_metadata(json!({
+            "total_bytes": total,
+            "byte_range": [start, end],
+            "bytes_returned": end - start,
+            "remaining_bytes": remaining,
+            "next_byte_offset": if remaining > 0 { Some(end) } else { None },
+        }))
+    }
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl Tool for ReadTool {
     fn name(&self) -> &str {
-        "read"
+        self.overrides.name("read")
     }
 
     fn definition(&self) -> ToolDefinition {
-        ToolDefinition {
+        self.overrides.apply(ToolDefinition {
             name: "read".into(),
-            description: "Read the contents of a file. Returns line-numbered output. Use offset and limit for large files.".into(),
+            description: "Read the contents of a file. Returns line-numbered output. Use offset and limit (or page) for large files; metadata reports remaining_lines and next_offset. Pass symbol to read a single definition, or summary to see both ends and an outline of a file too large to read whole. Jupyter notebooks (.ipynb) are rendered as numbered cells with outputs summarized; pass cell to read one, or offset/limit for the raw JSON.".into(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -54,11 +492,54 @@ impl Tool for ReadTool {
                     "limit": {
                         "type": "integer",
                         "description": "Number of lines to read"
+                    },
+                    "page": {
+                        "type": "integer",
+                        "description": "1-indexed page of `limit` lines (default 2000); alternative to offset"
+                    },
+                    "ranges": {
+                        "type": "array",
+                        "items": {
+                            "type": "array",
+                            "items": {"type": "integer"},
+                            "minItems": 2,
+                            "maxItems": 2
+                        },
+                        "description": "Several 1-indexed, inclusive line ranges to read at once, e.g. [[10, 40], [200, 260]] for a function and its call site; alternative to offset/limit"
+                    },
+                    "symbol": {
+                        "type": "string",
+                        "description": "Return only this function/type/class/heading (e.g. 'resolve_path', 'Config::new', 'Server.start')"
+                    },
+                    "cell": {
+                        "type": "integer",
+                        "description": "0-indexed cell to read from a .ipynb notebook"
+                    },
+                    "byte_offset": {
+                        "type": "integer",
+                        "description": "0-indexed byte to start reading from, for files with very long lines (minified code, JSON blobs); returns the raw text without line numbers"
+                    },
+                    "byte_limit": {
+                        "type": "integer",
+                        "description": format!("Number of bytes to read with byte_offset (default and maximum: {})", MAX_BYTES)
+                    },
+                    "summary": {
+                        "type": "boolean",
+                        "description": "For a file too large to read whole, return its first and last 50 lines and an outline of what lies between, instead of just the beginning (default: false)"
+                    },
+                    "pretty": {
+                        "type": "boolean",
+                        "description": "Re-indent a JSON or JSON Lines file (minified configs, one-line dumps) before reading it; keys keep their order and values are shown as written. Line numbers then refer to the re-indented view, so edit by text, not line (default: false)"
+                    },
+                    "line_numbers": {
+                        "type": "string",
+                        "enum": ["tab", "compact", "pipe", "none"],
+                        "description": "Line number gutter: tab (six columns and a tab), compact (just wide enough, then a tab), pipe (just wide enough, then '|') or none. The gutter is never part of the file."
                     }
                 },
                 "required": ["path"]
             }),
-        }
+        })
     }
 
     async fn execute(
@@ -73,85 +554,280 @@ impl Tool for ReadTool {
             .unwrap_or("");
 
         if path.is_empty() {
-            return Ok(ToolOutput::error("Missing required parameter: path"));
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Missing required parameter: path",
+            ));
         }
 
+        let style = match arguments.get("line_numbers").and_then(|v| v.as_str()) {
+            Some(name) => match LineNumberStyle::from_name(name) {
+                Some(style) => style,
+                None => {
+                    return Ok(tool_error(
+                        ToolErrorKind::InvalidArguments,
+                        format!(
+                            "Unknown line_numbers: {} (expected tab, compact, pipe or none)",
+                            name
+                        ),
+                    ))
+                }
+            },
+            None => self.line_numbers,
+        };
+
         let resolved = resolve_path(&self.cwd, path);
 
         let exists = self.fs.exists(&resolved).await?;
         if !exists {
-            return Ok(ToolOutput::error(format!("File not found: {}", path)));
+            return Ok(tool_error(
+                ToolErrorKind::NotFound,
+                format!("File not found: {}", path),
+            ));
         }
 
         let content = match self.fs.read_to_string(&resolved).await {
             Ok(c) => c,
-            Err(e) => return Ok(ToolOutput::error(format!("Failed to read {}: {}", path, e))),
+            Err(e) => {
+                return Ok(tool_error(
+                    ToolErrorKind::from_soul_error(&e),
+                    format!("Failed to read {}: {}", path, e),
+                ))
+            }
+        };
+
+        let pretty = arguments.get("pretty").and_then(|v| v.as_bool()) == Some(true);
+        // Conventions describe the file itself, not the re-indented view
+        let (content, conventions) = if pretty {
+            let conflict = ["byte_offset", "byte_limit", "ranges", "symbol", "summary", "cell"]
+                .into_iter()
+                .find(|key| arguments.get(*key).is_some());
+            if let Some(key) = conflict {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!("pretty can't be combined with {}", key),
+                ));
+            }
+            match pretty_json_auto(path, &content) {
+                Ok(view) => (view, Some(FileConventions::detect(&content))),
+                Err(e) => {
+                    return Ok(tool_error(
+                        ToolErrorKind::InvalidArguments,
+                        format!("pretty needs a JSON or JSON Lines file; {}: {}", path, e),
+                    ))
+                }
+            }
+        } else {
+            (content, None)
         };
 
-        let offset = arguments
-            .get("offset")
+        let byte_offset = arguments.get("byte_offset").and_then(|v| v.as_u64());
+        let byte_limit = arguments.get("byte_limit").and_then(|v| v.as_u64());
+        if byte_offset.is_some() || byte_limit.is_some() {
+            let conflict = ["offset", "limit", "page", "symbol", "cell", "ranges"]
+                .into_iter()
+                .find(|key| arguments.get(*key).is_some());
+            if let Some(key) = conflict {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!("byte_offset/byte_limit can't be combined with {}", key),
+                ));
+            }
+            let limit = byte_limit.map_or(MAX_BYTES, |l| (l as usize).min(MAX_BYTES));
+            return Ok(self.read_bytes(path, &content, byte_offset.unwrap_or(0) as usize, limit));
+        }
+
+        if let Some(ranges) = arguments.get("ranges").filter(|v| !v.is_null()) {
+            let conflict = ["offset", "limit", "page", "symbol", "cell"]
+                .into_iter()
+                .find(|key| arguments.get(*key).is_some());
+            if let Some(key) = conflict {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!("ranges can't be combined with {}", key),
+                ));
+            }
+            return Ok(match parse_ranges(ranges) {
+                Ok(ranges) => self.read_ranges(&content, &ranges, style),
+                Err(message) => tool_error(ToolErrorKind::InvalidArguments, message),
+            });
+        }
+
+        if let Some(symbol) = arguments
+            .get("symbol")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.trim().is_empty())
+        {
+            if arguments.get("offset").is_some() || arguments.get("page").is_some() {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    "Use either symbol or offset/page, not both",
+                ));
+            }
+            return Ok(self.read_symbol(path, &content, symbol, style));
+        }
+
+        if arguments.get("summary").and_then(|v| v.as_bool()) == Some(true) {
+            let conflict = ["offset", "limit", "page", "cell"]
+                .into_iter()
+                .find(|key| arguments.get(*key).is_some());
+            if let Some(key) = conflict {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!("summary can't be combined with {}", key),
+                ));
+            }
+            // Files that fit are simply read whole
+            if content.lines().count() > MAX_LINES || content.len() > MAX_BYTES {
+                return Ok(self.read_summary(path, &content, style));
+            }
+        }
+
+        let cell = arguments
+            .get("cell")
             .and_then(|v| v.as_u64())
-            .map(|v| v as usize)
-            .unwrap_or(1);
+            .map(|v| v as usize);
+        let raw_range = arguments.get("offset").is_some() || arguments.get("page").is_some();
+        if is_notebook(path) && !raw_range && !pretty {
+            match Notebook::parse(&content) {
+                Ok(notebook) => return Ok(self.read_notebook(path, &notebook, cell)),
+                Err(e) if cell.is_some() => {
+                    return Ok(tool_error(ToolErrorKind::Encoding, format!("{}: {}", path, e)))
+                }
+                // Not valid notebook JSON: show it as plain text
+                Err(_) => {}
+            }
+        } else if cell.is_some() {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "cell only applies to .ipynb notebooks (and not with offset/page)",
+            ));
+        }
 
         let limit = arguments
             .get("limit")
             .and_then(|v| v.as_u64())
             .map(|v| v as usize);
+        if limit == Some(0) {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "limit must be >= 1",
+            ));
+        }
+
+        let page = arguments
+            .get("page")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize);
+
+        let offset = match (arguments.get("offset").and_then(|v| v.as_u64()), page) {
+            (Some(_), Some(_)) => {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    "Use either offset or page, not both",
+                ));
+            }
+            (_, Some(0)) => {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    "page must be >= 1",
+                ))
+            }
+            (_, Some(p)) => {
+                let start = (p - 1)
+                    .checked_mul(limit.unwrap_or(MAX_LINES))
+                    .and_then(|line| line.checked_add(1));
+                match start {
+                    Some(start) => start,
+                    None => {
+                        return Ok(tool_error(
+                            ToolErrorKind::InvalidArguments,
+                            format!("page {} is out of range", p),
+                        ))
+                    }
+                }
+            }
+            (Some(o), None) => o as usize,
+            (None, None) => 1,
+        };
 
         let total_lines = content.lines().count();
 
         if offset < 1 {
-            return Ok(ToolOutput::error("offset must be >= 1"));
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "offset must be >= 1",
+            ));
         }
 
         // Extract the requested range
         let lines: Vec<&str> = content.lines().collect();
         let start_idx = (offset - 1).min(lines.len());
         let end_idx = match limit {
-            Some(l) => (start_idx + l).min(lines.len()),
+            Some(l) => start_idx.saturating_add(l).min(lines.len()),
             None => lines.len(),
         };
 
         if start_idx >= lines.len() {
-            return Ok(ToolOutput::error(format!(
-                "offset {} exceeds file length ({} lines)",
-                offset, total_lines
-            )));
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                format!("offset {} exceeds file length ({} lines)", offset, total_lines),
+            ));
         }
 
-        let selected: String = lines[start_idx..end_idx].join("\n");
+        // Cut overlong lines first, so one minified line can't use up the
+        // byte budget on its own
+        let (selected, mut long_lines) =
+            clip_lines(&lines[start_idx..end_idx], offset, self.max_line_length);
 
         // Apply truncation
         let max_lines = limit.unwrap_or(MAX_LINES).min(MAX_LINES);
         let result = truncate_head(&selected, max_lines, MAX_BYTES);
+        long_lines.retain(|&line| line < offset + result.output_lines);
 
-        let numbered = add_line_numbers(&result.content, offset);
+        let numbered = add_line_numbers_styled(&result.content, offset, style);
 
         let mut output = numbered;
 
-        if result.is_truncated() {
-            if let Some(notice) = result.truncation_notice() {
-                output.push('\n');
-                output.push_str(&notice);
-            }
-            // Suggest next read parameters
-            let next_offset = offset + result.output_lines;
-            let remaining = total_lines.saturating_sub(next_offset - 1);
-            if remaining > 0 {
-                output.push_str(&format!(
-                    "\n[To continue reading: offset={}, limit={}]",
-                    next_offset,
-                    remaining.min(MAX_LINES)
-                ));
+        if let Some(notice) = result.truncation_notice() {
+            output.push('\n');
+            output.push_str(&notice);
+        }
+        if let Some(notice) = long_lines_notice(&long_lines, self.max_line_length) {
+            output.push('\n');
+            output.push_str(&notice);
+            if is_json_path(path) {
+                output.push_str("\n[Or pass pretty: true to read this JSON re-indented]");
             }
         }
+        if pretty {
+            output.push_str("\n[Re-indented view: line numbers don't match the file]");
+        }
+
+        // Suggest next read parameters whenever lines remain, whether the
+        // range was cut by truncation or by an explicit limit
+        let next_offset = offset + result.output_lines;
+        let remaining = total_lines.saturating_sub(next_offset - 1);
+        if remaining > 0 {
+            output.push_str(&format!(
+                "\n[{} more lines. To continue reading: offset={}, limit={}]",
+                remaining,
+                next_offset,
+                remaining.min(max_lines)
+            ));
+        }
 
         Ok(ToolOutput::success(output).with_metadata(json!({
             "total_lines": total_lines,
             "offset": offset,
             "lines_returned": result.output_lines,
             "truncated": result.is_truncated(),
+            "truncation": result.to_metadata(),
+            "long_lines": long_lines,
+            "remaining_lines": remaining,
+            "next_offset": if remaining > 0 { Some(next_offset) } else { None },
+            "pretty": pretty,
+            "conventions": conventions.unwrap_or_else(|| FileConventions::detect(&content)),
         })))
     }
 }
@@ -185,6 +861,28 @@ mod tests {
         assert!(result.content.contains("line3"));
     }
 
+    #[tokio::test]
+    async fn read_reports_conventions() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/main.go", "func main() {\r\n\tfmt.Println()\r\n}\r\n")
+            .await
+            .unwrap();
+
+        let result = tool
+            .execute("c1", json!({"path": "main.go", "offset": 2}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.metadata["conventions"],
+            json!({
+                "line_endings": "crlf",
+                "bom": false,
+                "trailing_newline": true,
+                "indent": {"style": "tabs"},
+            })
+        );
+    }
+
     #[tokio::test]
     async fn read_with_offset_and_limit() {
         let (fs, tool) = setup().await;
@@ -202,6 +900,374 @@ mod tests {
         assert!(!result.content.contains("line5"));
     }
 
+    #[tokio::test]
+    async fn read_multiple_ranges() {
+        let (fs, tool) = setup().await;
+        let content = (1..=30).map(|i| format!("line{}", i)).collect::<Vec<_>>().join("\n");
+        fs.write("/project/big.txt", &content).await.unwrap();
+
+        // Overlapping ranges merge; the last one is clamped to the file
+        let result = tool
+            .execute(
+                "c1",
+                json!({
+                    "path": "big.txt",
+                    "ranges": [[20, 21], [2, 3], [3, 4], [29, 100]],
+                    "line_numbers": "pipe"
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(
+            result.content,
+            "2|line2\n3|line3\n4|line4\n...\n20|line20\n21|line21\n...\n29|line29\n30|line30"
+        );
+        assert_eq!(result.metadata["ranges"], json!([[2, 4], [20, 21], [29, 30]]));
+
+        for mut arguments in [
+            json!({"ranges": [[31, 40]]}),
+            json!({"ranges": [[5, 2]]}),
+            json!({"ranges": [[1, 2]], "offset": 1}),
+        ] {
+            arguments["path"] = json!("big.txt");
+            let result = tool.execute("c2", arguments, None).await.unwrap();
+            assert_eq!(
+                ToolErrorKind::of(&result),
+                Some(ToolErrorKind::InvalidArguments),
+                "{}",
+                result.content
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn read_line_number_styles() {
+        let (fs, tool) = setup().await;
+        let content = (1..=12).map(|i| format!("line{}", i)).collect::<Vec<_>>().join("\n");
+        fs.write("/project/big.txt", &content).await.unwrap();
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({"path": "big.txt", "offset": 9, "limit": 2, "line_numbers": "pipe"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(result.content.starts_with(" 9|line9\n10|line10\n"), "{}", result.content);
+
+        let result = tool
+            .execute("c2", json!({"path": "big.txt", "line_numbers": "none"}), None)
+            .await
+            .unwrap();
+        assert!(result.content.starts_with("line1\nline2\n"));
+
+        let result = tool
+            .execute("c3", json!({"path": "big.txt", "line_numbers": "wide"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::InvalidArguments));
+
+        let tool = ReadTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project")
+            .with_line_numbers(LineNumberStyle::Compact);
+        let result = tool
+            .execute("c4", json!({"path": "big.txt", "limit": 1}), None)
+            .await
+            .unwrap();
+        assert!(result.content.starts_with("1\tline1\n"), "{}", result.content);
+    }
+
+    #[tokio::test]
+    async fn read_limit_reports_remaining() {
+        let (fs, tool) = setup().await;
+        let content = (1..=10).map(|i| format!("line{}", i)).collect::<Vec<_>>().join("\n");
+        fs.write("/project/big.txt", &content).await.unwrap();
+
+        let result = tool
+            .execute("c2", json!({"path": "big.txt", "limit": 4}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["truncated"], false);
+        assert_eq!(result.metadata["truncation"]["strategy"], "head");
+        assert_eq!(result.metadata["truncation"]["output_lines"], 4);
+        assert_eq!(result.metadata["remaining_lines"], 6);
+        assert_eq!(result.metadata["next_offset"], 5);
+        assert!(result.content.contains("[6 more lines. To continue reading: offset=5, limit=4]"));
+
+        let result = tool
+            .execute("c2", json!({"path": "big.txt", "offset": 7}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["remaining_lines"], 0);
+        assert!(result.metadata["next_offset"].is_null());
+        assert!(!result.content.contains("To continue"));
+    }
+
+    #[tokio::test]
+    async fn read_cuts_long_lines() {
+        let (fs, tool) = setup().await;
+        let bundle = format!("var a=1;{}\nshort\n{}\n", "x".repeat(50_000), "y".repeat(2_500));
+        fs.write("/project/app.min.js", &bundle).await.unwrap();
+
+        let result = tool
+            .execute("c1", json!({"path": "app.min.js"}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        assert!(result.content.len() < 5_000, "{} bytes", result.content.len());
+        assert!(result.content.contains("x…[+48,008 chars]\n"));
+        assert!(result.content.contains("\tshort\n"));
+        assert!(result.content.contains("y…[+500 chars]"));
+        assert!(result
+            .content
+            .ends_with("[Lines 1, 3 cut at 2000 characters; read with byte_offset to see them in full]"));
+        assert_eq!(result.metadata["long_lines"], json!([1, 3]));
+        assert_eq!(result.metadata["truncated"], false);
+
+        let result = tool
+            .execute("c2", json!({"path": "app.min.js", "offset": 2, "limit": 1}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["long_lines"], json!([]));
+
+        let tool = ReadTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project")
+            .with_max_line_length(usize::MAX);
+        let result = tool
+            .execute("c3", json!({"path": "app.min.js", "limit": 1}), None)
+            .await
+            .unwrap();
+        assert!(result.content.len() > 50_000);
+    }
+
+    #[tokio::test]
+    async fn read_pretty_json() {
+        let (fs, tool) = setup().await;
+        let blob = format!(
+            r#"{{"name":"app","deps":{{"b":"2.0","a":"1.0"}},"pad":"{}"}}"#,
+            "x".repeat(3_000)
+        );
+        fs.write("/project/config.json", &blob).await.unwrap();
+
+        let result = tool
+            .execute("c1", json!({"path": "config.json"}), None)
+            .await
+            .unwrap();
+        assert!(result.content.ends_with("[Or pass pretty: true to read this JSON re-indented]"));
+
+        let args = json!({"path": "config.json", "pretty": true, "offset": 4, "limit": 3});
+        let result = tool.execute("c2", args, None).await.unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        // Keys keep the file's order
+        assert!(result
+            .content
+            .starts_with("     4\t    \"b\": \"2.0\",\n     5\t    \"a\": \"1.0\"\n     6\t  },"));
+        assert!(result.content.contains("[Re-indented view: line numbers don't match the file]"));
+        assert_eq!(result.metadata["pretty"], true);
+        assert_eq!(result.metadata["total_lines"], 8);
+        assert_eq!(result.metadata["conventions"]["trailing_newline"], false);
+
+        fs.write("/project/notes.md", "# not json\n").await.unwrap();
+        let result = tool
+            .execute("c3", json!({"path": "notes.md", "pretty": true}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            ToolErrorKind::of(&result),
+            Some(ToolErrorKind::InvalidArguments)
+        );
+    }
+
+    #[tokio::test]
+    async fn read_page() {
+        let (fs, tool) = setup().await;
+        let content = (1..=10).map(|i| format!("line{}", i)).collect::<Vec<_>>().join("\n");
+        fs.write("/project/big.txt", &content).await.unwrap();
+
+        let result = tool
+            .execute("c2", json!({"path": "big.txt", "page": 3, "limit": 3}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        assert_eq!(result.metadata["offset"], 7);
+        assert!(result.content.contains("line7"));
+        assert!(result.content.contains("line9"));
+        assert!(!result.content.contains("line10"));
+
+        let result = tool
+            .execute("c2", json!({"path": "big.txt", "page": 1, "offset": 2}), None)
+            .await
+            .unwrap();
+        assert!(result.is_error);
+
+        for arguments in [
+            json!({"path": "big.txt", "page": u64::MAX}),
+            json!({"path": "big.txt", "page": u64::MAX, "limit": u64::MAX}),
+            json!({"path": "big.txt", "limit": 0}),
+            json!({"path": "big.txt", "page": 2, "limit": 0}),
+        ] {
+            let result = tool.execute("c3", arguments.clone(), None).await.unwrap();
+            assert_eq!(
+                ToolErrorKind::of(&result),
+                Some(ToolErrorKind::InvalidArguments),
+                "{}",
+                arguments
+            );
+        }
+        let result = tool
+            .execute("c4", json!({"path": "big.txt", "limit": u64::MAX}), None)
+            .await
+            .unwrap();
+        assert!(result.content.contains("line10"), "{}", result.content);
+    }
+
+    #[tokio::test]
+    async fn read_summary() {
+        let (fs, tool) = setup().await;
+        let mut content: String = (1..=100).map(|i| format!("// head {}\n", i)).collect();
+        content.push_str("pub fn middle() {\n");
+        content.push_str(&"    step();\n".repeat(2000));
+        content.push_str("}\n");
+        content.push_str(&(1..=60).map(|i| format!("// tail {}\n", i)).collect::<String>());
+        fs.write("/project/big.rs", &content).await.unwrap();
+        fs.write("/project/small.rs", "fn f() {}\n").await.unwrap();
+
+        let result = tool
+            .execute("c1", json!({"path": "big.rs", "summary": true}), None)
+            .await
+            .unwrap();
+        assert!(result.content.starts_with("     1\t// head 1\n"));
+        assert!(result.content.contains(
+            "    50\t// head 50\n[Lines 51-2112 not shown; they contain:]\n  line 101: fn middle\n"
+        ));
+        assert!(result.content.contains("  2162\t// tail 60\n[Summary of 2162 lines."));
+        assert_eq!(result.metadata["tail_start"], 2113);
+        assert_eq!(result.metadata["outline"][0]["name"], "middle");
+
+        let result = tool
+            .execute("c2", json!({"path": "small.rs", "summary": true}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "     1\tfn f() {}");
+        let result = tool
+            .execute("c3", json!({"path": "big.rs", "summary": true, "offset": 5}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::InvalidArguments));
+    }
+
+    #[tokio::test]
+    async fn read_symbol() {
+        let (fs, tool) = setup().await;
+        fs.write(
+            "/project/lib.rs",
+            "use std::fmt;\n\n/// Resolve a path.\npub fn resolve_path(p: &str) -> String {\n    p.into()\n}\n\nfn other() {}\n",
+        )
+        .await
+        .unwrap();
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({"path": "lib.rs", "symbol": "resolve_path"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.starts_with("     3\t/// Resolve a path."));
+        assert!(result.content.contains("     6\t}"));
+        assert!(!result.content.contains("other"));
+        assert_eq!(result.metadata["start_line"], 3);
+        assert_eq!(result.metadata["end_line"], 6);
+
+        let result = tool
+            .execute("c2", json!({"path": "lib.rs", "symbol": "missing"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+        assert!(result.content.contains("resolve_path, other"));
+    }
+
+    #[tokio::test]
+    async fn read_notebook() {
+        let (fs, tool) = setup().await;
+        fs.write(
+            "/project/nb.ipynb",
+            r##"{"cells": [{"cell_type": "markdown", "source": ["# Notes"]}, {"cell_type": "code", "execution_count": 1, "source": ["x = 1\n", "x"], "outputs": [{"output_type": "execute_result", "data": {"text/plain": ["1"]}}]}], "nbformat": 4}"##,
+        )
+        .await
+        .unwrap();
+
+        let result = tool
+            .execute("c1", json!({"path": "nb.ipynb"}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.starts_with("[cell 0] markdown\n# Notes"));
+        assert!(result
+            .content
+            .contains("[cell 1] code (execution_count: 1)\nx = 1\nx"));
+        assert!(result.content.contains("[output] execute_result: 1"));
+        assert_eq!(result.metadata["cells"], 2);
+
+        let result = tool
+            .execute("c2", json!({"path": "nb.ipynb", "cell": 0}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "[cell 0] markdown\n# Notes");
+
+        // offset reads the raw JSON
+        let result = tool
+            .execute("c3", json!({"path": "nb.ipynb", "offset": 1}), None)
+            .await
+            .unwrap();
+        assert!(result.content.contains("\"cell_type\""));
+    }
+
+    #[tokio::test]
+    async fn read_byte_range() {
+        let (fs, tool) = setup().await;
+        // One long line with multi-byte characters at the cut points
+        fs.write("/project/min.js", "var a=\"é\";var b=\"日本\";")
+            .await
+            .unwrap();
+
+        let result = tool
+            .execute("c1", json!({"path": "min.js", "byte_offset": 0, "byte_limit": 8}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.starts_with("var a=\"\n[Bytes 0-7 of 26"));
+        assert_eq!(result.metadata["byte_range"], json!([0, 7]));
+        assert_eq!(result.metadata["next_byte_offset"], 7);
+
+        // Starting inside 日 moves forward to 本
+        let result = tool
+            .execute("c2", json!({"path": "min.js", "byte_offset": 19}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "本\";");
+        assert_eq!(result.metadata["byte_range"], json!([21, 26]));
+        assert_eq!(result.metadata["remaining_bytes"], 0);
+
+        // A limit too small for the character still makes progress
+        let result = tool
+            .execute("c3", json!({"path": "min.js", "byte_offset": 7, "byte_limit": 1}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["byte_range"], json!([7, 9]));
+
+        for args in [
+            json!({"path": "min.js", "byte_offset": 26}),
+            json!({"path": "min.js", "byte_offset": 0, "offset": 1}),
+        ] {
+            let result = tool.execute("c4", args, None).await.unwrap();
+            assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::InvalidArguments));
+        }
+    }
+
     #[tokio::test]
     async fn read_nonexistent() {
         let (_fs, tool) = setup().await;
@@ -211,6 +1277,7 @@ mod tests {
             .unwrap();
         assert!(result.is_error);
         assert!(result.content.contains("not found"));
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
     }
 
     #[tokio::test]
@@ -0,0 +1,555 @@
+//! Rename tool — rename or move many files at once by mapping their paths.
+//!
+//! Renaming `*.test.js` to `*.spec.js` across a tree takes one call instead
+//! of a bash loop the model has to get right. `from` is a glob; what each of
+//! its wildcards matched fills the wildcards of `to` in order, or `$1`,
+//! `$2`, ... pick them like regex capture groups. A `from` without a `/`
+//! matches file names at any depth and renames files where they are;
+//! otherwise it matches paths relative to the search directory.
+//!
+//! Every pair is checked before anything moves: two files mapped to the same
+//! path, or onto a file that already exists, fail the call. Calls are dry
+//! runs listing `old -> new` unless `dry_run: false` is passed. The VFS has
+//! no rename, so files are copied and the originals removed; only text files
+//! can be moved, and a failure part-way moves the files already done back.
+//! Not part of [`all_tools`](crate::presets::all_tools); add it with
+//! [`Toolset::with_rename`](crate::Toolset::with_rename).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::error::{tool_error, ToolErrorKind};
+use crate::file_modes::{current_mode, restore_mode, FileModes};
+use crate::locks::PathLocks;
+use crate::paths;
+use crate::truncate::{truncate_head, MAX_BYTES};
+use crate::walk::WalkGuard;
+
+use super::grep::collect_files;
+use super::{impl_overrides, resolve_path, ToolOverrides};
+
+/// Maximum files renamed by one call.
+pub const MAX_FILES: usize = 500;
+
+/// System-prompt guidance returned by [`RenameTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    To rename or move many files by a pattern, run rename as a dry run, check \
+    the old -> new list, then repeat the call with dry_run: false.";
+
+pub struct RenameTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    locks: Arc<PathLocks>,
+    modes: Option<Arc<dyn FileModes>>,
+    overrides: ToolOverrides,
+}
+
+/// One file of a call, read before anything is moved.
+struct Move {
+    from: String,
+    to: String,
+    content: String,
+    mode: Option<u32>,
+}
+
+impl RenameTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            locks: Arc::new(PathLocks::new()),
+            modes: None,
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+    /// Share `locks` with other tools, so that their changes to the same
+    /// files wait for each other (default: a set of this tool's own).
+    pub fn with_locks(mut self, locks: Arc<PathLocks>) -> Self {
+        self.locks = locks;
+        self
+    }
+
+    /// Give renamed files the permissions of the originals (default: left
+    /// to the filesystem).
+    pub fn with_file_modes(mut self, modes: Arc<dyn FileModes>) -> Self {
+        self.modes = Some(modes);
+        self
+    }
+
+    /// Move `done` back, most recent first. Returns the files that could
+    /// not be restored.
+    async fn roll_back(&self, done: &[&Move]) -> Vec<String> {
+        let mut failed = Vec::new();
+        for file in done.iter().rev() {
+            match self.fs.write(&file.from, &file.content).await {
+                Ok(()) => {
+                    restore_mode(&self.modes, &file.from, file.mode).await;
+                    let _ = self.fs.remove_file(&file.to).await;
+                }
+                Err(_) => failed.push(paths::display(&file.from, &self.cwd)),
+            }
+        }
+        failed
+    }
+}
+
+impl_overrides!(RenameTool);
+
+/// What each wildcard (`**`, `*`, `?`) of `pattern` matched in `text`, or
+/// `None` if it doesn't match. Wildcards behave as in
+/// [`glob_match`](crate::ignore::glob_match); a `**` capture has no
+/// trailing `/`.
+fn glob_captures(pattern: &str, text: &str) -> Option<Vec<String>> {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let mut captures = Vec::new();
+    captures_at(&p, &t, &mut captures).then_some(captures)
+}
+
+fn captures_at(p: &[char], t: &[char], captures: &mut Vec<String>) -> bool {
+    match p {
+        [] => t.is_empty(),
+        ['*', '*', rest @ ..] => {
+            // `**/` also matches zero directories
+            let rest = rest.strip_prefix(&['/']).unwrap_or(rest);
+            (0..=t.len()).any(|i| {
+                (i == 0 || t[i - 1] == '/' || rest.is_empty())
+                    && capture_then(&t[..i], rest, &t[i..], captures)
+            })
+        }
+        ['*', rest @ ..] => {
+            for i in 0..=t.len() {
+                if capture_then(&t[..i], rest, &t[i..], captures) {
+                    return true;
+                }
+                if i < t.len() && t[i] == '/' {
+                    break;
+                }
+            }
+            false
+        }
+        ['?', rest @ ..] => {
+            !t.is_empty() && t[0] != '/' && capture_then(&t[..1], rest, &t[1..], captures)
+        }
+        [c, rest @ ..] => {
+            matches!(t, [d, tail @ ..] if c == d && captures_at(rest, tail, captures))
+        }
+    }
+}
+
+/// Record `captured`, then match `rest` against `tail`; the capture is
+/// dropped again if that fails.
+fn capture_then(
+    captured: &[char],
+    rest: &[char],
+    tail: &[char],
+    captures: &mut Vec<String>,
+) -> bool {
+    let captured: String = captured.iter().collect();
+    captures.push(captured.trim_end_matches('/').to_string());
+    if captures_at(rest, tail, captures) {
+        return true;
+    }
+    captures.pop();
+    false
+}
+
+/// `template` with its wildcards replaced by `captures` in order, and `$n`
+/// or `${n}` by the n-th capture.
+fn expand(template: &str, captures: &[String]) -> Result<String, String> {
+    let mut out = String::new();
+    let mut next = 0;
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' | '?' => {
+                if c == '*' {
+                    chars.next_if_eq(&'*');
+                }
+                let Some(capture) = captures.get(next) else {
+                    return Err(format!(
+                        "to has more wildcards than from ({})",
+                        captures.len()
+                    ));
+                };
+                out.push_str(capture);
+                next += 1;
+            }
+            '$' if chars
+                .peek()
+                .is_some_and(|c| c.is_ascii_digit() || *c == '{') =>
+            {
+                let braced = chars.next_if_eq(&'{').is_some();
+                let mut digits = String::new();
+                while let Some(d) = chars.next_if(|c| c.is_ascii_digit()) {
+                    digits.push(d);
+                }
+                if braced && chars.next() != Some('}') {
+                    return Err("Expected ${n} in to, with n a number".into());
+                }
+                let n: usize = digits.parse().unwrap_or(0);
+                let Some(capture) = n.checked_sub(1).and_then(|i| captures.get(i)) else {
+                    return Err(format!(
+                        "to refers to ${} but from has {} wildcard(s)",
+                        n,
+                        captures.len()
+                    ));
+                };
+                out.push_str(capture);
+            }
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for RenameTool {
+    fn name(&self) -> &str {
+        self.overrides.name("rename")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "rename".into(),
+            description: "Rename or move every file matching a glob, mapping each path through a pattern: what the wildcards of from matched fills the wildcards of to in order, or $1, $2, ... (e.g. from '*.test.js' to '*.spec.js', or from 'src/**/*.jsx' to 'src/$1/$2.tsx'). Refuses collisions. Dry run by default: pass dry_run: false to rename.".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "from": {
+                        "type": "string",
+                        "description": "Glob of files to rename: a file name pattern matches at any depth, a pattern with '/' matches paths relative to path"
+                    },
+                    "to": {
+                        "type": "string",
+                        "description": "New name or relative path, with wildcards or $n standing for what from's wildcards matched"
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "Directory to search (defaults to working directory); files stay inside it"
+                    },
+                    "dry_run": {
+                        "type": "boolean",
+                        "description": "List the renames without making them (default: true)"
+                    }
+                },
+                "required": ["from", "to"]
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let from = arguments.get("from").and_then(|v| v.as_str()).unwrap_or("");
+        let to = arguments.get("to").and_then(|v| v.as_str()).unwrap_or("");
+        for (name, value) in [("from", from), ("to", to)] {
+            if value.is_empty() {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!("Missing required parameter: {}", name),
+                ));
+            }
+        }
+        let dry_run = arguments
+            .get("dry_run")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        let base = arguments
+            .get("path")
+            .and_then(|v| v.as_str())
+            .map(|p| resolve_path(&self.cwd, p))
+            .unwrap_or_else(|| self.cwd.clone());
+        let show = |path: &str| paths::display(path, &self.cwd);
+
+        let guard = WalkGuard::new();
+        let mut files = Vec::new();
+        guard.enter(&base, 0);
+        let walk = collect_files(self.fs.as_ref(), &base, &mut files, None, None, &guard, 0);
+        if let Err(e) = walk.await {
+            return Ok(tool_error(
+                ToolErrorKind::from_soul_error(&e),
+                format!("Failed to enumerate files in {}: {}", show(&base), e),
+            ));
+        }
+
+        // Map every matching file, checking the whole set before moving any
+        let by_name = !from.contains('/');
+        let mut pairs = Vec::new();
+        let mut targets: HashMap<String, String> = HashMap::new();
+        for file in files {
+            let Some(relative) = paths::relative_to(&file, &base) else {
+                continue;
+            };
+            let (dir, name) = match relative.rsplit_once('/') {
+                Some((dir, name)) => (dir, name),
+                None => (".", relative.as_str()),
+            };
+            let subject = if by_name { name } else { relative.as_str() };
+            let Some(captures) = glob_captures(from, subject) else {
+                continue;
+            };
+            let mapped = match expand(to, &captures) {
+                Ok(mapped) => mapped,
+                Err(message) => return Ok(tool_error(ToolErrorKind::InvalidArguments, message)),
+            };
+            let target = match by_name {
+                true => resolve_path(&resolve_path(&base, dir), &mapped),
+                false => resolve_path(&base, &mapped),
+            };
+            if target == file {
+                continue;
+            }
+            if target == base || !paths::is_within(&target, &base) {
+                return Ok(tool_error(
+                    ToolErrorKind::Policy,
+                    format!(
+                        "{} would be moved to {}, outside {}; nothing was renamed",
+                        show(&file),
+                        show(&target),
+                        show(&base)
+                    ),
+                ));
+            }
+            if let Some(other) = targets.insert(target.clone(), file.clone()) {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!(
+                        "{} and {} would both be renamed to {}; nothing was renamed",
+                        show(&other),
+                        show(&file),
+                        show(&target)
+                    ),
+                ));
+            }
+            pairs.push((file, target));
+        }
+
+        if pairs.is_empty() {
+            return Ok(ToolOutput::success(format!(
+                "No files under {} match {}",
+                show(&base),
+                from
+            ))
+            .with_metadata(json!({"renamed": [], "count": 0, "dry_run": dry_run})));
+        }
+        if pairs.len() > MAX_FILES {
+            return Ok(tool_error(
+                ToolErrorKind::TooLarge,
+                format!(
+                    "Refusing to rename {} files in one call (limit {}); narrow from or path",
+                    pairs.len(),
+                    MAX_FILES
+                ),
+            ));
+        }
+        for (file, target) in &pairs {
+            if self.fs.exists(target).await.unwrap_or(false) {
+                return Ok(tool_error(
+                    ToolErrorKind::AlreadyExists,
+                    format!(
+                        "Renaming {} would replace {}, which already exists; nothing was renamed",
+                        show(file),
+                        show(target)
+                    ),
+                ));
+            }
+        }
+
+        let listing = pairs
+            .iter()
+            .map(|(file, target)| format!("  {} -> {}", show(file), show(target)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let renamed: Vec<_> = pairs
+            .iter()
+            .map(|(file, target)| json!({"from": show(file), "to": show(target)}))
+            .collect();
+        let metadata = json!({"renamed": renamed, "count": pairs.len(), "dry_run": dry_run});
+        let truncated = truncate_head(&listing, pairs.len(), MAX_BYTES);
+        let mut listing = truncated.content.clone();
+        if let Some(notice) = truncated.truncation_notice() {
+            listing.push_str(&format!("\n{}", notice));
+        }
+        if dry_run {
+            return Ok(ToolOutput::success(format!(
+                "Would rename {} file(s) (dry run; pass dry_run: false to rename)\n{}",
+                pairs.len(),
+                listing
+            ))
+            .with_metadata(metadata));
+        }
+
+        // Lock in path order, so two calls sharing files can't deadlock
+        let mut order: Vec<&str> = pairs
+            .iter()
+            .flat_map(|(file, target)| [file.as_str(), target.as_str()])
+            .collect();
+        order.sort_unstable();
+        let mut guards = Vec::with_capacity(order.len());
+        for path in order {
+            guards.push(self.locks.lock(path).await);
+        }
+
+        let mut moves = Vec::with_capacity(pairs.len());
+        for (from, to) in pairs {
+            let content = match self.fs.read_to_string(&from).await {
+                Ok(content) => content,
+                Err(e) => {
+                    return Ok(tool_error(
+                        ToolErrorKind::from_soul_error(&e),
+                        format!(
+                            "Can't move {} (only text files can be moved): {}; nothing was renamed",
+                            show(&from),
+                            e
+                        ),
+                    ))
+                }
+            };
+            let mode = current_mode(&self.modes, &from).await;
+            moves.push(Move {
+                from,
+                to,
+                content,
+                mode,
+            });
+        }
+
+        let mut done: Vec<&Move> = Vec::new();
+        for file in &moves {
+            if let Some((parent, _)) = file.to.rsplit_once('/') {
+                if !parent.is_empty() {
+                    let _ = self.fs.create_dir_all(parent).await;
+                }
+            }
+            let moved = match self.fs.write(&file.to, &file.content).await {
+                Ok(()) => self.fs.remove_file(&file.from).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = moved {
+                // Don't leave a copy behind next to the original
+                let _ = self.fs.remove_file(&file.to).await;
+                let failed = self.roll_back(&done).await;
+                let outcome = match failed.is_empty() {
+                    true => "no files were renamed".to_string(),
+                    false => format!("could not move back {}", failed.join(", ")),
+                };
+                return Ok(tool_error(
+                    ToolErrorKind::from_soul_error(&e),
+                    format!("Failed to move {}: {}; {}", show(&file.from), e, outcome),
+                ));
+            }
+            restore_mode(&self.modes, &file.to, file.mode).await;
+            done.push(file);
+        }
+
+        let mut output =
+            ToolOutput::success(format!("Renamed {} file(s)\n{}", moves.len(), listing))
+                .with_metadata(metadata);
+        if let Some(guard) = guards.iter().find(|g| g.contended()) {
+            guard.annotate(&mut output);
+        }
+        guard.annotate(&mut output);
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    #[test]
+    fn maps_wildcards_and_references() {
+        let captures = glob_captures("src/**/*.test.js", "src/a/b/util.test.js").unwrap();
+        assert_eq!(captures, ["a/b", "util"]);
+        assert_eq!(
+            expand("src/**/*.spec.js", &captures).unwrap(),
+            "src/a/b/util.spec.js"
+        );
+        assert_eq!(
+            expand("tests/${2}_$1.js", &captures).unwrap(),
+            "tests/util_a/b.js"
+        );
+        assert_eq!(glob_captures("src/**/*.js", "src/x.js").unwrap(), ["", "x"]);
+        assert!(glob_captures("*.js", "a/b.js").is_none());
+        assert!(expand("$3.js", &captures).is_err());
+        assert!(expand("*/*/*", &captures).is_err());
+    }
+
+    #[tokio::test]
+    async fn dry_run_then_rename() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/src/a.test.js", "a").await.unwrap();
+        fs.write("/project/src/lib/b.test.js", "b").await.unwrap();
+        fs.write("/project/src/c.js", "c").await.unwrap();
+        let tool = RenameTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+
+        let args = json!({"from": "*.test.js", "to": "*.spec.js"});
+        let result = tool.execute("c1", args.clone(), None).await.unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(
+            result.content,
+            "Would rename 2 file(s) (dry run; pass dry_run: false to rename)\n  \
+             src/a.test.js -> src/a.spec.js\n  src/lib/b.test.js -> src/lib/b.spec.js"
+        );
+        assert!(fs.exists("/project/src/a.test.js").await.unwrap());
+
+        let mut args = args;
+        args["dry_run"] = json!(false);
+        let result = tool.execute("c2", args, None).await.unwrap();
+        assert!(result.content.starts_with("Renamed 2 file(s)"));
+        assert_eq!(result.metadata["renamed"][1]["to"], "src/lib/b.spec.js");
+        assert_eq!(
+            fs.read_to_string("/project/src/lib/b.spec.js")
+                .await
+                .unwrap(),
+            "b"
+        );
+        assert!(!fs.exists("/project/src/lib/b.test.js").await.unwrap());
+
+        // Paths map through captures; collisions and escapes move nothing
+        let args = json!({"from": "src/**/*.spec.js", "to": "test/$1/$2.js", "dry_run": false});
+        let result = tool.execute("c3", args, None).await.unwrap();
+        assert_eq!(result.metadata["renamed"][0]["to"], "test/a.js");
+        assert_eq!(
+            fs.read_to_string("/project/test/lib/b.js").await.unwrap(),
+            "b"
+        );
+        for (args, kind) in [
+            (
+                json!({"from": "**/*.js", "to": "all.js"}),
+                ToolErrorKind::InvalidArguments,
+            ),
+            (
+                json!({"from": "c.js", "to": "../c.js", "path": "src"}),
+                ToolErrorKind::Policy,
+            ),
+        ] {
+            let result = tool.execute("c4", args, None).await.unwrap();
+            assert_eq!(ToolErrorKind::of(&result), Some(kind), "{}", result.content);
+        }
+        fs.write("/project/src/c.ts", "ts").await.unwrap();
+        let args = json!({"from": "*.js", "to": "*.ts", "path": "src", "dry_run": false});
+        let result = tool.execute("c5", args, None).await.unwrap();
+        assert_eq!(
+            ToolErrorKind::of(&result),
+            Some(ToolErrorKind::AlreadyExists)
+        );
+        assert_eq!(fs.read_to_string("/project/src/c.js").await.unwrap(), "c");
+    }
+}
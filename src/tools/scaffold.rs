@@ -0,0 +1,487 @@
+//! Scaffold tool — instantiate a template directory tree in one call.
+//!
+//! Copies every file under a template directory to a destination, replacing
+//! `{{name}}` placeholders in both paths and contents with caller-supplied
+//! variables. Placeholders must be identifiers (letters, digits, `_`, `-`,
+//! `.`), so JSX like `style={{color: 'red'}}` passes through untouched. All
+//! placeholders are checked and all targets resolved before anything is
+//! written, so a missing variable or an existing file leaves the destination
+//! unchanged.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::error::{tool_error, ToolErrorKind};
+use crate::paths;
+
+use super::{impl_overrides, resolve_path, set_metadata, ToolOverrides};
+
+/// Maximum files copied by one call.
+const MAX_TEMPLATE_FILES: usize = 1_000;
+
+/// Replace `{{name}}` placeholders with `variables`, collecting names that
+/// have no value into `missing`. Non-identifier contents are left as-is.
+pub(crate) fn render_template(
+    text: &str,
+    variables: &BTreeMap<String, String>,
+    missing: &mut BTreeSet<String>,
+) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = after[..end].trim();
+        let is_placeholder = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        if is_placeholder {
+            match variables.get(name) {
+                Some(value) => out.push_str(value),
+                None => {
+                    missing.insert(name.to_string());
+                    out.push_str(&rest[start..start + end + 4]);
+                }
+            }
+        } else {
+            out.push_str(&rest[start..start + end + 4]);
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Parse a `variables` argument: an object of strings, numbers or booleans.
+pub(crate) fn parse_variables(
+    value: &serde_json::Value,
+) -> Result<BTreeMap<String, String>, String> {
+    let Some(vars) = value.as_object() else {
+        return Err("variables must be an object".into());
+    };
+    let mut variables = BTreeMap::new();
+    for (name, value) in vars {
+        let value = match value {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Number(n) => n.to_string(),
+            serde_json::Value::Bool(b) => b.to_string(),
+            _ => {
+                return Err(format!(
+                    "Variable {} must be a string, number or boolean",
+                    name
+                ))
+            }
+        };
+        variables.insert(name.clone(), value);
+    }
+    Ok(variables)
+}
+
+/// Recursively collect `(relative_path, is_dir)` for everything under `dir`.
+async fn collect_template(
+    fs: &dyn VirtualFs,
+    dir: &str,
+    rel: &str,
+    entries: &mut Vec<(String, bool)>,
+) {
+    let Ok(mut children) = fs.read_dir(dir).await else {
+        return;
+    };
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+    for child in children {
+        if entries.len() > MAX_TEMPLATE_FILES {
+            return;
+        }
+        let path = paths::resolve(dir, &child.name);
+        let child_rel = if rel.is_empty() {
+            child.name.clone()
+        } else {
+            format!("{}/{}", rel, child.name)
+        };
+        if child.is_dir {
+            entries.push((child_rel.clone(), true));
+            Box::pin(collect_template(fs, &path, &child_rel, entries)).await;
+        } else if child.is_file {
+            entries.push((child_rel, false));
+        }
+    }
+}
+
+/// System-prompt guidance returned by [`ScaffoldTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Instantiate a template directory in one call instead of many writes, supplying a \
+    value for every {{variable}} it uses.";
+
+pub struct ScaffoldTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    overrides: ToolOverrides,
+}
+
+impl ScaffoldTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            overrides: ToolOverrides::default(),
+        }
+    }
+}
+
+impl_overrides!(ScaffoldTool);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for ScaffoldTool {
+    fn name(&self) -> &str {
+        self.overrides.name("scaffold")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "scaffold".into(),
+            description: "Copy a template directory to a new location, replacing {{variable}} placeholders in file names and contents. Use to instantiate a project template (new crate, component folder) in one call instead of many writes. Fails without writing anything if a placeholder has no value or a target file exists (unless overwrite is set).".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "template": {
+                        "type": "string",
+                        "description": "Template directory to copy"
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "Destination directory (created if missing)"
+                    },
+                    "variables": {
+                        "type": "object",
+                        "additionalProperties": {"type": ["string", "number", "boolean"]},
+                        "description": "Values for {{name}} placeholders, e.g. {\"name\": \"Button\"}"
+                    },
+                    "overwrite": {
+                        "type": "boolean",
+                        "description": "Replace files that already exist at the destination (default: false)"
+                    }
+                },
+                "required": ["template", "path"]
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let template = arguments
+            .get("template")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let path = arguments.get("path").and_then(|v| v.as_str()).unwrap_or("");
+        let overwrite = arguments
+            .get("overwrite")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if template.is_empty() || path.is_empty() {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Missing required parameter: template and path are both required",
+            ));
+        }
+
+        let variables = match arguments.get("variables").filter(|v| !v.is_null()) {
+            None => BTreeMap::new(),
+            Some(vars) => match parse_variables(vars) {
+                Ok(variables) => variables,
+                Err(message) => return Ok(tool_error(ToolErrorKind::InvalidArguments, message)),
+            },
+        };
+
+        let source = resolve_path(&self.cwd, template);
+        let dest = resolve_path(&self.cwd, path);
+        let is_dir = self
+            .fs
+            .metadata(&source)
+            .await
+            .map(|m| m.is_dir)
+            .unwrap_or(false);
+        if !is_dir {
+            return Ok(tool_error(
+                ToolErrorKind::NotFound,
+                format!(
+                    "Template directory not found: {}",
+                    paths::display(&source, &self.cwd)
+                ),
+            ));
+        }
+        if paths::is_within(&dest, &source) {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Destination must not be inside the template directory",
+            ));
+        }
+
+        let mut entries = Vec::new();
+        collect_template(self.fs.as_ref(), &source, "", &mut entries).await;
+        if entries.len() > MAX_TEMPLATE_FILES {
+            return Ok(tool_error(
+                ToolErrorKind::TooLarge,
+                format!(
+                    "Template has more than {} entries; copy it in parts",
+                    MAX_TEMPLATE_FILES
+                ),
+            ));
+        }
+
+        // Render every path and file up front so failures write nothing
+        let mut missing = BTreeSet::new();
+        let mut dirs = Vec::new();
+        let mut files = Vec::new();
+        let mut skipped = Vec::new();
+        for (rel, is_dir) in &entries {
+            let target = paths::resolve(&dest, &render_template(rel, &variables, &mut missing));
+            if !paths::is_within(&target, &dest) {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!("Template path {} would be written outside {}", rel, path),
+                ));
+            }
+            if *is_dir {
+                dirs.push(target);
+                continue;
+            }
+            match self.fs.read_to_string(&paths::resolve(&source, rel)).await {
+                Ok(content) => {
+                    let content = render_template(&content, &variables, &mut missing);
+                    files.push((target, content));
+                }
+                // Binary files can't round-trip through the string VFS API
+                Err(_) => skipped.push(rel.clone()),
+            }
+        }
+        if !missing.is_empty() {
+            let names: Vec<_> = missing.into_iter().collect();
+            let mut out = tool_error(
+                ToolErrorKind::InvalidArguments,
+                format!("Missing template variables: {}", names.join(", ")),
+            );
+            set_metadata(&mut out, "missing_variables", json!(names));
+            return Ok(out);
+        }
+        if !overwrite {
+            let mut existing = Vec::new();
+            for (target, _) in &files {
+                if self.fs.exists(target).await.unwrap_or(false) {
+                    existing.push(paths::display(target, &self.cwd));
+                }
+            }
+            if !existing.is_empty() {
+                return Ok(tool_error(
+                    ToolErrorKind::AlreadyExists,
+                    format!(
+                        "{} file(s) already exist: {}. Pass overwrite: true to replace them.",
+                        existing.len(),
+                        existing.join(", ")
+                    ),
+                ));
+            }
+        }
+
+        if let Err(e) = self.fs.create_dir_all(&dest).await {
+            return Ok(tool_error(
+                ToolErrorKind::from_soul_error(&e),
+                format!("Failed to create {}: {}", path, e),
+            ));
+        }
+        for dir in &dirs {
+            let _ = self.fs.create_dir_all(dir).await;
+        }
+        let mut created = Vec::new();
+        let mut bytes = 0;
+        for (target, content) in &files {
+            if let Some((parent, _)) = target.rsplit_once('/') {
+                if !parent.is_empty() {
+                    let _ = self.fs.create_dir_all(parent).await;
+                }
+            }
+            if let Err(e) = self.fs.write(target, content).await {
+                return Ok(tool_error(
+                    ToolErrorKind::from_soul_error(&e),
+                    format!(
+                        "Failed to write {} after creating {} file(s): {}",
+                        paths::display(target, &self.cwd),
+                        created.len(),
+                        e
+                    ),
+                ));
+            }
+            bytes += content.len();
+            created.push(paths::display(target, &self.cwd));
+        }
+
+        let mut output = format!(
+            "Created {} file(s) in {} from {}",
+            created.len(),
+            paths::display(&dest, &self.cwd),
+            paths::display(&source, &self.cwd)
+        );
+        for file in &created {
+            output.push_str(&format!("\n  {}", file));
+        }
+        if !skipped.is_empty() {
+            output.push_str(&format!(
+                "\n[Skipped {} non-UTF-8 file(s): {}]",
+                skipped.len(),
+                skipped.join(", ")
+            ));
+        }
+        Ok(ToolOutput::success(output).with_metadata(json!({
+            "path": paths::display(&dest, &self.cwd),
+            "files": created,
+            "directories": dirs.len(),
+            "bytes_written": bytes,
+            "skipped": skipped,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    async fn setup() -> (Arc<MemoryFs>, ScaffoldTool) {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write(
+            "/project/templates/component/{{name}}.tsx",
+            "export function {{name}}() {\n  return <div style={{color: 'red'}}>{{ title }}</div>;\n}\n",
+        )
+        .await
+        .unwrap();
+        fs.write(
+            "/project/templates/component/{{name}}.test.tsx",
+            "import { {{name}} } from './{{name}}';\n",
+        )
+        .await
+        .unwrap();
+        fs.write(
+            "/project/templates/component/styles/index.css",
+            ".root {}\n",
+        )
+        .await
+        .unwrap();
+        let tool = ScaffoldTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        (fs, tool)
+    }
+
+    #[test]
+    fn renders_placeholders() {
+        let vars = BTreeMap::from([("name".to_string(), "Button".to_string())]);
+        let mut missing = BTreeSet::new();
+        assert_eq!(
+            render_template(
+                "{{name}} {{ name }} {{x: 1}} {{other}} {{",
+                &vars,
+                &mut missing
+            ),
+            "Button Button {{x: 1}} {{other}} {{"
+        );
+        assert_eq!(missing, BTreeSet::from(["other".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn scaffold_instantiates_template() {
+        let (fs, tool) = setup().await;
+        let result = tool
+            .execute(
+                "c1",
+                json!({
+                    "template": "templates/component",
+                    "path": "src/components/Button",
+                    "variables": {"name": "Button", "title": "Click"}
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.metadata["files"].as_array().unwrap().len(), 3);
+
+        let component = fs
+            .read_to_string("/project/src/components/Button/Button.tsx")
+            .await
+            .unwrap();
+        assert!(component.starts_with("export function Button() {"));
+        assert!(component.contains("style={{color: 'red'}}>Click<"));
+        assert!(fs
+            .exists("/project/src/components/Button/Button.test.tsx")
+            .await
+            .unwrap());
+        assert!(fs
+            .exists("/project/src/components/Button/styles/index.css")
+            .await
+            .unwrap());
+
+        // Same call again collides with the files it just created
+        let args = json!({
+            "template": "templates/component",
+            "path": "src/components/Button",
+            "variables": {"name": "Button", "title": "Other"}
+        });
+        let result = tool.execute("c2", args.clone(), None).await.unwrap();
+        assert_eq!(
+            ToolErrorKind::of(&result),
+            Some(ToolErrorKind::AlreadyExists)
+        );
+        let mut args = args;
+        args["overwrite"] = json!(true);
+        let result = tool.execute("c3", args, None).await.unwrap();
+        assert!(!result.is_error, "{}", result.content);
+    }
+
+    #[tokio::test]
+    async fn scaffold_missing_variables_writes_nothing() {
+        let (fs, tool) = setup().await;
+        let result = tool
+            .execute(
+                "c1",
+                json!({"template": "templates/component", "path": "out", "variables": {"name": "X"}}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            ToolErrorKind::of(&result),
+            Some(ToolErrorKind::InvalidArguments)
+        );
+        assert_eq!(result.metadata["missing_variables"], json!(["title"]));
+        assert!(!fs.exists("/project/out").await.unwrap());
+
+        let result = tool
+            .execute("c2", json!({"template": "nope", "path": "out"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+    }
+
+    #[tokio::test]
+    async fn tool_name_and_definition() {
+        let (_fs, tool) = setup().await;
+        assert_eq!(tool.name(), "scaffold");
+        assert_eq!(tool.definition().name, "scaffold");
+    }
+}
@@ -0,0 +1,420 @@
+//! Scratch tool — a private notepad under a reserved VFS prefix.
+//!
+//! Notes live under [`DEFAULT_SCRATCH_ROOT`] (`/scratch`), outside the working
+//! directory, so plans, intermediate findings and stashed command output never
+//! show up in the user's project tree, workspace snapshots or `git status`.
+//! The tool is not in [`MUTATING_TOOLS`](crate::audit::MUTATING_TOOLS) and
+//! doesn't invalidate the [`ResultCache`](crate::cache::ResultCache), so
+//! hosts that gate or checkpoint workspace changes can let it through freely.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::error::{tool_error, ToolErrorKind};
+use crate::paths;
+use crate::truncate::{truncate_head, MAX_BYTES, MAX_LINES};
+use crate::walk::WalkGuard;
+
+use super::{impl_overrides, resolve_path, ToolOverrides};
+
+/// Default scratch location.
+pub const DEFAULT_SCRATCH_ROOT: &str = "/scratch";
+
+/// System-prompt guidance returned by [`ScratchTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Keep plans, intermediate findings and long command output here instead of \
+    writing notes into the project.";
+
+pub struct ScratchTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    root: String,
+    overrides: ToolOverrides,
+}
+
+impl ScratchTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            root: DEFAULT_SCRATCH_ROOT.into(),
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+    /// Keep notes under `root` (relative to the working directory or absolute).
+    pub fn with_root(mut self, root: impl Into<String>) -> Self {
+        self.root = root.into();
+        self
+    }
+
+    /// Resolve a note name inside the scratch root, rejecting escapes.
+    fn note_path(&self, root: &str, name: &str) -> Result<String, String> {
+        if name.trim().is_empty() {
+            return Err("Missing required parameter: name".into());
+        }
+        let path = if paths::is_within(name, root) {
+            paths::normalize(name)
+        } else {
+            paths::resolve(root, name.trim_start_matches('/'))
+        };
+        if path == root || !paths::is_within(&path, root) {
+            return Err(format!("Scratch note names must stay inside {}", root));
+        }
+        Ok(path)
+    }
+}
+
+impl_overrides!(ScratchTool);
+
+/// Recursively collect `(path, bytes)` for every note under `dir`, `depth`
+/// levels below the scratch root, entering subdirectories through `guard`.
+async fn collect_notes(
+    fs: &dyn VirtualFs,
+    dir: &str,
+    depth: usize,
+    guard: &WalkGuard,
+    notes: &mut Vec<(String, u64)>,
+) {
+    let Ok(entries) = fs.read_dir(dir).await else {
+        return;
+    };
+    for entry in entries {
+        let path = paths::resolve(dir, &entry.name);
+        if entry.is_dir {
+            if guard.enter(&path, depth + 1) {
+                Box::pin(collect_notes(fs, &path, depth + 1, guard, notes)).await;
+            }
+        } else if entry.is_file {
+            let size = fs.metadata(&path).await.map(|m| m.size).unwrap_or(0);
+            notes.push((path, size));
+        }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for ScratchTool {
+    fn name(&self) -> &str {
+        self.overrides.name("scratch")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "scratch".into(),
+            description: "Private scratchpad outside the project: stash plans, notes and long command output without touching the user's files. Actions: write (replace a note), append, read, list, delete.".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "action": {
+                        "type": "string",
+                        "enum": ["write", "append", "read", "list", "delete"],
+                        "description": "Operation to perform"
+                    },
+                    "name": {
+                        "type": "string",
+                        "description": "Note name, e.g. 'plan.md' or 'logs/test-run.txt' (write, append, read, delete)"
+                    },
+                    "content": {
+                        "type": "string",
+                        "description": "Text to store (write, append)"
+                    }
+                },
+                "required": ["action"]
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let action = arguments
+            .get("action")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let name = arguments.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let content = arguments.get("content").and_then(|v| v.as_str());
+
+        let root = resolve_path(&self.cwd, &self.root);
+
+        if action == "list" {
+            let mut notes = Vec::new();
+            let guard = WalkGuard::new();
+            guard.enter(&root, 0);
+            collect_notes(self.fs.as_ref(), &root, 0, &guard, &mut notes).await;
+            notes.sort();
+            if notes.is_empty() {
+                let mut output = ToolOutput::success("Scratchpad is empty")
+                    .with_metadata(json!({"notes": [], "root": root}));
+                guard.annotate(&mut output);
+                return Ok(output);
+            }
+            let listing: Vec<String> = notes
+                .iter()
+                .map(|(path, size)| {
+                    format!(
+                        "{} ({} bytes)",
+                        paths::relative_to(path, &root).unwrap_or_default(),
+                        size
+                    )
+                })
+                .collect();
+            let meta: Vec<_> = notes
+                .iter()
+                .map(|(path, size)| json!({"name": paths::relative_to(path, &root), "bytes": size}))
+                .collect();
+            let mut output = ToolOutput::success(listing.join("\n"))
+                .with_metadata(json!({"notes": meta, "root": root}));
+            guard.annotate(&mut output);
+            return Ok(output);
+        }
+
+        let path = match action {
+            "write" | "append" | "read" | "delete" => match self.note_path(&root, name) {
+                Ok(p) => p,
+                Err(e) => return Ok(tool_error(ToolErrorKind::InvalidArguments, e)),
+            },
+            "" => {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    "Missing required parameter: action",
+                ))
+            }
+            other => {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!(
+                        "Unknown action: {} (expected write, append, read, list or delete)",
+                        other
+                    ),
+                ))
+            }
+        };
+        let display = paths::relative_to(&path, &root).unwrap_or_default();
+
+        match action {
+            "write" | "append" => {
+                let Some(content) = content else {
+                    return Ok(tool_error(
+                        ToolErrorKind::InvalidArguments,
+                        format!("{} requires content", action),
+                    ));
+                };
+                if let Some((parent, _)) = path.rsplit_once('/') {
+                    if !parent.is_empty() {
+                        let _ = self.fs.create_dir_all(parent).await;
+                    }
+                }
+                let written = if action == "write" {
+                    self.fs.write(&path, content).await
+                } else {
+                    self.fs.append(&path, content).await
+                };
+                if let Err(e) = written {
+                    return Ok(tool_error(
+                        ToolErrorKind::from_soul_error(&e),
+                        format!("Failed to save scratch note {}: {}", display, e),
+                    ));
+                }
+                let size = self.fs.metadata(&path).await.map(|m| m.size).unwrap_or(0);
+                let verb = if action == "write" {
+                    "Saved"
+                } else {
+                    "Appended to"
+                };
+                Ok(ToolOutput::success(format!(
+                    "{} scratch note {} ({} bytes)",
+                    verb, display, size
+                ))
+                .with_metadata(json!({"name": display, "path": path, "bytes": size})))
+            }
+            "read" => match self.fs.read_to_string(&path).await {
+                Ok(text) => {
+                    let truncated = truncate_head(&text, MAX_LINES, MAX_BYTES);
+                    let mut output = truncated.content.clone();
+                    if let Some(notice) = truncated.truncation_notice() {
+                        output.push('\n');
+                        output.push_str(&notice);
+                        output
+                            .push_str(&format!("\n[Read {} with offset/limit for the rest]", path));
+                    }
+                    Ok(ToolOutput::success(output).with_metadata(json!({
+                        "name": display,
+                        "path": path,
+                        "truncation": truncated.to_metadata(),
+                    })))
+                }
+                Err(_) => Ok(tool_error(
+                    ToolErrorKind::NotFound,
+                    format!("No scratch note named {}", display),
+                )),
+            },
+            _ => {
+                if !self.fs.exists(&path).await.unwrap_or(false) {
+                    return Ok(tool_error(
+                        ToolErrorKind::NotFound,
+                        format!("No scratch note named {}", display),
+                    ));
+                }
+                match self.fs.remove_file(&path).await {
+                    Ok(()) => Ok(
+                        ToolOutput::success(format!("Deleted scratch note {}", display))
+                            .with_metadata(json!({"name": display, "path": path})),
+                    ),
+                    Err(e) => Ok(tool_error(
+                        ToolErrorKind::from_soul_error(&e),
+                        format!("Failed to delete scratch note {}: {}", display, e),
+                    )),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    async fn setup() -> (Arc<MemoryFs>, ScratchTool) {
+        let fs = Arc::new(MemoryFs::new());
+        let tool = ScratchTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        (fs, tool)
+    }
+
+    #[tokio::test]
+    async fn write_append_read_list_delete() {
+        let (fs, tool) = setup().await;
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({"action": "write", "name": "plan.md", "content": "1. parse\n"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.metadata["path"], "/scratch/plan.md");
+        assert!(!fs.exists("/project/plan.md").await.unwrap());
+
+        tool.execute(
+            "c2",
+            json!({"action": "append", "name": "plan.md", "content": "2. emit\n"}),
+            None,
+        )
+        .await
+        .unwrap();
+        tool.execute(
+            "c3",
+            json!({"action": "write", "name": "logs/test.txt", "content": "ok"}),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let result = tool
+            .execute("c4", json!({"action": "read", "name": "plan.md"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "1. parse\n2. emit\n");
+
+        let result = tool
+            .execute("c5", json!({"action": "list"}), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content,
+            "logs/test.txt (2 bytes)\nplan.md (17 bytes)"
+        );
+
+        let result = tool
+            .execute(
+                "c6",
+                json!({"action": "delete", "name": "/scratch/plan.md"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        let result = tool
+            .execute("c7", json!({"action": "read", "name": "plan.md"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+    }
+
+    #[tokio::test]
+    async fn names_cannot_escape_root() {
+        let (_fs, tool) = setup().await;
+        for name in ["../project/src/main.rs", "a/../../etc/passwd", "."] {
+            let result = tool
+                .execute(
+                    "c1",
+                    json!({"action": "write", "name": name, "content": "x"}),
+                    None,
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                ToolErrorKind::of(&result),
+                Some(ToolErrorKind::InvalidArguments),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn empty_list_and_custom_root() {
+        let (fs, tool) = setup().await;
+        let result = tool
+            .execute("c1", json!({"action": "list"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "Scratchpad is empty");
+
+        let tool =
+            ScratchTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project").with_root(".notes");
+        tool.execute(
+            "c2",
+            json!({"action": "write", "name": "a.txt", "content": "x"}),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(fs.exists("/project/.notes/a.txt").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn list_stops_endless_trees() {
+        let fs = crate::walk::tests::loop_fs().await;
+        let tool = ScratchTool::new(fs, "/").with_root("/w");
+        let result = tool
+            .execute("c1", json!({"action": "list"}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("loop/start.txt (6 bytes)"), "{}", result.content);
+        assert!(result.content.contains("[Walk stopped at depth 64"), "{}", result.content);
+        assert_eq!(result.metadata["walk"]["depth_limited"], 1);
+    }
+
+    #[tokio::test]
+    async fn tool_name_and_definition() {
+        let (_fs, tool) = setup().await;
+        assert_eq!(tool.name(), "scratch");
+        assert_eq!(tool.definition().name, "scratch");
+    }
+}
@@ -0,0 +1,134 @@
+//! Set cwd tool — move the working directory of every tool in a registry.
+//!
+//! Changes the [`WorkingDir`] shared with the registry's middleware, so an
+//! agent working in one package of a monorepo can drop the package prefix
+//! from its paths. Not part of [`all_tools`](crate::presets::all_tools); add
+//! it with [`Toolset::with_working_dir`](crate::Toolset::with_working_dir).
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+
+use crate::error::{tool_error, ToolErrorKind};
+use crate::working_dir::WorkingDir;
+
+use super::{impl_overrides, ToolOverrides};
+
+/// System-prompt guidance returned by [`SetCwdTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    When most of the work is inside one subdirectory, such as a package of a \
+    monorepo, set_cwd there once instead of prefixing every path with it.";
+
+pub struct SetCwdTool {
+    dir: Arc<WorkingDir>,
+    overrides: ToolOverrides,
+}
+
+impl SetCwdTool {
+    pub fn new(dir: Arc<WorkingDir>) -> Self {
+        Self {
+            dir,
+            overrides: ToolOverrides::default(),
+        }
+    }
+}
+
+impl_overrides!(SetCwdTool);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for SetCwdTool {
+    fn name(&self) -> &str {
+        self.overrides.name("set_cwd")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "set_cwd".into(),
+            description: "Change the working directory that relative paths in every tool, and bash commands, resolve against. It must be a directory inside the workspace; pass '.' to see the current one.".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "New working directory, relative to the current one (e.g. 'packages/foo' or '..') or absolute"
+                    }
+                },
+                "required": ["path"]
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let path = arguments.get("path").and_then(|v| v.as_str()).unwrap_or("");
+        if path.is_empty() {
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Missing required parameter: path",
+            ));
+        }
+        let previous = self.dir.relative();
+        if let Err(e) = self.dir.change(path).await {
+            return Ok(tool_error(
+                ToolErrorKind::from_soul_error(&e),
+                e.to_string(),
+            ));
+        }
+        let current = self.dir.relative();
+        let message = match current.as_str() {
+            "." => "Working directory is the workspace root".to_string(),
+            dir => format!("Working directory is now {}", dir),
+        };
+        Ok(ToolOutput::success(message).with_metadata(json!({
+            "cwd": self.dir.current(),
+            "relative": current,
+            "previous": previous,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::{MemoryFs, VirtualFs};
+
+    #[tokio::test]
+    async fn moves_within_the_workspace() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/repo/packages/foo/lib.rs", "").await.unwrap();
+        let dir = Arc::new(WorkingDir::new(fs as Arc<dyn VirtualFs>, "/repo"));
+        let tool = SetCwdTool::new(dir.clone());
+
+        let result = tool
+            .execute("c1", json!({"path": "packages/foo"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "Working directory is now packages/foo");
+        assert_eq!(result.metadata["previous"], ".");
+        assert_eq!(dir.current(), "/repo/packages/foo");
+
+        let result = tool
+            .execute("c2", json!({"path": "/repo"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.content, "Working directory is the workspace root");
+
+        let result = tool
+            .execute("c3", json!({"path": "/etc"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::Policy));
+        assert_eq!(tool.name(), "set_cwd");
+    }
+}
@@ -0,0 +1,200 @@
+//! Stats tool — how big a workspace is, for planning what to read.
+//!
+//! A thin wrapper over [`crate::stats::workspace_stats`]: total files, bytes
+//! and lines, the largest files and per-extension totals, so the model can
+//! tell whether a directory can be read whole or needs grep and outline.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::error::{tool_error, ToolErrorKind};
+use crate::paths;
+use crate::stats::{workspace_stats, LARGEST_FILES, MAX_FILES};
+
+use super::{impl_overrides, resolve_path, ToolOverrides};
+
+/// Default number of largest files listed.
+const DEFAULT_LARGEST: usize = 10;
+
+/// Extensions listed in the output; the metadata has all of them.
+const MAX_EXTENSIONS: usize = 15;
+
+/// System-prompt guidance returned by [`StatsTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Before reading a directory wholesale, check its size with stats; if it is \
+    large, search it with grep or outline instead.";
+
+pub struct StatsTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    overrides: ToolOverrides,
+}
+
+impl StatsTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            overrides: ToolOverrides::default(),
+        }
+    }
+}
+
+impl_overrides!(StatsTool);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for StatsTool {
+    fn name(&self) -> &str {
+        self.overrides.name("stats")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "stats".into(),
+            description: "Total files, bytes and lines under a directory, its largest files and totals per extension, skipping hidden and .gitignore'd paths. Use it to judge how much of a project fits in context before reading it.".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "Directory to measure (defaults to working directory)"
+                    },
+                    "largest": {
+                        "type": "integer",
+                        "description": format!("Number of largest files to list (default: {}, at most {})", DEFAULT_LARGEST, LARGEST_FILES)
+                    }
+                }
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let path = arguments.get("path").and_then(|v| v.as_str()).unwrap_or("");
+        let resolved = resolve_path(&self.cwd, path);
+        let largest = arguments
+            .get("largest")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_LARGEST, |n| (n as usize).min(LARGEST_FILES));
+
+        let stats = match workspace_stats(self.fs.as_ref(), &resolved).await {
+            Ok(stats) => stats,
+            Err(_) => {
+                return Ok(tool_error(
+                    ToolErrorKind::NotFound,
+                    format!(
+                        "Directory not found: {}",
+                        paths::display(&resolved, &self.cwd)
+                    ),
+                ))
+            }
+        };
+
+        let mut content = format!(
+            "{}: {} files, {} bytes, {} lines",
+            paths::display(&resolved, &self.cwd),
+            stats.files,
+            stats.bytes,
+            stats.lines
+        );
+        if stats.binary_files > 0 {
+            content.push_str(&format!(" ({} binary files)", stats.binary_files));
+        }
+        let extensions = stats.extensions_by_size();
+        if !extensions.is_empty() {
+            content.push_str("\n\nBy extension:");
+            for (ext, totals) in extensions.iter().take(MAX_EXTENSIONS) {
+                let ext = match *ext {
+                    "" => "(none)".to_string(),
+                    ext => format!(".{}", ext),
+                };
+                content.push_str(&format!(
+                    "\n  {:<10} {} files, {} bytes, {} lines",
+                    ext, totals.files, totals.bytes, totals.lines
+                ));
+            }
+            if extensions.len() > MAX_EXTENSIONS {
+                content.push_str(&format!(
+                    "\n  ... {} more",
+                    extensions.len() - MAX_EXTENSIONS
+                ));
+            }
+        }
+        if largest > 0 && !stats.largest.is_empty() {
+            content.push_str("\n\nLargest files:");
+            for file in stats.largest.iter().take(largest) {
+                let lines = match file.lines {
+                    Some(lines) => format!("{} lines", lines),
+                    None => "binary".to_string(),
+                };
+                content.push_str(&format!(
+                    "\n  {} ({} bytes, {})",
+                    file.path, file.bytes, lines
+                ));
+            }
+        }
+        if stats.truncated {
+            content.push_str(&format!("\n\n[Stopped after {} files]", MAX_FILES));
+        }
+
+        let mut metadata = json!(stats);
+        metadata["largest"] = json!(stats.largest.iter().take(largest).collect::<Vec<_>>());
+        Ok(ToolOutput::success(content).with_metadata(metadata))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    #[tokio::test]
+    async fn summarizes_a_directory() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/src/lib.rs", &"pub fn f() {}\n".repeat(4))
+            .await
+            .unwrap();
+        fs.write("/project/src/parse.rs", "fn p() {}\n")
+            .await
+            .unwrap();
+        fs.write("/project/README.md", "# Project\n").await.unwrap();
+        let tool = StatsTool::new(fs as Arc<dyn VirtualFs>, "/project");
+
+        let result = tool
+            .execute("c1", json!({"largest": 2}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(
+            result.content,
+            ".: 3 files, 76 bytes, 6 lines\n\n\
+             By extension:\n  \
+             .rs        2 files, 66 bytes, 5 lines\n  \
+             .md        1 files, 10 bytes, 1 lines\n\n\
+             Largest files:\n  \
+             src/lib.rs (56 bytes, 4 lines)\n  \
+             README.md (10 bytes, 1 lines)"
+        );
+        assert_eq!(result.metadata["extensions"]["md"]["files"], 1);
+        assert_eq!(result.metadata["largest"].as_array().unwrap().len(), 2);
+
+        let result = tool
+            .execute("c2", json!({"path": "missing"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+    }
+}
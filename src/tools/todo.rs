@@ -0,0 +1,436 @@
+//! Todo tool — a persistent task list so multi-step agents can keep a visible plan.
+//!
+//! The list lives in a small JSON file in the VFS (default `.soul/todos.json`
+//! under the working directory), so it survives across turns and sessions.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::error::{tool_error, ToolErrorKind};
+
+use super::{impl_overrides, resolve_path, ToolOverrides};
+
+/// Default store location, relative to the working directory.
+pub const DEFAULT_TODO_PATH: &str = ".soul/todos.json";
+
+/// Status of a single todo item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoStatus {
+    Pending,
+    InProgress,
+    Completed,
+}
+
+impl TodoStatus {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "in_progress" => Some(Self::InProgress),
+            "completed" => Some(Self::Completed),
+            _ => None,
+        }
+    }
+
+    fn marker(&self) -> &'static str {
+        match self {
+            Self::Pending => "[ ]",
+            Self::InProgress => "[~]",
+            Self::Completed => "[x]",
+        }
+    }
+}
+
+/// A single task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TodoItem {
+    pub id: u64,
+    pub title: String,
+    pub status: TodoStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+/// On-disk representation of the list.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TodoList {
+    next_id: u64,
+    items: Vec<TodoItem>,
+}
+
+/// System-prompt guidance returned by [`TodoTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    For tasks with three or more steps, add the steps up front, keep exactly one \
+    in_progress while working, and complete each step as soon as it is done.";
+
+pub struct TodoTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    path: String,
+    overrides: ToolOverrides,
+}
+
+impl TodoTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            path: DEFAULT_TODO_PATH.into(),
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+    /// Store the list at `path` (relative to the working directory or absolute).
+    pub fn with_store_path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    async fn load(&self, store: &str) -> Result<TodoList, String> {
+        if !self.fs.exists(store).await.unwrap_or(false) {
+            return Ok(TodoList::default());
+        }
+        let raw = self
+            .fs
+            .read_to_string(store)
+            .await
+            .map_err(|e| format!("Failed to read todo list: {}", e))?;
+        if raw.trim().is_empty() {
+            return Ok(TodoList::default());
+        }
+        serde_json::from_str(&raw).map_err(|e| format!("Corrupt todo list at {}: {}", self.path, e))
+    }
+
+    async fn save(&self, store: &str, list: &TodoList) -> Result<(), String> {
+        if let Some(parent) = store.rsplit_once('/') {
+            if !parent.0.is_empty() {
+                let _ = self.fs.create_dir_all(parent.0).await;
+            }
+        }
+        let json = serde_json::to_string_pretty(list).map_err(|e| e.to_string())?;
+        self.fs
+            .write(store, &json)
+            .await
+            .map_err(|e| format!("Failed to save todo list: {}", e))
+    }
+}
+
+impl_overrides!(TodoTool);
+
+/// Render the list as a checklist with a progress header.
+fn render(list: &TodoList) -> String {
+    if list.items.is_empty() {
+        return "Todo list is empty".into();
+    }
+    let completed = list
+        .items
+        .iter()
+        .filter(|i| i.status == TodoStatus::Completed)
+        .count();
+    let mut out = format!("Todo ({}/{} completed)", completed, list.items.len());
+    for item in &list.items {
+        out.push_str(&format!(
+            "\n{} {}. {}",
+            item.status.marker(),
+            item.id,
+            item.title
+        ));
+        if let Some(notes) = &item.notes {
+            out.push_str(&format!(" — {}", notes));
+        }
+    }
+    out
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for TodoTool {
+    fn name(&self) -> &str {
+        self.overrides.name("todo")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "todo".into(),
+            description: "Track a task list for multi-step work. Add steps up front, mark one in_progress while working on it, complete it when done. Every call returns the full list.".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "action": {
+                        "type": "string",
+                        "enum": ["add", "update", "complete", "list"],
+                        "description": "Operation to perform"
+                    },
+                    "id": {
+                        "type": "integer",
+                        "description": "Item id (update, complete)"
+                    },
+                    "title": {
+                        "type": "string",
+                        "description": "Item title (add, update)"
+                    },
+                    "titles": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "Several item titles to add at once (add)"
+                    },
+                    "status": {
+                        "type": "string",
+                        "enum": ["pending", "in_progress", "completed"],
+                        "description": "New status (update)"
+                    },
+                    "notes": {
+                        "type": "string",
+                        "description": "Free-form notes for the item (add, update)"
+                    }
+                },
+                "required": ["action"]
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        let action = arguments
+            .get("action")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let id = arguments.get("id").and_then(|v| v.as_u64());
+        let title = arguments
+            .get("title")
+            .and_then(|v| v.as_str())
+            .filter(|t| !t.trim().is_empty());
+        let notes = arguments
+            .get("notes")
+            .and_then(|v| v.as_str())
+            .map(String::from);
+
+        let store = resolve_path(&self.cwd, &self.path);
+        let mut list = match self.load(&store).await {
+            Ok(l) => l,
+            Err(e) => return Ok(tool_error(ToolErrorKind::Io, e)),
+        };
+
+        let summary = match action {
+            "list" => None,
+            "add" => {
+                let mut titles: Vec<String> = arguments
+                    .get("titles")
+                    .and_then(|v| v.as_array())
+                    .map(|a| {
+                        a.iter()
+                            .filter_map(|t| t.as_str())
+                            .filter(|t| !t.trim().is_empty())
+                            .map(String::from)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                if let Some(t) = title {
+                    titles.insert(0, t.to_string());
+                }
+                if titles.is_empty() {
+                    return Ok(tool_error(
+                        ToolErrorKind::InvalidArguments,
+                        "add requires title or titles",
+                    ));
+                }
+                let count = titles.len();
+                for title in titles {
+                    list.next_id += 1;
+                    list.items.push(TodoItem {
+                        id: list.next_id,
+                        title,
+                        status: TodoStatus::Pending,
+                        notes: if count == 1 { notes.clone() } else { None },
+                    });
+                }
+                Some(format!("Added {} item(s)", count))
+            }
+            "update" | "complete" => {
+                let Some(id) = id else {
+                    return Ok(tool_error(
+                        ToolErrorKind::InvalidArguments,
+                        format!("{} requires id", action),
+                    ));
+                };
+                let Some(item) = list.items.iter_mut().find(|i| i.id == id) else {
+                    return Ok(tool_error(
+                        ToolErrorKind::NotFound,
+                        format!("No todo item with id {}", id),
+                    ));
+                };
+                if action == "complete" {
+                    item.status = TodoStatus::Completed;
+                } else {
+                    if let Some(status) = arguments.get("status").and_then(|v| v.as_str()) {
+                        match TodoStatus::parse(status) {
+                            Some(s) => item.status = s,
+                            None => return Ok(tool_error(
+                                ToolErrorKind::InvalidArguments,
+                                format!(
+                                    "Invalid status: {} (expected pending, in_progress or completed)",
+                                    status
+                                ),
+                            )),
+                        }
+                    }
+                    if let Some(t) = title {
+                        item.title = t.to_string();
+                    }
+                    if notes.is_some() {
+                        item.notes = notes;
+                    }
+                }
+                Some(format!("Updated item {}", id))
+            }
+            "" => {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    "Missing required parameter: action",
+                ))
+            }
+            other => {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!("Unknown action: {} (expected add, update, complete or list)", other),
+                ))
+            }
+        };
+
+        if summary.is_some() {
+            if let Err(e) = self.save(&store, &list).await {
+                return Ok(tool_error(ToolErrorKind::Io, e));
+            }
+        }
+
+        let rendered = render(&list);
+        let content = match summary {
+            Some(s) => format!("{}\n\n{}", s, rendered),
+            None => rendered,
+        };
+        let completed = list
+            .items
+            .iter()
+            .filter(|i| i.status == TodoStatus::Completed)
+            .count();
+
+        Ok(ToolOutput::success(content).with_metadata(json!({
+            "todos": list.items,
+            "completed": completed,
+            "total": list.items.len(),
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    async fn setup() -> (Arc<MemoryFs>, TodoTool) {
+        let fs = Arc::new(MemoryFs::new());
+        let tool = TodoTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        (fs, tool)
+    }
+
+    #[tokio::test]
+    async fn add_update_complete_list() {
+        let (fs, tool) = setup().await;
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({"action": "add", "titles": ["Read code", "Implement", "Test"]}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        assert!(result.content.contains("Todo (0/3 completed)"));
+        assert!(result.content.contains("[ ] 2. Implement"));
+
+        tool.execute("c2", json!({"action": "complete", "id": 1}), None)
+            .await
+            .unwrap();
+        let result = tool
+            .execute(
+                "c3",
+                json!({"action": "update", "id": 2, "status": "in_progress", "notes": "parser first"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(result.content.contains("[x] 1. Read code"));
+        assert!(result.content.contains("[~] 2. Implement — parser first"));
+
+        // Persisted across tool instances
+        let stored = fs
+            .read_to_string("/project/.soul/todos.json")
+            .await
+            .unwrap();
+        assert!(stored.contains("in_progress"));
+        let again = TodoTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        let result = again
+            .execute("c4", json!({"action": "list"}), None)
+            .await
+            .unwrap();
+        assert!(result.content.starts_with("Todo (1/3 completed)"));
+        assert_eq!(result.metadata["total"], 3);
+        assert_eq!(result.metadata["todos"][1]["status"], "in_progress");
+    }
+
+    #[tokio::test]
+    async fn errors() {
+        let (_fs, tool) = setup().await;
+        let result = tool
+            .execute("c1", json!({"action": "complete", "id": 9}), None)
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(result.content.contains("No todo item"));
+
+        let result = tool
+            .execute("c2", json!({"action": "add"}), None)
+            .await
+            .unwrap();
+        assert!(result.is_error);
+
+        let result = tool
+            .execute("c3", json!({"action": "delete"}), None)
+            .await
+            .unwrap();
+        assert!(result.is_error);
+    }
+
+    #[tokio::test]
+    async fn custom_store_path() {
+        let fs = Arc::new(MemoryFs::new());
+        let tool = TodoTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project")
+            .with_store_path("/state/plan.json");
+        tool.execute("c1", json!({"action": "add", "title": "One"}), None)
+            .await
+            .unwrap();
+        assert!(fs.exists("/state/plan.json").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn tool_name_and_definition() {
+        let (_fs, tool) = setup().await;
+        assert_eq!(tool.name(), "todo");
+        let def = tool.definition();
+        assert_eq!(def.name, "todo");
+    }
+}
@@ -1,5 +1,19 @@
 //! Write tool — create or overwrite files, auto-creating parent directories.
+//!
+//! Optional guards (all off by default) reject writes that are too large or
+//! that look like an accidental truncation of the existing file — the model
+//! "rewriting" a 2000-line module as an 80-line stub with `// ... rest
+//! unchanged` placeholders. A call can bypass them with `force: true`.
+//!
+//! With [`FileModes`] configured, overwrites keep the file's permissions and
+//! a `mode` argument sets them, so scripts the model writes can be run.
+//!
+//! A call passing `variables` has `{{name}}` placeholders in its content
+//! filled in first, from those values and any the host set with
+//! [`WriteTool::with_variables`] (a package name, a license header), so the
+//! model needn't restate them. A placeholder with no value fails the call.
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -11,11 +25,43 @@ use soul_core::tool::{Tool, ToolOutput};
 use soul_core::types::ToolDefinition;
 use soul_core::vfs::VirtualFs;
 
-use super::resolve_path;
+use crate::error::{tool_error, ToolErrorKind};
+use crate::file_modes::{current_mode, format_mode, parse_mode, restore_mode, FileModes};
+use crate::locks::PathLocks;
+use crate::diff::render_diff;
+use crate::truncate::MAX_DIFF_BYTES;
+
+use super::scaffold::{parse_variables, render_template};
+use super::{impl_overrides, resolve_path, set_metadata, ToolOverrides};
+
+/// System-prompt guidance returned by [`WriteTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Create new files or replace one completely. To change part of an existing file \
+    use edit; if you do rewrite a file, read it first so nothing is lost.";
 
 pub struct WriteTool {
     fs: Arc<dyn VirtualFs>,
     cwd: String,
+    locks: Arc<PathLocks>,
+    modes: Option<Arc<dyn FileModes>>,
+    max_bytes: Option<usize>,
+    max_lines: Option<usize>,
+    shrink_ratio: Option<f64>,
+    max_diff_bytes: usize,
+    variables: BTreeMap<String, String>,
+    overrides: ToolOverrides,
+}
+
+/// Whether `line` is an elision placeholder such as `// ... rest unchanged`,
+/// `# ...` or `/* ... */`.
+fn is_elision_marker(line: &str) -> bool {
+    let line = line.trim();
+    let body = ["//", "#", "--", "/*", "<!--", "*"]
+        .iter()
+        .find_map(|prefix| line.strip_prefix(prefix))
+        .unwrap_or(line)
+        .trim_start();
+    body.starts_with("...") || body.starts_with('…')
 }
 
 impl WriteTool {
@@ -23,21 +69,166 @@ impl WriteTool {
         Self {
             fs,
             cwd: cwd.into(),
+            locks: Arc::new(PathLocks::new()),
+            modes: None,
+            max_bytes: None,
+            max_lines: None,
+            shrink_ratio: None,
+            max_diff_bytes: MAX_DIFF_BYTES,
+            variables: BTreeMap::new(),
+            overrides: ToolOverrides::default(),
         }
     }
+
+    /// Share `locks` with other tools, so that their changes to the same
+    /// file wait for each other (default: a set of this tool's own).
+    pub fn with_locks(mut self, locks: Arc<PathLocks>) -> Self {
+        self.locks = locks;
+        self
+    }
+
+    /// Keep the permissions of overwritten files and accept a `mode`
+    /// argument (default: modes are left to the filesystem, and `mode` is
+    /// rejected).
+    pub fn with_file_modes(mut self, modes: Arc<dyn FileModes>) -> Self {
+        self.modes = Some(modes);
+        self
+    }
+
+    /// Reject content larger than `bytes` (default: no limit).
+    pub fn with_max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Reject content with more than `lines` lines (default: no limit).
+    pub fn with_max_lines(mut self, lines: usize) -> Self {
+        self.max_lines = Some(lines);
+        self
+    }
+
+    /// Reject overwrites that look like an accidental truncation: the new
+    /// content is under `ratio` (e.g. `0.3`) of the existing file's size, or
+    /// it shrinks the file and introduces elision placeholders like
+    /// `// ... rest unchanged` (default: off).
+    pub fn with_truncation_guard(mut self, ratio: f64) -> Self {
+        self.shrink_ratio = Some(ratio.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Cap the diff in the output at about `bytes`, summarizing the hunks
+    /// left out; the `diff` metadata still counts all of them (default:
+    /// [`MAX_DIFF_BYTES`]).
+    pub fn with_max_diff_bytes(mut self, bytes: usize) -> Self {
+        self.max_diff_bytes = bytes;
+        self
+    }
+
+    /// Values for `{{name}}` placeholders in calls that pass `variables`,
+    /// which take precedence over these (default: none).
+    pub fn with_variables(mut self, variables: BTreeMap<String, String>) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// The guard `content` trips, as `(name, kind, message)`.
+    fn check_guards(
+        &self,
+        path: &str,
+        content: &str,
+        previous: Option<&str>,
+    ) -> Option<(&'static str, ToolErrorKind, String)> {
+        if let Some(max) = self.max_bytes {
+            if content.len() > max {
+                return Some((
+                    "max_bytes",
+                    ToolErrorKind::TooLarge,
+                    format!(
+                        "Refusing to write {}: {} bytes exceeds the {}-byte limit",
+                        path,
+                        content.len(),
+                        max
+                    ),
+                ));
+            }
+        }
+        if let Some(max) = self.max_lines {
+            let lines = content.lines().count();
+            if lines > max {
+                return Some((
+                    "max_lines",
+                    ToolErrorKind::TooLarge,
+                    format!(
+                        "Refusing to write {}: {} lines exceeds the {}-line limit",
+                        path, lines, max
+                    ),
+                ));
+            }
+        }
+        let (Some(ratio), Some(old)) = (self.shrink_ratio, previous) else {
+            return None;
+        };
+        if content.len() >= old.len() {
+            return None;
+        }
+        let old_lines = old.lines().count();
+        let new_lines = content.lines().count();
+        let placeholder = content
+            .lines()
+            .find(|line| is_elision_marker(line) && !old.lines().any(|o| o == *line));
+        if let Some(line) = placeholder {
+            return Some((
+                "truncation",
+                ToolErrorKind::Policy,
+                format!(
+                    "Refusing to write {}: the new content shrinks it from {} to {} lines and contains the placeholder {:?}. Write the complete file, use edit for partial changes, or pass force: true if this is intended.",
+                    path,
+                    old_lines,
+                    new_lines,
+                    line.trim()
+                ),
+            ));
+        }
+        if (content.len() as f64) < old.len() as f64 * ratio {
+            return Some((
+                "truncation",
+                ToolErrorKind::Policy,
+                format!(
+                    "Refusing to write {}: the new content is {}% of the existing file ({} -> {} lines), which looks like an accidental truncation. Use edit for partial changes, or pass force: true if this is intended.",
+                    path,
+                    content.len() * 100 / old.len(),
+                    old_lines,
+                    new_lines
+                ),
+            ));
+        }
+        None
+    }
 }
 
+impl_overrides!(WriteTool);
+
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl Tool for WriteTool {
     fn name(&self) -> &str {
-        "write"
+        self.overrides.name("write")
     }
 
     fn definition(&self) -> ToolDefinition {
-        ToolDefinition {
+        let mut variables = String::from(
+            "Fill in {{name}} placeholders in content with these values, e.g. {\"name\": \"parser\"}; every placeholder needs a value",
+        );
+        if !self.variables.is_empty() {
+            let names: Vec<_> = self.variables.keys().map(String::as_str).collect();
+            variables.push_str(&format!(
+                ". Also available without passing them: {}",
+                names.join(", ")
+            ));
+        }
+        self.overrides.apply(ToolDefinition {
             name: "write".into(),
-            description: "Write content to a file. Creates the file and parent directories if they don't exist. Overwrites existing files.".into(),
+            description: "Write content to a file. Creates the file and parent directories if they don't exist. Overwrites existing files and returns a diff against the previous content; writing identical content is a no-op.".into(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -48,11 +239,24 @@ impl Tool for WriteTool {
                     "content": {
                         "type": "string",
                         "description": "Content to write to the file"
+                    },
+                    "force": {
+                        "type": "boolean",
+                        "description": "Bypass size limits and the truncation check (default: false)"
+                    },
+                    "mode": {
+                        "type": "string",
+                        "description": "Octal permissions to set, e.g. \"755\" for an executable script (default: keep the existing file's, or the filesystem default for new files)"
+                    },
+                    "variables": {
+                        "type": "object",
+                        "additionalProperties": {"type": ["string", "number", "boolean"]},
+                        "description": variables
                     }
                 },
                 "required": ["path", "content"]
             }),
-        }
+        })
     }
 
     async fn execute(
@@ -61,6 +265,19 @@ impl Tool for WriteTool {
         arguments: serde_json::Value,
         _partial_tx: Option<mpsc::UnboundedSender<String>>,
     ) -> SoulResult<ToolOutput> {
+        let lock = self.locks.lock_for(&self.cwd, &arguments).await;
+        let mut output = self.apply(arguments).await?;
+        if let Some(lock) = lock {
+            lock.annotate(&mut output);
+        }
+        Ok(output)
+    }
+}
+
+impl WriteTool {
+    /// Carry out a call; [`execute`](Tool::execute) holds the path lock
+    /// around it.
+    async fn apply(&self, arguments: serde_json::Value) -> SoulResult<ToolOutput> {
         // If the provider passed args as a JSON string instead of an object
         // (can happen with large payloads on streaming providers), unwrap it.
         let arguments = if let Some(s) = arguments.as_str() {
@@ -77,13 +294,114 @@ impl Tool for WriteTool {
             .get("content")
             .and_then(|v| v.as_str())
             .unwrap_or("");
+        let force = arguments
+            .get("force")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
 
         if path.is_empty() {
-            return Ok(ToolOutput::error("Missing required parameter: path"));
+            return Ok(tool_error(
+                ToolErrorKind::InvalidArguments,
+                "Missing required parameter: path",
+            ));
         }
 
+        let mode = match arguments.get("mode").filter(|v| !v.is_null()) {
+            None => None,
+            Some(_) if self.modes.is_none() => {
+                return Ok(tool_error(
+                    ToolErrorKind::Unsupported,
+                    "This filesystem does not support file modes; omit mode",
+                ))
+            }
+            Some(value) => match parse_mode(value) {
+                Ok(mode) => Some(mode),
+                Err(message) => return Ok(tool_error(ToolErrorKind::InvalidArguments, message)),
+            },
+        };
+
+        let rendered = match arguments.get("variables").filter(|v| !v.is_null()) {
+            None => None,
+            Some(vars) => {
+                let mut variables = match parse_variables(vars) {
+                    Ok(variables) => variables,
+                    Err(message) => {
+                        return Ok(tool_error(ToolErrorKind::InvalidArguments, message))
+                    }
+                };
+                for (name, value) in &self.variables {
+                    variables.entry(name.clone()).or_insert_with(|| value.clone());
+                }
+                let mut missing = BTreeSet::new();
+                let rendered = render_template(content, &variables, &mut missing);
+                if !missing.is_empty() {
+                    let names: Vec<_> = missing.into_iter().collect();
+                    let mut out = tool_error(
+                        ToolErrorKind::InvalidArguments,
+                        format!("Missing template variables: {}", names.join(", ")),
+                    );
+                    set_metadata(&mut out, "missing_variables", json!(names));
+                    return Ok(out);
+                }
+                Some(rendered)
+            }
+        };
+        let content = rendered.as_deref().unwrap_or(content);
+
         let resolved = resolve_path(&self.cwd, path);
 
+        // Capture previous content so overwrites can report what changed
+        let previous = self.fs.read_to_string(&resolved).await.ok();
+        let previous_mode = match previous {
+            Some(_) => current_mode(&self.modes, &resolved).await,
+            None => None,
+        };
+
+        // Identical content: leave the file (and its mtime) untouched so
+        // watchers and incremental builds don't see a spurious change
+        if previous.as_deref() == Some(content) {
+            let mut message = format!(
+                "No change: {} already has this content ({} bytes)",
+                path,
+                content.len()
+            );
+            if let (Some(mode), Some(modes)) = (mode, &self.modes) {
+                if previous_mode != Some(mode) {
+                    if let Err(e) = modes.set_mode(&resolved, mode).await {
+                        return Ok(tool_error(
+                            ToolErrorKind::from_soul_error(&e),
+                            format!("Failed to set mode of {}: {}", path, e),
+                        ));
+                    }
+                    message = format!(
+                        "Set mode of {} to {} (content unchanged)",
+                        path,
+                        format_mode(mode)
+                    );
+                }
+            }
+            return Ok(ToolOutput::success(message).with_metadata(json!({
+                "bytes_written": 0,
+                "path": path,
+                "created": false,
+                "modified": false,
+                "previous_size": content.len(),
+                "new_size": content.len(),
+                "mode": mode.or(previous_mode).map(format_mode),
+            })));
+        }
+
+        if !force {
+            if let Some((guard, kind, message)) =
+                self.check_guards(path, content, previous.as_deref())
+            {
+                let mut out = tool_error(kind, message);
+                set_metadata(&mut out, "guard", json!(guard));
+                set_metadata(&mut out, "path", json!(path));
+                return Ok(out);
+            }
+        }
+
         // Auto-create parent directories
         if let Some(parent) = resolved.rsplit_once('/') {
             if !parent.0.is_empty() {
@@ -92,19 +410,47 @@ impl Tool for WriteTool {
         }
 
         match self.fs.write(&resolved, content).await {
-            Ok(()) => Ok(ToolOutput::success(format!(
-                "Wrote {} bytes to {}",
-                content.len(),
-                path
-            ))
-            .with_metadata(json!({
-                "bytes_written": content.len(),
-                "path": path,
-            }))),
-            Err(e) => Ok(ToolOutput::error(format!(
-                "Failed to write {}: {}",
-                path, e
-            ))),
+            Ok(()) => {
+                let mut output = format!("Wrote {} bytes to {}", content.len(), path);
+                let mut diff = None;
+
+                if let Some(modes) = &self.modes {
+                    match mode {
+                        Some(mode) => {
+                            if let Err(e) = modes.set_mode(&resolved, mode).await {
+                                return Ok(tool_error(
+                                    ToolErrorKind::from_soul_error(&e),
+                                    format!("Wrote {} but failed to set its mode: {}", path, e),
+                                ));
+                            }
+                            output.push_str(&format!(" (mode {})", format_mode(mode)));
+                        }
+                        None => restore_mode(&self.modes, &resolved, previous_mode).await,
+                    }
+                }
+
+                if let Some(ref old) = previous {
+                    let rendered = render_diff(old, content, path, self.max_diff_bytes);
+                    output.push_str("\n\n");
+                    output.push_str(&rendered.text);
+                    diff = Some(rendered.to_metadata());
+                }
+
+                Ok(ToolOutput::success(output).with_metadata(json!({
+                    "bytes_written": content.len(),
+                    "path": path,
+                    "created": previous.is_none(),
+                    "modified": true,
+                    "previous_size": previous.as_ref().map(|old| old.len()),
+                    "new_size": content.len(),
+                    "mode": mode.or(previous_mode).map(format_mode),
+                    "diff": diff,
+                })))
+            }
+            Err(e) => Ok(tool_error(
+                ToolErrorKind::from_soul_error(&e),
+                format!("Failed to write {}: {}", path, e),
+            )),
         }
     }
 }
@@ -171,6 +517,67 @@ mod tests {
         assert_eq!(content, "new content");
     }
 
+    #[tokio::test]
+    async fn write_overwrite_includes_diff() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/diff.txt", "line1\nline2\nline3\n")
+            .await
+            .unwrap();
+
+        let result = tool
+            .execute(
+                "c7",
+                json!({"path": "diff.txt", "content": "line1\nchanged\nline3\n"}),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error);
+        assert!(result.content.contains("-line2"));
+        assert!(result.content.contains("+changed"));
+        assert_eq!(result.metadata["created"], json!(false));
+        assert_eq!(result.metadata["previous_size"], json!(18));
+        assert_eq!(result.metadata["new_size"], json!(20));
+    }
+
+    #[tokio::test]
+    async fn write_new_file_reports_created() {
+        let (_fs, tool) = setup().await;
+        let result = tool
+            .execute("c8", json!({"path": "fresh.txt", "content": "new"}), None)
+            .await
+            .unwrap();
+
+        assert!(!result.is_error);
+        assert!(!result.content.contains("---"));
+        assert_eq!(result.metadata["created"], json!(true));
+        assert_eq!(result.metadata["previous_size"], json!(null));
+        assert_eq!(result.metadata["new_size"], json!(3));
+    }
+
+    #[tokio::test]
+    async fn write_unchanged_is_noop() {
+        let (fs, tool) = setup().await;
+        fs.write("/project/same.txt", "same\n").await.unwrap();
+
+        let result = tool
+            .execute("c9", json!({"path": "same.txt", "content": "same\n"}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        assert!(result.content.starts_with("No change"));
+        assert!(!result.content.contains("---"));
+        assert_eq!(result.metadata["modified"], json!(false));
+        assert_eq!(result.metadata["bytes_written"], json!(0));
+
+        let result = tool
+            .execute("c10", json!({"path": "same.txt", "content": "other\n"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["modified"], json!(true));
+    }
+
     #[tokio::test]
     async fn write_empty_path() {
         let (_fs, tool) = setup().await;
@@ -206,8 +613,196 @@ mod tests {
         assert_eq!(def.name, "write");
     }
 
+    #[tokio::test]
+    async fn write_size_guards() {
+        let (fs, _tool) = setup().await;
+        let tool = WriteTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project")
+            .with_max_bytes(10)
+            .with_max_lines(2);
+
+        let result = tool
+            .execute("c1", json!({"path": "big.txt", "content": "0123456789abc"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::TooLarge));
+        assert_eq!(result.metadata["guard"], "max_bytes");
+        assert!(!fs.exists("/project/big.txt").await.unwrap());
+
+        let result = tool
+            .execute("c2", json!({"path": "big.txt", "content": "a\nb\nc\n"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["guard"], "max_lines");
+
+        let result = tool
+            .execute(
+                "c3",
+                json!({"path": "big.txt", "content": "a\nb\nc\n", "force": true}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+    }
+
+    #[tokio::test]
+    async fn write_truncation_guard() {
+        let (fs, _tool) = setup().await;
+        let original: String = (0..100).map(|i| format!("fn f{}() {{}}\n", i)).collect();
+        fs.write("/project/lib.rs", &original).await.unwrap();
+        let tool = WriteTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project")
+            .with_truncation_guard(0.3);
+
+        // Far smaller than the original
+        let result = tool
+            .execute("c1", json!({"path": "lib.rs", "content": "fn f0() {}\n"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::Policy));
+        assert_eq!(result.metadata["guard"], "truncation");
+        assert!(result.content.contains("100 -> 1 lines"), "{}", result.content);
+
+        // Mostly intact, but with an elision placeholder
+        let half: String = original.lines().take(50).map(|l| format!("{}\n", l)).collect();
+        let stub = format!("{}// ... rest unchanged\n", half);
+        let result = tool
+            .execute("c2", json!({"path": "lib.rs", "content": stub}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.metadata["guard"], "truncation");
+        assert!(result.content.contains("// ... rest unchanged"));
+        assert_eq!(fs.read_to_string("/project/lib.rs").await.unwrap(), original);
+
+        // Modest shrink, new files and forced writes go through
+        let result = tool
+            .execute("c3", json!({"path": "lib.rs", "content": half}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        let result = tool
+            .execute("c4", json!({"path": "lib.rs", "content": "", "force": true}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        let result = tool
+            .execute("c5", json!({"path": "new.rs", "content": "// ...\n"}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+    }
+
+    #[test]
+    fn elision_markers() {
+        assert!(is_elision_marker("    // ... existing code ..."));
+        assert!(is_elision_marker("# ..."));
+        assert!(is_elision_marker("/* … */"));
+        assert!(is_elision_marker("..."));
+        assert!(!is_elision_marker("let rest = [..];"));
+        assert!(!is_elision_marker("foo(...args)"));
+    }
+
+    #[tokio::test]
+    async fn write_keeps_and_sets_modes() {
+        use crate::file_modes::MemoryFileModes;
+
+        let (fs, tool) = setup().await;
+        let result = tool
+            .execute("c1", json!({"path": "run.sh", "content": "x", "mode": "755"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::Unsupported));
+
+        let modes = Arc::new(MemoryFileModes::new());
+        let tool = tool.with_file_modes(modes.clone());
+        let result = tool
+            .execute("c2", json!({"path": "run.sh", "content": "echo 1\n", "mode": "755"}), None)
+            .await
+            .unwrap();
+        assert!(result.content.contains("(mode 755)"), "{}", result.content);
+        assert_eq!(modes.mode("/project/run.sh").await, Some(0o755));
+
+        // A backend that resets modes on write: the overwrite puts +x back
+        fs.write("/project/run.sh", "echo 1\n").await.unwrap();
+        modes.set_mode("/project/run.sh", 0o755).await.unwrap();
+        let overwrite = tool
+            .execute("c3", json!({"path": "run.sh", "content": "echo 2\n"}), None)
+            .await
+            .unwrap();
+        assert_eq!(overwrite.metadata["mode"], "755");
+
+        // Same content, new mode: only the mode changes
+        let result = tool
+            .execute("c4", json!({"path": "run.sh", "content": "echo 2\n", "mode": 700}), None)
+            .await
+            .unwrap();
+        assert!(result.content.starts_with("Set mode of run.sh to 700"));
+        assert_eq!(modes.mode("/project/run.sh").await, Some(0o700));
+
+        let result = tool
+            .execute("c5", json!({"path": "run.sh", "content": "", "mode": "rwx"}), None)
+            .await
+            .unwrap();
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::InvalidArguments));
+    }
+
     /// When streaming providers return args as a JSON-escaped string instead of
     /// an object, the write tool should unwrap and use it correctly.
+    #[tokio::test]
+    async fn write_renders_variables() {
+        let fs = Arc::new(MemoryFs::new());
+        let tool = WriteTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project").with_variables(
+            BTreeMap::from([
+                ("license".to_string(), "// SPDX-License-Identifier: MIT".to_string()),
+                ("crate".to_string(), "soul".to_string()),
+            ]),
+        );
+        assert!(tool.definition().input_schema["properties"]["variables"]["description"]
+            .as_str()
+            .unwrap()
+            .ends_with("Also available without passing them: crate, license"));
+
+        let content = "{{license}}\n//! {{crate}}::{{module}}, v{{ version }}\n";
+        let result = tool
+            .execute(
+                "c1",
+                json!({
+                    "path": "src/parser.rs",
+                    "content": content,
+                    "variables": {"module": "parser", "version": 2, "crate": "soul_coder"}
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(
+            fs.read_to_string("/project/src/parser.rs").await.unwrap(),
+            "// SPDX-License-Identifier: MIT\n//! soul_coder::parser, v2\n"
+        );
+
+        // Without variables, placeholders are written as they are
+        tool.execute("c2", json!({"path": "t.hbs", "content": "{{title}}"}), None)
+            .await
+            .unwrap();
+        assert_eq!(fs.read_to_string("/project/t.hbs").await.unwrap(), "{{title}}");
+
+        let result = tool
+            .execute(
+                "c3",
+                json!({
+                    "path": "b.rs",
+                    "content": "{{license}} {{author}} {{year}}",
+                    "variables": {}
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.content, "Missing template variables: author, year");
+        assert_eq!(result.metadata["missing_variables"], json!(["author", "year"]));
+        assert!(!fs.exists("/project/b.rs").await.unwrap());
+    }
+
     #[tokio::test]
     async fn write_string_encoded_args() {
         let (fs, tool) = setup().await;
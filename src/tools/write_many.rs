@@ -0,0 +1,456 @@
+//! Write many tool — create or overwrite several files in one call.
+//!
+//! Scaffolding a feature usually touches a few related files at once: a new
+//! module, its tests and the `mod.rs` line declaring it. As separate write
+//! calls, a failure halfway leaves the tree with only some of them. Here
+//! every file is staged first — arguments, paths and current contents are
+//! checked before anything is written — and if a write still fails, the
+//! files already written are put back as they were and new ones removed, so
+//! the call changes all of the files or none. Parent directories created on
+//! the way may be left behind empty.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use soul_core::error::SoulResult;
+use soul_core::tool::{Tool, ToolOutput};
+use soul_core::types::ToolDefinition;
+use soul_core::vfs::VirtualFs;
+
+use crate::diff::render_diff;
+use crate::error::{tool_error, ToolErrorKind};
+use crate::file_modes::{current_mode, restore_mode, FileModes};
+use crate::locks::PathLocks;
+use crate::paths;
+use crate::truncate::MAX_DIFF_BYTES;
+
+use super::{impl_overrides, resolve_path, ToolOverrides};
+
+/// Maximum files written by one call.
+pub const MAX_FILES: usize = 100;
+
+/// System-prompt guidance returned by [`WriteManyTool::usage_guide`].
+pub const USAGE_GUIDE: &str = "\
+    Create or replace several related files at once, such as a new module, its \
+    tests and the line declaring it. Either every file is written or none is.";
+
+pub struct WriteManyTool {
+    fs: Arc<dyn VirtualFs>,
+    cwd: String,
+    locks: Arc<PathLocks>,
+    modes: Option<Arc<dyn FileModes>>,
+    max_diff_bytes: usize,
+    overrides: ToolOverrides,
+}
+
+/// One file of a call, staged before anything is written.
+struct Staged {
+    path: String,
+    shown: String,
+    content: String,
+    previous: Option<String>,
+    mode: Option<u32>,
+}
+
+impl Staged {
+    fn changed(&self) -> bool {
+        self.previous.as_deref() != Some(self.content.as_str())
+    }
+}
+
+impl WriteManyTool {
+    pub fn new(fs: Arc<dyn VirtualFs>, cwd: impl Into<String>) -> Self {
+        Self {
+            fs,
+            cwd: cwd.into(),
+            locks: Arc::new(PathLocks::new()),
+            modes: None,
+            max_diff_bytes: MAX_DIFF_BYTES,
+            overrides: ToolOverrides::default(),
+        }
+    }
+
+    /// Share `locks` with other tools, so that their changes to the same
+    /// files wait for each other (default: a set of this tool's own).
+    pub fn with_locks(mut self, locks: Arc<PathLocks>) -> Self {
+        self.locks = locks;
+        self
+    }
+
+    /// Keep the permissions of overwritten files (default: modes are left
+    /// to the filesystem).
+    pub fn with_file_modes(mut self, modes: Arc<dyn FileModes>) -> Self {
+        self.modes = Some(modes);
+        self
+    }
+
+    /// Cap the diffs of overwritten files in the output at about `bytes` in
+    /// total (default: [`MAX_DIFF_BYTES`]).
+    pub fn with_max_diff_bytes(mut self, bytes: usize) -> Self {
+        self.max_diff_bytes = bytes;
+        self
+    }
+
+    /// Parse the `files` argument into `(path, content)` pairs.
+    fn entries(&self, arguments: &serde_json::Value) -> Result<Vec<(String, String)>, String> {
+        let files = match arguments.get("files").and_then(|v| v.as_array()) {
+            Some(files) if !files.is_empty() => files,
+            _ => return Err("Missing required parameter: files (a non-empty array)".into()),
+        };
+        let mut entries = Vec::with_capacity(files.len());
+        for (i, file) in files.iter().enumerate() {
+            let path = file.get("path").and_then(|v| v.as_str()).unwrap_or("");
+            if path.is_empty() {
+                return Err(format!("files[{}]: missing path", i));
+            }
+            let Some(content) = file.get("content").and_then(|v| v.as_str()) else {
+                return Err(format!("files[{}] ({}): missing content", i, path));
+            };
+            entries.push((resolve_path(&self.cwd, path), content.to_string()));
+        }
+        Ok(entries)
+    }
+
+    /// Undo the writes of `written`, most recent first. Returns the files
+    /// that could not be restored.
+    async fn roll_back(&self, written: &[&Staged]) -> Vec<String> {
+        let mut failed = Vec::new();
+        for file in written.iter().rev() {
+            let result = match &file.previous {
+                Some(previous) => self.fs.write(&file.path, previous).await,
+                None => self.fs.remove_file(&file.path).await,
+            };
+            match result {
+                Ok(()) => restore_mode(&self.modes, &file.path, file.mode).await,
+                Err(_) => failed.push(file.shown.clone()),
+            }
+        }
+        failed
+    }
+}
+
+impl_overrides!(WriteManyTool);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Tool for WriteManyTool {
+    fn name(&self) -> &str {
+        self.overrides.name("write_many")
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        self.overrides.apply(ToolDefinition {
+            name: "write_many".into(),
+            description: "Write several files in one call, creating parent directories as needed. All files are checked before any is written, and a failure rolls back the ones already written, so either every file changes or none does. Returns diffs against overwritten files.".into(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "files": {
+                        "type": "array",
+                        "description": "Files to write, each at most once",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "path": {
+                                    "type": "string",
+                                    "description": "File path (relative to working directory or absolute)"
+                                },
+                                "content": {
+                                    "type": "string",
+                                    "description": "Complete content of the file"
+                                }
+                            },
+                            "required": ["path", "content"]
+                        }
+                    }
+                },
+                "required": ["files"]
+            }),
+        })
+    }
+
+    async fn execute(
+        &self,
+        _call_id: &str,
+        arguments: serde_json::Value,
+        _partial_tx: Option<mpsc::UnboundedSender<String>>,
+    ) -> SoulResult<ToolOutput> {
+        // Large payloads sometimes arrive as a JSON string
+        let arguments = match arguments.as_str() {
+            Some(raw) => serde_json::from_str(raw).unwrap_or(arguments),
+            None => arguments,
+        };
+
+        let entries = match self.entries(&arguments) {
+            Ok(entries) => entries,
+            Err(message) => return Ok(tool_error(ToolErrorKind::InvalidArguments, message)),
+        };
+        if entries.len() > MAX_FILES {
+            return Ok(tool_error(
+                ToolErrorKind::TooLarge,
+                format!(
+                    "Refusing to write {} files in one call (limit {}); split them up",
+                    entries.len(),
+                    MAX_FILES
+                ),
+            ));
+        }
+        let mut seen = HashSet::new();
+        for (path, _) in &entries {
+            if !seen.insert(path.as_str()) {
+                return Ok(tool_error(
+                    ToolErrorKind::InvalidArguments,
+                    format!(
+                        "{} is listed more than once; nothing was written",
+                        paths::display(path, &self.cwd)
+                    ),
+                ));
+            }
+        }
+
+        // Lock in path order, so two calls sharing files can't deadlock
+        let mut order: Vec<&str> = seen.into_iter().collect();
+        order.sort_unstable();
+        let mut guards = Vec::with_capacity(order.len());
+        for path in order {
+            guards.push(self.locks.lock(path).await);
+        }
+
+        let mut staged = Vec::with_capacity(entries.len());
+        for (path, content) in entries {
+            let shown = paths::display(&path, &self.cwd);
+            if let Ok(metadata) = self.fs.metadata(&path).await {
+                if metadata.is_dir {
+                    return Ok(tool_error(
+                        ToolErrorKind::InvalidArguments,
+                        format!("{} is a directory; nothing was written", shown),
+                    ));
+                }
+            }
+            let previous = self.fs.read_to_string(&path).await.ok();
+            let mode = match previous {
+                Some(_) => current_mode(&self.modes, &path).await,
+                None => None,
+            };
+            staged.push(Staged {
+                path,
+                shown,
+                content,
+                previous,
+                mode,
+            });
+        }
+
+        let mut written: Vec<&Staged> = Vec::new();
+        for file in staged.iter().filter(|f| f.changed()) {
+            if let Some((parent, _)) = file.path.rsplit_once('/') {
+                if !parent.is_empty() {
+                    let _ = self.fs.create_dir_all(parent).await;
+                }
+            }
+            if let Err(e) = self.fs.write(&file.path, &file.content).await {
+                let failed = self.roll_back(&written).await;
+                let outcome = match failed.is_empty() {
+                    true => "no files were changed".to_string(),
+                    false => format!("could not restore {}", failed.join(", ")),
+                };
+                return Ok(tool_error(
+                    ToolErrorKind::from_soul_error(&e),
+                    format!("Failed to write {}: {}; {}", file.shown, e, outcome),
+                ));
+            }
+            restore_mode(&self.modes, &file.path, file.mode).await;
+            written.push(file);
+        }
+
+        let created = staged.iter().filter(|f| f.previous.is_none()).count();
+        let unchanged = staged.iter().filter(|f| !f.changed()).count();
+        let updated = staged.len() - created - unchanged;
+        let bytes: usize = written.iter().map(|f| f.content.len()).sum();
+
+        let mut output = format!(
+            "Wrote {} of {} file(s), {} bytes ({} created, {} updated, {} unchanged)",
+            written.len(),
+            staged.len(),
+            bytes,
+            created,
+            updated,
+            unchanged
+        );
+        let mut diffs = String::new();
+        let mut budget = self.max_diff_bytes;
+        let mut omitted = 0;
+        let mut files = Vec::with_capacity(staged.len());
+        for file in &staged {
+            let mut diff = None;
+            let status = match &file.previous {
+                None => "created",
+                Some(_) if !file.changed() => "unchanged",
+                Some(previous) if budget == 0 => {
+                    omitted += 1;
+                    let stats = render_diff(previous, &file.content, &file.shown, 0).stats;
+                    diff = Some(json!(stats));
+                    "updated"
+                }
+                Some(previous) => {
+                    let rendered = render_diff(previous, &file.content, &file.shown, budget);
+                    budget = budget.saturating_sub(rendered.text.len());
+                    diffs.push_str("\n\n");
+                    diffs.push_str(&rendered.text);
+                    diff = Some(rendered.to_metadata());
+                    "updated"
+                }
+            };
+            output.push_str(&format!(
+                "\n  {} {} ({} bytes)",
+                status,
+                file.shown,
+                file.content.len()
+            ));
+            files.push(json!({
+                "path": file.shown,
+                "status": status,
+                "bytes": file.content.len(),
+                "diff": diff,
+            }));
+        }
+        output.push_str(&diffs);
+        if omitted > 0 {
+            output.push_str(&format!(
+                "\n\n[Diffs of {} more updated file(s) omitted]",
+                omitted
+            ));
+        }
+
+        let mut output = ToolOutput::success(output).with_metadata(json!({
+            "files": files,
+            "created": created,
+            "updated": updated,
+            "unchanged": unchanged,
+            "bytes_written": bytes,
+        }));
+        if let Some(guard) = guards.iter().find(|g| g.contended()) {
+            guard.annotate(&mut output);
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    async fn setup() -> (Arc<MemoryFs>, WriteManyTool) {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/src/lib.rs", "pub mod lexer;\n")
+            .await
+            .unwrap();
+        fs.write("/project/README.md", "# Parser\n").await.unwrap();
+        let tool = WriteManyTool::new(fs.clone() as Arc<dyn VirtualFs>, "/project");
+        (fs, tool)
+    }
+
+    #[tokio::test]
+    async fn writes_a_module_with_its_tests() {
+        let (fs, tool) = setup().await;
+        let result = tool
+            .execute(
+                "c1",
+                json!({"files": [
+                    {"path": "src/parser.rs", "content": "pub fn parse() {}\n"},
+                    {"path": "tests/parser.rs", "content": "#[test]\nfn parses() {}\n"},
+                    {"path": "src/lib.rs", "content": "pub mod lexer;\npub mod parser;\n"},
+                    {"path": "README.md", "content": "# Parser\n"},
+                ]}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert!(
+            result
+                .content
+                .starts_with("Wrote 3 of 4 file(s), 72 bytes (2 created, 1 updated, 1 unchanged)"),
+            "{}",
+            result.content
+        );
+        assert!(result.content.contains("\n  updated src/lib.rs"));
+        assert!(result.content.contains("+pub mod parser;"));
+        assert_eq!(result.metadata["files"][2]["diff"]["added"], 1);
+        assert_eq!(result.metadata["files"][3]["status"], "unchanged");
+        assert_eq!(
+            fs.read_to_string("/project/tests/parser.rs").await.unwrap(),
+            "#[test]\nfn parses() {}\n"
+        );
+        assert_eq!(
+            fs.read_to_string("/project/src/lib.rs").await.unwrap(),
+            "pub mod lexer;\npub mod parser;\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_calls_write_nothing() {
+        let (fs, tool) = setup().await;
+        let calls = [
+            json!({"files": []}),
+            json!({"files": [{"path": "a.rs", "content": "x"}, {"path": "b.rs"}]}),
+            json!({"files": [{"path": "a.rs", "content": "x"}, {"path": "./a.rs", "content": "y"}]}),
+            json!({"files": [{"path": "a.rs", "content": "x"}, {"path": "src", "content": "y"}]}),
+        ];
+        for (i, arguments) in calls.into_iter().enumerate() {
+            let result = tool
+                .execute(&format!("c{}", i), arguments, None)
+                .await
+                .unwrap();
+            assert_eq!(
+                ToolErrorKind::of(&result),
+                Some(ToolErrorKind::InvalidArguments),
+                "{}",
+                result.content
+            );
+        }
+        assert!(!fs.exists("/project/a.rs").await.unwrap());
+    }
+
+    #[cfg(all(feature = "native", unix))]
+    #[tokio::test]
+    async fn failed_write_rolls_back() {
+        use soul_core::vfs::NativeFs;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap().to_string();
+        std::fs::write(dir.path().join("lib.rs"), "old\n").unwrap();
+        std::fs::write(dir.path().join("notes"), "a file, not a directory\n").unwrap();
+        let tool = WriteManyTool::new(Arc::new(NativeFs::new(&root)), root.clone());
+
+        let result = tool
+            .execute(
+                "c1",
+                json!({"files": [
+                    {"path": "new.rs", "content": "new\n"},
+                    {"path": "lib.rs", "content": "changed\n"},
+                    {"path": "notes/today.md", "content": "x\n"},
+                ]}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(
+            result.content.starts_with("Failed to write notes/today.md")
+                && result.content.ends_with("no files were changed"),
+            "{}",
+            result.content
+        );
+        assert!(!dir.path().join("new.rs").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("lib.rs")).unwrap(),
+            "old\n"
+        );
+    }
+}
@@ -0,0 +1,495 @@
+//! Toolset builder — compose any subset of tools with per-tool options.
+//!
+//! The [`presets`](crate::presets) functions cover the common cases;
+//! [`Toolset`] is for everything in between:
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use soul_core::vfs::MemoryFs;
+//! use soul_core::vexec::NoopExecutor;
+//! use soul_coder::{Shell, Toolset};
+//!
+//! let fs = Arc::new(MemoryFs::new());
+//! let registry = Toolset::new(fs, Arc::new(NoopExecutor), "/workspace")
+//!     .with_read_only()
+//!     .with_edit()
+//!     .with_bash_configured(|bash| bash.with_shell(Shell::Bash).with_default_timeout(30))
+//!     .build_registry();
+//!
+//! assert_eq!(registry.len(), 6);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use soul_core::executor::ExecutorRegistry;
+use soul_core::tool::{Tool, ToolRegistry};
+use soul_core::vexec::VirtualExecutor;
+use soul_core::vfs::VirtualFs;
+
+use crate::aliases::{alias_registry, ToolAlias};
+use crate::audit::{AuditSink, AuditedTool};
+use crate::editor::EditorBridge;
+use crate::file_identity::FileIdentity;
+use crate::file_modes::FileModes;
+use crate::formatter::Formatter;
+use crate::locks::PathLocks;
+use crate::metrics::{Metrics, MetricsMiddleware};
+use crate::middleware::{wrap_with_base_names, ToolMiddleware};
+use crate::presets::wrap_as_executor;
+use crate::read_only::ReadOnlyFs;
+use crate::soulignore::SoulIgnoreFs;
+use crate::tools::{
+    append::AppendTool, bash::BashTool, delete::DeleteTool, dupes::DupesTool, edit::EditTool,
+    env_info::EnvInfoTool,
+    find::FindTool, format::FormatTool, grep::GrepTool, grep_replace::GrepReplaceTool,
+    hash::HashTool, loc::LocTool, ls::LsTool, manifest::ManifestTool, open_file::OpenFileTool,
+    outline::OutlineTool, project_info::ProjectInfoTool, read::ReadTool, rename::RenameTool,
+    scaffold::ScaffoldTool,
+    scratch::ScratchTool, set_cwd::SetCwdTool, stats::StatsTool, todo::TodoTool, write::WriteTool,
+    write_many::WriteManyTool,
+};
+use crate::tools::builtin_name;
+use crate::working_dir::WorkingDir;
+
+/// Builder for a [`ToolRegistry`] with an arbitrary selection of tools.
+///
+/// Adding a tool whose name is already present replaces the earlier one, so
+/// a group like [`with_all`](Self::with_all) can be followed by a configured
+/// variant of a single tool.
+pub struct Toolset {
+    fs: Arc<dyn VirtualFs>,
+    executor: Arc<dyn VirtualExecutor>,
+    cwd: String,
+    tools: Vec<Box<dyn Tool>>,
+    middlewares: Vec<Arc<dyn ToolMiddleware>>,
+    read_only: bool,
+    locks: Arc<PathLocks>,
+    file_modes: Option<Arc<dyn FileModes>>,
+    file_identity: Option<Arc<dyn FileIdentity>>,
+    aliases: Vec<ToolAlias>,
+    audit: Option<Arc<dyn AuditSink>>,
+    /// Built-in names of the tools added, by registered name.
+    base_names: HashMap<String, &'static str>,
+}
+
+/// Generates `with_<tool>()` and `with_<tool>_configured(f)` for a VFS tool.
+/// Tools marked `locked` get the toolset's shared [`PathLocks`] and its
+/// [`FileModes`], if set; tools marked `identified` get its [`FileIdentity`].
+macro_rules! fs_tool_methods {
+    ($($tool:ident, $configured:ident, $ty:ty, $label:literal $(, $locked:ident)?;)*) => {
+        $(
+            #[doc = concat!("Add the ", $label, " tool.")]
+            pub fn $tool(self) -> Self {
+                self.$configured(|tool| tool)
+            }
+
+            #[doc = concat!("Add the ", $label, " tool, customized by `configure`.")]
+            pub fn $configured(self, configure: impl FnOnce($ty) -> $ty) -> Self {
+                let tool = <$ty>::new(self.fs.clone(), &self.cwd);
+                $(let tool = fs_tool_methods!(@$locked tool, self);)?
+                let tool = configure(tool);
+                self.with_tool(tool)
+            }
+        )*
+    };
+    (@locked $tool:ident, $set:ident) => {{
+        let tool = $tool.with_locks($set.locks.clone());
+        match &$set.file_modes {
+            Some(modes) => tool.with_file_modes(modes.clone()),
+            None => tool,
+        }
+    }};
+    (@identified $tool:ident, $set:ident) => {
+        match &$set.file_identity {
+            Some(identity) => $tool.with_file_identity(identity.clone()),
+            None => $tool,
+        }
+    };
+}
+
+impl Toolset {
+    pub fn new(
+        fs: Arc<dyn VirtualFs>,
+        executor: Arc<dyn VirtualExecutor>,
+        cwd: impl Into<String>,
+    ) -> Self {
+        Self {
+            fs,
+            executor,
+            cwd: cwd.into(),
+            tools: Vec::new(),
+            middlewares: Vec::new(),
+            read_only: false,
+            locks: Arc::new(PathLocks::new()),
+            file_modes: None,
+            file_identity: None,
+            aliases: Vec::new(),
+            audit: None,
+            base_names: HashMap::new(),
+        }
+    }
+
+    /// Make every tool added afterwards unable to modify anything: the VFS is
+    /// wrapped in a [`ReadOnlyFs`] and bash gets the
+    /// [read-only policy](BashTool::with_read_only_policy). Call it before
+    /// adding tools; ones already added keep the writable VFS.
+    pub fn enforce_read_only(mut self) -> Self {
+        self.fs = Arc::new(ReadOnlyFs::new(self.fs));
+        self.read_only = true;
+        self
+    }
+
+    /// Hide the paths `.soulignore` files in the working directory and
+    /// below it match from every tool added afterwards, by wrapping the VFS
+    /// in a [`SoulIgnoreFs`]. Call it before adding tools.
+    pub fn with_soulignore(mut self) -> Self {
+        self.fs = Arc::new(SoulIgnoreFs::new(self.fs, self.cwd.clone()));
+        self
+    }
+
+    fs_tool_methods! {
+        with_read, with_read_configured, ReadTool, "read";
+        with_write, with_write_configured, WriteTool, "write", locked;
+        with_append, with_append_configured, AppendTool, "append", locked;
+        with_edit, with_edit_configured, EditTool, "edit", locked;
+        with_grep, with_grep_configured, GrepTool, "grep", identified;
+        with_find, with_find_configured, FindTool, "find", identified;
+        with_ls, with_ls_configured, LsTool, "ls";
+        with_outline, with_outline_configured, OutlineTool, "outline";
+        with_todo, with_todo_configured, TodoTool, "todo";
+        with_loc, with_loc_configured, LocTool, "loc";
+        with_scratch, with_scratch_configured, ScratchTool, "scratch";
+        with_scaffold, with_scaffold_configured, ScaffoldTool, "scaffold";
+        with_project_info, with_project_info_configured, ProjectInfoTool, "project_info";
+        with_hash, with_hash_configured, HashTool, "hash";
+        with_write_many, with_write_many_configured, WriteManyTool, "write_many", locked;
+        with_dupes, with_dupes_configured, DupesTool, "dupes";
+        with_stats, with_stats_configured, StatsTool, "stats";
+        with_grep_replace, with_grep_replace_configured, GrepReplaceTool, "grep_replace", locked;
+        with_manifest, with_manifest_configured, ManifestTool, "manifest";
+        with_open_file, with_open_file_configured, OpenFileTool, "open_file";
+        with_delete, with_delete_configured, DeleteTool, "delete";
+        with_format, with_format_configured, FormatTool, "format", locked;
+        with_rename, with_rename_configured, RenameTool, "rename", locked;
+    }
+
+    /// Share `locks` with write, write_many, edit, append, grep_replace,
+    /// format and rename tools added afterwards, e.g. to serialize them with
+    /// another registry's (default: one set per toolset).
+    pub fn with_locks(mut self, locks: Arc<PathLocks>) -> Self {
+        self.locks = locks;
+        self
+    }
+
+    /// Keep file permissions across rewrites by write, write_many, edit,
+    /// append, grep_replace, format and rename tools added afterwards, and
+    /// let write set them (default: off).
+    pub fn with_file_modes(mut self, modes: Arc<dyn FileModes>) -> Self {
+        self.file_modes = Some(modes);
+        self
+    }
+
+    /// List each file once in the results of grep and find tools added
+    /// afterwards when several paths reach it (default: off).
+    pub fn with_file_identity(mut self, identity: Arc<dyn FileIdentity>) -> Self {
+        self.file_identity = Some(identity);
+        self
+    }
+
+    /// Add the bash tool.
+    pub fn with_bash(self) -> Self {
+        self.with_bash_configured(|tool| tool)
+    }
+
+    /// Add the bash tool, customized by `configure`.
+    pub fn with_bash_configured(self, configure: impl FnOnce(BashTool) -> BashTool) -> Self {
+        let mut tool = BashTool::new(self.executor.clone(), &self.cwd);
+        if self.read_only {
+            tool = tool.with_read_only_policy();
+        }
+        let tool = configure(tool);
+        self.with_tool(tool)
+    }
+
+    /// Add the env_info tool, probing the toolset's executor.
+    pub fn with_env_info(self) -> Self {
+        self.with_env_info_configured(|tool| tool)
+    }
+
+    /// Add the env_info tool, customized by `configure`.
+    pub fn with_env_info_configured(
+        self,
+        configure: impl FnOnce(EnvInfoTool) -> EnvInfoTool,
+    ) -> Self {
+        let tool = configure(EnvInfoTool::new(self.executor.clone(), &self.cwd));
+        self.with_tool(tool)
+    }
+
+    /// Add read, grep, find and ls (the [`read_only_tools`](crate::presets::read_only_tools) set).
+    pub fn with_read_only(self) -> Self {
+        self.with_read().with_grep().with_find().with_ls()
+    }
+
+    /// Add read, write, edit and bash (the [`coding_tools`](crate::presets::coding_tools) set).
+    pub fn with_coding(self) -> Self {
+        self.with_read().with_write().with_edit().with_bash()
+    }
+
+    /// Add every built-in tool (the [`all_tools`](crate::presets::all_tools) set).
+    /// The optional manifest, open_file, delete, format, rename and env_info
+    /// tools are not included.
+    pub fn with_all(self) -> Self {
+        self.with_read()
+            .with_write()
+            .with_append()
+            .with_edit()
+            .with_bash()
+            .with_grep()
+            .with_find()
+            .with_ls()
+            .with_outline()
+            .with_todo()
+            .with_loc()
+            .with_scratch()
+            .with_scaffold()
+            .with_project_info()
+            .with_hash()
+            .with_write_many()
+            .with_dupes()
+            .with_stats()
+            .with_grep_replace()
+    }
+
+    /// Add any tool, including ones defined outside this crate.
+    pub fn with_tool<T: Tool + 'static>(mut self, tool: T) -> Self {
+        match builtin_name::<T>() {
+            Some(base) => self.base_names.insert(tool.name().to_string(), base),
+            None => self.base_names.remove(tool.name()),
+        };
+        match self.tools.iter().position(|t| t.name() == tool.name()) {
+            Some(i) => self.tools[i] = Box::new(tool),
+            None => self.tools.push(Box::new(tool)),
+        }
+        self
+    }
+
+    /// Wrap every tool in `middleware`; the first one added is outermost.
+    pub fn with_middleware(mut self, middleware: Arc<dyn ToolMiddleware>) -> Self {
+        self.middlewares.push(middleware);
+        self
+    }
+
+    /// Report calls, errors, bytes, truncations and durations of every tool
+    /// to `metrics`, through a [`MetricsMiddleware`] added at this point of
+    /// the middleware stack.
+    pub fn with_metrics(self, metrics: Arc<dyn Metrics>) -> Self {
+        self.with_middleware(Arc::new(MetricsMiddleware::new(metrics)))
+    }
+
+    /// Add the open_file tool, showing locations through `bridge`.
+    pub fn with_editor(self, bridge: Arc<dyn EditorBridge>) -> Self {
+        self.with_open_file_configured(|tool| tool.with_bridge(bridge))
+    }
+
+    /// Add the format tool, formatting files with `formatter`.
+    pub fn with_formatter(self, formatter: Arc<dyn Formatter>) -> Self {
+        self.with_format_configured(|tool| tool.with_formatter(formatter))
+    }
+
+    /// Add the set_cwd tool, moving `dir`, and rewrite relative paths in
+    /// every call against it through a middleware added at this point of the
+    /// stack; keep `dir` to move it from the host as well.
+    pub fn with_working_dir(self, dir: Arc<WorkingDir>) -> Self {
+        self.with_tool(SetCwdTool::new(dir.clone()))
+            .with_middleware(dir)
+    }
+
+    /// Record every mutating call to `sink` (see [`crate::audit`]). Applied
+    /// when the registry is built, to every tool whatever it was added
+    /// before or after this, and going by what each tool is rather than its
+    /// name: a bash added as `shell`, or called through an alias, is audited
+    /// too.
+    pub fn with_audit(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit = Some(sink);
+        self
+    }
+
+    /// Also register the tool `alias` points to under its name; see
+    /// [`compat_aliases`](crate::presets::compat_aliases) for common ones.
+    /// Aliases are resolved when the registry is built, so the target may be
+    /// added later.
+    pub fn with_alias(mut self, alias: ToolAlias) -> Self {
+        self.aliases.push(alias);
+        self
+    }
+
+    /// Names of the tools added so far, in registration order.
+    pub fn names(&self) -> Vec<&str> {
+        self.tools.iter().map(|t| t.name()).collect()
+    }
+
+    pub fn build_registry(self) -> ToolRegistry {
+        // Let middlewares see through renamed tools and aliases
+        let mut base_names: HashMap<String, String> = HashMap::new();
+        for tool in &self.tools {
+            let base = self.base_names.get(tool.name()).copied();
+            base_names.insert(tool.name().to_string(), base.unwrap_or(tool.name()).to_string());
+        }
+        for alias in &self.aliases {
+            if let Some(base) = base_names.get(alias.target()).cloned() {
+                base_names.entry(alias.name().to_string()).or_insert(base);
+            }
+        }
+
+        let mut registry = ToolRegistry::new();
+        for tool in self.tools {
+            match &self.audit {
+                Some(sink) => {
+                    let base_name = self.base_names.get(tool.name()).copied();
+                    let mut audited =
+                        AuditedTool::new(tool, self.fs.clone(), &self.cwd, sink.clone());
+                    if let Some(base_name) = base_name {
+                        audited = audited.with_base_name(base_name);
+                    }
+                    registry.register(Box::new(audited));
+                }
+                None => registry.register(tool),
+            }
+        }
+        if !self.aliases.is_empty() {
+            registry = alias_registry(registry, &self.aliases);
+        }
+        if self.middlewares.is_empty() {
+            registry
+        } else {
+            wrap_with_base_names(registry, self.middlewares, &base_names)
+        }
+    }
+
+    /// Build an [`ExecutorRegistry`], as [`wrap_as_executor`] does for presets.
+    pub fn build_executor(self) -> ExecutorRegistry {
+        wrap_as_executor(self.build_registry())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use soul_core::vexec::NoopExecutor;
+    use soul_core::vfs::MemoryFs;
+
+    use crate::cache::ResultCache;
+
+    fn toolset(fs: Arc<MemoryFs>) -> Toolset {
+        Toolset::new(
+            fs as Arc<dyn VirtualFs>,
+            Arc::new(NoopExecutor) as Arc<dyn VirtualExecutor>,
+            "/project",
+        )
+    }
+
+    #[test]
+    fn groups_match_presets() {
+        let fs = Arc::new(MemoryFs::new());
+        assert_eq!(toolset(fs.clone()).with_all().build_registry().len(), 19);
+        assert_eq!(
+            toolset(fs.clone())
+                .with_all()
+                .with_manifest()
+                .build_registry()
+                .len(),
+            20
+        );
+        assert_eq!(
+            toolset(fs.clone()).with_read_only().names(),
+            vec!["read", "grep", "find", "ls"]
+        );
+        assert_eq!(
+            toolset(fs).with_coding().names(),
+            vec!["read", "write", "edit", "bash"]
+        );
+    }
+
+    #[test]
+    fn configured_tool_replaces_default() {
+        let fs = Arc::new(MemoryFs::new());
+        let set = toolset(fs)
+            .with_read_only()
+            .with_bash()
+            .with_bash_configured(|bash| bash.with_name("shell"))
+            .with_read_configured(|read| read.with_description("Read carefully"));
+        assert_eq!(
+            set.names(),
+            vec!["read", "grep", "find", "ls", "bash", "shell"]
+        );
+
+        let registry = set.build_registry();
+        assert_eq!(
+            registry.get("read").unwrap().definition().description,
+            "Read carefully"
+        );
+    }
+
+    #[tokio::test]
+    async fn middleware_and_executor() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "alpha").await.unwrap();
+        let cache = Arc::new(ResultCache::new());
+        let registry = toolset(fs.clone())
+            .with_read()
+            .with_middleware(cache.clone())
+            .build_registry();
+        let read = registry.get("read").unwrap();
+        read.execute("c1", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        read.execute("c2", json!({"path": "a.txt"}), None)
+            .await
+            .unwrap();
+        assert_eq!(cache.stats().hits, 1);
+
+        let executor = toolset(fs).with_read_only().build_executor();
+        assert!(executor.has_tool("grep"));
+        assert!(!executor.has_tool("bash"));
+    }
+
+    #[tokio::test]
+    async fn writers_share_path_locks() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/project/a.txt", "alpha").await.unwrap();
+        let locks = Arc::new(PathLocks::new());
+        let registry = toolset(fs.clone())
+            .with_locks(locks.clone())
+            .with_write()
+            .with_edit()
+            .build_registry();
+
+        // Another branch is mid-change on a.txt: the edit waits for it
+        let held = locks.lock("/project/a.txt").await;
+        let edit = registry.get("edit").unwrap().execute(
+            "c1",
+            json!({"path": "./a.txt", "old": "alpha", "new": "beta"}),
+            None,
+        );
+        let other_branch = async {
+            tokio::task::yield_now().await;
+            let other = registry
+                .get("write")
+                .unwrap()
+                .execute("c2", json!({"path": "b.txt", "content": "b"}), None)
+                .await
+                .unwrap();
+            assert!(other.metadata.get("lock").is_none());
+            fs.write("/project/a.txt", "alpha\n").await.unwrap();
+            drop(held);
+        };
+        let (edited, ()) = tokio::join!(edit, other_branch);
+        let edited = edited.unwrap();
+        assert!(!edited.is_error, "{}", edited.content);
+        assert_eq!(edited.metadata["lock"]["contended"], true);
+        assert_eq!(fs.read_to_string("/project/a.txt").await.unwrap(), "beta\n");
+        assert_eq!(locks.held(), 0);
+    }
+}
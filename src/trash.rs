@@ -0,0 +1,358 @@
+//! Recoverable deletion.
+//!
+//! A delete an agent gets wrong should cost an undo, not the file. [`Trash`]
+//! moves deleted files into a trash area inside the VFS (by default
+//! [`DEFAULT_TRASH_ROOT`] under the working directory), one entry per delete
+//! under `<root>/<timestamp>/`, from where [`Trash::restore`] puts them back.
+//! Entries older than the retention period are dropped by [`Trash::purge`].
+//!
+//! `VirtualFs` has no way to remove directories, so deleting a directory
+//! moves its files and leaves the empty directories behind; expired entries
+//! likewise leave empty directories in the trash. Only text files can be
+//! moved: a delete including a file that can't be read as UTF-8 fails
+//! without changing anything.
+//!
+//! ```rust
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! use std::sync::Arc;
+//! use soul_core::vfs::{MemoryFs, VirtualFs};
+//! use soul_coder::trash::Trash;
+//!
+//! let fs = Arc::new(MemoryFs::new());
+//! fs.write("/workspace/notes.md", "# Notes\n").await.unwrap();
+//!
+//! let trash = Trash::new(fs.clone(), "/workspace/.soul/trash");
+//! let entry = trash.delete("/workspace/notes.md").await.unwrap();
+//! assert!(!fs.exists("/workspace/notes.md").await.unwrap());
+//!
+//! trash.restore(&entry.id, false).await.unwrap();
+//! assert_eq!(fs.read_to_string("/workspace/notes.md").await.unwrap(), "# Notes\n");
+//! # });
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use soul_core::error::{SoulError, SoulResult};
+use soul_core::vfs::VirtualFs;
+
+use crate::audit::now_ms;
+use crate::paths;
+use crate::walk::WalkGuard;
+
+/// Default trash location, relative to the working directory.
+pub const DEFAULT_TRASH_ROOT: &str = ".soul/trash";
+
+/// Default time deleted files are kept: a week.
+pub const DEFAULT_RETENTION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Description of an entry, next to its files.
+const ENTRY_FILE: &str = "entry.json";
+
+/// Directory of an entry holding the deleted files, under their full paths.
+const FILES_DIR: &str = "files";
+
+/// One delete, as kept in the trash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    /// Name of the entry's directory: its deletion time in milliseconds
+    /// since the Unix epoch, bumped past entries deleted the same moment.
+    pub id: String,
+    /// The deleted file or directory.
+    pub path: String,
+    /// Milliseconds since the Unix epoch (0 where no clock is available).
+    pub deleted_ms: u64,
+    /// Paths of the deleted files.
+    pub files: Vec<String>,
+}
+
+/// A trash area in a VFS.
+pub struct Trash {
+    fs: Arc<dyn VirtualFs>,
+    root: String,
+    retention: Duration,
+}
+
+impl Trash {
+    /// Keep deleted files under the absolute path `root`.
+    pub fn new(fs: Arc<dyn VirtualFs>, root: &str) -> Self {
+        Self {
+            fs,
+            root: paths::normalize(root),
+            retention: DEFAULT_RETENTION,
+        }
+    }
+
+    /// Time after which [`purge`](Self::purge) drops an entry (default: a
+    /// week).
+    pub fn with_retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    /// Move the file or directory `path` into the trash.
+    pub async fn delete(&self, path: &str) -> SoulResult<TrashEntry> {
+        let path = paths::normalize(path);
+        if paths::is_within(&path, &self.root) || paths::is_within(&self.root, &path) {
+            return Err(io_error(
+                std::io::ErrorKind::PermissionDenied,
+                format!("{} is or contains the trash", path),
+            ));
+        }
+        let metadata = self.fs.metadata(&path).await?;
+        let mut files = Vec::new();
+        if metadata.is_dir {
+            let guard = WalkGuard::new();
+            guard.enter(&path, 0);
+            collect_files(self.fs.as_ref(), &path, 0, &guard, &mut files).await;
+            // Trashing part of a tree would silently leave the rest behind
+            if let Some(notice) = guard.notice() {
+                return Err(io_error(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("{} can't be deleted as a whole: {}", path, notice),
+                ));
+            }
+        } else {
+            files.push(path.clone());
+        }
+        if files.is_empty() {
+            return Err(io_error(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} has no files to delete", path),
+            ));
+        }
+        files.sort_by(|a, b| paths::natural_cmp(a, b));
+
+        // Read everything first so an unreadable file leaves the tree untouched
+        let mut contents = Vec::with_capacity(files.len());
+        for file in &files {
+            match self.fs.read_to_string(file).await {
+                Ok(content) => contents.push(content),
+                Err(_) => {
+                    return Err(io_error(
+                        std::io::ErrorKind::InvalidData,
+                        format!("{} isn't UTF-8 text and can't be moved to the trash", file),
+                    ))
+                }
+            }
+        }
+
+        let deleted_ms = now_ms();
+        let mut stamp = deleted_ms;
+        while self.fs.exists(&self.entry_dir(&stamp.to_string())).await? {
+            stamp += 1;
+        }
+        let entry = TrashEntry {
+            id: stamp.to_string(),
+            path,
+            deleted_ms,
+            files,
+        };
+        for (file, content) in entry.files.iter().zip(&contents) {
+            self.write(&self.trashed_path(&entry.id, file), content)
+                .await?;
+        }
+        let description = serde_json::to_string_pretty(&entry)?;
+        self.write(&self.entry_file(&entry.id), &description)
+            .await?;
+        for file in &entry.files {
+            self.fs.remove_file(file).await?;
+        }
+        Ok(entry)
+    }
+
+    /// Put the files of entry `id` back where they were deleted from. Files
+    /// created there since are replaced only with `overwrite`.
+    pub async fn restore(&self, id: &str, overwrite: bool) -> SoulResult<TrashEntry> {
+        let entry = self.entry(id).await?;
+        if !overwrite {
+            let mut existing = Vec::new();
+            for file in &entry.files {
+                if self.fs.exists(file).await? {
+                    existing.push(file.as_str());
+                }
+            }
+            if !existing.is_empty() {
+                return Err(io_error(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("Already exists: {}", existing.join(", ")),
+                ));
+            }
+        }
+        for file in &entry.files {
+            let trashed = self.trashed_path(&entry.id, file);
+            let content = self.fs.read_to_string(&trashed).await?;
+            self.write(file, &content).await?;
+            self.fs.remove_file(&trashed).await?;
+        }
+        self.fs.remove_file(&self.entry_file(&entry.id)).await?;
+        Ok(entry)
+    }
+
+    /// Entries in the trash, most recently deleted first.
+    pub async fn list(&self) -> SoulResult<Vec<TrashEntry>> {
+        let Ok(dirs) = self.fs.read_dir(&self.root).await else {
+            return Ok(Vec::new()); // Nothing deleted yet
+        };
+        let mut entries = Vec::new();
+        for dir in dirs.into_iter().filter(|d| d.is_dir) {
+            // Restored and purged entries leave an empty directory
+            if let Ok(entry) = self.entry(&dir.name).await {
+                entries.push(entry);
+            }
+        }
+        entries.sort_by(|a, b| {
+            b.deleted_ms
+                .cmp(&a.deleted_ms)
+                .then_with(|| b.id.cmp(&a.id))
+        });
+        Ok(entries)
+    }
+
+    /// Drop the entries that have outlived the retention period at `now_ms`
+    /// (milliseconds since the Unix epoch), returning them.
+    pub async fn purge(&self, now_ms: u64) -> SoulResult<Vec<TrashEntry>> {
+        let retention = self.retention.as_millis() as u64;
+        let mut purged = Vec::new();
+        for entry in self.list().await? {
+            if now_ms.saturating_sub(entry.deleted_ms) <= retention {
+                continue;
+            }
+            for file in &entry.files {
+                self.fs
+                    .remove_file(&self.trashed_path(&entry.id, file))
+                    .await?;
+            }
+            self.fs.remove_file(&self.entry_file(&entry.id)).await?;
+            purged.push(entry);
+        }
+        Ok(purged)
+    }
+
+    async fn entry(&self, id: &str) -> SoulResult<TrashEntry> {
+        let valid = !id.is_empty() && !id.contains('/') && id != "." && id != "..";
+        let description = match valid {
+            true => self.fs.read_to_string(&self.entry_file(id)).await.ok(),
+            false => None,
+        };
+        let Some(description) = description else {
+            return Err(io_error(
+                std::io::ErrorKind::NotFound,
+                format!("No trash entry {}", id),
+            ));
+        };
+        Ok(serde_json::from_str(&description)?)
+    }
+
+    fn entry_dir(&self, id: &str) -> String {
+        paths::resolve(&self.root, id)
+    }
+
+    fn entry_file(&self, id: &str) -> String {
+        paths::resolve(&self.entry_dir(id), ENTRY_FILE)
+    }
+
+    /// Where the deleted `file` is kept in entry `id`.
+    fn trashed_path(&self, id: &str, file: &str) -> String {
+        let files = paths::resolve(&self.entry_dir(id), FILES_DIR);
+        paths::resolve(&files, file.trim_start_matches('/'))
+    }
+
+    async fn write(&self, path: &str, content: &str) -> SoulResult<()> {
+        if let Some((parent, _)) = path.rsplit_once('/') {
+            if !parent.is_empty() {
+                self.fs.create_dir_all(parent).await?;
+            }
+        }
+        self.fs.write(path, content).await
+    }
+}
+
+fn io_error(kind: std::io::ErrorKind, message: String) -> SoulError {
+    SoulError::Io(std::io::Error::new(kind, message))
+}
+
+/// Recursively collect the files under `dir`, at `depth` below the deleted
+/// directory.
+async fn collect_files(
+    fs: &dyn VirtualFs,
+    dir: &str,
+    depth: usize,
+    guard: &WalkGuard,
+    files: &mut Vec<String>,
+) {
+    let Ok(entries) = fs.read_dir(dir).await else {
+        return;
+    };
+    for entry in entries {
+        let path = paths::resolve(dir, &entry.name);
+        if entry.is_dir {
+            if guard.enter(&path, depth + 1) {
+                Box::pin(collect_files(fs, &path, depth + 1, guard, files)).await;
+            }
+        } else if entry.is_file {
+            files.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soul_core::vfs::MemoryFs;
+
+    #[tokio::test]
+    async fn delete_restore_and_purge() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/w/src/a.rs", "a").await.unwrap();
+        fs.write("/w/src/sub/b.rs", "b").await.unwrap();
+        let trash = Trash::new(fs.clone(), "/w/.soul/trash");
+
+        let entry = trash.delete("/w/src").await.unwrap();
+        assert_eq!(entry.files, ["/w/src/a.rs", "/w/src/sub/b.rs"]);
+        assert!(!fs.exists("/w/src/a.rs").await.unwrap());
+        assert_eq!(trash.list().await.unwrap(), std::slice::from_ref(&entry));
+
+        fs.write("/w/src/a.rs", "new").await.unwrap();
+        assert!(trash.restore(&entry.id, false).await.is_err());
+        trash.restore(&entry.id, true).await.unwrap();
+        assert_eq!(fs.read_to_string("/w/src/a.rs").await.unwrap(), "a");
+        assert_eq!(fs.read_to_string("/w/src/sub/b.rs").await.unwrap(), "b");
+        assert!(trash.restore(&entry.id, false).await.is_err());
+
+        fs.write("/w/c.rs", "c").await.unwrap();
+        let entry = trash.delete("/w/c.rs").await.unwrap();
+        let week = DEFAULT_RETENTION.as_millis() as u64;
+        assert!(trash
+            .purge(entry.deleted_ms + week)
+            .await
+            .unwrap()
+            .is_empty());
+        let purged = trash.purge(entry.deleted_ms + week + 1).await.unwrap();
+        assert_eq!(purged.len(), 1);
+        assert!(trash.list().await.unwrap().is_empty());
+
+        assert!(trash.delete("/w").await.is_err());
+        assert!(trash.delete("/w/.soul/trash/x").await.is_err());
+        assert!(trash.delete("/w/missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn delete_refuses_endless_trees() {
+        let fs = crate::walk::tests::loop_fs().await;
+        let trash = Trash::new(fs.clone(), "/trash");
+
+        let error = trash.delete("/w").await.unwrap_err().to_string();
+        assert!(error.contains("can't be deleted as a whole"), "{}", error);
+        assert!(error.contains("[Walk stopped at depth 64"), "{}", error);
+        assert!(fs.exists("/w/loop/start.txt").await.unwrap());
+        assert!(fs.exists("/w/src/lib.rs").await.unwrap());
+        assert!(!fs.exists("/trash").await.unwrap());
+    }
+}
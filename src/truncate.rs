@@ -1,7 +1,10 @@
 //! Unified truncation system for tool outputs.
 //!
 //! Provides head and tail truncation by line count and byte size,
-//! with structured metadata about what was truncated.
+//! with structured metadata about what was truncated. Tools report it under
+//! a `truncation` metadata key via [`TruncationResult::to_metadata`].
+
+use serde_json::{json, Value};
 
 /// Maximum lines returned from a file read.
 pub const MAX_LINES: usize = 2000;
@@ -12,6 +15,12 @@ pub const MAX_BYTES: usize = 51_200;
 /// Maximum characters per line in grep output.
 pub const GREP_MAX_LINE_LENGTH: usize = 500;
 
+/// Maximum bytes of diff included in write output (~10 KB).
+pub const MAX_DIFF_BYTES: usize = 10_240;
+
+/// Maximum characters per line in read output.
+pub const READ_MAX_LINE_LENGTH: usize = 2_000;
+
 /// Result of a truncation operation.
 #[derive(Debug, Clone)]
 pub struct TruncationResult {
@@ -21,6 +30,7 @@ pub struct TruncationResult {
     pub original_bytes: usize,
     pub output_bytes: usize,
     pub truncated_by: Option<TruncatedBy>,
+    pub strategy: TruncationStrategy,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -29,6 +39,13 @@ pub enum TruncatedBy {
     Bytes,
 }
 
+/// Which end of the input was kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TruncationStrategy {
+    Head,
+    Tail,
+}
+
 impl TruncationResult {
     pub fn is_truncated(&self) -> bool {
         self.truncated_by.is_some()
@@ -47,6 +64,25 @@ impl TruncationResult {
             None => None,
         }
     }
+
+    /// Machine-readable summary for a tool's `truncation` metadata key.
+    pub fn to_metadata(&self) -> Value {
+        json!({
+            "truncated": self.is_truncated(),
+            "truncated_by": self.truncated_by.as_ref().map(|by| match by {
+                TruncatedBy::Lines => "lines",
+                TruncatedBy::Bytes => "bytes",
+            }),
+            "strategy": match self.strategy {
+                TruncationStrategy::Head => "head",
+                TruncationStrategy::Tail => "tail",
+            },
+            "original_lines": self.original_lines,
+            "output_lines": self.output_lines,
+            "original_bytes": self.original_bytes,
+            "output_bytes": self.output_bytes,
+        })
+    }
 }
 
 /// Keep the first `max_lines` lines and first `max_bytes` bytes.
@@ -64,6 +100,7 @@ pub fn truncate_head(input: &str, max_lines: usize, max_bytes: usize) -> Truncat
             original_bytes,
             output_bytes: original_bytes,
             truncated_by: None,
+            strategy: TruncationStrategy::Head,
         };
     }
 
@@ -101,6 +138,7 @@ pub fn truncate_head(input: &str, max_lines: usize, max_bytes: usize) -> Truncat
         original_bytes,
         output_bytes: output.len(),
         truncated_by,
+        strategy: TruncationStrategy::Head,
         content: output,
     }
 }
@@ -120,6 +158,7 @@ pub fn truncate_tail(input: &str, max_lines: usize, max_bytes: usize) -> Truncat
             original_bytes,
             output_bytes: original_bytes,
             truncated_by: None,
+            strategy: TruncationStrategy::Tail,
         };
     }
 
@@ -161,6 +200,7 @@ pub fn truncate_tail(input: &str, max_lines: usize, max_bytes: usize) -> Truncat
         original_bytes,
         output_bytes: joined.len(),
         truncated_by: final_truncated_by,
+        strategy: TruncationStrategy::Tail,
         content: joined,
     }
 }
@@ -178,12 +218,72 @@ pub fn truncate_line(line: &str, max_chars: usize) -> String {
     }
 }
 
+/// Cut `line` after `max_chars` characters, marking how many were left out
+/// (`…[+48,200 chars]`). Returns `None` if it already fits.
+pub fn clip_line(line: &str, max_chars: usize) -> Option<String> {
+    let (end, _) = line.char_indices().nth(max_chars)?;
+    let rest = line[end..].chars().count();
+    Some(format!("{}…[+{} chars]", &line[..end], group_digits(rest)))
+}
+
+/// `48200` as `48,200`.
+fn group_digits(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Gutter layout used by [`add_line_numbers_styled`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineNumberStyle {
+    /// `cat -n`: numbers right-aligned to six columns, then a tab.
+    #[default]
+    Tab,
+    /// Numbers right-aligned to the widest one shown, then a tab.
+    Compact,
+    /// Numbers right-aligned to the widest one shown, then `|`.
+    Pipe,
+    /// No gutter at all.
+    None,
+}
+
+impl LineNumberStyle {
+    /// Parse a style name (`tab`, `compact`, `pipe`, `none`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "tab" => Some(Self::Tab),
+            "compact" => Some(Self::Compact),
+            "pipe" => Some(Self::Pipe),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+}
+
 /// Add line numbers to content (1-indexed, matching `cat -n` format).
 pub fn add_line_numbers(content: &str, start_line: usize) -> String {
+    add_line_numbers_styled(content, start_line, LineNumberStyle::Tab)
+}
+
+/// Add line numbers to content (1-indexed) with the given gutter layout.
+pub fn add_line_numbers_styled(content: &str, start_line: usize, style: LineNumberStyle) -> String {
+    let last_line = start_line + content.lines().count().saturating_sub(1);
+    let (width, separator) = match style {
+        LineNumberStyle::Tab => (6, '\t'),
+        LineNumberStyle::Compact => (last_line.to_string().len(), '\t'),
+        LineNumberStyle::Pipe => (last_line.to_string().len(), '|'),
+        LineNumberStyle::None => return content.lines().collect::<Vec<_>>().join("\n"),
+    };
     content
         .lines()
         .enumerate()
-        .map(|(i, line)| format!("{:>6}\t{}", start_line + i, line))
+        .map(|(i, line)| format!("{:>width$}{}{}", start_line + i, separator, line))
         .collect::<Vec<_>>()
         .join("\n")
 }
@@ -267,6 +367,40 @@ mod tests {
         assert!(result.contains("    11\tline11"));
     }
 
+    #[test]
+    fn line_number_styles() {
+        let input = "a\nb\nc";
+        assert_eq!(
+            add_line_numbers_styled(input, 8, LineNumberStyle::Compact),
+            " 8\ta\n 9\tb\n10\tc"
+        );
+        assert_eq!(
+            add_line_numbers_styled(input, 1, LineNumberStyle::Pipe),
+            "1|a\n2|b\n3|c"
+        );
+        assert_eq!(
+            add_line_numbers_styled(input, 1, LineNumberStyle::None),
+            input
+        );
+        assert_eq!(
+            add_line_numbers_styled(input, 1, LineNumberStyle::Tab),
+            add_line_numbers(input, 1)
+        );
+        assert_eq!(LineNumberStyle::from_name("PIPE"), Some(LineNumberStyle::Pipe));
+        assert_eq!(LineNumberStyle::from_name("spaces"), None);
+    }
+
+    #[test]
+    fn clip_line_marks_elided_chars() {
+        assert_eq!(clip_line("short", 10), None);
+        assert_eq!(clip_line("0123456789", 10), None);
+        let long = format!("ab{}", "x".repeat(48_200));
+        assert_eq!(clip_line(&long, 2).unwrap(), "ab…[+48,200 chars]");
+        assert_eq!(clip_line("héllo wörld", 4).unwrap(), "héll…[+7 chars]");
+        assert_eq!(group_digits(1_234_567), "1,234,567");
+        assert_eq!(group_digits(999), "999");
+    }
+
     #[test]
     fn truncation_notice() {
         let result = truncate_head("a\nb\nc\nd\ne", 3, MAX_BYTES);
@@ -274,6 +408,22 @@ mod tests {
         assert!(notice.contains("3 of 5 lines"));
     }
 
+    #[test]
+    fn truncation_metadata() {
+        let meta = truncate_tail("a\nb\nc\nd\ne", 2, MAX_BYTES).to_metadata();
+        assert_eq!(meta["truncated"], true);
+        assert_eq!(meta["truncated_by"], "lines");
+        assert_eq!(meta["strategy"], "tail");
+        assert_eq!(meta["original_lines"], 5);
+        assert_eq!(meta["output_lines"], 2);
+        assert_eq!(meta["output_bytes"], 3);
+
+        let meta = truncate_head("a", 2, MAX_BYTES).to_metadata();
+        assert_eq!(meta["truncated"], false);
+        assert!(meta["truncated_by"].is_null());
+        assert_eq!(meta["strategy"], "head");
+    }
+
     #[test]
     fn empty_input() {
         let result = truncate_head("", MAX_LINES, MAX_BYTES);
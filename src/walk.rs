@@ -0,0 +1,500 @@
+//! Streaming workspace walks.
+//!
+//! [`walk`] yields every file under a directory as soon as its directory has
+//! been listed, instead of collecting and sorting the whole tree first, so
+//! callers can stop early (a result limit) or start processing files while
+//! the rest of the tree is still being read. Up to
+//! [`WalkOptions::concurrency`] directories are listed at once; results
+//! still come out in a fixed order — breadth-first, each directory's files
+//! in natural order — whichever listing finishes first. Unreadable
+//! directories are skipped, and so are dot-directories unless
+//! [`WalkOptions::include_hidden`] is set.
+//!
+//! A misbehaving backend can make a tree endless: a directory listing
+//! itself, or a symlink loop that never resolves. Every walker in the crate
+//! without an entry cap of its own enters directories through a
+//! [`WalkGuard`], which refuses a directory already visited and stops
+//! descending below [`MAX_DEPTH`]; tools report cut-short walks with
+//! [`WalkGuard::annotate`] rather than hanging. The manifest and scaffold
+//! templates stop at their entry caps instead.
+//!
+//! ```rust
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! use std::path::PathBuf;
+//! use std::sync::Arc;
+//! use futures::StreamExt;
+//! use soul_core::vfs::{MemoryFs, VirtualFs};
+//! use soul_coder::walk::{walk, WalkOptions};
+//!
+//! let fs = Arc::new(MemoryFs::new());
+//! fs.write("/workspace/src/lib.rs", "").await.unwrap();
+//! fs.write("/workspace/Cargo.toml", "").await.unwrap();
+//!
+//! let files: Vec<PathBuf> = walk(fs, "/workspace", WalkOptions::default())
+//!     .collect()
+//!     .await;
+//! assert_eq!(
+//!     files,
+//!     vec![
+//!         PathBuf::from("/workspace/Cargo.toml"),
+//!         PathBuf::from("/workspace/src/lib.rs"),
+//!     ]
+//! );
+//! # });
+//! ```
+
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use futures::future::BoxFuture;
+use futures::stream::{self, FuturesOrdered, Stream, StreamExt};
+use serde_json::json;
+
+use soul_core::tool::ToolOutput;
+use soul_core::vfs::{VfsDirEntry, VirtualFs};
+
+use crate::paths;
+use crate::tools::set_metadata;
+
+/// Deepest directory level any walker descends to, counting the root's
+/// children as level 1. Real trees stay far below it.
+pub const MAX_DEPTH: usize = 64;
+
+/// Default number of directories listed at once: several on native, where
+/// listings are real I/O, and one under WASM.
+#[cfg(not(target_arch = "wasm32"))]
+pub const DEFAULT_CONCURRENCY: usize = 8;
+#[cfg(target_arch = "wasm32")]
+pub const DEFAULT_CONCURRENCY: usize = 1;
+
+/// Concurrency and filters for [`walk`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkOptions {
+    /// Maximum directory listings in flight; 0 is treated as 1.
+    pub concurrency: usize,
+    /// Descend into dot-directories. Dot-files are always yielded.
+    pub include_hidden: bool,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: DEFAULT_CONCURRENCY,
+            include_hidden: false,
+        }
+    }
+}
+
+impl WalkOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn with_hidden(mut self, include: bool) -> Self {
+        self.include_hidden = include;
+        self
+    }
+}
+
+/// Depth and cycle protection for one walk: which directories it entered,
+/// and where it had to stop.
+#[derive(Debug)]
+pub struct WalkGuard {
+    max_depth: usize,
+    state: Mutex<GuardState>,
+}
+
+#[derive(Debug, Default)]
+struct GuardState {
+    visited: HashSet<String>,
+    /// Directories not entered because they are below the depth cap.
+    depth_limited: Vec<String>,
+    /// Directories not entered because they were visited already.
+    cycles: usize,
+}
+
+impl Default for WalkGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WalkGuard {
+    pub fn new() -> Self {
+        Self {
+            max_depth: MAX_DEPTH,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Lower the depth cap (default: [`MAX_DEPTH`]; it can't be raised).
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth.min(MAX_DEPTH);
+        self
+    }
+
+    /// Whether to list `dir`, `depth` levels below the walk's root (0 for
+    /// the root itself). Refusals are recorded for [`notice`](Self::notice).
+    pub fn enter(&self, dir: &str, depth: usize) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let dir = paths::normalize(dir);
+        if state.visited.contains(&dir) {
+            state.cycles += 1;
+            return false;
+        }
+        if depth > self.max_depth {
+            state.depth_limited.push(dir);
+            return false;
+        }
+        state.visited.insert(dir);
+        true
+    }
+
+    /// Directories not entered because they were below the depth cap.
+    pub fn depth_limited(&self) -> usize {
+        self.state.lock().unwrap().depth_limited.len()
+    }
+
+    /// Directories not entered because they were visited already.
+    pub fn cycles(&self) -> usize {
+        self.state.lock().unwrap().cycles
+    }
+
+    /// Whether any directory was left out.
+    pub fn is_cut_short(&self) -> bool {
+        self.depth_limited() > 0 || self.cycles() > 0
+    }
+
+    /// What was left out, e.g. `[Walk stopped at depth 64 in 2 directories
+    /// (first: /w/a/a/...); skipped 1 directory already visited]`.
+    pub fn notice(&self) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        let mut parts = Vec::new();
+        if let Some(first) = state.depth_limited.first() {
+            parts.push(format!(
+                "Walk stopped at depth {} in {} {} (first: {})",
+                self.max_depth,
+                state.depth_limited.len(),
+                plural(state.depth_limited.len()),
+                first
+            ));
+        }
+        if state.cycles > 0 {
+            parts.push(format!(
+                "skipped {} {} already visited",
+                state.cycles,
+                plural(state.cycles)
+            ));
+        }
+        (!parts.is_empty()).then(|| format!("[{}]", parts.join("; ")))
+    }
+
+    /// Append the [`notice`](Self::notice) to a tool's output and record it
+    /// under a `walk` metadata key; complete walks are left unchanged.
+    pub fn annotate(&self, output: &mut ToolOutput) {
+        let Some(notice) = self.notice() else {
+            return;
+        };
+        output.content.push_str("\n\n");
+        output.content.push_str(&notice);
+        let state = self.state.lock().unwrap();
+        set_metadata(
+            output,
+            "walk",
+            json!({
+                "max_depth": self.max_depth,
+                "depth_limited": state.depth_limited.len(),
+                "cycles": state.cycles,
+            }),
+        );
+    }
+}
+
+fn plural(n: usize) -> &'static str {
+    if n == 1 {
+        "directory"
+    } else {
+        "directories"
+    }
+}
+
+/// A directory listing in flight: the directory, its depth and its entries
+/// (empty when unreadable).
+type Listing = BoxFuture<'static, (String, usize, Vec<VfsDirEntry>)>;
+
+struct WalkState {
+    fs: Arc<dyn VirtualFs>,
+    options: WalkOptions,
+    guard: Arc<WalkGuard>,
+    /// Directories waiting for a listing slot, with their depth.
+    pending: VecDeque<(String, usize)>,
+    listing: FuturesOrdered<Listing>,
+    /// Files from finished listings, not yet yielded.
+    files: VecDeque<PathBuf>,
+}
+
+impl WalkState {
+    /// Start listings for pending directories, up to the concurrency cap.
+    fn fill(&mut self) {
+        while self.listing.len() < self.options.concurrency.max(1) {
+            let Some((dir, depth)) = self.pending.pop_front() else {
+                break;
+            };
+            let fs = self.fs.clone();
+            self.listing.push_back(Box::pin(async move {
+                let entries = fs.read_dir(&dir).await.unwrap_or_default();
+                (dir, depth, entries)
+            }));
+        }
+    }
+
+    /// Queue a finished listing's files and subdirectories.
+    fn visit(&mut self, dir: &str, depth: usize, mut entries: Vec<VfsDirEntry>) {
+        entries.sort_by(|a, b| paths::natural_cmp(&a.name, &b.name));
+        for entry in entries {
+            let path = paths::resolve(dir, &entry.name);
+            if entry.is_dir {
+                let shown = self.options.include_hidden || !entry.name.starts_with('.');
+                if shown && self.guard.enter(&path, depth + 1) {
+                    self.pending.push_back((path, depth + 1));
+                }
+            } else if entry.is_file {
+                self.files.push_back(PathBuf::from(path));
+            }
+        }
+    }
+}
+
+/// Stream the absolute paths of all files under `root`. Directories are
+/// only listed as the stream is polled, so dropping it stops the walk.
+pub fn walk(
+    fs: Arc<dyn VirtualFs>,
+    root: &str,
+    options: WalkOptions,
+) -> impl Stream<Item = PathBuf> + Send {
+    walk_guarded(fs, root, options, Arc::new(WalkGuard::new()))
+}
+
+/// [`walk`], entering directories through `guard` so the caller can report
+/// whether the walk was cut short.
+pub fn walk_guarded(
+    fs: Arc<dyn VirtualFs>,
+    root: &str,
+    options: WalkOptions,
+    guard: Arc<WalkGuard>,
+) -> impl Stream<Item = PathBuf> + Send {
+    let root = paths::normalize(root);
+    let pending = match guard.enter(&root, 0) {
+        true => VecDeque::from([(root, 0)]),
+        false => VecDeque::new(),
+    };
+    let state = WalkState {
+        fs,
+        options,
+        guard,
+        pending,
+        listing: FuturesOrdered::new(),
+        files: VecDeque::new(),
+    };
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(file) = state.files.pop_front() {
+                return Some((file, state));
+            }
+            state.fill();
+            let (dir, depth, entries) = state.listing.next().await?;
+            state.visit(&dir, depth, entries);
+        }
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::future::Future;
+    use std::pin::Pin;
+
+    use soul_core::error::SoulResult;
+    use soul_core::vfs::{MemoryFs, VfsMetadata};
+
+    /// A backend whose `/w/loop` directory contains itself, endlessly, and
+    /// whose listings include `.` like a naive `readdir`.
+    pub(crate) struct LoopFs(pub(crate) MemoryFs);
+
+    impl VirtualFs for LoopFs {
+        fn read_to_string<'a>(
+            &'a self,
+            path: &'a str,
+        ) -> Pin<Box<dyn Future<Output = SoulResult<String>> + Send + 'a>> {
+            self.0.read_to_string(path)
+        }
+
+        fn write<'a>(
+            &'a self,
+            path: &'a str,
+            contents: &'a str,
+        ) -> Pin<Box<dyn Future<Output = SoulResult<()>> + Send + 'a>> {
+            self.0.write(path, contents)
+        }
+
+        fn append<'a>(
+            &'a self,
+            path: &'a str,
+            contents: &'a str,
+        ) -> Pin<Box<dyn Future<Output = SoulResult<()>> + Send + 'a>> {
+            self.0.append(path, contents)
+        }
+
+        fn exists<'a>(
+            &'a self,
+            path: &'a str,
+        ) -> Pin<Box<dyn Future<Output = SoulResult<bool>> + Send + 'a>> {
+            self.0.exists(path)
+        }
+
+        fn create_dir_all<'a>(
+            &'a self,
+            path: &'a str,
+        ) -> Pin<Box<dyn Future<Output = SoulResult<()>> + Send + 'a>> {
+            self.0.create_dir_all(path)
+        }
+
+        fn remove_file<'a>(
+            &'a self,
+            path: &'a str,
+        ) -> Pin<Box<dyn Future<Output = SoulResult<()>> + Send + 'a>> {
+            self.0.remove_file(path)
+        }
+
+        fn read_dir<'a>(
+            &'a self,
+            path: &'a str,
+        ) -> Pin<Box<dyn Future<Output = SoulResult<Vec<VfsDirEntry>>> + Send + 'a>> {
+            Box::pin(async move {
+                let mut entries = self.0.read_dir(path).await.unwrap_or_default();
+                let dir = |name: &str| VfsDirEntry {
+                    name: name.to_string(),
+                    is_file: false,
+                    is_dir: true,
+                };
+                entries.push(dir("."));
+                if path.starts_with("/w/loop") {
+                    entries.push(dir("loop"));
+                }
+                Ok(entries)
+            })
+        }
+
+        fn metadata<'a>(
+            &'a self,
+            path: &'a str,
+        ) -> Pin<Box<dyn Future<Output = SoulResult<VfsMetadata>> + Send + 'a>> {
+            self.0.metadata(path)
+        }
+    }
+
+    /// A [`LoopFs`] with a couple of ordinary files.
+    pub(crate) async fn loop_fs() -> Arc<LoopFs> {
+        let fs = MemoryFs::new();
+        fs.write("/w/loop/start.txt", "needle").await.unwrap();
+        fs.write("/w/src/lib.rs", "needle").await.unwrap();
+        Arc::new(LoopFs(fs))
+    }
+
+    async fn workspace() -> Arc<MemoryFs> {
+        let fs = Arc::new(MemoryFs::new());
+        for path in [
+            "/w/src/util/file10.rs",
+            "/w/src/util/file2.rs",
+            "/w/src/main.rs",
+            "/w/README.md",
+            "/w/.env",
+            "/w/.git/HEAD",
+            "/w/a/b/c/deep.txt",
+        ] {
+            fs.write(path, "").await.unwrap();
+        }
+        fs
+    }
+
+    async fn collect(fs: Arc<MemoryFs>, options: WalkOptions) -> Vec<String> {
+        walk(fs, "/w/", options)
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn breadth_first_in_natural_order() {
+        let fs = workspace().await;
+        let expected = vec![
+            "/w/.env",
+            "/w/README.md",
+            "/w/src/main.rs",
+            "/w/src/util/file2.rs",
+            "/w/src/util/file10.rs",
+            "/w/a/b/c/deep.txt",
+        ];
+        // The order doesn't depend on how many listings run at once
+        for concurrency in [0, 1, 3, 64] {
+            let options = WalkOptions::new().with_concurrency(concurrency);
+            assert_eq!(collect(fs.clone(), options).await, expected);
+        }
+
+        let hidden = collect(fs.clone(), WalkOptions::new().with_hidden(true)).await;
+        assert!(hidden.contains(&"/w/.git/HEAD".to_string()));
+    }
+
+    #[tokio::test]
+    async fn stops_when_dropped_and_skips_missing_roots() {
+        let fs = workspace().await;
+        let first: Vec<PathBuf> = walk(fs.clone(), "/w", WalkOptions::default())
+            .take(2)
+            .collect()
+            .await;
+        assert_eq!(first.len(), 2);
+
+        let none: Vec<PathBuf> = walk(fs, "/missing", WalkOptions::default())
+            .collect()
+            .await;
+        assert!(none.is_empty());
+    }
+
+    #[tokio::test]
+    async fn cycles_and_depth_are_bounded() {
+        let guard = Arc::new(WalkGuard::new().with_max_depth(5));
+        let options = WalkOptions::new().with_hidden(true);
+        let files: Vec<PathBuf> = walk_guarded(loop_fs().await, "/w", options, guard.clone())
+            .collect()
+            .await;
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("/w/loop/start.txt"),
+                PathBuf::from("/w/src/lib.rs"),
+            ]
+        );
+        assert_eq!(guard.depth_limited(), 1);
+        assert!(guard.cycles() > 0);
+
+        let mut output = ToolOutput::success("found");
+        guard.annotate(&mut output);
+        let notice =
+            "[Walk stopped at depth 5 in 1 directory (first: /w/loop/loop/loop/loop/loop/loop)";
+        assert!(output.content.contains(notice), "{}", output.content);
+        assert_eq!(output.metadata["walk"]["depth_limited"], 1);
+
+        let complete = WalkGuard::new();
+        assert!(complete.enter("/w", 0));
+        let mut output = ToolOutput::success("found");
+        complete.annotate(&mut output);
+        assert_eq!(output.content, "found");
+        assert_eq!(WalkGuard::new().with_max_depth(1000).max_depth, MAX_DEPTH);
+    }
+}
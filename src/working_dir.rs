@@ -0,0 +1,340 @@
+//! A working directory shared by every tool in a registry.
+//!
+//! Tools resolve relative paths against the directory they were built with,
+//! normally the workspace root. An agent working inside one package of a
+//! monorepo would otherwise prefix every path with `packages/foo/`.
+//! [`WorkingDir`] holds a current directory that the
+//! [`set_cwd`](crate::tools::set_cwd) tool or the host can move anywhere
+//! inside the root. As a middleware, it rewrites each call's relative paths
+//! against that directory before the tool sees them:
+//!
+//! - `path`, `relative_to`, `template`, `cwd` and each `files[].path` that
+//!   are relative become absolute paths under the current directory;
+//! - grep, find, ls, loc, dupes, stats and project_info (and hash with a
+//!   `pattern`) search the current directory when no `path` is given, and
+//!   grep, find, ls, dupes and hash list results relative to it;
+//! - bash runs commands in it.
+//!
+//! In registries built by a [`Toolset`](crate::Toolset), tools are told
+//! apart by what they are rather than by name, so a bash registered as
+//! `shell` or an `execute_command` alias runs there too.
+//!
+//! Add it before other middlewares, so caches and indexes see the rewritten
+//! arguments. A [`ResultCache`](crate::ResultCache) added first still drops
+//! its entries on set_cwd, but not when the host calls
+//! [`WorkingDir::change`]; invalidate it then. At the root, calls pass
+//! through unchanged.
+//!
+//! ```rust
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! use std::sync::Arc;
+//! use soul_core::vexec::NoopExecutor;
+//! use soul_core::vfs::{MemoryFs, VirtualFs};
+//! use soul_coder::{Toolset, WorkingDir};
+//!
+//! let fs = Arc::new(MemoryFs::new());
+//! fs.write("/workspace/packages/foo/src/lib.rs", "pub fn foo() {}\n").await.unwrap();
+//! let dir = Arc::new(WorkingDir::new(fs.clone(), "/workspace"));
+//! let registry = Toolset::new(fs, Arc::new(NoopExecutor), "/workspace")
+//!     .with_read()
+//!     .with_working_dir(dir.clone())
+//!     .build_registry();
+//!
+//! dir.change("packages/foo").await.unwrap();
+//! let read = registry.get("read").unwrap();
+//! let result = read
+//!     .execute("c1", serde_json::json!({"path": "src/lib.rs"}), None)
+//!     .await
+//!     .unwrap();
+//! assert!(result.content.contains("pub fn foo"));
+//! # });
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use soul_core::error::{SoulError, SoulResult};
+use soul_core::tool::ToolOutput;
+use soul_core::vfs::VirtualFs;
+
+use crate::middleware::{Next, ToolMiddleware};
+use crate::paths;
+
+/// Arguments holding a path, rewritten when relative.
+const PATH_ARGUMENTS: &[&str] = &["path", "relative_to", "template", "cwd"];
+
+/// Tools that search the working directory when called without a `path`.
+const DIRECTORY_TOOLS: &[&str] = &[
+    "grep",
+    "find",
+    "ls",
+    "loc",
+    "dupes",
+    "stats",
+    "project_info",
+];
+
+/// Tools taking a `relative_to` for the paths they list.
+const LISTING_TOOLS: &[&str] = &["grep", "find", "ls", "dupes", "hash"];
+
+/// The current directory of a registry, confined to a root.
+pub struct WorkingDir {
+    fs: Arc<dyn VirtualFs>,
+    root: String,
+    current: Mutex<String>,
+}
+
+impl WorkingDir {
+    /// Start at `root`, the directory the tools were built with; the current
+    /// directory can never leave it.
+    pub fn new(fs: Arc<dyn VirtualFs>, root: impl Into<String>) -> Self {
+        let root = paths::normalize(&root.into());
+        Self {
+            fs,
+            current: Mutex::new(root.clone()),
+            root,
+        }
+    }
+
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    /// The current directory, absolute.
+    pub fn current(&self) -> String {
+        self.current.lock().unwrap().clone()
+    }
+
+    /// The current directory relative to the root (`.` at the root).
+    pub fn relative(&self) -> String {
+        paths::relative_path(&self.current(), &self.root)
+    }
+
+    /// Move to `path`, relative to the current directory or absolute, and
+    /// return the new directory. It must be an existing directory inside the
+    /// root; otherwise the current directory is kept.
+    pub async fn change(&self, path: &str) -> SoulResult<String> {
+        let target = paths::resolve(&self.current(), path);
+        if !paths::is_within(&target, &self.root) {
+            return Err(SoulError::Io(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("{} is outside the workspace {}", target, self.root),
+            )));
+        }
+        match self.fs.metadata(&target).await {
+            Ok(metadata) if metadata.is_dir => {}
+            _ => {
+                return Err(SoulError::Io(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!(
+                        "No directory at {}",
+                        paths::relative_path(&target, &self.root)
+                    ),
+                )))
+            }
+        }
+        *self.current.lock().unwrap() = target.clone();
+        Ok(target)
+    }
+
+    /// Go back to the root.
+    pub fn reset(&self) {
+        *self.current.lock().unwrap() = self.root.clone();
+    }
+
+    /// `arguments` of a call to `tool`, with paths taken relative to `dir`.
+    fn rewrite(tool: &str, arguments: Value, dir: &str) -> Value {
+        // Providers sometimes send the arguments as a JSON string
+        let mut arguments = match arguments.as_str() {
+            Some(raw) => serde_json::from_str(raw).unwrap_or(arguments),
+            None => arguments,
+        };
+        let Some(map) = arguments.as_object_mut() else {
+            return arguments;
+        };
+        let absolute = |value: &mut Value| {
+            if let Some(path) = value.as_str().filter(|p| !paths::is_absolute(p)) {
+                *value = json!(paths::resolve(dir, path));
+            }
+        };
+        for name in PATH_ARGUMENTS {
+            map.get_mut(*name).map(absolute);
+        }
+        if let Some(files) = map.get_mut("files").and_then(|f| f.as_array_mut()) {
+            for file in files {
+                file.get_mut("path").map(absolute);
+            }
+        }
+
+        let searches_dir =
+            DIRECTORY_TOOLS.contains(&tool) || (tool == "hash" && map.contains_key("pattern"));
+        if searches_dir && !map.contains_key("path") {
+            map.insert("path".into(), json!(dir));
+        }
+        if LISTING_TOOLS.contains(&tool) && !map.contains_key("relative_to") {
+            map.insert("relative_to".into(), json!(dir));
+        }
+        if tool == "bash" && !map.contains_key("cwd") {
+            map.insert("cwd".into(), json!(dir));
+        }
+        arguments
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ToolMiddleware for WorkingDir {
+    async fn handle(
+        &self,
+        _tool: &str,
+        call_id: &str,
+        arguments: Value,
+        next: Next<'_>,
+    ) -> SoulResult<ToolOutput> {
+        let dir = self.current();
+        if dir == self.root {
+            return next.run(call_id, arguments).await;
+        }
+        // Aliases and renamed tools default to the directory like their base
+        let arguments = Self::rewrite(next.base_name(), arguments, &dir);
+        next.run(call_id, arguments).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use soul_core::vexec::NoopExecutor;
+    use soul_core::vfs::MemoryFs;
+
+    use crate::cache::ResultCache;
+    use crate::error::ToolErrorKind;
+    use crate::simulated_bash::SimulatedBash;
+    use crate::toolset::Toolset;
+
+    #[tokio::test]
+    async fn tools_follow_the_working_directory() {
+        let fs = Arc::new(MemoryFs::new());
+        for (path, content) in [
+            ("/repo/README.md", "root readme\n"),
+            ("/repo/packages/foo/src/lib.rs", "pub fn foo() {}\n"),
+            ("/repo/packages/bar/src/lib.rs", "pub fn bar() {}\n"),
+        ] {
+            fs.write(path, content).await.unwrap();
+        }
+        let dir = Arc::new(WorkingDir::new(fs.clone(), "/repo"));
+        let registry = Toolset::new(
+            fs.clone(),
+            Arc::new(SimulatedBash::new(fs.clone())),
+            "/repo",
+        )
+        .with_read_only()
+        .with_write_many()
+        .with_bash()
+        .with_working_dir(dir.clone())
+        .build_registry();
+        let call = |tool: &str, arguments: Value| {
+            let tool = registry.get(tool).unwrap();
+            async move { tool.execute("c1", arguments, None).await.unwrap() }
+        };
+
+        let result = call("set_cwd", json!({"path": "packages/foo"})).await;
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(dir.relative(), "packages/foo");
+
+        let result = call("read", json!({"path": "src/lib.rs"})).await;
+        assert!(result.content.contains("pub fn foo"), "{}", result.content);
+        let result = call("grep", json!({"pattern": "pub fn"})).await;
+        assert_eq!(result.content.trim_end(), "src/lib.rs:1:pub fn foo() {}");
+        let result = call("grep", json!({"pattern": "readme", "path": ".."})).await;
+        assert_eq!(result.metadata["matches"], 0);
+        let result = call("bash", json!({"command": "pwd"})).await;
+        assert_eq!(result.content.trim_end(), "/repo/packages/foo");
+
+        let result = call(
+            "write_many",
+            json!({"files": [{"path": "src/a.rs", "content": "a\n"}]}),
+        )
+        .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(fs.exists("/repo/packages/foo/src/a.rs").await.unwrap());
+
+        // Leaving the workspace or entering a file keeps the directory
+        let result = call("set_cwd", json!({"path": "../../.."})).await;
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::Policy));
+        let result = call("set_cwd", json!({"path": "src/lib.rs"})).await;
+        assert_eq!(ToolErrorKind::of(&result), Some(ToolErrorKind::NotFound));
+        assert_eq!(dir.relative(), "packages/foo");
+
+        dir.reset();
+        let result = call("ls", json!({})).await;
+        assert!(result.content.contains("README.md"), "{}", result.content);
+    }
+
+    #[tokio::test]
+    async fn aliases_and_renamed_tools_follow_the_working_directory() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/repo/packages/foo/src/lib.rs", "pub fn foo() {}\n")
+            .await
+            .unwrap();
+        fs.write("/repo/README.md", "pub fn root() {}\n").await.unwrap();
+        let dir = Arc::new(WorkingDir::new(fs.clone(), "/repo"));
+        let mut toolset = Toolset::new(
+            fs.clone(),
+            Arc::new(SimulatedBash::new(fs.clone())),
+            "/repo",
+        )
+        .with_all()
+        .with_bash_configured(|bash| bash.with_name("shell"));
+        for alias in crate::presets::compat_aliases() {
+            toolset = toolset.with_alias(alias);
+        }
+        let registry = toolset.with_working_dir(dir.clone()).build_registry();
+        let call = |tool: &str, arguments: Value| {
+            let tool = registry.get(tool).unwrap();
+            async move { tool.execute("c1", arguments, None).await.unwrap() }
+        };
+        dir.change("packages/foo").await.unwrap();
+
+        for tool in ["shell", "execute_command", "run_terminal_cmd"] {
+            let result = call(tool, json!({"command": "pwd"})).await;
+            assert_eq!(result.content.trim_end(), "/repo/packages/foo", "{}", tool);
+        }
+        let result = call("search_files", json!({"regex": "pub fn"})).await;
+        assert_eq!(result.content.trim_end(), "src/lib.rs:1:pub fn foo() {}");
+    }
+
+    #[tokio::test]
+    async fn cache_added_first_follows_set_cwd() {
+        let fs = Arc::new(MemoryFs::new());
+        fs.write("/repo/packages/foo/src/lib.rs", "pub fn foo() {}\n")
+            .await
+            .unwrap();
+        fs.write("/repo/packages/bar/src/lib.rs", "pub fn bar() {}\n")
+            .await
+            .unwrap();
+        let dir = Arc::new(WorkingDir::new(fs.clone(), "/repo"));
+        let cache = Arc::new(ResultCache::new());
+        let registry = Toolset::new(fs, Arc::new(NoopExecutor), "/repo")
+            .with_read()
+            .with_middleware(cache.clone())
+            .with_working_dir(dir)
+            .build_registry();
+        let call = |tool: &str, arguments: Value| {
+            let tool = registry.get(tool).unwrap();
+            async move { tool.execute("c1", arguments, None).await.unwrap() }
+        };
+
+        call("set_cwd", json!({"path": "packages/foo"})).await;
+        let result = call("read", json!({"path": "src/lib.rs"})).await;
+        assert!(result.content.contains("pub fn foo"), "{}", result.content);
+
+        call("set_cwd", json!({"path": "../bar"})).await;
+        let result = call("read", json!({"path": "src/lib.rs"})).await;
+        assert!(result.content.contains("pub fn bar"), "{}", result.content);
+        assert_eq!(cache.stats().hits, 0);
+    }
+}